@@ -0,0 +1,7 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/dkg.proto");
+        tonic_build::compile_protos("proto/dkg.proto").expect("failed to compile proto/dkg.proto");
+    }
+}
@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use threshold_dkg::ristretto_curve::GE;
+use threshold_dkg::wire;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = wire::decode_encrypted_deal::<GE>(data);
+    let _ = wire::decode_justification::<GE>(data, 3);
+    let _ = wire::decode_response::<GE>(data);
+});
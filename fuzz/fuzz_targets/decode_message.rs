@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use threshold_dkg::ristretto_curve::GE;
+use threshold_dkg::wire;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = wire::decode_message::<GE>(data, 3);
+});
@@ -0,0 +1,97 @@
+/*
+ * C API of the threshold-dkg library, built with the `ffi` feature.
+ *
+ * Keys, scalars and messages are passed as their bincode encoding. Input
+ * buffers are borrowed; output DkgBuffers are allocated by the library and
+ * must be released with dkg_buffer_free. Every function returning an int
+ * returns DKG_OK or an error code, and dkg_last_error gives the message of the
+ * last error of the calling thread.
+ */
+
+#ifndef THRESHOLD_DKG_H
+#define THRESHOLD_DKG_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define DKG_OK 0
+#define DKG_ERR_INVALID_ARGUMENT 1
+#define DKG_ERR_PROTOCOL 2
+#define DKG_ERR_PANIC 3
+
+typedef struct {
+    uint8_t *data;
+    size_t len;
+} DkgBuffer;
+
+typedef struct DkgDealer DkgDealer;
+typedef struct DkgVerifier DkgVerifier;
+typedef struct DkgGenerator DkgGenerator;
+
+void dkg_buffer_free(DkgBuffer buf);
+int dkg_last_error(DkgBuffer *out);
+
+int dkg_new_secret(DkgBuffer *out);
+int dkg_public_key(const uint8_t *secret, size_t len, DkgBuffer *out);
+
+int dkg_dealer_new(const uint8_t *longterm, size_t longterm_len,
+                   const uint8_t *secret, size_t secret_len,
+                   const uint8_t *verifiers, size_t verifiers_len,
+                   uint32_t t, DkgDealer **out);
+int dkg_dealer_encrypt_deals(DkgDealer *d, DkgBuffer *out);
+/* out is empty if there is no justification to broadcast */
+int dkg_dealer_process_response(DkgDealer *d, const uint8_t *resp,
+                                size_t resp_len, DkgBuffer *out);
+int dkg_dealer_deal_certified(const DkgDealer *d);
+void dkg_dealer_free(DkgDealer *d);
+
+int dkg_verifier_new(const uint8_t *longterm, size_t longterm_len,
+                     const uint8_t *dealer, size_t dealer_len,
+                     const uint8_t *verifiers, size_t verifiers_len,
+                     DkgVerifier **out);
+int dkg_verifier_process_encrypted_deal(DkgVerifier *v, const uint8_t *deal,
+                                        size_t deal_len, DkgBuffer *out);
+int dkg_verifier_process_response(DkgVerifier *v, const uint8_t *resp,
+                                  size_t resp_len);
+int dkg_verifier_process_justification(DkgVerifier *v, const uint8_t *j,
+                                       size_t j_len);
+int dkg_verifier_deal_certified(const DkgVerifier *v);
+int dkg_verifier_deal(DkgVerifier *v, DkgBuffer *out);
+void dkg_verifier_free(DkgVerifier *v);
+
+int dkg_generator_new(const uint8_t *longterm, size_t longterm_len,
+                      const uint8_t *participants, size_t participants_len,
+                      uint32_t t, DkgGenerator **out);
+int dkg_generator_deals(DkgGenerator *g, DkgBuffer *out);
+int dkg_generator_process_deal(DkgGenerator *g, const uint8_t *deal,
+                               size_t deal_len, DkgBuffer *out);
+/* out is empty if there is no justification to broadcast */
+int dkg_generator_process_response(DkgGenerator *g, const uint8_t *resp,
+                                   size_t resp_len, DkgBuffer *out);
+int dkg_generator_process_justification(DkgGenerator *g, const uint8_t *j,
+                                        size_t j_len);
+int dkg_generator_set_timeout(DkgGenerator *g);
+int dkg_generator_certified(const DkgGenerator *g);
+int dkg_generator_secret_commits(DkgGenerator *g, DkgBuffer *out);
+/* out is empty if there is no complaint to broadcast */
+int dkg_generator_process_secret_commits(DkgGenerator *g, const uint8_t *sc,
+                                         size_t sc_len, DkgBuffer *out);
+int dkg_generator_process_complaint_commits(DkgGenerator *g,
+                                            const uint8_t *cc, size_t cc_len,
+                                            DkgBuffer *out);
+int dkg_generator_process_reconstruct_commits(DkgGenerator *g,
+                                              const uint8_t *rc,
+                                              size_t rc_len);
+int dkg_generator_finished(const DkgGenerator *g);
+int dkg_generator_dist_key_share(DkgGenerator *g, DkgBuffer *out);
+void dkg_generator_free(DkgGenerator *g);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* THRESHOLD_DKG_H */
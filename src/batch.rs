@@ -0,0 +1,813 @@
+//! Batch dealing of many secrets to the same verifiers
+//!
+//! A BatchDealer shares every secret of a batch as a vss Dealer would, with
+//! its own polynomials and session id, but derives h and the encryption
+//! context once for the batch. Each verifier receives a single EncryptedDeal
+//! packing its deals for all the secrets, under a single ephemeral key and
+//! signature, and processes it with a BatchVerifier, which approves or
+//! complains about the whole batch with a single Response.
+
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::curve_traits;
+use crate::dh;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::signer;
+use crate::utils;
+use crate::vss;
+use crate::wire;
+
+use curve_traits::{CurvePoint, ECScalar};
+use encoding::{Domain, Encoder};
+use poly::{PriPoly, PubPoly};
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use signer::{KeyAgreement, Signer, SoftwareSigner};
+use vss::{
+    derive_h_in, open_cipher, session_id_of_keys, valid_t, verify_deal_signature, Aggregator,
+    Certification, ComplaintReason, Deal, DealAead, DealMode, EncryptedDeal, ProtocolStatus,
+    Response, ENCRYPTED_DEAL_VERSION,
+};
+
+/// BatchJustification is the Justification of a BatchDealer, revealing the
+/// deals of all the secrets of the batch for a verifier which complained.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BatchJustification<P: CurvePoint = GE> {
+    // SessionID of the batch
+    #[serde(with = "crate::serde_hex")]
+    pub session_id: Vec<u8>,
+    // Index of the verifier who issued the Complaint i.e. index of this Deal
+    pub index: u32,
+    // Deals of the verifier, one per secret
+    pub deals: Vec<Deal<P>>,
+    // Signature over the whole packet
+    #[serde(with = "crate::serde_hex")]
+    pub signature: Vec<u8>,
+}
+
+impl<P: CurvePoint> BatchJustification<P> {
+    pub fn hash(session_id: &[u8], index: u32, deals: &[Deal<P>]) -> [u8; 32] {
+        BatchJustification::hash_in(&Domain::default(), session_id, index, deals)
+    }
+
+    /// hash_in is hash for a batch of the given domain.
+    pub fn hash_in(domain: &Domain, session_id: &[u8], index: u32, deals: &[Deal<P>]) -> [u8; 32] {
+        let deals: Vec<Vec<u8>> = deals.iter().map(|d| d.encode()).collect();
+        domain
+            .encoder(b"batchjustification")
+            .put_bytes(session_id)
+            .put_u32(index)
+            .put_list(&deals)
+            .hash()
+    }
+}
+
+/// batch_session_id returns the session id of a batch, binding the session ids
+/// of all its secrets in order.
+pub fn batch_session_id(session_ids: &[Vec<u8>]) -> [u8; 32] {
+    Encoder::new(b"batchsessionid").put_list(session_ids).hash()
+}
+
+/// BatchDealer shares many secrets with the same verifiers at once. It derives
+/// h and the encryption context once for the batch, and each verifier receives
+/// a single EncryptedDeal packing its deals for all the secrets, under a single
+/// ephemeral key and signature. The verifiers process it with a BatchVerifier
+/// and issue a single Response for the whole batch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(bound(serialize = "K: Serialize", deserialize = "K: Deserialize<'de>"))]
+pub struct BatchDealer<P: CurvePoint = GE, K = SoftwareSigner<P>> {
+    long: K,
+    pub_key: P,
+    verifiers: Arc<[P]>,
+    hkdf_context: Vec<u8>,
+    // session id of the batch, see batch_session_id
+    session_id: Vec<u8>,
+    secrets: Vec<P::Scalar>,
+    // commitments of the secret polynomial of each secret
+    secret_commits: Vec<Vec<Vec<u8>>>,
+    // deals of each verifier, one per secret
+    deals: Vec<Vec<Deal<P>>>,
+    aggregator: Aggregator<P>,
+    #[serde(default)]
+    aead: DealAead,
+}
+
+impl<P: CurvePoint> BatchDealer<P> {
+    /// new returns a BatchDealer sharing every secret of secrets with the
+    /// verifiers, with the threshold t, as Dealer::new does for a single secret.
+    pub fn new(
+        longterm: P::Scalar,
+        secrets: Vec<P::Scalar>,
+        verifiers: Vec<P>,
+        t: u32,
+    ) -> Result<BatchDealer<P>, Box<dyn Error>> {
+        BatchDealer::new_in(&Domain::default(), longterm, secrets, verifiers, t)
+    }
+
+    /// new_in is new for a batch of the given domain, see
+    /// DealerBuilder::domain.
+    pub fn new_in(
+        domain: &Domain,
+        longterm: P::Scalar,
+        secrets: Vec<P::Scalar>,
+        verifiers: Vec<P>,
+        t: u32,
+    ) -> Result<BatchDealer<P>, Box<dyn Error>> {
+        BatchDealer::with_signer_in(
+            domain,
+            SoftwareSigner::new(longterm),
+            secrets,
+            verifiers,
+            t,
+            &mut utils::rand_hack(),
+        )
+    }
+}
+
+impl<P: CurvePoint, K: Signer<P>> BatchDealer<P, K> {
+    /// with_signer_and_rng returns a BatchDealer as new, whose longterm key is
+    /// held by signer and whose polynomials are drawn from rng.
+    pub fn with_signer_and_rng<R: RngCore + CryptoRng>(
+        signer: K,
+        secrets: Vec<P::Scalar>,
+        verifiers: Vec<P>,
+        t: u32,
+        rng: &mut R,
+    ) -> Result<BatchDealer<P, K>, Box<dyn Error>> {
+        BatchDealer::with_signer_in(&Domain::default(), signer, secrets, verifiers, t, rng)
+    }
+
+    /// with_signer_in is with_signer_and_rng for a batch of the given domain.
+    pub fn with_signer_in<R: RngCore + CryptoRng>(
+        domain: &Domain,
+        signer: K,
+        secrets: Vec<P::Scalar>,
+        verifiers: Vec<P>,
+        t: u32,
+        rng: &mut R,
+    ) -> Result<BatchDealer<P, K>, Box<dyn Error>> {
+        if !valid_t(t, &verifiers) {
+            bail!("Invalid threshold")
+        }
+        if secrets.is_empty() {
+            bail!("vss: no secret in the batch");
+        }
+
+        let h: P = derive_h_in(domain.h_derivation(), &verifiers)?;
+        let generator = P::generator();
+        let dealer_pub: P = signer.public();
+        let dealer_key = dealer_pub.pk_to_key_slice();
+        let verifier_keys: Vec<Vec<u8>> = verifiers.iter().map(|v| v.pk_to_key_slice()).collect();
+
+        let n = verifiers.len() as u32;
+        let mut deals: Vec<Vec<Deal<P>>> = vec![Vec::new(); n as usize];
+        let mut session_ids: Vec<Vec<u8>> = Vec::with_capacity(secrets.len());
+        let mut secret_commits: Vec<Vec<Vec<u8>>> = Vec::with_capacity(secrets.len());
+        for secret in secrets.iter() {
+            let sec_pri_poly: PriPoly<P> = PriPoly::new_with_rng(t, Some(*secret), rng);
+            let rand_pri_poly: PriPoly<P> = PriPoly::new_with_rng(t, None, rng);
+            let f_pub: PubPoly<P> = sec_pri_poly.commit(Some(generator));
+            let c: PubPoly<P> = f_pub.add(&rand_pri_poly.commit(Some(h)))?;
+
+            let (_, commitments) = c.info();
+            let commitments: Vec<Vec<u8>> =
+                commitments.iter().map(|x| x.pk_to_key_slice()).collect();
+            let session_id = session_id_of_keys::<P>(
+                domain,
+                &dealer_key,
+                &verifier_keys,
+                None,
+                &[],
+                &commitments,
+                t,
+            );
+
+            let shares = sec_pri_poly
+                .shares(n)
+                .into_iter()
+                .zip(rand_pri_poly.shares(n));
+            for (verifier_deals, (sec_share, rnd_share)) in deals.iter_mut().zip(shares) {
+                verifier_deals.push(Deal {
+                    session_id: session_id.to_vec(),
+                    sec_share,
+                    rnd_share,
+                    t,
+                    commitments: commitments.clone(),
+                    context: Vec::new(),
+                    commitments_root: Vec::new(),
+                });
+            }
+
+            let (_, secret_commit) = f_pub.info();
+            secret_commits.push(secret_commit.iter().map(|x| x.pk_to_key_slice()).collect());
+            session_ids.push(session_id.to_vec());
+        }
+
+        let session_id = batch_session_id(&session_ids).to_vec();
+        let verifiers: Arc<[P]> = verifiers.into();
+        let hkdf_context: Vec<u8> = dh::context_in(domain.hash_suite(), &dealer_pub, &verifiers);
+        let mut aggregator = Aggregator::new(dealer_pub, verifiers.clone(), t, session_id.clone());
+        aggregator.domain = domain.clone();
+
+        Ok(BatchDealer {
+            long: signer,
+            pub_key: dealer_pub,
+            verifiers,
+            hkdf_context,
+            session_id,
+            secrets,
+            secret_commits,
+            deals,
+            aggregator,
+            aead: DealAead::default(),
+        })
+    }
+
+    /// set_aead sets the AEAD encrypting the deals, see Dealer::set_aead.
+    pub fn set_aead(&mut self, aead: DealAead) {
+        self.aead = aead;
+    }
+
+    /// session_id returns the session id of the batch.
+    pub fn session_id(&self) -> Vec<u8> {
+        self.session_id.clone()
+    }
+
+    /// encrypt_deal returns the deals of all the secrets for the verifier at
+    /// index i, encrypted in a single EncryptedDeal.
+    pub fn encrypt_deal(&self, i: u32) -> Result<EncryptedDeal<P>, Box<dyn Error>> {
+        self.encrypt_deal_with_rng(i, &mut utils::rand_hack())
+    }
+
+    /// Same as encrypt_deal, with the ephemeral key and the nonce drawn from rng.
+    pub fn encrypt_deal_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        i: u32,
+        rng: &mut R,
+    ) -> Result<EncryptedDeal<P>, Box<dyn Error>> {
+        let v_pub = self
+            .verifiers
+            .get(i as usize)
+            .ok_or_else(|| simple_error!("dealer: wrong index to generate encrypted deal"))?;
+
+        let dh_secret: P::Scalar = ECScalar::new_random_with_rng(rng);
+        let dh_key: P = P::base_mul(&dh_secret);
+        let pre: P = dh::dh_exchange(&dh_secret, v_pub);
+        let nonce: Vec<u8> = dh::new_nonce(rng).to_vec();
+        let (key, aead_nonce) = DealMode::Legacy.key(
+            self.aead,
+            &pre,
+            &[],
+            &dh_key,
+            v_pub,
+            &self.hkdf_context,
+            &nonce,
+        )?;
+        let deals = self
+            .deals
+            .get(i as usize)
+            .ok_or_else(|| simple_error!("dealer: wrong index to get deal"))?;
+        let deals_buff: Vec<u8> = bincode::serialize(deals)?;
+        let cipher = self
+            .aead
+            .seal(&key, &aead_nonce, &self.hkdf_context, &deals_buff)?;
+
+        let e_hash = EncryptedDeal::hash(
+            ENCRYPTED_DEAL_VERSION,
+            self.aead,
+            DealMode::Legacy,
+            &self.session_id,
+            i,
+            &dh_key,
+            &nonce,
+            &cipher,
+        );
+        let signature = self
+            .long
+            .sign(&e_hash, &self.aggregator.domain.signing_context(i))?;
+
+        Ok(EncryptedDeal {
+            version: ENCRYPTED_DEAL_VERSION,
+            session_id: self.session_id.clone(),
+            cipher,
+            nonce,
+            dh_key,
+            signature,
+            aead: self.aead,
+            mode: DealMode::Legacy,
+        })
+    }
+
+    /// encrypt_deals calls encrypt_deal for each index of the verifiers.
+    pub fn encrypt_deals(&self) -> Result<Vec<EncryptedDeal<P>>, Box<dyn Error>> {
+        let mut rng = utils::rand_hack();
+        (0..self.verifiers.len() as u32)
+            .map(|i| self.encrypt_deal_with_rng(i, &mut rng))
+            .collect()
+    }
+
+    /// process_response analyzes the Response of a verifier about the whole
+    /// batch. If it's a complaint, it returns the BatchJustification to be
+    /// broadcasted to every participant.
+    pub fn process_response(
+        &mut self,
+        r: &Response,
+    ) -> Result<Option<BatchJustification<P>>, Box<dyn Error>> {
+        let certified = self.aggregator.deal_certified();
+        if !self.aggregator.verify_response(r)? {
+            return Ok(None);
+        }
+        self.aggregator.record_certified(certified);
+
+        if r.approved || self.aggregator.certification == Certification::ApprovalOnly {
+            return Ok(None);
+        }
+
+        let deals: Vec<Deal<P>> = self
+            .deals
+            .get(r.index as usize)
+            .cloned()
+            .ok_or_else(|| simple_error!("dealer: index out of bounds in response"))?;
+        let domain = &self.aggregator.domain;
+        let j_hash = BatchJustification::hash_in(domain, &self.session_id, r.index, &deals);
+        let signature = self.long.sign(&j_hash, &domain.signing_context(r.index))?;
+
+        Ok(Some(BatchJustification {
+            session_id: self.session_id.clone(),
+            index: r.index,
+            deals,
+            signature,
+        }))
+    }
+
+    /// commits returns the commitments of the secret polynomial of each secret,
+    /// once the batch is certified.
+    pub fn commits(&self) -> Result<Vec<Vec<Vec<u8>>>, Box<dyn Error>> {
+        if !self.aggregator.enough_approvals() || !self.deal_certified() {
+            bail!("Not enough approvals or the deal is not certified");
+        }
+
+        Ok(self.secret_commits.clone())
+    }
+
+    /// secrets returns the secrets shared by this dealer.
+    pub fn secrets(&self) -> &[P::Scalar] {
+        &self.secrets
+    }
+
+    pub fn set_timeout(&mut self) {
+        self.aggregator.clean_verifiers();
+    }
+
+    pub fn deal_certified(&self) -> bool {
+        self.aggregator.deal_certified()
+    }
+
+    pub fn status(&self) -> ProtocolStatus {
+        self.aggregator.status()
+    }
+}
+
+/// BatchVerifier receives the deals of a BatchDealer and approves or complains
+/// about the whole batch with a single Response.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(bound(serialize = "K: Serialize", deserialize = "K: Deserialize<'de>"))]
+pub struct BatchVerifier<P: CurvePoint = GE, K = SoftwareSigner<P>> {
+    longterm: K,
+    pub_k: P,
+    dealer: P,
+    index: u32,
+    verifiers: Arc<[P]>,
+    hkdf_context: Vec<u8>,
+    aggregator: Aggregator<P>,
+    // deals received from the dealer, one per secret
+    deals: Vec<Deal<P>>,
+}
+
+impl<P: CurvePoint> BatchVerifier<P> {
+    /// new returns a BatchVerifier, see Verifier::new.
+    pub fn new(
+        longterm: P::Scalar,
+        dealer: P,
+        verifiers: Vec<P>,
+    ) -> Result<BatchVerifier<P>, Box<dyn Error>> {
+        BatchVerifier::new_in(&Domain::default(), longterm, dealer, verifiers)
+    }
+
+    /// new_in is new for a batch of the given domain, see
+    /// Verifier::set_domain.
+    pub fn new_in(
+        domain: &Domain,
+        longterm: P::Scalar,
+        dealer: P,
+        verifiers: Vec<P>,
+    ) -> Result<BatchVerifier<P>, Box<dyn Error>> {
+        BatchVerifier::with_signer_in(domain, SoftwareSigner::new(longterm), dealer, verifiers)
+    }
+}
+
+impl<P: CurvePoint, K: Signer<P> + KeyAgreement<P>> BatchVerifier<P, K> {
+    /// with_signer returns a BatchVerifier as new, whose longterm key is held
+    /// by signer.
+    pub fn with_signer(
+        signer: K,
+        dealer: P,
+        verifiers: Vec<P>,
+    ) -> Result<BatchVerifier<P, K>, Box<dyn Error>> {
+        BatchVerifier::with_signer_in(&Domain::default(), signer, dealer, verifiers)
+    }
+
+    /// with_signer_in is with_signer for a batch of the given domain.
+    pub fn with_signer_in(
+        domain: &Domain,
+        signer: K,
+        dealer: P,
+        verifiers: Vec<P>,
+    ) -> Result<BatchVerifier<P, K>, Box<dyn Error>> {
+        let pub_k: P = signer.public();
+        let index = verifiers
+            .iter()
+            .position(|point| point == &pub_k)
+            .map(|i| i as u32)
+            .ok_or_else(|| simple_error!("vss: public key not found in the list of verifiers"))?;
+        let verifiers: Arc<[P]> = verifiers.into();
+        let hkdf_context = dh::context_in(domain.hash_suite(), &dealer, &verifiers);
+        let mut aggregator = Aggregator::new(dealer, verifiers.clone(), 0, Vec::new());
+        aggregator.domain = domain.clone();
+
+        Ok(BatchVerifier {
+            longterm: signer,
+            pub_k,
+            dealer,
+            index,
+            verifiers,
+            hkdf_context,
+            aggregator,
+            deals: Vec::new(),
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// process_encrypted_deal decrypts the deals of the batch and returns the
+    /// Response of this verifier, approving the batch if all the deals are
+    /// valid, to be broadcasted to every participant.
+    pub fn process_encrypted_deal(
+        &mut self,
+        encr_d: &EncryptedDeal<P>,
+    ) -> Result<Response, Box<dyn Error>> {
+        if encr_d.version != ENCRYPTED_DEAL_VERSION {
+            bail!(
+                "vss: unsupported encrypted deal version {}, expected {}",
+                encr_d.version,
+                ENCRYPTED_DEAL_VERSION
+            );
+        }
+        if encr_d.mode != DealMode::Legacy {
+            bail!("vss: batch deals are encrypted in legacy mode");
+        }
+        if !self.deals.is_empty() {
+            bail!("vss: verifier already received a deal")
+        }
+        wire::check_encrypted_deal(encr_d)?;
+        verify_deal_signature(&self.aggregator.domain, &self.dealer, self.index, encr_d)?;
+
+        let pre: P = self.longterm.dh(&encr_d.dh_key)?;
+        let buff = open_cipher(&pre, &self.pub_k, None, &self.hkdf_context, encr_d)?;
+        let deals: Vec<Deal<P>> = wire::decode(&buff)?;
+        for deal in deals.iter() {
+            wire::check_deal(deal, self.verifiers.len() as u32)?;
+        }
+
+        let t = match deals.first() {
+            Some(deal) => deal.t,
+            None => bail!("vss: no deal in the batch"),
+        };
+        if !valid_t(t, &self.verifiers) || deals.iter().any(|d| d.t != t) {
+            bail!("vss: invalid threshold received");
+        }
+        if deals.iter().any(|d| d.sec_share.i != self.index) {
+            bail!("vss: verifier got wrong index from deal")
+        }
+        let session_ids: Vec<Vec<u8>> = deals.iter().map(|d| d.session_id.clone()).collect();
+        if batch_session_id(&session_ids)[..] != encr_d.session_id[..] {
+            bail!("vss: session id doesn't match");
+        }
+
+        let certification = self.aggregator.certification;
+        let domain = self.aggregator.domain.clone();
+        self.aggregator = Aggregator::new(
+            self.dealer,
+            self.verifiers.clone(),
+            t,
+            encr_d.session_id.clone(),
+        );
+        self.aggregator.certification = certification;
+        self.aggregator.domain = domain;
+
+        let reason = self.deals_complaint(&deals);
+        let approved = reason.is_none();
+        let domain = &self.aggregator.domain;
+        let r_hash = Response::hash_reason_in(
+            domain,
+            &encr_d.session_id,
+            self.index,
+            approved as u32,
+            reason,
+        )?;
+        let signature = self
+            .longterm
+            .sign(&r_hash, &domain.signing_context(self.index))?;
+        let response = Response {
+            session_id: encr_d.session_id.clone(),
+            index: self.index,
+            approved,
+            signature,
+            reason,
+        };
+
+        self.deals = deals;
+        self.aggregator.add_response(&response)?;
+        self.aggregator.record_response(&response);
+
+        Ok(response)
+    }
+
+    // deals_complaint returns the reason to complain about the first invalid
+    // deal of the batch, or None if they all verify.
+    fn deals_complaint(&self, deals: &[Deal<P>]) -> Option<ComplaintReason> {
+        let dealer_key = self.dealer.pk_to_key_slice();
+        let verifier_keys: Vec<Vec<u8>> =
+            self.verifiers.iter().map(|v| v.pk_to_key_slice()).collect();
+        let domain = &self.aggregator.domain;
+        deals.iter().find_map(|deal| {
+            let sid = session_id_of_keys::<P>(
+                domain,
+                &dealer_key,
+                &verifier_keys,
+                None,
+                &[],
+                &deal.commitments,
+                deal.t,
+            );
+            let x = P::Scalar::from(deal.sec_share.i as u64 + 1);
+            deal.complaint_reason(domain, &self.verifiers, &sid, &x)
+        })
+    }
+
+    /// process_response records the Response of another verifier about the
+    /// batch.
+    pub fn process_response(&mut self, resp: &Response) -> Result<(), Box<dyn Error>> {
+        let certified = self.aggregator.deal_certified();
+        self.aggregator.verify_response(resp)?;
+        self.aggregator.record_certified(certified);
+        Ok(())
+    }
+
+    /// process_justification checks the deals revealed by a justification of
+    /// the dealer. It returns an error if one of them is invalid, in which case
+    /// the batch will never be certified.
+    pub fn process_justification(
+        &mut self,
+        j: &BatchJustification<P>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.aggregator.certification == Certification::ApprovalOnly {
+            bail!("vss: no justification with the approval-only certification")
+        }
+        if self.aggregator.session_id.is_empty() || self.aggregator.session_id != j.session_id {
+            bail!("vss: session id doesn't match");
+        }
+        let domain = &self.aggregator.domain;
+        let hash = BatchJustification::hash_in(domain, &j.session_id, j.index, &j.deals);
+        P::verify_signature(
+            &self.dealer,
+            &j.signature,
+            &hash,
+            &domain.signing_context(j.index),
+        )
+        .map_err(|e| simple_error!("vss: invalid justification signature: {}", e))?;
+
+        let approved = self
+            .aggregator
+            .responses
+            .get(&j.index)
+            .ok_or_else(|| simple_error!("vss: no complaints received for this justification"))?
+            .approved;
+        if approved {
+            bail!("vss: justification received for an approval")
+        }
+
+        let certified = self.aggregator.deal_certified();
+        let session_ids: Vec<Vec<u8>> = j.deals.iter().map(|d| d.session_id.clone()).collect();
+        let valid = batch_session_id(&session_ids)[..] == j.session_id[..]
+            && j.deals.iter().all(|d| d.sec_share.i == j.index)
+            && self.deals_complaint(&j.deals).is_none();
+        if !valid {
+            self.aggregator.bad_dealer = true;
+            self.aggregator.record_certified(certified);
+            bail!("vss: invalid deal in justification");
+        }
+
+        if let Some(r) = self.aggregator.responses.get_mut(&j.index) {
+            r.approved = true;
+        }
+        self.aggregator.record_certified(certified);
+        Ok(())
+    }
+
+    pub fn set_timeout(&mut self) {
+        self.aggregator.clean_verifiers();
+    }
+
+    pub fn deal_certified(&self) -> bool {
+        self.aggregator.deal_certified()
+    }
+
+    pub fn status(&self) -> ProtocolStatus {
+        self.aggregator.status()
+    }
+
+    /// get_deals returns the deals of this verifier, one per secret, once the
+    /// batch is certified.
+    pub fn get_deals(&self) -> Result<Vec<Deal<P>>, Box<dyn Error>> {
+        if !self.aggregator.enough_approvals() || !self.aggregator.deal_certified() {
+            bail!("Not enough approvals or deal not certified");
+        }
+
+        Ok(self.deals.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use encoding::HDerivation;
+    use ristretto_curve::FE;
+    use vss::{minimum_t, recover_secret, Verifier};
+
+    struct InitData {
+        vss_threshold: u32,
+        verifiers_pub: Vec<GE>,
+        verifiers_sec: Vec<FE>,
+        dealer_pub: GE,
+        dealer_sec: FE,
+    }
+
+    fn setup(nb_verifiers: u32) -> InitData {
+        let (verifiers_sec, verifiers_pub): (Vec<FE>, Vec<GE>) =
+            sim::new_participants(nb_verifiers);
+        let (mut dealer_sec, mut dealer_pub): (Vec<FE>, Vec<GE>) = sim::new_participants(1);
+        InitData {
+            vss_threshold: minimum_t(nb_verifiers),
+            verifiers_pub,
+            verifiers_sec,
+            dealer_pub: dealer_pub.remove(0),
+            dealer_sec: dealer_sec.remove(0),
+        }
+    }
+
+    #[test]
+    fn test_vss_batch_dealer() {
+        let init_data: InitData = setup(7);
+        let secrets: Vec<FE> = (0..3).map(|_| FE::new_random()).collect();
+        let mut dealer: BatchDealer = BatchDealer::new(
+            init_data.dealer_sec,
+            secrets.clone(),
+            init_data.verifiers_pub.clone(),
+            init_data.vss_threshold,
+        )
+        .unwrap();
+        let mut verifiers: Vec<BatchVerifier> = init_data
+            .verifiers_sec
+            .iter()
+            .map(|s| {
+                BatchVerifier::new(*s, init_data.dealer_pub, init_data.verifiers_pub.clone())
+                    .unwrap()
+            })
+            .collect();
+
+        // the deals of the first verifier are invalid, and justified
+        let good_deal: Deal = dealer.deals[0][1].clone();
+        dealer.deals[0][1].sec_share.v = FE::new_random();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        dealer.deals[0][1] = good_deal;
+        assert_eq!(dealer.session_id(), enc_deals[0].session_id);
+
+        // a plain verifier can't open the batch
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[0],
+            init_data.dealer_pub,
+            init_data.verifiers_pub.clone(),
+        )
+        .unwrap();
+        assert!(v.process_encrypted_deal(&enc_deals[0]).is_err());
+
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(d, v)| v.process_encrypted_deal(d).unwrap())
+            .collect();
+        assert!(!resps[0].approved);
+        assert!(resps[1..].iter().all(|r| r.approved));
+        assert!(verifiers[1].process_encrypted_deal(&enc_deals[1]).is_err());
+
+        let mut justification: Option<BatchJustification> = None;
+        for resp in resps.iter() {
+            if let Some(j) = dealer.process_response(resp).unwrap() {
+                justification = Some(j);
+            }
+            for (i, v) in verifiers.iter_mut().enumerate() {
+                if i as u32 != resp.index {
+                    v.process_response(resp).unwrap();
+                }
+            }
+        }
+        let j = justification.unwrap();
+        assert_eq!(3, j.deals.len());
+
+        let mut forged = j.clone();
+        forged.deals[1].sec_share.v = FE::new_random();
+        assert!(verifiers[2].process_justification(&forged).is_err());
+        for v in verifiers.iter_mut() {
+            v.process_justification(&j).unwrap();
+            assert!(v.deal_certified());
+        }
+        assert!(dealer.deal_certified());
+        assert_eq!(3, dealer.commits().unwrap().len());
+
+        // every secret is recovered from the deals of the verifiers
+        let deals: Vec<Vec<Deal>> = verifiers.iter().map(|v| v.get_deals().unwrap()).collect();
+        for (k, secret) in secrets.iter().enumerate() {
+            let column: Vec<Deal> = deals.iter().map(|d| d[k].clone()).collect();
+            let recovered: FE = recover_secret(&column, init_data.vss_threshold).unwrap();
+            assert_eq!(*secret, recovered);
+        }
+    }
+
+    #[test]
+    fn test_vss_batch_domain() {
+        let init_data: InitData = setup(7);
+        let pubs = &init_data.verifiers_pub;
+        let h_derivation = match HDerivation::default() {
+            HDerivation::Legacy => HDerivation::HashToGroup,
+            HDerivation::HashToGroup => HDerivation::Legacy,
+        };
+        let domain = Domain::new(b"deployment a").with_h_derivation(h_derivation);
+        let secrets: Vec<FE> = (0..2).map(|_| FE::new_random()).collect();
+        let mut dealer: BatchDealer = BatchDealer::new_in(
+            &domain,
+            init_data.dealer_sec,
+            secrets,
+            pubs.clone(),
+            init_data.vss_threshold,
+        )
+        .unwrap();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+
+        // a verifier of the default domain rejects the batch
+        let mut v: BatchVerifier = BatchVerifier::new(
+            init_data.verifiers_sec[0],
+            init_data.dealer_pub,
+            pubs.clone(),
+        )
+        .unwrap();
+        assert!(v.process_encrypted_deal(&enc_deals[0]).is_err());
+
+        let mut verifiers: Vec<BatchVerifier> = init_data
+            .verifiers_sec
+            .iter()
+            .map(|s| {
+                BatchVerifier::new_in(&domain, *s, init_data.dealer_pub, pubs.clone()).unwrap()
+            })
+            .collect();
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(d, v)| v.process_encrypted_deal(d).unwrap())
+            .collect();
+        for resp in resps.iter() {
+            assert!(resp.approved);
+            resp.verify_in(&domain, pubs, &dealer.session_id()).unwrap();
+            assert!(dealer.process_response(resp).unwrap().is_none());
+        }
+        assert!(dealer.deal_certified());
+
+        // the deals are the ones of single sessions of the domain
+        for (i, deal) in dealer.deals.iter().enumerate() {
+            let x = FE::from(i as u64 + 1);
+            for d in deal.iter() {
+                d.verify_in(&domain, pubs, &d.session_id, &x).unwrap();
+                d.verify(pubs, &d.session_id)
+                    .expect_err("Must fail, deal of another domain");
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Distributed randomness beacon
+//!
+//! A chained beacon as in drand, on a threshold BLS key generated by the DKG
+//! over G1Point. At every round, the nodes sign the round number along with the
+//! signature of the previous round, and any t partial signatures are aggregated
+//! into the signature of the round. The randomness of a round is the hash of its
+//! signature. BLS signatures are unique, so that less than t nodes can neither
+//! predict nor bias the randomness. Enabled with the `tbls` feature.
+
+use std::error::Error;
+
+use crate::bls12_381_curve;
+use crate::dkg;
+use crate::tbls;
+
+use bls12_381_curve::G1Point;
+use dkg::DistKeyShare;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Beacon is the output of a round of the beacon.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Beacon {
+    pub round: u64,
+    // Signature of the previous round
+    pub previous_signature: Vec<u8>,
+    // Signature of the round by the distributed key
+    pub signature: Vec<u8>,
+}
+
+impl Beacon {
+    /// genesis returns the beacon of round 0, whose signature is the seed of
+    /// the chain, e.g. the hash of the group configuration.
+    pub fn genesis(seed: &[u8]) -> Beacon {
+        Beacon {
+            round: 0,
+            previous_signature: Vec::new(),
+            signature: seed.to_vec(),
+        }
+    }
+
+    /// randomness returns the random output of the round, the SHA-256 hash of
+    /// its signature.
+    pub fn randomness(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&Sha256::digest(&self.signature));
+        out
+    }
+
+    /// verify checks the signature of the beacon under the distributed public
+    /// key.
+    pub fn verify(&self, public: &G1Point) -> Result<(), Box<dyn Error>> {
+        if self.round == 0 {
+            bail!("beacon: the genesis beacon has no signature");
+        }
+        tbls::verify(
+            public,
+            &message(self.round, &self.previous_signature),
+            &self.signature,
+        )
+    }
+
+    /// verify_next checks that next is a valid beacon of the round following
+    /// this one.
+    pub fn verify_next(&self, next: &Beacon, public: &G1Point) -> Result<(), Box<dyn Error>> {
+        if next.round != self.round + 1 {
+            bail!(
+                "beacon: round {} doesn't follow round {}",
+                next.round,
+                self.round
+            );
+        }
+        if next.previous_signature != self.signature {
+            bail!(
+                "beacon: round {} isn't chained to the previous round",
+                next.round
+            );
+        }
+        next.verify(public)
+    }
+}
+
+/// message returns the message signed at the given round, the SHA-256 hash of
+/// the signature of the previous round followed by the round as a big endian
+/// u64, as in drand.
+pub fn message(round: u64, previous_signature: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.input(previous_signature);
+    hasher.input(round.to_be_bytes());
+    hasher.result().to_vec()
+}
+
+/// verify_chain checks that the beacons are the consecutive rounds following
+/// the beacon first.
+pub fn verify_chain(
+    public: &G1Point,
+    first: &Beacon,
+    chain: &[Beacon],
+) -> Result<(), Box<dyn Error>> {
+    let mut last: &Beacon = first;
+    for beacon in chain.iter() {
+        last.verify_next(beacon, public)?;
+        last = beacon;
+    }
+    Ok(())
+}
+
+/// Node is a participant of the beacon, holding its share of the distributed
+/// key and the last beacon of the chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Node {
+    share: DistKeyShare<G1Point>,
+    // number of participants
+    n: u32,
+    last: Beacon,
+}
+
+impl Node {
+    /// new returns the Node of a participant out of n, starting the chain after
+    /// the beacon last, e.g. the genesis beacon.
+    pub fn new(share: DistKeyShare<G1Point>, n: u32, last: Beacon) -> Node {
+        Node { share, n, last }
+    }
+
+    /// last returns the last beacon of the chain.
+    pub fn last(&self) -> &Beacon {
+        &self.last
+    }
+
+    /// public_key returns the distributed public key verifying the beacons.
+    pub fn public_key(&self) -> G1Point {
+        self.share.get_public_key()
+    }
+
+    /// partial returns the partial signature of this node for the next round,
+    /// to be sent to the other nodes.
+    pub fn partial(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let msg = message(self.last.round + 1, &self.last.signature);
+        tbls::sign_partial(&self.share.get_pri_share(), &msg)
+    }
+
+    /// aggregate recovers the beacon of the next round out of the partial
+    /// signatures, of which at least t must be valid, and appends it to the
+    /// chain.
+    pub fn aggregate(&mut self, partials: &[Vec<u8>]) -> Result<Beacon, Box<dyn Error>> {
+        let round = self.last.round + 1;
+        let pub_poly = self.share.pub_poly();
+        let signature = tbls::aggregate(
+            &pub_poly,
+            &message(round, &self.last.signature),
+            partials,
+            pub_poly.threshold(),
+            self.n,
+        )?;
+        self.last = Beacon {
+            round,
+            previous_signature: self.last.signature.clone(),
+            signature,
+        };
+        Ok(self.last.clone())
+    }
+
+    /// process_beacon appends the beacon of the next round, aggregated by
+    /// another node, to the chain if it is valid.
+    pub fn process_beacon(&mut self, beacon: &Beacon) -> Result<(), Box<dyn Error>> {
+        self.last.verify_next(beacon, &self.public_key())?;
+        self.last = beacon.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::PriPoly;
+
+    fn new_nodes(n: u32, t: u32) -> Vec<Node> {
+        let pri_poly: PriPoly<G1Point> = PriPoly::new(t, None);
+        let (_, commits) = pri_poly.commit(None).info();
+        pri_poly
+            .shares(n)
+            .into_iter()
+            .map(|s| {
+                let share = DistKeyShare::new(commits.clone(), s);
+                Node::new(share, n, Beacon::genesis(b"genesis"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_beacon() {
+        let (n, t) = (5, 3);
+        let mut nodes = new_nodes(n, t);
+        let public = nodes[0].public_key();
+        let genesis = nodes[0].last().clone();
+
+        let mut chain: Vec<Beacon> = Vec::new();
+        for _ in 0..3 {
+            let partials: Vec<Vec<u8>> = nodes.iter().map(|node| node.partial().unwrap()).collect();
+            let beacon = nodes[0].aggregate(&partials[1..=t as usize]).unwrap();
+            for node in nodes.iter_mut().skip(1) {
+                node.process_beacon(&beacon).unwrap();
+            }
+            chain.push(beacon);
+        }
+        verify_chain(&public, &genesis, &chain).unwrap();
+        assert_eq!(3, nodes[4].last().round);
+        assert_ne!(chain[0].randomness(), chain[1].randomness());
+
+        // every subset of t nodes gives the same beacon
+        let partials: Vec<Vec<u8>> = nodes.iter().map(|node| node.partial().unwrap()).collect();
+        let b1 = nodes[0].aggregate(&partials[..t as usize]).unwrap();
+        let b2 = nodes[1].aggregate(&partials[(n - t) as usize..]).unwrap();
+        assert_eq!(b1, b2);
+
+        // not enough partial signatures
+        assert!(nodes[2].aggregate(&partials[..(t - 1) as usize]).is_err());
+
+        // rounds out of order or unchained
+        chain.swap(0, 1);
+        assert!(verify_chain(&public, &genesis, &chain).is_err());
+        let mut forged = b1.clone();
+        forged.round += 1;
+        assert!(nodes[2].process_beacon(&forged).is_err());
+        assert!(nodes[2].process_beacon(&b1).is_ok());
+    }
+}
@@ -0,0 +1,239 @@
+//! dkg-node runs a participant of a DKG ceremony over TCP, so that operators
+//! can run a ceremony without writing Rust. Built with the `cli` feature:
+//!
+//! ```text
+//! dkg-node <node config> <private key> <output directory>
+//! ```
+//!
+//! The node config is a JSON file holding the group::GroupConfig of the
+//! ceremony, the address of every participant, in the order of the group, and
+//! the duration of the phases in seconds:
+//!
+//! ```text
+//! {"group": {...}, "addresses": ["10.0.0.1:7000", ...], "phase_seconds": 10}
+//! ```
+//!
+//! The private key file holds the hex encoded private key of the participant,
+//! as a JSON string. The node listens on its own address, runs the dealer and
+//! verifier roles of the DKG with a machine::DkgMachine ended by timers, and
+//! writes its share to share.json and the distributed public key to
+//! public_key in the output directory.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use threshold_dkg::curve_traits::{ECPoint, ECScalar};
+use threshold_dkg::dkg::DistKeyGenerator;
+use threshold_dkg::group::GroupConfig;
+use threshold_dkg::machine::{DkgMachine, OutgoingMessage, Phase, PhaseDurations};
+use threshold_dkg::ristretto_curve::{FE, GE};
+use threshold_dkg::transport::{Message, Transport};
+use threshold_dkg::wire;
+
+// Maximum time to wait for the commitments of QUAL once the last timed phase
+// ended
+const COMMITS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// NodeConfig is the configuration of a node, shared by all the participants.
+#[derive(Deserialize)]
+struct NodeConfig {
+    group: GroupConfig<GE>,
+    // addresses of the participants, in the order of the group
+    addresses: Vec<String>,
+    // duration of every timed phase
+    phase_seconds: u64,
+}
+
+/// TcpTransport sends every message on a new connection to its recipient, as
+/// the index of the sender followed by the length prefixed encoded message.
+/// The sender index is only a hint: the messages of the DKG are signed.
+struct TcpTransport {
+    index: u32,
+    addresses: Vec<String>,
+    received: Receiver<(u32, Vec<u8>)>,
+}
+
+impl TcpTransport {
+    fn new(index: u32, addresses: Vec<String>) -> Result<TcpTransport, Box<dyn Error>> {
+        let listener = TcpListener::bind(&addresses[index as usize])?;
+        let (sender, received) = channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                thread::spawn(move || read_frame(stream, &sender));
+            }
+        });
+        Ok(TcpTransport {
+            index,
+            addresses,
+            received,
+        })
+    }
+}
+
+// read_frame reads a message from a connection and passes it on. Invalid
+// frames are dropped.
+fn read_frame(mut stream: TcpStream, sender: &Sender<(u32, Vec<u8>)>) {
+    let mut header = [0u8; 8];
+    if stream.read_exact(&mut header).is_err() {
+        return;
+    }
+    let from = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+    if len > wire::MAX_MESSAGE_SIZE {
+        return;
+    }
+    let mut bytes = vec![0u8; len as usize];
+    if stream.read_exact(&mut bytes).is_ok() {
+        let _ = sender.send((from, bytes));
+    }
+}
+
+impl Transport<GE> for TcpTransport {
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn send(&mut self, to: u32, msg: Message<GE>) -> Result<(), Box<dyn Error>> {
+        let address = self
+            .addresses
+            .get(to as usize)
+            .ok_or_else(|| format!("dkg-node: no address for participant {}", to))?;
+        let bytes = bincode::serialize(&msg)?;
+        let mut stream = TcpStream::connect(address)?;
+        stream.write_all(&self.index.to_le_bytes())?;
+        stream.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        stream.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn broadcast(&mut self, msg: Message<GE>) -> Result<(), Box<dyn Error>> {
+        for to in 0..self.addresses.len() as u32 {
+            if to != self.index {
+                // a participant down must not stop the ceremony
+                if let Err(e) = self.send(to, msg.clone()) {
+                    eprintln!("dkg-node: sending to {}: {}", to, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Option<(u32, Message<GE>)>, Box<dyn Error>> {
+        match self.received.try_recv() {
+            Ok((from, bytes)) => {
+                let n = self.addresses.len() as u32;
+                Ok(Some((from, wire::decode_message(&bytes, n)?)))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+fn deliver(transport: &mut TcpTransport, out: Vec<OutgoingMessage<GE>>) {
+    for msg in out {
+        let res = match msg {
+            OutgoingMessage::Send(to, msg) => transport.send(to, msg),
+            OutgoingMessage::Broadcast(msg) => transport.broadcast(msg),
+        };
+        if let Err(e) = res {
+            eprintln!("dkg-node: {}", e);
+        }
+    }
+}
+
+fn run(config: &Path, key: &Path, out: &Path) -> Result<(), Box<dyn Error>> {
+    let config: NodeConfig = serde_json::from_str(&fs::read_to_string(config)?)?;
+    let longterm: FE = serde_json::from_str(&fs::read_to_string(key)?)?;
+    config.group.check()?;
+    if config.addresses.len() != config.group.participants.len() {
+        return Err("dkg-node: not one address per participant".into());
+    }
+    let public = GE::generator().scalar_mul(&longterm.get_element());
+    let index = config
+        .group
+        .index_of(&public)
+        .ok_or("dkg-node: own public key not in the group")?;
+
+    let mut transport = TcpTransport::new(index, config.addresses.clone())?;
+    let mut machine = DkgMachine::new(DistKeyGenerator::for_group(longterm, &config.group)?);
+    let phase = Duration::from_secs(config.phase_seconds);
+    machine.set_durations(PhaseDurations {
+        deal_distribution: phase,
+        responses: phase,
+        justifications: phase,
+    });
+    // the other nodes are given one phase to start listening
+    thread::sleep(phase);
+    let out = machine.start()?;
+    deliver(&mut transport, out);
+
+    // messages received ahead of their phase, processed once it starts
+    let mut pending: VecDeque<Message<GE>> = VecDeque::new();
+    let mut commits_start: Option<Instant> = None;
+    while machine.phase() != Phase::Finalized {
+        while let Some((from, msg)) = transport.recv().unwrap_or_else(|e| {
+            eprintln!("dkg-node: invalid message: {}", e);
+            None
+        }) {
+            match machine.process(&msg) {
+                Ok(out) => deliver(&mut transport, out),
+                Err(e) => {
+                    eprintln!("dkg-node: message from {}: {}", from, e);
+                    pending.push_back(msg);
+                }
+            }
+        }
+        let out = machine.tick(Instant::now())?;
+        if !out.is_empty() || machine.phase() == Phase::Commits {
+            deliver(&mut transport, out);
+            for msg in pending.drain(..).collect::<Vec<_>>() {
+                match machine.process(&msg) {
+                    Ok(out) => deliver(&mut transport, out),
+                    Err(_) => pending.push_back(msg),
+                }
+            }
+        }
+        if machine.phase() == Phase::Commits {
+            let start = *commits_start.get_or_insert_with(Instant::now);
+            if start.elapsed() > COMMITS_TIMEOUT {
+                return Err("dkg-node: commitments of QUAL still missing".into());
+            }
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let share = machine.dist_key_share()?;
+    fs::create_dir_all(out)?;
+    fs::write(
+        out.join("share.json"),
+        serde_json::to_string_pretty(&share)?,
+    )?;
+    fs::write(out.join("public_key"), share.get_public_key().to_hex())?;
+    println!("{}", share.get_public_key().to_hex());
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 4 {
+        eprintln!("usage: dkg-node <node config> <private key> <output directory>");
+        std::process::exit(2);
+    }
+    if let Err(e) = run(
+        Path::new(&args[1]),
+        Path::new(&args[2]),
+        Path::new(&args[3]),
+    ) {
+        eprintln!("dkg-node: {}", e);
+        std::process::exit(1);
+    }
+}
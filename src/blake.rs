@@ -1,6 +1,9 @@
 use blake2b::blake2xb::Iter;
 use blake2b::Blake2xb;
 
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
 /// Hash bytes using blake2xb algorithm
 pub fn new_blake2xb(buffer: Vec<u8>) -> Iter {
     if buffer.len() > 64 {
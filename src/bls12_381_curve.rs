@@ -0,0 +1,529 @@
+//! BLS12-381 curve - a pairing friendly backend, so that the DKG output can be
+//! used for threshold BLS signatures. Both source groups G1 and G2 implement
+//! the curve traits and share the same scalar field. Enabled with the
+//! `bls12_381` feature.
+
+use crate::blake;
+use crate::curve_traits::{CurvePoint, ECPoint, ECScalar};
+use std::convert::{From, TryInto};
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "tbls")]
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Gt, Scalar};
+use hex::{decode, encode};
+use rand_core::{CryptoRng, RngCore};
+use serde::de;
+use serde::de::Visitor;
+use serde::ser::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha512};
+
+pub const SECRET_KEY_SIZE: usize = 32;
+
+// 5^((r-1)/2^32) with the non-residue 5, a primitive 2^32-th root of unity of
+// the scalar field, little endian
+const ROOT_OF_UNITY: &str = "1f2f910b508f781b94e0b370f24f02c4c0d668d1c86dd50f6f6b415b9ed71202";
+/// Size of a G1 point in compressed form
+pub const G1_SIZE: usize = 48;
+/// Size of a G2 point in compressed form
+pub const G2_SIZE: usize = 96;
+
+pub type SK = Scalar;
+
+#[derive(Clone, Debug, Copy)]
+pub struct Bls12381Scalar {
+    purpose: &'static str,
+    fe: SK,
+}
+#[derive(Clone, Debug, Copy)]
+pub struct G1Point {
+    purpose: &'static str,
+    ge: G1Projective,
+}
+#[derive(Clone, Debug, Copy)]
+pub struct G2Point {
+    purpose: &'static str,
+    ge: G2Projective,
+}
+pub type FE = Bls12381Scalar;
+
+/// pairing computes e(p, q)
+pub fn pairing(p: &G1Point, q: &G2Point) -> Gt {
+    bls12_381::pairing(&G1Affine::from(p.ge), &G2Affine::from(q.ge))
+}
+
+/// hash_to_g2 hashes msg to G2 following the hash_to_curve specification
+/// (expand_message_xmd with SHA-256, simplified SWU map) with the domain
+/// separation tag dst.
+#[cfg(feature = "tbls")]
+pub fn hash_to_g2(msg: &[u8], dst: &[u8]) -> G2Point {
+    G2Point {
+        purpose: "from_hash",
+        ge: <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(msg, dst),
+    }
+}
+
+/// scalar_to_be_bytes returns the 32 bytes big endian encoding of the scalar,
+/// used by Ethereum and drand, while to_bytes is little endian.
+pub fn scalar_to_be_bytes(s: &FE) -> Vec<u8> {
+    let mut bytes = s.fe.to_bytes();
+    bytes.reverse();
+    bytes.to_vec()
+}
+
+/// scalar_from_be_bytes decodes a scalar from its big endian encoding. It
+/// returns an error unless the encoding is canonical.
+pub fn scalar_from_be_bytes(bytes: &[u8]) -> Result<FE, Box<dyn Error>> {
+    let mut le: [u8; SECRET_KEY_SIZE] = bytes
+        .try_into()
+        .map_err(|_| simple_error!("Invalid scalar length"))?;
+    le.reverse();
+    let fe: Option<SK> = SK::from_bytes(&le).into();
+    let fe = fe.ok_or_else(|| simple_error!("Invalid scalar"))?;
+    Ok(FE {
+        purpose: "from_bytes",
+        fe,
+    })
+}
+
+impl From<u64> for Bls12381Scalar {
+    fn from(n: u64) -> Self {
+        Self {
+            purpose: "from_big_int",
+            fe: SK::from(n),
+        }
+    }
+}
+
+impl From<SK> for Bls12381Scalar {
+    fn from(scalar: SK) -> Self {
+        Self {
+            purpose: "from_scalar",
+            fe: scalar,
+        }
+    }
+}
+
+impl ECScalar<SK> for Bls12381Scalar {
+    fn new_random_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        Self {
+            purpose: "random",
+            fe: SK::from_bytes_wide(&bytes),
+        }
+    }
+
+    fn zero() -> Self {
+        Self {
+            purpose: "zero",
+            fe: SK::from(0u64),
+        }
+    }
+
+    fn get_element(&self) -> SK {
+        self.fe
+    }
+    fn set_element(&mut self, element: SK) {
+        self.fe = element
+    }
+
+    fn to_hex(&self) -> String {
+        encode(self.to_bytes())
+    }
+
+    /// Little endian encoding of the scalar
+    fn to_bytes(&self) -> Vec<u8> {
+        self.fe.to_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        if bytes.len() != SECRET_KEY_SIZE {
+            bail!("Invalid scalar length")
+        }
+        let mut wide = [0u8; 64];
+        wide[..SECRET_KEY_SIZE].copy_from_slice(bytes);
+        Ok(Self {
+            purpose: "from_bytes",
+            fe: SK::from_bytes_wide(&wide),
+        })
+    }
+
+    fn add(&self, other: &SK) -> Self {
+        Self {
+            purpose: "add",
+            fe: self.get_element() + other,
+        }
+    }
+
+    fn mul(&self, other: &SK) -> Self {
+        Self {
+            purpose: "mul",
+            fe: self.get_element() * other,
+        }
+    }
+
+    fn sub(&self, other: &SK) -> Self {
+        Self {
+            purpose: "sub",
+            fe: self.get_element() - other,
+        }
+    }
+
+    fn invert(&self) -> Self {
+        // zero has no inverse, keep it as zero like the ristretto backend does
+        let inv: Option<SK> = self.get_element().invert().into();
+        Self {
+            purpose: "invert",
+            fe: inv.unwrap_or_else(|| SK::from(0u64)),
+        }
+    }
+}
+
+impl Default for Bls12381Scalar {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl Serialize for Bls12381Scalar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Bls12381Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Bls12381Scalar, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(Bls12381ScalarVisitor)
+    }
+}
+
+struct Bls12381ScalarVisitor;
+
+impl<'de> Visitor<'de> for Bls12381ScalarVisitor {
+    type Value = Bls12381Scalar;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("bls12-381 scalar")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Bls12381Scalar, E> {
+        let s_bytes: Vec<u8> = decode(s).map_err(E::custom)?;
+        Bls12381Scalar::from_bytes(&s_bytes).map_err(E::custom)
+    }
+}
+
+impl PartialEq for Bls12381Scalar {
+    fn eq(&self, other: &Bls12381Scalar) -> bool {
+        self.get_element() == other.get_element()
+    }
+}
+
+/// Hash bytes with blake2xb and cut the output stream in chunks of `size` bytes.
+/// Each chunk is given to `try_point` until a valid point is found.
+fn hash_to_point_with<P>(
+    bytes: &[u8],
+    size: usize,
+    try_point: impl Fn(&[u8]) -> Option<P>,
+) -> Result<P, Box<dyn Error>> {
+    let mut buffer: Vec<u8> = Vec::new();
+    for value in blake::new_blake2xb(bytes.to_vec()) {
+        buffer.extend_from_slice(value.as_ref());
+        while buffer.len() >= size {
+            let chunk: Vec<u8> = buffer.drain(..size).collect();
+            if let Some(point) = try_point(&chunk) {
+                return Ok(point);
+            }
+        }
+    }
+    bail!("Error hash")
+}
+
+// G1 and G2 only differ by their underlying types and encoding size
+macro_rules! impl_bls_point {
+    ($point:ident, $projective:ident, $affine:ident, $size:expr, $name:expr, $curve_id:expr, $suite:expr) => {
+        impl ECPoint<$projective, SK> for $point {
+            fn generator() -> $point {
+                $point {
+                    purpose: "base_fe",
+                    ge: $projective::generator(),
+                }
+            }
+
+            /// The point in compressed form
+            fn pk_to_key_slice(&self) -> Vec<u8> {
+                $affine::from(self.ge).to_compressed().to_vec()
+            }
+
+            fn get_element(&self) -> $projective {
+                self.ge
+            }
+
+            fn to_hex(&self) -> String {
+                encode(self.pk_to_key_slice())
+            }
+
+            /// Reads a compressed point, which must be in the prime order subgroup
+            fn from_bytes(bytes: &[u8]) -> Result<$point, Box<dyn Error>> {
+                let bytes: [u8; $size] = bytes
+                    .try_into()
+                    .map_err(|_| simple_error!("Invalid Public Key length"))?;
+                let affine: Option<$affine> = $affine::from_compressed(&bytes).into();
+                let new_point = affine
+                    .map(|x| $point {
+                        purpose: "from_bytes",
+                        ge: $projective::from(x),
+                    })
+                    .ok_or_else(|| simple_error!("Invalid Public Key"))?;
+                Ok(new_point)
+            }
+
+            fn scalar_mul(&self, fe: &SK) -> $point {
+                $point {
+                    purpose: "scalar_point_mul",
+                    ge: self.ge * fe,
+                }
+            }
+
+            fn add_point(&self, other: &$projective) -> $point {
+                $point {
+                    purpose: "combine",
+                    ge: self.ge + other,
+                }
+            }
+
+            fn sub_point(&self, other: &$projective) -> $point {
+                $point {
+                    purpose: "sub",
+                    ge: self.ge - other,
+                }
+            }
+        }
+
+        impl CurvePoint for $point {
+            type SK = SK;
+            type PK = $projective;
+            type Scalar = Bls12381Scalar;
+
+            const CURVE_ID: &'static str = $curve_id;
+            const TWO_ADICITY: u32 = 32;
+
+            fn root_of_unity() -> Bls12381Scalar {
+                Bls12381Scalar::from_bytes(&decode(ROOT_OF_UNITY).unwrap()).unwrap()
+            }
+
+            fn identity() -> $point {
+                $point {
+                    purpose: "identity",
+                    ge: $projective::identity(),
+                }
+            }
+
+            /// Try-and-increment: every chunk of the blake2xb output is read as the
+            /// x coordinate of a compressed point. The first one on the curve is
+            /// mapped to the prime order subgroup by clearing the cofactor.
+            fn hash_to_point(bytes: &[u8]) -> Result<$point, Box<dyn Error>> {
+                hash_to_point_with(bytes, $size, |chunk| {
+                    let mut candidate: [u8; $size] = chunk.try_into().ok()?;
+                    // compression flag set, infinity flag cleared
+                    candidate[0] = (candidate[0] | 0x80) & 0xbf;
+                    let affine: Option<$affine> =
+                        $affine::from_compressed_unchecked(&candidate).into();
+                    let point = $projective::from(affine?).clear_cofactor();
+                    if bool::from(point.is_identity()) {
+                        return None;
+                    }
+                    Some($point {
+                        purpose: "from_hash",
+                        ge: point,
+                    })
+                })
+            }
+
+            #[cfg(feature = "tbls")]
+            const HASH_TO_GROUP_SUITE: &'static str = $suite;
+
+            /// hash_to_curve of RFC 9380 with expand_message_xmd over SHA-256
+            /// and the simplified SWU map
+            #[cfg(feature = "tbls")]
+            fn hash_to_group(msg: &[u8], dst: &[u8]) -> Result<$point, Box<dyn Error>> {
+                Ok($point {
+                    purpose: "from_hash",
+                    ge: <$projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+                        msg, dst,
+                    ),
+                })
+            }
+
+            fn hash_to_scalar(bytes: &[u8]) -> Bls12381Scalar {
+                let mut hasher = Sha512::new();
+                hasher.input(bytes);
+                let digest: [u8; 64] = hasher
+                    .result()
+                    .as_slice()
+                    .try_into()
+                    .expect("Slice with incorrect length");
+                Bls12381Scalar {
+                    purpose: "from_hash",
+                    fe: SK::from_bytes_wide(&digest),
+                }
+            }
+        }
+
+        impl Default for $point {
+            fn default() -> Self {
+                Self::identity()
+            }
+        }
+
+        impl PartialEq for $point {
+            fn eq(&self, other: &$point) -> bool {
+                self.ge == other.ge
+            }
+        }
+
+        impl Serialize for $point {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.serialize_str(&self.to_hex())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $point {
+            fn deserialize<D>(deserializer: D) -> Result<$point, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct PointVisitor;
+
+                impl<'de> Visitor<'de> for PointVisitor {
+                    type Value = $point;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str($name)
+                    }
+
+                    fn visit_str<E: de::Error>(self, s: &str) -> Result<$point, E> {
+                        let s_bytes: Vec<u8> = decode(s).map_err(E::custom)?;
+                        $point::from_bytes(&s_bytes).map_err(E::custom)
+                    }
+                }
+
+                deserializer.deserialize_str(PointVisitor)
+            }
+        }
+    };
+}
+
+impl_bls_point!(
+    G1Point,
+    G1Projective,
+    G1Affine,
+    G1_SIZE,
+    "G1Point",
+    "bls12_381_g1",
+    "BLS12381G1_XMD:SHA-256_SSWU_RO_"
+);
+impl_bls_point!(
+    G2Point,
+    G2Projective,
+    G2Affine,
+    G2_SIZE,
+    "G2Point",
+    "bls12_381_g2",
+    "BLS12381G2_XMD:SHA-256_SSWU_RO_"
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vss::{recover_secret, Deal, Dealer, EncryptedDeal, Response, Verifier};
+
+    fn gen_pair<P: CurvePoint>() -> (P::Scalar, P) {
+        let secret: P::Scalar = ECScalar::new_random();
+        let public: P = P::generator().scalar_mul(&secret.get_element());
+        (secret, public)
+    }
+
+    #[test]
+    fn test_bls12_381_point_bytes() {
+        let (_, p1): (FE, G1Point) = gen_pair();
+        let bytes = p1.pk_to_key_slice();
+        assert_eq!(G1_SIZE, bytes.len());
+        assert_eq!(p1, G1Point::from_bytes(&bytes).unwrap());
+
+        let (_, p2): (FE, G2Point) = gen_pair();
+        let bytes = p2.pk_to_key_slice();
+        assert_eq!(G2_SIZE, bytes.len());
+        assert_eq!(p2, G2Point::from_bytes(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_bls12_381_hash_to_point() {
+        let h1 = G1Point::hash_to_point(b"some bytes").unwrap();
+        assert_eq!(h1, G1Point::hash_to_point(b"some bytes").unwrap());
+        assert_ne!(h1, G1Point::hash_to_point(b"other bytes").unwrap());
+        // from_bytes checks the point is in the prime order subgroup
+        G1Point::from_bytes(&h1.pk_to_key_slice()).unwrap();
+
+        let h2 = G2Point::hash_to_point(b"some bytes").unwrap();
+        G2Point::from_bytes(&h2.pk_to_key_slice()).unwrap();
+    }
+
+    #[test]
+    fn test_bls12_381_pairing() {
+        let a = FE::new_random();
+        let g1 = G1Point::generator();
+        let g2 = G2Point::generator();
+        let left = pairing(&g1.scalar_mul(&a.get_element()), &g2);
+        let right = pairing(&g1, &g2.scalar_mul(&a.get_element()));
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn test_bls12_381_vss_whole() {
+        let nb_verifiers: u32 = 7;
+        let threshold = crate::vss::minimum_t(nb_verifiers);
+        let (verifiers_sec, verifiers_pub): (Vec<FE>, Vec<G1Point>) =
+            (0..nb_verifiers).map(|_| gen_pair()).unzip();
+        let (dealer_sec, dealer_pub): (FE, G1Point) = gen_pair();
+        let secret = FE::new_random();
+
+        let mut dealer: Dealer<G1Point> =
+            Dealer::new(dealer_sec, secret, verifiers_pub.clone(), threshold).unwrap();
+        let mut verifiers: Vec<Verifier<G1Point>> = verifiers_sec
+            .iter()
+            .map(|sec| Verifier::new(*sec, dealer_pub, verifiers_pub.clone()).unwrap())
+            .collect();
+
+        let enc_deals: Vec<EncryptedDeal<G1Point>> = dealer.encrypt_deals().unwrap();
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(deal, v)| v.process_encrypted_deal(deal).unwrap())
+            .collect();
+
+        for resp in resps.iter() {
+            for (i, v) in verifiers.iter_mut().enumerate() {
+                if resp.index != i as u32 {
+                    v.process_response(resp).unwrap();
+                }
+            }
+            assert!(dealer.process_response(resp).unwrap().is_none());
+        }
+
+        let deals: Vec<Deal<G1Point>> = verifiers.iter().map(|v| v.get_deal().unwrap()).collect();
+        let recovered: FE = recover_secret(&deals, threshold).unwrap();
+        assert_eq!(secret, recovered);
+    }
+}
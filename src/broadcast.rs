@@ -0,0 +1,477 @@
+//! Echo broadcast of the responses and justifications of the DKG.
+//!
+//! The DKG assumes a broadcast channel: every participant gets the same
+//! response or justification from a given origin. Over point-to-point links a
+//! malicious participant can instead send different messages to different
+//! participants, e.g. approve a deal towards some of them and complain towards
+//! the others. EchoBroadcast prevents such an equivocation with a signed echo
+//! broadcast:
+//!  1. the origin sends its message to every other participant;
+//!  2. a participant checks the signature of the message and sends back to the
+//!     origin a signed echo of its digest. It echoes a single message per slot,
+//!     i.e. per kind, origin and subject of the message;
+//!  3. once it has the echoes of a quorum, the origin sends the message along
+//!     with the echoes to every other participant;
+//!  4. a participant delivers a message echoed by a quorum.
+//!
+//! With n participants of whom at most f = (n - 1) / 3 are malicious, two
+//! quorums of (n + f) / 2 + 1 participants share an honest one, so that no two
+//! participants deliver different messages for the same slot.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::encoding;
+use crate::ristretto_curve;
+use crate::transport;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use encoding::Encoder;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use transport::Message;
+
+/// SlotKind is the kind of an echo broadcasted message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SlotKind {
+    Response,
+    Justification,
+}
+
+/// Slot identifies the messages of which a single one is delivered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Slot {
+    pub kind: SlotKind,
+    // Index of the participant issuing the message
+    pub origin: u32,
+    // Index of the dealer of a response, or of the complainer of a
+    // justification
+    pub subject: u32,
+}
+
+impl Slot {
+    /// of returns the slot of a response or a justification.
+    pub fn of<P: CurvePoint>(msg: &Message<P>) -> Result<Slot, Box<dyn Error>> {
+        match msg {
+            Message::Response(r) => Ok(Slot {
+                kind: SlotKind::Response,
+                origin: r.response.index,
+                subject: r.index,
+            }),
+            Message::Justification(j) => Ok(Slot {
+                kind: SlotKind::Justification,
+                origin: j.index,
+                subject: j.justification.index,
+            }),
+            _ => bail!("broadcast: only responses and justifications are echo broadcasted"),
+        }
+    }
+}
+
+/// Echo is the signature of a participant over the digest of the message it
+/// accepted for a slot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Echo {
+    pub slot: Slot,
+    pub digest: [u8; 32],
+    // Index of the participant issuing the echo
+    pub index: u32,
+    #[serde(with = "crate::serde_hex")]
+    pub signature: Vec<u8>,
+}
+
+impl Echo {
+    /// hash returns the hash signed by the echo of digest in the given session.
+    pub fn hash(session: &[u8], slot: &Slot, digest: &[u8; 32]) -> [u8; 32] {
+        Encoder::new(b"echo")
+            .put_bytes(session)
+            .put_u8(slot.kind as u8)
+            .put_u32(slot.origin)
+            .put_u32(slot.subject)
+            .put_bytes(digest)
+            .hash()
+    }
+}
+
+/// EchoMessage is a message of the echo broadcast.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum EchoMessage<P: CurvePoint = GE> {
+    // Message sent by its origin to every other participant
+    Send(Box<Message<P>>),
+    // Echo of a message, sent back to its origin
+    Echo(Echo),
+    // Message along with the echoes of a quorum
+    Final(Box<Message<P>>, Vec<Echo>),
+}
+
+/// EchoAction is what the caller must do after processing an EchoMessage.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum EchoAction<P: CurvePoint = GE> {
+    // Send the message to the participant at the given index only
+    Send(u32, EchoMessage<P>),
+    // Send the message to every other participant
+    Broadcast(EchoMessage<P>),
+    // Process the message, which is delivered
+    Deliver(Message<P>),
+}
+
+// Pending is a message of this participant waiting for a quorum of echoes.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct Pending<P: CurvePoint> {
+    message: Message<P>,
+    digest: [u8; 32],
+    echoes: BTreeMap<u32, Echo>,
+}
+
+/// quorum returns the number of echoes needed to deliver a message among n
+/// participants.
+pub fn quorum(n: usize) -> usize {
+    let f = n.saturating_sub(1) / 3;
+    (n + f) / 2 + 1
+}
+
+/// EchoBroadcast runs the echo broadcast of a participant.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct EchoBroadcast<P: CurvePoint = GE> {
+    index: u32,
+    // longterm secret, signing the echoes
+    long: P::Scalar,
+    pub_key: P,
+    participants: Vec<P>,
+    // session in which the echoes are valid
+    session: Vec<u8>,
+    // digest echoed for each slot
+    echoed: HashMap<Slot, [u8; 32]>,
+    pending: HashMap<Slot, Pending<P>>,
+    // digest delivered for each slot
+    delivered: HashMap<Slot, [u8; 32]>,
+}
+
+impl<P: CurvePoint> EchoBroadcast<P> {
+    /// new returns the echo broadcast of the participant with the longterm
+    /// secret among participants. The echoes are only valid in the given
+    /// session, which must be the same for all the participants.
+    pub fn new(
+        longterm: P::Scalar,
+        participants: Vec<P>,
+        session: &[u8],
+    ) -> Result<EchoBroadcast<P>, Box<dyn Error>> {
+        let pub_key: P = P::generator().scalar_mul(&longterm.get_element());
+        let index = participants
+            .iter()
+            .position(|p| p == &pub_key)
+            .ok_or_else(|| {
+                simple_error!("broadcast: own public key not found in list of participants")
+            })?;
+        Ok(EchoBroadcast {
+            index: index as u32,
+            long: longterm,
+            pub_key,
+            participants,
+            session: session.to_vec(),
+            echoed: HashMap::new(),
+            pending: HashMap::new(),
+            delivered: HashMap::new(),
+        })
+    }
+
+    /// broadcast starts the echo broadcast of a response or a justification of
+    /// this participant. It returns the message to send to every other
+    /// participant: the message itself, or directly the final message if the
+    /// echo of this participant is a quorum.
+    pub fn broadcast(&mut self, msg: Message<P>) -> Result<EchoMessage<P>, Box<dyn Error>> {
+        let slot = Slot::of(&msg)?;
+        if slot.origin != self.index {
+            bail!("broadcast: message of another participant");
+        }
+        let digest = digest(&msg)?;
+        if let Some(d) = self.echoed.get(&slot) {
+            if d != &digest {
+                bail!("broadcast: another message already broadcasted for this slot");
+            }
+        }
+        let echo = self.echo(slot, digest)?;
+        self.echoed.insert(slot, digest);
+        let mut echoes = BTreeMap::new();
+        echoes.insert(self.index, echo);
+        if echoes.len() >= quorum(self.participants.len()) {
+            return Ok(EchoMessage::Final(
+                Box::new(msg),
+                echoes.into_iter().map(|(_, e)| e).collect(),
+            ));
+        }
+        self.pending.insert(
+            slot,
+            Pending {
+                message: msg.clone(),
+                digest,
+                echoes,
+            },
+        );
+        Ok(EchoMessage::Send(Box::new(msg)))
+    }
+
+    /// process processes a message of the echo broadcast and returns the
+    /// actions to take. It returns an error if the message is invalid, in
+    /// particular if its origin equivocates.
+    pub fn process(&mut self, msg: &EchoMessage<P>) -> Result<Vec<EchoAction<P>>, Box<dyn Error>> {
+        match msg {
+            EchoMessage::Send(m) => self.process_send(m),
+            EchoMessage::Echo(e) => self.process_echo(e),
+            EchoMessage::Final(m, echoes) => self.process_final(m, echoes),
+        }
+    }
+
+    fn process_send(&mut self, msg: &Message<P>) -> Result<Vec<EchoAction<P>>, Box<dyn Error>> {
+        let slot = Slot::of(msg)?;
+        if slot.origin == self.index {
+            bail!("broadcast: own message sent back");
+        }
+        self.verify_message(msg)?;
+        let digest = digest(msg)?;
+        if let Some(d) = self.echoed.get(&slot) {
+            if d != &digest {
+                bail!(
+                    "broadcast: participant {} sent two messages for the same slot",
+                    slot.origin
+                );
+            }
+        }
+        // an echo already sent is sent again, in case it was lost
+        let echo = self.echo(slot, digest)?;
+        self.echoed.insert(slot, digest);
+        Ok(vec![EchoAction::Send(slot.origin, EchoMessage::Echo(echo))])
+    }
+
+    fn process_echo(&mut self, echo: &Echo) -> Result<Vec<EchoAction<P>>, Box<dyn Error>> {
+        self.verify_echo(echo)?;
+        let quorum = quorum(self.participants.len());
+        let pending = self
+            .pending
+            .get_mut(&echo.slot)
+            .ok_or_else(|| simple_error!("broadcast: echo of no pending message"))?;
+        if echo.digest != pending.digest {
+            bail!("broadcast: echo of another message");
+        }
+        if pending.echoes.contains_key(&echo.index) {
+            return Ok(Vec::new());
+        }
+        pending.echoes.insert(echo.index, echo.clone());
+        if pending.echoes.len() != quorum {
+            return Ok(Vec::new());
+        }
+        Ok(vec![EchoAction::Broadcast(EchoMessage::Final(
+            Box::new(pending.message.clone()),
+            pending.echoes.values().cloned().collect(),
+        ))])
+    }
+
+    fn process_final(
+        &mut self,
+        msg: &Message<P>,
+        echoes: &[Echo],
+    ) -> Result<Vec<EchoAction<P>>, Box<dyn Error>> {
+        let slot = Slot::of(msg)?;
+        self.verify_message(msg)?;
+        let digest = digest(msg)?;
+        match self.delivered.get(&slot) {
+            Some(d) if d == &digest => return Ok(Vec::new()),
+            Some(_) => bail!("broadcast: two messages delivered for the same slot"),
+            None => (),
+        }
+
+        let mut indices: BTreeSet<u32> = BTreeSet::new();
+        for echo in echoes {
+            if echo.slot != slot || echo.digest != digest {
+                bail!("broadcast: echo of another message");
+            }
+            if !indices.insert(echo.index) {
+                bail!("broadcast: duplicated echo");
+            }
+            self.verify_echo(echo)?;
+        }
+        if indices.len() < quorum(self.participants.len()) {
+            bail!("broadcast: not enough echoes");
+        }
+
+        self.delivered.insert(slot, digest);
+        Ok(vec![EchoAction::Deliver(msg.clone())])
+    }
+
+    // echo returns the echo of this participant for digest.
+    fn echo(&self, slot: Slot, digest: [u8; 32]) -> Result<Echo, Box<dyn Error>> {
+        let hash = Echo::hash(&self.session, &slot, &digest);
+        let signature = P::sign_msg(&self.long, &self.pub_key, &hash, &self.index.to_le_bytes())?;
+        Ok(Echo {
+            slot,
+            digest,
+            index: self.index,
+            signature,
+        })
+    }
+
+    fn verify_echo(&self, echo: &Echo) -> Result<(), Box<dyn Error>> {
+        let pub_k = self
+            .participants
+            .get(echo.index as usize)
+            .ok_or_else(|| simple_error!("broadcast: index out of bounds in echo"))?;
+        let hash = Echo::hash(&self.session, &echo.slot, &echo.digest);
+        P::verify_signature(pub_k, &echo.signature, &hash, &echo.index.to_le_bytes())
+            .map_err(|e| simple_error!("broadcast: invalid echo signature: {}", e).into())
+    }
+
+    // verify_message checks that the message is signed by its origin. Its
+    // content is checked by the DKG once delivered.
+    fn verify_message(&self, msg: &Message<P>) -> Result<(), Box<dyn Error>> {
+        let slot = Slot::of(msg)?;
+        let pub_k = self
+            .participants
+            .get(slot.origin as usize)
+            .ok_or_else(|| simple_error!("broadcast: index out of bounds in message"))?;
+        let (hash, signature, ctx) = match msg {
+            Message::Response(r) => (
+                r.response.hash_self()?,
+                &r.response.signature,
+                r.response.index,
+            ),
+            Message::Justification(j) => (
+                vss::Justification::hash(
+                    &j.justification.session_id,
+                    j.justification.index,
+                    &j.justification.deal,
+                )?,
+                &j.justification.signature,
+                j.justification.index,
+            ),
+            _ => bail!("broadcast: only responses and justifications are echo broadcasted"),
+        };
+        P::verify_signature(pub_k, signature, &hash, &ctx.to_le_bytes())
+            .map_err(|e| simple_error!("broadcast: invalid message signature: {}", e).into())
+    }
+}
+
+// digest returns the digest of the content of a message, leaving out its
+// signature.
+fn digest<P: CurvePoint>(msg: &Message<P>) -> Result<[u8; 32], Box<dyn Error>> {
+    match msg {
+        Message::Response(dkg::Response { index, response }) => Ok(Encoder::new(b"echoresponse")
+            .put_u32(*index)
+            .put_bytes(&response.hash_self()?)
+            .hash()),
+        Message::Justification(dkg::Justification {
+            index,
+            justification,
+        }) => Ok(Encoder::new(b"echojustification")
+            .put_u32(*index)
+            .put_bytes(&vss::Justification::hash(
+                &justification.session_id,
+                justification.index,
+                &justification.deal,
+            )?)
+            .hash()),
+        _ => bail!("broadcast: only responses and justifications are echo broadcasted"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+
+    #[test]
+    fn test_echo_broadcast() {
+        assert_eq!(1, quorum(1));
+        assert_eq!(3, quorum(4));
+        assert_eq!(4, quorum(5));
+        assert_eq!(5, quorum(7));
+
+        let n = 4;
+        let (secrets, publics) = sim::new_participants::<GE>(n);
+        let mut dkgs: Vec<DistKeyGenerator> = secrets
+            .iter()
+            .map(|s| DistKeyGenerator::new(*s, publics.clone(), 3).unwrap())
+            .collect();
+        let mut echoes: Vec<EchoBroadcast> = secrets
+            .iter()
+            .map(|s| EchoBroadcast::new(*s, publics.clone(), b"session").unwrap())
+            .collect();
+
+        // participant 1 approves the deal of 0
+        let deals = dkgs[0].deals().unwrap();
+        let resp = dkgs[1].process_deal(&deals[&1]).unwrap();
+        let send = echoes[1]
+            .broadcast(Message::Response(resp.clone()))
+            .unwrap();
+
+        let mut finals: Vec<EchoMessage> = Vec::new();
+        for i in &[0usize, 2, 3] {
+            let actions = echoes[*i].process(&send).unwrap();
+            let echo = match &actions[..] {
+                [EchoAction::Send(1, echo)] => echo.clone(),
+                _ => panic!("must echo to the origin"),
+            };
+            for action in echoes[1].process(&echo).unwrap() {
+                match action {
+                    EchoAction::Broadcast(m) => finals.push(m),
+                    _ => panic!("must broadcast the final message"),
+                }
+            }
+        }
+        // the final message is sent once, at the quorum
+        assert_eq!(1, finals.len());
+        for i in &[0usize, 2, 3] {
+            let delivered = echoes[*i].process(&finals[0]).unwrap();
+            match &delivered[..] {
+                [EchoAction::Deliver(Message::Response(r))] => assert_eq!(resp.index, r.index),
+                _ => panic!("must deliver the response"),
+            }
+            assert!(echoes[*i].process(&finals[0]).unwrap().is_empty());
+        }
+
+        // participant 1 then complains towards participant 2
+        let mut complaint = resp.clone();
+        complaint.response.approved = false;
+        complaint.response.signature = GE::sign_msg(
+            &secrets[1],
+            &publics[1],
+            &complaint.response.hash_self().unwrap(),
+            &1u32.to_le_bytes(),
+        )
+        .unwrap();
+        let equivocation = EchoMessage::Send(Box::new(Message::Response(complaint.clone())));
+        assert!(echoes[2].process(&equivocation).is_err());
+
+        // a forged message isn't echoed
+        let mut forged = complaint;
+        forged.response.signature = resp.response.signature;
+        let forged = EchoMessage::Send(Box::new(Message::Response(forged)));
+        assert!(echoes[3].process(&forged).is_err());
+
+        // a final message needs a quorum of echoes
+        if let EchoMessage::Final(m, mut echoes_1) = finals.remove(0) {
+            let mut fresh =
+                EchoBroadcast::<GE>::new(secrets[0], publics.clone(), b"session").unwrap();
+            echoes_1.pop();
+            assert!(fresh
+                .process(&EchoMessage::Final(m.clone(), echoes_1.clone()))
+                .is_err());
+            echoes_1.push(echoes_1[0].clone());
+            assert!(fresh
+                .process(&EchoMessage::Final(m.clone(), echoes_1))
+                .is_err());
+        }
+
+        // the longterm key must be one of the participants
+        let rand_scalar: FE = ECScalar::new_random();
+        assert!(EchoBroadcast::<GE>::new(rand_scalar, publics, b"session").is_err());
+    }
+}
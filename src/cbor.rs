@@ -0,0 +1,102 @@
+//! CBOR (RFC 8949) encoding of the protocol messages, enabled with the `cbor`
+//! feature, for COSE based systems and constrained devices.
+//!
+//! The encoding is deterministic, so that an encoded message can be hashed or
+//! used as the payload of a COSE_Sign1:
+//!  - the fields of a struct are a map keyed by their position, in increasing
+//!    order, and the variants of an enum are keyed by their position;
+//!  - integers and lengths take their shortest form, and every length is
+//!    definite;
+//!  - byte strings, e.g. session ids and signatures, are CBOR byte strings,
+//!    and points and scalars are their hex encoding as text strings.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::transport;
+use crate::wire;
+
+use curve_traits::CurvePoint;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use transport::Message;
+
+/// encode returns the deterministic CBOR encoding of the value.
+pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(serde_cbor::ser::to_vec_packed(value)?)
+}
+
+/// decode decodes a value encoded with encode, of at most
+/// wire::MAX_MESSAGE_SIZE bytes and without trailing bytes.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Box<dyn Error>> {
+    if bytes.len() as u64 > wire::MAX_MESSAGE_SIZE {
+        bail!(
+            "cbor: message of {} bytes over the limit of {}",
+            bytes.len(),
+            wire::MAX_MESSAGE_SIZE
+        );
+    }
+    Ok(serde_cbor::from_slice(bytes)?)
+}
+
+/// decode_message decodes a message of the transports and checks the vss
+/// messages it holds, of a session of threshold t, as wire::decode_message.
+pub fn decode_message<P: CurvePoint>(bytes: &[u8], t: u32) -> Result<Message<P>, Box<dyn Error>> {
+    let msg: Message<P> = decode(bytes)?;
+    wire::check_message(&msg, t)?;
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_traits::ECScalar;
+    use crate::dkg;
+    use crate::ristretto_curve::{FE, GE};
+    use crate::sim;
+    use crate::vss::{Dealer, Response};
+
+    #[test]
+    fn test_cbor() {
+        let (secs, pubs) = sim::new_participants::<GE>(4);
+        let secret: FE = ECScalar::new_random();
+        let dealer: Dealer = Dealer::new(secs[0], secret, pubs, 3).unwrap();
+        let deal = dealer.encrypt_deal(1).unwrap();
+        let msg: Message = Message::Deal(dkg::Deal {
+            index: 0,
+            deal: deal.clone(),
+        });
+
+        let bytes = encode(&msg).unwrap();
+        let decoded: Message = decode_message(&bytes, 3).unwrap();
+        assert_eq!(bytes, encode(&decoded).unwrap());
+        match decoded {
+            Message::Deal(d) => assert_eq!(deal, d.deal),
+            _ => panic!("not a deal"),
+        }
+        // the session id is a byte string of 32 bytes
+        let sid = [&[0x58, 0x20][..], &deal.session_id].concat();
+        assert!(bytes.windows(sid.len()).any(|w| w == &sid[..]));
+
+        let resp = Response {
+            session_id: vec![0xab; 32],
+            index: 3,
+            approved: true,
+            signature: vec![0x01, 0x02],
+            reason: None,
+        };
+        // {0: h'abab..', 1: 3, 2: true, 3: h'0102', 4: null}
+        let expected = [
+            &[0xa5, 0x00, 0x58, 0x20][..],
+            &[0xab; 32],
+            &[0x01, 0x03, 0x02, 0xf5, 0x03, 0x42, 0x01, 0x02, 0x04, 0xf6],
+        ]
+        .concat();
+        assert_eq!(expected, encode(&resp).unwrap());
+        assert_eq!(resp, decode::<Response>(&expected).unwrap());
+
+        decode::<Response>(&[expected.as_slice(), &[0]].concat())
+            .expect_err("Must fail, trailing byte");
+        decode::<Response>(&expected[..expected.len() - 1]).expect_err("Must fail, truncated");
+    }
+}
@@ -0,0 +1,171 @@
+//! Certificates of certified deals
+//!
+//! A Certificate holds the commitments of a deal and at least t approving
+//! Responses signed by the verifiers. It is returned by Dealer::certificate
+//! or Verifier::certificate once the deal is certified, and checked with only
+//! the public keys of the dealer and of the verifiers.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::ristretto_curve;
+use crate::vss;
+
+use curve_traits::CurvePoint;
+use encoding::Domain;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use vss::{check_points, session_id_at, valid_t, Response};
+
+/// Certificate is a compact proof that a deal is certified: the commitments of
+/// the deal and at least t approving Responses signed by the verifiers. It
+/// can be checked by a party which didn't follow the protocol, e.g. a
+/// gateway posting the deal on a chain or a node joining late.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Certificate<P: CurvePoint = GE> {
+    // SessionID of the deal
+    #[serde(with = "crate::serde_hex")]
+    pub session_id: Vec<u8>,
+    // Threshold security parameter
+    pub t: u32,
+    // Polynomial commitments of the deal
+    #[serde(with = "crate::serde_hex::list")]
+    pub commitments: Vec<Vec<u8>>,
+    // Application context bound to the session id, empty if none
+    #[serde(with = "crate::serde_hex")]
+    pub context: Vec<u8>,
+    // x-coordinates of the shares of a Dealer created with new_with_points
+    #[serde(default)]
+    pub points: Option<Vec<P::Scalar>>,
+    // Approving responses, by increasing index
+    pub responses: Vec<Response>,
+}
+
+impl<P: CurvePoint> Certificate<P> {
+    /// verify checks that the certificate is about a session of the dealer
+    /// with the verifiers and holds at least t valid approvals of distinct
+    /// verifiers.
+    pub fn verify(&self, dealer: &P, verifiers: &[P]) -> Result<(), Box<dyn Error>> {
+        self.verify_in(&Domain::default(), dealer, verifiers)
+    }
+
+    /// verify_in is verify for a session of the given domain.
+    pub fn verify_in(
+        &self,
+        domain: &Domain,
+        dealer: &P,
+        verifiers: &[P],
+    ) -> Result<(), Box<dyn Error>> {
+        if !valid_t(self.t, verifiers) || self.commitments.len() != self.t as usize {
+            bail!("vss: invalid threshold in certificate");
+        }
+        if let Some(points) = &self.points {
+            check_points::<P>(points, verifiers.len())?;
+        }
+        let session_id = session_id_at(
+            domain,
+            dealer,
+            verifiers,
+            self.points.as_deref(),
+            &self.context,
+            &self.commitments,
+            self.t,
+        );
+        if self.session_id != session_id {
+            bail!("vss: certificate of another session");
+        }
+        let mut previous: Option<u32> = None;
+        for r in self.responses.iter() {
+            if previous.map_or(false, |i| i >= r.index) {
+                bail!("vss: unsorted or duplicate responses in certificate");
+            }
+            if !r.approved {
+                bail!("vss: complaint in certificate");
+            }
+            r.verify_in(domain, verifiers, &session_id)?;
+            previous = Some(r.index);
+        }
+        if self.responses.len() < self.t as usize {
+            bail!("vss: not enough approvals in certificate");
+        }
+        Ok(())
+    }
+
+    /// secret_commit returns the commitment of the secret of the certified
+    /// deal.
+    pub fn secret_commit(&self) -> Result<P, Box<dyn Error>> {
+        let commit = self
+            .commitments
+            .first()
+            .ok_or_else(|| simple_error!("vss: no commitments in certificate"))?;
+        P::from_bytes(commit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use curve_traits::ECScalar;
+    use ristretto_curve::FE;
+    use vss::{minimum_t, Dealer, Verifier};
+
+    #[test]
+    fn test_vss_certificate() {
+        let (secs, verifiers_pub): (Vec<FE>, Vec<GE>) = sim::new_participants(7);
+        let (dealer_sec, dealer_pub): (Vec<FE>, Vec<GE>) = sim::new_participants(1);
+        let t = minimum_t(7);
+        let mut dealer: Dealer =
+            Dealer::new(dealer_sec[0], FE::new_random(), verifiers_pub.clone(), t).unwrap();
+        let mut verifiers: Vec<Verifier> = secs
+            .iter()
+            .map(|s| Verifier::new(*s, dealer_pub[0], verifiers_pub.clone()).unwrap())
+            .collect();
+        dealer
+            .certificate()
+            .expect_err("Must fail, deal not certified");
+
+        let mut resps = Vec::new();
+        for (i, v) in verifiers.iter_mut().enumerate() {
+            let deal = dealer.encrypt_deal(i as u32).unwrap();
+            resps.push(v.process_encrypted_deal(&deal).unwrap());
+        }
+        for r in resps.iter() {
+            assert!(dealer.process_response(r).unwrap().is_none());
+            for v in verifiers.iter_mut() {
+                if v.index() != r.index {
+                    v.process_response(r).unwrap();
+                }
+            }
+        }
+
+        let cert = dealer.certificate().unwrap();
+        assert_eq!(cert, verifiers[2].certificate().unwrap());
+        assert_eq!(7, cert.responses.len());
+        cert.verify(&dealer_pub[0], &verifiers_pub).unwrap();
+        assert_eq!(
+            dealer.secret_commit().unwrap(),
+            cert.secret_commit().unwrap()
+        );
+
+        let mut short = cert.clone();
+        short.responses.truncate(cert.t as usize - 1);
+        short
+            .verify(&dealer_pub[0], &verifiers_pub)
+            .expect_err("Must fail, not enough approvals");
+        let mut duplicate = cert.clone();
+        duplicate.responses[1] = duplicate.responses[0].clone();
+        duplicate
+            .verify(&dealer_pub[0], &verifiers_pub)
+            .expect_err("Must fail, duplicate approval");
+        let mut forged = cert.clone();
+        forged.commitments.swap(0, 1);
+        forged
+            .verify(&dealer_pub[0], &verifiers_pub)
+            .expect_err("Must fail, other commitments");
+        cert.verify(&verifiers_pub[0], &verifiers_pub)
+            .expect_err("Must fail, other dealer");
+    }
+}
@@ -0,0 +1,323 @@
+//! Coordination of a DKG ceremony.
+//!
+//! The most common failure of a ceremony is a participant running the DKG
+//! with another list of participants, or another order of them, than the
+//! others: its session ids and indices differ and the whole run fails late.
+//! The optional Coordinator fixes the group before the DKG starts:
+//!  1. every participant sends a Registration holding its public key, signed
+//!     to prove that it holds the private key.
+//!  2. the Coordinator sorts the public keys, which fixes the index of every
+//!     participant, and distributes the GroupDescription.
+//!  3. every participant checks that it is in the group and returns an
+//!     Acknowledgement signing the hash of the description it received. The
+//!     Coordinator reports the participants acknowledging another group.
+//!
+//! The Coordinator isn't trusted: a participant only acknowledges a group
+//! holding its own key, and the DKG binds its messages to the participants.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::ristretto_curve;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECScalar};
+use encoding::Encoder;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+
+/// Registration is sent by a participant to the Coordinator.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Registration<P: CurvePoint = GE> {
+    pub public: P,
+    // signature of the ceremony label, proving the possession of the key
+    pub signature: Vec<u8>,
+}
+
+/// GroupDescription is the agreed group of a ceremony, distributed by the
+/// Coordinator.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct GroupDescription<P: CurvePoint = GE> {
+    // label of the ceremony
+    pub label: Vec<u8>,
+    // security parameter t of the DKG
+    pub threshold: u32,
+    // public keys of the participants, the index of a participant being its
+    // position
+    pub participants: Vec<P>,
+}
+
+/// Acknowledgement is returned by a participant to the Coordinator, for the
+/// group description it received. It holds the public key of the participant
+/// rather than its index, which differs in a diverging group.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Acknowledgement<P: CurvePoint = GE> {
+    pub public: P,
+    pub group_hash: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+// acknowledgement_hash returns the hash signed by an Acknowledgement.
+fn acknowledgement_hash(group_hash: &[u8; 32]) -> [u8; 32] {
+    Encoder::new(b"acknowledgement")
+        .put_bytes(group_hash)
+        .hash()
+}
+
+// registration_hash returns the hash signed by a Registration.
+fn registration_hash<P: CurvePoint>(label: &[u8], public: &P) -> [u8; 32] {
+    Encoder::new(b"registration")
+        .put_bytes(label)
+        .put_bytes(&public.pk_to_key_slice())
+        .hash()
+}
+
+impl<P: CurvePoint> Registration<P> {
+    /// new returns the registration of the participant of private key
+    /// longterm to the ceremony of the label.
+    pub fn new(longterm: &P::Scalar, label: &[u8]) -> Result<Registration<P>, Box<dyn Error>> {
+        let public: P = P::generator().scalar_mul(&longterm.get_element());
+        let signature = P::sign_msg(longterm, &public, &registration_hash(label, &public), &[])?;
+        Ok(Registration { public, signature })
+    }
+
+    /// verify checks the signature of the registration to the ceremony of the
+    /// label.
+    pub fn verify(&self, label: &[u8]) -> Result<(), Box<dyn Error>> {
+        P::verify_signature(
+            &self.public,
+            &self.signature,
+            &registration_hash(label, &self.public),
+            &[],
+        )
+        .map_err(|e| simple_error!("coordinator: invalid registration signature: {}", e).into())
+    }
+}
+
+impl<P: CurvePoint> GroupDescription<P> {
+    /// hash returns the hash of the group description, signed by the
+    /// acknowledgements.
+    pub fn hash(&self) -> [u8; 32] {
+        let participants: Vec<Vec<u8>> = self.participants.iter().map(P::pk_to_key_slice).collect();
+        Encoder::new(b"groupdescription")
+            .put_bytes(&self.label)
+            .put_u32(self.threshold)
+            .put_list(&participants)
+            .hash()
+    }
+
+    /// index_of returns the index of the participant of the public key.
+    pub fn index_of(&self, public: &P) -> Option<u32> {
+        self.participants
+            .iter()
+            .position(|p| p == public)
+            .map(|i| i as u32)
+    }
+
+    /// acknowledge checks that the participant of private key longterm is in
+    /// the group and returns its acknowledgement of the group.
+    pub fn acknowledge(&self, longterm: &P::Scalar) -> Result<Acknowledgement<P>, Box<dyn Error>> {
+        let public: P = P::generator().scalar_mul(&longterm.get_element());
+        if self.index_of(&public).is_none() {
+            bail!("coordinator: own public key not in the group");
+        }
+        if !vss::valid_t(self.threshold, &self.participants) {
+            bail!("coordinator: invalid threshold in the group");
+        }
+        let group_hash = self.hash();
+        let signature = P::sign_msg(longterm, &public, &acknowledgement_hash(&group_hash), &[])?;
+        Ok(Acknowledgement {
+            public,
+            group_hash,
+            signature,
+        })
+    }
+}
+
+/// Coordinator collects the registrations of the participants and fixes the
+/// group of the ceremony.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Coordinator<P: CurvePoint = GE> {
+    label: Vec<u8>,
+    // security parameter t, minimum_t(n) if None
+    threshold: Option<u32>,
+    // public keys registered so far
+    registered: Vec<P>,
+    // group fixed by finalize
+    group: Option<GroupDescription<P>>,
+    // acknowledgements received, by index
+    acks: Vec<Option<Acknowledgement<P>>>,
+}
+
+impl<P: CurvePoint> Coordinator<P> {
+    /// new returns the Coordinator of the ceremony of the label, with the
+    /// threshold of the DKG, or minimum_t(n) if None.
+    pub fn new(label: &[u8], threshold: Option<u32>) -> Coordinator<P> {
+        Coordinator {
+            label: label.to_vec(),
+            threshold,
+            registered: Vec::new(),
+            group: None,
+            acks: Vec::new(),
+        }
+    }
+
+    /// register checks the registration of a participant and adds its public
+    /// key. It returns false if the key is already registered.
+    pub fn register(&mut self, registration: &Registration<P>) -> Result<bool, Box<dyn Error>> {
+        if self.group.is_some() {
+            bail!("coordinator: group already fixed");
+        }
+        registration.verify(&self.label)?;
+        if self.registered.contains(&registration.public) {
+            return Ok(false);
+        }
+        self.registered.push(registration.public);
+        Ok(true)
+    }
+
+    /// finalize fixes the group, sorting the participants by their encoded
+    /// public key, and returns its description to distribute to them.
+    pub fn finalize(&mut self) -> Result<GroupDescription<P>, Box<dyn Error>> {
+        if let Some(group) = &self.group {
+            return Ok(group.clone());
+        }
+        let mut participants = self.registered.clone();
+        participants.sort_by_key(P::pk_to_key_slice);
+        let threshold = self
+            .threshold
+            .unwrap_or_else(|| vss::minimum_t(participants.len() as u32));
+        if !vss::valid_t(threshold, &participants) {
+            bail!(
+                "coordinator: invalid threshold {} for {} participants",
+                threshold,
+                participants.len()
+            );
+        }
+        let group = GroupDescription {
+            label: self.label.clone(),
+            threshold,
+            participants,
+        };
+        self.acks = vec![None; group.participants.len()];
+        self.group = Some(group.clone());
+        Ok(group)
+    }
+
+    /// process_acknowledgement checks the signature of an acknowledgement and
+    /// records it. An acknowledgement of another group is recorded as well,
+    /// and reported by divergent.
+    pub fn process_acknowledgement(
+        &mut self,
+        ack: &Acknowledgement<P>,
+    ) -> Result<(), Box<dyn Error>> {
+        let group = self
+            .group
+            .as_ref()
+            .ok_or_else(|| simple_error!("coordinator: group not fixed yet"))?;
+        let index = group
+            .index_of(&ack.public)
+            .ok_or_else(|| simple_error!("coordinator: acknowledgement from outside the group"))?;
+        P::verify_signature(
+            &ack.public,
+            &ack.signature,
+            &acknowledgement_hash(&ack.group_hash),
+            &[],
+        )
+        .map_err(|e| simple_error!("coordinator: invalid acknowledgement signature: {}", e))?;
+        self.acks[index as usize] = Some(ack.clone());
+        Ok(())
+    }
+
+    /// divergent returns the indices of the participants which acknowledged
+    /// another group than the one distributed, e.g. with another list or order
+    /// of participants.
+    pub fn divergent(&self) -> Vec<u32> {
+        let group_hash = match &self.group {
+            Some(group) => group.hash(),
+            None => return Vec::new(),
+        };
+        self.acks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, ack)| match ack {
+                Some(ack) if ack.group_hash != group_hash => Some(i as u32),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// missing returns the indices of the participants whose acknowledgement
+    /// hasn't been received.
+    pub fn missing(&self) -> Vec<u32> {
+        self.acks
+            .iter()
+            .enumerate()
+            .filter(|(_, ack)| ack.is_none())
+            .map(|(i, _)| i as u32)
+            .collect()
+    }
+
+    /// agreed returns true once every participant acknowledged the group
+    /// distributed, so that the DKG can start.
+    pub fn agreed(&self) -> bool {
+        self.group.is_some() && self.missing().is_empty() && self.divergent().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use curve_traits::ECPoint;
+
+    #[test]
+    fn test_coordinator() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(5);
+        let mut coordinator: Coordinator = Coordinator::new(b"ceremony", None);
+        for sec in secs.iter() {
+            let registration = Registration::new(sec, b"ceremony").unwrap();
+            assert!(coordinator.register(&registration).unwrap());
+            assert!(!coordinator.register(&registration).unwrap());
+        }
+        let other = Registration::<GE>::new(&secs[0], b"other ceremony").unwrap();
+        let mut forged = Registration::new(&secs[1], b"ceremony").unwrap();
+        forged.public = other.public;
+        coordinator
+            .register(&forged)
+            .expect_err("Must fail, signature of another key");
+
+        let group: GroupDescription = coordinator.finalize().unwrap();
+        assert_eq!(3, group.threshold);
+        let mut sorted = pubs.clone();
+        sorted.sort_by_key(GE::pk_to_key_slice);
+        assert_eq!(sorted, group.participants);
+        assert!(!coordinator.agreed());
+
+        // the participant of secs[0] runs with another order of participants
+        let mut diverging = group.clone();
+        diverging.participants.swap(0, 1);
+        for sec in secs.iter() {
+            let ack = if sec == &secs[0] {
+                diverging.acknowledge(sec).unwrap()
+            } else {
+                group.acknowledge(sec).unwrap()
+            };
+            coordinator.process_acknowledgement(&ack).unwrap();
+        }
+        let index = group.index_of(&pubs[0]).unwrap();
+        assert_eq!(vec![index], coordinator.divergent());
+        assert!(coordinator.missing().is_empty());
+        assert!(!coordinator.agreed());
+
+        let ack = group.acknowledge(&secs[0]).unwrap();
+        coordinator.process_acknowledgement(&ack).unwrap();
+        assert!(coordinator.agreed());
+    }
+}
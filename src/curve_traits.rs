@@ -1,12 +1,34 @@
-use std::convert::From;
-use std::error::Error;
+use core::convert::From;
+use core::fmt::Debug;
+
+use rand_core::{CryptoRng, RngCore};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::error::Error;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::sign;
+#[cfg(feature = "std")]
+use crate::utils;
 
 pub trait ECScalar<SK>: From<u64> + From<SK> {
-    fn new_random() -> Self;
+    /// new_random returns a random scalar drawn from the OS random generator.
+    #[cfg(feature = "std")]
+    fn new_random() -> Self {
+        Self::new_random_with_rng(&mut utils::rand_hack())
+    }
+    /// new_random_with_rng returns a random scalar drawn from rng.
+    fn new_random_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self;
     fn zero() -> Self;
     fn get_element(&self) -> SK;
     fn set_element(&mut self, element: SK);
     fn to_hex(&self) -> String;
+    fn to_bytes(&self) -> Vec<u8>;
+    /// from_bytes reads a scalar from its byte representation, reducing it
+    /// modulo the group order.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>>;
     fn add(&self, other: &SK) -> Self;
     fn mul(&self, other: &SK) -> Self;
     fn sub(&self, other: &SK) -> Self;
@@ -26,3 +48,245 @@ where
     fn add_point(&self, other: &PK) -> Self;
     fn sub_point(&self, other: &PK) -> Self;
 }
+
+/// CurvePoint ties the point type of a curve backend to its scalar type. The
+/// protocols (poly, vss, dkg, dss) are generic over it, so that a new backend
+/// only has to implement ECScalar, ECPoint and this trait.
+pub trait CurvePoint:
+    ECPoint<<Self as CurvePoint>::PK, <Self as CurvePoint>::SK>
+    + Copy
+    + Debug
+    + Default
+    + PartialEq
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + 'static
+{
+    // Backend specific representation of scalars
+    type SK;
+    // Backend specific representation of points
+    type PK;
+    // Scalar field of the curve
+    type Scalar: ECScalar<Self::SK>
+        + Copy
+        + Debug
+        + Default
+        + PartialEq
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static;
+
+    /// CURVE_ID names the curve and group of the backend, e.g. in a
+    /// group::GroupConfig.
+    const CURVE_ID: &'static str;
+
+    /// TWO_ADICITY is the largest s such that 2^s divides the order of the
+    /// multiplicative group of the scalar field: fft::EvaluationDomain
+    /// supports sizes up to 2^TWO_ADICITY.
+    const TWO_ADICITY: u32 = 0;
+
+    /// root_of_unity returns a primitive 2^TWO_ADICITY-th root of unity of
+    /// the scalar field.
+    fn root_of_unity() -> Self::Scalar {
+        Self::Scalar::from(1)
+    }
+
+    /// identity returns the neutral element of the group.
+    fn identity() -> Self;
+
+    /// hash_to_point maps a byte string to a point whose discrete logarithm
+    /// with respect to the generator is unknown.
+    fn hash_to_point(bytes: &[u8]) -> Result<Self, Box<dyn Error>>;
+
+    /// hash_to_scalar maps a byte string to a scalar.
+    fn hash_to_scalar(bytes: &[u8]) -> Self::Scalar;
+
+    /// HASH_TO_GROUP_SUITE is the RFC 9380 suite of hash_to_group, empty for
+    /// a backend without one.
+    const HASH_TO_GROUP_SUITE: &'static str = "";
+
+    /// hash_to_group hashes msg to a point with the domain separation tag
+    /// dst, with the hash_to_curve of HASH_TO_GROUP_SUITE. The default
+    /// implementation, for the backends without a suite, passes 64 bytes of
+    /// expand_message_xmd to hash_to_point: it is deterministic and domain
+    /// separated, but specific to this library.
+    fn hash_to_group(msg: &[u8], dst: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Self::hash_to_point(&expand_message_xmd(msg, dst, 64)?)
+    }
+
+    /// base_mul returns scalar * generator. The default implementation is a
+    /// scalar_mul of the generator; backends can override it with a
+    /// multiplication by a precomputed table of the generator.
+    fn base_mul(scalar: &Self::Scalar) -> Self {
+        Self::generator().scalar_mul(&scalar.get_element())
+    }
+
+    /// multi_scalar_mul returns the sum of scalars[i] * points[i]. The default
+    /// implementation does one scalar_mul per point; backends can override it
+    /// with a variable-time multi-scalar multiplication, so it must only be
+    /// used with public scalars.
+    fn multi_scalar_mul(scalars: &[Self::Scalar], points: &[Self]) -> Self {
+        scalars
+            .iter()
+            .zip(points.iter())
+            .fold(Self::identity(), |acc, (s, p)| {
+                acc.add_point(&p.scalar_mul(&s.get_element()).get_element())
+            })
+    }
+
+    /// sign_msg signs msg under the longterm key pair (secret, public). The
+    /// default implementation is a plain Schnorr signature over the curve.
+    #[cfg(feature = "std")]
+    fn sign_msg(
+        secret: &Self::Scalar,
+        public: &Self,
+        msg: &[u8],
+        context: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(sign::schnorr_sign(secret, public, msg, context))
+    }
+
+    /// verify_signature checks a signature produced by sign_msg.
+    fn verify_signature(
+        public: &Self,
+        signature: &[u8],
+        msg: &[u8],
+        context: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        sign::schnorr_verify(public, signature, msg, context)
+    }
+
+    /// verify_signatures checks the signatures[i] of msgs[i] under publics[i]
+    /// in contexts[i], slices of the same length, and returns the positions of
+    /// the invalid signatures. The default implementation calls
+    /// verify_signature for each of them; backends can override it with a
+    /// batch verification.
+    fn verify_signatures(
+        publics: &[Self],
+        signatures: &[&[u8]],
+        msgs: &[&[u8]],
+        contexts: &[&[u8]],
+    ) -> Result<(), Vec<usize>> {
+        let failed: Vec<usize> = (0..publics.len())
+            .filter(
+                |&i| match (signatures.get(i), msgs.get(i), contexts.get(i)) {
+                    (Some(signature), Some(msg), Some(context)) => {
+                        Self::verify_signature(&publics[i], signature, msg, context).is_err()
+                    }
+                    _ => true,
+                },
+            )
+            .collect();
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(failed)
+        }
+    }
+}
+
+/// expand_message_xmd is the expand_message_xmd of RFC 9380 with SHA-512: it
+/// returns len pseudo-random bytes derived from msg and the domain separation
+/// tag dst.
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    expand_message_xmd_with::<Sha512>(msg, dst, len, 128)
+}
+
+/// expand_message_xmd_sha256 is expand_message_xmd with SHA-256.
+pub fn expand_message_xmd_sha256(
+    msg: &[u8],
+    dst: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    expand_message_xmd_with::<Sha256>(msg, dst, len, 64)
+}
+
+// expand_message_xmd_with is expand_message_xmd with the hash D, whose input
+// block is block_len bytes.
+fn expand_message_xmd_with<D: Digest>(
+    msg: &[u8],
+    dst: &[u8],
+    len: usize,
+    block_len: usize,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let ell = (len + D::output_size() - 1) / D::output_size();
+    if ell > 255 || len > 65535 {
+        bail!("curve: expand_message_xmd of {} bytes", len);
+    }
+    let oversize: Vec<u8>;
+    let dst: &[u8] = if dst.len() > 255 {
+        oversize = D::new()
+            .chain(b"H2C-OVERSIZE-DST-")
+            .chain(dst)
+            .result()
+            .to_vec();
+        &oversize
+    } else {
+        dst
+    };
+    let dst_prime: Vec<u8> = [dst, &[dst.len() as u8][..]].concat();
+    let b_0 = D::new()
+        .chain(&vec![0u8; block_len])
+        .chain(msg)
+        .chain(&(len as u16).to_be_bytes())
+        .chain(&[0u8])
+        .chain(&dst_prime)
+        .result();
+    let mut b_i = D::new()
+        .chain(&b_0)
+        .chain(&[1u8])
+        .chain(&dst_prime)
+        .result();
+    let mut uniform: Vec<u8> = b_i.to_vec();
+    for i in 2..=ell {
+        let xored: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        b_i = D::new()
+            .chain(&xored)
+            .chain(&[i as u8])
+            .chain(&dst_prime)
+            .result();
+        uniform.extend_from_slice(&b_i);
+    }
+    uniform.truncate(len);
+    Ok(uniform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // RFC 9380, K.3 expand_message_xmd(SHA-512)
+        let dst = b"QUUX-V01-CS02-with-expander-SHA512-256";
+        assert_eq!(
+            "6b9a7312411d92f921c6f68ca0b6380730a1a4d982c507211a90964c394179ba",
+            hex::encode(expand_message_xmd(b"", dst, 0x20).unwrap())
+        );
+        assert_eq!(
+            "0da749f12fbe5483eb066a5f595055679b976e93abe9be6f0f6318bce7aca8dc",
+            hex::encode(expand_message_xmd(b"abc", dst, 0x20).unwrap())
+        );
+        assert_eq!(200, expand_message_xmd(b"abc", dst, 200).unwrap().len());
+        expand_message_xmd(b"abc", dst, 256 * 64).expect_err("Must fail, too long");
+    }
+
+    #[test]
+    fn test_expand_message_xmd_sha256() {
+        // RFC 9380, K.1 expand_message_xmd(SHA-256)
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            hex::encode(expand_message_xmd_sha256(b"", dst, 0x20).unwrap())
+        );
+        assert_eq!(
+            "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            hex::encode(expand_message_xmd_sha256(b"abc", dst, 0x20).unwrap())
+        );
+        expand_message_xmd_sha256(b"abc", dst, 256 * 32).expect_err("Must fail, too long");
+    }
+}
@@ -7,18 +7,21 @@
 //! have both encrypted and unencrypted parts.
 
 use {
-    crate::{curve_traits, ristretto_curve},
+    crate::curve_traits,
+    crate::encoding::HashSuite,
     aead::{generic_array::GenericArray, NewAead},
     aes_gcm::Aes256Gcm,
-    blake2b::{blake2xb::Iter, Blake2xb},
-    curve_traits::{ECPoint, ECScalar},
+    curve_traits::{CurvePoint, ECScalar},
     hkdf::Hkdf,
-    ristretto_curve::{FE, GE},
+    rand_core::{CryptoRng, RngCore},
     sha2::Sha256,
 };
 
+/// Size in bytes of the nonce used by the AEAD scheme (AES256-GCM)
+pub const NONCE_SIZE: usize = 12;
+
 /// Compute shared private key from public input
-pub fn dh_exchange(own_priv: &FE, remote_public: &GE) -> GE {
+pub fn dh_exchange<P: CurvePoint>(own_priv: &P::Scalar, remote_public: &P) -> P {
     remote_public.scalar_mul(&own_priv.get_element())
 }
 
@@ -30,41 +33,53 @@ pub fn dh_exchange(own_priv: &FE, remote_public: &GE) -> GE {
 /// `context`: a byte string of any length that must be unique to the key produced
 ///
 /// https://eprint.iacr.org/2010/264.pdf
-pub fn new_aead(pre_shared_key: &GE, context: &[u8]) -> Aes256Gcm {
-    let input_key_material = &pre_shared_key.get_element().to_bytes();
+pub fn new_aead<P: CurvePoint>(pre_shared_key: &P, context: &[u8]) -> Aes256Gcm {
+    Aes256Gcm::new(GenericArray::clone_from_slice(&derive_key(
+        pre_shared_key,
+        context,
+    )))
+}
+
+/// derive_key returns the 256-bit key of new_aead, for any AEAD.
+pub fn derive_key<P: CurvePoint>(pre_shared_key: &P, context: &[u8]) -> [u8; 32] {
+    let input_key_material = &pre_shared_key.pk_to_key_slice();
     let h = Hkdf::<Sha256>::new(None, input_key_material);
 
     let mut shared_key = [0u8; 32];
     h.expand(context, &mut shared_key).unwrap();
 
-    let key = GenericArray::clone_from_slice(&shared_key);
-    Aes256Gcm::new(key)
+    shared_key
+}
+
+/// Generates a fresh random nonce for the AEAD scheme, drawn from rng. A new
+/// nonce must be used for every encryption, so that no (key, nonce) pair is ever
+/// reused.
+pub fn new_nonce<R: RngCore + CryptoRng>(rng: &mut R) -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    rng.fill_bytes(&mut nonce);
+    nonce
 }
 
 /// Create context byte string for new_aead from dealer and verifiers pub keys.
-pub fn context(dealer: &GE, verifiers: &[GE]) -> Vec<u8> {
-    let mut hash = Blake2xb::keyed(None, b"vss-dealer");
-    hash.update(&dealer.get_element().to_bytes());
-    hash.update(b"vss-verifiers");
+pub fn context<P: CurvePoint>(dealer: &P, verifiers: &[P]) -> Vec<u8> {
+    context_in(HashSuite::default(), dealer, verifiers)
+}
 
+/// context_in is context with the XOF of the hash suite.
+pub fn context_in<P: CurvePoint>(suite: HashSuite, dealer: &P, verifiers: &[P]) -> Vec<u8> {
+    let mut data: Vec<u8> = dealer.pk_to_key_slice();
+    data.extend_from_slice(b"vss-verifiers");
     for point in verifiers.iter() {
-        hash.update(point.get_element().as_bytes());
+        data.extend_from_slice(&point.pk_to_key_slice());
     }
-
-    let hash: Iter = hash.finish();
-
-    let mut contx: Vec<u8> = Vec::new();
-    for value in hash.take(2) {
-        contx.extend_from_slice(&value);
-    }
-
-    contx
+    suite.xof(b"vss-dealer", &data, 128)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::curve_traits;
     use crate::ristretto_curve;
+    use crate::utils;
     use aead::{generic_array::GenericArray, Aead, Payload};
     use curve_traits::{ECPoint, ECScalar};
     use ristretto_curve::{FE, GE};
@@ -104,7 +119,8 @@ mod tests {
             msg: b"super phrase".as_ref(),
             aad: context.as_ref(),
         };
-        let nonce = GenericArray::from_slice(&[0u8; 12]);
+        let nonce_buf = super::new_nonce(&mut utils::rand_hack());
+        let nonce = GenericArray::from_slice(&nonce_buf);
 
         let ciphertext = aead.encrypt(nonce, pay).expect("encryption failure!");
         let plaintext = aead
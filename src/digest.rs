@@ -0,0 +1,143 @@
+//! Digests of the deals received by the verifiers
+//!
+//! A verifier signs a DealDigest of the deal it received, with the Merkle root
+//! of its commitments, and gossips it to the other verifiers, which check it
+//! with Verifier::process_digest to catch a dealer sending different
+//! commitments to different verifiers.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::utils;
+
+use curve_traits::CurvePoint;
+use encoding::Domain;
+use serde::{Deserialize, Serialize};
+use utils::bitwise_eq;
+
+/// DealDigest is the digest of the deal received by a verifier, signed by it
+/// and gossiped to the other verifiers, see Verifier::process_digest. Two
+/// verifiers holding digests of different commitments reveal a dealer which
+/// sent different commitments to different verifiers, before the deal is
+/// certified.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DealDigest {
+    // SessionID of the deal received
+    #[serde(with = "crate::serde_hex")]
+    pub session_id: Vec<u8>,
+    // Index of the verifier issuing this digest
+    pub index: u32,
+    // Merkle root of the commitments of the deal received
+    #[serde(with = "crate::serde_hex")]
+    pub commitments_root: Vec<u8>,
+    // Signature of the verifier over the whole packet
+    #[serde(with = "crate::serde_hex")]
+    pub signature: Vec<u8>,
+}
+
+impl DealDigest {
+    /// hash_in returns the hash of a digest for a session of the given
+    /// domain, which is signed by the verifier.
+    pub fn hash_in(
+        domain: &Domain,
+        session_id: &[u8],
+        index: u32,
+        commitments_root: &[u8],
+    ) -> [u8; 32] {
+        domain
+            .encoder(b"dealdigest")
+            .put_bytes(session_id)
+            .put_u32(index)
+            .put_bytes(commitments_root)
+            .hash()
+    }
+
+    /// verify_in checks that the digest is signed by the verifier at its
+    /// index, for a session of the given domain.
+    pub fn verify_in<P: CurvePoint>(
+        &self,
+        domain: &Domain,
+        verifiers: &[P],
+    ) -> Result<(), Box<dyn Error>> {
+        let pub_k = verifiers
+            .get(self.index as usize)
+            .ok_or_else(|| simple_error!("vss: index out of bounds in digest"))?;
+        let hash =
+            DealDigest::hash_in(domain, &self.session_id, self.index, &self.commitments_root);
+        P::verify_signature(
+            pub_k,
+            &self.signature,
+            &hash,
+            &domain.signing_context(self.index),
+        )
+        .map_err(|e| simple_error!("vss: incorrect digest signature: {}", e).into())
+    }
+
+    // same_deal returns true if the digests are about the same session and
+    // commitments.
+    pub(crate) fn same_deal(&self, other: &DealDigest) -> bool {
+        bitwise_eq(&self.session_id, &other.session_id)
+            && bitwise_eq(&self.commitments_root, &other.commitments_root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve;
+    use crate::sim;
+    use crate::vss;
+    use curve_traits::ECScalar;
+    use ristretto_curve::{FE, GE};
+    use vss::{minimum_t, Dealer, EncryptedDeal, EquivocationError, Verifier};
+
+    #[test]
+    fn test_vss_deal_digest() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(5);
+        let (dealer_sec, dealer_pub): (Vec<FE>, Vec<GE>) = sim::new_participants(1);
+        let t = minimum_t(5);
+        let dealer: Dealer = Dealer::new(dealer_sec[0], FE::new_random(), pubs.clone(), t).unwrap();
+        let mut verifiers: Vec<Verifier> = secs
+            .iter()
+            .map(|s| Verifier::new(*s, dealer_pub[0], pubs.clone()).unwrap())
+            .collect();
+        // the dealer sends the deals of other commitments to the verifier 2
+        let other: Dealer = Dealer::new(dealer_sec[0], FE::new_random(), pubs.clone(), t).unwrap();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        verifiers[0]
+            .digest()
+            .expect_err("Must fail, no deal received");
+        verifiers[0].process_encrypted_deal(&enc_deals[0]).unwrap();
+        verifiers[1].process_encrypted_deal(&enc_deals[1]).unwrap();
+        verifiers[2]
+            .process_encrypted_deal(&other.encrypt_deal(2).unwrap())
+            .unwrap();
+
+        let d1: DealDigest = verifiers[1].digest().unwrap();
+        let d2: DealDigest = verifiers[2].digest().unwrap();
+        verifiers[0].process_digest(&d1).unwrap();
+        verifiers[0].process_digest(&d1).unwrap();
+        match verifiers[0].process_digest(&d2) {
+            Err(e) => match e.downcast_ref::<EquivocationError>() {
+                Some(EquivocationError::Digest(own, d)) => {
+                    assert_eq!(0, own.index);
+                    assert_eq!(&d2, d);
+                }
+                _ => panic!("not a digest equivocation"),
+            },
+            Ok(()) => panic!("Must fail, other commitments"),
+        }
+        assert_eq!(vec![2], verifiers[0].digest_conflicts());
+
+        let mut forged: DealDigest = d2.clone();
+        forged.commitments_root = d1.commitments_root.clone();
+        verifiers[0]
+            .process_digest(&forged)
+            .expect_err("Must fail, invalid signature");
+        let own: DealDigest = verifiers[0].digest().unwrap();
+        verifiers[0]
+            .process_digest(&own)
+            .expect_err("Must fail, own digest");
+    }
+}
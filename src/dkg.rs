@@ -2,40 +2,48 @@
 //! parties contribute to the calculation of a shared public and private key set.
 
 use std::collections::{HashMap, HashSet};
-use std::convert::TryInto;
 use std::error::Error;
-use std::io::Write;
+use std::sync::Arc;
 
 use crate::curve_traits;
+use crate::encoding;
+use crate::events;
+use crate::group;
 use crate::poly;
 use crate::ristretto_curve;
-use crate::sign;
 use crate::utils;
 use crate::vss;
 
-use curve_traits::{ECPoint, ECScalar};
-use ristretto_curve::{FE, GE};
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use encoding::{Domain, Encoder};
+use group::GroupConfig;
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 
 use poly::PriPoly;
 use poly::PriShare;
 use poly::PubPoly;
+use poly::PubShare;
 use utils::bitwise_eq;
+use vss::Certification;
 use vss::Deal as vssDeal;
 use vss::Dealer;
+use vss::DealerBuilder;
 use vss::EncryptedDeal;
 use vss::Justification as vssJustification;
 use vss::Response as vssResponce;
 use vss::Verifier as vssVerifier;
+use vss::{DealerRole, VerifierConfig, VerifierIdentity};
 
 /// DistKeyShare holds the share of a distributed key for a participant.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DistKeyShare {
+#[serde(bound = "")]
+pub struct DistKeyShare<P: CurvePoint = GE> {
     // Coefficients of the public polynomial holding the public key
-    commits: Vec<GE>,
+    commits: Vec<P>,
     // Share of the distributed secret
-    share: PriShare<FE>,
+    share: PriShare<P::Scalar>,
 }
 
 /// Deal holds the Deal for one participant as well as the index of the issuing
@@ -43,11 +51,12 @@ pub struct DistKeyShare {
 /// NOTE: Doing that in vss.rs would be possible but then the Dealer is always
 /// assumed to be a member of the participants. It's only the case here.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Deal {
+#[serde(bound = "")]
+pub struct Deal<P: CurvePoint = GE> {
     // Index of the Dealer in the list of participants
     pub index: u32,
     // Deal issued for another participant
-    pub deal: EncryptedDeal,
+    pub deal: EncryptedDeal<P>,
 }
 
 /// Response holds the Response from another participant as well as the index of
@@ -63,11 +72,12 @@ pub struct Response {
 /// Justification holds the Justification from a Dealer as well as the index of
 /// the Dealer in question.
 #[derive(Serialize, Deserialize, Clone)]
-pub struct Justification {
+#[serde(bound = "")]
+pub struct Justification<P: CurvePoint = GE> {
     // Index of the Dealer who answered with this Justification
     pub index: u32,
     // Justification issued from the Dealer
-    pub justification: vssJustification,
+    pub justification: vssJustification<P>,
 }
 
 /// SecretCommits is sent during the distributed public key reconstruction phase,
@@ -77,74 +87,117 @@ pub struct SecretCommits {
     // Index of the Dealer in the list of participants
     pub index: u32,
     // Commitments generated by the Dealer
+    #[serde(with = "crate::serde_hex::list")]
     pub commitments: Vec<Vec<u8>>,
     // SessionID generated by the Dealer tied to the Deal
+    #[serde(with = "crate::serde_hex")]
     pub session_id: Vec<u8>,
     // Signature from the Dealer
+    #[serde(with = "crate::serde_hex")]
     pub signature: Vec<u8>,
 }
 
 /// ComplaintCommits is sent if the secret commitments revealed by a peer are not
 /// valid.
-#[derive(Serialize, Deserialize)]
-pub struct ComplaintCommits {
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ComplaintCommits<P: CurvePoint = GE> {
     // Index of the Verifier _issuing_ the ComplaintCommit
     pub index: u32,
     // DealerIndex being the index of the Dealer who issued the SecretCommits
     pub dealer_index: u32,
     // Deal that has been given from the Dealer (at DealerIndex) to this node
     // (at Index)
-    pub deal: vssDeal,
+    pub deal: vssDeal<P>,
     // Signature made by the verifier
+    #[serde(with = "crate::serde_hex")]
     pub signature: Vec<u8>,
 }
 
 /// ReconstructCommits holds the information given by a participant who reveals
 /// the deal received from a peer that has received a ComplaintCommits.
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct ReconstructCommits {
+#[serde(bound = "")]
+pub struct ReconstructCommits<P: CurvePoint = GE> {
     // Id of the session
-    session_id: Vec<u8>,
+    #[serde(with = "crate::serde_hex")]
+    pub(crate) session_id: Vec<u8>,
     // Index of the verifier who received the deal
-    index: u32,
+    pub(crate) index: u32,
     // DealerIndex is the index of the dealer who issued the Deal
-    dealer_index: u32,
+    pub(crate) dealer_index: u32,
     // Share contained in the Deal
-    share: PriShare<FE>,
+    pub(crate) share: PriShare<P::Scalar>,
     // Signature over all over fields generated by the issuing verifier
-    signature: Vec<u8>,
+    #[serde(with = "crate::serde_hex")]
+    pub(crate) signature: Vec<u8>,
+}
+
+/// DkgConfig holds the options of a run of the DKG, which must be the same for
+/// all the participants.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgConfig {
+    /// gennaro selects the bias-resistant variant of Gennaro et al.: QUAL is
+    /// decided on the hiding Pedersen commitments of the deals only, and the
+    /// secret commits are revealed and accepted once it is fixed with
+    /// fix_qual. Otherwise a participant can reveal its secret commits as soon
+    /// as its deal is certified, which lets an adversary look at the revealed
+    /// commitments before deciding whether its own deal gets qualified, and so
+    /// bias the distributed public key.
+    pub gennaro: bool,
+    /// certification is the rule deciding which deals are certified, hence
+    /// which dealers are in QUAL. Certification::ApprovalOnly runs the
+    /// Pedersen DKG, where QUAL is made of the dealers approved by all the
+    /// participants and there is no justification.
+    pub certification: Certification,
 }
 
 /// DistKeyGenerator is the struct that runs the DKG protocol.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DistKeyGenerator {
+#[serde(bound = "")]
+pub struct DistKeyGenerator<P: CurvePoint = GE> {
     // Index of participant in the DKG protocol
     index: u32,
     // longterm secret, i.e. private key of the node
-    long: FE,
+    long: P::Scalar,
     // longterm public key
-    pub_key: GE,
-    // pub keys of all the participants
-    participants: Vec<GE>,
+    pub_key: P,
+    // pub keys of all the participants, shared with the verifiers
+    participants: Arc<[P]>,
     // threshold
     t: u32,
     // vss dealer, to distribute secret committments
-    dealer: Dealer,
+    dealer: Dealer<P>,
     // verifiers to process deals from DKG with index i
-    verifiers: HashMap<u32, vssVerifier>,
+    verifiers: HashMap<u32, vssVerifier<P>>,
     // list of commitments to each secret polynomial
-    commitments: HashMap<u32, PubPoly>,
+    commitments: HashMap<u32, PubPoly<P>>,
 
     // Map of deals collected to reconstruct the full polynomial of a dealer.
     // The key is index of the dealer. Once there are enough ReconstructCommits
     // struct, this dkg will re-construct the polynomial and stores it into the
     // list of commitments.
-    pending_reconstruct: HashMap<u32, Vec<ReconstructCommits>>,
+    pending_reconstruct: HashMap<u32, Vec<ReconstructCommits<P>>>,
     reconstructed: HashSet<u32>,
     sig_ctx: Vec<u8>,
+    // dealers excluded from QUAL by the caller
+    #[serde(default)]
+    excluded: HashSet<u32>,
+    #[serde(default)]
+    config: DkgConfig,
+    // true once QUAL can't change anymore
+    #[serde(default)]
+    qual_fixed: bool,
+    // application context of the deals, the hash of the group if created
+    // with for_group
+    #[serde(default)]
+    context: Vec<u8>,
+    // domain of the deals, the one of the group if created with for_group
+    #[serde(default)]
+    domain: Domain,
 }
 
-impl DistKeyGenerator {
+impl<P: CurvePoint> DistKeyGenerator<P> {
     /// longterm: the secret (private) key,
     /// participants: the list of participants pubkeys,
     /// t: threshold parameter
@@ -152,12 +205,88 @@ impl DistKeyGenerator {
     /// It returns an error if the secret key's commitment can't
     /// be found in the list of participants.
     pub fn new(
-        longterm: FE,
-        participants: Vec<GE>,
+        longterm: P::Scalar,
+        participants: Vec<P>,
         t: u32,
-    ) -> Result<DistKeyGenerator, Box<dyn Error>> {
-        let generator = GE::generator();
-        let pub_k: GE = generator.scalar_mul(&longterm.get_element());
+    ) -> Result<DistKeyGenerator<P>, Box<dyn Error>> {
+        DistKeyGenerator::new_with_rng(longterm, participants, t, &mut utils::rand_hack())
+    }
+
+    /// Same as new, with the secret of this participant and its sharing drawn
+    /// from rng.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        participants: Vec<P>,
+        t: u32,
+        rng: &mut R,
+    ) -> Result<DistKeyGenerator<P>, Box<dyn Error>> {
+        DistKeyGenerator::new_with_config(longterm, participants, t, DkgConfig::default(), rng)
+    }
+
+    /// Same as new_with_rng, running the DKG with the given options.
+    pub fn new_with_config<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        participants: Vec<P>,
+        t: u32,
+        config: DkgConfig,
+        rng: &mut R,
+    ) -> Result<DistKeyGenerator<P>, Box<dyn Error>> {
+        DistKeyGenerator::create(
+            longterm,
+            participants,
+            t,
+            config,
+            &[],
+            &Domain::default(),
+            rng,
+        )
+    }
+
+    /// for_group returns the DistKeyGenerator of a participant of the group,
+    /// whose deals are bound to the hash of the group: the deals of a
+    /// participant with another description of the group are rejected.
+    pub fn for_group(
+        longterm: P::Scalar,
+        group: &GroupConfig<P>,
+    ) -> Result<DistKeyGenerator<P>, Box<dyn Error>> {
+        DistKeyGenerator::for_group_with_config(
+            longterm,
+            group,
+            DkgConfig::default(),
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as for_group, running the DKG with the given options and with the
+    /// secret of this participant and its sharing drawn from rng.
+    pub fn for_group_with_config<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        group: &GroupConfig<P>,
+        config: DkgConfig,
+        rng: &mut R,
+    ) -> Result<DistKeyGenerator<P>, Box<dyn Error>> {
+        group.check()?;
+        DistKeyGenerator::create(
+            longterm,
+            group.participants.clone(),
+            group.threshold,
+            config,
+            &group.hash(),
+            &group.domain(),
+            rng,
+        )
+    }
+
+    fn create<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        participants: Vec<P>,
+        t: u32,
+        config: DkgConfig,
+        context: &[u8],
+        domain: &Domain,
+        rng: &mut R,
+    ) -> Result<DistKeyGenerator<P>, Box<dyn Error>> {
+        let pub_k: P = P::base_mul(&longterm);
 
         let index = participants
             .iter()
@@ -167,14 +296,24 @@ impl DistKeyGenerator {
             })?;
 
         // generate our dealer
-        let own_secret = FE::new_random();
-        let dealer = Dealer::new(longterm, own_secret, participants.clone(), t)?;
+        let own_secret: P::Scalar = ECScalar::new_random_with_rng(rng);
+        let mut dealer = if context.is_empty() && *domain == Domain::default() {
+            Dealer::new_with_rng(longterm, own_secret, participants.clone(), t, rng)?
+        } else {
+            DealerBuilder::new(longterm, participants.clone())
+                .threshold(t)
+                .secret(own_secret)
+                .context(context)
+                .domain(domain)
+                .build_with_rng(rng)?
+        };
+        dealer.set_certification(config.certification);
 
         Ok(DistKeyGenerator {
             index: index as u32,
             long: longterm,
             pub_key: pub_k,
-            participants,
+            participants: participants.into(),
             t,
             dealer,
             verifiers: Default::default(),
@@ -182,6 +321,11 @@ impl DistKeyGenerator {
             pending_reconstruct: Default::default(),
             reconstructed: Default::default(),
             sig_ctx: index.to_le_bytes().to_vec(),
+            excluded: Default::default(),
+            config,
+            qual_fixed: false,
+            context: context.to_vec(),
+            domain: domain.clone(),
         })
     }
 
@@ -190,12 +334,20 @@ impl DistKeyGenerator {
     /// to this DKG and is omitted from the returned map. To know
     /// to which participant a deal belongs to, loop over the keys as indices in
     /// the list of participants.
-    pub fn deals(&mut self) -> Result<HashMap<u32, Deal>, Box<dyn Error>> {
-        let deals: Vec<EncryptedDeal> = self.dealer.encrypt_deals()?;
+    pub fn deals(&mut self) -> Result<HashMap<u32, Deal<P>>, Box<dyn Error>> {
+        self.deals_with_rng(&mut utils::rand_hack())
+    }
+
+    /// Same as deals, drawing the randomness of the encryptions from rng.
+    pub fn deals_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<HashMap<u32, Deal<P>>, Box<dyn Error>> {
+        let deals: Vec<EncryptedDeal<P>> = self.dealer.encrypt_deals_with_rng(rng)?;
 
         debug_assert!(deals.len() == self.participants.len());
 
-        let mut dist_deals: HashMap<u32, Deal> = HashMap::new();
+        let mut dist_deals: HashMap<u32, Deal<P>> = HashMap::new();
         for (i, deal) in deals.into_iter().enumerate() {
             let dist_d = Deal {
                 index: self.index,
@@ -211,9 +363,9 @@ impl DistKeyGenerator {
                     bail!("dkg: own deal gave a complaint")
                 }
 
-                // If processed own deal correctly, set positive response in this
-                // DKG's dealer's own verifier
-                self.dealer.unsafe_set_response_dkg(self.index, true)?;
+                // If processed own deal correctly, this DKG's dealer approves
+                // its deal as the verifier at our index
+                self.dealer.set_dealer_index(self.index)?;
             } else {
                 dist_deals.insert(i as u32, dist_d);
             }
@@ -224,28 +376,39 @@ impl DistKeyGenerator {
 
     /// process_deal takes a Deal created by deals() and stores and verifies it. It
     /// returns a Response to broadcast to every other participants. It returns an
-    /// error in case the deal has already been stored, or if the deal is incorrect
-    pub fn process_deal(&mut self, dd: &Deal) -> Result<Response, Box<dyn Error>> {
+    /// error if the deal is incorrect. A re-delivery of a deal already processed
+    /// returns the same Response, while another deal from the same dealer gives
+    /// a vss::EquivocationError.
+    pub fn process_deal(&mut self, dd: &Deal<P>) -> Result<Response, Box<dyn Error>> {
+        let _span = events::span("dkg", &dd.deal.session_id, Some(self.index), Some(dd.index));
         // public key of the dealer
         let pub_k = self
             .participants
             .get(dd.index as usize)
             .ok_or_else(|| simple_error!("dkg: dist deal out of bounds index"))?;
 
-        if self.verifiers.contains_key(&dd.index) {
-            bail!("dkg: already received dist deal from same index");
+        if let Some(ver) = self.verifiers.get_mut(&dd.index) {
+            return Ok(Response {
+                index: dd.index,
+                response: ver.process_encrypted_deal(&dd.deal)?,
+            });
         }
 
         // verifier receiving the dealer's deal
-        let mut ver: vssVerifier = vssVerifier::new(self.long, *pub_k, self.participants.clone())?;
+        let mut identity: VerifierIdentity<P> =
+            VerifierIdentity::new_at(self.long, self.participants.clone(), self.index)?;
+        identity.set_context(&self.context);
+        identity.set_domain(&self.domain);
+        // The participant that distributed the Deal approves it implicitly. Our
+        // own deal is approved by the response below.
+        identity.set_config(&VerifierConfig {
+            certification: self.config.certification,
+            dealer_role: DealerRole::Participant,
+            ..Default::default()
+        });
+        let mut ver: vssVerifier<P> = identity.session(*pub_k)?;
         let resp: vssResponce = ver.process_encrypted_deal(&dd.deal)?;
 
-        // Set status_approval for the verifier that represents the participant
-        // that distributed the Deal
-        // We shouldn't check result here such as call unsafe bypass method
-        ver.unsafe_set_response_dkg(dd.index, true)
-            .unwrap_or_default();
-
         self.verifiers.insert(dd.index, ver);
 
         Ok(Response {
@@ -262,8 +425,14 @@ impl DistKeyGenerator {
     pub fn process_response(
         &mut self,
         resp: &Response,
-    ) -> Result<Option<Justification>, Box<dyn Error>> {
-        let v: &mut vssVerifier = self
+    ) -> Result<Option<Justification<P>>, Box<dyn Error>> {
+        let _span = events::span(
+            "dkg",
+            &resp.response.session_id,
+            Some(self.index),
+            Some(resp.index),
+        );
+        let v: &mut vssVerifier<P> = self
             .verifiers
             .get_mut(&resp.index)
             .ok_or_else(|| simple_error!("dkg: complaint received but no deal for it"))?;
@@ -290,7 +459,16 @@ impl DistKeyGenerator {
 
     /// process_justification takes a justification and validates it. It returns an
     /// error in case the justification is wrong.
-    pub fn process_justification(&mut self, j: &Justification) -> Result<(), Box<dyn Error>> {
+    pub fn process_justification(&mut self, j: &Justification<P>) -> Result<(), Box<dyn Error>> {
+        let _span = events::span(
+            "dkg",
+            &j.justification.session_id,
+            Some(self.index),
+            Some(j.index),
+        );
+        if self.qual_fixed {
+            bail!("dkg: justification received after QUAL is fixed");
+        }
         self.verifiers
             .get_mut(&j.index)
             .ok_or_else(|| simple_error!("dkg: Justification received but no deal for it"))?
@@ -305,6 +483,30 @@ impl DistKeyGenerator {
         }
     }
 
+    /// end_justifications ends the justification phase on all verifiers, see
+    /// vss::Verifier::end_justifications: a dealer which left a complaint
+    /// without a valid justification is disqualified from QUAL. It returns the
+    /// sorted indices of the dealers disqualified so far. It must be called
+    /// before fix_qual.
+    pub fn end_justifications(&mut self) -> Result<Vec<u32>, Box<dyn Error>> {
+        if self.qual_fixed {
+            bail!("dkg: QUAL already fixed");
+        }
+        let mut bad: Vec<u32> = self
+            .verifiers
+            .iter_mut()
+            .filter_map(|(&i, v)| {
+                if v.end_justifications() {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        bad.sort_unstable();
+        Ok(bad)
+    }
+
     /// certified returns true if at least t deals are certified (see
     /// vss.Verifier.deal_certified()). If the distribution is certified, the protocol
     /// can continue using d.SecretCommits().
@@ -312,25 +514,84 @@ impl DistKeyGenerator {
         self.qual().len() as u32 >= self.t
     }
 
+    /// fix_qual ends the qualification, once the justifications have been
+    /// processed: the participants which did not respond are considered as
+    /// complaining, and the justifications and exclusions are rejected from now
+    /// on. It returns the final QUAL. With the gennaro option, the secret
+    /// commits can only be exchanged after this call.
+    pub fn fix_qual(&mut self) -> Vec<u32> {
+        self.set_timeout();
+        self.qual_fixed = true;
+        self.qual()
+    }
+
+    pub fn config(&self) -> DkgConfig {
+        self.config
+    }
+
     /// qual returns the index in the list of participants that forms the qualified
     /// set as described in the "New-DKG" protocol by Rabin. Basically, it consists
     /// of all participants that are not disqualified after having exchanged all
     /// deals, responses and justification. This is the set that is used to extract
     /// the distributed public key with secret_commits() and process_secret_commits().
+    /// The indices are sorted and exclude the dealers removed with exclude().
     pub fn qual(&self) -> Vec<u32> {
-        self.verifiers
-            .iter()
-            .filter(|(_, v)| v.deal_certified())
-            .map(|(&i, _)| i)
-            .collect()
+        let mut qual: Vec<u32> = self
+            .verifiers
+            .keys()
+            .copied()
+            .filter(|&i| self.is_in_qual(i))
+            .collect();
+        qual.sort_unstable();
+        qual
     }
 
     /// Checks if verifier with idx is in qualified set
     pub fn is_in_qual(&self, idx: u32) -> bool {
-        self.verifiers
-            .get(&idx)
-            .map(vssVerifier::deal_certified)
-            .unwrap_or(false)
+        !self.excluded.contains(&idx)
+            && self
+                .verifiers
+                .get(&idx)
+                .map(vssVerifier::deal_certified)
+                .unwrap_or(false)
+    }
+
+    /// exclude removes the dealer at idx from QUAL, for instance a participant
+    /// which was too late or misbehaved. Its commitments are discarded and its
+    /// later messages are rejected. All the participants must exclude the same
+    /// dealers before the secret commits are exchanged, otherwise they end up
+    /// with different distributed keys.
+    pub fn exclude(&mut self, idx: u32) -> Result<(), Box<dyn Error>> {
+        if self.qual_fixed {
+            bail!("dkg: QUAL already fixed");
+        }
+        if idx as usize >= self.participants.len() {
+            bail!("dkg: excluded index out of bounds");
+        }
+        self.excluded.insert(idx);
+        self.commitments.remove(&idx);
+        self.pending_reconstruct.remove(&idx);
+        Ok(())
+    }
+
+    /// excluded returns the sorted indices of the dealers removed from QUAL.
+    pub fn excluded(&self) -> Vec<u32> {
+        let mut excluded: Vec<u32> = self.excluded.iter().copied().collect();
+        excluded.sort_unstable();
+        excluded
+    }
+
+    pub fn participants(&self) -> &[P] {
+        &self.participants
+    }
+
+    pub fn threshold(&self) -> u32 {
+        self.t
+    }
+
+    // longterm returns the private key of this participant.
+    pub(crate) fn longterm(&self) -> P::Scalar {
+        self.long
     }
 
     /// secret_commits returns the commitments of the coefficients of the secret
@@ -340,18 +601,16 @@ impl DistKeyGenerator {
     /// This dkg must have its deal certified, otherwise it returns an error. The
     /// secret_commits returned is already added to this dkg's list of secret_commits.
     pub fn secret_commits(&mut self) -> Result<SecretCommits, Box<dyn Error>> {
+        if self.config.gennaro && !self.qual_fixed {
+            bail!("dkg: can't give SecretCommits before QUAL is fixed");
+        }
         if !self.dealer.deal_certified() {
             bail!("dkg: can't give SecretCommits if deal not certified");
         }
-        let commits: Vec<Vec<u8>> = self.dealer.commits().unwrap();
+        let commits: Vec<Vec<u8>> = self.dealer.commits()?;
         let session_id: Vec<u8> = self.dealer.get_session_id().to_vec();
         let msg: [u8; 32] = SecretCommits::hash(&commits, self.index)?;
-        let signature = sign::sign_msg(
-            self.long.get_element().to_bytes(),
-            self.pub_key.get_element().to_bytes(),
-            &msg,
-            &self.index.to_le_bytes(),
-        )?;
+        let signature = P::sign_msg(&self.long, &self.pub_key, &msg, &self.index.to_le_bytes())?;
         let sc = SecretCommits {
             index: self.index,
             commitments: commits.clone(),
@@ -361,9 +620,8 @@ impl DistKeyGenerator {
         // adding our own commitments
         let commits_p = commits
             .iter()
-            .map(|c| GE::from_bytes(&c))
-            .collect::<Result<Vec<_>, _>>()
-            .unwrap();
+            .map(|c| P::from_bytes(&c))
+            .collect::<Result<Vec<_>, _>>()?;
         self.commitments.insert(
             self.index,
             poly::PubPoly::new(ECPoint::generator(), commits_p),
@@ -379,52 +637,49 @@ impl DistKeyGenerator {
     pub fn process_secret_commit(
         &mut self,
         sc: &SecretCommits,
-    ) -> Result<Option<ComplaintCommits>, Box<dyn Error>> {
-        let pub_k: &GE = self
+    ) -> Result<Option<ComplaintCommits<P>>, Box<dyn Error>> {
+        let pub_k: &P = self
             .participants
             .get(sc.index as usize)
             .ok_or_else(|| simple_error!("dkg: secretcommits received with index out of bounds"))?;
 
+        if self.config.gennaro && !self.qual_fixed {
+            bail!("dkg: secretcommits received before QUAL is fixed");
+        }
+
         if !self.is_in_qual(sc.index) {
             bail!("dkg: secretcommits from a non QUAL member");
         }
 
-        // mapping verified by is_in_qual
-        let v: &mut vssVerifier = self.verifiers.get_mut(&sc.index).unwrap();
+        let v: &mut vssVerifier<P> = self
+            .verifiers
+            .get_mut(&sc.index)
+            .ok_or_else(|| simple_error!("dkg: secretcommits from a dealer without deal"))?;
 
         if !bitwise_eq(&v.session_id(), &sc.session_id) {
             bail!("dkg: secretcommits received with wrong session id");
         }
 
         let msg: [u8; 32] = SecretCommits::hash(&sc.commitments, sc.index)?;
-        sign::verify_signature(
-            &pub_k.get_element().to_bytes(),
-            &sc.signature,
-            &msg,
-            &sc.index.to_le_bytes(),
-        )
-        .map_err(|e| simple_error!("dkg: invalid signature in SecretCommit: {}", e))?;
+        P::verify_signature(pub_k, &sc.signature, &msg, &sc.index.to_le_bytes())
+            .map_err(|e| simple_error!("dkg: invalid signature in SecretCommit: {}", e))?;
 
-        let deal: vssDeal = v.get_deal().unwrap();
-        let commitments: Vec<GE> = sc
+        let deal: vssDeal<P> = v.get_deal()?;
+        let commitments: Vec<P> = sc
             .commitments
             .iter()
-            .map(|x| ECPoint::from_bytes(x))
+            .map(|x| P::from_bytes(x))
             .collect::<Result<Vec<_>, _>>()
             .map_err(|x| simple_error!("Error while constructing point from bytes: {}", x))?;
 
-        let polynomial: PubPoly = PubPoly::new(ECPoint::generator(), commitments);
+        let polynomial: PubPoly<P> = PubPoly::new(ECPoint::generator(), commitments);
         if polynomial.check(&deal.sec_share) {
             self.commitments.insert(sc.index, polynomial);
             Ok(None)
         } else {
             let msg: [u8; 32] = ComplaintCommits::hash(self.index, sc.index, &deal)?;
-            let signature = sign::sign_msg(
-                self.long.get_element().to_bytes(),
-                self.pub_key.get_element().to_bytes(),
-                &msg,
-                &self.index.to_le_bytes(),
-            )?;
+            let signature =
+                P::sign_msg(&self.long, &self.pub_key, &msg, &self.index.to_le_bytes())?;
             Ok(Some(ComplaintCommits {
                 index: self.index,
                 dealer_index: sc.index,
@@ -440,9 +695,9 @@ impl DistKeyGenerator {
     /// in QUAL so the polynomial in question can be reconstructed.
     pub fn process_complaints_commits(
         &mut self,
-        complaint_commits: &ComplaintCommits,
-    ) -> Result<ReconstructCommits, Box<dyn Error>> {
-        let issuer: &GE = self
+        complaint_commits: &ComplaintCommits<P>,
+    ) -> Result<ReconstructCommits<P>, Box<dyn Error>> {
+        let issuer: &P = self
             .participants
             .get(complaint_commits.index as usize)
             .ok_or_else(|| simple_error!("dkg: commitcomplaint with unknown issuer"))?;
@@ -456,8 +711,8 @@ impl DistKeyGenerator {
             complaint_commits.dealer_index,
             &complaint_commits.deal,
         )?;
-        sign::verify_signature(
-            &issuer.get_element().to_bytes(),
+        P::verify_signature(
+            issuer,
             &complaint_commits.signature,
             &msg,
             &complaint_commits.index.to_le_bytes(),
@@ -471,10 +726,14 @@ impl DistKeyGenerator {
 
         // the verification should pass for the deal, and not with the secret
         // commits. Verification 4) in DKG Rabin's paper.
-        complaint_commits
-            .deal
-            .verify(verifier.verifiers(), verifier.session_id())
-            .map_err(|e| simple_error!("dkg: verifying deal: {:?}", e))?;
+        let deal = &complaint_commits.deal;
+        deal.verify_in(
+            &self.domain,
+            verifier.verifiers(),
+            verifier.session_id(),
+            &P::Scalar::from(deal.sec_share.i as u64 + 1),
+        )
+        .map_err(|e| simple_error!("dkg: verifying deal: {:?}", e))?;
 
         let secret_commit = self
             .commitments
@@ -489,14 +748,12 @@ impl DistKeyGenerator {
 
         self.commitments.remove(&complaint_commits.dealer_index);
 
-        let msg: [u8; 32] =
-            ReconstructCommits::hash(self.index, complaint_commits.dealer_index, &deal.sec_share)?;
-        let signature = sign::sign_msg(
-            self.long.get_element().to_bytes(),
-            self.pub_key.get_element().to_bytes(),
-            &msg,
-            &self.index.to_le_bytes(),
+        let msg: [u8; 32] = ReconstructCommits::<P>::hash(
+            self.index,
+            complaint_commits.dealer_index,
+            &deal.sec_share,
         )?;
+        let signature = P::sign_msg(&self.long, &self.pub_key, &msg, &self.index.to_le_bytes())?;
 
         let rc = ReconstructCommits {
             session_id: complaint_commits.deal.session_id.clone(),
@@ -520,12 +777,16 @@ impl DistKeyGenerator {
     /// polynomial is recovered.
     pub fn process_reconstruct_commits(
         &mut self,
-        rs: &ReconstructCommits,
+        rs: &ReconstructCommits<P>,
     ) -> Result<(), Box<dyn Error>> {
         if self.reconstructed.contains(&rs.dealer_index) {
             return Ok(());
         }
 
+        if self.excluded.contains(&rs.dealer_index) {
+            bail!("dkg: reconstruct commits for an excluded dealer");
+        }
+
         if self.commitments.get(&rs.dealer_index).is_some() {
             bail!("dkg: commitments not invalidated by any complaints")
         }
@@ -535,9 +796,9 @@ impl DistKeyGenerator {
             .get(rs.index as usize)
             .ok_or_else(|| simple_error!("dkg: reconstruct commits with invalid verifier index"))?;
 
-        let msg: [u8; 32] = ReconstructCommits::hash(rs.index, rs.dealer_index, &rs.share)?;
-        sign::verify_signature(
-            pub_k.get_element().to_bytes().as_ref(),
+        let msg: [u8; 32] = ReconstructCommits::<P>::hash(rs.index, rs.dealer_index, &rs.share)?;
+        P::verify_signature(
+            pub_k,
             rs.signature.as_ref(),
             msg.as_ref(),
             rs.index.to_le_bytes().as_ref(),
@@ -559,14 +820,14 @@ impl DistKeyGenerator {
 
         // check if we can reconstruct commitments
         if rec_comms.len() as u32 >= self.t {
-            let mut shares: Vec<PriShare<FE>> = Vec::new();
+            let mut shares: Vec<PriShare<P::Scalar>> = Vec::new();
             for el in rec_comms.iter() {
                 shares.push(el.share.clone());
             }
             // error only happens when you have less than t shares, but we ensure
             // there are more just before
-            let pri_poly: PriPoly = poly::recover_pri_poly(&mut shares, self.t as u32)?;
-            let commit: PubPoly = pri_poly.commit(None);
+            let pri_poly: PriPoly<P> = poly::recover_pri_poly(&mut shares, self.t as u32)?;
+            let commit: PubPoly<P> = pri_poly.commit(None);
             self.commitments.insert(rs.dealer_index, commit);
             // note it has been reconstructed.
             self.reconstructed.insert(rs.dealer_index);
@@ -580,18 +841,10 @@ impl DistKeyGenerator {
     /// all necessary information to generate the DistKeyShare() by itself. It
     /// returns false otherwise.
     pub fn finished(&self) -> bool {
-        let mut nb: u32 = 0;
-        for (i, v) in self.verifiers.iter() {
-            if v.deal_certified() {
-                nb += 1;
-                // ALL QUAL members should have their commitments by now either given or
-                // reconstructed.
-                if self.commitments.get(i).is_none() {
-                    return false;
-                }
-            }
-        }
-        nb >= self.t
+        let qual: Vec<u32> = self.qual();
+        // ALL QUAL members should have their commitments by now either given or
+        // reconstructed.
+        qual.len() as u32 >= self.t && qual.iter().all(|i| self.commitments.contains_key(i))
     }
 
     /// dist_key_share generates the distributed key relative to this receiver
@@ -601,33 +854,32 @@ impl DistKeyGenerator {
     /// of all aggregated individual public commits of each individual secrets.
     /// the share is evaluated from the global Private Polynomial, basically SUM of
     /// fj(i) for a receiver i.
-    pub fn dist_key_share(&self) -> Result<DistKeyShare, Box<dyn Error>> {
+    pub fn dist_key_share(&self) -> Result<DistKeyShare<P>, Box<dyn Error>> {
         if !self.certified() {
             bail!("dkg: distributed key not certified")
         }
 
-        let mut sh: FE = ECScalar::zero();
-        let mut pub_poly: Option<PubPoly> = None;
-        for (i, v) in self.verifiers.iter() {
-            if v.deal_certified() {
-                // share of dist. secret = sum of all share received.
-                let s: FE = v.get_deal().unwrap().sec_share.v;
-                sh = sh.add(&s.get_element());
-                // Dist. public key = sum of all revealed commitments
-                let poly = self.commitments.get(&i).ok_or_else(|| {
-                    simple_error!("dkg: protocol not finished: commitments from {} missing", i)
-                })?;
-                if pub_poly.is_none() {
-                    // first polynomial we see (instead of generating n empty commits)
-                    pub_poly.replace(poly.clone());
-                    continue;
-                }
-                let sum_poly: PubPoly = pub_poly.unwrap().add(poly)?;
-                pub_poly = Some(sum_poly);
+        let mut sh: P::Scalar = ECScalar::zero();
+        let mut pub_poly: Option<PubPoly<P>> = None;
+        // only the contributions of QUAL make up the distributed key
+        for i in self.qual() {
+            // share of dist. secret = sum of all share received.
+            let s: P::Scalar = self.verifiers[&i].get_deal()?.sec_share.v;
+            sh = sh.add(&s.get_element());
+            // Dist. public key = sum of all revealed commitments
+            let poly = self.commitments.get(&i).ok_or_else(|| {
+                simple_error!("dkg: protocol not finished: commitments from {} missing", i)
+            })?;
+            if pub_poly.is_none() {
+                // first polynomial we see (instead of generating n empty commits)
+                pub_poly.replace(poly.clone());
+                continue;
             }
+            let sum_poly: PubPoly<P> = pub_poly.unwrap().add(poly)?;
+            pub_poly = Some(sum_poly);
         }
 
-        let (_, commits): (_, Vec<GE>) = pub_poly.unwrap().info();
+        let (_, commits): (_, Vec<P>) = pub_poly.unwrap().info();
 
         Ok(DistKeyShare {
             commits,
@@ -643,66 +895,75 @@ impl DistKeyGenerator {
     }
 }
 
-impl DistKeyShare {
+impl<P: CurvePoint> DistKeyShare<P> {
+    pub(crate) fn new(commits: Vec<P>, share: PriShare<P::Scalar>) -> DistKeyShare<P> {
+        DistKeyShare { commits, share }
+    }
+
     // get_public_key returns the public key associated with the distributed private key.
-    pub fn get_public_key(&self) -> GE {
+    pub fn get_public_key(&self) -> P {
         self.commits[0]
     }
 
-    pub fn get_pri_share(&self) -> PriShare<FE> {
+    pub fn get_pri_share(&self) -> PriShare<P::Scalar> {
         self.share.clone()
     }
 
-    pub fn get_commitments(&self) -> &[GE] {
+    pub fn get_commitments(&self) -> &[P] {
         &self.commits
     }
+
+    /// pub_poly returns the public polynomial of the distributed key, whose
+    /// constant term is the public key.
+    pub fn pub_poly(&self) -> PubPoly<P> {
+        PubPoly::new(P::generator(), self.commits.clone())
+    }
+
+    /// pub_share returns the public share of the participant at index i, i.e.
+    /// the public key of its share of the distributed key.
+    pub fn pub_share(&self, i: u32) -> PubShare<P> {
+        self.pub_poly().eval(i)
+    }
 }
 
 impl SecretCommits {
     /// hash returns the hash value of data used in the signature process.
     pub fn hash(commitments: &[Vec<u8>], index: u32) -> Result<[u8; 32], Box<dyn Error>> {
-        let mut hasher = Sha256::new();
-        hasher.write_all(b"secretcommits".as_ref()).unwrap();
-        hasher.write_all(&index.to_le_bytes()).unwrap();
-        for comm in commitments.iter() {
-            hasher.write_all(comm.as_ref()).unwrap();
-        }
-        let result = hasher.result().as_slice().try_into()?;
-        Ok(result)
+        Ok(Encoder::new(b"secretcommits")
+            .put_u32(index)
+            .put_list(commitments)
+            .hash())
     }
 }
 
-impl ComplaintCommits {
+impl<P: CurvePoint> ComplaintCommits<P> {
     /// hash returns the hash value of this struct used in the signature process.
-    pub fn hash(index: u32, dealer_index: u32, deal: &vssDeal) -> Result<[u8; 32], Box<dyn Error>> {
-        let deal_buff: Vec<u8> = bincode::serialize(deal)?;
-
-        let mut hasher = Sha256::new();
-        hasher.write_all(b"commitcomplaint".as_ref())?;
-        hasher.write_all(&index.to_le_bytes())?;
-        hasher.write_all(&dealer_index.to_le_bytes())?;
-        hasher.write_all(deal_buff.as_ref())?;
-
-        let result = hasher.result().as_slice().try_into()?;
-        Ok(result)
+    pub fn hash(
+        index: u32,
+        dealer_index: u32,
+        deal: &vssDeal<P>,
+    ) -> Result<[u8; 32], Box<dyn Error>> {
+        Ok(Encoder::new(b"commitcomplaint")
+            .put_u32(index)
+            .put_u32(dealer_index)
+            .put_bytes(&deal.encode())
+            .hash())
     }
 }
 
-impl ReconstructCommits {
+impl<P: CurvePoint> ReconstructCommits<P> {
     /// hash returns the hash value of this struct used in the signature process.
     pub fn hash(
         index: u32,
         dealer_index: u32,
-        sec_share: &PriShare<FE>,
+        sec_share: &PriShare<P::Scalar>,
     ) -> Result<[u8; 32], Box<dyn Error>> {
-        let mut hasher = Sha256::new();
-        hasher.write_all(b"reconstructcommits".as_ref())?;
-        hasher.write_all(&index.to_le_bytes())?;
-        hasher.write_all(&dealer_index.to_le_bytes())?;
-        hasher.write_all(sec_share.hash().as_ref())?;
-
-        let result = hasher.result().as_slice().try_into()?;
-        Ok(result)
+        Ok(Encoder::new(b"reconstructcommits")
+            .put_u32(index)
+            .put_u32(dealer_index)
+            .put_u32(sec_share.i)
+            .put_bytes(&sec_share.v.to_bytes())
+            .hash())
     }
 }
 
@@ -712,6 +973,7 @@ mod tests {
     use crate::curve_traits;
     use crate::poly;
     use crate::ristretto_curve;
+    use crate::sign;
     use curve_traits::{ECPoint, ECScalar};
     use ristretto_curve::{FE, GE};
     use schnorrkel::context::signing_context;
@@ -801,6 +1063,16 @@ mod tests {
         signature.to_bytes().to_vec()
     }
 
+    #[test]
+    fn test_dkg_secret_commits_hash_vector() {
+        // golden vector of the canonical encoding
+        let hash = SecretCommits::hash(&[vec![1, 2], vec![3]], 4).unwrap();
+        assert_eq!(
+            "8fe591750ce41e11058493c93cafb7167c639605a032e3012be6e4133596b80a",
+            hex::encode(hash)
+        );
+    }
+
     #[test]
     fn test_dkg_new_dist_key_generator() {
         let participants_count: u32 = 7;
@@ -856,8 +1128,8 @@ mod tests {
         assert_eq!(1, rec.index);
 
         // verifier don't find itself
-        let correct_p: Vec<GE> = rec.participants.clone();
-        rec.participants = Vec::new();
+        let correct_p: Arc<[GE]> = rec.participants.clone();
+        rec.participants = Vec::new().into();
         let resp = rec.process_deal(&deal).is_err();
 
         assert!(resp);
@@ -875,9 +1147,9 @@ mod tests {
         assert_eq!(0, resp.index);
 
         // duplicate
-        let resp = rec.process_deal(&deal);
+        let dup = rec.process_deal(&deal).unwrap();
 
-        assert!(resp.is_err());
+        assert_eq!(resp.response, dup.response);
 
         // wrong index
         let correct_idx: u32 = deal.index;
@@ -978,6 +1250,28 @@ mod tests {
         assert!(err.is_err());
     }
 
+    #[test]
+    fn test_dkg_end_justifications() {
+        let init_data = setup(5);
+        let mut dkgs: Vec<DistKeyGenerator> = init_data.dkgs;
+
+        // dealer 1 sends a wrong share to participant 2 and never justifies it
+        dkgs[1].dealer.deals[2].sec_share.v = ECScalar::zero();
+        let deals = dkgs[1].deals().unwrap();
+        let complaint: Response = dkgs[2].process_deal(deals.get(&2).unwrap()).unwrap();
+        assert!(!complaint.response.approved);
+
+        let dkg: &mut DistKeyGenerator = &mut dkgs[0];
+        dkg.process_deal(deals.get(&0).unwrap()).unwrap();
+        dkg.process_response(&complaint).unwrap();
+        assert_eq!(vec![1], dkg.end_justifications().unwrap());
+        assert!(!dkg.is_in_qual(1));
+
+        dkg.fix_qual();
+        dkg.end_justifications()
+            .expect_err("Must fail, QUAL already fixed");
+    }
+
     #[test]
     fn test_dkg_secret_commits() {
         let participants_count: u32 = 7;
@@ -1187,7 +1481,7 @@ mod tests {
         // peer 1 wants to reconstruct coeffs from dealer 1
         let mut temp_dkg: DistKeyGenerator = dkgs.get(1 as usize).unwrap().clone();
         let share: vssDeal = temp_dkg.verifiers.get_mut(&0).unwrap().get_deal().unwrap();
-        let msg = ReconstructCommits::hash(1, 0, &share.sec_share).unwrap();
+        let msg = ReconstructCommits::<GE>::hash(1, 0, &share.sec_share).unwrap();
         let signature = sign::sign_msg(
             temp_dkg.long.get_element().to_bytes(),
             temp_dkg.pub_key.get_element().to_bytes(),
@@ -1195,7 +1489,7 @@ mod tests {
             &temp_dkg.index.to_le_bytes(),
         )
         .unwrap();
-        let mut rc = ReconstructCommits {
+        let mut rc: ReconstructCommits = ReconstructCommits {
             session_id: share.session_id.clone(),
             index: 1,
             dealer_index: 0,
@@ -1263,7 +1557,7 @@ mod tests {
         for ind in 2..dkgs.len() {
             let dkg: &mut DistKeyGenerator = dkgs.get_mut(ind as usize).unwrap();
             let share: vssDeal = dkg.verifiers.get_mut(&0).unwrap().get_deal().unwrap();
-            let msg = ReconstructCommits::hash(dkg.index, 0, &share.sec_share).unwrap();
+            let msg = ReconstructCommits::<GE>::hash(dkg.index, 0, &share.sec_share).unwrap();
             let signature = sign::sign_msg(
                 dkg.long.get_element().to_bytes(),
                 dkg.pub_key.get_element().to_bytes(),
@@ -1271,7 +1565,7 @@ mod tests {
                 &dkg.index.to_le_bytes(),
             )
             .unwrap();
-            let mut rc = ReconstructCommits {
+            let mut rc: ReconstructCommits = ReconstructCommits {
                 session_id: share.session_id.clone(),
                 index: dkg.index,
                 dealer_index: 0,
@@ -1354,6 +1648,167 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dkg_gennaro() {
+        let n: u32 = 5;
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = (0..n).map(|_| gen_pair()).unzip();
+        let config = DkgConfig {
+            gennaro: true,
+            ..Default::default()
+        };
+        let mut dkgs: Vec<DistKeyGenerator> = secs
+            .iter()
+            .map(|sec| {
+                DistKeyGenerator::new_with_config(
+                    *sec,
+                    pubs.clone(),
+                    n / 2 + 1,
+                    config,
+                    &mut utils::rand_hack(),
+                )
+                .unwrap()
+            })
+            .collect();
+        full_exchange(&mut dkgs, n);
+
+        // no secret commits while QUAL may still change
+        dkgs[0]
+            .secret_commits()
+            .expect_err("Must fail, QUAL not fixed");
+        for dkg in dkgs.iter_mut() {
+            assert_eq!((0..n).collect::<Vec<u32>>(), dkg.fix_qual());
+        }
+        dkgs[0].exclude(1).expect_err("Must fail, QUAL fixed");
+
+        let scs: Vec<SecretCommits> = dkgs
+            .iter_mut()
+            .map(|d| d.secret_commits().unwrap())
+            .collect();
+        for (i, dkg) in dkgs.iter_mut().enumerate() {
+            for (j, sc) in scs.iter().enumerate() {
+                if i != j {
+                    assert!(dkg.process_secret_commit(sc).unwrap().is_none());
+                }
+            }
+            assert!(dkg.finished());
+        }
+        let public: GE = dkgs[0].dist_key_share().unwrap().get_public_key();
+        for dkg in dkgs.iter() {
+            assert_eq!(public, dkg.dist_key_share().unwrap().get_public_key());
+        }
+    }
+
+    #[test]
+    fn test_dkg_approval_only() {
+        let n: u32 = 5;
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = (0..n).map(|_| gen_pair()).unzip();
+        let config = DkgConfig {
+            certification: Certification::ApprovalOnly,
+            ..Default::default()
+        };
+        let mut dkgs: Vec<DistKeyGenerator> = secs
+            .iter()
+            .map(|sec| {
+                DistKeyGenerator::new_with_config(
+                    *sec,
+                    pubs.clone(),
+                    n / 2 + 1,
+                    config,
+                    &mut utils::rand_hack(),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        // the deal of dealer 0 for participant 1 is wrong
+        dkgs[0].dealer.deals[1].rnd_share.v = ECScalar::zero();
+        let mut resps: Vec<Response> = Vec::new();
+        for i in 0..n as usize {
+            let deals = dkgs[i].deals().unwrap();
+            for (&j, deal) in deals.iter() {
+                resps.push(dkgs[j as usize].process_deal(deal).unwrap());
+            }
+        }
+        for r in resps.iter() {
+            for dkg in dkgs.iter_mut() {
+                if r.response.index != dkg.index {
+                    // no justification for the complaint
+                    assert!(dkg.process_response(r).unwrap().is_none());
+                }
+            }
+        }
+
+        // the dealer with a complaint is disqualified without a justification round
+        for dkg in dkgs.iter() {
+            assert_eq!((1..n).collect::<Vec<u32>>(), dkg.qual());
+        }
+        dkgs[0]
+            .secret_commits()
+            .expect_err("Must fail, deal not certified");
+        let scs: Vec<SecretCommits> = dkgs[1..]
+            .iter_mut()
+            .map(|d| d.secret_commits().unwrap())
+            .collect();
+        for dkg in dkgs.iter_mut() {
+            for sc in scs.iter().filter(|sc| sc.index != dkg.index) {
+                assert!(dkg.process_secret_commit(sc).unwrap().is_none());
+            }
+            assert!(dkg.finished());
+        }
+        let public: GE = dkgs[0].dist_key_share().unwrap().get_public_key();
+        for dkg in dkgs.iter() {
+            assert_eq!(public, dkg.dist_key_share().unwrap().get_public_key());
+        }
+    }
+
+    #[test]
+    fn test_dkg_exclude() {
+        let participants_count: u32 = 5;
+        let init_data = setup(participants_count);
+        let mut dkgs: Vec<DistKeyGenerator> = init_data.dkgs;
+        full_exchange(&mut dkgs, participants_count);
+
+        // everyone excludes the last dealer, e.g. for being too late
+        let excluded: u32 = participants_count - 1;
+        for dkg in dkgs.iter_mut() {
+            dkg.exclude(excluded).unwrap();
+            assert_eq!(vec![excluded], dkg.excluded());
+            assert_eq!((0..excluded).collect::<Vec<u32>>(), dkg.qual());
+        }
+        dkgs[0]
+            .exclude(participants_count)
+            .expect_err("Must fail, index out of bounds");
+
+        let scs: Vec<SecretCommits> = dkgs
+            .iter_mut()
+            .map(|d| d.secret_commits().unwrap())
+            .collect();
+        for (i, dkg) in dkgs.iter_mut().enumerate() {
+            for (j, sc) in scs.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                if j as u32 == excluded {
+                    dkg.process_secret_commit(sc)
+                        .expect_err("Must fail, dealer excluded");
+                } else {
+                    assert!(dkg.process_secret_commit(sc).unwrap().is_none());
+                }
+            }
+            assert!(dkg.finished());
+        }
+
+        // the distributed key only holds the contributions of QUAL
+        let expected: GE = scs
+            .iter()
+            .take(excluded as usize)
+            .map(|sc| GE::from_bytes(&sc.commitments[0]).unwrap())
+            .fold(GE::identity(), |acc, p| acc.add_point(&p.get_element()));
+        for dkg in dkgs.iter() {
+            assert_eq!(expected, dkg.dist_key_share().unwrap().get_public_key());
+        }
+    }
+
     #[test]
     fn test_dkg_dist_key_share() {
         let participants_count: u32 = 7;
@@ -1432,6 +1887,18 @@ mod tests {
             shares.push(dks.share.clone());
         }
 
+        // every participant computes the public share of every other one
+        for dks in dkss.iter() {
+            for other in dkss.iter() {
+                let pub_share = dks.pub_share(other.share.i);
+                assert_eq!(other.share.i, pub_share.i);
+                assert_eq!(
+                    GE::generator().scalar_mul(&other.share.v.get_element()),
+                    pub_share.v
+                );
+            }
+        }
+
         let secret = poly::recover_secret(&shares.as_slice(), participants_count / 2 + 1).unwrap();
 
         let generator = GE::generator();
@@ -0,0 +1,106 @@
+//! Non-interactive proofs of equality of discrete logarithms.
+//!
+//! A Chaum-Pedersen proof shows that xG = x * g and xH = x * h for the same
+//! secret x, without revealing x. The challenge is derived with the
+//! Fiat-Shamir heuristic from the canonical encoding of the points.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::ristretto_curve;
+use crate::utils;
+
+use curve_traits::{CurvePoint, ECScalar};
+use encoding::Encoder;
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+
+/// DleqProof proves that two points have the same discrete logarithm with
+/// respect to two bases.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct DleqProof<P: CurvePoint = GE> {
+    // challenge
+    pub c: P::Scalar,
+    // response
+    pub r: P::Scalar,
+}
+
+fn challenge<P: CurvePoint>(g: &P, h: &P, xg: &P, xh: &P, vg: &P, vh: &P) -> P::Scalar {
+    let points: Vec<Vec<u8>> = [g, h, xg, xh, vg, vh]
+        .iter()
+        .map(|p| p.pk_to_key_slice())
+        .collect();
+    P::hash_to_scalar(&Encoder::new(b"dleq").put_list(&points).into_bytes())
+}
+
+impl<P: CurvePoint> DleqProof<P> {
+    /// new returns the proof that x * g and x * h, also returned, have the same
+    /// discrete logarithm x.
+    pub fn new(g: &P, h: &P, x: &P::Scalar) -> (DleqProof<P>, P, P) {
+        DleqProof::new_with_rng(g, h, x, &mut utils::rand_hack())
+    }
+
+    /// Same as new, with the commitment of the proof drawn from rng.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        g: &P,
+        h: &P,
+        x: &P::Scalar,
+        rng: &mut R,
+    ) -> (DleqProof<P>, P, P) {
+        let xg: P = g.scalar_mul(&x.get_element());
+        let xh: P = h.scalar_mul(&x.get_element());
+
+        let v: P::Scalar = ECScalar::new_random_with_rng(rng);
+        let vg: P = g.scalar_mul(&v.get_element());
+        let vh: P = h.scalar_mul(&v.get_element());
+
+        // r = v - c * x
+        let c: P::Scalar = challenge(g, h, &xg, &xh, &vg, &vh);
+        let r: P::Scalar = v.sub(&c.mul(&x.get_element()).get_element());
+        (DleqProof { c, r }, xg, xh)
+    }
+
+    /// verify checks that xg and xh have the same discrete logarithm with
+    /// respect to g and h.
+    pub fn verify(&self, g: &P, h: &P, xg: &P, xh: &P) -> Result<(), Box<dyn Error>> {
+        // v * g = r * g + c * xG, and likewise with h
+        let vg: P = g
+            .scalar_mul(&self.r.get_element())
+            .add_point(&xg.scalar_mul(&self.c.get_element()).get_element());
+        let vh: P = h
+            .scalar_mul(&self.r.get_element())
+            .add_point(&xh.scalar_mul(&self.c.get_element()).get_element());
+        if challenge(g, h, xg, xh, &vg, &vh) != self.c {
+            bail!("dleq: invalid proof");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_traits::ECPoint;
+    use crate::ristretto_curve::FE;
+
+    #[test]
+    fn test_dleq_proof() {
+        let g: GE = GE::generator();
+        let h: GE = GE::hash_to_point(b"h").unwrap();
+        let x: FE = ECScalar::new_random();
+        let (proof, xg, xh) = DleqProof::new(&g, &h, &x);
+        proof.verify(&g, &h, &xg, &xh).unwrap();
+
+        let y: FE = ECScalar::new_random();
+        let yh: GE = h.scalar_mul(&y.get_element());
+        proof
+            .verify(&g, &h, &xg, &yh)
+            .expect_err("Must fail, different discrete logarithms");
+        proof
+            .verify(&h, &g, &xh, &xg)
+            .expect_err("Must fail, bases swapped");
+    }
+}
@@ -0,0 +1,422 @@
+//! drand group and share files
+//!
+//! A committee whose key is generated by the DKG over G1Point can run drand,
+//! and a drand committee can be taken over by this crate: Group reads and
+//! writes the group.toml file of drand, listing the identities of the nodes and
+//! the distributed public key, and share_to_toml / share_from_toml the private
+//! share file of a node. The index of a node in the group file is the index of
+//! its longterm key among the participants of the DKG, and the index of its
+//! share. Points are hex encoded in compressed form and scalars as big endian
+//! integers, as with the kyber bls12381 suite. Enabled with the `drand`
+//! feature.
+
+use std::error::Error;
+
+use crate::bls12_381_curve;
+use crate::curve_traits;
+use crate::dkg;
+use crate::poly;
+
+use bls12_381_curve::{scalar_from_be_bytes, scalar_to_be_bytes, G1Point, FE};
+use curve_traits::{ECPoint, ECScalar};
+use dkg::DistKeyShare;
+use poly::PriShare;
+use serde::{Deserialize, Serialize};
+
+/// Scheme of the chained beacon of the beacon module
+pub const DEFAULT_SCHEME: &str = "pedersen-bls-chained";
+
+/// Node is the identity of a drand node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    // Index of the node, i.e. of its longterm key among the participants
+    pub index: u32,
+    // Address of the node, host:port
+    pub address: String,
+    // Longterm public key of the node
+    pub key: G1Point,
+    pub tls: bool,
+    // Signature of the node over its identity, empty if none
+    pub signature: Vec<u8>,
+}
+
+/// Group is the configuration of a drand committee.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    pub threshold: u32,
+    // Period of the beacon, as a Go duration, e.g. "30s"
+    pub period: String,
+    pub catchup_period: String,
+    // Nodes of the group, sorted by index
+    pub nodes: Vec<Node>,
+    // Unix time of the first round
+    pub genesis_time: i64,
+    pub transition_time: i64,
+    // Seed of the chain, empty if not computed yet
+    pub genesis_seed: Vec<u8>,
+    // Coefficients of the public polynomial of the distributed key, empty
+    // before the DKG
+    pub public_key: Vec<G1Point>,
+    pub scheme_id: String,
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NodeToml {
+    #[serde(rename = "Address")]
+    address: String,
+    #[serde(rename = "Key")]
+    key: String,
+    #[serde(rename = "TLS", default)]
+    tls: bool,
+    #[serde(rename = "Signature", default)]
+    signature: String,
+    #[serde(rename = "Index")]
+    index: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DistPublicToml {
+    #[serde(rename = "Coefficients")]
+    coefficients: Vec<String>,
+}
+
+// The simple values come before the tables, as required by the toml encoding.
+#[derive(Serialize, Deserialize)]
+struct GroupToml {
+    #[serde(rename = "Threshold")]
+    threshold: u32,
+    #[serde(rename = "Period")]
+    period: String,
+    #[serde(rename = "CatchupPeriod", default)]
+    catchup_period: String,
+    #[serde(rename = "GenesisTime")]
+    genesis_time: i64,
+    #[serde(rename = "TransitionTime", default)]
+    transition_time: i64,
+    #[serde(rename = "GenesisSeed", default)]
+    genesis_seed: String,
+    #[serde(rename = "SchemeID", default)]
+    scheme_id: String,
+    #[serde(rename = "ID", default)]
+    id: String,
+    #[serde(rename = "Nodes")]
+    nodes: Vec<NodeToml>,
+    #[serde(rename = "PublicKey", skip_serializing_if = "Option::is_none")]
+    public_key: Option<DistPublicToml>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ShareToml {
+    #[serde(rename = "Index")]
+    index: u32,
+    #[serde(rename = "Share")]
+    share: String,
+    #[serde(rename = "Commits")]
+    commits: Vec<String>,
+    #[serde(rename = "SchemeName", default)]
+    scheme_name: String,
+}
+
+impl Group {
+    /// new returns the group of the participants of a DKG of threshold t, the
+    /// node at index i having the longterm key participants[i] and the address
+    /// addresses[i].
+    pub fn new(
+        participants: &[G1Point],
+        addresses: &[String],
+        threshold: u32,
+        period: &str,
+        genesis_time: i64,
+    ) -> Result<Group, Box<dyn Error>> {
+        if participants.len() != addresses.len() {
+            bail!("drand: as many addresses as participants are required");
+        }
+        if threshold == 0 || threshold as usize > participants.len() {
+            bail!("drand: invalid threshold");
+        }
+        let nodes = participants
+            .iter()
+            .zip(addresses.iter())
+            .enumerate()
+            .map(|(i, (key, address))| Node {
+                index: i as u32,
+                address: address.clone(),
+                key: *key,
+                tls: true,
+                signature: Vec::new(),
+            })
+            .collect();
+        Ok(Group {
+            threshold,
+            period: period.to_string(),
+            catchup_period: String::new(),
+            nodes,
+            genesis_time,
+            transition_time: genesis_time,
+            genesis_seed: Vec::new(),
+            public_key: Vec::new(),
+            scheme_id: DEFAULT_SCHEME.to_string(),
+            id: String::new(),
+        })
+    }
+
+    /// set_dist_key records the public polynomial of the distributed key
+    /// generated by the group.
+    pub fn set_dist_key(&mut self, share: &DistKeyShare<G1Point>) {
+        self.public_key = share.get_commitments().to_vec();
+    }
+
+    /// participants returns the longterm keys of the nodes, in the order of
+    /// their indices, for the DKG or a resharing. It returns an error unless
+    /// the indices of the nodes are 0..n.
+    pub fn participants(&self) -> Result<Vec<G1Point>, Box<dyn Error>> {
+        let mut participants: Vec<Option<G1Point>> = vec![None; self.nodes.len()];
+        for node in self.nodes.iter() {
+            match participants.get_mut(node.index as usize) {
+                Some(p @ None) => *p = Some(node.key),
+                _ => bail!("drand: node indices aren't 0..{}", self.nodes.len()),
+            }
+        }
+        Ok(participants.into_iter().flatten().collect())
+    }
+
+    /// node returns the node at index i, if any.
+    pub fn node(&self, i: u32) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.index == i)
+    }
+
+    /// index_of returns the index of the node whose longterm key is key, if
+    /// any.
+    pub fn index_of(&self, key: &G1Point) -> Option<u32> {
+        self.nodes.iter().find(|n| n.key == *key).map(|n| n.index)
+    }
+
+    /// to_toml returns the group.toml file of the group.
+    pub fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        let mut nodes: Vec<&Node> = self.nodes.iter().collect();
+        nodes.sort_by_key(|n| n.index);
+        let group = GroupToml {
+            threshold: self.threshold,
+            period: self.period.clone(),
+            catchup_period: self.catchup_period.clone(),
+            genesis_time: self.genesis_time,
+            transition_time: self.transition_time,
+            genesis_seed: hex::encode(&self.genesis_seed),
+            scheme_id: self.scheme_id.clone(),
+            id: self.id.clone(),
+            nodes: nodes
+                .into_iter()
+                .map(|n| NodeToml {
+                    address: n.address.clone(),
+                    key: hex::encode(n.key.pk_to_key_slice()),
+                    tls: n.tls,
+                    signature: hex::encode(&n.signature),
+                    index: n.index,
+                })
+                .collect(),
+            public_key: if self.public_key.is_empty() {
+                None
+            } else {
+                Some(DistPublicToml {
+                    coefficients: encode_points(&self.public_key),
+                })
+            },
+        };
+        Ok(toml::to_string(&group)?)
+    }
+
+    /// from_toml decodes a group.toml file of drand.
+    pub fn from_toml(s: &str) -> Result<Group, Box<dyn Error>> {
+        let group: GroupToml = toml::from_str(s)?;
+        let mut nodes = group
+            .nodes
+            .into_iter()
+            .map(|n| {
+                Ok(Node {
+                    index: n.index,
+                    address: n.address,
+                    key: decode_point(&n.key)?,
+                    tls: n.tls,
+                    signature: hex::decode(&n.signature)?,
+                })
+            })
+            .collect::<Result<Vec<Node>, Box<dyn Error>>>()?;
+        nodes.sort_by_key(|n| n.index);
+        if group.threshold == 0 || group.threshold as usize > nodes.len() {
+            bail!("drand: invalid threshold");
+        }
+        let public_key = match group.public_key {
+            Some(pk) => decode_points(&pk.coefficients)?,
+            None => Vec::new(),
+        };
+        if !public_key.is_empty() && public_key.len() != group.threshold as usize {
+            bail!("drand: public key of another threshold");
+        }
+        Ok(Group {
+            threshold: group.threshold,
+            period: group.period,
+            catchup_period: group.catchup_period,
+            nodes,
+            genesis_time: group.genesis_time,
+            transition_time: group.transition_time,
+            genesis_seed: hex::decode(&group.genesis_seed)?,
+            public_key,
+            scheme_id: group.scheme_id,
+            id: group.id,
+        })
+    }
+}
+
+/// share_to_toml returns the private share file of drand holding share.
+pub fn share_to_toml(
+    share: &DistKeyShare<G1Point>,
+    scheme: &str,
+) -> Result<String, Box<dyn Error>> {
+    let pri_share: PriShare<FE> = share.get_pri_share();
+    let share = ShareToml {
+        index: pri_share.i,
+        share: hex::encode(scalar_to_be_bytes(&pri_share.v)),
+        commits: encode_points(share.get_commitments()),
+        scheme_name: scheme.to_string(),
+    };
+    Ok(toml::to_string(&share)?)
+}
+
+/// share_from_toml decodes a private share file of drand. It returns an error
+/// if the share doesn't match the public polynomial.
+pub fn share_from_toml(s: &str) -> Result<DistKeyShare<G1Point>, Box<dyn Error>> {
+    let share: ShareToml = toml::from_str(s)?;
+    let v: FE = scalar_from_be_bytes(&hex::decode(&share.share)?)
+        .map_err(|e| simple_error!("drand: invalid share: {}", e))?;
+    let commits = decode_points(&share.commits)?;
+    if commits.is_empty() {
+        bail!("drand: no commitments in the share file");
+    }
+    let dks = DistKeyShare::new(commits, PriShare { i: share.index, v });
+    let public: G1Point = G1Point::generator().scalar_mul(&v.get_element());
+    if dks.pub_share(share.index).v != public {
+        bail!("drand: share not matching the public polynomial");
+    }
+    Ok(dks)
+}
+
+fn encode_points(points: &[G1Point]) -> Vec<String> {
+    points
+        .iter()
+        .map(|p| hex::encode(p.pk_to_key_slice()))
+        .collect()
+}
+
+fn decode_point(s: &str) -> Result<G1Point, Box<dyn Error>> {
+    G1Point::from_bytes(&hex::decode(s)?).map_err(|e| simple_error!("drand: {}", e).into())
+}
+
+fn decode_points(points: &[String]) -> Result<Vec<G1Point>, Box<dyn Error>> {
+    points.iter().map(|s| decode_point(s)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::sim;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_drand_group_file() {
+        let (n, t) = (4, 3);
+        let (secs, pubs) = sim::new_participants::<G1Point>(n);
+        let addresses: Vec<String> = (0..n).map(|i| format!("127.0.0.1:{}", 8080 + i)).collect();
+        let mut group = Group::new(&pubs, &addresses, t, "30s", 1_590_445_175).unwrap();
+        assert_eq!(pubs, group.participants().unwrap());
+
+        let mut dkgs: Vec<DistKeyGenerator<G1Point>> = secs
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, group.participants().unwrap(), t).unwrap())
+            .collect();
+        let network = sim::Network::new(n, Default::default(), ChaCha20Rng::seed_from_u64(42));
+        let shares: Vec<DistKeyShare<G1Point>> = sim::run_dkg(&network, &mut dkgs, 100)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        group.set_dist_key(&shares[0]);
+
+        let decoded = Group::from_toml(&group.to_toml().unwrap()).unwrap();
+        assert_eq!(group, decoded);
+        for (i, key) in pubs.iter().enumerate() {
+            assert_eq!(Some(i as u32), decoded.index_of(key));
+            assert_eq!(addresses[i], decoded.node(i as u32).unwrap().address);
+        }
+        assert_eq!(shares[0].get_public_key(), decoded.public_key[0]);
+
+        for share in shares.iter() {
+            let file = share_to_toml(share, DEFAULT_SCHEME).unwrap();
+            let decoded = share_from_toml(&file).unwrap();
+            assert_eq!(share.get_pri_share(), decoded.get_pri_share());
+            assert_eq!(share.get_commitments(), decoded.get_commitments());
+        }
+
+        // a share of another node
+        let forged = share_to_toml(&shares[1], DEFAULT_SCHEME)
+            .unwrap()
+            .replace("Index = 1", "Index = 2");
+        share_from_toml(&forged).expect_err("Must fail, share of another index");
+
+        // indices of the nodes must be 0..n
+        let mut broken = group;
+        broken.nodes[0].index = 1;
+        broken
+            .participants()
+            .expect_err("Must fail, duplicate index");
+    }
+
+    #[test]
+    fn test_drand_group_file_from_drand() {
+        let (_, pubs) = sim::new_participants::<G1Point>(3);
+        let file = format!(
+            r#"Threshold = 2
+Period = "30s"
+CatchupPeriod = "1s"
+GenesisTime = 1590445175
+TransitionTime = 1590445175
+GenesisSeed = "2ff8b9e0a6d0f8c1b2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5"
+SchemeID = "pedersen-bls-chained"
+ID = "default"
+
+[[Nodes]]
+  Address = "drand-1:4444"
+  Key = "{}"
+  TLS = true
+  Signature = "00"
+  Index = 1
+
+[[Nodes]]
+  Address = "drand-0:4444"
+  Key = "{}"
+  TLS = true
+  Signature = "00"
+  Index = 0
+
+[[Nodes]]
+  Address = "drand-2:4444"
+  Key = "{}"
+  TLS = false
+  Signature = "00"
+  Index = 2
+"#,
+            hex::encode(pubs[1].pk_to_key_slice()),
+            hex::encode(pubs[0].pk_to_key_slice()),
+            hex::encode(pubs[2].pk_to_key_slice()),
+        );
+        let group = Group::from_toml(&file).unwrap();
+        assert_eq!(2, group.threshold);
+        assert_eq!("default", group.id);
+        assert!(group.public_key.is_empty());
+        assert_eq!(pubs, group.participants().unwrap());
+        assert_eq!("drand-0:4444", group.node(0).unwrap().address);
+        assert!(!group.node(2).unwrap().tls);
+    }
+}
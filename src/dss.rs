@@ -5,41 +5,41 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::error::Error;
-use std::io::Write;
 
 use crate::dkg;
+use crate::encoding;
 use crate::poly;
-use crate::sign;
 use crate::utils;
 
-use curve25519_dalek::scalar::Scalar;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256, Sha512};
+use sha2::{Digest, Sha256};
 
 use crate::curve_traits;
 use crate::ristretto_curve;
-use curve_traits::{ECPoint, ECScalar};
-use ristretto_curve::{FE, GE, SK};
+use curve_traits::{CurvePoint, ECScalar};
+use ristretto_curve::GE;
 
 use dkg::DistKeyShare;
+use encoding::Encoder;
 use poly::{PriShare, PubPoly, PubShare};
 use utils::bitwise_eq;
 
 /// DSS holds the information used to issue partial signatures as well as to
 /// compute the distributed schnorr signature.
 #[derive(Serialize, Deserialize, Debug)]
-pub struct DSS {
-    secret: FE,
-    public: GE,
+#[serde(bound = "")]
+pub struct DSS<P: CurvePoint = GE> {
+    secret: P::Scalar,
+    public: P,
     index: u32,
-    participants: Vec<GE>,
+    participants: Vec<P>,
     t: u32,
-    long: DistKeyShare,
-    random: DistKeyShare,
-    long_poly: PubPoly,
-    random_poly: PubPoly,
+    long: DistKeyShare<P>,
+    random: DistKeyShare<P>,
+    long_poly: PubPoly<P>,
+    random_poly: PubPoly<P>,
     msg: Vec<u8>, // the message to sign
-    partials: Vec<PriShare<FE>>,
+    partials: Vec<PriShare<P::Scalar>>,
     partials_idx: HashMap<u32, bool>,
     signed: bool,
     session_id: Vec<u8>,
@@ -48,28 +48,29 @@ pub struct DSS {
 /// PartialSig is partial representation of the final distributed signature. It
 /// must be sent to each of the other participants.
 #[derive(Serialize, Deserialize)]
-pub struct PartialSig {
-    partial: PriShare<FE>,
+#[serde(bound = "")]
+pub struct PartialSig<P: CurvePoint = GE> {
+    partial: PriShare<P::Scalar>,
     session_id: Vec<u8>,
     signature: Vec<u8>,
 }
 
-impl DSS {
+impl<P: CurvePoint> DSS<P> {
     /// Create DSS struct out of the longterm secret of this
     /// node, the list of participants, the longterm and random distributed key
     /// (generated by the dkg package), the message to sign and finally the T
     /// threshold. It returns an error if the public key of the secret can't be found
     /// in the list of participants.
     pub fn new(
-        secret: FE,
-        participants: Vec<GE>,
-        long: DistKeyShare,
-        random: DistKeyShare,
+        secret: P::Scalar,
+        participants: Vec<P>,
+        long: DistKeyShare<P>,
+        random: DistKeyShare<P>,
         msg: Vec<u8>,
         t: u32,
-    ) -> Result<DSS, Box<dyn Error>> {
-        let generator = GE::generator();
-        let pub_k: GE = generator.scalar_mul(&secret.get_element());
+    ) -> Result<DSS<P>, Box<dyn Error>> {
+        let generator = P::generator();
+        let pub_k: P = generator.scalar_mul(&secret.get_element());
 
         let index = participants
             .iter()
@@ -98,24 +99,24 @@ impl DSS {
     /// PartialSig can be broadcasted to every other participant or only to a
     /// trusted combiner as described in the paper.
     /// The signature format is compatible with EdDSA verification implementations.
-    pub fn partial_sig(&mut self) -> Result<PartialSig, Box<dyn Error>> {
-        let alpha: FE = self.long.get_pri_share().v;
-        let beta: FE = self.random.get_pri_share().v;
+    pub fn partial_sig(&mut self) -> Result<PartialSig<P>, Box<dyn Error>> {
+        let alpha: P::Scalar = self.long.get_pri_share().v;
+        let beta: P::Scalar = self.random.get_pri_share().v;
 
-        let hash: FE = self.hash_sig();
+        let hash: P::Scalar = self.hash_sig();
 
-        let right: FE = hash.mul(&alpha.get_element());
-        let right: FE = right.add(&beta.get_element());
+        let right: P::Scalar = hash.mul(&alpha.get_element());
+        let right: P::Scalar = right.add(&beta.get_element());
 
-        let part_pri_share: PriShare<FE> = PriShare {
+        let part_pri_share: PriShare<P::Scalar> = PriShare {
             i: self.index,
             v: right,
         };
-        let part_hash: [u8; 32] = PartialSig::hash(&part_pri_share, &self.session_id)?;
+        let part_hash: [u8; 32] = PartialSig::<P>::hash(&part_pri_share, &self.session_id)?;
 
-        let signature = sign::sign_msg(
-            self.secret.get_element().to_bytes(),
-            self.public.get_element().to_bytes(),
+        let signature = P::sign_msg(
+            &self.secret,
+            &self.public,
             &part_hash,
             &self.index.to_le_bytes(),
         )?;
@@ -141,16 +142,16 @@ impl DSS {
     /// received by the same peer. To know whether the distributed signature can be
     /// computed after this call, one can use the enough_partial_sigs method.
     /// not done yet
-    pub fn process_partial_sig(&mut self, ps: &PartialSig) -> Result<(), Box<dyn Error>> {
+    pub fn process_partial_sig(&mut self, ps: &PartialSig<P>) -> Result<(), Box<dyn Error>> {
         let pub_k = self
             .participants
             .get(ps.partial.i as usize)
             .ok_or_else(|| simple_error!("dss: partial signature with invalid index"))?;
 
-        let msg: [u8; 32] = PartialSig::hash(&ps.partial, &self.session_id)?;
+        let msg: [u8; 32] = PartialSig::<P>::hash(&ps.partial, &self.session_id)?;
 
-        sign::verify_signature(
-            pub_k.get_element().to_bytes().as_ref(),
+        P::verify_signature(
+            pub_k,
             ps.signature.as_ref(),
             msg.as_ref(),
             ps.partial.i.to_le_bytes().as_ref(),
@@ -165,14 +166,14 @@ impl DSS {
             bail!("dss: partial signature already received from peer")
         }
 
-        let hash: FE = self.hash_sig();
-        let rand_share: PubShare<GE> = self.random_poly.eval(ps.partial.i);
-        let long_share: PubShare<GE> = self.long_poly.eval(ps.partial.i);
+        let hash: P::Scalar = self.hash_sig();
+        let rand_share: PubShare<P> = self.random_poly.eval(ps.partial.i);
+        let long_share: PubShare<P> = self.long_poly.eval(ps.partial.i);
 
-        let right: GE = long_share.v.scalar_mul(&hash.get_element());
-        let right: GE = right.add_point(&rand_share.v.get_element());
-        let generator = GE::generator();
-        let left: GE = generator.scalar_mul(&ps.partial.v.get_element());
+        let right: P = long_share.v.scalar_mul(&hash.get_element());
+        let right: P = right.add_point(&rand_share.v.get_element());
+        let generator = P::generator();
+        let left: P = generator.scalar_mul(&ps.partial.v.get_element());
 
         if left != right {
             bail!("dss: partial signature not valid")
@@ -184,18 +185,17 @@ impl DSS {
         Ok(())
     }
 
-    fn hash_sig(&self) -> FE {
+    fn hash_sig(&self) -> P::Scalar {
         // H(R || A || msg) with
         //  * R = distributed random "key"
         //  * A = distributed public key
         //  * msg = msg to sign
-        let mut hasher = Sha512::new();
-        hasher.input(self.random.get_commitments()[0].get_element().to_bytes());
-        hasher.input(self.long.get_commitments()[0].get_element().to_bytes());
-        hasher.write_all(self.msg.as_ref()).unwrap();
+        let mut buff: Vec<u8> = Vec::new();
+        buff.extend(self.random.get_commitments()[0].pk_to_key_slice());
+        buff.extend(self.long.get_commitments()[0].pk_to_key_slice());
+        buff.extend_from_slice(self.msg.as_ref());
 
-        let scalar = SK::from_hash(hasher);
-        FE::from(scalar)
+        P::hash_to_scalar(&buff)
     }
 
     /// enough_partial_sigs returns true if there are enough partial signature to compute
@@ -213,74 +213,77 @@ impl DSS {
         if !self.enough_partial_sigs() {
             bail!("dkg: not enough partial signatures to sign")
         }
-        let gamma: FE = poly::recover_secret(self.partials.as_slice(), self.t)?;
+        let gamma: P::Scalar = poly::recover_secret(self.partials.as_slice(), self.t)?;
 
         // RandomPublic || gamma
         let mut buff: Vec<u8> = Vec::new();
-        let rand_bytes = self.random.get_commitments()[0].get_element().to_bytes();
+        let rand_bytes = self.random.get_commitments()[0].pk_to_key_slice();
         buff.extend_from_slice(&rand_bytes);
-        let gamma_bytes = gamma.get_element().to_bytes();
+        let gamma_bytes = gamma.to_bytes();
         buff.extend_from_slice(&gamma_bytes);
         Ok(buff)
     }
 }
 
-impl PartialSig {
+impl<P: CurvePoint> PartialSig<P> {
     /// Hash of PriShare and session_id to sign later
-    pub fn hash(partial: &PriShare<FE>, session_id: &[u8]) -> Result<[u8; 32], Box<dyn Error>> {
-        let mut hasher = Sha256::new();
-        let partial_hash = partial.hash();
-        hasher.input(&partial_hash);
-        hasher.input(session_id);
-        let result = hasher.result();
-        Ok(result.try_into()?)
+    pub fn hash(
+        partial: &PriShare<P::Scalar>,
+        session_id: &[u8],
+    ) -> Result<[u8; 32], Box<dyn Error>> {
+        Ok(Encoder::new(b"partialsig")
+            .put_u32(partial.i)
+            .put_bytes(&partial.v.to_bytes())
+            .put_bytes(session_id)
+            .hash())
     }
 }
 
 /// verify uses a public key, a message and a signature. It will return Ok(boolean) indicating
 /// if signature is valid. It will return Err if msg or sig bytes are of invalid length
-pub fn verify(public: GE, msg: &[u8], sig: &[u8]) -> Result<bool, Box<dyn Error>> {
-    if sig.len() != 64 as usize {
+pub fn verify<P: CurvePoint>(public: P, msg: &[u8], sig: &[u8]) -> Result<bool, Box<dyn Error>> {
+    let p_buff = public.pk_to_key_slice();
+    let point_len = p_buff.len();
+    let sig_len = point_len + P::Scalar::zero().to_bytes().len();
+    if sig.len() != sig_len {
         bail!(
-            "dss: Signature length invalid, expect 64 but got {}",
+            "dss: Signature length invalid, expect {} but got {}",
+            sig_len,
             sig.len()
         )
     }
 
-    let r = GE::from_bytes(&sig[..32]).map_err(|_| {
+    let r = P::from_bytes(&sig[..point_len]).map_err(|_| {
         simple_error!("dss: Error while create point from bytes in sig verification")
     })?;
 
-    let scalar_bytes: [u8; 32] = sig[32..].try_into()?;
-    let ristr_s: Scalar = Scalar::from_bytes_mod_order(scalar_bytes);
-    let s = FE::from(ristr_s);
+    let s = P::Scalar::from_bytes(&sig[point_len..])?;
 
     // reconstruct h = H(R || Public || Msg)
-    let p_buff = public.get_element().to_bytes();
-    let mut hash = Sha512::new();
-    hash.input(&sig[..32]);
-    hash.input(p_buff);
-    hash.input(msg);
+    let mut buff: Vec<u8> = Vec::new();
+    buff.extend_from_slice(&sig[..point_len]);
+    buff.extend_from_slice(&p_buff);
+    buff.extend_from_slice(msg);
 
-    let h = FE::from(SK::from_hash(hash));
+    let h: P::Scalar = P::hash_to_scalar(&buff);
     // reconstruct S == k*A + R
-    let generator = GE::generator();
-    let ss: GE = generator.scalar_mul(&s.get_element());
-    let ha: GE = public.scalar_mul(&h.get_element());
-    let rha: GE = ha.add_point(&r.get_element());
+    let generator = P::generator();
+    let ss: P = generator.scalar_mul(&s.get_element());
+    let ha: P = public.scalar_mul(&h.get_element());
+    let rha: P = ha.add_point(&r.get_element());
 
     Ok(rha == ss)
 }
 
-fn session_id(a: &DistKeyShare, b: &DistKeyShare) -> [u8; 32] {
+fn session_id<P: CurvePoint>(a: &DistKeyShare<P>, b: &DistKeyShare<P>) -> [u8; 32] {
     let mut hasher = Sha256::new();
 
     for p in a.get_commitments().iter() {
-        hasher.input(p.get_element().to_bytes());
+        hasher.input(p.pk_to_key_slice());
     }
 
     for p in b.get_commitments().iter() {
-        hasher.input(p.get_element().to_bytes());
+        hasher.input(p.pk_to_key_slice());
     }
 
     hasher
@@ -295,6 +298,7 @@ mod tests {
     use super::*;
     use crate::curve_traits;
     use crate::ristretto_curve;
+    use crate::sign;
     use curve_traits::{ECPoint, ECScalar};
     use dkg::*;
     use ristretto_curve::{FE, GE};
@@ -478,7 +482,7 @@ mod tests {
         // invalid partial sig
         let correct_v = ps0.partial.v;
         ps0.partial.v = ECScalar::new_random();
-        let msg: [u8; 32] = PartialSig::hash(&ps0.partial, &ps0.session_id).unwrap();
+        let msg: [u8; 32] = PartialSig::<GE>::hash(&ps0.partial, &ps0.session_id).unwrap();
 
         let signature = sign::sign_msg(
             dss0.secret.get_element().to_bytes(),
@@ -0,0 +1,417 @@
+//! Ed25519 curve - the edwards form of curve25519, so that the distributed key
+//! can be consumed by standard ed25519 (RFC 8032) verifiers. It shares its
+//! scalar field with the ristretto backend. Enabled with the `ed25519` feature.
+//!
+//! With this backend the dss module produces R || s signatures over
+//! H(R || A || msg) with SHA-512, which is the RFC 8032 verification equation.
+
+use crate::blake;
+use crate::curve_traits;
+use crate::curve_traits::{CurvePoint, ECPoint, ECScalar};
+use crate::ristretto_curve::{RistrettoCurvPoint, FE, SK};
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt;
+
+use curve25519_dalek::constants::{ED25519_BASEPOINT_COMPRESSED, ED25519_BASEPOINT_TABLE};
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::traits::{Identity, VartimeMultiscalarMul};
+use hex::{decode, encode};
+use num_bigint::BigUint;
+use serde::de;
+use serde::de::Visitor;
+use serde::ser::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha512};
+
+pub const PUBLIC_KEY_SIZE: usize = 32;
+pub const EXPANDED_SECRET_KEY_SIZE: usize = 64;
+
+// edwards25519_XMD:SHA-512_ELL2_RO_ of RFC 9380: the elligator 2 map to
+// curve25519 y^2 = x^3 + MONTGOMERY_A * x^2 + x, with Z = 2, followed by the
+// rational map to edwards25519.
+const MONTGOMERY_A: u32 = 486662;
+
+pub type PK = CompressedEdwardsY;
+
+#[derive(Clone, Debug, Copy)]
+pub struct Ed25519Point {
+    purpose: &'static str,
+    ge: PK,
+}
+pub type GE = Ed25519Point;
+
+impl ECPoint<PK, SK> for Ed25519Point {
+    fn generator() -> Ed25519Point {
+        Ed25519Point {
+            purpose: "base_fe",
+            ge: ED25519_BASEPOINT_COMPRESSED,
+        }
+    }
+
+    fn pk_to_key_slice(&self) -> Vec<u8> {
+        self.ge.to_bytes().to_vec()
+    }
+
+    fn get_element(&self) -> PK {
+        self.ge
+    }
+
+    fn to_hex(&self) -> String {
+        encode(self.pk_to_key_slice())
+    }
+
+    /// Reads a point, which must be in the prime order subgroup
+    fn from_bytes(bytes: &[u8]) -> Result<Ed25519Point, Box<dyn Error>> {
+        if bytes.len() != PUBLIC_KEY_SIZE {
+            bail!("Invalid Public Key length")
+        }
+        let point = CompressedEdwardsY::from_slice(bytes)
+            .decompress()
+            .filter(|x| x.is_torsion_free())
+            .ok_or_else(|| simple_error!("Invalid Public Key"))?;
+        Ok(Ed25519Point {
+            purpose: "from_bytes",
+            ge: point.compress(),
+        })
+    }
+
+    fn scalar_mul(&self, fe: &SK) -> Ed25519Point {
+        let skpk = fe * (self.ge.decompress().unwrap());
+        Ed25519Point {
+            purpose: "scalar_point_mul",
+            ge: skpk.compress(),
+        }
+    }
+
+    fn add_point(&self, other: &PK) -> Ed25519Point {
+        let pkpk = self.ge.decompress().unwrap() + other.decompress().unwrap();
+        Ed25519Point {
+            purpose: "combine",
+            ge: pkpk.compress(),
+        }
+    }
+
+    fn sub_point(&self, other: &PK) -> Ed25519Point {
+        let pkpk = self.ge.decompress().unwrap() - other.decompress().unwrap();
+        Ed25519Point {
+            purpose: "sub",
+            ge: pkpk.compress(),
+        }
+    }
+}
+
+impl CurvePoint for Ed25519Point {
+    type SK = SK;
+    type PK = PK;
+    type Scalar = FE;
+
+    const CURVE_ID: &'static str = "edwards25519";
+    const TWO_ADICITY: u32 = RistrettoCurvPoint::TWO_ADICITY;
+
+    /// same scalar field as ristretto255
+    fn root_of_unity() -> FE {
+        RistrettoCurvPoint::root_of_unity()
+    }
+
+    fn identity() -> Ed25519Point {
+        Ed25519Point {
+            purpose: "identity",
+            ge: CompressedEdwardsY::identity(),
+        }
+    }
+
+    /// Hash bytes with blake2xb and take the first 32 bytes chunk of the output
+    /// which decodes to a point, mapped to the prime order subgroup by
+    /// multiplying it with the cofactor
+    fn hash_to_point(bytes: &[u8]) -> Result<Ed25519Point, Box<dyn Error>> {
+        let hash = blake::new_blake2xb(bytes.to_vec());
+        for value in hash {
+            for chunk in value.as_ref().chunks(32) {
+                let point = CompressedEdwardsY::from_slice(chunk)
+                    .decompress()
+                    .map(|x| x.mul_by_cofactor());
+                if let Some(point) = point.filter(|x| !x.is_small_order()) {
+                    return Ok(Ed25519Point {
+                        purpose: "from_hash",
+                        ge: point.compress(),
+                    });
+                }
+            }
+        }
+        bail!("Error hash")
+    }
+
+    const HASH_TO_GROUP_SUITE: &'static str = "edwards25519_XMD:SHA-512_ELL2_RO_";
+
+    /// hash_to_curve of RFC 9380 with expand_message_xmd over SHA-512 and the
+    /// elligator 2 map, cleared of the cofactor. It isn't constant time,
+    /// which suits the public inputs it is used for.
+    fn hash_to_group(msg: &[u8], dst: &[u8]) -> Result<Ed25519Point, Box<dyn Error>> {
+        let p = field_modulus();
+        let uniform: Vec<u8> = curve_traits::expand_message_xmd(msg, dst, 96)?;
+        let q0 = map_to_curve(&(BigUint::from_bytes_be(&uniform[..48]) % &p))?;
+        let q1 = map_to_curve(&(BigUint::from_bytes_be(&uniform[48..]) % &p))?;
+        Ok(Ed25519Point {
+            purpose: "from_hash",
+            ge: (q0 + q1).mul_by_cofactor().compress(),
+        })
+    }
+
+    /// SHA-512 reduced modulo the group order, as in RFC 8032
+    fn hash_to_scalar(bytes: &[u8]) -> FE {
+        FE::from(SK::hash_from_bytes::<Sha512>(bytes))
+    }
+
+    /// multiplication by the precomputed table of the basepoint of dalek
+    fn base_mul(scalar: &FE) -> Ed25519Point {
+        Ed25519Point {
+            purpose: "base_mul",
+            ge: (&ED25519_BASEPOINT_TABLE * &scalar.get_element()).compress(),
+        }
+    }
+
+    /// Straus' method for small inputs and Pippenger's for large ones
+    fn multi_scalar_mul(scalars: &[FE], points: &[Ed25519Point]) -> Ed25519Point {
+        let sum = EdwardsPoint::vartime_multiscalar_mul(
+            scalars.iter().map(|s| s.get_element()),
+            points.iter().map(|p| p.ge.decompress().unwrap()),
+        );
+        Ed25519Point {
+            purpose: "multi_scalar_mul",
+            ge: sum.compress(),
+        }
+    }
+}
+
+// field_modulus returns 2^255 - 19.
+fn field_modulus() -> BigUint {
+    (BigUint::from(1u32) << 255) - 19u32
+}
+
+// map_to_curve maps the field element u to edwards25519 with the elligator 2
+// map and the rational map, RFC 9380 sections 6.7.1 and 6.8.2. The point
+// isn't cleared of the cofactor.
+fn map_to_curve(u: &BigUint) -> Result<EdwardsPoint, Box<dyn Error>> {
+    let p = field_modulus();
+    let zero = BigUint::from(0u32);
+    let one = BigUint::from(1u32);
+    let inv = |x: &BigUint| x.modpow(&(&p - 2u32), &p);
+    let is_square = |x: &BigUint| x.modpow(&((&p - 1u32) >> 1), &p) != &p - 1u32;
+    // p = 5 mod 8
+    let sqrt = |x: &BigUint| {
+        let r = x.modpow(&((&p + 3u32) >> 3), &p);
+        if &r * &r % &p == *x {
+            r
+        } else {
+            r * BigUint::from(2u32).modpow(&((&p - 1u32) >> 2), &p) % &p
+        }
+    };
+    let j = BigUint::from(MONTGOMERY_A);
+    let g = |x: &BigUint| (x * x * x + &j * x * x + x) % &p;
+
+    let den = (u * u * 2u32 + 1u32) % &p;
+    let mut x1 = (&p - &j) % &p;
+    if den != zero {
+        x1 = x1 * inv(&den) % &p;
+    }
+    let gx1 = g(&x1);
+    let (s, mut t, odd) = if is_square(&gx1) {
+        let y = sqrt(&gx1);
+        (x1, y, 1)
+    } else {
+        let x2 = (&p + &p - &x1 - &j) % &p;
+        let y = sqrt(&g(&x2));
+        (x2, y, 0)
+    };
+    if t.to_bytes_le()[0] & 1 != odd {
+        t = (&p - &t) % &p;
+    }
+
+    // x = sqrt(-486664) * s / t and y = (s - 1) / (s + 1), with the
+    // identity for the exceptional cases
+    let (x, y) = if t == zero || (&s + 1u32) % &p == zero {
+        (zero, one)
+    } else {
+        let mut c1 = sqrt(&(&p - (&j + 2u32)));
+        if c1.to_bytes_le()[0] & 1 == 1 {
+            c1 = &p - c1;
+        }
+        let x = c1 * &s % &p * inv(&t) % &p;
+        let y = (&s + &p - 1u32) % &p * inv(&((&s + 1u32) % &p)) % &p;
+        (x, y)
+    };
+
+    let mut bytes = [0u8; 32];
+    let y = y.to_bytes_le();
+    bytes[..y.len()].copy_from_slice(&y);
+    bytes[31] |= (x.to_bytes_le()[0] & 1) << 7;
+    CompressedEdwardsY(bytes)
+        .decompress()
+        .ok_or_else(|| simple_error!("Invalid point of hash_to_group").into())
+}
+
+/// export_public_key returns the RFC 8032 encoding of a public key, e.g. the
+/// distributed public key of a DKG.
+pub fn export_public_key(public: &Ed25519Point) -> [u8; PUBLIC_KEY_SIZE] {
+    public.get_element().to_bytes()
+}
+
+/// export_expanded_secret_key returns a reconstructed secret as an expanded
+/// ed25519 secret key: the secret scalar followed by the 32 bytes prefix used to
+/// derive signing nonces. A secret scalar has no RFC 8032 seed, so this is the
+/// form to use with libraries that accept expanded keys. The prefix is derived
+/// deterministically from the scalar.
+pub fn export_expanded_secret_key(secret: &FE) -> [u8; EXPANDED_SECRET_KEY_SIZE] {
+    let scalar_bytes: [u8; 32] = secret.get_element().to_bytes();
+
+    let mut hasher = Sha512::new();
+    hasher.input(b"ed25519-expanded-key-prefix");
+    hasher.input(scalar_bytes);
+    let prefix: [u8; 32] = hasher.result()[..32]
+        .try_into()
+        .expect("Slice with incorrect length");
+
+    let mut expanded = [0u8; EXPANDED_SECRET_KEY_SIZE];
+    expanded[..32].copy_from_slice(&scalar_bytes);
+    expanded[32..].copy_from_slice(&prefix);
+    expanded
+}
+
+/// import_secret_key returns the secret scalar of an RFC 8032 secret key
+/// given by its 32 bytes seed, e.g. to share an existing ed25519 key with
+/// vss::Dealer::for_existing_key.
+pub fn import_secret_key(seed: &[u8; 32]) -> FE {
+    let hash = Sha512::digest(seed);
+    let mut bytes: [u8; 32] = hash[..32].try_into().expect("Slice with incorrect length");
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    FE::from(SK::from_bytes_mod_order(bytes))
+}
+
+impl Default for Ed25519Point {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Serialize for Ed25519Point {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Ed25519Point {
+    fn deserialize<D>(deserializer: D) -> Result<Ed25519Point, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(Ed25519PointVisitor)
+    }
+}
+
+struct Ed25519PointVisitor;
+
+impl<'de> Visitor<'de> for Ed25519PointVisitor {
+    type Value = Ed25519Point;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Ed25519Point")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Ed25519Point, E> {
+        let s_bytes: Vec<u8> = decode(s).map_err(E::custom)?;
+        Ed25519Point::from_bytes(&s_bytes).map_err(E::custom)
+    }
+}
+
+impl PartialEq for Ed25519Point {
+    fn eq(&self, other: &Ed25519Point) -> bool {
+        self.get_element() == other.get_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dss;
+    use ed25519_dalek::{ExpandedSecretKey, PublicKey, SecretKey, Verifier};
+
+    fn gen_pair() -> (FE, GE) {
+        let secret: FE = ECScalar::new_random();
+        let public: GE = GE::generator().scalar_mul(&secret.get_element());
+        (secret, public)
+    }
+
+    #[test]
+    fn test_ed25519_point_bytes() {
+        let (_, point) = gen_pair();
+        assert_eq!(point, GE::from_bytes(&point.pk_to_key_slice()).unwrap());
+        let h = GE::hash_to_point(b"some bytes").unwrap();
+        assert_eq!(h, GE::from_bytes(&h.pk_to_key_slice()).unwrap());
+        let (secret, public) = gen_pair();
+        assert_eq!(public, GE::base_mul(&secret));
+    }
+
+    #[test]
+    fn test_ed25519_hash_to_group() {
+        // RFC 9380, J.5.1 edwards25519_XMD:SHA-512_ELL2_RO_, the compressed
+        // encoding of P
+        let dst = b"QUUX-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_RO_";
+        assert_eq!(
+            "21dc15e10253796df23a7699c8a383ea624cce88c52431f6be220b1a56c8a609",
+            GE::hash_to_group(b"", dst).unwrap().to_hex()
+        );
+        assert_eq!(
+            "31558a26887f23fb8218f143e69d5f0af2e7831130bd5b432ef23883b895839a",
+            GE::hash_to_group(b"abc", dst).unwrap().to_hex()
+        );
+    }
+
+    #[test]
+    fn test_ed25519_signature_canonical() {
+        let (secret, public) = gen_pair();
+        let mut sig = GE::sign_msg(&secret, &public, b"message", b"context").unwrap();
+        GE::verify_signature(&public, &sig, b"message", b"context").unwrap();
+        // s + l reduces to the same s, but isn't its canonical encoding
+        let order = curve25519_dalek::constants::BASEPOINT_ORDER.to_bytes();
+        let mut carry: u16 = 0;
+        for (b, o) in sig[32..].iter_mut().zip(order.iter()) {
+            let sum = u16::from(*b) + u16::from(*o) + carry;
+            *b = sum as u8;
+            carry = sum >> 8;
+        }
+        GE::verify_signature(&public, &sig, b"message", b"context")
+            .expect_err("Must fail, non-canonical s");
+    }
+
+    #[test]
+    fn test_ed25519_export() {
+        let (secret, public) = gen_pair();
+        let msg: &[u8] = b"Very secret message";
+
+        let expanded = ExpandedSecretKey::from_bytes(&export_expanded_secret_key(&secret)).unwrap();
+        let public_key = PublicKey::from_bytes(&export_public_key(&public)).unwrap();
+        let sig = expanded.sign(msg, &public_key);
+
+        public_key.verify(msg, &sig).unwrap();
+        // dss signatures use the same format
+        assert!(dss::verify(public, msg, &sig.to_bytes()).unwrap());
+        assert!(!dss::verify(public, b"other message", &sig.to_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_ed25519_import() {
+        let seed = [7u8; 32];
+        let secret: FE = import_secret_key(&seed);
+        let public_key = PublicKey::from(&SecretKey::from_bytes(&seed).unwrap());
+        assert_eq!(
+            public_key.to_bytes(),
+            export_public_key(&GE::generator().scalar_mul(&secret.get_element()))
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Threshold ElGamal encryption
+//!
+//! Messages are encrypted to the distributed public key X of a DKG: the sender
+//! draws r, publishes U = r * G and encrypts the message with AES256-GCM under
+//! a key derived from r * X. The sender signs the ciphertext with r, so that it
+//! can't be mauled into another valid ciphertext. Each share holder i produces
+//! the decryption share x_i * U along with a Chaum-Pedersen proof that it used
+//! its share, and any t valid decryption shares combine to r * X by Lagrange
+//! interpolation, and then to the plaintext. The label is public data bound to
+//! the ciphertext, e.g. the round of an encrypted mempool or of a sealed-bid
+//! auction.
+
+use std::collections::HashSet;
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dh;
+use crate::dkg;
+use crate::dleq;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::utils;
+
+use aead::{generic_array::GenericArray, Aead, Payload};
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use dkg::DistKeyShare;
+use dleq::DleqProof;
+use encoding::Encoder;
+use poly::{PubPoly, PubShare};
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+
+// context of the signature of the ciphertexts
+const CONTEXT: &[u8] = b"elgamal";
+
+/// Ciphertext is the threshold encryption of a message.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Ciphertext<P: CurvePoint = GE> {
+    // r * G
+    pub u: P,
+    // Public data bound to the ciphertext
+    pub label: Vec<u8>,
+    // Nonce of the AEAD encryption
+    pub nonce: Vec<u8>,
+    // AEAD encryption of the message
+    pub cipher: Vec<u8>,
+    // Signature of the ciphertext with r
+    pub signature: Vec<u8>,
+}
+
+/// DecryptionShare is the share of share holder i to decrypt a Ciphertext.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct DecryptionShare<P: CurvePoint = GE> {
+    pub i: u32,
+    // x_i * U
+    pub d: P,
+    // Proof that d and the public share of i have the same discrete logarithm
+    pub proof: DleqProof<P>,
+}
+
+/// encrypt returns the encryption of msg to the distributed public key, bound
+/// to the label.
+pub fn encrypt<P: CurvePoint>(
+    public: &P,
+    label: &[u8],
+    msg: &[u8],
+) -> Result<Ciphertext<P>, Box<dyn Error>> {
+    encrypt_with_rng(public, label, msg, &mut utils::rand_hack())
+}
+
+/// Same as encrypt, with the randomness drawn from rng.
+pub fn encrypt_with_rng<P: CurvePoint, R: RngCore + CryptoRng>(
+    public: &P,
+    label: &[u8],
+    msg: &[u8],
+    rng: &mut R,
+) -> Result<Ciphertext<P>, Box<dyn Error>> {
+    let r: P::Scalar = ECScalar::new_random_with_rng(rng);
+    let u: P = P::generator().scalar_mul(&r.get_element());
+    let shared: P = public.scalar_mul(&r.get_element());
+
+    let context = aead_context(&u, label);
+    let nonce: [u8; dh::NONCE_SIZE] = dh::new_nonce(rng);
+    let cipher = dh::new_aead(&shared, &context)
+        .encrypt(
+            GenericArray::from_slice(&nonce),
+            Payload { msg, aad: &context },
+        )
+        .map_err(|_| simple_error!("elgamal: encryption failure"))?;
+
+    let mut c = Ciphertext {
+        u,
+        label: label.to_vec(),
+        nonce: nonce.to_vec(),
+        cipher,
+        signature: Vec::new(),
+    };
+    c.signature = P::sign_msg(&r, &u, &c.hash(), CONTEXT)?;
+    Ok(c)
+}
+
+impl<P: CurvePoint> Ciphertext<P> {
+    /// hash returns the hash of the ciphertext signed by the sender.
+    pub fn hash(&self) -> [u8; 32] {
+        Encoder::new(b"elgamal")
+            .put_bytes(&self.u.pk_to_key_slice())
+            .put_bytes(&self.label)
+            .put_bytes(&self.nonce)
+            .put_bytes(&self.cipher)
+            .hash()
+    }
+
+    /// verify checks that the ciphertext was produced by the sender knowing r.
+    /// The share holders must not decrypt a ciphertext which doesn't verify.
+    pub fn verify(&self) -> Result<(), Box<dyn Error>> {
+        P::verify_signature(&self.u, &self.signature, &self.hash(), CONTEXT)
+            .map_err(|e| simple_error!("elgamal: invalid ciphertext: {}", e).into())
+    }
+}
+
+/// decryption_share returns the decryption share of the ciphertext by the
+/// holder of the share of the distributed key.
+pub fn decryption_share<P: CurvePoint>(
+    share: &DistKeyShare<P>,
+    c: &Ciphertext<P>,
+) -> Result<DecryptionShare<P>, Box<dyn Error>> {
+    c.verify()?;
+    let pri_share = share.get_pri_share();
+    let (proof, _, d) = DleqProof::new(&P::generator(), &c.u, &pri_share.v);
+    Ok(DecryptionShare {
+        i: pri_share.i,
+        d,
+        proof,
+    })
+}
+
+/// verify_decryption_share checks the decryption share of the ciphertext
+/// against the public share of its issuer, evaluated from the public
+/// polynomial.
+pub fn verify_decryption_share<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    c: &Ciphertext<P>,
+    ds: &DecryptionShare<P>,
+) -> Result<(), Box<dyn Error>> {
+    let pub_share: PubShare<P> = pub_poly.eval(ds.i);
+    ds.proof
+        .verify(&P::generator(), &c.u, &pub_share.v, &ds.d)
+        .map_err(|e| simple_error!("elgamal: invalid decryption share {}: {}", ds.i, e).into())
+}
+
+/// decrypt verifies the decryption shares and recovers the message out of t
+/// valid ones. Invalid decryption shares are ignored; an error is returned if
+/// less than t are valid.
+pub fn decrypt<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    c: &Ciphertext<P>,
+    shares: &[DecryptionShare<P>],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    c.verify()?;
+    let t = pub_poly.threshold();
+    let mut valid: Vec<PubShare<P>> = Vec::new();
+    let mut seen: HashSet<u32> = HashSet::new();
+    for ds in shares.iter() {
+        if seen.contains(&ds.i) || verify_decryption_share(pub_poly, c, ds).is_err() {
+            continue;
+        }
+        seen.insert(ds.i);
+        valid.push(PubShare::new(ds.i, ds.d));
+        if valid.len() as u32 >= t {
+            break;
+        }
+    }
+    if (valid.len() as u32) < t {
+        bail!("elgamal: not enough valid decryption shares");
+    }
+
+    let shared: P = poly::recover_commit(&mut valid, t)?;
+    let context = aead_context(&c.u, &c.label);
+    if c.nonce.len() != dh::NONCE_SIZE {
+        bail!("elgamal: invalid nonce size");
+    }
+    dh::new_aead(&shared, &context)
+        .decrypt(
+            GenericArray::from_slice(&c.nonce),
+            Payload {
+                msg: &c.cipher,
+                aad: &context,
+            },
+        )
+        .map_err(|_| simple_error!("elgamal: decryption failure").into())
+}
+
+// aead_context returns the context of the AEAD encryption of a ciphertext.
+fn aead_context<P: CurvePoint>(u: &P, label: &[u8]) -> Vec<u8> {
+    Encoder::new(b"elgamal-aead")
+        .put_bytes(&u.pk_to_key_slice())
+        .put_bytes(label)
+        .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::PriPoly;
+
+    fn gen_shares(n: u32, t: u32) -> (Vec<DistKeyShare>, PubPoly) {
+        let pri_poly: PriPoly = PriPoly::new(t, None);
+        let pub_poly: PubPoly = pri_poly.commit(None);
+        let (_, commits) = pub_poly.info();
+        let shares = pri_poly
+            .shares(n)
+            .into_iter()
+            .map(|s| DistKeyShare::new(commits.clone(), s))
+            .collect();
+        (shares, pub_poly)
+    }
+
+    #[test]
+    fn test_threshold_elgamal() {
+        let (n, t) = (7, 4);
+        let (shares, pub_poly) = gen_shares(n, t);
+        let msg = b"sealed bid of 42";
+        let c: Ciphertext = encrypt(&pub_poly.commit(), b"auction 1", msg).unwrap();
+
+        let mut dec_shares: Vec<DecryptionShare> = shares
+            .iter()
+            .map(|s| decryption_share(s, &c).unwrap())
+            .collect();
+        for ds in dec_shares.iter() {
+            verify_decryption_share(&pub_poly, &c, ds).unwrap();
+        }
+        assert_eq!(
+            msg.to_vec(),
+            decrypt(&pub_poly, &c, &dec_shares[..t as usize]).unwrap()
+        );
+        assert_eq!(
+            msg.to_vec(),
+            decrypt(&pub_poly, &c, &dec_shares[(n - t) as usize..]).unwrap()
+        );
+
+        // an invalid decryption share is skipped
+        dec_shares[0].d = dec_shares[1].d;
+        assert!(verify_decryption_share(&pub_poly, &c, &dec_shares[0]).is_err());
+        assert!(decrypt(&pub_poly, &c, &dec_shares[..t as usize]).is_err());
+        assert_eq!(
+            msg.to_vec(),
+            decrypt(&pub_poly, &c, &dec_shares[..=t as usize]).unwrap()
+        );
+
+        // duplicated shares count once
+        let dup: Vec<DecryptionShare> = vec![dec_shares[1].clone(); t as usize];
+        assert!(decrypt(&pub_poly, &c, &dup).is_err());
+    }
+
+    #[test]
+    fn test_threshold_elgamal_mauled() {
+        let (_, pub_poly) = gen_shares(5, 3);
+        let (shares, _) = gen_shares(5, 3);
+        let c: Ciphertext = encrypt(&pub_poly.commit(), b"round 7", b"tx").unwrap();
+
+        let mut mauled = c.clone();
+        mauled.label = b"round 8".to_vec();
+        assert!(mauled.verify().is_err());
+        assert!(decryption_share(&shares[0], &mauled).is_err());
+        mauled = c.clone();
+        mauled.cipher[0] ^= 1;
+        assert!(mauled.verify().is_err());
+        c.verify().unwrap();
+    }
+}
@@ -0,0 +1,341 @@
+//! Canonical encoding of the data which is hashed and signed by the protocols.
+//!
+//! The encoding does not depend on any serialization library so that hashes
+//! and signatures are stable across versions and platforms. It starts with
+//! ENCODING_VERSION and a domain tag, followed by the fields in order:
+//!  - u8 and bool as one byte, u32 as 4 and u64 as 8 little endian bytes;
+//!  - byte strings prefixed by their length as a u32;
+//!  - lists prefixed by their number of elements as a u32, each element being a
+//!    length prefixed byte string.
+
+use core::convert::TryInto;
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "blake3")]
+use sha2::Sha512;
+use sha2::{Digest, Sha256};
+
+/// Version of the canonical encoding, written first in every encoding.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// Version of the protocol messages, bound to the hashes and signatures of a
+/// Domain.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// HashSuite is the set of hash functions of the sessions: the hash of the
+/// commitments, the hash of the session ids and the XOF deriving the context
+/// of the deal encryption. Its id is bound to the session ids unless it is
+/// the default suite, so that participants configured with different suites
+/// fail to agree on any session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashSuite {
+    /// SHA-256 hashes and the blake2xb XOF, the suite of the previous versions.
+    Blake2,
+    /// Blake3 commitment hash and XOF, SHA-512 session hash.
+    #[cfg(feature = "blake3")]
+    Blake3Sha512,
+}
+
+impl Default for HashSuite {
+    fn default() -> Self {
+        HashSuite::Blake2
+    }
+}
+
+impl HashSuite {
+    /// all returns the suites built in this version of the library.
+    pub fn all() -> &'static [HashSuite] {
+        &[
+            HashSuite::Blake2,
+            #[cfg(feature = "blake3")]
+            HashSuite::Blake3Sha512,
+        ]
+    }
+
+    /// id returns the identifier of the suite bound to the session ids.
+    pub fn id(&self) -> &'static str {
+        match self {
+            HashSuite::Blake2 => "sha256-blake2xb",
+            #[cfg(feature = "blake3")]
+            HashSuite::Blake3Sha512 => "blake3-sha512",
+        }
+    }
+
+    /// commitment_hash returns the 32 bytes hash of the encoded commitments,
+    /// e.g. the nodes of their Merkle tree.
+    pub fn commitment_hash(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            HashSuite::Blake2 => to_array(&Sha256::digest(data)),
+            #[cfg(feature = "blake3")]
+            HashSuite::Blake3Sha512 => *blake3::hash(data).as_bytes(),
+        }
+    }
+
+    /// session_hash returns the 32 bytes hash of an encoded session id, the
+    /// first half of SHA-512 for Blake3Sha512.
+    pub fn session_hash(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            HashSuite::Blake2 => to_array(&Sha256::digest(data)),
+            #[cfg(feature = "blake3")]
+            HashSuite::Blake3Sha512 => to_array(&Sha512::digest(data)[..32]),
+        }
+    }
+
+    /// xof returns len bytes of the output of the XOF keyed with key over
+    /// data. Blake2 is blake2xb of unknown output length, whose key is at
+    /// most 64 bytes.
+    pub fn xof(&self, key: &[u8], data: &[u8], len: usize) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::with_capacity(len);
+        match self {
+            HashSuite::Blake2 => {
+                let mut hash = blake2b::Blake2xb::keyed(None, key);
+                hash.update(data);
+                for block in hash.finish() {
+                    if out.len() >= len {
+                        break;
+                    }
+                    out.extend_from_slice(&block);
+                }
+                out.truncate(len);
+            }
+            #[cfg(feature = "blake3")]
+            HashSuite::Blake3Sha512 => {
+                out.resize(len, 0);
+                blake3::Hasher::new()
+                    .update(&(key.len() as u32).to_le_bytes())
+                    .update(key)
+                    .update(data)
+                    .finalize_xof()
+                    .fill(&mut out);
+            }
+        }
+        out
+    }
+}
+
+/// HDerivation is the derivation of the second generator H of the
+/// commitments from the keys of the verifiers, see vss::derive_h_in. Its id is
+/// bound to the session ids unless it is Legacy, the derivation of the
+/// previous versions, so that participants deriving different generators fail
+/// to agree on any session. The default is HashToGroup, or Legacy with the
+/// `legacy-derive-h` feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HDerivation {
+    /// The RFC 9380 CurvePoint::hash_to_group of the curve.
+    HashToGroup,
+    /// The rejection sampling of CurvePoint::hash_to_point, the derivation
+    /// of kyber and of the previous versions.
+    Legacy,
+}
+
+impl Default for HDerivation {
+    fn default() -> Self {
+        if cfg!(feature = "legacy-derive-h") {
+            HDerivation::Legacy
+        } else {
+            HDerivation::HashToGroup
+        }
+    }
+}
+
+impl HDerivation {
+    /// id returns the identifier of the derivation bound to the session ids.
+    pub fn id(&self) -> &'static str {
+        match self {
+            HDerivation::HashToGroup => "rfc9380",
+            HDerivation::Legacy => "legacy",
+        }
+    }
+}
+
+fn to_array(hash: &[u8]) -> [u8; 32] {
+    hash.try_into().expect("Slice with incorrect length")
+}
+
+/// Domain separates the session ids, hashes and signatures of a deployment
+/// from the ones of another deployment, or protocol, using the same longterm
+/// keys. The default domain has an empty tag and gives the hashes and
+/// signatures of the library without domains, for compatibility.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Domain {
+    tag: Vec<u8>,
+    // hash functions of the sessions of the domain
+    #[serde(default)]
+    suite: HashSuite,
+    // derivation of the second generator of the commitments
+    #[serde(default)]
+    h_derivation: HDerivation,
+}
+
+impl Domain {
+    pub fn new(tag: &[u8]) -> Domain {
+        Domain {
+            tag: tag.to_vec(),
+            suite: HashSuite::default(),
+            h_derivation: HDerivation::default(),
+        }
+    }
+
+    /// with_suite returns the domain hashing its sessions with suite.
+    pub fn with_suite(mut self, suite: HashSuite) -> Domain {
+        self.suite = suite;
+        self
+    }
+
+    /// with_h_derivation returns the domain deriving the second generator of
+    /// the commitments of its sessions with h_derivation.
+    pub fn with_h_derivation(mut self, h_derivation: HDerivation) -> Domain {
+        self.h_derivation = h_derivation;
+        self
+    }
+
+    pub fn tag(&self) -> &[u8] {
+        &self.tag
+    }
+
+    pub fn hash_suite(&self) -> HashSuite {
+        self.suite
+    }
+
+    pub fn h_derivation(&self) -> HDerivation {
+        self.h_derivation
+    }
+
+    /// encoder starts the encoding of a message of the domain with the given
+    /// tag, followed by PROTOCOL_VERSION and the tag of the domain unless it
+    /// is the default one.
+    pub fn encoder(&self, tag: &[u8]) -> Encoder {
+        if self.tag.is_empty() {
+            return Encoder::new(tag);
+        }
+        Encoder::new(&[&b"domain/"[..], tag].concat())
+            .put_u32(PROTOCOL_VERSION)
+            .put_bytes(&self.tag)
+    }
+
+    /// signing_context returns the context of the signatures of the domain
+    /// issued by the participant at index, the little endian index for the
+    /// default domain.
+    pub fn signing_context(&self, index: u32) -> Vec<u8> {
+        if self.tag.is_empty() {
+            return index.to_le_bytes().to_vec();
+        }
+        self.encoder(b"signingcontext").put_u32(index).into_bytes()
+    }
+}
+
+/// Encoder builds the canonical encoding of a message.
+#[derive(Clone, Debug)]
+pub struct Encoder {
+    buff: Vec<u8>,
+}
+
+impl Encoder {
+    /// new starts the encoding of a message with the given domain tag.
+    pub fn new(tag: &[u8]) -> Encoder {
+        Encoder {
+            buff: vec![ENCODING_VERSION],
+        }
+        .put_bytes(tag)
+    }
+
+    pub fn put_u8(mut self, v: u8) -> Encoder {
+        self.buff.push(v);
+        self
+    }
+
+    pub fn put_bool(self, v: bool) -> Encoder {
+        self.put_u8(u8::from(v))
+    }
+
+    pub fn put_u32(mut self, v: u32) -> Encoder {
+        self.buff.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn put_u64(mut self, v: u64) -> Encoder {
+        self.buff.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn put_bytes(self, v: &[u8]) -> Encoder {
+        let mut enc = self.put_u32(v.len() as u32);
+        enc.buff.extend_from_slice(v);
+        enc
+    }
+
+    pub fn put_list<T: AsRef<[u8]>>(self, list: &[T]) -> Encoder {
+        let enc = self.put_u32(list.len() as u32);
+        list.iter().fold(enc, |enc, v| enc.put_bytes(v.as_ref()))
+    }
+
+    /// into_bytes returns the encoded message.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buff
+    }
+
+    /// hash returns the SHA-256 hash of the encoded message.
+    pub fn hash(self) -> [u8; 32] {
+        to_array(&Sha256::digest(&self.buff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoder_layout() {
+        let buff: Vec<u8> = Encoder::new(b"tag")
+            .put_u8(7)
+            .put_bool(true)
+            .put_u32(0x0102_0304)
+            .put_bytes(&[0xaa, 0xbb])
+            .put_list(&[vec![0xcc], vec![]])
+            .into_bytes();
+        #[rustfmt::skip]
+        let expected: Vec<u8> = vec![
+            ENCODING_VERSION,
+            3, 0, 0, 0, b't', b'a', b'g',
+            7,
+            1,
+            4, 3, 2, 1,
+            2, 0, 0, 0, 0xaa, 0xbb,
+            2, 0, 0, 0, 1, 0, 0, 0, 0xcc, 0, 0, 0, 0,
+        ];
+        assert_eq!(expected, buff);
+    }
+
+    #[test]
+    fn test_domain() {
+        let default = Domain::default();
+        assert_eq!(
+            Encoder::new(b"tag").put_u32(1).hash(),
+            default.encoder(b"tag").put_u32(1).hash()
+        );
+        assert_eq!(7u32.to_le_bytes().to_vec(), default.signing_context(7));
+
+        let a = Domain::new(b"deployment a");
+        let b = Domain::new(b"deployment b");
+        assert_ne!(default.encoder(b"tag").hash(), a.encoder(b"tag").hash());
+        assert_ne!(a.encoder(b"tag").hash(), b.encoder(b"tag").hash());
+        assert_ne!(a.signing_context(7), b.signing_context(7));
+        assert_ne!(a.signing_context(7), a.signing_context(8));
+    }
+
+    #[test]
+    fn test_encoder_no_ambiguity() {
+        // moving bytes between adjacent fields changes the encoding
+        let h1 = Encoder::new(b"tag").put_bytes(b"ab").put_bytes(b"c").hash();
+        let h2 = Encoder::new(b"tag").put_bytes(b"a").put_bytes(b"bc").hash();
+        assert_ne!(h1, h2);
+        let h3 = Encoder::new(b"tagab").put_bytes(b"c").hash();
+        let h4 = Encoder::new(b"tag")
+            .put_list(&[&b"ab"[..], &b"c"[..]])
+            .hash();
+        assert_ne!(h1, h3);
+        assert_ne!(h1, h4);
+    }
+}
@@ -0,0 +1,240 @@
+//! Envelope of the messages carried by the transports.
+//!
+//! An Envelope holds the version of the message format, the session, the type
+//! of the message, the index of its sender and the encoded message, signed by
+//! the sender. A transport can route and authenticate the messages from the
+//! envelope alone, and a receiver reads the version and the type before
+//! decoding the payload: a message of a future version or of an unknown type
+//! gives an error naming them instead of a failed deserialization.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::signer;
+use crate::transport;
+use crate::wire;
+
+use curve_traits::CurvePoint;
+use encoding::Encoder;
+use serde::{Deserialize, Serialize};
+use signer::{Signer, SoftwareSigner};
+use transport::Message;
+
+/// Version of the envelope and message format. Envelopes of any other version
+/// are rejected.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// MessageType is the type of the message of an envelope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageType {
+    Deal = 1,
+    Response = 2,
+    Justification = 3,
+    SecretCommits = 4,
+    ComplaintCommits = 5,
+    ReconstructCommits = 6,
+    Echo = 7,
+}
+
+impl MessageType {
+    /// of returns the type of a message.
+    pub fn of<P: CurvePoint>(msg: &Message<P>) -> MessageType {
+        match msg {
+            Message::Deal(_) => MessageType::Deal,
+            Message::Response(_) => MessageType::Response,
+            Message::Justification(_) => MessageType::Justification,
+            Message::SecretCommits(_) => MessageType::SecretCommits,
+            Message::ComplaintCommits(_) => MessageType::ComplaintCommits,
+            Message::ReconstructCommits(_) => MessageType::ReconstructCommits,
+            Message::Echo(_) => MessageType::Echo,
+        }
+    }
+
+    /// from_u16 returns the type of the tag, or None if it is unknown.
+    pub fn from_u16(tag: u16) -> Option<MessageType> {
+        match tag {
+            1 => Some(MessageType::Deal),
+            2 => Some(MessageType::Response),
+            3 => Some(MessageType::Justification),
+            4 => Some(MessageType::SecretCommits),
+            5 => Some(MessageType::ComplaintCommits),
+            6 => Some(MessageType::ReconstructCommits),
+            7 => Some(MessageType::Echo),
+            _ => None,
+        }
+    }
+}
+
+/// Envelope is a message signed by its sender.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    // Version of the format, ENVELOPE_VERSION
+    pub version: u32,
+    // Session of the message, e.g. the hash of the group::GroupConfig
+    #[serde(with = "crate::serde_hex")]
+    pub session_id: Vec<u8>,
+    // MessageType of the payload, kept as a tag to report unknown types
+    pub msg_type: u16,
+    // Message encoded with bincode
+    #[serde(with = "crate::serde_hex")]
+    pub payload: Vec<u8>,
+    // Index of the sender in the list of participants
+    pub sender_index: u32,
+    // Signature of the sender over all the other fields
+    #[serde(with = "crate::serde_hex")]
+    pub signature: Vec<u8>,
+}
+
+impl Envelope {
+    /// seal returns the envelope of the message of the participant at
+    /// sender_index, of private key longterm, in the session.
+    pub fn seal<P: CurvePoint>(
+        longterm: &P::Scalar,
+        sender_index: u32,
+        session_id: &[u8],
+        msg: &Message<P>,
+    ) -> Result<Envelope, Box<dyn Error>> {
+        Envelope::seal_with_signer(
+            &SoftwareSigner::new(*longterm),
+            sender_index,
+            session_id,
+            msg,
+        )
+    }
+
+    /// seal_with_signer is seal, signing with the given Signer.
+    pub fn seal_with_signer<P: CurvePoint, K: Signer<P>>(
+        signer: &K,
+        sender_index: u32,
+        session_id: &[u8],
+        msg: &Message<P>,
+    ) -> Result<Envelope, Box<dyn Error>> {
+        let mut envelope = Envelope {
+            version: ENVELOPE_VERSION,
+            session_id: session_id.to_vec(),
+            msg_type: MessageType::of(msg) as u16,
+            payload: bincode::serialize(msg)?,
+            sender_index,
+            signature: Vec::new(),
+        };
+        envelope.signature = signer.sign(&envelope.hash(), &[])?;
+        Ok(envelope)
+    }
+
+    /// hash returns the hash signed by the sender.
+    pub fn hash(&self) -> [u8; 32] {
+        Encoder::new(b"envelope")
+            .put_u32(self.version)
+            .put_bytes(&self.session_id)
+            .put_u32(self.msg_type as u32)
+            .put_bytes(&self.payload)
+            .put_u32(self.sender_index)
+            .hash()
+    }
+
+    /// message_type returns the type of the message, or an error if the
+    /// version or the type are unknown.
+    pub fn message_type(&self) -> Result<MessageType, Box<dyn Error>> {
+        if self.version != ENVELOPE_VERSION {
+            bail!(
+                "envelope: unsupported version {}, expected {}",
+                self.version,
+                ENVELOPE_VERSION
+            );
+        }
+        MessageType::from_u16(self.msg_type)
+            .ok_or_else(|| simple_error!("envelope: unknown message type {}", self.msg_type).into())
+    }
+
+    /// open checks the envelope, of the given session, against the public
+    /// keys of the participants and returns its message.
+    pub fn open<P: CurvePoint>(
+        &self,
+        participants: &[P],
+        session_id: &[u8],
+    ) -> Result<Message<P>, Box<dyn Error>> {
+        let msg_type = self.message_type()?;
+        if self.session_id != session_id {
+            bail!("envelope: message of another session");
+        }
+        let sender = participants
+            .get(self.sender_index as usize)
+            .ok_or_else(|| simple_error!("envelope: sender index out of bounds"))?;
+        P::verify_signature(sender, &self.signature, &self.hash(), &[])
+            .map_err(|e| simple_error!("envelope: invalid signature: {}", e))?;
+        let msg: Message<P> = wire::decode_message(&self.payload, participants.len() as u32)?;
+        if MessageType::of(&msg) != msg_type {
+            bail!("envelope: payload not of type {:?}", msg_type);
+        }
+        Ok(msg)
+    }
+
+    /// to_bytes returns the encoding of the envelope.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// from_bytes decodes an envelope received from the network, without
+    /// checking it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Envelope, Box<dyn Error>> {
+        wire::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg;
+    use crate::ristretto_curve::{FE, GE};
+    use crate::sim;
+    use crate::vss::Response;
+
+    #[test]
+    fn test_envelope() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(3);
+        let msg: Message = Message::Response(dkg::Response {
+            index: 0,
+            response: Response {
+                session_id: vec![0xab; 32],
+                index: 1,
+                approved: true,
+                signature: vec![0x01; 64],
+                reason: None,
+            },
+        });
+        let envelope = Envelope::seal(&secs[1], 1, b"session", &msg).unwrap();
+        assert_eq!(
+            Some(MessageType::Response),
+            MessageType::from_u16(envelope.msg_type)
+        );
+        let envelope = Envelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap();
+        match envelope.open(&pubs, b"session").unwrap() {
+            Message::Response(r) => assert_eq!(1, r.response.index),
+            _ => panic!("not a response"),
+        }
+        envelope
+            .open(&pubs, b"other session")
+            .expect_err("Must fail, other session");
+
+        let mut forged = envelope.clone();
+        forged.sender_index = 2;
+        forged
+            .open(&pubs, b"session")
+            .expect_err("Must fail, other sender");
+        let mut forged = envelope.clone();
+        forged.msg_type = MessageType::Deal as u16;
+        forged
+            .open(&pubs, b"session")
+            .expect_err("Must fail, other type");
+
+        let mut future = envelope.clone();
+        future.version = ENVELOPE_VERSION + 1;
+        let err = future.open(&pubs, b"session").unwrap_err();
+        assert!(err.to_string().contains("unsupported version"));
+        let mut unknown = envelope;
+        unknown.msg_type = 42;
+        let err = unknown.message_type().unwrap_err();
+        assert!(err.to_string().contains("unknown message type 42"));
+    }
+}
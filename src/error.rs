@@ -0,0 +1,66 @@
+//! Errors of the library
+//!
+//! Fallible functions return a `Box<dyn Error>`. With the std feature, Error is
+//! std::error::Error. Without it, Error is the trait below, which only needs
+//! Debug and Display, and the errors of the library are SimpleErrors.
+
+#[cfg(feature = "std")]
+pub use std::error::Error;
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+/// Error is the no_std counterpart of std::error::Error.
+#[cfg(not(feature = "std"))]
+pub trait Error: fmt::Debug + fmt::Display {}
+
+/// SimpleError is an error holding its message.
+#[cfg(not(feature = "std"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimpleError {
+    msg: String,
+}
+
+#[cfg(not(feature = "std"))]
+impl SimpleError {
+    pub fn new<S: Into<String>>(msg: S) -> SimpleError {
+        SimpleError { msg: msg.into() }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.msg
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl fmt::Display for SimpleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Error for SimpleError {}
+
+#[cfg(not(feature = "std"))]
+impl<'a, E: Error + 'a> From<E> for Box<dyn Error + 'a> {
+    fn from(err: E) -> Box<dyn Error + 'a> {
+        Box::new(err)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a> From<String> for Box<dyn Error + 'a> {
+    fn from(msg: String) -> Box<dyn Error + 'a> {
+        Box::new(SimpleError::new(msg))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a> From<&str> for Box<dyn Error + 'a> {
+    fn from(msg: &str) -> Box<dyn Error + 'a> {
+        Box::new(SimpleError::new(msg))
+    }
+}
@@ -0,0 +1,488 @@
+//! Verifiable encryption of vss shares to a recovery key, for disaster
+//! recovery policies.
+//!
+//! The share s of a deal is encrypted bit by bit with ElGamal in the exponent
+//! under the recovery key Y: the j-th bit b_j gives (r_j * G, b_j * G + r_j * Y)
+//! along with a proof that b_j is 0 or 1. Weighted by the value w_j of the
+//! bits, the ciphertexts sum to an encryption of s, which a proof shows to be
+//! the secret share committed in the deal: with C = s * G + g * H the Pedersen
+//! commitment of the share, sum(w_j * r_j * G) = R * G and
+//! sum(w_j * (b_j * G + r_j * Y)) - C = R * Y - g * H. Anyone knowing the
+//! commitments of the deal checks the encryption, and the holder of the
+//! recovery key decrypts the bits one by one.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::utils;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use encoding::{Domain, Encoder};
+use poly::{PriShare, PubPoly};
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use vss::Deal;
+
+/// BitProof proves that an ElGamal ciphertext encrypts 0 or 1 in the exponent.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BitProof<P: CurvePoint = GE> {
+    pub c0: P::Scalar,
+    pub c1: P::Scalar,
+    pub z0: P::Scalar,
+    pub z1: P::Scalar,
+}
+
+/// BitCiphertext is the encryption of a bit of the share.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BitCiphertext<P: CurvePoint = GE> {
+    // r * G
+    pub u: P,
+    // b * G + r * Y
+    pub v: P,
+    pub proof: BitProof<P>,
+}
+
+/// EncryptedShare is the share of a verifier encrypted to the recovery key,
+/// with the proof that it is the share committed in the deal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct EncryptedShare<P: CurvePoint = GE> {
+    pub session_id: Vec<u8>,
+    // Index of the verifier
+    pub index: u32,
+    pub bits: Vec<BitCiphertext<P>>,
+    // proof of the randomness R and of the blinding g
+    pub c: P::Scalar,
+    pub z_r: P::Scalar,
+    pub z_g: P::Scalar,
+}
+
+/// encrypt_share returns the share of the deal encrypted to the recovery key.
+/// verifiers are the longterm keys of the verifiers of the vss run. The dealer
+/// can call it for every deal, or a verifier for its own deal.
+pub fn encrypt_share<P: CurvePoint>(
+    recovery_key: &P,
+    deal: &Deal<P>,
+    verifiers: &[P],
+) -> Result<EncryptedShare<P>, Box<dyn Error>> {
+    encrypt_share_with_rng(recovery_key, deal, verifiers, &mut utils::rand_hack())
+}
+
+/// Same as encrypt_share, with the randomness drawn from rng.
+pub fn encrypt_share_with_rng<P: CurvePoint, R: RngCore + CryptoRng>(
+    recovery_key: &P,
+    deal: &Deal<P>,
+    verifiers: &[P],
+    rng: &mut R,
+) -> Result<EncryptedShare<P>, Box<dyn Error>> {
+    encrypt_share_in(&Domain::default(), recovery_key, deal, verifiers, rng)
+}
+
+/// encrypt_share_in is encrypt_share_with_rng for a deal of a session of the
+/// given domain.
+pub fn encrypt_share_in<P: CurvePoint, R: RngCore + CryptoRng>(
+    domain: &Domain,
+    recovery_key: &P,
+    deal: &Deal<P>,
+    verifiers: &[P],
+    rng: &mut R,
+) -> Result<EncryptedShare<P>, Box<dyn Error>> {
+    let generator = P::generator();
+    let h: P = vss::derive_h_in(domain.h_derivation(), verifiers)?;
+    let index = deal.sec_share.i;
+    let weights: Vec<P::Scalar> = bit_weights::<P>()?;
+    let bytes: Vec<u8> = deal.sec_share.v.to_bytes();
+
+    let mut bits: Vec<BitCiphertext<P>> = Vec::with_capacity(weights.len());
+    let mut sum_r: P::Scalar = ECScalar::zero();
+    for (j, w) in weights.iter().enumerate() {
+        let bit = bytes[j / 8] >> (j % 8) & 1 == 1;
+        let r: P::Scalar = ECScalar::new_random_with_rng(rng);
+        let u: P = generator.scalar_mul(&r.get_element());
+        let mut v: P = recovery_key.scalar_mul(&r.get_element());
+        if bit {
+            v = v.add_point(&generator.get_element());
+        }
+        let proof = prove_bit(
+            recovery_key,
+            &deal.session_id,
+            index,
+            j,
+            &u,
+            &v,
+            bit,
+            &r,
+            rng,
+        );
+        bits.push(BitCiphertext { u, v, proof });
+        sum_r = sum_r.add(&w.mul(&r.get_element()).get_element());
+    }
+
+    // proof of knowledge of R and g
+    let (u, v) = weighted_sum(&bits, &weights);
+    let c_i: P = commitment(deal, index)?;
+    let k_r: P::Scalar = ECScalar::new_random_with_rng(rng);
+    let k_g: P::Scalar = ECScalar::new_random_with_rng(rng);
+    let t1: P = generator.scalar_mul(&k_r.get_element());
+    let t2: P = recovery_key
+        .scalar_mul(&k_r.get_element())
+        .sub_point(&h.scalar_mul(&k_g.get_element()).get_element());
+    let mut enc = EncryptedShare {
+        session_id: deal.session_id.clone(),
+        index,
+        bits,
+        c: ECScalar::zero(),
+        z_r: ECScalar::zero(),
+        z_g: ECScalar::zero(),
+    };
+    let a2: P = v.sub_point(&c_i.get_element());
+    enc.c = share_challenge(recovery_key, &enc, &u, &a2, &t1, &t2);
+    enc.z_r = k_r.add(&enc.c.mul(&sum_r.get_element()).get_element());
+    enc.z_g = k_g.add(&enc.c.mul(&deal.rnd_share.v.get_element()).get_element());
+    Ok(enc)
+}
+
+/// verify_encrypted_share checks that the encrypted share is the share of the
+/// verifier at its index committed with commitments in the deal of the
+/// session, and decryptable with the recovery key.
+pub fn verify_encrypted_share<P: CurvePoint>(
+    recovery_key: &P,
+    commitments: &[Vec<u8>],
+    verifiers: &[P],
+    session_id: &[u8],
+    enc: &EncryptedShare<P>,
+) -> Result<(), Box<dyn Error>> {
+    verify_encrypted_share_in(
+        &Domain::default(),
+        recovery_key,
+        commitments,
+        verifiers,
+        session_id,
+        enc,
+    )
+}
+
+/// verify_encrypted_share_in is verify_encrypted_share for a session of the
+/// given domain.
+pub fn verify_encrypted_share_in<P: CurvePoint>(
+    domain: &Domain,
+    recovery_key: &P,
+    commitments: &[Vec<u8>],
+    verifiers: &[P],
+    session_id: &[u8],
+    enc: &EncryptedShare<P>,
+) -> Result<(), Box<dyn Error>> {
+    if enc.session_id != session_id {
+        bail!("escrow: encrypted share of another session");
+    }
+    if enc.index as usize >= verifiers.len() {
+        bail!("escrow: index out of bounds in encrypted share");
+    }
+    let weights: Vec<P::Scalar> = bit_weights::<P>()?;
+    if enc.bits.len() != weights.len() {
+        bail!("escrow: wrong number of bits in encrypted share");
+    }
+    for (j, bit) in enc.bits.iter().enumerate() {
+        verify_bit(recovery_key, session_id, enc.index, j, bit)?;
+    }
+
+    let generator = P::generator();
+    let h: P = vss::derive_h_in(domain.h_derivation(), verifiers)?;
+    let commits: Vec<P> = commitments
+        .iter()
+        .map(|c| P::from_bytes(c))
+        .collect::<Result<_, _>>()?;
+    let c_i: P = PubPoly::new(generator, commits).eval(enc.index).v;
+    let (u, v) = weighted_sum(&enc.bits, &weights);
+    let a2: P = v.sub_point(&c_i.get_element());
+    // T1 = z_r * G - c * U and T2 = z_r * Y - z_g * H - c * (V - C)
+    let t1: P = generator
+        .scalar_mul(&enc.z_r.get_element())
+        .sub_point(&u.scalar_mul(&enc.c.get_element()).get_element());
+    let t2: P = recovery_key
+        .scalar_mul(&enc.z_r.get_element())
+        .sub_point(&h.scalar_mul(&enc.z_g.get_element()).get_element())
+        .sub_point(&a2.scalar_mul(&enc.c.get_element()).get_element());
+    if share_challenge(recovery_key, enc, &u, &a2, &t1, &t2) != enc.c {
+        bail!("escrow: invalid proof of the encrypted share");
+    }
+    Ok(())
+}
+
+/// decrypt_share returns the share encrypted to the recovery key. The
+/// encrypted share must have been verified.
+pub fn decrypt_share<P: CurvePoint>(
+    recovery_secret: &P::Scalar,
+    enc: &EncryptedShare<P>,
+) -> Result<PriShare<P::Scalar>, Box<dyn Error>> {
+    let generator = P::generator();
+    let identity = P::identity();
+    let mut bytes: Vec<u8> = vec![0u8; (enc.bits.len() + 7) / 8];
+    for (j, bit) in enc.bits.iter().enumerate() {
+        let m: P = bit.v.sub_point(
+            &bit.u
+                .scalar_mul(&recovery_secret.get_element())
+                .get_element(),
+        );
+        if m == generator {
+            bytes[j / 8] |= 1 << (j % 8);
+        } else if m != identity {
+            bail!("escrow: bit {} doesn't decrypt to 0 or 1", j);
+        }
+    }
+    Ok(PriShare {
+        i: enc.index,
+        v: ECScalar::from_bytes(&bytes)?,
+    })
+}
+
+// bit_weights returns the value of each bit of the encoding of the scalars.
+fn bit_weights<P: CurvePoint>() -> Result<Vec<P::Scalar>, Box<dyn Error>> {
+    let size: usize = P::Scalar::zero().to_bytes().len();
+    (0..size * 8)
+        .map(|j| {
+            let mut bytes = vec![0u8; size];
+            bytes[j / 8] = 1 << (j % 8);
+            ECScalar::from_bytes(&bytes)
+        })
+        .collect()
+}
+
+fn weighted_sum<P: CurvePoint>(bits: &[BitCiphertext<P>], weights: &[P::Scalar]) -> (P, P) {
+    bits.iter()
+        .zip(weights.iter())
+        .fold((P::identity(), P::identity()), |(u, v), (bit, w)| {
+            (
+                u.add_point(&bit.u.scalar_mul(&w.get_element()).get_element()),
+                v.add_point(&bit.v.scalar_mul(&w.get_element()).get_element()),
+            )
+        })
+}
+
+// commitment returns the Pedersen commitment of the share at index.
+fn commitment<P: CurvePoint>(deal: &Deal<P>, index: u32) -> Result<P, Box<dyn Error>> {
+    let commits: Vec<P> = deal
+        .commitments
+        .iter()
+        .map(|c| P::from_bytes(c))
+        .collect::<Result<_, _>>()?;
+    Ok(PubPoly::new(P::generator(), commits).eval(index).v)
+}
+
+fn bit_challenge<P: CurvePoint>(
+    recovery_key: &P,
+    session_id: &[u8],
+    index: u32,
+    j: usize,
+    points: &[&P],
+) -> P::Scalar {
+    let points: Vec<Vec<u8>> = points.iter().map(|p| p.pk_to_key_slice()).collect();
+    let bytes = Encoder::new(b"escrowbit")
+        .put_bytes(&recovery_key.pk_to_key_slice())
+        .put_bytes(session_id)
+        .put_u32(index)
+        .put_u32(j as u32)
+        .put_list(&points)
+        .into_bytes();
+    P::hash_to_scalar(&bytes)
+}
+
+// prove_bit returns the proof that (u, v) encrypts bit: a proof of equality of
+// the discrete logs of u and v - bit * G for the real branch, and a simulated
+// one for the other branch.
+#[allow(clippy::too_many_arguments)]
+fn prove_bit<P: CurvePoint, R: RngCore + CryptoRng>(
+    recovery_key: &P,
+    session_id: &[u8],
+    index: u32,
+    j: usize,
+    u: &P,
+    v: &P,
+    bit: bool,
+    r: &P::Scalar,
+    rng: &mut R,
+) -> BitProof<P> {
+    let generator = P::generator();
+    // v - b * G for b = 0, 1
+    let targets: [P; 2] = [*v, v.sub_point(&generator.get_element())];
+    let (real, fake) = if bit { (1, 0) } else { (0, 1) };
+
+    let c_fake: P::Scalar = ECScalar::new_random_with_rng(rng);
+    let z_fake: P::Scalar = ECScalar::new_random_with_rng(rng);
+    let k: P::Scalar = ECScalar::new_random_with_rng(rng);
+    let mut a: [P; 2] = [P::identity(); 2];
+    let mut b: [P; 2] = [P::identity(); 2];
+    a[real] = generator.scalar_mul(&k.get_element());
+    b[real] = recovery_key.scalar_mul(&k.get_element());
+    a[fake] = generator
+        .scalar_mul(&z_fake.get_element())
+        .sub_point(&u.scalar_mul(&c_fake.get_element()).get_element());
+    b[fake] = recovery_key.scalar_mul(&z_fake.get_element()).sub_point(
+        &targets[fake]
+            .scalar_mul(&c_fake.get_element())
+            .get_element(),
+    );
+
+    let c: P::Scalar = bit_challenge(
+        recovery_key,
+        session_id,
+        index,
+        j,
+        &[u, v, &a[0], &b[0], &a[1], &b[1]],
+    );
+    let c_real: P::Scalar = c.sub(&c_fake.get_element());
+    let z_real: P::Scalar = k.add(&c_real.mul(&r.get_element()).get_element());
+    let mut cs: [P::Scalar; 2] = [c_fake; 2];
+    let mut zs: [P::Scalar; 2] = [z_fake; 2];
+    cs[real] = c_real;
+    zs[real] = z_real;
+    BitProof {
+        c0: cs[0],
+        c1: cs[1],
+        z0: zs[0],
+        z1: zs[1],
+    }
+}
+
+fn verify_bit<P: CurvePoint>(
+    recovery_key: &P,
+    session_id: &[u8],
+    index: u32,
+    j: usize,
+    bit: &BitCiphertext<P>,
+) -> Result<(), Box<dyn Error>> {
+    let generator = P::generator();
+    let targets: [P; 2] = [bit.v, bit.v.sub_point(&generator.get_element())];
+    let cs = [bit.proof.c0, bit.proof.c1];
+    let zs = [bit.proof.z0, bit.proof.z1];
+    let mut a: [P; 2] = [P::identity(); 2];
+    let mut b: [P; 2] = [P::identity(); 2];
+    for i in 0..2 {
+        a[i] = generator
+            .scalar_mul(&zs[i].get_element())
+            .sub_point(&bit.u.scalar_mul(&cs[i].get_element()).get_element());
+        b[i] = recovery_key
+            .scalar_mul(&zs[i].get_element())
+            .sub_point(&targets[i].scalar_mul(&cs[i].get_element()).get_element());
+    }
+    let c: P::Scalar = bit_challenge(
+        recovery_key,
+        session_id,
+        index,
+        j,
+        &[&bit.u, &bit.v, &a[0], &b[0], &a[1], &b[1]],
+    );
+    if c != cs[0].add(&cs[1].get_element()) {
+        bail!("escrow: invalid proof for bit {}", j);
+    }
+    Ok(())
+}
+
+fn share_challenge<P: CurvePoint>(
+    recovery_key: &P,
+    enc: &EncryptedShare<P>,
+    u: &P,
+    a2: &P,
+    t1: &P,
+    t2: &P,
+) -> P::Scalar {
+    let bits: Vec<Vec<u8>> = enc
+        .bits
+        .iter()
+        .map(|b| [b.u.pk_to_key_slice(), b.v.pk_to_key_slice()].concat())
+        .collect();
+    let points: Vec<Vec<u8>> = [u, a2, t1, t2]
+        .iter()
+        .map(|p| p.pk_to_key_slice())
+        .collect();
+    let bytes = Encoder::new(b"escrowshare")
+        .put_bytes(&recovery_key.pk_to_key_slice())
+        .put_bytes(&enc.session_id)
+        .put_u32(enc.index)
+        .put_list(&bits)
+        .put_list(&points)
+        .into_bytes();
+    P::hash_to_scalar(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use crate::vss::Dealer;
+
+    #[test]
+    fn test_escrow_share() {
+        let (_, verifiers) = sim::new_participants::<GE>(5);
+        let (dealer_sec, _) = sim::new_participants::<GE>(1);
+        let (recovery_sec, recovery_pub) = sim::new_participants::<GE>(1);
+        let (recovery_sec, recovery_pub) = (recovery_sec[0], recovery_pub[0]);
+        let secret: FE = ECScalar::new_random();
+        let dealer: Dealer = Dealer::new(dealer_sec[0], secret, verifiers.clone(), 3).unwrap();
+        let deal = &dealer.deals[2];
+        let session_id = deal.session_id.clone();
+        let enc = encrypt_share(&recovery_pub, deal, &verifiers).unwrap();
+        verify_encrypted_share(
+            &recovery_pub,
+            &deal.commitments,
+            &verifiers,
+            &session_id,
+            &enc,
+        )
+        .unwrap();
+        assert_eq!(deal.sec_share, decrypt_share(&recovery_sec, &enc).unwrap());
+
+        // bound to the index and to the recovery key
+        let mut forged = enc.clone();
+        forged.index = 1;
+        verify_encrypted_share(
+            &recovery_pub,
+            &deal.commitments,
+            &verifiers,
+            &session_id,
+            &forged,
+        )
+        .expect_err("Must fail, share of another index");
+        verify_encrypted_share(
+            &verifiers[0],
+            &deal.commitments,
+            &verifiers,
+            &session_id,
+            &enc,
+        )
+        .expect_err("Must fail, another recovery key");
+
+        // a share which isn't the committed one
+        let mut wrong = deal.clone();
+        wrong.sec_share.v = ECScalar::new_random();
+        let enc = encrypt_share(&recovery_pub, &wrong, &verifiers).unwrap();
+        verify_encrypted_share(
+            &recovery_pub,
+            &deal.commitments,
+            &verifiers,
+            &session_id,
+            &enc,
+        )
+        .expect_err("Must fail, share not matching the commitments");
+
+        // a bit which isn't 0 or 1
+        let mut enc = encrypt_share(&recovery_pub, deal, &verifiers).unwrap();
+        enc.bits[0].v = enc.bits[0].v.add_point(&GE::generator().get_element());
+        verify_encrypted_share(
+            &recovery_pub,
+            &deal.commitments,
+            &verifiers,
+            &session_id,
+            &enc,
+        )
+        .expect_err("Must fail, invalid bit");
+    }
+}
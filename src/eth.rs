@@ -0,0 +1,390 @@
+//! Export of a distributed BLS key for Ethereum validators.
+//!
+//! The key of a DKG run on G1 of BLS12-381 is a validator key: its public key
+//! is the 48 bytes compressed point expected in the deposit data, and every
+//! operator of the distributed validator stores its share in an EIP-2335
+//! keystore, as produced by the staking tooling. The keystores are encrypted
+//! with pbkdf2 and aes-128-ctr, and hold the public key of the share so that
+//! validator clients can match them with the public polynomial.
+//! Enabled with the `eth-keystore` feature.
+
+use std::error::Error;
+
+use crate::bls12_381_curve;
+use crate::curve_traits;
+use crate::dkg;
+use crate::utils;
+
+use aes::Aes128;
+use bls12_381_curve::{scalar_from_be_bytes, scalar_to_be_bytes, G1Point, FE};
+use ctr::cipher::stream::{NewStreamCipher, SyncStreamCipher};
+use curve_traits::{ECPoint, ECScalar};
+use dkg::DistKeyShare;
+use hmac::Hmac;
+use rand_core::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2_09::{Digest, Sha256};
+use unicode_normalization::UnicodeNormalization;
+use utils::bitwise_eq;
+
+/// Version of the EIP-2335 keystores
+pub const KEYSTORE_VERSION: u32 = 4;
+/// Default number of pbkdf2 rounds of the keystores
+pub const PBKDF2_ROUNDS: u32 = 262_144;
+
+const SALT_SIZE: usize = 32;
+const IV_SIZE: usize = 16;
+const DK_SIZE: usize = 32;
+
+type Aes128Ctr = ctr::Ctr128<Aes128>;
+
+/// validator_pubkey returns the compressed distributed public key, as
+/// expected in the deposit data of the validator.
+pub fn validator_pubkey(share: &DistKeyShare<G1Point>) -> Vec<u8> {
+    share.get_public_key().pk_to_key_slice()
+}
+
+/// share_pubkey returns the compressed public key of the share of the operator
+/// at index i.
+pub fn share_pubkey(share: &DistKeyShare<G1Point>, i: u32) -> Vec<u8> {
+    share.pub_share(i).v.pk_to_key_slice()
+}
+
+/// KeystoreModule is a step of the decryption of a keystore: the function
+/// run, its parameters and its hex encoded message.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeystoreModule<T> {
+    pub function: String,
+    pub params: T,
+    pub message: String,
+}
+
+/// KdfParams are the parameters of the pbkdf2 key derivation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub dklen: u32,
+    pub c: u32,
+    pub prf: String,
+    // hex encoded
+    pub salt: String,
+}
+
+/// ChecksumParams are the (empty) parameters of the sha256 checksum.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChecksumParams {}
+
+/// CipherParams are the parameters of the aes-128-ctr encryption.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CipherParams {
+    // hex encoded
+    pub iv: String,
+}
+
+/// KeystoreCrypto holds the encrypted secret of a keystore.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub kdf: KeystoreModule<KdfParams>,
+    pub checksum: KeystoreModule<ChecksumParams>,
+    pub cipher: KeystoreModule<CipherParams>,
+}
+
+/// Keystore is an EIP-2335 keystore holding a BLS secret key encrypted under
+/// a password.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub crypto: KeystoreCrypto,
+    #[serde(default)]
+    pub description: String,
+    // hex encoded compressed public key of the secret
+    pub pubkey: String,
+    // EIP-2334 derivation path of the key, empty if none
+    pub path: String,
+    pub uuid: String,
+    pub version: u32,
+}
+
+impl Keystore {
+    /// encrypt_share returns the keystore of the share of a distributed key,
+    /// encrypted under password. The keystore holds the public key of the
+    /// share and no derivation path.
+    pub fn encrypt_share(
+        share: &DistKeyShare<G1Point>,
+        password: &str,
+    ) -> Result<Keystore, Box<dyn Error>> {
+        let pri_share = share.get_pri_share();
+        let description = format!(
+            "Share {} of the distributed validator key {}",
+            pri_share.i,
+            hex::encode(validator_pubkey(share))
+        );
+        Keystore::encrypt(&pri_share.v, password, "", &description)
+    }
+
+    /// encrypt returns the keystore of secret, encrypted under password with
+    /// PBKDF2_ROUNDS rounds of pbkdf2.
+    pub fn encrypt(
+        secret: &FE,
+        password: &str,
+        path: &str,
+        description: &str,
+    ) -> Result<Keystore, Box<dyn Error>> {
+        Keystore::encrypt_with_rng(
+            secret,
+            password,
+            path,
+            description,
+            PBKDF2_ROUNDS,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as encrypt, with the given number of pbkdf2 rounds and the salt,
+    /// iv and uuid drawn from rng.
+    pub fn encrypt_with_rng<R: RngCore + CryptoRng>(
+        secret: &FE,
+        password: &str,
+        path: &str,
+        description: &str,
+        rounds: u32,
+        rng: &mut R,
+    ) -> Result<Keystore, Box<dyn Error>> {
+        if rounds == 0 {
+            bail!("eth: no pbkdf2 rounds");
+        }
+        let mut salt = [0u8; SALT_SIZE];
+        let mut iv = [0u8; IV_SIZE];
+        let mut uuid = [0u8; 16];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut iv);
+        rng.fill_bytes(&mut uuid);
+
+        let dk = derive_key(password, &salt, rounds);
+        // Ethereum encodes the secret keys as big endian integers
+        let mut cipher_msg = scalar_to_be_bytes(secret);
+        apply_cipher(&dk, &iv, &mut cipher_msg)?;
+        let pubkey: G1Point = G1Point::generator().scalar_mul(&secret.get_element());
+
+        Ok(Keystore {
+            crypto: KeystoreCrypto {
+                kdf: KeystoreModule {
+                    function: "pbkdf2".to_string(),
+                    params: KdfParams {
+                        dklen: DK_SIZE as u32,
+                        c: rounds,
+                        prf: "hmac-sha256".to_string(),
+                        salt: hex::encode(salt),
+                    },
+                    message: String::new(),
+                },
+                checksum: KeystoreModule {
+                    function: "sha256".to_string(),
+                    params: ChecksumParams {},
+                    message: hex::encode(checksum(&dk, &cipher_msg)),
+                },
+                cipher: KeystoreModule {
+                    function: "aes-128-ctr".to_string(),
+                    params: CipherParams {
+                        iv: hex::encode(iv),
+                    },
+                    message: hex::encode(cipher_msg),
+                },
+            },
+            description: description.to_string(),
+            pubkey: hex::encode(pubkey.pk_to_key_slice()),
+            path: path.to_string(),
+            uuid: format_uuid(uuid),
+            version: KEYSTORE_VERSION,
+        })
+    }
+
+    /// decrypt returns the secret held by the keystore. It returns an error if
+    /// the password is wrong or if the secret doesn't match the public key of
+    /// the keystore.
+    pub fn decrypt(&self, password: &str) -> Result<FE, Box<dyn Error>> {
+        if self.version != KEYSTORE_VERSION {
+            bail!("eth: unsupported keystore version {}", self.version);
+        }
+        let crypto = &self.crypto;
+        let kdf = &crypto.kdf.params;
+        if crypto.kdf.function != "pbkdf2" || kdf.prf != "hmac-sha256" {
+            bail!("eth: unsupported key derivation {}", crypto.kdf.function);
+        }
+        if kdf.dklen as usize != DK_SIZE || kdf.c == 0 {
+            bail!("eth: invalid pbkdf2 parameters");
+        }
+        if crypto.checksum.function != "sha256" {
+            bail!("eth: unsupported checksum {}", crypto.checksum.function);
+        }
+        if crypto.cipher.function != "aes-128-ctr" {
+            bail!("eth: unsupported cipher {}", crypto.cipher.function);
+        }
+        let salt = hex::decode(&kdf.salt)?;
+        let iv = hex::decode(&crypto.cipher.params.iv)?;
+        let mut cipher_msg = hex::decode(&crypto.cipher.message)?;
+
+        let dk = derive_key(password, &salt, kdf.c);
+        let expected = hex::decode(&crypto.checksum.message)?;
+        if !bitwise_eq(&checksum(&dk, &cipher_msg), &expected) {
+            bail!("eth: invalid password");
+        }
+        apply_cipher(&dk, &iv, &mut cipher_msg)?;
+        let secret = scalar_from_be_bytes(&cipher_msg)
+            .map_err(|e| simple_error!("eth: invalid secret: {}", e))?;
+
+        if !self.pubkey.is_empty() {
+            let pubkey: G1Point = G1Point::generator().scalar_mul(&secret.get_element());
+            if hex::encode(pubkey.pk_to_key_slice()) != self.pubkey.to_lowercase() {
+                bail!("eth: the secret doesn't match the public key of the keystore");
+            }
+        }
+        Ok(secret)
+    }
+
+    /// to_json returns the JSON encoding of the keystore.
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// from_json decodes a keystore from its JSON encoding.
+    pub fn from_json(json: &str) -> Result<Keystore, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+// derive_key runs pbkdf2 over the password, normalized to NFKD with the control
+// codes removed as specified by EIP-2335.
+fn derive_key(password: &str, salt: &[u8], rounds: u32) -> [u8; DK_SIZE] {
+    let password: String = password.nfkd().filter(|c| !c.is_control()).collect();
+    let mut dk = [0u8; DK_SIZE];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, rounds, &mut dk);
+    dk
+}
+
+fn checksum(dk: &[u8; DK_SIZE], cipher_msg: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(&dk[16..]);
+    hasher.update(cipher_msg);
+    hasher.finalize().to_vec()
+}
+
+fn apply_cipher(dk: &[u8; DK_SIZE], iv: &[u8], msg: &mut [u8]) -> Result<(), Box<dyn Error>> {
+    let mut cipher = Aes128Ctr::new_var(&dk[..16], iv)
+        .map_err(|_| simple_error!("eth: invalid aes-128-ctr iv"))?;
+    cipher.apply_keystream(msg);
+    Ok(())
+}
+
+// format_uuid returns the version 4 UUID of the random bytes.
+fn format_uuid(mut b: [u8; 16]) -> String {
+    b[6] = (b[6] & 0x0f) | 0x40;
+    b[8] = (b[8] & 0x3f) | 0x80;
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex::encode(&b[..4]),
+        hex::encode(&b[4..6]),
+        hex::encode(&b[6..8]),
+        hex::encode(&b[8..10]),
+        hex::encode(&b[10..])
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::sim;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    // pbkdf2 test vector of EIP-2335
+    const TEST_KEYSTORE: &str = r#"{
+        "crypto": {
+            "kdf": {
+                "function": "pbkdf2",
+                "params": {
+                    "dklen": 32,
+                    "c": 262144,
+                    "prf": "hmac-sha256",
+                    "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                },
+                "message": ""
+            },
+            "checksum": {
+                "function": "sha256",
+                "params": {},
+                "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+            },
+            "cipher": {
+                "function": "aes-128-ctr",
+                "params": {
+                    "iv": "264daa3f303d7259501c93d997d84fe6"
+                },
+                "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+            }
+        },
+        "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+        "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+        "path": "m/12381/60/0/0",
+        "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+        "version": 4
+    }"#;
+
+    #[test]
+    fn test_eth_keystore_vector() {
+        let keystore = Keystore::from_json(TEST_KEYSTORE).unwrap();
+        let password = "\u{1d531}\u{1d522}\u{1d530}\u{1d531}\u{1d52d}\u{1d51e}\u{1d530}\u{1d530}\u{1d534}\u{1d52c}\u{1d52f}\u{1d521}\u{1f511}";
+        let secret = keystore.decrypt(password).unwrap();
+        assert_eq!(
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+            hex::encode(scalar_to_be_bytes(&secret))
+        );
+        keystore
+            .decrypt("testpassword")
+            .expect_err("Must fail, wrong password");
+    }
+
+    #[test]
+    fn test_eth_keystore_shares() {
+        let (n, t) = (4, 3);
+        let (secs, pubs) = sim::new_participants::<G1Point>(n);
+        let mut dkgs: Vec<DistKeyGenerator<G1Point>> = secs
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, pubs.clone(), t).unwrap())
+            .collect();
+        let network = sim::Network::new(n, Default::default(), ChaCha20Rng::seed_from_u64(42));
+        let shares: Vec<DistKeyShare<G1Point>> = sim::run_dkg(&network, &mut dkgs, 100)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+
+        let pubkey = validator_pubkey(&shares[0]);
+        assert_eq!(bls12_381_curve::G1_SIZE, pubkey.len());
+        for (i, share) in shares.iter().enumerate() {
+            assert_eq!(pubkey, validator_pubkey(share));
+            let keystore = Keystore::encrypt_with_rng(
+                &share.get_pri_share().v,
+                "operator password",
+                "",
+                "",
+                16,
+                &mut utils::rand_hack(),
+            )
+            .unwrap();
+            assert_eq!(hex::encode(share_pubkey(share, i as u32)), keystore.pubkey);
+
+            let keystore = Keystore::from_json(&keystore.to_json().unwrap()).unwrap();
+            let secret = keystore.decrypt("operator password").unwrap();
+            assert_eq!(share.get_pri_share().v, secret);
+            keystore
+                .decrypt("other password")
+                .expect_err("Must fail, wrong password");
+
+            let mut forged = keystore.clone();
+            forged.pubkey = hex::encode(&pubkey);
+            forged
+                .decrypt("operator password")
+                .expect_err("Must fail, public key of another secret");
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Protocol events
+//!
+//! The vss and dkg flows report their progress as Events, recorded as tracing
+//! events within a span naming the session and the index of the participant,
+//! so that operators can follow a live ceremony and find out which round is
+//! stuck. Spans and events are only emitted with the `tracing` feature, and
+//! recording them is a no-op without it.
+
+#[cfg(feature = "tracing")]
+use tracing::field;
+
+/// Event is a step of a run of the protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The verifier at index approved the deal.
+    DealVerified { index: u32 },
+    /// The verifier at index complained about the deal.
+    ComplaintFiled { index: u32 },
+    /// The justification of the dealer for the complaint of the verifier at
+    /// index was accepted.
+    JustificationAccepted { index: u32 },
+    /// The deal got certified.
+    Certified,
+}
+
+impl Event {
+    /// name returns the name of the event, as recorded.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::DealVerified { .. } => "DealVerified",
+            Event::ComplaintFiled { .. } => "ComplaintFiled",
+            Event::JustificationAccepted { .. } => "JustificationAccepted",
+            Event::Certified => "Certified",
+        }
+    }
+
+    /// index returns the index of the verifier the event is about, if any.
+    pub fn index(&self) -> Option<u32> {
+        match *self {
+            Event::DealVerified { index }
+            | Event::ComplaintFiled { index }
+            | Event::JustificationAccepted { index } => Some(index),
+            Event::Certified => None,
+        }
+    }
+}
+
+/// record records the event in the current span.
+#[cfg(feature = "tracing")]
+pub fn record(event: Event) {
+    match event.index() {
+        Some(index) => tracing::info!(event = event.name(), index),
+        None => tracing::info!(event = event.name()),
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub fn record(_event: Event) {}
+
+/// Span is the span of a participant in a session, entered until it is
+/// dropped.
+#[must_use]
+pub struct Span {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+/// span enters the span of the participant with the given role (e.g.
+/// "vss-verifier") in the session. index is the index of the participant,
+/// unknown for a vss Dealer, and dealer the index of the dealer whose deal is
+/// processed, in the dkg.
+#[cfg(feature = "tracing")]
+pub fn span(
+    role: &'static str,
+    session_id: &[u8],
+    index: Option<u32>,
+    dealer: Option<u32>,
+) -> Span {
+    let span = tracing::info_span!(
+        "session",
+        role,
+        session_id = field::display(hex::encode(session_id)),
+        index = field::Empty,
+        dealer = field::Empty,
+    );
+    if let Some(index) = index {
+        span.record("index", &index);
+    }
+    if let Some(dealer) = dealer {
+        span.record("dealer", &dealer);
+    }
+    Span {
+        _entered: span.entered(),
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub fn span(
+    _role: &'static str,
+    _session_id: &[u8],
+    _index: Option<u32>,
+    _dealer: Option<u32>,
+) -> Span {
+    Span {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        let _span = span("vss-verifier", &[1u8; 32], Some(2), None);
+        let event = Event::ComplaintFiled { index: 2 };
+        assert_eq!("ComplaintFiled", event.name());
+        assert_eq!(Some(2), event.index());
+        assert_eq!(None, Event::Certified.index());
+        record(event);
+    }
+}
@@ -0,0 +1,312 @@
+//! Evidence of the misbehavior of a vss dealer, e.g. for slashing.
+//!
+//! An Evidence holds everything needed to blame the dealer: the public
+//! parameters of the run and the messages signed by the dealer. It is signed by
+//! the verifier reporting it, and verify_evidence checks it without any
+//! protocol state, e.g. in an on-chain slashing contract.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::ristretto_curve;
+use crate::signer;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use encoding::{Domain, Encoder};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use signer::Signer;
+use vss::{Complaint, Justification};
+
+/// Misbehavior is a misbehavior of a dealer, proven by messages it signed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Misbehavior<P: CurvePoint = GE> {
+    // Complaint showing that the deal of a verifier doesn't verify
+    InvalidDeal(Complaint<P>),
+    // Justification revealing a deal which doesn't verify
+    InvalidJustification(Justification<P>),
+    // Two justifications revealing different deals for the same verifier of a
+    // session
+    Equivocation(Justification<P>, Justification<P>),
+}
+
+/// Evidence is a Misbehavior of the dealer of a run of the vss protocol,
+/// reported by one of its verifiers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Evidence<P: CurvePoint = GE> {
+    // Longterm public key of the dealer
+    pub dealer: P,
+    // Longterm public keys of the verifiers
+    pub verifiers: Vec<P>,
+    // x-coordinates of the shares of a Dealer created with points, if any
+    #[serde(default)]
+    pub points: Option<Vec<P::Scalar>>,
+    // Application context bound to the session id, empty if none
+    #[serde(default)]
+    pub context: Vec<u8>,
+    pub misbehavior: Misbehavior<P>,
+    // Index of the verifier reporting the evidence
+    pub reporter: u32,
+    // Signature of the reporter over the whole evidence
+    pub signature: Vec<u8>,
+}
+
+impl<P: CurvePoint> Evidence<P> {
+    /// sign returns the evidence of misbehavior reported by the verifier at
+    /// index reporter, signed with its longterm key. It returns an error if the
+    /// evidence doesn't prove the misbehavior.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sign<S: Signer<P>>(
+        signer: &S,
+        reporter: u32,
+        dealer: P,
+        verifiers: Vec<P>,
+        points: Option<Vec<P::Scalar>>,
+        context: &[u8],
+        misbehavior: Misbehavior<P>,
+    ) -> Result<Evidence<P>, Box<dyn Error>> {
+        let mut evidence = Evidence {
+            dealer,
+            verifiers,
+            points,
+            context: context.to_vec(),
+            misbehavior,
+            reporter,
+            signature: Vec::new(),
+        };
+        evidence.signature = signer.sign(&evidence.hash()?, &reporter.to_le_bytes())?;
+        verify_evidence(&evidence)?;
+        Ok(evidence)
+    }
+
+    /// hash returns the hash of the evidence, which is signed by the reporter.
+    pub fn hash(&self) -> Result<[u8; 32], Box<dyn Error>> {
+        let verifiers: Vec<Vec<u8>> = self.verifiers.iter().map(|v| v.pk_to_key_slice()).collect();
+        let mut enc = Encoder::new(b"evidence")
+            .put_bytes(&self.dealer.pk_to_key_slice())
+            .put_list(&verifiers);
+        enc = match &self.points {
+            Some(points) => {
+                let points: Vec<Vec<u8>> = points.iter().map(|x| x.to_bytes()).collect();
+                enc.put_bool(true).put_list(&points)
+            }
+            None => enc.put_bool(false),
+        };
+        enc = enc.put_bytes(&self.context);
+        enc = match &self.misbehavior {
+            Misbehavior::InvalidDeal(c) => enc
+                .put_u8(0)
+                .put_bytes(&c.response.hash_self()?)
+                .put_bytes(&c.response.signature)
+                .put_bytes(&c.deal.hash_self(c.response.index))
+                .put_bytes(&c.deal.signature)
+                .put_bytes(&c.dh_shared.pk_to_key_slice())
+                .put_bytes(&c.proof.c.to_bytes())
+                .put_bytes(&c.proof.r.to_bytes()),
+            Misbehavior::InvalidJustification(j) => put_justification(enc.put_u8(1), j)?,
+            Misbehavior::Equivocation(a, b) => {
+                put_justification(put_justification(enc.put_u8(2), a)?, b)?
+            }
+        };
+        Ok(enc.put_u32(self.reporter).hash())
+    }
+}
+
+fn put_justification<P: CurvePoint>(
+    enc: Encoder,
+    j: &Justification<P>,
+) -> Result<Encoder, Box<dyn Error>> {
+    Ok(enc
+        .put_bytes(&Justification::hash(&j.session_id, j.index, &j.deal)?)
+        .put_bytes(&j.signature))
+}
+
+/// verify_evidence returns an error unless the evidence is signed by its
+/// reporter and proves the misbehavior of the dealer.
+pub fn verify_evidence<P: CurvePoint>(evidence: &Evidence<P>) -> Result<(), Box<dyn Error>> {
+    let dealer = &evidence.dealer;
+    let verifiers = &evidence.verifiers;
+    let points = evidence.points.as_deref();
+    if let Some(points) = points {
+        vss::check_points::<P>(points, verifiers.len())?;
+    }
+    let reporter = verifiers
+        .get(evidence.reporter as usize)
+        .ok_or_else(|| simple_error!("evidence: index out of bounds for the reporter"))?;
+    P::verify_signature(
+        reporter,
+        &evidence.signature,
+        &evidence.hash()?,
+        &evidence.reporter.to_le_bytes(),
+    )
+    .map_err(|e| simple_error!("evidence: invalid signature of the reporter: {}", e))?;
+
+    match &evidence.misbehavior {
+        Misbehavior::InvalidDeal(c) => {
+            if !c.judge_in_context(dealer, verifiers, points, &evidence.context)? {
+                bail!("evidence: the deal of the complaint is valid");
+            }
+        }
+        Misbehavior::InvalidJustification(j) => {
+            verify_justification_signature(dealer, j)?;
+            if check_justification(dealer, verifiers, points, &evidence.context, j).is_ok() {
+                bail!("evidence: the deal of the justification is valid");
+            }
+        }
+        Misbehavior::Equivocation(a, b) => {
+            verify_justification_signature(dealer, a)?;
+            verify_justification_signature(dealer, b)?;
+            if a.session_id != b.session_id || a.index != b.index {
+                bail!("evidence: justifications for different deals");
+            }
+            if a.deal.encode() == b.deal.encode() {
+                bail!("evidence: justifications revealing the same deal");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn verify_justification_signature<P: CurvePoint>(
+    dealer: &P,
+    j: &Justification<P>,
+) -> Result<(), Box<dyn Error>> {
+    let hash = Justification::hash(&j.session_id, j.index, &j.deal)?;
+    P::verify_signature(dealer, &j.signature, &hash, &j.index.to_le_bytes())
+        .map_err(|e| simple_error!("evidence: invalid signature of the dealer: {}", e).into())
+}
+
+// check_justification returns an error if the deal revealed by the
+// justification isn't a valid deal of its session for the verifier at its
+// index.
+fn check_justification<P: CurvePoint>(
+    dealer: &P,
+    verifiers: &[P],
+    points: Option<&[P::Scalar]>,
+    context: &[u8],
+    j: &Justification<P>,
+) -> Result<(), Box<dyn Error>> {
+    let deal = &j.deal;
+    if deal.sec_share.i != j.index || deal.session_id != j.session_id {
+        bail!("evidence: justification about another deal");
+    }
+    let sid = vss::session_id_at(
+        &Domain::default(),
+        dealer,
+        verifiers,
+        points,
+        context,
+        &deal.commitments,
+        deal.t,
+    );
+    match points {
+        Some(points) => {
+            let x = points
+                .get(j.index as usize)
+                .ok_or_else(|| simple_error!("evidence: index out of bounds in justification"))?;
+            deal.verify_at(verifiers, &sid, x)
+        }
+        None => deal.verify(verifiers, &sid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve::FE;
+    use crate::signer::SoftwareSigner;
+    use crate::sim;
+    use crate::vss::{Dealer, EncryptedDeal, Response, Verifier};
+
+    #[test]
+    fn test_evidence() {
+        let (secs, pubs) = sim::new_participants::<GE>(5);
+        let (dealer_sec, dealer_pub) = sim::new_participants::<GE>(1);
+        let (dealer_sec, dealer_pub) = (dealer_sec[0], dealer_pub[0]);
+        let secret: FE = ECScalar::new_random();
+        let mut dealer: Dealer = Dealer::new(dealer_sec, secret, pubs.clone(), 3).unwrap();
+        let reporter = SoftwareSigner::<GE>::new(secs[1]);
+
+        // a deal with a wrong share
+        dealer.deals[0].sec_share.v = ECScalar::new_random();
+        let enc_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        let mut v: Verifier = Verifier::new(secs[0], dealer_pub, pubs.clone()).unwrap();
+        let resp: Response = v.process_encrypted_deal(&enc_deal).unwrap();
+        assert!(!resp.approved);
+        let complaint = v.complaint(&enc_deal).unwrap();
+        let evidence = v.evidence(Misbehavior::InvalidDeal(complaint)).unwrap();
+        assert_eq!(0, evidence.reporter);
+        verify_evidence(&evidence).unwrap();
+
+        // the evidence is bound to the reporter
+        let mut forged = evidence.clone();
+        forged.reporter = 1;
+        assert!(verify_evidence(&forged).is_err());
+        // and to the dealer
+        let mut forged = evidence;
+        forged.dealer = pubs[4];
+        assert!(verify_evidence(&forged).is_err());
+
+        // the justification reveals the wrong share
+        let j = dealer.process_response(&resp).unwrap().unwrap();
+        let evidence = Evidence::sign(
+            &reporter,
+            1,
+            dealer_pub,
+            pubs.clone(),
+            None,
+            &[],
+            Misbehavior::InvalidJustification(j),
+        )
+        .unwrap();
+        verify_evidence(&evidence).unwrap();
+
+        // a valid justification is no evidence
+        let mut honest: Dealer = Dealer::new(dealer_sec, secret, pubs.clone(), 3).unwrap();
+        let mut bad: Dealer = honest.clone();
+        bad.deals[0].sec_share.v = ECScalar::new_random();
+        let mut v: Verifier = Verifier::new(secs[0], dealer_pub, pubs.clone()).unwrap();
+        let resp = v
+            .process_encrypted_deal(&bad.encrypt_deal(0).unwrap())
+            .unwrap();
+        let honest_j = honest.process_response(&resp).unwrap().unwrap();
+        assert!(Evidence::sign(
+            &reporter,
+            1,
+            dealer_pub,
+            pubs.clone(),
+            None,
+            &[],
+            Misbehavior::InvalidJustification(honest_j.clone()),
+        )
+        .is_err());
+
+        // two justifications revealing different deals
+        let bad_j = bad.process_response(&resp).unwrap().unwrap();
+        let evidence = Evidence::sign(
+            &reporter,
+            1,
+            dealer_pub,
+            pubs.clone(),
+            None,
+            &[],
+            Misbehavior::Equivocation(honest_j.clone(), bad_j),
+        )
+        .unwrap();
+        verify_evidence(&evidence).unwrap();
+        assert!(Evidence::sign(
+            &reporter,
+            1,
+            dealer_pub,
+            pubs,
+            None,
+            &[],
+            Misbehavior::Equivocation(honest_j.clone(), honest_j),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,669 @@
+//! C API
+//!
+//! A stable C interface to the vss Dealer and Verifier and to the dkg
+//! DistKeyGenerator on the default Ristretto backend, declared in
+//! include/dkg.h, so that node software written in other languages can embed
+//! the protocols. Enabled with the `ffi` feature, the shared library being
+//! built with `cargo rustc --lib --features ffi --crate-type cdylib`.
+//!
+//! Protocol objects are opaque handles created by the *_new functions and
+//! released by the matching *_free functions. Keys, scalars and messages are
+//! passed as their bincode encoding in byte buffers: inputs are borrowed
+//! (pointer, length) pairs, and outputs are DkgBuffers allocated by the library
+//! to release with dkg_buffer_free. Every function returns DKG_OK or an error
+//! code, and dkg_last_error gives the message of the last error of the thread.
+//!
+//! Safety: the caller must pass handles created by this library and not yet
+//! freed, input pointers valid for reads of their length, and output pointers
+//! valid for writes. A handle must not be used by several threads at once.
+
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::ristretto_curve;
+use crate::vss;
+use crate::wire;
+
+use curve_traits::{ECPoint, ECScalar};
+use ristretto_curve::{FE, GE};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The call succeeded.
+pub const DKG_OK: i32 = 0;
+/// A pointer is null or an input can't be decoded.
+pub const DKG_ERR_INVALID_ARGUMENT: i32 = 1;
+/// The protocol rejected the call, e.g. an invalid message.
+pub const DKG_ERR_PROTOCOL: i32 = 2;
+/// The library panicked; the handles involved must not be used anymore.
+pub const DKG_ERR_PANIC: i32 = 3;
+
+/// DkgBuffer is a byte buffer allocated by the library.
+#[repr(C)]
+pub struct DkgBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+pub struct DkgDealer(vss::Dealer);
+pub struct DkgVerifier(vss::Verifier);
+pub struct DkgGenerator(dkg::DistKeyGenerator);
+
+enum FfiError {
+    InvalidArgument(String),
+    Protocol(String),
+}
+
+impl From<Box<dyn Error>> for FfiError {
+    fn from(e: Box<dyn Error>) -> FfiError {
+        FfiError::Protocol(e.to_string())
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = RefCell::new(String::new());
+}
+
+fn set_last_error(msg: String) {
+    LAST_ERROR.with(|e| *e.borrow_mut() = msg);
+}
+
+// run runs f, catching any panic, and returns its error code.
+fn run<F: FnOnce() -> Result<(), FfiError>>(f: F) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => DKG_OK,
+        Ok(Err(FfiError::InvalidArgument(msg))) => {
+            set_last_error(msg);
+            DKG_ERR_INVALID_ARGUMENT
+        }
+        Ok(Err(FfiError::Protocol(msg))) => {
+            set_last_error(msg);
+            DKG_ERR_PROTOCOL
+        }
+        Err(_) => {
+            set_last_error("ffi: panic".to_string());
+            DKG_ERR_PANIC
+        }
+    }
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], FfiError> {
+    if data.is_null() {
+        if len == 0 {
+            return Ok(&[]);
+        }
+        return Err(FfiError::InvalidArgument("ffi: null input".to_string()));
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+// decode_with decodes the input with f, one of the bounded decoders of wire.
+unsafe fn decode_with<T, F: Fn(&[u8]) -> Result<T, Box<dyn Error>>>(
+    data: *const u8,
+    len: usize,
+    f: F,
+) -> Result<T, FfiError> {
+    f(input(data, len)?).map_err(|e| FfiError::InvalidArgument(format!("ffi: {}", e)))
+}
+
+unsafe fn decode<T: DeserializeOwned>(data: *const u8, len: usize) -> Result<T, FfiError> {
+    decode_with(data, len, wire::decode)
+}
+
+unsafe fn handle<'a, T>(h: *mut T) -> Result<&'a mut T, FfiError> {
+    h.as_mut()
+        .ok_or_else(|| FfiError::InvalidArgument("ffi: null handle".to_string()))
+}
+
+// output writes the encoding of value to out, or an empty buffer if it is None.
+unsafe fn output<T: Serialize>(out: *mut DkgBuffer, value: Option<&T>) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(FfiError::InvalidArgument("ffi: null output".to_string()));
+    }
+    let bytes: Vec<u8> = match value {
+        Some(v) => bincode::serialize(v).map_err(|e| FfiError::Protocol(format!("ffi: {}", e)))?,
+        None => Vec::new(),
+    };
+    let len = bytes.len();
+    let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+    *out = DkgBuffer { data, len };
+    Ok(())
+}
+
+unsafe fn new_handle<T>(out: *mut *mut T, value: T) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(FfiError::InvalidArgument("ffi: null output".to_string()));
+    }
+    *out = Box::into_raw(Box::new(value));
+    Ok(())
+}
+
+unsafe fn free_handle<T>(h: *mut T) {
+    if !h.is_null() {
+        drop(Box::from_raw(h));
+    }
+}
+
+/// dkg_buffer_free releases a buffer returned by the library.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_buffer_free(buf: DkgBuffer) {
+    if !buf.data.is_null() {
+        drop(Box::from_raw(slice::from_raw_parts_mut(buf.data, buf.len)));
+    }
+}
+
+/// dkg_last_error writes the message of the last error of the thread to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_last_error(out: *mut DkgBuffer) -> i32 {
+    run(|| {
+        let msg = LAST_ERROR.with(|e| e.borrow().clone());
+        if out.is_null() {
+            return Err(FfiError::InvalidArgument("ffi: null output".to_string()));
+        }
+        let bytes = msg.into_bytes().into_boxed_slice();
+        let len = bytes.len();
+        *out = DkgBuffer {
+            data: Box::into_raw(bytes) as *mut u8,
+            len,
+        };
+        Ok(())
+    })
+}
+
+/// dkg_new_secret writes a fresh private key to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_new_secret(out: *mut DkgBuffer) -> i32 {
+    run(|| {
+        let secret: FE = ECScalar::new_random();
+        output(out, Some(&secret))
+    })
+}
+
+/// dkg_public_key writes the public key of the private key to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_public_key(secret: *const u8, len: usize, out: *mut DkgBuffer) -> i32 {
+    run(|| {
+        let secret: FE = decode(secret, len)?;
+        output(
+            out,
+            Some(&GE::generator().scalar_mul(&secret.get_element())),
+        )
+    })
+}
+
+/// dkg_dealer_new creates a Dealer from its longterm private key, the secret
+/// to share, the list of public keys of the verifiers and the threshold.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_dealer_new(
+    longterm: *const u8,
+    longterm_len: usize,
+    secret: *const u8,
+    secret_len: usize,
+    verifiers: *const u8,
+    verifiers_len: usize,
+    t: u32,
+    out: *mut *mut DkgDealer,
+) -> i32 {
+    run(|| {
+        let dealer = vss::Dealer::new(
+            decode(longterm, longterm_len)?,
+            decode(secret, secret_len)?,
+            decode(verifiers, verifiers_len)?,
+            t,
+        )?;
+        new_handle(out, DkgDealer(dealer))
+    })
+}
+
+/// dkg_dealer_encrypt_deals writes the list of EncryptedDeals to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_dealer_encrypt_deals(d: *mut DkgDealer, out: *mut DkgBuffer) -> i32 {
+    run(|| {
+        let deals = handle(d)?.0.encrypt_deals()?;
+        output(out, Some(&deals))
+    })
+}
+
+/// dkg_dealer_process_response writes the Justification to broadcast to out,
+/// or an empty buffer if there is none.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_dealer_process_response(
+    d: *mut DkgDealer,
+    resp: *const u8,
+    resp_len: usize,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let j = handle(d)?.0.process_response(&decode_with(
+            resp,
+            resp_len,
+            wire::decode_response::<GE>,
+        )?)?;
+        output(out, j.as_ref())
+    })
+}
+
+/// dkg_dealer_deal_certified returns 1 if the deal is certified, 0 otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_dealer_deal_certified(d: *const DkgDealer) -> i32 {
+    d.as_ref().map_or(0, |d| d.0.deal_certified() as i32)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_dealer_free(d: *mut DkgDealer) {
+    free_handle(d)
+}
+
+/// dkg_verifier_new creates a Verifier from its longterm private key, the
+/// public key of the dealer and the list of public keys of the verifiers.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_verifier_new(
+    longterm: *const u8,
+    longterm_len: usize,
+    dealer: *const u8,
+    dealer_len: usize,
+    verifiers: *const u8,
+    verifiers_len: usize,
+    out: *mut *mut DkgVerifier,
+) -> i32 {
+    run(|| {
+        let verifier = vss::Verifier::new(
+            decode(longterm, longterm_len)?,
+            decode(dealer, dealer_len)?,
+            decode(verifiers, verifiers_len)?,
+        )?;
+        new_handle(out, DkgVerifier(verifier))
+    })
+}
+
+/// dkg_verifier_process_encrypted_deal writes the Response to broadcast to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_verifier_process_encrypted_deal(
+    v: *mut DkgVerifier,
+    deal: *const u8,
+    deal_len: usize,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let resp = handle(v)?.0.process_encrypted_deal(&decode_with(
+            deal,
+            deal_len,
+            wire::decode_encrypted_deal::<GE>,
+        )?)?;
+        output(out, Some(&resp))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_verifier_process_response(
+    v: *mut DkgVerifier,
+    resp: *const u8,
+    resp_len: usize,
+) -> i32 {
+    run(|| {
+        handle(v)?
+            .0
+            .process_response(&decode_with(resp, resp_len, wire::decode_response::<GE>)?)
+            .map_err(FfiError::from)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_verifier_process_justification(
+    v: *mut DkgVerifier,
+    j: *const u8,
+    j_len: usize,
+) -> i32 {
+    run(|| {
+        let v = handle(v)?;
+        let t = v.0.max_threshold();
+        v.0.process_justification(&decode_with(j, j_len, |b| {
+            wire::decode_justification::<GE>(b, t)
+        })?)
+        .map_err(FfiError::from)
+    })
+}
+
+/// dkg_verifier_deal_certified returns 1 if the deal is certified, 0
+/// otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_verifier_deal_certified(v: *const DkgVerifier) -> i32 {
+    v.as_ref().map_or(0, |v| v.0.deal_certified() as i32)
+}
+
+/// dkg_verifier_deal writes the Deal of the verifier, holding its share, to
+/// out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_verifier_deal(v: *mut DkgVerifier, out: *mut DkgBuffer) -> i32 {
+    run(|| {
+        let deal = handle(v)?.0.get_deal()?;
+        output(out, Some(&deal))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_verifier_free(v: *mut DkgVerifier) {
+    free_handle(v)
+}
+
+/// dkg_generator_new creates a DistKeyGenerator from the longterm private key
+/// of the participant, the list of public keys of the participants and the
+/// threshold.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_new(
+    longterm: *const u8,
+    longterm_len: usize,
+    participants: *const u8,
+    participants_len: usize,
+    t: u32,
+    out: *mut *mut DkgGenerator,
+) -> i32 {
+    run(|| {
+        let dkg = dkg::DistKeyGenerator::new(
+            decode(longterm, longterm_len)?,
+            decode(participants, participants_len)?,
+            t,
+        )?;
+        new_handle(out, DkgGenerator(dkg))
+    })
+}
+
+/// dkg_generator_deals writes the map of the Deals to send, by index of the
+/// recipient, to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_deals(g: *mut DkgGenerator, out: *mut DkgBuffer) -> i32 {
+    run(|| {
+        let deals = handle(g)?.0.deals()?;
+        output(out, Some(&deals))
+    })
+}
+
+/// dkg_generator_process_deal writes the Response to broadcast to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_process_deal(
+    g: *mut DkgGenerator,
+    deal: *const u8,
+    deal_len: usize,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let resp = handle(g)?.0.process_deal(&decode_with(
+            deal,
+            deal_len,
+            wire::decode_dkg_deal::<GE>,
+        )?)?;
+        output(out, Some(&resp))
+    })
+}
+
+/// dkg_generator_process_response writes the Justification to broadcast to
+/// out, or an empty buffer if there is none.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_process_response(
+    g: *mut DkgGenerator,
+    resp: *const u8,
+    resp_len: usize,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let j = handle(g)?.0.process_response(&decode_with(
+            resp,
+            resp_len,
+            wire::decode_dkg_response::<GE>,
+        )?)?;
+        output(out, j.as_ref())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_process_justification(
+    g: *mut DkgGenerator,
+    j: *const u8,
+    j_len: usize,
+) -> i32 {
+    run(|| {
+        let g = handle(g)?;
+        let t = g.0.threshold();
+        g.0.process_justification(&decode_with(j, j_len, |b| {
+            wire::decode_dkg_justification::<GE>(b, t)
+        })?)
+        .map_err(FfiError::from)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_set_timeout(g: *mut DkgGenerator) -> i32 {
+    run(|| {
+        handle(g)?.0.set_timeout();
+        Ok(())
+    })
+}
+
+/// dkg_generator_certified returns 1 if the DKG is certified, 0 otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_certified(g: *const DkgGenerator) -> i32 {
+    g.as_ref().map_or(0, |g| g.0.certified() as i32)
+}
+
+/// dkg_generator_secret_commits writes the SecretCommits to broadcast to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_secret_commits(
+    g: *mut DkgGenerator,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let sc = handle(g)?.0.secret_commits()?;
+        output(out, Some(&sc))
+    })
+}
+
+/// dkg_generator_process_secret_commits writes the ComplaintCommits to
+/// broadcast to out, or an empty buffer if there is none.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_process_secret_commits(
+    g: *mut DkgGenerator,
+    sc: *const u8,
+    sc_len: usize,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let g = handle(g)?;
+        let t = g.0.threshold();
+        let cc = g.0.process_secret_commit(&decode_with(sc, sc_len, |b| {
+            wire::decode_secret_commits::<GE>(b, t)
+        })?)?;
+        output(out, cc.as_ref())
+    })
+}
+
+/// dkg_generator_process_complaint_commits writes the ReconstructCommits to
+/// broadcast to out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_process_complaint_commits(
+    g: *mut DkgGenerator,
+    cc: *const u8,
+    cc_len: usize,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let g = handle(g)?;
+        let t = g.0.threshold();
+        let rc =
+            g.0.process_complaints_commits(&decode_with(cc, cc_len, |b| {
+                wire::decode_complaint_commits::<GE>(b, t)
+            })?)?;
+        output(out, Some(&rc))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_process_reconstruct_commits(
+    g: *mut DkgGenerator,
+    rc: *const u8,
+    rc_len: usize,
+) -> i32 {
+    run(|| {
+        handle(g)?
+            .0
+            .process_reconstruct_commits(&decode_with(
+                rc,
+                rc_len,
+                wire::decode_reconstruct_commits::<GE>,
+            )?)
+            .map_err(FfiError::from)
+    })
+}
+
+/// dkg_generator_finished returns 1 if the DKG is finished, 0 otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_finished(g: *const DkgGenerator) -> i32 {
+    g.as_ref().map_or(0, |g| g.0.finished() as i32)
+}
+
+/// dkg_generator_dist_key_share writes the DistKeyShare of the participant to
+/// out.
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_dist_key_share(
+    g: *mut DkgGenerator,
+    out: *mut DkgBuffer,
+) -> i32 {
+    run(|| {
+        let share = handle(g)?.0.dist_key_share()?;
+        output(out, Some(&share))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dkg_generator_free(g: *mut DkgGenerator) {
+    free_handle(g)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    fn empty() -> DkgBuffer {
+        DkgBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    unsafe fn take(buf: DkgBuffer) -> Vec<u8> {
+        let bytes = slice::from_raw_parts(buf.data, buf.len).to_vec();
+        dkg_buffer_free(buf);
+        bytes
+    }
+
+    unsafe fn new_key() -> (Vec<u8>, Vec<u8>) {
+        let mut sec = empty();
+        assert_eq!(DKG_OK, dkg_new_secret(&mut sec));
+        let sec = take(sec);
+        let mut public = empty();
+        assert_eq!(DKG_OK, dkg_public_key(sec.as_ptr(), sec.len(), &mut public));
+        (sec, take(public))
+    }
+
+    #[test]
+    fn test_ffi_vss() {
+        unsafe {
+            let (n, t) = (3, 2);
+            let keys: Vec<(Vec<u8>, Vec<u8>)> = (0..n).map(|_| new_key()).collect();
+            let (dealer_sec, dealer_pub) = new_key();
+            let pubs: Vec<GE> = keys
+                .iter()
+                .map(|(_, p)| bincode::deserialize(p).unwrap())
+                .collect();
+            let pubs = bincode::serialize(&pubs).unwrap();
+            let secret: FE = ECScalar::new_random();
+            let secret = bincode::serialize(&secret).unwrap();
+
+            let mut dealer: *mut DkgDealer = ptr::null_mut();
+            assert_eq!(
+                DKG_OK,
+                dkg_dealer_new(
+                    dealer_sec.as_ptr(),
+                    dealer_sec.len(),
+                    secret.as_ptr(),
+                    secret.len(),
+                    pubs.as_ptr(),
+                    pubs.len(),
+                    t,
+                    &mut dealer,
+                )
+            );
+            let mut deals = empty();
+            assert_eq!(DKG_OK, dkg_dealer_encrypt_deals(dealer, &mut deals));
+            let deals: Vec<vss::EncryptedDeal> = bincode::deserialize(&take(deals)).unwrap();
+
+            for (i, (sec, _)) in keys.iter().enumerate() {
+                let mut v: *mut DkgVerifier = ptr::null_mut();
+                assert_eq!(
+                    DKG_OK,
+                    dkg_verifier_new(
+                        sec.as_ptr(),
+                        sec.len(),
+                        dealer_pub.as_ptr(),
+                        dealer_pub.len(),
+                        pubs.as_ptr(),
+                        pubs.len(),
+                        &mut v,
+                    )
+                );
+                let deal = bincode::serialize(&deals[i]).unwrap();
+
+                // a length announcing a huge session id is refused undecoded
+                let mut huge = deal.clone();
+                huge[1..9].copy_from_slice(&u64::MAX.to_le_bytes());
+                let mut resp = empty();
+                assert_eq!(
+                    DKG_ERR_INVALID_ARGUMENT,
+                    dkg_verifier_process_encrypted_deal(v, huge.as_ptr(), huge.len(), &mut resp)
+                );
+
+                let mut resp = empty();
+                assert_eq!(
+                    DKG_OK,
+                    dkg_verifier_process_encrypted_deal(v, deal.as_ptr(), deal.len(), &mut resp)
+                );
+                let resp = take(resp);
+                let mut j = empty();
+                assert_eq!(
+                    DKG_OK,
+                    dkg_dealer_process_response(dealer, resp.as_ptr(), resp.len(), &mut j)
+                );
+                assert_eq!(0, take(j).len());
+
+                // the same deal twice is refused
+                let mut again = empty();
+                assert_eq!(
+                    DKG_ERR_PROTOCOL,
+                    dkg_verifier_process_encrypted_deal(v, deal.as_ptr(), deal.len(), &mut again)
+                );
+                let mut msg = empty();
+                assert_eq!(DKG_OK, dkg_last_error(&mut msg));
+                assert!(!take(msg).is_empty());
+                dkg_verifier_free(v);
+            }
+            assert_eq!(1, dkg_dealer_deal_certified(dealer));
+
+            // invalid arguments
+            let mut out = empty();
+            assert_eq!(
+                DKG_ERR_INVALID_ARGUMENT,
+                dkg_dealer_process_response(dealer, [1u8, 2].as_ptr(), 2, &mut out)
+            );
+            assert_eq!(
+                DKG_ERR_INVALID_ARGUMENT,
+                dkg_dealer_encrypt_deals(ptr::null_mut(), &mut out)
+            );
+            dkg_dealer_free(dealer);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Radix-2 FFT over the scalar field of a curve, for committees of thousands
+//! of verifiers, e.g. a PVSS to all the token holders.
+//!
+//! An EvaluationDomain of size 2^k holds the powers of a primitive 2^k-th
+//! root of unity w. A Dealer whose evaluation points are the first n powers
+//! 1, w, .., w^(n-1) of a domain computes its shares with one FFT instead of n
+//! evaluations, and the secret is recovered from the shares of the whole
+//! domain with one inverse FFT, which also detects shares not lying on a
+//! polynomial of degree less than t.
+//!
+//! The sizes are bounded by the 2-adicity of the scalar field,
+//! CurvePoint::TWO_ADICITY: 2^32 for BLS12-381, but 2^6 for secp256k1 and only
+//! 4 for ristretto255 and edwards25519.
+
+use crate::curve_traits;
+use crate::error::Error;
+use crate::poly;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::ristretto_curve;
+
+use curve_traits::{CurvePoint, ECScalar};
+use poly::PriShare;
+use ristretto_curve::GE;
+
+/// EvaluationDomain is the multiplicative subgroup of size 2^log_size of the
+/// scalar field.
+#[derive(Clone, Debug)]
+pub struct EvaluationDomain<P: CurvePoint = GE> {
+    size: usize,
+    log_size: u32,
+    // primitive size-th root of unity, and its inverse
+    omega: P::Scalar,
+    omega_inv: P::Scalar,
+    // inverse of size, scaling the inverse FFT
+    size_inv: P::Scalar,
+}
+
+impl<P: CurvePoint> EvaluationDomain<P> {
+    /// new returns the smallest domain holding n points. It returns an error
+    /// if the scalar field has no such domain.
+    pub fn new(n: usize) -> Result<EvaluationDomain<P>, Box<dyn Error>> {
+        if n == 0 {
+            bail!("fft: empty domain");
+        }
+        let size = n
+            .checked_next_power_of_two()
+            .ok_or_else(|| simple_error!("fft: no domain of {} points", n))?;
+        let log_size = size.trailing_zeros();
+        if log_size > P::TWO_ADICITY {
+            bail!(
+                "fft: no domain of size {} over {}, the largest is 2^{}",
+                size,
+                P::CURVE_ID,
+                P::TWO_ADICITY
+            );
+        }
+        let omega = square_times(P::root_of_unity(), P::TWO_ADICITY - log_size);
+        Ok(EvaluationDomain {
+            size,
+            log_size,
+            omega,
+            omega_inv: omega.invert(),
+            size_inv: P::Scalar::from(size as u64).invert(),
+        })
+    }
+
+    /// size returns the number of points of the domain.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// points returns the points of the domain, w^0, .., w^(size-1).
+    pub fn points(&self) -> Vec<P::Scalar> {
+        let mut points = Vec::with_capacity(self.size);
+        let mut x = P::Scalar::from(1);
+        for _ in 0..self.size {
+            points.push(x);
+            x = x.mul(&self.omega.get_element());
+        }
+        points
+    }
+
+    /// fft returns the evaluations at the points of the domain of the
+    /// polynomial of coefficients coeffs, of at most size coefficients.
+    pub fn fft(&self, coeffs: &[P::Scalar]) -> Result<Vec<P::Scalar>, Box<dyn Error>> {
+        if coeffs.len() > self.size {
+            bail!(
+                "fft: {} coefficients over a domain of size {}",
+                coeffs.len(),
+                self.size
+            );
+        }
+        let mut values = coeffs.to_vec();
+        values.resize(self.size, P::Scalar::zero());
+        self.radix2(&mut values, &self.omega);
+        Ok(values)
+    }
+
+    /// ifft returns the coefficients of the polynomial of the given
+    /// evaluations at the points of the domain.
+    pub fn ifft(&self, evals: &[P::Scalar]) -> Result<Vec<P::Scalar>, Box<dyn Error>> {
+        if evals.len() != self.size {
+            bail!(
+                "fft: {} evaluations over a domain of size {}",
+                evals.len(),
+                self.size
+            );
+        }
+        let mut values = evals.to_vec();
+        self.radix2(&mut values, &self.omega_inv);
+        for v in values.iter_mut() {
+            *v = v.mul(&self.size_inv.get_element());
+        }
+        Ok(values)
+    }
+
+    /// recover_secret returns the secret of the shares evaluated at the
+    /// points of the domain, the share at index i holding p(w^i). Given the
+    /// shares of all the points, it interpolates them with an inverse FFT and
+    /// returns an error if they are not on a polynomial of degree less than t.
+    /// Otherwise it interpolates t of them as poly::recover_secret_at.
+    pub fn recover_secret(
+        &self,
+        shares: &[PriShare<P::Scalar>],
+        t: u32,
+    ) -> Result<P::Scalar, Box<dyn Error>> {
+        let mut evals: Vec<Option<P::Scalar>> = vec![None; self.size];
+        for share in shares.iter() {
+            if let Some(v) = evals.get_mut(share.i as usize) {
+                *v = Some(share.v);
+            }
+        }
+        let evals: Option<Vec<P::Scalar>> = evals.into_iter().collect();
+        let evals = match evals {
+            Some(evals) if t as usize <= self.size => evals,
+            _ => return poly::recover_secret_at(shares, &self.points(), t),
+        };
+        let coeffs = self.ifft(&evals)?;
+        if coeffs[t as usize..].iter().any(|c| *c != P::Scalar::zero()) {
+            bail!("fft: shares not on a polynomial of degree less than {}", t);
+        }
+        Ok(coeffs[0])
+    }
+
+    // radix2 replaces the coefficients in values by their evaluations at the
+    // powers of omega, a primitive size-th root of unity, with the iterative
+    // Cooley-Tukey algorithm.
+    fn radix2(&self, values: &mut [P::Scalar], omega: &P::Scalar) {
+        let n = values.len();
+        if n == 1 {
+            return;
+        }
+        for i in 0..n {
+            let j = i.reverse_bits() >> (usize::BITS - self.log_size);
+            if i < j {
+                values.swap(i, j);
+            }
+        }
+        let mut m = 1;
+        let mut stage = 1;
+        while m < n {
+            // primitive 2m-th root of unity
+            let w_m = square_times(*omega, self.log_size - stage);
+            for k in (0..n).step_by(2 * m) {
+                let mut w = P::Scalar::from(1);
+                for j in 0..m {
+                    let t = values[k + j + m].mul(&w.get_element());
+                    let u = values[k + j];
+                    values[k + j] = u.add(&t.get_element());
+                    values[k + j + m] = u.sub(&t.get_element());
+                    w = w.mul(&w_m.get_element());
+                }
+            }
+            m *= 2;
+            stage += 1;
+        }
+    }
+}
+
+/// domain_of returns the domain whose first points are the given points, or
+/// None if there is none.
+pub fn domain_of<P: CurvePoint>(points: &[P::Scalar]) -> Option<EvaluationDomain<P>> {
+    let domain: EvaluationDomain<P> = EvaluationDomain::new(points.len()).ok()?;
+    if domain.points()[..points.len()] == *points {
+        Some(domain)
+    } else {
+        None
+    }
+}
+
+// square_times returns x^(2^k).
+fn square_times<S: ECScalar<SK>, SK>(mut x: S, k: u32) -> S {
+    for _ in 0..k {
+        x = x.mul(&x.get_element());
+    }
+    x
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::poly::PriPoly;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use crate::vss::{recover_secret_at, Dealer};
+
+    #[test]
+    fn test_fft() {
+        let domain: EvaluationDomain = EvaluationDomain::new(3).unwrap();
+        assert_eq!(4, domain.size());
+        let points = domain.points();
+        // primitive 4th root of unity
+        assert_eq!(points[0], square_times(points[1], 2));
+        assert_ne!(points[0], square_times(points[1], 1));
+        EvaluationDomain::<GE>::new(5).expect_err("Must fail, no domain of size 8");
+
+        let poly: PriPoly = PriPoly::new(3, None);
+        let evals = domain.fft(&poly.coeffs).unwrap();
+        for (x, v) in points.iter().zip(evals.iter()) {
+            assert_eq!(poly.eval_at(x), *v);
+        }
+        let coeffs = domain.ifft(&evals).unwrap();
+        assert_eq!(poly.coeffs, coeffs[..3].to_vec());
+        assert_eq!(FE::zero(), coeffs[3]);
+
+        // shares of the whole domain are checked
+        let secret = poly.coeffs[0];
+        let mut shares: Vec<PriShare<_>> = evals
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| PriShare { i: i as u32, v })
+            .collect();
+        assert_eq!(secret, domain.recover_secret(&shares, 3).unwrap());
+        assert_eq!(secret, domain.recover_secret(&shares[1..], 3).unwrap());
+        shares[2].v = shares[2].v.add(&secret.get_element());
+        domain
+            .recover_secret(&shares, 3)
+            .expect_err("Must fail, share not on the polynomial");
+
+        // shares of a dealer over the first points of a domain
+        let (secs, pubs) = sim::new_participants::<GE>(3);
+        let points = points[..3].to_vec();
+        assert!(domain_of::<GE>(&points).is_some());
+        assert!(domain_of::<GE>(&points[1..]).is_none());
+        let dealer: Dealer =
+            Dealer::new_with_points(secs[0], secret, pubs.clone(), points.clone(), 2).unwrap();
+        for (deal, x) in dealer.deals.iter().zip(points.iter()) {
+            deal.verify_at(&pubs, &deal.session_id, x).unwrap();
+        }
+        assert_eq!(
+            secret,
+            recover_secret_at(&dealer.deals, &points, 2).unwrap()
+        );
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn test_fft_secp256k1() {
+        use crate::secp256k1_curve::Secp256k1Point;
+
+        let domain: EvaluationDomain<Secp256k1Point> = EvaluationDomain::new(40).unwrap();
+        assert_eq!(64, domain.size());
+        EvaluationDomain::<Secp256k1Point>::new(65).expect_err("Must fail, no domain of size 128");
+        let poly: PriPoly<Secp256k1Point> = PriPoly::new(10, None);
+        let evals = domain.fft(&poly.coeffs).unwrap();
+        for (x, v) in domain.points().iter().zip(evals.iter()) {
+            assert_eq!(poly.eval_at(x), *v);
+        }
+        assert_eq!(poly.coeffs, domain.ifft(&evals).unwrap()[..10].to_vec());
+    }
+}
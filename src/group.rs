@@ -0,0 +1,351 @@
+//! Description of the group of a ceremony.
+//!
+//! A GroupConfig holds the ordered public keys of the participants, the
+//! threshold, the application context and the curve. Its content hash is
+//! exchanged before the ceremony, e.g. through a coordinator::Coordinator, and
+//! is the application context of the sessions built from the group, so that
+//! every session id, and so every message of the vss and of the DKG, is bound
+//! to it: a participant with another ordering of the keys fails on the first
+//! message instead of producing incompatible shares.
+//!
+//! GroupConfig::canonical orders the keys by their encoding, so that the
+//! index of a participant follows from its public key alone. The messages
+//! wrapped in a Keyed carry the public key of their sender, and
+//! GroupConfig::open checks that it matches the index in the message with an
+//! IdentityError naming the mismatch.
+//!
+//! The hash suite of the group, HashSuite::Blake2 by default, is the suite of
+//! the domain of its sessions: it is bound to the hash of the group and to the
+//! session ids. So is its HDerivation, the derivation of the second generator
+//! of the commitments, unless it is HDerivation::Legacy.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::ristretto_curve;
+use crate::vss;
+
+use curve_traits::CurvePoint;
+use encoding::{Domain, Encoder, HDerivation, HashSuite};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use vss::{Complaint, Response};
+
+/// GroupConfig describes the group of a ceremony.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct GroupConfig<P: CurvePoint = GE> {
+    // public keys of the participants, the index of a participant being its
+    // position
+    pub participants: Vec<P>,
+    // security parameter t
+    pub threshold: u32,
+    // application context of the ceremony
+    pub context: Vec<u8>,
+    // CurvePoint::CURVE_ID of the curve of the keys
+    pub curve: String,
+    // hash functions of the sessions of the group
+    #[serde(default)]
+    pub hash_suite: HashSuite,
+    // derivation of the second generator of the commitments of the group
+    #[serde(default)]
+    pub h_derivation: HDerivation,
+}
+
+impl<P: CurvePoint> GroupConfig<P> {
+    /// new returns the description of the group of the participants, in this
+    /// order, with the threshold and the application context.
+    pub fn new(
+        participants: Vec<P>,
+        threshold: u32,
+        context: &[u8],
+    ) -> Result<GroupConfig<P>, Box<dyn Error>> {
+        let group = GroupConfig {
+            participants,
+            threshold,
+            context: context.to_vec(),
+            curve: P::CURVE_ID.to_string(),
+            hash_suite: HashSuite::default(),
+            h_derivation: HDerivation::default(),
+        };
+        group.check()?;
+        Ok(group)
+    }
+
+    /// canonical returns the description of the group of the participants,
+    /// ordered by the encoding of their public keys whatever their order in
+    /// participants.
+    pub fn canonical(
+        mut participants: Vec<P>,
+        threshold: u32,
+        context: &[u8],
+    ) -> Result<GroupConfig<P>, Box<dyn Error>> {
+        participants.sort_by_cached_key(P::pk_to_key_slice);
+        GroupConfig::new(participants, threshold, context)
+    }
+
+    /// with_hash_suite returns the group hashing its sessions with suite.
+    pub fn with_hash_suite(mut self, suite: HashSuite) -> GroupConfig<P> {
+        self.hash_suite = suite;
+        self
+    }
+
+    /// with_h_derivation returns the group deriving the second generator of
+    /// the commitments with h_derivation.
+    pub fn with_h_derivation(mut self, h_derivation: HDerivation) -> GroupConfig<P> {
+        self.h_derivation = h_derivation;
+        self
+    }
+
+    /// domain returns the Domain of the sessions of the group.
+    pub fn domain(&self) -> Domain {
+        Domain::default()
+            .with_suite(self.hash_suite)
+            .with_h_derivation(self.h_derivation)
+    }
+
+    /// is_canonical returns true if the participants are in the order of
+    /// GroupConfig::canonical.
+    pub fn is_canonical(&self) -> bool {
+        self.participants
+            .windows(2)
+            .all(|w| w[0].pk_to_key_slice() <= w[1].pk_to_key_slice())
+    }
+
+    /// check checks that the group is over the curve P, with distinct
+    /// participants and a valid threshold.
+    pub fn check(&self) -> Result<(), Box<dyn Error>> {
+        if self.curve != P::CURVE_ID {
+            bail!(
+                "group: group over {} instead of {}",
+                self.curve,
+                P::CURVE_ID
+            );
+        }
+        for (i, p) in self.participants.iter().enumerate() {
+            if self.participants[..i].contains(p) {
+                bail!("group: duplicate participant at index {}", i);
+            }
+        }
+        if !vss::valid_t(self.threshold, &self.participants) {
+            bail!(
+                "group: invalid threshold {} for {} participants",
+                self.threshold,
+                self.participants.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// hash returns the content hash of the group, which is the context of the
+    /// sessions of the group.
+    pub fn hash(&self) -> [u8; 32] {
+        let participants: Vec<Vec<u8>> = self.participants.iter().map(P::pk_to_key_slice).collect();
+        let mut enc = Encoder::new(b"groupconfig")
+            .put_bytes(self.curve.as_bytes())
+            .put_u32(self.threshold)
+            .put_list(&participants)
+            .put_bytes(&self.context);
+        if self.hash_suite != HashSuite::default() {
+            enc = enc.put_bytes(self.hash_suite.id().as_bytes());
+        }
+        if self.h_derivation != HDerivation::Legacy {
+            enc = enc.put_bytes(self.h_derivation.id().as_bytes());
+        }
+        enc.hash()
+    }
+
+    /// index_of returns the index of the participant of the public key.
+    pub fn index_of(&self, public: &P) -> Option<u32> {
+        self.participants
+            .iter()
+            .position(|p| p == public)
+            .map(|i| i as u32)
+    }
+
+    /// index_for is index_of returning an IdentityError for a public key
+    /// out of the group.
+    pub fn index_for(&self, public: &P) -> Result<u32, IdentityError> {
+        self.index_of(public)
+            .ok_or_else(|| IdentityError::UnknownParticipant(public.pk_to_key_slice()))
+    }
+
+    /// seal wraps the message of the participant of the public key sender,
+    /// checking that the message holds its index.
+    pub fn seal<M: Sent>(&self, sender: &P, message: M) -> Result<Keyed<M, P>, IdentityError> {
+        let keyed = Keyed {
+            sender: *sender,
+            message,
+        };
+        self.open(&keyed)?;
+        Ok(keyed)
+    }
+
+    /// open returns the message of a Keyed, once checked that its sender is
+    /// a participant whose index is the one of the message.
+    pub fn open<'a, M: Sent>(&self, keyed: &'a Keyed<M, P>) -> Result<&'a M, IdentityError> {
+        let index = self.index_for(&keyed.sender)?;
+        let claimed = keyed.message.sender_index();
+        if index != claimed {
+            return Err(IdentityError::IndexMismatch { index, claimed });
+        }
+        Ok(&keyed.message)
+    }
+
+    /// check_hash checks the hash of the group received from another
+    /// participant.
+    pub fn check_hash(&self, hash: &[u8]) -> Result<(), Box<dyn Error>> {
+        if hash != self.hash() {
+            bail!("group: mismatching group description");
+        }
+        Ok(())
+    }
+}
+
+/// Sent is a message sent by a participant, holding its index.
+pub trait Sent {
+    /// sender_index returns the index of the sender held by the message.
+    fn sender_index(&self) -> u32;
+}
+
+impl Sent for Response {
+    fn sender_index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl<P: CurvePoint> Sent for Complaint<P> {
+    fn sender_index(&self) -> u32 {
+        self.response.index
+    }
+}
+
+/// Keyed is a message along with the public key of its sender.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "M: Serialize", deserialize = "M: Deserialize<'de>"))]
+pub struct Keyed<M, P: CurvePoint = GE> {
+    pub sender: P,
+    pub message: M,
+}
+
+/// IdentityError is the error of a public key not matching the index of a
+/// participant of the group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdentityError {
+    /// The public key, of the given encoding, isn't a participant.
+    UnknownParticipant(Vec<u8>),
+    /// The sender of a message is the participant at index, but the message
+    /// claims the index claimed.
+    IndexMismatch { index: u32, claimed: u32 },
+}
+
+impl fmt::Display for IdentityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentityError::UnknownParticipant(key) => {
+                write!(f, "group: public key {} not in the group", hex::encode(key))
+            }
+            IdentityError::IndexMismatch { index, claimed } => write!(
+                f,
+                "group: message of participant {} claiming index {}",
+                index, claimed
+            ),
+        }
+    }
+}
+
+impl Error for IdentityError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use curve_traits::{ECPoint, ECScalar};
+    use vss::{DealerBuilder, Verifier};
+
+    #[test]
+    fn test_group_identities() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(4);
+        let group: GroupConfig = GroupConfig::canonical(pubs.clone(), 3, b"").unwrap();
+        assert!(group.is_canonical());
+        let mut reversed = pubs.clone();
+        reversed.reverse();
+        assert_eq!(group, GroupConfig::canonical(reversed, 3, b"").unwrap());
+
+        // the deal of a verifier is addressed by its key
+        let dealer = DealerBuilder::from_group(secs[0], &group).build().unwrap();
+        let i = group.index_for(&pubs[2]).unwrap();
+        let mut v: Verifier = Verifier::for_group(secs[2], pubs[0], &group).unwrap();
+        let resp = v
+            .process_encrypted_deal(&dealer.encrypt_deal_to(&pubs[2]).unwrap())
+            .unwrap();
+        assert_eq!(i, resp.index);
+
+        let keyed = group.seal(&pubs[2], resp.clone()).unwrap();
+        assert_eq!(&resp, group.open(&keyed).unwrap());
+        let mut wrong = keyed.clone();
+        wrong.sender = pubs[1];
+        assert_eq!(
+            Err(IdentityError::IndexMismatch {
+                index: group.index_for(&pubs[1]).unwrap(),
+                claimed: i
+            }),
+            group.open(&wrong)
+        );
+        let outsider: GE = sim::new_participants(1).1[0];
+        wrong.sender = outsider;
+        assert_eq!(
+            Err(IdentityError::UnknownParticipant(
+                outsider.pk_to_key_slice()
+            )),
+            group.open(&wrong)
+        );
+        dealer
+            .encrypt_deal_to(&outsider)
+            .expect_err("Must fail, not a verifier");
+    }
+
+    #[test]
+    fn test_group_config() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(5);
+        let group: GroupConfig = GroupConfig::new(pubs.clone(), 3, b"wallet").unwrap();
+        group.check_hash(&group.hash()).unwrap();
+        assert_eq!(Some(2), group.index_of(&pubs[2]));
+
+        let mut swapped = pubs.clone();
+        swapped.swap(1, 2);
+        let other: GroupConfig = GroupConfig::new(swapped, 3, b"wallet").unwrap();
+        other
+            .check_hash(&group.hash())
+            .expect_err("Must fail, other ordering");
+        GroupConfig::new(vec![pubs[0], pubs[1], pubs[0]], 2, b"")
+            .expect_err("Must fail, duplicate participant");
+        let mut wrong = group.clone();
+        wrong.curve = "secp256k1".to_string();
+        wrong.check().expect_err("Must fail, other curve");
+
+        // a verifier with the other ordering rejects the deal
+        let secret: FE = ECScalar::new_random();
+        let dealer = DealerBuilder::from_group(secs[0], &group)
+            .secret(secret)
+            .build()
+            .unwrap();
+        let deal = dealer.encrypt_deal(3).unwrap();
+        let mut v: Verifier = Verifier::for_group(secs[3], pubs[0], &other).unwrap();
+        v.process_encrypted_deal(&deal)
+            .expect_err("Must fail, other group");
+        let mut v: Verifier = Verifier::for_group(secs[3], pubs[0], &group).unwrap();
+        assert!(v.process_encrypted_deal(&deal).unwrap().approved);
+
+        let mut dkg: DistKeyGenerator = DistKeyGenerator::for_group(secs[0], &group).unwrap();
+        let mut other_dkg: DistKeyGenerator = DistKeyGenerator::for_group(secs[3], &other).unwrap();
+        let deals = dkg.deals().unwrap();
+        other_dkg
+            .process_deal(&deals[&3])
+            .expect_err("Must fail, other group");
+    }
+}
@@ -0,0 +1,373 @@
+//! gRPC transport for the DKG, over mutually authenticated TLS.
+//!
+//! The services are defined in proto/dkg.proto: DealService carries the deals,
+//! ResponseService the responses, JustificationService the justifications and
+//! CommitService the messages of the secret commits phase. GrpcNode serves them
+//! and collects the received messages, while GrpcTransport queues the messages
+//! of the local participant and sends them with flush.
+//!
+//! Every participant has a TLS identity signed by a common CA. The server
+//! requires a client certificate, and a message claiming to come from the
+//! participant at index i is only accepted over a connection authenticated by
+//! the certificate of i.
+//! Enabled with the `grpc` feature.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use crate::broadcast;
+use crate::curve_traits;
+use crate::ristretto_curve;
+use crate::transport;
+use crate::wire;
+
+use broadcast::{EchoMessage, SlotKind};
+use curve_traits::CurvePoint;
+use ristretto_curve::GE;
+use tonic::transport::{
+    Certificate, Channel, ClientTlsConfig, Endpoint, Identity, Server, ServerTlsConfig,
+};
+use tonic::{Request, Response, Status};
+use transport::{Message, Transport};
+
+/// Code generated from proto/dkg.proto
+pub mod proto {
+    tonic::include_proto!("dkg");
+}
+
+use proto::commit_service_client::CommitServiceClient;
+use proto::commit_service_server::{CommitService, CommitServiceServer};
+use proto::deal_service_client::DealServiceClient;
+use proto::deal_service_server::{DealService, DealServiceServer};
+use proto::justification_service_client::JustificationServiceClient;
+use proto::justification_service_server::{JustificationService, JustificationServiceServer};
+use proto::response_service_client::ResponseServiceClient;
+use proto::response_service_server::{ResponseService, ResponseServiceServer};
+use proto::{Ack, Envelope};
+
+/// server_tls returns the TLS configuration of a server with the given
+/// identity, requiring client certificates signed by ca. All arguments are PEM
+/// encoded.
+pub fn server_tls(cert: &[u8], key: &[u8], ca: &[u8]) -> ServerTlsConfig {
+    ServerTlsConfig::new()
+        .identity(Identity::from_pem(cert, key))
+        .client_ca_root(Certificate::from_pem(ca))
+}
+
+/// client_tls returns the TLS configuration of a client with the given
+/// identity, accepting servers named domain with a certificate signed by ca.
+/// All arguments but domain are PEM encoded.
+pub fn client_tls(cert: &[u8], key: &[u8], ca: &[u8], domain: &str) -> ClientTlsConfig {
+    ClientTlsConfig::new()
+        .domain_name(domain)
+        .ca_certificate(Certificate::from_pem(ca))
+        .identity(Identity::from_pem(cert, key))
+}
+
+// Kind of the messages carried by each service
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Deal,
+    Response,
+    Justification,
+    Commits,
+}
+
+fn kind<P: CurvePoint>(msg: &Message<P>) -> Kind {
+    match msg {
+        Message::Deal(_) => Kind::Deal,
+        Message::Response(_) => Kind::Response,
+        Message::Justification(_) => Kind::Justification,
+        Message::SecretCommits(_)
+        | Message::ComplaintCommits(_)
+        | Message::ReconstructCommits(_) => Kind::Commits,
+        // echoes go through the service of the message they echo
+        Message::Echo(EchoMessage::Send(m)) | Message::Echo(EchoMessage::Final(m, _)) => kind(m),
+        Message::Echo(EchoMessage::Echo(e)) => match e.slot.kind {
+            SlotKind::Response => Kind::Response,
+            SlotKind::Justification => Kind::Justification,
+        },
+    }
+}
+
+/// GrpcNode implements the services of the local participant and stores the
+/// messages it receives. Clones share the same inbox.
+#[derive(Clone)]
+pub struct GrpcNode<P: CurvePoint = GE> {
+    // DER encoded client certificate of each participant
+    client_certs: Arc<Vec<Vec<u8>>>,
+    inbox: Arc<Mutex<VecDeque<(u32, Message<P>)>>>,
+}
+
+impl<P: CurvePoint> GrpcNode<P> {
+    /// new returns the node of a participant, client_certs[i] being the DER
+    /// encoded client certificate of the participant at index i.
+    pub fn new(client_certs: Vec<Vec<u8>>) -> GrpcNode<P> {
+        GrpcNode {
+            client_certs: Arc::new(client_certs),
+            inbox: Default::default(),
+        }
+    }
+
+    /// recv returns the next received message along with the index of its
+    /// sender.
+    pub fn recv(&self) -> Option<(u32, Message<P>)> {
+        self.inbox.lock().ok()?.pop_front()
+    }
+
+    /// serve runs the services on addr until an error occurs.
+    pub async fn serve(self, addr: SocketAddr, tls: ServerTlsConfig) -> Result<(), Box<dyn Error>> {
+        Server::builder()
+            .tls_config(tls)
+            .add_service(DealServiceServer::new(self.clone()))
+            .add_service(ResponseServiceServer::new(self.clone()))
+            .add_service(JustificationServiceServer::new(self.clone()))
+            .add_service(CommitServiceServer::new(self))
+            .serve(addr)
+            .await?;
+        Ok(())
+    }
+
+    // accept checks that an envelope received by the service of the given kind
+    // comes from the participant it claims and stores its message.
+    fn accept(
+        &self,
+        envelope: &Envelope,
+        peer_certs: &[Certificate],
+        expected: Kind,
+    ) -> Result<(), Status> {
+        let cert = self
+            .client_certs
+            .get(envelope.from as usize)
+            .ok_or_else(|| Status::invalid_argument("unknown participant"))?;
+        if !peer_certs.iter().any(|c| c.get_ref() == cert.as_slice()) {
+            return Err(Status::unauthenticated(
+                "certificate of another participant",
+            ));
+        }
+
+        // the threshold isn't known to the transport, and is at most n
+        let n = self.client_certs.len() as u32;
+        let msg: Message<P> = wire::decode_message(&envelope.payload, n)
+            .map_err(|_| Status::invalid_argument("invalid message"))?;
+        if kind(&msg) != expected {
+            return Err(Status::invalid_argument(
+                "message sent to the wrong service",
+            ));
+        }
+
+        self.inbox
+            .lock()
+            .map_err(|_| Status::internal("poisoned inbox"))?
+            .push_back((envelope.from, msg));
+        Ok(())
+    }
+
+    fn receive(&self, request: Request<Envelope>, expected: Kind) -> Result<Response<Ack>, Status> {
+        let peer_certs = request
+            .peer_certs()
+            .ok_or_else(|| Status::unauthenticated("no client certificate"))?;
+        self.accept(request.get_ref(), &peer_certs, expected)?;
+        Ok(Response::new(Ack {}))
+    }
+}
+
+#[tonic::async_trait]
+impl<P: CurvePoint> DealService for GrpcNode<P> {
+    async fn send_deal(&self, request: Request<Envelope>) -> Result<Response<Ack>, Status> {
+        self.receive(request, Kind::Deal)
+    }
+}
+
+#[tonic::async_trait]
+impl<P: CurvePoint> ResponseService for GrpcNode<P> {
+    async fn send_response(&self, request: Request<Envelope>) -> Result<Response<Ack>, Status> {
+        self.receive(request, Kind::Response)
+    }
+}
+
+#[tonic::async_trait]
+impl<P: CurvePoint> JustificationService for GrpcNode<P> {
+    async fn send_justification(
+        &self,
+        request: Request<Envelope>,
+    ) -> Result<Response<Ack>, Status> {
+        self.receive(request, Kind::Justification)
+    }
+}
+
+#[tonic::async_trait]
+impl<P: CurvePoint> CommitService for GrpcNode<P> {
+    async fn send_commits(&self, request: Request<Envelope>) -> Result<Response<Ack>, Status> {
+        self.receive(request, Kind::Commits)
+    }
+}
+
+/// GrpcTransport is the Transport of the local participant over gRPC. Sent
+/// messages are queued until flush delivers them, and received messages are
+/// read from the GrpcNode of the participant.
+pub struct GrpcTransport<P: CurvePoint = GE> {
+    index: u32,
+    node: GrpcNode<P>,
+    endpoints: Vec<Endpoint>,
+    channels: Vec<Option<Channel>>,
+    outbox: VecDeque<(u32, Message<P>)>,
+}
+
+impl<P: CurvePoint> GrpcTransport<P> {
+    /// new returns the transport of the participant at index, uris[i] being the
+    /// address of the server of the participant at index i.
+    pub fn new(
+        index: u32,
+        node: GrpcNode<P>,
+        uris: Vec<String>,
+        tls: ClientTlsConfig,
+    ) -> Result<GrpcTransport<P>, Box<dyn Error>> {
+        if index as usize >= uris.len() {
+            bail!("grpc: index out of the participants");
+        }
+        let endpoints: Vec<Endpoint> = uris
+            .into_iter()
+            .map(|uri| Ok(Endpoint::from_shared(uri)?.tls_config(tls.clone())))
+            .collect::<Result<_, Box<dyn Error>>>()?;
+        Ok(GrpcTransport {
+            index,
+            node,
+            channels: endpoints.iter().map(|_| None).collect(),
+            endpoints,
+            outbox: VecDeque::new(),
+        })
+    }
+
+    async fn channel(&mut self, to: u32) -> Result<Channel, Box<dyn Error>> {
+        if let Some(channel) = &self.channels[to as usize] {
+            return Ok(channel.clone());
+        }
+        let channel = self.endpoints[to as usize].connect().await?;
+        self.channels[to as usize] = Some(channel.clone());
+        Ok(channel)
+    }
+
+    /// flush sends the queued messages. It stops at the first failure, the
+    /// message which failed staying queued.
+    pub async fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        while let Some((to, msg)) = self.outbox.pop_front() {
+            if let Err(e) = self.deliver(to, &msg).await {
+                // reconnect on the next attempt
+                self.channels[to as usize] = None;
+                self.outbox.push_front((to, msg));
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn deliver(&mut self, to: u32, msg: &Message<P>) -> Result<(), Box<dyn Error>> {
+        let channel = self.channel(to).await?;
+        let envelope = Envelope {
+            from: self.index,
+            payload: bincode::serialize(msg)?,
+        };
+        match kind(msg) {
+            Kind::Deal => {
+                DealServiceClient::new(channel).send_deal(envelope).await?;
+            }
+            Kind::Response => {
+                ResponseServiceClient::new(channel)
+                    .send_response(envelope)
+                    .await?;
+            }
+            Kind::Justification => {
+                JustificationServiceClient::new(channel)
+                    .send_justification(envelope)
+                    .await?;
+            }
+            Kind::Commits => {
+                CommitServiceClient::new(channel)
+                    .send_commits(envelope)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P: CurvePoint> Transport<P> for GrpcTransport<P> {
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn send(&mut self, to: u32, msg: Message<P>) -> Result<(), Box<dyn Error>> {
+        if to as usize >= self.endpoints.len() {
+            bail!("grpc: unknown recipient {}", to);
+        }
+        self.outbox.push_back((to, msg));
+        Ok(())
+    }
+
+    fn broadcast(&mut self, msg: Message<P>) -> Result<(), Box<dyn Error>> {
+        for to in 0..self.endpoints.len() as u32 {
+            if to != self.index {
+                self.outbox.push_back((to, msg.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Option<(u32, Message<P>)>, Box<dyn Error>> {
+        Ok(self.node.recv())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg;
+    use crate::vss;
+
+    fn envelope(from: u32, msg: &Message) -> Envelope {
+        Envelope {
+            from,
+            payload: bincode::serialize(msg).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_grpc_node_accept() {
+        let certs: Vec<Vec<u8>> = vec![vec![0; 16], vec![1; 16]];
+        let node: GrpcNode = GrpcNode::new(certs.clone());
+        let cert_1 = vec![Certificate::from_pem(&certs[1])];
+        let resp: Message = Message::Response(dkg::Response {
+            index: 0,
+            // well-formed, as the transports check the sizes of the fields
+            response: vss::Response {
+                session_id: vec![0; 32],
+                signature: vec![0; 64],
+                ..Default::default()
+            },
+        });
+
+        node.accept(&envelope(1, &resp), &cert_1, Kind::Response)
+            .unwrap();
+        match node.recv() {
+            Some((1, Message::Response(_))) => (),
+            _ => panic!("expected a response from participant 1"),
+        }
+
+        node.accept(&envelope(0, &resp), &cert_1, Kind::Response)
+            .expect_err("Must fail, certificate of another participant");
+        node.accept(&envelope(2, &resp), &cert_1, Kind::Response)
+            .expect_err("Must fail, unknown participant");
+        node.accept(&envelope(1, &resp), &cert_1, Kind::Deal)
+            .expect_err("Must fail, wrong service");
+        let invalid = Envelope {
+            from: 1,
+            payload: vec![0xff],
+        };
+        node.accept(&invalid, &cert_1, Kind::Response)
+            .expect_err("Must fail, invalid message");
+        assert!(node.recv().is_none());
+    }
+}
@@ -0,0 +1,241 @@
+//! Hierarchical deterministic derivation of child keys of a distributed key.
+//!
+//! As the non-hardened derivation of BIP32, a child key is the parent key
+//! tweaked by a public scalar: with I = HMAC-SHA512(chain code, K || index),
+//! the child public key is K + I_L * G and its chain code I_R. Adding I_L to
+//! every share of the distributed key gives the shares of the child key, so
+//! that the holders derive their child shares locally and one DKG backs many
+//! addresses. Over secp256k1, the derivation of the public keys is the one of
+//! BIP32, but for the negligible indices BIP32 skips. Hardened derivation needs
+//! the private key and isn't supported.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+
+use curve_traits::{CurvePoint, ECScalar};
+use dkg::DistKeyShare;
+use encoding::Encoder;
+use hkdf::Hkdf;
+use poly::PriShare;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+
+/// First index of the hardened children
+pub const HARDENED: u32 = 1 << 31;
+
+/// ExtendedPubKey is a public key along with its chain code, from which the
+/// public keys of the children are derived.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ExtendedPubKey<P: CurvePoint = GE> {
+    pub public: P,
+    pub chain_code: [u8; 32],
+}
+
+/// ExtendedKeyShare is the share of a distributed key along with the chain
+/// code of the key, from which the shares of the children are derived.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ExtendedKeyShare<P: CurvePoint = GE> {
+    share: DistKeyShare<P>,
+    chain_code: [u8; 32],
+}
+
+/// root_chain_code returns the default chain code of a distributed key, bound
+/// to its public polynomial.
+pub fn root_chain_code<P: CurvePoint>(share: &DistKeyShare<P>) -> [u8; 32] {
+    let commits: Vec<Vec<u8>> = share
+        .get_commitments()
+        .iter()
+        .map(P::pk_to_key_slice)
+        .collect();
+    Encoder::new(b"hdchaincode").put_list(&commits).hash()
+}
+
+// child_tweak returns the tweak I_L and the chain code I_R of the child at
+// index of the public key.
+fn child_tweak<P: CurvePoint>(
+    public: &P,
+    chain_code: &[u8; 32],
+    index: u32,
+) -> Result<(P::Scalar, [u8; 32]), Box<dyn Error>> {
+    if index >= HARDENED {
+        bail!("hd: hardened derivation isn't possible without the private key");
+    }
+    let mut data: Vec<u8> = public.pk_to_key_slice();
+    data.extend_from_slice(&index.to_be_bytes());
+    // HKDF-Extract is HMAC with the salt as key
+    let (i, _) = Hkdf::<Sha512>::extract(Some(chain_code), &data);
+    let tweak: P::Scalar = ECScalar::from_bytes(&i[..32])?;
+    let mut child_chain_code = [0u8; 32];
+    child_chain_code.copy_from_slice(&i[32..]);
+    Ok((tweak, child_chain_code))
+}
+
+impl<P: CurvePoint> ExtendedPubKey<P> {
+    pub fn new(public: P, chain_code: [u8; 32]) -> ExtendedPubKey<P> {
+        ExtendedPubKey { public, chain_code }
+    }
+
+    /// derive_child returns the extended public key of the child at index.
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedPubKey<P>, Box<dyn Error>> {
+        let (tweak, chain_code) = child_tweak(&self.public, &self.chain_code, index)?;
+        let public: P = P::generator()
+            .scalar_mul(&tweak.get_element())
+            .add_point(&self.public.get_element());
+        if public == P::identity() {
+            bail!("hd: invalid child at index {}", index);
+        }
+        Ok(ExtendedPubKey { public, chain_code })
+    }
+
+    /// derive_path returns the extended public key at the path of indices.
+    pub fn derive_path(&self, path: &[u32]) -> Result<ExtendedPubKey<P>, Box<dyn Error>> {
+        path.iter()
+            .try_fold(self.clone(), |key, &index| key.derive_child(index))
+    }
+}
+
+impl<P: CurvePoint> ExtendedKeyShare<P> {
+    pub fn new(share: DistKeyShare<P>, chain_code: [u8; 32]) -> ExtendedKeyShare<P> {
+        ExtendedKeyShare { share, chain_code }
+    }
+
+    /// from_dist_key returns the extended share of the distributed key with
+    /// its root chain code.
+    pub fn from_dist_key(share: DistKeyShare<P>) -> ExtendedKeyShare<P> {
+        let chain_code = root_chain_code(&share);
+        ExtendedKeyShare { share, chain_code }
+    }
+
+    /// extended_pub_key returns the extended public key matching the share.
+    pub fn extended_pub_key(&self) -> ExtendedPubKey<P> {
+        ExtendedPubKey::new(self.share.get_public_key(), self.chain_code)
+    }
+
+    /// dist_key_share returns the share of the distributed key, e.g. to sign
+    /// with the tss module.
+    pub fn dist_key_share(&self) -> &DistKeyShare<P> {
+        &self.share
+    }
+
+    /// derive_child returns the share of the child at index. Every holder
+    /// derives its own share, without interaction.
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedKeyShare<P>, Box<dyn Error>> {
+        let public: P = self.share.get_public_key();
+        let (tweak, chain_code) = child_tweak(&public, &self.chain_code, index)?;
+        // the constant term of the polynomial is tweaked, so is every share
+        let mut commits: Vec<P> = self.share.get_commitments().to_vec();
+        commits[0] = P::generator()
+            .scalar_mul(&tweak.get_element())
+            .add_point(&public.get_element());
+        if commits[0] == P::identity() {
+            bail!("hd: invalid child at index {}", index);
+        }
+        let pri_share: PriShare<P::Scalar> = self.share.get_pri_share();
+        let share = PriShare {
+            i: pri_share.i,
+            v: pri_share.v.add(&tweak.get_element()),
+        };
+        Ok(ExtendedKeyShare {
+            share: DistKeyShare::new(commits, share),
+            chain_code,
+        })
+    }
+
+    /// derive_path returns the share at the path of indices.
+    pub fn derive_path(&self, path: &[u32]) -> Result<ExtendedKeyShare<P>, Box<dyn Error>> {
+        path.iter()
+            .try_fold(self.clone(), |share, &index| share.derive_child(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use curve_traits::ECPoint;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_hd_shares() {
+        let (n, t) = (5, 3);
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(n);
+        let mut dkgs: Vec<DistKeyGenerator> = secs
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, pubs.clone(), t).unwrap())
+            .collect();
+        let network = sim::Network::new(n, Default::default(), ChaCha20Rng::seed_from_u64(42));
+        let shares: Vec<ExtendedKeyShare> = sim::run_dkg(&network, &mut dkgs, 100)
+            .unwrap()
+            .into_iter()
+            .map(|s| ExtendedKeyShare::from_dist_key(s.unwrap()))
+            .collect();
+        let xpub: ExtendedPubKey = shares[0].extended_pub_key();
+
+        let path = [0, 7, 42];
+        let child_pub = xpub.derive_path(&path).unwrap();
+        assert_ne!(xpub.public, child_pub.public);
+        let children: Vec<ExtendedKeyShare> = shares
+            .iter()
+            .map(|s| s.derive_path(&path).unwrap())
+            .collect();
+        let pri_shares: Vec<PriShare<FE>> = children
+            .iter()
+            .map(|c| {
+                assert_eq!(child_pub, c.extended_pub_key());
+                let share = c.dist_key_share();
+                assert!(share.pub_poly().check(&share.get_pri_share()));
+                share.get_pri_share()
+            })
+            .collect();
+        let secret: FE = poly::recover_secret(&pri_shares[1..], t).unwrap();
+        assert_eq!(
+            child_pub.public,
+            GE::generator().scalar_mul(&secret.get_element())
+        );
+
+        xpub.derive_child(HARDENED)
+            .expect_err("Must fail, hardened index");
+        shares[0]
+            .derive_child(HARDENED + 1)
+            .expect_err("Must fail, hardened index");
+    }
+
+    #[cfg(feature = "secp256k1")]
+    #[test]
+    fn test_hd_bip32_vector() {
+        use crate::secp256k1_curve::GE as Secp256k1;
+        // test vector 1 of BIP32, from m/0H to m/0H/1
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(
+            &hex::decode("47fdacbd0f1097043b78c63c20c34ef4ed9a111d980047ad16282c7ae6236141")
+                .unwrap(),
+        );
+        let public = Secp256k1::from_bytes(
+            &hex::decode("035a784662a4a20a65bf6aab9ae98a6c068a81c52e4b032c0fb5400c706cfccc56")
+                .unwrap(),
+        )
+        .unwrap();
+        let child = ExtendedPubKey::new(public, chain_code)
+            .derive_child(1)
+            .unwrap();
+        assert_eq!(
+            "03501e454bf00751f24b1b489aa925215d66af2234e3891c3c21a52bedb3cd711c",
+            hex::encode(child.public.pk_to_key_slice())
+        );
+        assert_eq!(
+            "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19",
+            hex::encode(child.chain_code)
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Hybrid Public Key Encryption of RFC 9180
+//!
+//! Key schedule of HPKE in base mode with HKDF-SHA256, for the DHKEM of section
+//! 4.1 over the group of a curve backend. The vss Dealer encrypts a deal in this
+//! mode with DealMode::Hpke: the ephemeral Diffie Hellman key of the
+//! EncryptedDeal is the encapsulated key, and both the info and the aad are the
+//! context of the dealer and verifiers keys.
+
+use crate::curve_traits;
+
+use curve_traits::{CurvePoint, ECPoint};
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+/// KEM_ID identifies the DHKEM of the curve backend. The groups of the curve
+/// backends have no identifier in the IANA registry, so a single unassigned
+/// one is used for all of them.
+pub const KEM_ID: u16 = 0xff00;
+
+/// KDF_ID identifies HKDF-SHA256.
+pub const KDF_ID: u16 = 0x0001;
+
+/// MODE_BASE is the mode without sender authentication, as the dealer signs
+/// the EncryptedDeal instead.
+pub const MODE_BASE: u8 = 0x00;
+
+/// NONCE_SIZE is the length of the nonce of the AEADs.
+pub const NONCE_SIZE: usize = 12;
+
+/// shared_secret returns the shared secret of the KEM, from the Diffie Hellman
+/// key dh of the encapsulated key enc with the key of the recipient
+/// (ExtractAndExpand of section 4.1).
+pub fn shared_secret<P: CurvePoint>(dh: &P, enc: &P, recipient: &P) -> [u8; 32] {
+    let suite_id = [&b"KEM"[..], &KEM_ID.to_be_bytes()[..]].concat();
+    let kem_context = [enc.pk_to_key_slice(), recipient.pk_to_key_slice()].concat();
+    let eae_prk = labeled_extract(&suite_id, b"", b"eae_prk", &dh.pk_to_key_slice());
+    let mut secret = [0u8; 32];
+    labeled_expand(
+        &suite_id,
+        &eae_prk,
+        b"shared_secret",
+        &kem_context,
+        &mut secret,
+    );
+    secret
+}
+
+/// key_schedule returns the key and the nonce of the first message of the AEAD
+/// identified by aead_id, with a 256-bit key (KeySchedule of section 5.1).
+pub fn key_schedule(
+    shared_secret: &[u8],
+    aead_id: u16,
+    info: &[u8],
+) -> ([u8; 32], [u8; NONCE_SIZE]) {
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; NONCE_SIZE];
+    key_schedule_with(KEM_ID, shared_secret, aead_id, info, &mut key, &mut nonce);
+    (key, nonce)
+}
+
+// key_schedule_with is key_schedule for any KEM and key size.
+fn key_schedule_with(
+    kem_id: u16,
+    shared_secret: &[u8],
+    aead_id: u16,
+    info: &[u8],
+    key: &mut [u8],
+    nonce: &mut [u8],
+) {
+    let suite_id = [
+        &b"HPKE"[..],
+        &kem_id.to_be_bytes()[..],
+        &KDF_ID.to_be_bytes()[..],
+        &aead_id.to_be_bytes()[..],
+    ]
+    .concat();
+    let psk_id_hash = labeled_extract(&suite_id, b"", b"psk_id_hash", b"");
+    let info_hash = labeled_extract(&suite_id, b"", b"info_hash", info);
+    let ks_context = [&[MODE_BASE][..], &psk_id_hash[..], &info_hash[..]].concat();
+
+    let secret = labeled_extract(&suite_id, shared_secret, b"secret", b"");
+    labeled_expand(&suite_id, &secret, b"key", &ks_context, key);
+    labeled_expand(&suite_id, &secret, b"base_nonce", &ks_context, nonce);
+}
+
+// labeled_extract is LabeledExtract of section 4.
+fn labeled_extract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> Vec<u8> {
+    let labeled_ikm = [&b"HPKE-v1"[..], suite_id, label, ikm].concat();
+    let (prk, _) = Hkdf::<Sha256>::extract(Some(salt), &labeled_ikm);
+    prk.to_vec()
+}
+
+// labeled_expand is LabeledExpand of section 4.
+fn labeled_expand(suite_id: &[u8], prk: &[u8], label: &[u8], info: &[u8], okm: &mut [u8]) {
+    let len = (okm.len() as u16).to_be_bytes();
+    let labeled_info = [&len[..], &b"HPKE-v1"[..], suite_id, label, info].concat();
+    let h = Hkdf::<Sha256>::from_prk(prk).unwrap();
+    h.expand(&labeled_info, okm).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_schedule_rfc9180() {
+        // test vector A.1.1 of RFC 9180: DHKEM(X25519, HKDF-SHA256), HKDF-SHA256,
+        // AES-128-GCM in base mode
+        let shared_secret =
+            hex::decode("fe0e18c9f024ce43799ae393c7e8fe8fce9d218875e8227b0187c04e7d2ea1fc")
+                .unwrap();
+        let info = hex::decode("4f6465206f6e2061204772656369616e2055726e").unwrap();
+        let mut key = [0u8; 16];
+        let mut nonce = [0u8; NONCE_SIZE];
+        key_schedule_with(0x0020, &shared_secret, 0x0001, &info, &mut key, &mut nonce);
+        assert_eq!("4531685d41d65f03dc48f6b8302c05b0", hex::encode(key));
+        assert_eq!("56d890e5accaaf011cff4b7d", hex::encode(nonce));
+    }
+}
@@ -0,0 +1,205 @@
+//! Property-based invariants of the vss over randomized runs, enabled with the
+//! `invariants` feature, for downstream crates to check their own use of the
+//! protocol with proptest.
+//!
+//! run_params is a proptest Strategy of RunParams: the number of verifiers n,
+//! the threshold t, the order in which the responses are delivered, the
+//! verifiers which stay offline and the seed of the keys and polynomials. run
+//! plays the session of these parameters between honest participants and
+//! check asserts the invariants of its Outcome:
+//!  - a certified deal has at least t approvals;
+//!  - the dealer and the online verifiers agree on the certification, which
+//!    happens iff at least t verifiers are online;
+//!  - the deals of a certified session recover the dealt secret.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECScalar};
+use proptest::collection;
+use proptest::prelude::*;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use vss::{Deal, Dealer, EncryptedDeal, Response, Verifier};
+
+/// RunParams are the parameters of a randomized run.
+#[derive(Clone, Debug)]
+pub struct RunParams {
+    // number of verifiers
+    pub n: u32,
+    // threshold, between 2 and n
+    pub t: u32,
+    // permutation of the verifier indices, the order of delivery of their
+    // responses
+    pub order: Vec<u32>,
+    // offline[i] is true if the verifier i never processes its deal
+    pub offline: Vec<bool>,
+    // seed of the keys and of the polynomials
+    pub seed: [u8; 32],
+}
+
+/// run_params returns the Strategy of the parameters of runs of at most
+/// max_n verifiers, max_n being at least 2.
+pub fn run_params(max_n: u32) -> impl Strategy<Value = RunParams> {
+    (2..=max_n.max(2))
+        .prop_flat_map(|n| {
+            (
+                Just(n),
+                2..=n,
+                Just((0..n).collect::<Vec<u32>>()).prop_shuffle(),
+                collection::vec(any::<bool>(), n as usize),
+                any::<[u8; 32]>(),
+            )
+        })
+        .prop_map(|(n, t, order, offline, seed)| RunParams {
+            n,
+            t,
+            order,
+            offline,
+            seed,
+        })
+}
+
+/// Outcome is the state of the participants at the end of a run.
+#[derive(Clone, Debug)]
+pub struct Outcome<P: CurvePoint> {
+    // dealt secret
+    pub secret: P::Scalar,
+    pub dealer_certified: bool,
+    // certification of every online verifier, by index
+    pub verifiers_certified: Vec<(u32, bool)>,
+    // indices of the verifiers whose approval the dealer received
+    pub approvals: Vec<u32>,
+    // secret recovered from the deals of the online verifiers, if the deal
+    // is certified
+    pub recovered: Option<P::Scalar>,
+}
+
+/// run plays the session of the parameters: the online verifiers process
+/// their deals, their responses are delivered in order to the dealer and to
+/// the other online verifiers, and every participant times out the missing
+/// ones.
+pub fn run<P: CurvePoint>(params: &RunParams) -> Result<Outcome<P>, Box<dyn Error>> {
+    let mut rng = ChaCha20Rng::from_seed(params.seed);
+    let dealer_secret: P::Scalar = ECScalar::new_random_with_rng(&mut rng);
+    let verifier_secrets: Vec<P::Scalar> = (0..params.n)
+        .map(|_| ECScalar::new_random_with_rng(&mut rng))
+        .collect();
+    let verifier_publics: Vec<P> = verifier_secrets.iter().map(P::base_mul).collect();
+    let secret: P::Scalar = ECScalar::new_random_with_rng(&mut rng);
+
+    let mut dealer: Dealer<P> = Dealer::new_with_rng(
+        dealer_secret,
+        secret,
+        verifier_publics.clone(),
+        params.t,
+        &mut rng,
+    )?;
+    let encrypted: Vec<EncryptedDeal<P>> = dealer.encrypt_deals_with_rng(&mut rng)?;
+
+    let mut verifiers: Vec<Verifier<P>> = Vec::new();
+    let mut responses: Vec<Option<Response>> = vec![None; params.n as usize];
+    for (i, encr_d) in encrypted.iter().enumerate() {
+        if params.offline.get(i).copied().unwrap_or(false) {
+            continue;
+        }
+        let mut verifier: Verifier<P> = Verifier::new(
+            verifier_secrets[i],
+            P::base_mul(&dealer_secret),
+            verifier_publics.clone(),
+        )?;
+        responses[i] = Some(verifier.process_encrypted_deal(encr_d)?);
+        verifiers.push(verifier);
+    }
+
+    for &i in params.order.iter() {
+        let resp = match &responses[i as usize] {
+            Some(resp) => resp,
+            None => continue,
+        };
+        if dealer.process_response(resp)?.is_some() {
+            bail!("invariants: justification of an honest deal");
+        }
+        for verifier in verifiers.iter_mut().filter(|v| v.index() != i) {
+            verifier.process_response(resp)?;
+        }
+    }
+    dealer.set_timeout();
+    for verifier in verifiers.iter_mut() {
+        verifier.set_timeout();
+    }
+
+    let dealer_certified = dealer.deal_certified();
+    let recovered = if dealer_certified {
+        let deals: Vec<Deal<P>> = verifiers
+            .iter()
+            .map(|v| v.get_deal())
+            .collect::<Result<_, _>>()?;
+        Some(vss::recover_secret(&deals, params.t)?)
+    } else {
+        None
+    };
+    Ok(Outcome {
+        secret,
+        dealer_certified,
+        verifiers_certified: verifiers
+            .iter()
+            .map(|v| (v.index(), v.deal_certified()))
+            .collect(),
+        approvals: dealer.approvals(),
+        recovered,
+    })
+}
+
+/// check returns an error naming the first invariant the outcome of the run
+/// of the parameters breaks.
+pub fn check<P: CurvePoint>(
+    params: &RunParams,
+    outcome: &Outcome<P>,
+) -> Result<(), Box<dyn Error>> {
+    let online = params.offline.iter().filter(|&&offline| !offline).count() as u32;
+    if outcome.dealer_certified && (outcome.approvals.len() as u32) < params.t {
+        bail!(
+            "invariants: certified with {} approvals under the threshold {}",
+            outcome.approvals.len(),
+            params.t
+        );
+    }
+    if outcome.dealer_certified != (online >= params.t) {
+        bail!(
+            "invariants: certified is {} with {} online verifiers of threshold {}",
+            outcome.dealer_certified,
+            online,
+            params.t
+        );
+    }
+    if let Some((i, _)) = outcome
+        .verifiers_certified
+        .iter()
+        .find(|(_, certified)| *certified != outcome.dealer_certified)
+    {
+        bail!("invariants: verifier {} disagrees with the dealer", i);
+    }
+    if outcome.dealer_certified && outcome.recovered != Some(outcome.secret) {
+        bail!("invariants: recovered secret not the dealt one");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve::GE;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_vss_invariants(params in run_params(7)) {
+            let outcome = run::<GE>(&params).unwrap();
+            check(&params, &outcome).unwrap();
+        }
+    }
+}
@@ -0,0 +1,584 @@
+//! Protobuf encoding of the vss and dkg messages compatible with the rabin
+//! packages of DEDIS kyber (and thus drand).
+//!
+//! kyber marshals its messages with dedis/protobuf: the fields of a message are
+//! numbered in the order of the Go struct, points and scalars are encoded as
+//! bytes, unsigned integers as varints and signed integers with zigzag. The
+//! messages below mirror these Go structs so that a Rust node can exchange them
+//! with Go nodes. Fields which only exist in this crate, such as the version
+//! and session id of an EncryptedDeal, are appended with new field numbers and
+//! are skipped by Go decoders. kyber derives the generator H with blake2xb
+//! rejection sampling, so the domain of a group shared with Go nodes must be
+//! built with `HDerivation::Legacy`.
+//! Enabled with the `kyber-compat` feature.
+
+use std::convert::TryInto;
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::poly;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECScalar};
+use poly::PriShare;
+use prost::Message;
+
+/// KyberMessage is implemented by the messages which can be exchanged with
+/// kyber nodes.
+pub trait KyberMessage: Sized {
+    /// to_kyber_bytes returns the protobuf encoding of the message.
+    fn to_kyber_bytes(&self) -> Vec<u8>;
+    /// from_kyber_bytes reads a message from its protobuf encoding.
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>>;
+}
+
+// share.PriShare
+#[derive(Clone, PartialEq, Message)]
+struct PbPriShare {
+    #[prost(sint64, tag = "1")]
+    i: i64,
+    #[prost(bytes, tag = "2")]
+    v: Vec<u8>,
+}
+
+// vss.Deal
+#[derive(Clone, PartialEq, Message)]
+struct PbVssDeal {
+    #[prost(bytes, tag = "1")]
+    session_id: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    sec_share: Option<PbPriShare>,
+    #[prost(message, optional, tag = "3")]
+    rnd_share: Option<PbPriShare>,
+    #[prost(uint32, tag = "4")]
+    t: u32,
+    #[prost(bytes, repeated, tag = "5")]
+    commitments: Vec<Vec<u8>>,
+}
+
+// vss.EncryptedDeal
+#[derive(Clone, PartialEq, Message)]
+struct PbEncryptedDeal {
+    #[prost(bytes, tag = "1")]
+    dh_key: Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    signature: Vec<u8>,
+    #[prost(bytes, tag = "3")]
+    nonce: Vec<u8>,
+    #[prost(bytes, tag = "4")]
+    cipher: Vec<u8>,
+    // not part of kyber
+    #[prost(uint32, tag = "5")]
+    version: u32,
+    // not part of kyber
+    #[prost(bytes, tag = "6")]
+    session_id: Vec<u8>,
+}
+
+// vss.Response
+#[derive(Clone, PartialEq, Message)]
+struct PbVssResponse {
+    #[prost(bytes, tag = "1")]
+    session_id: Vec<u8>,
+    #[prost(uint32, tag = "2")]
+    index: u32,
+    #[prost(bool, tag = "3")]
+    status: bool,
+    #[prost(bytes, tag = "4")]
+    signature: Vec<u8>,
+}
+
+// vss.Justification
+#[derive(Clone, PartialEq, Message)]
+struct PbVssJustification {
+    #[prost(bytes, tag = "1")]
+    session_id: Vec<u8>,
+    #[prost(uint32, tag = "2")]
+    index: u32,
+    #[prost(message, optional, tag = "3")]
+    deal: Option<PbVssDeal>,
+    #[prost(bytes, tag = "4")]
+    signature: Vec<u8>,
+}
+
+// dkg.Deal
+#[derive(Clone, PartialEq, Message)]
+struct PbDkgDeal {
+    #[prost(uint32, tag = "1")]
+    index: u32,
+    #[prost(message, optional, tag = "2")]
+    deal: Option<PbEncryptedDeal>,
+}
+
+// dkg.Response
+#[derive(Clone, PartialEq, Message)]
+struct PbDkgResponse {
+    #[prost(uint32, tag = "1")]
+    index: u32,
+    #[prost(message, optional, tag = "2")]
+    response: Option<PbVssResponse>,
+}
+
+// dkg.Justification
+#[derive(Clone, PartialEq, Message)]
+struct PbDkgJustification {
+    #[prost(uint32, tag = "1")]
+    index: u32,
+    #[prost(message, optional, tag = "2")]
+    justification: Option<PbVssJustification>,
+}
+
+// dkg.SecretCommits
+#[derive(Clone, PartialEq, Message)]
+struct PbSecretCommits {
+    #[prost(uint32, tag = "1")]
+    index: u32,
+    #[prost(bytes, repeated, tag = "2")]
+    commitments: Vec<Vec<u8>>,
+    #[prost(bytes, tag = "3")]
+    session_id: Vec<u8>,
+    #[prost(bytes, tag = "4")]
+    signature: Vec<u8>,
+}
+
+// dkg.ComplaintCommits
+#[derive(Clone, PartialEq, Message)]
+struct PbComplaintCommits {
+    #[prost(uint32, tag = "1")]
+    index: u32,
+    #[prost(uint32, tag = "2")]
+    dealer_index: u32,
+    #[prost(message, optional, tag = "3")]
+    deal: Option<PbVssDeal>,
+    #[prost(bytes, tag = "4")]
+    signature: Vec<u8>,
+}
+
+// dkg.ReconstructCommits
+#[derive(Clone, PartialEq, Message)]
+struct PbReconstructCommits {
+    #[prost(bytes, tag = "1")]
+    session_id: Vec<u8>,
+    #[prost(uint32, tag = "2")]
+    index: u32,
+    #[prost(uint32, tag = "3")]
+    dealer_index: u32,
+    #[prost(message, optional, tag = "4")]
+    share: Option<PbPriShare>,
+    #[prost(bytes, tag = "5")]
+    signature: Vec<u8>,
+}
+
+fn encode<M: Message>(msg: &M) -> Vec<u8> {
+    let mut buff: Vec<u8> = Vec::with_capacity(msg.encoded_len());
+    msg.encode(&mut buff)
+        .expect("Vec<u8> grows to fit the message");
+    buff
+}
+
+fn decode<M: Message + Default>(bytes: &[u8]) -> Result<M, Box<dyn Error>> {
+    M::decode(bytes).map_err(|e| simple_error!("kyber-compat: {}", e).into())
+}
+
+// required returns the embedded message, which kyber always sets.
+fn required<M>(msg: Option<M>, name: &str) -> Result<M, Box<dyn Error>> {
+    msg.ok_or_else(|| simple_error!("kyber-compat: missing {}", name).into())
+}
+
+fn pri_share_to_pb<P: CurvePoint>(share: &PriShare<P::Scalar>) -> PbPriShare {
+    PbPriShare {
+        i: i64::from(share.i),
+        v: share.v.to_bytes(),
+    }
+}
+
+fn pri_share_from_pb<P: CurvePoint>(
+    share: Option<PbPriShare>,
+) -> Result<PriShare<P::Scalar>, Box<dyn Error>> {
+    let share: PbPriShare = required(share, "share")?;
+    Ok(PriShare {
+        i: share
+            .i
+            .try_into()
+            .map_err(|_| simple_error!("kyber-compat: invalid share index"))?,
+        v: P::Scalar::from_bytes(&share.v)?,
+    })
+}
+
+fn vss_deal_to_pb<P: CurvePoint>(deal: &vss::Deal<P>) -> PbVssDeal {
+    PbVssDeal {
+        session_id: deal.session_id.clone(),
+        sec_share: Some(pri_share_to_pb::<P>(&deal.sec_share)),
+        rnd_share: Some(pri_share_to_pb::<P>(&deal.rnd_share)),
+        t: deal.t,
+        commitments: deal.commitments.clone(),
+    }
+}
+
+fn vss_deal_from_pb<P: CurvePoint>(deal: PbVssDeal) -> Result<vss::Deal<P>, Box<dyn Error>> {
+    Ok(vss::Deal {
+        session_id: deal.session_id,
+        sec_share: pri_share_from_pb::<P>(deal.sec_share)?,
+        rnd_share: pri_share_from_pb::<P>(deal.rnd_share)?,
+        t: deal.t,
+        commitments: deal.commitments,
+        context: Vec::new(),
+        commitments_root: Vec::new(),
+    })
+}
+
+fn encrypted_deal_to_pb<P: CurvePoint>(deal: &vss::EncryptedDeal<P>) -> PbEncryptedDeal {
+    PbEncryptedDeal {
+        dh_key: deal.dh_key.pk_to_key_slice(),
+        signature: deal.signature.clone(),
+        nonce: deal.nonce.clone(),
+        cipher: deal.cipher.clone(),
+        version: u32::from(deal.version),
+        session_id: deal.session_id.clone(),
+    }
+}
+
+fn encrypted_deal_from_pb<P: CurvePoint>(
+    deal: PbEncryptedDeal,
+) -> Result<vss::EncryptedDeal<P>, Box<dyn Error>> {
+    Ok(vss::EncryptedDeal {
+        version: deal
+            .version
+            .try_into()
+            .map_err(|_| simple_error!("kyber-compat: invalid encrypted deal version"))?,
+        session_id: deal.session_id,
+        dh_key: P::from_bytes(&deal.dh_key)?,
+        signature: deal.signature,
+        nonce: deal.nonce,
+        cipher: deal.cipher,
+        // kyber only encrypts deals with AES256-GCM
+        aead: vss::DealAead::Aes256Gcm,
+        mode: vss::DealMode::Legacy,
+    })
+}
+
+fn vss_response_to_pb(resp: &vss::Response) -> PbVssResponse {
+    PbVssResponse {
+        session_id: resp.session_id.clone(),
+        index: resp.index,
+        status: resp.approved,
+        signature: resp.signature.clone(),
+    }
+}
+
+fn vss_response_from_pb(resp: PbVssResponse) -> vss::Response {
+    vss::Response {
+        session_id: resp.session_id,
+        index: resp.index,
+        approved: resp.status,
+        signature: resp.signature,
+        reason: None,
+    }
+}
+
+fn vss_justification_to_pb<P: CurvePoint>(j: &vss::Justification<P>) -> PbVssJustification {
+    PbVssJustification {
+        session_id: j.session_id.clone(),
+        index: j.index,
+        deal: Some(vss_deal_to_pb(&j.deal)),
+        signature: j.signature.clone(),
+    }
+}
+
+fn vss_justification_from_pb<P: CurvePoint>(
+    j: PbVssJustification,
+) -> Result<vss::Justification<P>, Box<dyn Error>> {
+    Ok(vss::Justification {
+        session_id: j.session_id,
+        index: j.index,
+        deal: vss_deal_from_pb(required(j.deal, "deal")?)?,
+        signature: j.signature,
+    })
+}
+
+impl<P: CurvePoint> KyberMessage for vss::Deal<P> {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&vss_deal_to_pb(self))
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        vss_deal_from_pb(decode(bytes)?)
+    }
+}
+
+impl<P: CurvePoint> KyberMessage for vss::EncryptedDeal<P> {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&encrypted_deal_to_pb(self))
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        encrypted_deal_from_pb(decode(bytes)?)
+    }
+}
+
+impl KyberMessage for vss::Response {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&vss_response_to_pb(self))
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Ok(vss_response_from_pb(decode(bytes)?))
+    }
+}
+
+impl<P: CurvePoint> KyberMessage for vss::Justification<P> {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&vss_justification_to_pb(self))
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        vss_justification_from_pb(decode(bytes)?)
+    }
+}
+
+impl<P: CurvePoint> KyberMessage for dkg::Deal<P> {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&PbDkgDeal {
+            index: self.index,
+            deal: Some(encrypted_deal_to_pb(&self.deal)),
+        })
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let d: PbDkgDeal = decode(bytes)?;
+        Ok(dkg::Deal {
+            index: d.index,
+            deal: encrypted_deal_from_pb(required(d.deal, "deal")?)?,
+        })
+    }
+}
+
+impl KyberMessage for dkg::Response {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&PbDkgResponse {
+            index: self.index,
+            response: Some(vss_response_to_pb(&self.response)),
+        })
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let r: PbDkgResponse = decode(bytes)?;
+        Ok(dkg::Response {
+            index: r.index,
+            response: vss_response_from_pb(required(r.response, "response")?),
+        })
+    }
+}
+
+impl<P: CurvePoint> KyberMessage for dkg::Justification<P> {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&PbDkgJustification {
+            index: self.index,
+            justification: Some(vss_justification_to_pb(&self.justification)),
+        })
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let j: PbDkgJustification = decode(bytes)?;
+        Ok(dkg::Justification {
+            index: j.index,
+            justification: vss_justification_from_pb(required(j.justification, "justification")?)?,
+        })
+    }
+}
+
+impl KyberMessage for dkg::SecretCommits {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&PbSecretCommits {
+            index: self.index,
+            commitments: self.commitments.clone(),
+            session_id: self.session_id.clone(),
+            signature: self.signature.clone(),
+        })
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let sc: PbSecretCommits = decode(bytes)?;
+        Ok(dkg::SecretCommits {
+            index: sc.index,
+            commitments: sc.commitments,
+            session_id: sc.session_id,
+            signature: sc.signature,
+        })
+    }
+}
+
+impl<P: CurvePoint> KyberMessage for dkg::ComplaintCommits<P> {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&PbComplaintCommits {
+            index: self.index,
+            dealer_index: self.dealer_index,
+            deal: Some(vss_deal_to_pb(&self.deal)),
+            signature: self.signature.clone(),
+        })
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let cc: PbComplaintCommits = decode(bytes)?;
+        Ok(dkg::ComplaintCommits {
+            index: cc.index,
+            dealer_index: cc.dealer_index,
+            deal: vss_deal_from_pb(required(cc.deal, "deal")?)?,
+            signature: cc.signature,
+        })
+    }
+}
+
+impl<P: CurvePoint> KyberMessage for dkg::ReconstructCommits<P> {
+    fn to_kyber_bytes(&self) -> Vec<u8> {
+        encode(&PbReconstructCommits {
+            session_id: self.session_id.clone(),
+            index: self.index,
+            dealer_index: self.dealer_index,
+            share: Some(pri_share_to_pb::<P>(&self.share)),
+            signature: self.signature.clone(),
+        })
+    }
+
+    fn from_kyber_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let rc: PbReconstructCommits = decode(bytes)?;
+        Ok(dkg::ReconstructCommits {
+            session_id: rc.session_id,
+            index: rc.index,
+            dealer_index: rc.dealer_index,
+            share: pri_share_from_pb::<P>(rc.share)?,
+            signature: rc.signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve;
+    use curve_traits::ECPoint;
+    use ristretto_curve::{FE, GE};
+
+    fn random_share(i: u32) -> PriShare<FE> {
+        PriShare {
+            i,
+            v: ECScalar::new_random(),
+        }
+    }
+
+    fn random_deal() -> vss::Deal {
+        vss::Deal {
+            session_id: vec![1, 2, 3],
+            sec_share: random_share(4),
+            rnd_share: random_share(4),
+            t: 3,
+            commitments: (0..3)
+                .map(|_| {
+                    let s: FE = ECScalar::new_random();
+                    GE::generator()
+                        .scalar_mul(&s.get_element())
+                        .pk_to_key_slice()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_kyber_response_encoding() {
+        let resp = vss::Response {
+            session_id: vec![1, 2],
+            index: 3,
+            approved: true,
+            signature: vec![4],
+            reason: None,
+        };
+        // field number and wire type, then the value, in the Go struct order
+        let expected: Vec<u8> = vec![0x0a, 2, 1, 2, 0x10, 3, 0x18, 1, 0x22, 1, 4];
+        assert_eq!(expected, resp.to_kyber_bytes());
+        assert_eq!(resp, vss::Response::from_kyber_bytes(&expected).unwrap());
+    }
+
+    #[test]
+    fn test_kyber_pri_share_zigzag() {
+        let share: PriShare<FE> = PriShare {
+            i: 1,
+            v: FE::zero(),
+        };
+        // the index of a share is a Go int, i.e. a zigzag encoded sint64
+        let encoded: Vec<u8> = encode(&pri_share_to_pb::<GE>(&share));
+        assert_eq!(&[0x08, 0x02], &encoded[..2]);
+    }
+
+    #[test]
+    fn test_kyber_vss_messages() {
+        let deal: vss::Deal = random_deal();
+        assert_eq!(
+            deal,
+            vss::Deal::from_kyber_bytes(&deal.to_kyber_bytes()).unwrap()
+        );
+
+        let j: vss::Justification = vss::Justification {
+            session_id: deal.session_id.clone(),
+            index: 4,
+            deal: deal.clone(),
+            signature: vec![5; 64],
+        };
+        assert_eq!(
+            j,
+            vss::Justification::from_kyber_bytes(&j.to_kyber_bytes()).unwrap()
+        );
+
+        let s: FE = ECScalar::new_random();
+        let enc: vss::EncryptedDeal = vss::EncryptedDeal {
+            version: vss::ENCRYPTED_DEAL_VERSION,
+            session_id: deal.session_id.clone(),
+            dh_key: GE::generator().scalar_mul(&s.get_element()),
+            signature: vec![6; 64],
+            nonce: vec![7; 12],
+            cipher: vec![8; 100],
+            aead: vss::DealAead::Aes256Gcm,
+            mode: vss::DealMode::Legacy,
+        };
+        let dd: dkg::Deal = dkg::Deal {
+            index: 2,
+            deal: enc,
+        };
+        let decoded: dkg::Deal = dkg::Deal::from_kyber_bytes(&dd.to_kyber_bytes()).unwrap();
+        assert_eq!(dd.index, decoded.index);
+        assert_eq!(dd.deal.to_kyber_bytes(), decoded.deal.to_kyber_bytes());
+        assert_eq!(dd.deal.dh_key, decoded.deal.dh_key);
+        assert_eq!(vss::ENCRYPTED_DEAL_VERSION, decoded.deal.version);
+    }
+
+    #[test]
+    fn test_kyber_dkg_messages() {
+        let deal: vss::Deal = random_deal();
+        let cc: dkg::ComplaintCommits = dkg::ComplaintCommits {
+            index: 1,
+            dealer_index: 2,
+            deal: deal.clone(),
+            signature: vec![3; 64],
+        };
+        let decoded: dkg::ComplaintCommits =
+            dkg::ComplaintCommits::from_kyber_bytes(&cc.to_kyber_bytes()).unwrap();
+        assert_eq!(cc.dealer_index, decoded.dealer_index);
+        assert_eq!(cc.deal, decoded.deal);
+
+        let rc: dkg::ReconstructCommits = dkg::ReconstructCommits {
+            session_id: deal.session_id.clone(),
+            index: 1,
+            dealer_index: 2,
+            share: deal.sec_share.clone(),
+            signature: vec![4; 64],
+        };
+        let decoded: dkg::ReconstructCommits =
+            dkg::ReconstructCommits::from_kyber_bytes(&rc.to_kyber_bytes()).unwrap();
+        assert_eq!(rc.share, decoded.share);
+        assert_eq!(rc.signature, decoded.signature);
+
+        // the embedded response is required
+        dkg::Response::from_kyber_bytes(&[0x08, 0x01]).expect_err("Must fail, missing response");
+        vss::Deal::<GE>::from_kyber_bytes(&[0xff]).expect_err("Must fail, invalid protobuf");
+    }
+}
@@ -1,14 +1,165 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate simple_error;
+extern crate alloc;
 extern crate blake2b;
 
+// Without std, simple_error and bail build the errors of the error module.
+#[cfg(not(feature = "std"))]
+macro_rules! simple_error {
+    ($e:expr) => {
+        $crate::error::SimpleError::new($e)
+    };
+    ($fmt:expr, $($arg:tt)+) => {
+        $crate::error::SimpleError::new(::alloc::format!($fmt, $($arg)+))
+    };
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! bail {
+    ($e:expr) => {
+        return Err(::core::convert::From::from($e));
+    };
+    ($fmt:expr, $($arg:tt)+) => {
+        return Err(::core::convert::From::from(::alloc::format!($fmt, $($arg)+)));
+    };
+}
+
+pub mod error;
 pub mod curve_traits;
+pub mod encoding;
+pub mod serde_hex;
 pub mod vss;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod watcher;
+#[cfg(feature = "std")]
+pub mod certificate;
+#[cfg(feature = "std")]
+pub mod digest;
+#[cfg(feature = "std")]
 pub mod dkg;
+#[cfg(feature = "std")]
+pub mod dleq;
+#[cfg(feature = "std")]
 pub mod dss;
 pub mod poly;
+pub mod fft;
+pub mod thresholds;
+#[cfg(feature = "std")]
+pub mod reshare;
+#[cfg(feature = "std")]
+pub mod recovery;
+#[cfg(feature = "std")]
+pub mod escrow;
+#[cfg(feature = "std")]
+pub mod rerandomize;
+#[cfg(feature = "std")]
+pub mod zero_sharing;
+#[cfg(feature = "std")]
+pub mod coordinator;
+#[cfg(feature = "std")]
+pub mod group;
+#[cfg(feature = "std")]
+pub mod tss;
+#[cfg(feature = "std")]
+pub mod sr25519;
+#[cfg(feature = "std")]
+pub mod hd;
+#[cfg(feature = "std")]
+pub mod transport;
+#[cfg(feature = "std")]
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod wire;
+#[cfg(feature = "std")]
+pub mod broadcast;
+#[cfg(feature = "std")]
+pub mod sim;
+#[cfg(feature = "std")]
+pub mod storage;
+#[cfg(feature = "std")]
+pub mod transcript;
+#[cfg(feature = "std")]
+pub mod machine;
+#[cfg(feature = "std")]
+pub mod weighted;
+#[cfg(feature = "std")]
+pub mod signer;
+#[cfg(feature = "std")]
+pub mod elgamal;
+#[cfg(feature = "std")]
+pub mod evidence;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "p2p")]
+pub mod p2p;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "tbls")]
+pub mod tbls;
+#[cfg(feature = "tbls")]
+pub mod beacon;
+#[cfg(feature = "drand")]
+pub mod drand;
+#[cfg(feature = "eth-keystore")]
+pub mod eth;
+#[cfg(feature = "kyber-compat")]
+pub mod kyber_compat;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+#[cfg(feature = "share-file")]
+pub mod share_file;
+#[cfg(feature = "vectors")]
+pub mod vectors;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+#[cfg(feature = "invariants")]
+pub mod invariants;
+#[cfg(feature = "hpke")]
+pub mod hpke;
+#[cfg(feature = "pq-kem")]
+pub mod pq;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod ristretto_curve;
+#[cfg(feature = "bls12_381")]
+pub mod bls12_381_curve;
+#[cfg(feature = "secp256k1")]
+pub mod secp256k1_curve;
+#[cfg(feature = "tecdsa")]
+pub mod tecdsa;
+#[cfg(feature = "ed25519")]
+pub mod ed25519_curve;
+#[cfg(feature = "std")]
 mod dh;
 mod sign;
 mod utils;
-mod blake;
\ No newline at end of file
+mod blake;
+#[cfg(not(feature = "std"))]
+mod prelude;
+// The protocol types only hold owned data and Arc, so that they can be moved
+// to and shared between threads for any curve backend.
+#[cfg(feature = "std")]
+fn _assert_send_sync<P: curve_traits::CurvePoint>() {
+    fn is_send_sync<T: Send + Sync>() {}
+    is_send_sync::<vss::Dealer<P>>();
+    is_send_sync::<vss::Verifier<P>>();
+    is_send_sync::<dkg::DistKeyGenerator<P>>();
+    is_send_sync::<dkg::DistKeyShare<P>>();
+    is_send_sync::<dss::DSS<P>>();
+    is_send_sync::<reshare::ReshareDealer<P>>();
+    is_send_sync::<reshare::ReshareReceiver<P>>();
+    is_send_sync::<recovery::RecoveryHelper<P>>();
+    is_send_sync::<recovery::RecoveryReceiver<P>>();
+    is_send_sync::<rerandomize::Rerandomizer<P>>();
+    is_send_sync::<tss::Signer<P>>();
+    is_send_sync::<hd::ExtendedKeyShare<P>>();
+}
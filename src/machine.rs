@@ -0,0 +1,508 @@
+//! Explicit state machine around the DKG, for embedding in an event loop.
+//!
+//! The protocol runs in phases, each of them ended by the caller with
+//! next_phase, typically on a timer shared by all the participants:
+//!  1. DealDistribution: the deals returned by start are sent and the deals of
+//!     the other participants are processed.
+//!  2. Responses: the responses to the received deals are broadcasted and the
+//!     responses of the other participants are processed.
+//!  3. Justifications: the justifications of this dealer are broadcasted and
+//!     the justifications of the other dealers are processed.
+//!  4. Commits: the secret commits are broadcasted, and the secret commits,
+//!     complaints and reconstructions of the other participants are processed.
+//!  5. Finalized: the distributed key share is available. This phase is
+//!     reached as soon as all the commitments of QUAL are known.
+//!
+//! A message of another phase than the current one is rejected, so that the
+//! outcome of the protocol doesn't depend on the arrival order of the messages.
+//!
+//! Instead of calling next_phase, the caller can give the duration of each
+//! phase with set_durations and call tick periodically: the phase ends when its
+//! duration has elapsed since the first tick of the phase.
+//!
+//! With enable_echo_broadcast, the responses and justifications are carried by
+//! the echo broadcast of the broadcast module, so that a participant can't send
+//! different ones to different participants. They are then only processed once
+//! delivered by the echo broadcast.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use crate::broadcast;
+use crate::curve_traits;
+use crate::dkg;
+use crate::ristretto_curve;
+use crate::storage;
+use crate::transport;
+
+use broadcast::{EchoAction, EchoBroadcast, EchoMessage};
+use curve_traits::CurvePoint;
+use dkg::{DistKeyGenerator, DistKeyShare, Justification, Response};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use storage::Snapshot;
+use transport::Message;
+
+/// Phase of the DKG.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    DealDistribution,
+    Responses,
+    Justifications,
+    Commits,
+    Finalized,
+}
+
+/// OutgoingMessage is a message to deliver to the other participants.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum OutgoingMessage<P: CurvePoint = GE> {
+    // Message for the participant at the given index only
+    Send(u32, Message<P>),
+    // Message for every other participant
+    Broadcast(Message<P>),
+}
+
+/// PhaseDurations holds the duration of the phases ended by tick. The Commits
+/// phase has no duration, as it ends once the commitments of QUAL are known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseDurations {
+    pub deal_distribution: Duration,
+    pub responses: Duration,
+    pub justifications: Duration,
+}
+
+impl PhaseDurations {
+    // duration returns the duration of the phase, if tick ends it.
+    fn duration(&self, phase: Phase) -> Option<Duration> {
+        match phase {
+            Phase::DealDistribution => Some(self.deal_distribution),
+            Phase::Responses => Some(self.responses),
+            Phase::Justifications => Some(self.justifications),
+            Phase::Commits | Phase::Finalized => None,
+        }
+    }
+}
+
+/// DkgMachine runs a DistKeyGenerator phase by phase.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct DkgMachine<P: CurvePoint = GE> {
+    dkg: DistKeyGenerator<P>,
+    phase: Phase,
+    // true once the deals have been issued
+    started: bool,
+    // responses to broadcast in the Responses phase
+    responses: Vec<Response>,
+    // justifications to broadcast in the Justifications phase
+    justifications: Vec<Justification<P>>,
+    // durations of the phases ended by tick
+    #[serde(default)]
+    durations: Option<PhaseDurations>,
+    // first tick of the current phase, not kept across serialization
+    #[serde(skip)]
+    phase_start: Option<Instant>,
+    // echo broadcast of the responses and justifications, if enabled
+    #[serde(default)]
+    echo: Option<EchoBroadcast<P>>,
+}
+
+impl<P: CurvePoint> DkgMachine<P> {
+    pub fn new(dkg: DistKeyGenerator<P>) -> DkgMachine<P> {
+        DkgMachine {
+            dkg,
+            phase: Phase::DealDistribution,
+            started: false,
+            responses: Vec::new(),
+            justifications: Vec::new(),
+            durations: None,
+            phase_start: None,
+            echo: None,
+        }
+    }
+
+    /// enable_echo_broadcast carries the responses and justifications with an
+    /// echo broadcast in the given session, which must be the same for all the
+    /// participants. It must be called by all of them before start.
+    pub fn enable_echo_broadcast(&mut self, session: &[u8]) -> Result<(), Box<dyn Error>> {
+        if self.started {
+            bail!("dkg: deals already issued");
+        }
+        self.echo = Some(EchoBroadcast::new(
+            self.dkg.longterm(),
+            self.dkg.participants().to_vec(),
+            session,
+        )?);
+        Ok(())
+    }
+
+    /// set_durations sets the durations of the phases ended by tick.
+    pub fn set_durations(&mut self, durations: PhaseDurations) {
+        self.durations = Some(durations);
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn dkg(&self) -> &DistKeyGenerator<P> {
+        &self.dkg
+    }
+
+    /// start returns the deals of this participant, each to be sent to its
+    /// recipient only. It can only be called once, during DealDistribution.
+    pub fn start(&mut self) -> Result<Vec<OutgoingMessage<P>>, Box<dyn Error>> {
+        if self.phase != Phase::DealDistribution || self.started {
+            bail!("dkg: deals already issued");
+        }
+        let mut deals: Vec<(u32, dkg::Deal<P>)> = self.dkg.deals()?.into_iter().collect();
+        deals.sort_by_key(|(i, _)| *i);
+        self.started = true;
+        Ok(deals
+            .into_iter()
+            .map(|(i, d)| OutgoingMessage::Send(i, Message::Deal(d)))
+            .collect())
+    }
+
+    /// process processes a message of the current phase and returns the
+    /// messages to send right away. It returns an error if the message belongs
+    /// to another phase or is invalid.
+    pub fn process(&mut self, msg: &Message<P>) -> Result<Vec<OutgoingMessage<P>>, Box<dyn Error>> {
+        match msg {
+            Message::Echo(m) => self.process_echo(m),
+            Message::Response(_) | Message::Justification(_) if self.echo.is_some() => {
+                bail!("dkg: message not delivered by the echo broadcast")
+            }
+            _ => self.process_delivered(msg),
+        }
+    }
+
+    // process_echo processes a message of the echo broadcast, and the message
+    // it delivers if any.
+    fn process_echo(
+        &mut self,
+        msg: &EchoMessage<P>,
+    ) -> Result<Vec<OutgoingMessage<P>>, Box<dyn Error>> {
+        let actions = match self.echo.as_mut() {
+            Some(echo) => echo.process(msg)?,
+            None => bail!("dkg: echo broadcast not enabled"),
+        };
+        let mut out: Vec<OutgoingMessage<P>> = Vec::new();
+        for action in actions {
+            match action {
+                EchoAction::Send(i, m) => out.push(OutgoingMessage::Send(i, Message::Echo(m))),
+                EchoAction::Broadcast(m) => out.push(OutgoingMessage::Broadcast(Message::Echo(m))),
+                EchoAction::Deliver(m) => out.extend(self.process_delivered(&m)?),
+            }
+        }
+        Ok(out)
+    }
+
+    fn process_delivered(
+        &mut self,
+        msg: &Message<P>,
+    ) -> Result<Vec<OutgoingMessage<P>>, Box<dyn Error>> {
+        let mut out: Vec<OutgoingMessage<P>> = Vec::new();
+        match (self.phase, msg) {
+            (Phase::DealDistribution, Message::Deal(d)) => {
+                let resp = self.dkg.process_deal(d)?;
+                self.responses.push(resp);
+            }
+            (Phase::Responses, Message::Response(r)) => {
+                if let Some(j) = self.dkg.process_response(r)? {
+                    self.justifications.push(j);
+                }
+            }
+            (Phase::Justifications, Message::Justification(j)) => {
+                self.dkg.process_justification(j)?;
+            }
+            (Phase::Commits, Message::SecretCommits(sc)) => {
+                if let Some(cc) = self.dkg.process_secret_commit(sc)? {
+                    out.push(OutgoingMessage::Broadcast(Message::ComplaintCommits(cc)));
+                }
+            }
+            (Phase::Commits, Message::ComplaintCommits(cc)) => {
+                let rc = self.dkg.process_complaints_commits(cc)?;
+                out.push(OutgoingMessage::Broadcast(Message::ReconstructCommits(rc)));
+            }
+            (Phase::Commits, Message::ReconstructCommits(rc)) => {
+                self.dkg.process_reconstruct_commits(rc)?;
+            }
+            (phase, _) => bail!("dkg: message out of the {:?} phase", phase),
+        }
+
+        if self.phase == Phase::Commits && self.dkg.finished() {
+            self.phase = Phase::Finalized;
+        }
+        Ok(out)
+    }
+
+    /// next_phase ends the current phase and returns the messages to send at
+    /// the start of the next one. A participant whose deal isn't certified at
+    /// the end of the Justifications phase has no secret commits to send.
+    pub fn next_phase(&mut self) -> Result<Vec<OutgoingMessage<P>>, Box<dyn Error>> {
+        let mut out: Vec<OutgoingMessage<P>> = Vec::new();
+        match self.phase {
+            Phase::DealDistribution => {
+                if !self.started {
+                    bail!("dkg: deals not issued");
+                }
+                self.phase = Phase::Responses;
+                let responses: Vec<Response> = self.responses.drain(..).collect();
+                for r in responses {
+                    out.push(self.broadcast(Message::Response(r))?);
+                }
+            }
+            Phase::Responses => {
+                // missing responses are complaints from now on
+                self.dkg.set_timeout();
+                self.phase = Phase::Justifications;
+                let justifications: Vec<Justification<P>> = self.justifications.drain(..).collect();
+                for j in justifications {
+                    out.push(self.broadcast(Message::Justification(j))?);
+                }
+            }
+            Phase::Justifications => {
+                self.dkg.fix_qual();
+                if !self.dkg.certified() {
+                    bail!("dkg: not enough certified deals");
+                }
+                self.phase = Phase::Commits;
+                if let Ok(sc) = self.dkg.secret_commits() {
+                    out.push(OutgoingMessage::Broadcast(Message::SecretCommits(sc)));
+                }
+                if self.dkg.finished() {
+                    self.phase = Phase::Finalized;
+                }
+            }
+            Phase::Commits => bail!("dkg: commitments of QUAL still missing"),
+            Phase::Finalized => bail!("dkg: protocol already finalized"),
+        }
+        Ok(out)
+    }
+
+    // broadcast returns the broadcast of msg, through the echo broadcast if
+    // enabled.
+    fn broadcast(&mut self, msg: Message<P>) -> Result<OutgoingMessage<P>, Box<dyn Error>> {
+        match self.echo.as_mut() {
+            Some(echo) => Ok(OutgoingMessage::Broadcast(Message::Echo(
+                echo.broadcast(msg)?,
+            ))),
+            None => Ok(OutgoingMessage::Broadcast(msg)),
+        }
+    }
+
+    /// tick ends the current phase, as next_phase, once its duration has
+    /// elapsed since the first tick of the phase. It returns the messages to
+    /// send at the start of the next phase, or none if the phase goes on. The
+    /// deals must have been issued with start before the first phase ends.
+    pub fn tick(&mut self, now: Instant) -> Result<Vec<OutgoingMessage<P>>, Box<dyn Error>> {
+        let duration: Duration = match self.durations {
+            Some(durations) => match durations.duration(self.phase) {
+                Some(duration) => duration,
+                None => return Ok(Vec::new()),
+            },
+            None => bail!("dkg: no phase durations"),
+        };
+        let start: Instant = *self.phase_start.get_or_insert(now);
+        if now < start + duration {
+            return Ok(Vec::new());
+        }
+        let out = self.next_phase()?;
+        self.phase_start = Some(now);
+        Ok(out)
+    }
+
+    /// dist_key_share returns the distributed key share once Finalized.
+    pub fn dist_key_share(&self) -> Result<DistKeyShare<P>, Box<dyn Error>> {
+        if self.phase != Phase::Finalized {
+            bail!("dkg: protocol not finalized");
+        }
+        self.dkg.dist_key_share()
+    }
+}
+
+impl<P: CurvePoint> Snapshot for DkgMachine<P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+
+    // deliver hands the outgoing messages of the participant at from over to
+    // the other participants, and so on with the messages they send back.
+    fn deliver(machines: &mut [DkgMachine], from: usize, out: Vec<OutgoingMessage>) {
+        let mut queue: Vec<(usize, OutgoingMessage)> = out.into_iter().map(|o| (from, o)).collect();
+        while let Some((from, o)) = queue.pop() {
+            let (targets, msg): (Vec<usize>, Message) = match o {
+                OutgoingMessage::Send(i, msg) => (vec![i as usize], msg),
+                OutgoingMessage::Broadcast(msg) => {
+                    ((0..machines.len()).filter(|&i| i != from).collect(), msg)
+                }
+            };
+            for i in targets {
+                let replies = machines[i].process(&msg).unwrap();
+                queue.extend(replies.into_iter().map(|r| (i, r)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_machine_whole() {
+        let n = 5;
+        let dkgs: Vec<DistKeyGenerator> = sim::new_dkgs(n, 3).unwrap();
+        let mut machines: Vec<DkgMachine> = dkgs.into_iter().map(DkgMachine::new).collect();
+
+        for i in 0..n as usize {
+            let deals = machines[i].start().unwrap();
+            assert_eq!(n as usize - 1, deals.len());
+            deliver(&mut machines, i, deals);
+        }
+        // deals already issued
+        assert!(machines[0].start().is_err());
+
+        for phase in &[Phase::Responses, Phase::Justifications, Phase::Commits] {
+            let outs: Vec<Vec<OutgoingMessage>> = machines
+                .iter_mut()
+                .map(|m| {
+                    let out = m.next_phase().unwrap();
+                    assert_eq!(*phase, m.phase());
+                    out
+                })
+                .collect();
+            for (i, out) in outs.into_iter().enumerate() {
+                deliver(&mut machines, i, out);
+            }
+        }
+
+        let public: GE = machines[0].dist_key_share().unwrap().get_public_key();
+        for m in machines.iter() {
+            assert_eq!(Phase::Finalized, m.phase());
+            assert_eq!(public, m.dist_key_share().unwrap().get_public_key());
+        }
+    }
+
+    #[test]
+    fn test_machine_echo_broadcast() {
+        let n = 4;
+        let dkgs: Vec<DistKeyGenerator> = sim::new_dkgs(n, 3).unwrap();
+        let mut machines: Vec<DkgMachine> = dkgs.into_iter().map(DkgMachine::new).collect();
+        for m in machines.iter_mut() {
+            m.enable_echo_broadcast(b"session").unwrap();
+        }
+
+        for i in 0..n as usize {
+            let deals = machines[i].start().unwrap();
+            deliver(&mut machines, i, deals);
+        }
+        assert!(machines[0].enable_echo_broadcast(b"session").is_err());
+
+        let outs: Vec<Vec<OutgoingMessage>> = machines
+            .iter_mut()
+            .map(|m| m.next_phase().unwrap())
+            .collect();
+        // responses must be echo broadcasted
+        match &outs[0][0] {
+            OutgoingMessage::Broadcast(Message::Echo(EchoMessage::Send(m))) => match &**m {
+                Message::Response(r) => {
+                    assert!(machines[1].process(&Message::Response(r.clone())).is_err())
+                }
+                _ => panic!("must send a response"),
+            },
+            _ => panic!("must echo broadcast the response"),
+        }
+        for (i, out) in outs.into_iter().enumerate() {
+            deliver(&mut machines, i, out);
+        }
+
+        for phase in &[Phase::Justifications, Phase::Commits] {
+            let outs: Vec<Vec<OutgoingMessage>> = machines
+                .iter_mut()
+                .map(|m| {
+                    let out = m.next_phase().unwrap();
+                    assert_eq!(*phase, m.phase());
+                    out
+                })
+                .collect();
+            for (i, out) in outs.into_iter().enumerate() {
+                deliver(&mut machines, i, out);
+            }
+        }
+
+        let public: GE = machines[0].dist_key_share().unwrap().get_public_key();
+        for m in machines.iter() {
+            assert_eq!(Phase::Finalized, m.phase());
+            assert_eq!(public, m.dist_key_share().unwrap().get_public_key());
+        }
+    }
+
+    #[test]
+    fn test_machine_tick() {
+        let n = 4;
+        let dkgs: Vec<DistKeyGenerator> = sim::new_dkgs(n, 3).unwrap();
+        let mut machines: Vec<DkgMachine> = dkgs.into_iter().map(DkgMachine::new).collect();
+        let second = Duration::from_secs(1);
+        let durations = PhaseDurations {
+            deal_distribution: second,
+            responses: second,
+            justifications: second,
+        };
+        assert!(machines[0].tick(Instant::now()).is_err());
+
+        let start = Instant::now();
+        for i in 0..n as usize {
+            machines[i].set_durations(durations);
+            assert!(machines[i].tick(start).unwrap().is_empty());
+            let deals = machines[i].start().unwrap();
+            deliver(&mut machines, i, deals);
+        }
+
+        let mut now = start;
+        for phase in &[Phase::Responses, Phase::Justifications, Phase::Commits] {
+            // the phase goes on until its duration has elapsed
+            for m in machines.iter_mut() {
+                assert!(m.tick(now + second / 2).unwrap().is_empty());
+            }
+            now += second;
+            let outs: Vec<Vec<OutgoingMessage>> = machines
+                .iter_mut()
+                .map(|m| {
+                    let out = m.tick(now).unwrap();
+                    assert_eq!(*phase, m.phase());
+                    out
+                })
+                .collect();
+            for (i, out) in outs.into_iter().enumerate() {
+                deliver(&mut machines, i, out);
+            }
+        }
+
+        for m in machines.iter_mut() {
+            assert_eq!(Phase::Finalized, m.phase());
+            assert!(m.tick(now + second).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_machine_out_of_phase() {
+        let mut dkgs: Vec<DistKeyGenerator> = sim::new_dkgs(3, 2).unwrap();
+        let deals = dkgs[0].deals().unwrap();
+        let resp = dkgs[1].process_deal(&deals[&1]).unwrap();
+
+        let mut machine: DkgMachine = DkgMachine::new(dkgs.remove(2));
+        // deals not issued
+        assert!(machine.next_phase().is_err());
+        machine.start().unwrap();
+        // response during the deals
+        assert!(machine.process(&Message::Response(resp.clone())).is_err());
+        machine.process(&Message::Deal(deals[&2].clone())).unwrap();
+        machine
+            .dist_key_share()
+            .expect_err("Must fail, not finalized");
+
+        machine.next_phase().unwrap();
+        // deal during the responses
+        assert!(machine.process(&Message::Deal(deals[&2].clone())).is_err());
+        machine.process(&Message::Response(resp)).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+//! Metrics hooks
+//!
+//! The vss and dkg flows count the deals processed, the messages failing
+//! verification and the complaints, and measure the latency of the responses
+//! and the duration of the sessions, through the Metrics trait. The host
+//! application plugs its metrics backend (e.g. a Prometheus registry) in with
+//! set_metrics; nothing is measured until then.
+
+use std::error::Error;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Metrics is implemented by the metrics backend of the host application.
+/// Every method does nothing by default.
+pub trait Metrics: Send + Sync {
+    /// deal_processed counts a deal processed by a verifier, approved or not.
+    fn deal_processed(&self, _approved: bool) {}
+
+    /// verification_failed counts a message which failed verification, with
+    /// its kind: "deal", "response" or "justification".
+    fn verification_failed(&self, _message: &'static str) {}
+
+    /// complaint_filed counts a complaint about a deal.
+    fn complaint_filed(&self) {}
+
+    /// response_received records the latency of a response, since the dealer
+    /// was created or, for a verifier, since it received the deal.
+    fn response_received(&self, _latency: Duration) {}
+
+    /// session_certified records the duration of a session until its deal got
+    /// certified, from the same starting point.
+    fn session_certified(&self, _duration: Duration) {}
+}
+
+static METRICS: RwLock<Option<Arc<dyn Metrics>>> = RwLock::new(None);
+
+/// set_metrics installs the metrics backend of the process, replacing the
+/// previous one.
+pub fn set_metrics(metrics: Arc<dyn Metrics>) {
+    *METRICS.write().unwrap_or_else(|e| e.into_inner()) = Some(metrics);
+}
+
+/// clear_metrics removes the metrics backend of the process.
+pub fn clear_metrics() {
+    *METRICS.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+// with calls f with the metrics backend, if any.
+pub(crate) fn with<F: FnOnce(&dyn Metrics)>(f: F) {
+    let metrics = METRICS.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(metrics) = metrics.as_ref() {
+        f(metrics.as_ref());
+    }
+}
+
+// check counts the verification failure of a message of the given kind if
+// result is an error, and returns result.
+pub(crate) fn check<T>(
+    message: &'static str,
+    result: Result<T, Box<dyn Error>>,
+) -> Result<T, Box<dyn Error>> {
+    if result.is_err() {
+        with(|m| m.verification_failed(message));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[derive(Default)]
+    struct Counter {
+        failures: AtomicU32,
+        complaints: AtomicU32,
+    }
+
+    impl Metrics for Counter {
+        fn verification_failed(&self, _message: &'static str) {
+            self.failures.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn complaint_filed(&self) {
+            self.complaints.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let counter = Arc::new(Counter::default());
+        set_metrics(counter.clone());
+        // other tests may run the protocols concurrently, so that the counts
+        // are lower bounds
+        check::<()>("deal", Err(simple_error!("invalid").into())).unwrap_err();
+        check("deal", Ok(())).unwrap();
+        with(|m| m.complaint_filed());
+        assert!(counter.failures.load(Ordering::SeqCst) >= 1);
+        assert!(counter.complaints.load(Ordering::SeqCst) >= 1);
+        clear_metrics();
+    }
+}
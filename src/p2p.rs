@@ -0,0 +1,340 @@
+//! libp2p transport for the DKG.
+//!
+//! Broadcasted messages are published on a gossipsub topic while deals, which
+//! are addressed to a single participant, are sent with a request-response
+//! protocol. Participants are identified on the network by their PeerId; the
+//! PeerMap gives the index in the list of participants of each of them.
+//!
+//! The swarm is driven by the application: the events of the gossipsub and
+//! request-response behaviours are fed to Libp2pTransport::on_gossip and
+//! Libp2pTransport::on_request, and the queued messages are handed over to the
+//! behaviours with Libp2pTransport::flush. Gossipsub must be configured with
+//! MessageAuthenticity::Signed so that the source of a message can be trusted.
+//! Enabled with the `p2p` feature.
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::io;
+
+use crate::curve_traits;
+use crate::ristretto_curve;
+use crate::transport;
+use crate::wire;
+
+use async_trait::async_trait;
+use curve_traits::CurvePoint;
+use futures::io::{AsyncRead, AsyncWrite};
+use libp2p::core::upgrade::{read_one, write_one, ProtocolName};
+use libp2p::gossipsub::{Gossipsub, GossipsubMessage, IdentTopic};
+use libp2p::request_response::{RequestResponse, RequestResponseCodec};
+use libp2p::PeerId;
+use ristretto_curve::GE;
+use transport::{Message, Transport};
+
+/// Name of the request-response protocol carrying the deals
+pub const DEAL_PROTOCOL: &[u8] = b"/rabin-dkg/deal/1.0.0";
+/// Maximum size of a message read from the network
+pub const MAX_MESSAGE_SIZE: usize = 1 << 20;
+
+/// PeerMap maps the PeerIds of the participants to their index in the list of
+/// participants, and back.
+#[derive(Clone, Debug)]
+pub struct PeerMap {
+    peers: Vec<PeerId>,
+    indices: HashMap<PeerId, u32>,
+}
+
+impl PeerMap {
+    /// new returns the map where the participant at index i is peers[i].
+    pub fn new(peers: Vec<PeerId>) -> Result<PeerMap, Box<dyn Error>> {
+        let indices: HashMap<PeerId, u32> = peers
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (p.clone(), i as u32))
+            .collect();
+        if indices.len() != peers.len() {
+            bail!("p2p: duplicate peer in the participants");
+        }
+        Ok(PeerMap { peers, indices })
+    }
+
+    /// peer returns the PeerId of the participant at index.
+    pub fn peer(&self, index: u32) -> Option<&PeerId> {
+        self.peers.get(index as usize)
+    }
+
+    /// index returns the index of the participant with the given PeerId.
+    pub fn index(&self, peer: &PeerId) -> Option<u32> {
+        self.indices.get(peer).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+/// Outbound is a message waiting to be handed over to the libp2p behaviours.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outbound {
+    // Message to publish on the gossipsub topic
+    Publish(Vec<u8>),
+    // Message to send to a single peer with the deal protocol
+    Request(PeerId, Vec<u8>),
+}
+
+/// Libp2pTransport is the Transport of the local participant over libp2p.
+pub struct Libp2pTransport<P: CurvePoint = GE> {
+    index: u32,
+    peers: PeerMap,
+    topic: IdentTopic,
+    outbox: VecDeque<Outbound>,
+    inbox: VecDeque<(u32, Message<P>)>,
+}
+
+impl<P: CurvePoint> Libp2pTransport<P> {
+    /// new returns the transport of the local peer, which must belong to the
+    /// participants, broadcasting on the given gossipsub topic.
+    pub fn new(
+        local: &PeerId,
+        peers: PeerMap,
+        topic: IdentTopic,
+    ) -> Result<Libp2pTransport<P>, Box<dyn Error>> {
+        let index = peers
+            .index(local)
+            .ok_or_else(|| simple_error!("p2p: local peer not found in the participants"))?;
+        Ok(Libp2pTransport {
+            index,
+            peers,
+            topic,
+            outbox: VecDeque::new(),
+            inbox: VecDeque::new(),
+        })
+    }
+
+    pub fn peers(&self) -> &PeerMap {
+        &self.peers
+    }
+
+    pub fn topic(&self) -> &IdentTopic {
+        &self.topic
+    }
+
+    // receive stores a message coming from peer.
+    fn receive(&mut self, peer: &PeerId, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let from = self
+            .peers
+            .index(peer)
+            .ok_or_else(|| simple_error!("p2p: message from unknown peer {}", peer))?;
+        if from == self.index {
+            return Ok(());
+        }
+        // the threshold isn't known to the transport, and is at most n
+        let msg: Message<P> = wire::decode_message(data, self.peers.peers.len() as u32)?;
+        self.inbox.push_back((from, msg));
+        Ok(())
+    }
+
+    /// on_gossip processes a message received on the gossipsub topic.
+    pub fn on_gossip(&mut self, msg: &GossipsubMessage) -> Result<(), Box<dyn Error>> {
+        if msg.topic != self.topic.hash() {
+            return Ok(());
+        }
+        let source = msg
+            .source
+            .as_ref()
+            .ok_or_else(|| simple_error!("p2p: gossip message without source"))?;
+        self.receive(source, &msg.data)
+    }
+
+    /// on_request processes a request received from peer with the deal
+    /// protocol.
+    pub fn on_request(&mut self, peer: &PeerId, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.receive(peer, data)
+    }
+
+    /// poll_outbound returns the next message to hand over to libp2p.
+    pub fn poll_outbound(&mut self) -> Option<Outbound> {
+        self.outbox.pop_front()
+    }
+
+    /// flush hands over all the queued messages to the behaviours. The
+    /// messages which could not be published stay queued.
+    pub fn flush(
+        &mut self,
+        gossipsub: &mut Gossipsub,
+        deals: &mut RequestResponse<DealCodec>,
+    ) -> Result<(), Box<dyn Error>> {
+        while let Some(out) = self.outbox.pop_front() {
+            match out {
+                Outbound::Publish(data) => {
+                    if let Err(e) = gossipsub.publish(self.topic.clone(), data.clone()) {
+                        self.outbox.push_front(Outbound::Publish(data));
+                        bail!("p2p: publish failed: {:?}", e);
+                    }
+                }
+                Outbound::Request(peer, data) => {
+                    deals.send_request(&peer, data);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P: CurvePoint> Transport<P> for Libp2pTransport<P> {
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn send(&mut self, to: u32, msg: Message<P>) -> Result<(), Box<dyn Error>> {
+        let peer = self
+            .peers
+            .peer(to)
+            .ok_or_else(|| simple_error!("p2p: unknown recipient {}", to))?
+            .clone();
+        self.outbox
+            .push_back(Outbound::Request(peer, bincode::serialize(&msg)?));
+        Ok(())
+    }
+
+    fn broadcast(&mut self, msg: Message<P>) -> Result<(), Box<dyn Error>> {
+        self.outbox
+            .push_back(Outbound::Publish(bincode::serialize(&msg)?));
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Option<(u32, Message<P>)>, Box<dyn Error>> {
+        Ok(self.inbox.pop_front())
+    }
+}
+
+/// DealProtocol is the request-response protocol carrying the deals.
+#[derive(Clone, Debug, Default)]
+pub struct DealProtocol;
+
+impl ProtocolName for DealProtocol {
+    fn protocol_name(&self) -> &[u8] {
+        DEAL_PROTOCOL
+    }
+}
+
+/// DealCodec reads and writes the length prefixed messages of the deal
+/// protocol. A request holds an encoded message and is answered with an empty
+/// acknowledgement.
+#[derive(Clone, Debug, Default)]
+pub struct DealCodec;
+
+fn invalid_data<E: Error + Send + Sync + 'static>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[async_trait]
+impl RequestResponseCodec for DealCodec {
+    type Protocol = DealProtocol;
+    type Request = Vec<u8>;
+    type Response = Vec<u8>;
+
+    async fn read_request<T>(&mut self, _: &DealProtocol, io: &mut T) -> io::Result<Vec<u8>>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_one(io, MAX_MESSAGE_SIZE).await.map_err(invalid_data)
+    }
+
+    async fn read_response<T>(&mut self, _: &DealProtocol, io: &mut T) -> io::Result<Vec<u8>>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_one(io, MAX_MESSAGE_SIZE).await.map_err(invalid_data)
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &DealProtocol,
+        io: &mut T,
+        req: Vec<u8>,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_one(io, req).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &DealProtocol,
+        io: &mut T,
+        resp: Vec<u8>,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_one(io, resp).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg;
+    use crate::vss;
+
+    fn response(index: u32) -> Message {
+        Message::Response(dkg::Response {
+            index,
+            // well-formed, as the transports check the sizes of the fields
+            response: vss::Response {
+                session_id: vec![0; 32],
+                signature: vec![0; 64],
+                ..Default::default()
+            },
+        })
+    }
+
+    #[test]
+    fn test_p2p_transport() {
+        let peers: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+        let map = PeerMap::new(peers.clone()).unwrap();
+        assert_eq!(Some(2), map.index(&peers[2]));
+        PeerMap::new(vec![peers[0].clone(), peers[0].clone()])
+            .expect_err("Must fail, duplicate peer");
+
+        let topic = IdentTopic::new("dkg");
+        Libp2pTransport::<GE>::new(&PeerId::random(), map.clone(), topic.clone())
+            .expect_err("Must fail, not a participant");
+        let mut t0: Libp2pTransport = Libp2pTransport::new(&peers[0], map.clone(), topic).unwrap();
+        assert_eq!(0, t0.index());
+
+        // deals go to a single peer, other messages are published
+        t0.send(1, response(1)).unwrap();
+        t0.broadcast(response(2)).unwrap();
+        t0.send(3, response(3))
+            .expect_err("Must fail, unknown recipient");
+        let data = bincode::serialize(&response(1)).unwrap();
+        assert_eq!(
+            Some(Outbound::Request(peers[1].clone(), data.clone())),
+            t0.poll_outbound()
+        );
+        match t0.poll_outbound() {
+            Some(Outbound::Publish(_)) => (),
+            _ => panic!("expected a published message"),
+        }
+        assert_eq!(None, t0.poll_outbound());
+
+        // received messages are tagged with the index of their sender
+        t0.on_request(&peers[2], &data).unwrap();
+        t0.on_request(&PeerId::random(), &data)
+            .expect_err("Must fail, unknown peer");
+        t0.on_request(&peers[1], &[0xff])
+            .expect_err("Must fail, invalid data");
+        match t0.recv().unwrap() {
+            Some((2, Message::Response(r))) => assert_eq!(1, r.index),
+            _ => panic!("expected a response from participant 2"),
+        }
+        assert!(t0.recv().unwrap().is_none());
+    }
+}
@@ -1,18 +1,20 @@
 //! Polynomials, used by vss and dkg modules to create private
 //! polynomials, commitments, etc.
 
-use std::collections::HashMap;
-use std::convert::TryInto;
-use std::error::Error;
-use std::io::Write;
+use alloc::collections::BTreeMap;
+use core::convert::TryInto;
 
 use crate::curve_traits;
+use crate::error::Error;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::ristretto_curve;
 use crate::utils;
 
-use curve_traits::{ECPoint, ECScalar};
-use ristretto_curve::{FE, GE, PK, SK};
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use ristretto_curve::GE;
 
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use utils::bitwise_eq;
@@ -24,12 +26,15 @@ pub struct PriShare<T> {
     pub v: T,   // value of share
 }
 
-impl<T: ECScalar<SK>> PriShare<T> {
+impl<T> PriShare<T> {
     /// hash returns the hash representation of this share
-    pub fn hash(&self) -> [u8; 32] {
+    pub fn hash<SK>(&self) -> [u8; 32]
+    where
+        T: ECScalar<SK>,
+    {
         let mut hasher = Sha256::new();
-        hasher.input(self.v.get_element().to_bytes());
-        hasher.write_all(&self.i.to_le_bytes()).unwrap();
+        hasher.input(self.v.to_bytes());
+        hasher.input(self.i.to_le_bytes());
 
         hasher
             .result()
@@ -41,24 +46,46 @@ impl<T: ECScalar<SK>> PriShare<T> {
 
 /// PriPoly represents a secret sharing polynomial.
 #[derive(Debug, Clone)]
-pub struct PriPoly {
-    pub coeffs: Vec<FE>, // Coefficients of the polynomial
+pub struct PriPoly<P: CurvePoint = GE> {
+    pub coeffs: Vec<P::Scalar>, // Coefficients of the polynomial
 }
 
-impl PriPoly {
+impl<P: CurvePoint> PriPoly<P> {
     /// Create a polynomial with random coeffs
     /// `threshold`: degree of polynomial
     /// `secret`: if Some, will be the first coefficient
-    pub fn new(threshold: u32, secret: Option<FE>) -> PriPoly {
-        let mut coeffs: Vec<FE> = Vec::new();
-        let secret = secret.unwrap_or_else(ECScalar::new_random);
+    #[cfg(feature = "std")]
+    pub fn new(threshold: u32, secret: Option<P::Scalar>) -> PriPoly<P> {
+        PriPoly::new_with_rng(threshold, secret, &mut utils::rand_hack())
+    }
+
+    /// Same as new, with the random coeffs drawn from rng
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        threshold: u32,
+        secret: Option<P::Scalar>,
+        rng: &mut R,
+    ) -> PriPoly<P> {
+        let mut coeffs: Vec<P::Scalar> = Vec::new();
+        let secret = secret.unwrap_or_else(|| ECScalar::new_random_with_rng(rng));
         coeffs.push(secret);
-        coeffs.resize_with(threshold as usize, ECScalar::new_random);
+        coeffs.resize_with(threshold as usize, || ECScalar::new_random_with_rng(rng));
         PriPoly { coeffs }
     }
 
+    /// zero returns a random polynomial whose constant term is zero, i.e. a
+    /// zero-sharing re-randomizing the shares of a secret.
+    #[cfg(feature = "std")]
+    pub fn zero(threshold: u32) -> PriPoly<P> {
+        PriPoly::zero_with_rng(threshold, &mut utils::rand_hack())
+    }
+
+    /// Same as zero, with the random coeffs drawn from rng
+    pub fn zero_with_rng<R: RngCore + CryptoRng>(threshold: u32, rng: &mut R) -> PriPoly<P> {
+        PriPoly::new_with_rng(threshold, Some(ECScalar::zero()), rng)
+    }
+
     /// coefficients_to_pri_poly returns a PriPoly based on the given coefficients
-    pub fn coefficients_to_pri_poly(coeffs: Vec<FE>) -> PriPoly {
+    pub fn coefficients_to_pri_poly(coeffs: Vec<P::Scalar>) -> PriPoly<P> {
         PriPoly { coeffs }
     }
 
@@ -68,33 +95,68 @@ impl PriPoly {
     }
 
     /// secret returns the shared secret p(0), i.e., the constant term of the polynomial.
-    pub fn secret(&self) -> &FE {
+    pub fn secret(&self) -> &P::Scalar {
         &self.coeffs[0]
     }
 
     /// eval computes the private share v = p(i).
-    pub fn eval(&self, i: u32) -> PriShare<FE> {
-        let xi = FE::from(i as u64 + 1);
-        let mut v: FE = ECScalar::zero();
+    pub fn eval(&self, i: u32) -> PriShare<P::Scalar> {
+        let v: P::Scalar = self.eval_at(&P::Scalar::from(i as u64 + 1));
+        PriShare { i, v }
+    }
+
+    /// eval_at computes p(x) at an arbitrary x-coordinate, e.g. one of the
+    /// evaluation points given to recover_secret_at.
+    pub fn eval_at(&self, x: &P::Scalar) -> P::Scalar {
+        let mut v: P::Scalar = ECScalar::zero();
         for el in self.coeffs.iter().rev() {
-            v = v.mul(&xi.get_element());
+            v = v.mul(&x.get_element());
             v = v.add(&el.get_element());
         }
-        PriShare { i, v }
+        v
     }
 
-    /// shares creates a list of n private shares p(1),...,p(n).
-    pub fn shares(&self, n: u32) -> Vec<PriShare<FE>> {
-        (0..n).map(|el| self.eval(el)).collect()
+    /// shares creates a list of n private shares p(1),...,p(n). Past the first
+    /// t shares, each share is computed from the forward differences of the
+    /// polynomial with t additions, instead of t multiplications with eval.
+    pub fn shares(&self, n: u32) -> Vec<PriShare<P::Scalar>> {
+        let t: u32 = self.threshold();
+        if n <= t {
+            return (0..n).map(|el| self.eval(el)).collect();
+        }
+
+        // diffs[k] is the k-th forward difference of p at the current index
+        let mut diffs: Vec<P::Scalar> = (0..t).map(|el| self.eval(el).v).collect();
+        for k in 1..diffs.len() {
+            for j in (k..diffs.len()).rev() {
+                let prev = diffs[j - 1].get_element();
+                diffs[j] = diffs[j].sub(&prev);
+            }
+        }
+
+        (0..n)
+            .map(|i| {
+                let v: P::Scalar = diffs[0];
+                let mut above: Option<P::Scalar> = None;
+                for d in diffs.iter_mut().rev() {
+                    let old: P::Scalar = *d;
+                    if let Some(a) = above {
+                        *d = d.add(&a.get_element());
+                    }
+                    above = Some(old);
+                }
+                PriShare { i, v }
+            })
+            .collect()
     }
 
     /// add computes the component-wise sum of the polynomials p and q and returns it
     /// as a new polynomial.
-    pub fn add(&self, q: &PriPoly) -> Result<PriPoly, Box<dyn Error>> {
+    pub fn add(&self, q: &PriPoly<P>) -> Result<PriPoly<P>, Box<dyn Error>> {
         if self.threshold() != q.threshold() {
             bail!("different number of coefficients");
         }
-        let mut coeffs: Vec<FE> = Vec::with_capacity(self.threshold() as usize);
+        let mut coeffs: Vec<P::Scalar> = Vec::with_capacity(self.threshold() as usize);
         for el in 0..self.threshold() {
             coeffs.push(self.coeffs[el as usize].add(&q.coeffs[el as usize].get_element()));
         }
@@ -105,14 +167,14 @@ impl PriPoly {
     /// unequal (e.g., due to mismatching cryptographic groups or polynomial size), this routine
     /// returns in variable time. Otherwise it runs in constant time regardless of whether it
     /// eventually returns true or false.
-    pub fn equal(&self, q: &PriPoly) -> bool {
+    pub fn equal(&self, q: &PriPoly<P>) -> bool {
         if self.coeffs.len() != q.coeffs.len() {
             return false;
         }
         let mut res = true;
         for (idx, el) in self.coeffs.iter().enumerate() {
-            let first_binary_scalar: [u8; 32] = el.get_element().to_bytes();
-            let second_binary_scalar: [u8; 32] = q.coeffs[idx].get_element().to_bytes();
+            let first_binary_scalar: Vec<u8> = el.to_bytes();
+            let second_binary_scalar: Vec<u8> = q.coeffs[idx].to_bytes();
 
             let equal = bitwise_eq(&first_binary_scalar, &second_binary_scalar);
             res = res && equal;
@@ -122,11 +184,17 @@ impl PriPoly {
 
     /// commit creates a public commitment polynomial for the given base point b or
     /// the standard base if base is None
-    pub fn commit(&self, poly_base: Option<GE>) -> PubPoly {
-        let mut commits: Vec<GE> = Vec::with_capacity(self.threshold() as usize);
-        let poly_base: GE = poly_base.unwrap_or_else(ECPoint::generator);
+    pub fn commit(&self, poly_base: Option<P>) -> PubPoly<P> {
+        let mut commits: Vec<P> = Vec::with_capacity(self.threshold() as usize);
+        let poly_base: P = poly_base.unwrap_or_else(ECPoint::generator);
+        // fixed-base multiplications for the standard base
+        let standard = poly_base == P::generator();
         for el in 0..self.threshold() as usize {
-            commits.push(poly_base.scalar_mul(&self.coeffs[el].get_element()));
+            if standard {
+                commits.push(P::base_mul(&self.coeffs[el]));
+            } else {
+                commits.push(poly_base.scalar_mul(&self.coeffs[el].get_element()));
+            }
         }
         PubPoly {
             b: poly_base,
@@ -141,13 +209,13 @@ impl PriPoly {
     /// after the multiplication, so the degree of the polynomial is "always" as
     /// described above. This is only for use in secret sharing schemes. It is not
     /// a general polynomial multiplication routine.
-    pub fn mul(&self, q: PriPoly) -> PriPoly {
+    pub fn mul(&self, q: PriPoly<P>) -> PriPoly<P> {
         let d1 = (self.coeffs.len() as u32) - 1;
         let d2 = (q.coeffs.len() as u32) - 1;
         let new_degree = d1 + d2;
 
-        let mut coeffs: Vec<FE> = Vec::with_capacity(new_degree as usize + 1);
-        coeffs.resize_with(new_degree as usize + 1, FE::zero);
+        let mut coeffs: Vec<P::Scalar> = Vec::with_capacity(new_degree as usize + 1);
+        coeffs.resize_with(new_degree as usize + 1, ECScalar::zero);
 
         for i in 0..self.coeffs.len() {
             for j in 0..q.coeffs.len() {
@@ -160,33 +228,165 @@ impl PriPoly {
 
     /// coefficients return the list of coefficients representing p. This
     /// information is generally PRIVATE and should not be revealed to a third party
-    pub fn coefficients(&self) -> Vec<FE> {
+    pub fn coefficients(&self) -> Vec<P::Scalar> {
         self.coeffs.clone()
     }
+
+    /// degree returns the degree of the polynomial, threshold - 1.
+    pub fn degree(&self) -> u32 {
+        self.threshold().saturating_sub(1)
+    }
+
+    /// sub computes the component-wise difference of the polynomials p and q.
+    /// It returns an error if their thresholds differ, as add.
+    pub fn sub(&self, q: &PriPoly<P>) -> Result<PriPoly<P>, Box<dyn Error>> {
+        self.add(&q.neg())
+    }
+
+    /// mul_scalar returns the polynomial k * p, sharing k times the secret of p.
+    pub fn mul_scalar(&self, k: &P::Scalar) -> PriPoly<P> {
+        let coeffs: Vec<P::Scalar> = self
+            .coeffs
+            .iter()
+            .map(|c| c.mul(&k.get_element()))
+            .collect();
+        PriPoly { coeffs }
+    }
+
+    /// neg returns the polynomial -p.
+    pub fn neg(&self) -> PriPoly<P> {
+        let zero: P::Scalar = ECScalar::zero();
+        let coeffs: Vec<P::Scalar> = self
+            .coeffs
+            .iter()
+            .map(|c| zero.sub(&c.get_element()))
+            .collect();
+        PriPoly { coeffs }
+    }
+}
+
+/// mul_shares returns the product of the shares a and b of the same index, the
+/// share of the product of their polynomials (see PriPoly::mul). The product of
+/// two polynomials of threshold t has threshold 2t - 1, so that 2t - 1 of these
+/// shares recover the product of the secrets. It returns an error if the
+/// indices differ.
+pub fn mul_shares<S, SK>(a: &PriShare<S>, b: &PriShare<S>) -> Result<PriShare<S>, Box<dyn Error>>
+where
+    S: ECScalar<SK> + Copy,
+{
+    if a.i != b.i {
+        bail!("Share: product of shares of indices {} and {}", a.i, b.i);
+    }
+    Ok(PriShare {
+        i: a.i,
+        v: a.v.mul(&b.v.get_element()),
+    })
 }
 
 /// recover_secret reconstructs the shared secret p(0) from a list of private
 /// shares using Lagrange interpolation.
-pub fn recover_secret(shares: &[PriShare<FE>], t: u32) -> Result<FE, Box<dyn Error>> {
+pub fn recover_secret<S, SK>(shares: &[PriShare<S>], t: u32) -> Result<S, Box<dyn Error>>
+where
+    S: ECScalar<SK> + Copy,
+{
     let (x, y) = xy_scalar(shares, t);
 
     if x.len() < t as usize {
         bail!("Share: not enough shares to recover secret");
     }
 
-    let mut acc: FE = ECScalar::zero();
+    Ok(interpolate_at_zero(&x, &y))
+}
+
+/// rerandomize_share returns the share of the same secret under the fresh
+/// polynomial obtained by adding the zero-sharings whose shares at the index of
+/// share are zero_shares.
+pub fn rerandomize_share<S, SK>(
+    share: &PriShare<S>,
+    zero_shares: &[PriShare<S>],
+) -> Result<PriShare<S>, Box<dyn Error>>
+where
+    S: ECScalar<SK> + Copy,
+{
+    let mut v: S = share.v;
+    for z in zero_shares {
+        if z.i != share.i {
+            bail!("Share: zero share at another index");
+        }
+        v = v.add(&z.v.get_element());
+    }
+    Ok(PriShare { i: share.i, v })
+}
+
+/// rerandomize_pub_poly is the public version of rerandomize_share: it adds the
+/// commitments of the zero-sharings to the public polynomial, which keeps its
+/// constant term. It returns an error if one of them doesn't commit to zero.
+pub fn rerandomize_pub_poly<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    zero_polys: &[PubPoly<P>],
+) -> Result<PubPoly<P>, Box<dyn Error>> {
+    let mut res: PubPoly<P> = pub_poly.clone();
+    for z in zero_polys {
+        if z.commit() != P::identity() {
+            bail!("Share: commitment of a zero-sharing with non-zero secret");
+        }
+        res = res.add(z)?;
+    }
+    Ok(res)
+}
+
+/// recover_secret_at is recover_secret for shares evaluated at arbitrary
+/// x-coordinates: the share at index i holds p(points[i]) instead of p(i+1).
+/// The points must be distinct and non-zero.
+pub fn recover_secret_at<S, SK>(
+    shares: &[PriShare<S>],
+    points: &[S],
+    t: u32,
+) -> Result<S, Box<dyn Error>>
+where
+    S: ECScalar<SK> + Copy,
+{
+    let mut shares = shares.to_vec();
+    shares.sort_by(|a, b| a.i.cmp(&b.i));
+
+    let mut x: BTreeMap<u32, S> = BTreeMap::new();
+    let mut y: BTreeMap<u32, S> = BTreeMap::new();
+    for el in shares.iter() {
+        let xi: &S = points
+            .get(el.i as usize)
+            .ok_or_else(|| simple_error!("Share: no evaluation point for share {}", el.i))?;
+        x.insert(el.i, *xi);
+        y.insert(el.i, el.v);
+        if x.len() as u32 == t {
+            break;
+        }
+    }
+
+    if x.len() < t as usize {
+        bail!("Share: not enough shares to recover secret");
+    }
+    Ok(interpolate_at_zero(&x, &y))
+}
+
+// interpolate_at_zero returns the value at 0 of the polynomial going through
+// the points (x[i], y[i]).
+fn interpolate_at_zero<S, SK>(x: &BTreeMap<u32, S>, y: &BTreeMap<u32, S>) -> S
+where
+    S: ECScalar<SK> + Copy,
+{
+    let mut acc: S = ECScalar::zero();
 
     for (i, xi) in x.iter() {
-        let yi: &FE = y.get(i).unwrap();
-        let mut num = FE::from(yi.get_element());
-        let mut den = FE::from(1 as u64);
+        let yi: &S = y.get(i).unwrap();
+        let mut num = S::from(yi.get_element());
+        let mut den = S::from(1 as u64);
 
         for (j, xj) in x.iter() {
             if i == j {
                 continue;
             }
             num = num.mul(&xj.get_element());
-            let tmp: FE = xj.sub(&xi.get_element());
+            let tmp: S = xj.sub(&xi.get_element());
             den = den.mul(&tmp.get_element());
         }
         den = den.invert();
@@ -194,24 +394,27 @@ pub fn recover_secret(shares: &[PriShare<FE>], t: u32) -> Result<FE, Box<dyn Err
         acc = acc.add(&num.get_element());
     }
 
-    Ok(acc)
+    acc
 }
 
 /// xy_scalar returns the list of (x_i, y_i) pairs indexed. The first map returned
 /// is the list of x_i and the second map is the list of y_i, both indexed in
 /// their respective map at index i.
-pub fn xy_scalar(shares: &[PriShare<FE>], t: u32) -> (HashMap<u32, FE>, HashMap<u32, FE>) {
+pub fn xy_scalar<S, SK>(shares: &[PriShare<S>], t: u32) -> (BTreeMap<u32, S>, BTreeMap<u32, S>)
+where
+    S: ECScalar<SK> + Copy,
+{
     // we are sorting first the shares since the shares may be unrelated for
     // some applications. In this case, all participants needs to interpolate on
     // the exact same order shares.
     let mut shares = shares.to_vec();
     shares.sort_by(|a, b| a.i.cmp(&b.i));
 
-    let mut x: HashMap<u32, FE> = HashMap::new();
-    let mut y: HashMap<u32, FE> = HashMap::new();
+    let mut x: BTreeMap<u32, S> = BTreeMap::new();
+    let mut y: BTreeMap<u32, S> = BTreeMap::new();
     for el in shares.iter() {
         let idx: u32 = el.i;
-        x.insert(idx, FE::from(idx as u64 + 1));
+        x.insert(idx, S::from(idx as u64 + 1));
         y.insert(idx, el.v);
 
         if x.len() as u32 == t {
@@ -221,18 +424,172 @@ pub fn xy_scalar(shares: &[PriShare<FE>], t: u32) -> (HashMap<u32, FE>, HashMap<
     (x, y)
 }
 
+/// lagrange_coefficient returns the Lagrange basis polynomial of the share i
+/// evaluated at 0, for the set of shares with the given indices (i included).
+pub fn lagrange_coefficient<S, SK>(i: u32, indices: &[u32]) -> S
+where
+    S: ECScalar<SK>,
+{
+    let xi = S::from(i as u64 + 1);
+    let mut num = S::from(1u64);
+    let mut den = S::from(1u64);
+    for &j in indices.iter().filter(|&&j| j != i) {
+        let xj = S::from(j as u64 + 1);
+        num = num.mul(&xj.get_element());
+        den = den.mul(&xj.sub(&xi.get_element()).get_element());
+    }
+    num.mul(&den.invert().get_element())
+}
+
+/// lagrange_coefficient_at returns the Lagrange basis polynomial of the share i
+/// evaluated at the index at, for the set of shares with the given indices (i
+/// included). The shares of these indices thus interpolate the share of index
+/// at.
+pub fn lagrange_coefficient_at<S, SK>(i: u32, indices: &[u32], at: u32) -> S
+where
+    S: ECScalar<SK>,
+{
+    let xi = S::from(i as u64 + 1);
+    let x = S::from(at as u64 + 1);
+    let mut num = S::from(1u64);
+    let mut den = S::from(1u64);
+    for &j in indices.iter().filter(|&&j| j != i) {
+        let xj = S::from(j as u64 + 1);
+        num = num.mul(&x.sub(&xj.get_element()).get_element());
+        den = den.mul(&xi.sub(&xj.get_element()).get_element());
+    }
+    num.mul(&den.invert().get_element())
+}
+
+/// lagrange_coefficients returns lambda_i, the Lagrange basis polynomial of the
+/// share i evaluated at 0, for every index i of the set of shares of the given
+/// distinct indices, in their order. It computes all of them with a single
+/// inversion, where calling lagrange_coefficient for each index inverts once
+/// per index.
+pub fn lagrange_coefficients<S, SK>(indices: &[u32]) -> Vec<S>
+where
+    S: ECScalar<SK> + Copy,
+{
+    let xs: Vec<S> = indices.iter().map(|&i| S::from(i as u64 + 1)).collect();
+    // lambda_i = prod_j x_j / (x_i * prod_{j != i} (x_j - x_i))
+    let mut num = S::from(1u64);
+    for x in xs.iter() {
+        num = num.mul(&x.get_element());
+    }
+    let dens: Vec<S> = xs
+        .iter()
+        .map(|xi| {
+            let mut den = *xi;
+            for xj in xs.iter().filter(|&xj| xj != xi) {
+                den = den.mul(&xj.sub(&xi.get_element()).get_element());
+            }
+            den
+        })
+        .collect();
+
+    // batch inversion of the denominators
+    let mut prefix: Vec<S> = Vec::with_capacity(dens.len());
+    let mut acc = S::from(1u64);
+    for den in dens.iter() {
+        prefix.push(acc);
+        acc = acc.mul(&den.get_element());
+    }
+    let mut inv = acc.invert().mul(&num.get_element());
+    let mut lambdas: Vec<S> = vec![S::zero(); dens.len()];
+    for k in (0..dens.len()).rev() {
+        lambdas[k] = inv.mul(&prefix[k].get_element());
+        inv = inv.mul(&dens[k].get_element());
+    }
+    lambdas
+}
+
+/// LagrangeCoefficients caches the Lagrange coefficients at 0 of sets of
+/// shares, e.g. of the signers of successive signatures or of the shares
+/// of a reconstruction, so that they are computed once per set.
+#[derive(Clone, Debug)]
+pub struct LagrangeCoefficients<P: CurvePoint = GE> {
+    // coefficients of every set of indices, sorted, in the order of the
+    // indices
+    cache: BTreeMap<Vec<u32>, Vec<P::Scalar>>,
+}
+
+impl<P: CurvePoint> Default for LagrangeCoefficients<P> {
+    fn default() -> LagrangeCoefficients<P> {
+        LagrangeCoefficients::new()
+    }
+}
+
+impl<P: CurvePoint> LagrangeCoefficients<P> {
+    /// new returns an empty cache.
+    pub fn new() -> LagrangeCoefficients<P> {
+        LagrangeCoefficients {
+            cache: BTreeMap::new(),
+        }
+    }
+
+    /// coefficients returns the Lagrange coefficients at 0 of the set of
+    /// shares of the given indices, in the increasing order of the indices. It
+    /// returns an error if an index is repeated.
+    pub fn coefficients(&mut self, indices: &[u32]) -> Result<&[P::Scalar], Box<dyn Error>> {
+        let mut set: Vec<u32> = indices.to_vec();
+        set.sort_unstable();
+        if set.windows(2).any(|w| w[0] == w[1]) {
+            bail!("Share: repeated index in the set of shares");
+        }
+        if !self.cache.contains_key(&set) {
+            let lambdas: Vec<P::Scalar> = lagrange_coefficients(&set);
+            self.cache.insert(set.clone(), lambdas);
+        }
+        Ok(&self.cache[&set])
+    }
+
+    /// coefficient returns lambda_i, the Lagrange coefficient at 0 of the share
+    /// i in the set of shares of the given indices, as lagrange_coefficient. It
+    /// returns an error if i isn't in the set.
+    pub fn coefficient(&mut self, i: u32, indices: &[u32]) -> Result<P::Scalar, Box<dyn Error>> {
+        if !indices.contains(&i) {
+            bail!("Share: index {} not in the set of shares", i);
+        }
+        let pos = indices.iter().filter(|&&j| j < i).count();
+        Ok(self.coefficients(indices)?[pos])
+    }
+
+    /// len returns the number of sets of shares in the cache.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// is_empty returns true if the cache holds no set of shares.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// clear empties the cache.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PubShare<T> {
     i: u32,
     pub(crate) v: T,
 }
 
-impl<T: ECPoint<PK, SK>> PubShare<T> {
+impl<T> PubShare<T> {
+    /// new creates the public share v = p(i).
+    pub fn new(i: u32, v: T) -> PubShare<T> {
+        PubShare { i, v }
+    }
+
     /// hash returns the hash representation of this share.
-    pub fn hash(&self) -> [u8; 32] {
+    pub fn hash<PK, SK>(&self) -> [u8; 32]
+    where
+        T: ECPoint<PK, SK>,
+    {
         let mut hasher = Sha256::new();
-        hasher.input(self.v.get_element().to_bytes());
-        hasher.write_all(&self.i.to_le_bytes()).unwrap();
+        hasher.input(self.v.pk_to_key_slice());
+        hasher.input(self.i.to_le_bytes());
 
         hasher
             .result()
@@ -243,19 +600,20 @@ impl<T: ECPoint<PK, SK>> PubShare<T> {
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct PubPoly {
-    b: GE, // base point
-    commits: Vec<GE>,
+#[serde(bound = "")]
+pub struct PubPoly<P: CurvePoint = GE> {
+    b: P, // base point
+    commits: Vec<P>,
 }
 
-impl PubPoly {
+impl<P: CurvePoint> PubPoly<P> {
     /// creates a new public commitment polynomial.
-    pub fn new(b: GE, commits: Vec<GE>) -> PubPoly {
+    pub fn new(b: P, commits: Vec<P>) -> PubPoly<P> {
         PubPoly { b, commits }
     }
 
     /// info returns the base point and the commitments to the polynomial coefficients.
-    pub fn info(&self) -> (GE, Vec<GE>) {
+    pub fn info(&self) -> (P, Vec<P>) {
         (self.b, self.commits.clone())
     }
 
@@ -265,26 +623,61 @@ impl PubPoly {
     }
 
     /// commit returns the secret commitment p(0), i.e., the constant term of the polynomial.
-    pub fn commit(&self) -> GE {
+    pub fn commit(&self) -> P {
         self.commits[0]
     }
 
-    /// eval computes the public share v = p(i).
-    pub fn eval(&self, i: u32) -> PubShare<GE> {
-        let xi = FE::from(i as u64 + 1); // x-coordinate of this share
-        let mut v: GE = zero_ge();
-        let mut r_commits: Vec<GE> = self.commits.clone();
-        r_commits.reverse();
-        for el in r_commits.iter() {
-            v = v.scalar_mul(&xi.get_element());
-            v = v.add_point(&el.get_element());
-        }
+    /// eval computes the public share v = p(i), i.e. the public key of the share
+    /// at index i, as a multi-scalar multiplication of the commitments with the
+    /// powers of the x-coordinate.
+    pub fn eval(&self, i: u32) -> PubShare<P> {
+        let v: P = self.eval_at(&P::Scalar::from(i as u64 + 1));
         PubShare { i, v }
     }
 
-    /// shares creates a list of n public commitment shares p(1),...,p(n).
-    pub fn shares(&self, n: u32) -> Vec<PubShare<GE>> {
-        (0..n).map(|el| self.eval(el)).collect()
+    /// eval_at computes p(x) at an arbitrary x-coordinate.
+    pub fn eval_at(&self, x: &P::Scalar) -> P {
+        let mut powers: Vec<P::Scalar> = Vec::with_capacity(self.commits.len());
+        let mut power = P::Scalar::from(1);
+        for _ in 0..self.commits.len() {
+            powers.push(power);
+            power = power.mul(&x.get_element());
+        }
+        P::multi_scalar_mul(&powers, &self.commits)
+    }
+
+    /// shares creates a list of n public commitment shares p(1),...,p(n). As
+    /// for PriPoly::shares, past the first t shares each share only costs t
+    /// point additions.
+    pub fn shares(&self, n: u32) -> Vec<PubShare<P>> {
+        let t: u32 = self.threshold();
+        if n <= t {
+            return (0..n).map(|el| self.eval(el)).collect();
+        }
+
+        // diffs[k] is the k-th forward difference of p at the current index
+        let mut diffs: Vec<P> = (0..t).map(|el| self.eval(el).v).collect();
+        for k in 1..diffs.len() {
+            for j in (k..diffs.len()).rev() {
+                let prev = diffs[j - 1].get_element();
+                diffs[j] = diffs[j].sub_point(&prev);
+            }
+        }
+
+        (0..n)
+            .map(|i| {
+                let v: P = diffs[0];
+                let mut above: Option<P> = None;
+                for d in diffs.iter_mut().rev() {
+                    let old: P = *d;
+                    if let Some(a) = above {
+                        *d = d.add_point(&a.get_element());
+                    }
+                    above = Some(old);
+                }
+                PubShare { i, v }
+            })
+            .collect()
     }
 
     /// add computes the component-wise sum of the polynomials "self" and q and returns it
@@ -293,11 +686,11 @@ impl PubPoly {
     /// discrete logarithm between "self".b and q.b. In this particular case, we are using
     /// "self".b as a default value which of course does not correspond to the correct
     /// base point and thus should not be used in further computations.
-    pub fn add(&self, q: &PubPoly) -> Result<PubPoly, Box<dyn Error>> {
+    pub fn add(&self, q: &PubPoly<P>) -> Result<PubPoly<P>, Box<dyn Error>> {
         if self.threshold() != q.threshold() {
             bail!("different number of coefficients");
         }
-        let mut commits: Vec<GE> = Vec::with_capacity(self.threshold() as usize);
+        let mut commits: Vec<P> = Vec::with_capacity(self.threshold() as usize);
         for i in 0..self.threshold() as usize {
             let commit = self.commits[i].add_point(&q.commits[i].get_element());
             commits.push(commit);
@@ -305,19 +698,51 @@ impl PubPoly {
         Ok(PubPoly { b: self.b, commits })
     }
 
+    /// sub computes the component-wise difference of the public polynomials p
+    /// and q, the commitment of the difference of their private polynomials. It
+    /// returns an error if their thresholds differ, as add.
+    pub fn sub(&self, q: &PubPoly<P>) -> Result<PubPoly<P>, Box<dyn Error>> {
+        self.add(&q.neg())
+    }
+
+    /// mul_scalar returns the commitment of k times the private polynomial.
+    pub fn mul_scalar(&self, k: &P::Scalar) -> PubPoly<P> {
+        let commits: Vec<P> = self
+            .commits
+            .iter()
+            .map(|c| c.scalar_mul(&k.get_element()))
+            .collect();
+        PubPoly { b: self.b, commits }
+    }
+
+    /// neg returns the commitment of the opposite of the private polynomial.
+    pub fn neg(&self) -> PubPoly<P> {
+        let commits: Vec<P> = self
+            .commits
+            .iter()
+            .map(|c| P::identity().sub_point(&c.get_element()))
+            .collect();
+        PubPoly { b: self.b, commits }
+    }
+
+    /// degree returns the degree of the committed polynomial, threshold - 1.
+    pub fn degree(&self) -> u32 {
+        self.threshold().saturating_sub(1)
+    }
+
     /// equal checks equality of two public commitment polynomials p and q. If p and
     /// q are trivially unequal (e.g., due to mismatching cryptographic groups),
     /// this routine returns in variable time. Otherwise it runs in constant time
     /// regardless of whether it eventually returns true or false.
-    pub fn equal(&self, q: PubPoly) -> bool {
+    pub fn equal(&self, q: PubPoly<P>) -> bool {
         if self.commits.len() != q.commits.len() {
             return false;
         }
 
         let mut res = true;
         for (ind, el) in self.commits.iter().enumerate() {
-            let first_binary_point: [u8; 32] = el.get_element().to_bytes();
-            let second_binary_point: [u8; 32] = q.commits[ind].get_element().to_bytes();
+            let first_binary_point: Vec<u8> = el.pk_to_key_slice();
+            let second_binary_point: Vec<u8> = q.commits[ind].pk_to_key_slice();
 
             let equal = bitwise_eq(&first_binary_point, &second_binary_point);
             res = res && equal;
@@ -326,26 +751,29 @@ impl PubPoly {
     }
 
     /// check a private share against a public commitment polynomial.
-    pub fn check(&self, s: &PriShare<FE>) -> bool {
-        let pv: PubShare<GE> = self.eval(s.i);
-        let ps: GE = self.b.scalar_mul(&s.v.get_element());
-        bitwise_eq(&pv.v.get_element().to_bytes(), &ps.get_element().to_bytes())
+    pub fn check(&self, s: &PriShare<P::Scalar>) -> bool {
+        let pv: PubShare<P> = self.eval(s.i);
+        let ps: P = self.b.scalar_mul(&s.v.get_element());
+        bitwise_eq(&pv.v.pk_to_key_slice(), &ps.pk_to_key_slice())
     }
 }
 
 /// xy_commit is the public version of xy_scalar.
-pub fn xy_commit(shares: &mut [PubShare<GE>], t: u32) -> (HashMap<u32, FE>, HashMap<u32, GE>) {
+pub fn xy_commit<P: CurvePoint>(
+    shares: &mut [PubShare<P>],
+    t: u32,
+) -> (BTreeMap<u32, P::Scalar>, BTreeMap<u32, P>) {
     // we are sorting first the shares since the shares may be unrelated for
     // some applications. In this case, all participants needs to interpolate on
     // the exact same order shares.
     shares.sort_by(|a, b| a.i.cmp(&b.i));
 
-    let mut x: HashMap<u32, FE> = HashMap::new();
-    let mut y: HashMap<u32, GE> = HashMap::new();
+    let mut x: BTreeMap<u32, P::Scalar> = BTreeMap::new();
+    let mut y: BTreeMap<u32, P> = BTreeMap::new();
 
     for el in shares.iter() {
         let idx: u32 = el.i;
-        x.insert(idx, FE::from(idx as u64 + 1));
+        x.insert(idx, P::Scalar::from(idx as u64 + 1));
         y.insert(idx, el.v);
 
         if x.len() as u32 == t {
@@ -357,58 +785,90 @@ pub fn xy_commit(shares: &mut [PubShare<GE>], t: u32) -> (HashMap<u32, FE>, Hash
 
 /// recover_commit reconstructs the secret commitment p(0) from a list of public
 /// shares using Lagrange interpolation.
-pub fn recover_commit(shares: &mut [PubShare<GE>], t: u32) -> Result<GE, Box<dyn Error>> {
+pub fn recover_commit<P: CurvePoint>(
+    shares: &mut [PubShare<P>],
+    t: u32,
+) -> Result<P, Box<dyn Error>> {
     let (x, y) = xy_commit(shares, t);
 
     if (x.len() as u32) < t {
         bail!("Share: not enough good public shares to reconstruct secret commitment");
     }
 
-    let mut acc: GE = zero_ge();
+    let mut acc: P = P::identity();
 
     for (i, xi) in x.iter() {
-        let mut num = FE::from(1 as u64);
-        let mut den = FE::from(1 as u64);
+        let mut num = P::Scalar::from(1 as u64);
+        let mut den = P::Scalar::from(1 as u64);
 
         for (j, xj) in x.iter() {
             if i == j {
                 continue;
             }
             num = num.mul(&xj.get_element());
-            let tmp: FE = xj.sub(&xi.get_element());
+            let tmp: P::Scalar = xj.sub(&xi.get_element());
             den = den.mul(&tmp.get_element());
         }
         den = den.invert();
         num = num.mul(&den.get_element());
-        let tmp_point: GE = y.get(i).unwrap().scalar_mul(&num.get_element());
+        let tmp_point: P = y.get(i).unwrap().scalar_mul(&num.get_element());
         acc = acc.add_point(&tmp_point.get_element());
     }
 
     Ok(acc)
 }
 
+/// recover_pub_share is the public version of the share interpolation: it
+/// returns the public share of index i, p(i+1) times the base point,
+/// interpolated in the exponent from t of the public shares. With
+/// recover_commit, it gives the public key of a group, or the public share of
+/// a participant, without any private share, e.g. to verify partial
+/// signatures.
+pub fn recover_pub_share<P: CurvePoint>(
+    shares: &mut [PubShare<P>],
+    t: u32,
+    i: u32,
+) -> Result<P, Box<dyn Error>> {
+    let (x, y) = xy_commit(shares, t);
+
+    if (x.len() as u32) < t {
+        bail!("Share: not enough good public shares to reconstruct public share");
+    }
+
+    let indices: Vec<u32> = x.keys().copied().collect();
+    let coeffs: Vec<P::Scalar> = indices
+        .iter()
+        .map(|&j| lagrange_coefficient_at(j, &indices, i))
+        .collect();
+    let points: Vec<P> = y.values().copied().collect();
+    Ok(P::multi_scalar_mul(&coeffs, &points))
+}
+
 /// recover_pub_poly reconstructs the full public polynomial from a set of public
 /// shares using Lagrange interpolation.
-pub fn recover_pub_poly(shares: &mut [PubShare<GE>], t: u32) -> Result<PubPoly, Box<dyn Error>> {
+pub fn recover_pub_poly<P: CurvePoint>(
+    shares: &mut [PubShare<P>],
+    t: u32,
+) -> Result<PubPoly<P>, Box<dyn Error>> {
     let (x, y) = xy_commit(shares, t);
     if (x.len() as u32) < t {
         bail!("Share: not enough good public shares to reconstruct secret commitment")
     }
 
-    let mut acc_poly: Option<PubPoly> = None;
+    let mut acc_poly: Option<PubPoly<P>> = None;
 
     for &j in x.keys() {
-        let basis: PriPoly = lagrange_basis(j, &x);
+        let basis: PriPoly<P> = lagrange_basis(j, &x);
 
         let tmp = basis.commit(y.get(&j).copied());
 
         acc_poly = match acc_poly {
-            Some(el) => Some(el.add(&tmp).unwrap()),
+            Some(el) => Some(el.add(&tmp)?),
             None => Some(tmp),
         }
     }
 
-    Ok(acc_poly.unwrap())
+    acc_poly.ok_or_else(|| simple_error!("Share: no public share to reconstruct").into())
 }
 
 /// recover_pri_poly takes a list of shares and the parameters t and n to
@@ -416,40 +876,43 @@ pub fn recover_pub_poly(shares: &mut [PubShare<GE>], t: u32) -> Result<PubPoly,
 /// coefficients.  It is up to the caller to make sure that there are enough
 /// shares to correctly re-construct the polynomial. There must be at least t
 /// shares.
-pub fn recover_pri_poly(shares: &mut [PriShare<FE>], t: u32) -> Result<PriPoly, Box<dyn Error>> {
+pub fn recover_pri_poly<P: CurvePoint>(
+    shares: &mut [PriShare<P::Scalar>],
+    t: u32,
+) -> Result<PriPoly<P>, Box<dyn Error>> {
     let (x, y) = xy_scalar(shares, t);
 
     if (x.len() as u32) != t {
         bail!("Share: not enogh shares to recover private polynomial")
     }
 
-    let mut acc_poly: Option<PriPoly> = None;
+    let mut acc_poly: Option<PriPoly<P>> = None;
 
     for &j in x.keys() {
-        let mut basis: PriPoly = lagrange_basis(j, &x);
+        let mut basis: PriPoly<P> = lagrange_basis(j, &x);
         for i in basis.coeffs.iter_mut() {
             *i = i.mul(&y.get(&j).unwrap().get_element());
         }
 
         acc_poly = match acc_poly {
-            Some(el) => Some(el.add(&basis).unwrap()),
+            Some(el) => Some(el.add(&basis)?),
             None => Some(basis),
         };
     }
 
-    Ok(acc_poly.unwrap())
+    acc_poly.ok_or_else(|| simple_error!("Share: no share to recover private polynomial").into())
 }
 
 /// lagrange_basis returns a PriPoly containing the Lagrange coefficients for the
 /// i-th position. xs is a mapping between the indices and the values that the
 /// interpolation is using, computed with xyScalar().
-pub fn lagrange_basis(i: u32, xs: &HashMap<u32, FE>) -> PriPoly {
-    let mut basis: PriPoly = PriPoly {
-        coeffs: vec![FE::from(1 as u64)],
+pub fn lagrange_basis<P: CurvePoint>(i: u32, xs: &BTreeMap<u32, P::Scalar>) -> PriPoly<P> {
+    let mut basis: PriPoly<P> = PriPoly {
+        coeffs: vec![P::Scalar::from(1 as u64)],
     };
 
-    let mut den: FE;
-    let mut acc = FE::from(1 as u64);
+    let mut den: P::Scalar;
+    let mut acc = P::Scalar::from(1 as u64);
     for (&m, xm) in xs.iter() {
         if i == m {
             continue;
@@ -467,10 +930,10 @@ pub fn lagrange_basis(i: u32, xs: &HashMap<u32, FE>) -> PriPoly {
     basis
 }
 
-pub fn minus_const(c: &FE) -> PriPoly {
-    let z_scalar: FE = ECScalar::zero();
-    let neg: FE = z_scalar.sub(&c.get_element());
-    let one = FE::from(1 as u64);
+pub fn minus_const<P: CurvePoint>(c: &P::Scalar) -> PriPoly<P> {
+    let z_scalar: P::Scalar = ECScalar::zero();
+    let neg: P::Scalar = z_scalar.sub(&c.get_element());
+    let one = P::Scalar::from(1 as u64);
     PriPoly {
         coeffs: vec![neg, one],
     }
@@ -478,8 +941,7 @@ pub fn minus_const(c: &FE) -> PriPoly {
 
 /// write this custom function because point in cryptoxide doesn't have public zero() function
 pub fn zero_ge() -> GE {
-    let zero_bytes = [0u8; 32];
-    ECPoint::from_bytes(&zero_bytes).unwrap()
+    GE::identity()
 }
 
 impl Default for GE {
@@ -495,24 +957,159 @@ mod tests {
     use crate::ristretto_curve;
     use std::collections::HashMap;
 
-    use curve_traits::{ECPoint, ECScalar};
+    use curve_traits::{CurvePoint, ECPoint, ECScalar};
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
     use ristretto_curve::{FE, GE};
 
+    #[test]
+    fn test_pri_poly_with_rng() {
+        let p1: PriPoly = PriPoly::new_with_rng(5, None, &mut ChaCha20Rng::seed_from_u64(1));
+        let p2: PriPoly = PriPoly::new_with_rng(5, None, &mut ChaCha20Rng::seed_from_u64(1));
+        let p3: PriPoly = PriPoly::new_with_rng(5, None, &mut ChaCha20Rng::seed_from_u64(2));
+        assert!(p1.equal(&p2));
+        assert!(!p1.equal(&p3));
+    }
+
+    #[test]
+    fn test_commit_base_mul() {
+        let poly: PriPoly<GE> = PriPoly::new(4, None);
+        let generator = GE::generator();
+        let commits = poly.commit(None).commits;
+        for (c, coeff) in commits.iter().zip(poly.coeffs.iter()) {
+            assert_eq!(generator.scalar_mul(&coeff.get_element()), *c);
+            assert_eq!(GE::base_mul(coeff), *c);
+        }
+        let h = GE::hash_to_point(b"base").unwrap();
+        let commits = poly.commit(Some(h)).commits;
+        assert_eq!(h.scalar_mul(&poly.coeffs[1].get_element()), commits[1]);
+    }
+
+    #[test]
+    fn test_lagrange_coefficient_at() {
+        let poly: PriPoly = PriPoly::new(3, None);
+        let indices: Vec<u32> = vec![0, 2, 5];
+        let mut share: FE = ECScalar::zero();
+        for &i in indices.iter() {
+            let lambda: FE = super::lagrange_coefficient_at(i, &indices, 4);
+            share = share.add(&poly.eval(i).v.mul(&lambda.get_element()).get_element());
+        }
+        assert_eq!(poly.eval(4).v, share);
+
+        let lambda: FE = super::lagrange_coefficient_at(2, &indices, 2);
+        assert_eq!(FE::from(1u64), lambda);
+    }
+
+    #[test]
+    fn test_lagrange_coefficients() {
+        let poly: PriPoly = PriPoly::new(4, None);
+        let indices: Vec<u32> = vec![1, 3, 4, 8];
+        let lambdas: Vec<FE> = super::lagrange_coefficients(&indices);
+        let mut secret: FE = ECScalar::zero();
+        for (&i, lambda) in indices.iter().zip(lambdas.iter()) {
+            assert_eq!(super::lagrange_coefficient::<FE, _>(i, &indices), *lambda);
+            secret = secret.add(&poly.eval(i).v.mul(&lambda.get_element()).get_element());
+        }
+        assert_eq!(*poly.secret(), secret);
+
+        let mut cache: LagrangeCoefficients = LagrangeCoefficients::new();
+        assert_eq!(lambdas[2], cache.coefficient(4, &[8, 4, 1, 3]).unwrap());
+        assert_eq!(lambdas[0], cache.coefficient(1, &indices).unwrap());
+        assert_eq!(lambdas, cache.coefficients(&indices).unwrap());
+        // the permutations of a set share an entry
+        assert_eq!(1, cache.len());
+        cache
+            .coefficient(2, &indices)
+            .expect_err("Must fail, index not in the set");
+        cache
+            .coefficients(&[1, 3, 3])
+            .expect_err("Must fail, repeated index");
+        assert_eq!(1, cache.len());
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_shares_batch_eval() {
+        let poly: PriPoly = PriPoly::new(4, None);
+        let shares = poly.shares(20);
+        assert_eq!(20, shares.len());
+        for (i, share) in shares.iter().enumerate() {
+            assert_eq!(poly.eval(i as u32), *share);
+        }
+        assert_eq!(poly.eval(1), poly.shares(2)[1]);
+
+        let pub_poly: PubPoly = poly.commit(None);
+        for (i, share) in pub_poly.shares(20).iter().enumerate() {
+            assert_eq!(pub_poly.eval(i as u32).v, share.v);
+        }
+    }
+
     #[test]
     fn test_recover_secret() {
         let n: u32 = 10;
         let t: u32 = 6;
-        let poly = PriPoly::new(t, None);
+        let poly: PriPoly = PriPoly::new(t, None);
         let mut shares = poly.shares(n);
         let recovered = super::recover_secret(shares.as_mut_slice(), t).unwrap();
         assert_eq!(recovered, *poly.secret());
     }
 
+    #[test]
+    fn test_recover_secret_at() {
+        let t: u32 = 4;
+        let poly: PriPoly = PriPoly::new(t, None);
+        let points: Vec<FE> = (0..6).map(|_| ECScalar::new_random()).collect();
+        let shares: Vec<PriShare<FE>> = points
+            .iter()
+            .enumerate()
+            .map(|(i, x)| PriShare {
+                i: i as u32,
+                v: poly.eval_at(x),
+            })
+            .collect();
+        let recovered = super::recover_secret_at(&shares[2..], &points, t).unwrap();
+        assert_eq!(*poly.secret(), recovered);
+
+        assert!(super::recover_secret_at(&shares[3..], &points, t).is_err());
+        assert!(super::recover_secret_at(&shares, &points[..3], t).is_err());
+    }
+
+    #[test]
+    fn test_rerandomize_share() {
+        let (n, t): (u32, u32) = (7, 4);
+        let poly: PriPoly = PriPoly::new(t, None);
+        let pub_poly: PubPoly = poly.commit(None);
+        let zeros: Vec<PriPoly> = (0..3).map(|_| PriPoly::zero(t)).collect();
+        let zero_pubs: Vec<PubPoly> = zeros.iter().map(|z| z.commit(None)).collect();
+        let new_pub: PubPoly = super::rerandomize_pub_poly(&pub_poly, &zero_pubs).unwrap();
+        assert_eq!(pub_poly.commit(), new_pub.commit());
+
+        let old_shares: Vec<PriShare<FE>> = poly.shares(n);
+        let shares: Vec<PriShare<FE>> = old_shares
+            .iter()
+            .map(|s| {
+                let z: Vec<PriShare<FE>> = zeros.iter().map(|z| z.eval(s.i)).collect();
+                super::rerandomize_share(s, &z).unwrap()
+            })
+            .collect();
+        for (old, new) in old_shares.iter().zip(shares.iter()) {
+            assert!(new_pub.check(new));
+            assert!(!pub_poly.check(new));
+            assert_ne!(old.v, new.v);
+        }
+        let recovered = super::recover_secret(&shares[2..], t).unwrap();
+        assert_eq!(*poly.secret(), recovered);
+
+        assert!(super::rerandomize_share(&shares[0], &[zeros[0].eval(1)]).is_err());
+        assert!(super::rerandomize_pub_poly(&pub_poly, &[pub_poly.clone()]).is_err());
+    }
+
     #[test]
     fn test_recover_commit() {
         let n: u32 = 10;
         let t: u32 = 6;
-        let poly = PriPoly::new(t, None);
+        let poly: PriPoly = PriPoly::new(t, None);
         let pub_poly = poly.commit(None);
         let mut pub_shares = pub_poly.shares(n);
         let recovered = super::recover_commit(pub_shares.as_mut_slice(), t).unwrap();
@@ -520,6 +1117,25 @@ mod tests {
         assert_eq!(recovered, pub_poly.commit());
     }
 
+    #[test]
+    fn test_recover_pub_share() {
+        let n: u32 = 10;
+        let t: u32 = 6;
+        let poly: PriPoly = PriPoly::new(t, None);
+        let pub_poly = poly.commit(None);
+        let mut pub_shares = pub_poly.shares(n);
+        // public share of a participant whose share is missing
+        let missing = pub_shares.remove(7);
+        let recovered = super::recover_pub_share(&mut pub_shares[2..], t, 7).unwrap();
+        assert_eq!(missing.v, recovered);
+        assert_eq!(GE::base_mul(&poly.eval(7).v), recovered);
+        // index out of the committee
+        let recovered = super::recover_pub_share(&mut pub_shares, t, n + 3).unwrap();
+        assert_eq!(pub_poly.eval(n + 3).v, recovered);
+        super::recover_pub_share(&mut pub_shares[..t as usize - 1], t, 7)
+            .expect_err("Must fail, t-1 public shares");
+    }
+
     #[test]
     fn test_secret_recovery() {
         let n: u32 = 10;
@@ -613,6 +1229,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pub_poly_eval() {
+        let pub_poly: PubPoly = PriPoly::new(6, None).commit(None);
+        let (_, commits) = pub_poly.info();
+        for i in 0..10 {
+            // Horner's method, one scalar_mul per commitment
+            let xi = FE::from(i as u64 + 1);
+            let expected: GE = commits.iter().rev().fold(GE::identity(), |v, c| {
+                v.scalar_mul(&xi.get_element()).add_point(&c.get_element())
+            });
+            assert_eq!(expected, pub_poly.eval(i).v);
+        }
+    }
+
     #[test]
     fn test_public_recovery() {
         let n: u32 = 10;
@@ -792,6 +1422,47 @@ mod tests {
         assert_eq!(*ct, mul);
     }
 
+    #[test]
+    fn test_poly_arithmetic() {
+        let t: u32 = 3;
+        let a: PriPoly = PriPoly::new(t, None);
+        let b: PriPoly = PriPoly::new(t, None);
+        let k: FE = ECScalar::new_random();
+        assert_eq!(t - 1, a.degree());
+
+        let diff = a.sub(&b).unwrap();
+        assert_eq!(a.secret().sub(&b.secret().get_element()), *diff.secret());
+        assert!(diff.add(&b).unwrap().equal(&a));
+        let zero: PriPoly = PriPoly::coefficients_to_pri_poly(vec![FE::zero(); t as usize]);
+        assert!(a.add(&a.neg()).unwrap().equal(&zero));
+        assert_eq!(a.secret().mul(&k.get_element()), *a.mul_scalar(&k).secret());
+        a.sub(&PriPoly::new(t + 1, None))
+            .expect_err("Must fail, different thresholds");
+
+        // commitments follow the private operations
+        let (a_pub, b_pub) = (a.commit(None), b.commit(None));
+        assert_eq!(t - 1, a_pub.degree());
+        assert!(a_pub.sub(&b_pub).unwrap().equal(diff.commit(None)));
+        assert!(a_pub.neg().equal(a.neg().commit(None)));
+        assert!(a_pub.mul_scalar(&k).equal(a.mul_scalar(&k).commit(None)));
+        a_pub
+            .sub(&PriPoly::new(t + 1, None).commit(None))
+            .expect_err("Must fail, different thresholds");
+
+        // shares of the product of the secrets
+        let c = a.mul(b.clone());
+        assert_eq!(2 * (t - 1), c.degree());
+        let shares: Vec<PriShare<FE>> = (0..2 * t - 1)
+            .map(|i| super::mul_shares(&a.eval(i), &b.eval(i)).unwrap())
+            .collect();
+        assert_eq!(c.eval(1), shares[1]);
+        assert_eq!(
+            a.secret().mul(&b.secret().get_element()),
+            super::recover_secret(&shares, 2 * t - 1).unwrap()
+        );
+        super::mul_shares(&a.eval(0), &b.eval(1)).expect_err("Must fail, different indices");
+    }
+
     #[test]
     fn test_recover_pri_poly() {
         let n: u32 = 10;
@@ -802,8 +1473,9 @@ mod tests {
         let mut shares2 = shares.clone();
         shares2.reverse();
 
-        let recovered = super::recover_pri_poly(shares.as_mut_slice(), t).unwrap();
-        let reverce_recovered = super::recover_pri_poly(shares2.as_mut_slice(), t).unwrap();
+        let recovered: PriPoly = super::recover_pri_poly(shares.as_mut_slice(), t).unwrap();
+        let reverce_recovered: PriPoly =
+            super::recover_pri_poly(shares2.as_mut_slice(), t).unwrap();
 
         for ind in 0..t {
             assert_eq!(recovered.eval(ind).v, pri_poly.eval(ind).v);
@@ -811,6 +1483,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_recover_hand_computed_poly() {
+        // p(x) = 5 + 3x + 2x^2, with p(2) = 19, p(4) = 49 and p(5) = 70
+        let expected: Vec<FE> = vec![FE::from(5), FE::from(3), FE::from(2)];
+        let values: Vec<(u32, u64)> = vec![(1, 19), (3, 49), (4, 70)];
+
+        let mut shares: Vec<PriShare<FE>> = values
+            .iter()
+            .map(|&(i, v)| PriShare { i, v: FE::from(v) })
+            .collect();
+        let pri_poly: PriPoly = super::recover_pri_poly(&mut shares, 3).unwrap();
+        assert_eq!(expected, pri_poly.coefficients());
+        assert!(super::recover_pri_poly::<GE>(&mut shares[..2], 3).is_err());
+
+        let g: GE = GE::generator();
+        let mut pub_shares: Vec<PubShare<GE>> = values
+            .iter()
+            .map(|&(i, v)| PubShare {
+                i,
+                v: g.scalar_mul(&FE::from(v).get_element()),
+            })
+            .collect();
+        let pub_poly: PubPoly = super::recover_pub_poly(&mut pub_shares, 3).unwrap();
+        let expected_commits: Vec<GE> = expected
+            .iter()
+            .map(|c| g.scalar_mul(&c.get_element()))
+            .collect();
+        assert_eq!(expected_commits, pub_poly.info().1);
+        assert!(super::recover_pub_poly(&mut pub_shares[..2], 3).is_err());
+    }
+
     #[test]
     fn test_pri_poly_coefficients() {
         let n: u32 = 10;
@@ -821,7 +1524,7 @@ mod tests {
         let coeffs = pri_poly.coefficients();
         assert_eq!(coeffs.len() as u32, t);
 
-        let b = PriPoly::coefficients_to_pri_poly(coeffs);
+        let b: PriPoly = PriPoly::coefficients_to_pri_poly(coeffs);
         assert_eq!(pri_poly.coefficients(), b.coefficients());
     }
 
@@ -987,7 +1690,7 @@ mod tests {
         }
 
         // Recover the private polynomial
-        let refreshed_pri_poly =
+        let refreshed_pri_poly: PriPoly =
             super::recover_pri_poly(new_dkg_shares.as_mut_slice(), threshold).unwrap();
 
         // Check that the secret and the corresponding (old) public commit match
@@ -0,0 +1,85 @@
+//! Post-quantum hybrid encryption of the deals
+//!
+//! With DealMode::Hybrid, the key encrypting a deal is derived from both the
+//! Diffie Hellman key of the ephemeral key with the key of the verifier, and
+//! the shared secret of a Kyber768 encapsulation to a KEM key of the verifier.
+//! An adversary recording the deals must break both to recover the shares, so
+//! that long-lived shares aren't decryptable once the curve is broken by a
+//! quantum computer.
+//!
+//! Every verifier holds a KEM key pair in addition to its longterm key, and
+//! the dealer gets the public KEM keys of all the verifiers. The KEM ciphertext
+//! leads the cipher of the EncryptedDeal, under the signature of the dealer.
+
+use std::error::Error;
+
+use crate::curve_traits;
+
+use curve_traits::{CurvePoint, ECPoint};
+use hkdf::Hkdf;
+use pqcrypto_kyber::kyber768;
+use pqcrypto_traits::kem::{Ciphertext, PublicKey, SecretKey, SharedSecret};
+use sha2::Sha256;
+
+/// CIPHERTEXT_SIZE is the length of a KEM ciphertext.
+pub const CIPHERTEXT_SIZE: usize = 1088;
+
+/// new_kem_key returns a fresh KEM key pair, as (public key, secret key).
+pub fn new_kem_key() -> (Vec<u8>, Vec<u8>) {
+    let (public, secret) = kyber768::keypair();
+    (public.as_bytes().to_vec(), secret.as_bytes().to_vec())
+}
+
+/// encapsulate returns a shared secret and its encapsulation to the public
+/// KEM key.
+pub fn encapsulate(public: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Box<dyn Error>> {
+    let public = kyber768::PublicKey::from_bytes(public)
+        .map_err(|_| simple_error!("pq: invalid KEM public key"))?;
+    let (shared, cipher) = kyber768::encapsulate(&public);
+    Ok((shared.as_bytes().to_vec(), cipher.as_bytes().to_vec()))
+}
+
+/// decapsulate returns the shared secret encapsulated in cipher with the
+/// secret KEM key.
+pub fn decapsulate(cipher: &[u8], secret: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let cipher = kyber768::Ciphertext::from_bytes(cipher)
+        .map_err(|_| simple_error!("pq: invalid KEM ciphertext"))?;
+    let secret = kyber768::SecretKey::from_bytes(secret)
+        .map_err(|_| simple_error!("pq: invalid KEM secret key"))?;
+    Ok(kyber768::decapsulate(&cipher, &secret).as_bytes().to_vec())
+}
+
+/// derive_key returns the 256-bit key derived with HKDF from both the Diffie
+/// Hellman key pre and the KEM shared secret.
+pub fn derive_key<P: CurvePoint>(pre: &P, kem_shared: &[u8], context: &[u8]) -> [u8; 32] {
+    let input_key_material = [pre.pk_to_key_slice(), kem_shared.to_vec()].concat();
+    let h = Hkdf::<Sha256>::new(Some(b"vss-hybrid"), &input_key_material);
+
+    let mut key = [0u8; 32];
+    h.expand(context, &mut key).unwrap();
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve::GE;
+
+    #[test]
+    fn test_kem() {
+        let (public, secret) = new_kem_key();
+        let (shared, cipher) = encapsulate(&public).unwrap();
+        assert_eq!(CIPHERTEXT_SIZE, cipher.len());
+        assert_eq!(shared, decapsulate(&cipher, &secret).unwrap());
+
+        let (_, other) = new_kem_key();
+        assert_ne!(shared, decapsulate(&cipher, &other).unwrap());
+        assert!(encapsulate(&public[1..]).is_err());
+
+        // both secrets are needed to derive the key
+        let pre: GE = GE::generator();
+        let key = derive_key(&pre, &shared, b"context");
+        assert_ne!(key, derive_key(&pre, &shared[1..], b"context"));
+        assert_ne!(key, derive_key(&GE::identity(), &shared, b"context"));
+    }
+}
@@ -0,0 +1,7 @@
+//! The items of the std prelude which come from alloc, for no_std builds.
+
+pub use alloc::borrow::ToOwned;
+pub use alloc::boxed::Box;
+pub use alloc::string::{String, ToString};
+pub use alloc::vec;
+pub use alloc::vec::Vec;
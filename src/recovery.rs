@@ -0,0 +1,772 @@
+//! Recovery of the share of a single participant who lost it.
+//!
+//! A set of at least t helpers, other holders of shares of the distributed
+//! key, re-derive the share of index lost for a replacement node without ever
+//! reconstructing the secret:
+//!  1. every helper j multiplies its share by its Lagrange coefficient for the
+//!     index lost, splits the result into one random part per helper and
+//!     broadcasts a RecoveryDeal holding the commitments of the parts and each
+//!     part encrypted to its helper. The commitments multiply to the public
+//!     share of j raised to its Lagrange coefficient, so that anybody can check
+//!     them against the public polynomial of the key.
+//!  2. every helper k sums the parts it received from all the helpers and
+//!     sends the sum, encrypted to the replacement node, in a RecoveryShare.
+//!  3. the replacement node sums the RecoveryShares, which gives the lost
+//!     share. Each of them is checked against the commitments of the deals.
+//!
+//! A helper only learns random parts, and the replacement node only learns
+//! sums mixing the contributions of all the helpers.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dh;
+use crate::dkg;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::utils;
+
+use aead::{generic_array::GenericArray, Aead, Payload};
+use curve_traits::{CurvePoint, ECScalar};
+use dkg::DistKeyShare;
+use encoding::Encoder;
+use poly::{PriShare, PubPoly};
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use utils::bitwise_eq;
+
+/// EncryptedPart is a scalar encrypted to a single recipient, under a key
+/// derived from an ephemeral Diffie-Hellman exchange.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct EncryptedPart<P: CurvePoint = GE> {
+    // ephemeral Diffie Hellman key
+    pub dh_key: P,
+    // nonce used in the encryption
+    pub nonce: Vec<u8>,
+    // AEAD encryption of the scalar
+    pub cipher: Vec<u8>,
+}
+
+/// RecoveryDeal is broadcasted by a helper to the other helpers and to the
+/// replacement node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecoveryDeal<P: CurvePoint = GE> {
+    // Index of the helper in the participants
+    pub index: u32,
+    pub session_id: Vec<u8>,
+    // commitments[k] is the commitment of the part of the k-th helper
+    pub commitments: Vec<Vec<u8>>,
+    // parts[k] is the part of the k-th helper, encrypted to it
+    pub parts: Vec<EncryptedPart<P>>,
+    pub signature: Vec<u8>,
+}
+
+/// RecoveryShare is sent by a helper to the replacement node, once it received
+/// the deals of all the helpers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecoveryShare<P: CurvePoint = GE> {
+    // Index of the helper in the participants
+    pub index: u32,
+    pub session_id: Vec<u8>,
+    // sum of the parts received by the helper, encrypted to the replacement node
+    pub share: EncryptedPart<P>,
+    pub signature: Vec<u8>,
+}
+
+impl<P: CurvePoint> RecoveryDeal<P> {
+    /// hash returns the hash of the deal signed by its helper.
+    pub fn hash(&self) -> [u8; 32] {
+        let enc = Encoder::new(b"recoverydeal")
+            .put_bytes(&self.session_id)
+            .put_u32(self.index)
+            .put_list(&self.commitments)
+            .put_u32(self.parts.len() as u32);
+        self.parts.iter().fold(enc, put_part).hash()
+    }
+}
+
+impl<P: CurvePoint> RecoveryShare<P> {
+    /// hash returns the hash of the share signed by its helper.
+    pub fn hash(&self) -> [u8; 32] {
+        let enc = Encoder::new(b"recoveryshare")
+            .put_bytes(&self.session_id)
+            .put_u32(self.index);
+        put_part(enc, &self.share).hash()
+    }
+}
+
+fn put_part<P: CurvePoint>(enc: Encoder, part: &EncryptedPart<P>) -> Encoder {
+    enc.put_bytes(&part.dh_key.pk_to_key_slice())
+        .put_bytes(&part.nonce)
+        .put_bytes(&part.cipher)
+}
+
+// session_id binds the messages of a recovery to the key, the helpers, the lost
+// index and the replacement node.
+fn session_id<P: CurvePoint>(
+    commits: &[P],
+    participants: &[P],
+    helpers: &[u32],
+    lost: u32,
+    replacement: &P,
+) -> Vec<u8> {
+    let commits: Vec<Vec<u8>> = commits.iter().map(P::pk_to_key_slice).collect();
+    let participants: Vec<Vec<u8>> = participants.iter().map(P::pk_to_key_slice).collect();
+    let enc = Encoder::new(b"recoverysession")
+        .put_list(&commits)
+        .put_list(&participants)
+        .put_u32(helpers.len() as u32);
+    helpers
+        .iter()
+        .fold(enc, |enc, &h| enc.put_u32(h))
+        .put_u32(lost)
+        .put_bytes(&replacement.pk_to_key_slice())
+        .hash()
+        .to_vec()
+}
+
+// check_setup validates the parameters of a recovery and returns the helpers
+// sorted.
+fn check_setup<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    participants: &[P],
+    helpers: &[u32],
+    lost: u32,
+) -> Result<Vec<u32>, Box<dyn Error>> {
+    let mut helpers: Vec<u32> = helpers.to_vec();
+    helpers.sort_unstable();
+    helpers.dedup();
+    if (helpers.len() as u32) < pub_poly.threshold() {
+        bail!("recovery: less helpers than the threshold");
+    }
+    if lost as usize >= participants.len() {
+        bail!("recovery: lost index out of bounds");
+    }
+    if helpers.contains(&lost) {
+        bail!("recovery: the lost participant can't help");
+    }
+    if helpers.iter().any(|&h| h as usize >= participants.len()) {
+        bail!("recovery: helper index out of bounds");
+    }
+    Ok(helpers)
+}
+
+pub(crate) fn encrypt_part<P: CurvePoint, R: RngCore + CryptoRng>(
+    to: &P,
+    context: &[u8],
+    value: &P::Scalar,
+    rng: &mut R,
+) -> Result<EncryptedPart<P>, Box<dyn Error>> {
+    let dh_secret: P::Scalar = ECScalar::new_random_with_rng(rng);
+    let dh_key: P = P::generator().scalar_mul(&dh_secret.get_element());
+    let pre: P = dh::dh_exchange(&dh_secret, to);
+    let gcm = dh::new_aead(&pre, context);
+    let nonce: [u8; dh::NONCE_SIZE] = dh::new_nonce(rng);
+    let cipher = gcm
+        .encrypt(
+            GenericArray::from_slice(&nonce),
+            Payload {
+                msg: value.to_bytes().as_ref(),
+                aad: context,
+            },
+        )
+        .map_err(|_| simple_error!("recovery: encryption failure!"))?;
+    Ok(EncryptedPart {
+        dh_key,
+        nonce: nonce.to_vec(),
+        cipher,
+    })
+}
+
+pub(crate) fn decrypt_part<P: CurvePoint>(
+    longterm: &P::Scalar,
+    context: &[u8],
+    part: &EncryptedPart<P>,
+) -> Result<P::Scalar, Box<dyn Error>> {
+    if part.nonce.len() != dh::NONCE_SIZE {
+        bail!("recovery: invalid nonce size");
+    }
+    let pre: P = dh::dh_exchange(longterm, &part.dh_key);
+    let gcm = dh::new_aead(&pre, context);
+    let plain = gcm
+        .decrypt(
+            GenericArray::from_slice(&part.nonce),
+            Payload {
+                msg: part.cipher.as_ref(),
+                aad: context,
+            },
+        )
+        .map_err(|_| simple_error!("recovery: failed to decrypt part"))?;
+    P::Scalar::from_bytes(&plain)
+}
+
+// verify_deal checks the session and signature of a deal, and that its
+// commitments multiply to the contribution expected from its helper. It
+// returns the commitments.
+fn verify_deal<P: CurvePoint>(
+    deal: &RecoveryDeal<P>,
+    session_id: &[u8],
+    participants: &[P],
+    pub_poly: &PubPoly<P>,
+    helpers: &[u32],
+    lost: u32,
+) -> Result<Vec<P>, Box<dyn Error>> {
+    if !helpers.contains(&deal.index) {
+        bail!("recovery: deal from a non helper");
+    }
+    if !bitwise_eq(&deal.session_id, session_id) {
+        bail!("recovery: deal with wrong session id");
+    }
+    P::verify_signature(
+        &participants[deal.index as usize],
+        &deal.signature,
+        &deal.hash(),
+        &deal.index.to_le_bytes(),
+    )
+    .map_err(|e| simple_error!("recovery: invalid signature in deal: {}", e))?;
+
+    if deal.commitments.len() != helpers.len() || deal.parts.len() != helpers.len() {
+        bail!("recovery: deal with wrong number of parts");
+    }
+    let commitments: Vec<P> = deal
+        .commitments
+        .iter()
+        .map(|c| P::from_bytes(c))
+        .collect::<Result<_, _>>()?;
+    let sum: P = commitments
+        .iter()
+        .fold(P::identity(), |acc, c| acc.add_point(&c.get_element()));
+    let lambda: P::Scalar = poly::lagrange_coefficient_at(deal.index, helpers, lost);
+    let expected: P = pub_poly
+        .eval(deal.index)
+        .v
+        .scalar_mul(&lambda.get_element());
+    if sum != expected {
+        bail!("recovery: deal not matching the public share of its helper");
+    }
+    Ok(commitments)
+}
+
+/// RecoveryHelper is run by every helper of the recovery.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecoveryHelper<P: CurvePoint = GE> {
+    // Index of the helper in the participants
+    index: u32,
+    // longterm secret, i.e. private key of the node
+    long: P::Scalar,
+    // pub keys of the participants
+    participants: Vec<P>,
+    // public polynomial of the distributed key
+    pub_poly: PubPoly<P>,
+    // sorted indices of the helpers
+    helpers: Vec<u32>,
+    // index of the lost share
+    lost: u32,
+    // pub key of the replacement node
+    replacement: P,
+    session_id: Vec<u8>,
+    // parts[k] is the part of our contribution for the k-th helper
+    parts: Vec<P::Scalar>,
+    // parts received from each helper
+    received: HashMap<u32, P::Scalar>,
+}
+
+impl<P: CurvePoint> RecoveryHelper<P> {
+    /// longterm: the secret (private) key of the node,
+    /// share: the share of the distributed key held by this node,
+    /// participants: the list of participants pubkeys, indexed by the index of
+    /// their share,
+    /// helpers: the indices of the helpers, at least as many as the threshold,
+    /// lost: the index of the lost share,
+    /// replacement: the pubkey of the node receiving the lost share
+    pub fn new(
+        longterm: P::Scalar,
+        share: &DistKeyShare<P>,
+        participants: Vec<P>,
+        helpers: &[u32],
+        lost: u32,
+        replacement: P,
+    ) -> Result<RecoveryHelper<P>, Box<dyn Error>> {
+        RecoveryHelper::new_with_rng(
+            longterm,
+            share,
+            participants,
+            helpers,
+            lost,
+            replacement,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as new, with the split of the contribution drawn from rng.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        share: &DistKeyShare<P>,
+        participants: Vec<P>,
+        helpers: &[u32],
+        lost: u32,
+        replacement: P,
+        rng: &mut R,
+    ) -> Result<RecoveryHelper<P>, Box<dyn Error>> {
+        let pub_poly: PubPoly<P> = share.pub_poly();
+        let helpers: Vec<u32> = check_setup(&pub_poly, &participants, helpers, lost)?;
+        let pri_share: PriShare<P::Scalar> = share.get_pri_share();
+        let pub_k: P = P::generator().scalar_mul(&longterm.get_element());
+        if participants.get(pri_share.i as usize) != Some(&pub_k) {
+            bail!("recovery: own public key not found at the index of the share");
+        }
+        if !helpers.contains(&pri_share.i) {
+            bail!("recovery: not a helper");
+        }
+
+        // split lambda * share into one random part per helper
+        let lambda: P::Scalar = poly::lagrange_coefficient_at(pri_share.i, &helpers, lost);
+        let mut last: P::Scalar = pri_share.v.mul(&lambda.get_element());
+        let mut parts: Vec<P::Scalar> = (1..helpers.len())
+            .map(|_| {
+                let part: P::Scalar = ECScalar::new_random_with_rng(rng);
+                last = last.sub(&part.get_element());
+                part
+            })
+            .collect();
+        parts.push(last);
+
+        let session_id = session_id(
+            share.get_commitments(),
+            &participants,
+            &helpers,
+            lost,
+            &replacement,
+        );
+        Ok(RecoveryHelper {
+            index: pri_share.i,
+            long: longterm,
+            participants,
+            pub_poly,
+            helpers,
+            lost,
+            replacement,
+            session_id,
+            parts,
+            received: Default::default(),
+        })
+    }
+
+    /// deal returns the deal of this helper, to be broadcasted to the other
+    /// helpers and to the replacement node.
+    pub fn deal(&mut self) -> Result<RecoveryDeal<P>, Box<dyn Error>> {
+        self.deal_with_rng(&mut utils::rand_hack())
+    }
+
+    /// Same as deal, with the encryptions drawn from rng.
+    pub fn deal_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<RecoveryDeal<P>, Box<dyn Error>> {
+        let own: usize = self.position(self.index)?;
+        let commitments: Vec<Vec<u8>> = self
+            .parts
+            .iter()
+            .map(|p| {
+                P::generator()
+                    .scalar_mul(&p.get_element())
+                    .pk_to_key_slice()
+            })
+            .collect();
+        let parts: Vec<EncryptedPart<P>> = self
+            .helpers
+            .iter()
+            .zip(self.parts.iter())
+            .map(|(&h, part)| {
+                encrypt_part(&self.participants[h as usize], &self.session_id, part, rng)
+            })
+            .collect::<Result<_, _>>()?;
+        let mut deal = RecoveryDeal {
+            index: self.index,
+            session_id: self.session_id.clone(),
+            commitments,
+            parts,
+            signature: Vec::new(),
+        };
+        let pub_key: P = self.participants[self.index as usize];
+        deal.signature = P::sign_msg(
+            &self.long,
+            &pub_key,
+            &deal.hash(),
+            &self.index.to_le_bytes(),
+        )?;
+        self.received.insert(self.index, self.parts[own]);
+        Ok(deal)
+    }
+
+    /// process_deal verifies the deal of another helper and stores the part of
+    /// this helper.
+    pub fn process_deal(&mut self, deal: &RecoveryDeal<P>) -> Result<(), Box<dyn Error>> {
+        if self.received.contains_key(&deal.index) {
+            bail!("recovery: already received deal from same index");
+        }
+        let commitments: Vec<P> = verify_deal(
+            deal,
+            &self.session_id,
+            &self.participants,
+            &self.pub_poly,
+            &self.helpers,
+            self.lost,
+        )?;
+
+        let own: usize = self.position(self.index)?;
+        let part: P::Scalar = decrypt_part(&self.long, &self.session_id, &deal.parts[own])?;
+        if P::generator().scalar_mul(&part.get_element()) != commitments[own] {
+            bail!("recovery: part not matching its commitment");
+        }
+        self.received.insert(deal.index, part);
+        Ok(())
+    }
+
+    /// share returns the sum of the parts received by this helper, to be sent
+    /// to the replacement node. The deals of all the helpers must have been
+    /// processed.
+    pub fn share(&self) -> Result<RecoveryShare<P>, Box<dyn Error>> {
+        self.share_with_rng(&mut utils::rand_hack())
+    }
+
+    /// Same as share, with the encryption drawn from rng.
+    pub fn share_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<RecoveryShare<P>, Box<dyn Error>> {
+        if self.received.len() != self.helpers.len() {
+            bail!("recovery: deals of some helpers are missing");
+        }
+        let sum: P::Scalar = self
+            .received
+            .values()
+            .fold(P::Scalar::zero(), |acc, p| acc.add(&p.get_element()));
+        let mut share = RecoveryShare {
+            index: self.index,
+            session_id: self.session_id.clone(),
+            share: encrypt_part(&self.replacement, &self.session_id, &sum, rng)?,
+            signature: Vec::new(),
+        };
+        let pub_key: P = self.participants[self.index as usize];
+        share.signature = P::sign_msg(
+            &self.long,
+            &pub_key,
+            &share.hash(),
+            &self.index.to_le_bytes(),
+        )?;
+        Ok(share)
+    }
+
+    fn position(&self, index: u32) -> Result<usize, Box<dyn Error>> {
+        self.helpers
+            .iter()
+            .position(|&h| h == index)
+            .ok_or_else(|| simple_error!("recovery: {} is not a helper", index).into())
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+/// RecoveryReceiver is run by the replacement node to collect the deals and
+/// shares of the helpers and compute the lost share.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecoveryReceiver<P: CurvePoint = GE> {
+    // longterm secret, i.e. private key of the replacement node
+    long: P::Scalar,
+    // pub keys of the participants
+    participants: Vec<P>,
+    // public polynomial of the distributed key
+    pub_poly: PubPoly<P>,
+    // sorted indices of the helpers
+    helpers: Vec<u32>,
+    // index of the lost share
+    lost: u32,
+    session_id: Vec<u8>,
+    // commitments of the deal of each helper
+    commitments: HashMap<u32, Vec<P>>,
+    // share received from each helper
+    shares: HashMap<u32, P::Scalar>,
+}
+
+impl<P: CurvePoint> RecoveryReceiver<P> {
+    /// longterm: the secret (private) key of the replacement node,
+    /// commits: the coefficients of the public polynomial of the distributed
+    /// key (DistKeyShare::get_commitments),
+    /// participants: the list of participants pubkeys, indexed by the index of
+    /// their share,
+    /// helpers: the indices of the helpers,
+    /// lost: the index of the lost share
+    pub fn new(
+        longterm: P::Scalar,
+        commits: Vec<P>,
+        participants: Vec<P>,
+        helpers: &[u32],
+        lost: u32,
+    ) -> Result<RecoveryReceiver<P>, Box<dyn Error>> {
+        if commits.is_empty() {
+            bail!("recovery: invalid commitments");
+        }
+        let pub_poly: PubPoly<P> = PubPoly::new(P::generator(), commits.clone());
+        let helpers: Vec<u32> = check_setup(&pub_poly, &participants, helpers, lost)?;
+        let replacement: P = P::generator().scalar_mul(&longterm.get_element());
+        let session_id = session_id(&commits, &participants, &helpers, lost, &replacement);
+        Ok(RecoveryReceiver {
+            long: longterm,
+            participants,
+            pub_poly,
+            helpers,
+            lost,
+            session_id,
+            commitments: Default::default(),
+            shares: Default::default(),
+        })
+    }
+
+    /// process_deal verifies the deal of a helper and stores its commitments.
+    pub fn process_deal(&mut self, deal: &RecoveryDeal<P>) -> Result<(), Box<dyn Error>> {
+        if self.commitments.contains_key(&deal.index) {
+            bail!("recovery: already received deal from same index");
+        }
+        let commitments: Vec<P> = verify_deal(
+            deal,
+            &self.session_id,
+            &self.participants,
+            &self.pub_poly,
+            &self.helpers,
+            self.lost,
+        )?;
+        self.commitments.insert(deal.index, commitments);
+        Ok(())
+    }
+
+    /// process_share verifies the share of a helper against the commitments of
+    /// the parts it received. The deals of all the helpers must have been
+    /// processed.
+    pub fn process_share(&mut self, share: &RecoveryShare<P>) -> Result<(), Box<dyn Error>> {
+        if self.commitments.len() != self.helpers.len() {
+            bail!("recovery: deals of some helpers are missing");
+        }
+        let k: usize = self
+            .helpers
+            .iter()
+            .position(|&h| h == share.index)
+            .ok_or_else(|| simple_error!("recovery: share from a non helper"))?;
+        if self.shares.contains_key(&share.index) {
+            bail!("recovery: already received share from same index");
+        }
+        if !bitwise_eq(&share.session_id, &self.session_id) {
+            bail!("recovery: share with wrong session id");
+        }
+        P::verify_signature(
+            &self.participants[share.index as usize],
+            &share.signature,
+            &share.hash(),
+            &share.index.to_le_bytes(),
+        )
+        .map_err(|e| simple_error!("recovery: invalid signature in share: {}", e))?;
+
+        let value: P::Scalar = decrypt_part(&self.long, &self.session_id, &share.share)?;
+        let expected: P = self
+            .commitments
+            .values()
+            .fold(P::identity(), |acc, c| acc.add_point(&c[k].get_element()));
+        if P::generator().scalar_mul(&value.get_element()) != expected {
+            bail!("recovery: share not matching the commitments of its parts");
+        }
+        self.shares.insert(share.index, value);
+        Ok(())
+    }
+
+    /// finished returns true once the shares of all the helpers are processed.
+    pub fn finished(&self) -> bool {
+        self.shares.len() == self.helpers.len()
+    }
+
+    /// dist_key_share returns the recovered share of the distributed key.
+    pub fn dist_key_share(&self) -> Result<DistKeyShare<P>, Box<dyn Error>> {
+        if !self.finished() {
+            bail!("recovery: shares of some helpers are missing");
+        }
+        let v: P::Scalar = self
+            .shares
+            .values()
+            .fold(P::Scalar::zero(), |acc, s| acc.add(&s.get_element()));
+        let share = PriShare { i: self.lost, v };
+        if !self.pub_poly.check(&share) {
+            bail!("recovery: recovered share not matching the public polynomial");
+        }
+        let (_, commits) = self.pub_poly.info();
+        Ok(DistKeyShare::new(commits, share))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_traits::ECPoint;
+    use crate::dkg::DistKeyGenerator;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn gen_pair() -> (FE, GE) {
+        let secret: FE = ECScalar::new_random();
+        let public: GE = GE::generator().scalar_mul(&secret.get_element());
+        (secret, public)
+    }
+
+    struct Setup {
+        secs: Vec<FE>,
+        pubs: Vec<GE>,
+        shares: Vec<DistKeyShare>,
+    }
+
+    fn setup(n: u32, t: u32) -> Setup {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(n);
+        let mut dkgs: Vec<DistKeyGenerator> = secs
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, pubs.clone(), t).unwrap())
+            .collect();
+        let network = sim::Network::new(n, Default::default(), ChaCha20Rng::seed_from_u64(42));
+        let shares: Vec<DistKeyShare> = sim::run_dkg(&network, &mut dkgs, 100)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        Setup { secs, pubs, shares }
+    }
+
+    fn helpers(s: &Setup, indices: &[u32], lost: u32, replacement: GE) -> Vec<RecoveryHelper> {
+        indices
+            .iter()
+            .map(|&i| {
+                RecoveryHelper::new(
+                    s.secs[i as usize],
+                    &s.shares[i as usize],
+                    s.pubs.clone(),
+                    indices,
+                    lost,
+                    replacement,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_recovery_whole() {
+        let (n, t) = (5, 3);
+        let s = setup(n, t);
+        let (rep_sec, rep_pub) = gen_pair();
+        let lost: u32 = 1;
+        let indices: Vec<u32> = vec![0, 2, 4];
+
+        let mut helpers = helpers(&s, &indices, lost, rep_pub);
+        let mut receiver: RecoveryReceiver = RecoveryReceiver::new(
+            rep_sec,
+            s.shares[0].get_commitments().to_vec(),
+            s.pubs.clone(),
+            &indices,
+            lost,
+        )
+        .unwrap();
+
+        let deals: Vec<RecoveryDeal> = helpers.iter_mut().map(|h| h.deal().unwrap()).collect();
+        helpers[0]
+            .share()
+            .expect_err("Must fail, deals of the other helpers missing");
+        for (i, helper) in helpers.iter_mut().enumerate() {
+            for (j, deal) in deals.iter().enumerate() {
+                if i != j {
+                    helper.process_deal(deal).unwrap();
+                }
+            }
+        }
+        for deal in deals.iter() {
+            receiver.process_deal(deal).unwrap();
+        }
+        receiver
+            .process_deal(&deals[0])
+            .expect_err("Must fail, deal already processed");
+
+        for helper in helpers.iter() {
+            assert!(!receiver.finished());
+            receiver.process_share(&helper.share().unwrap()).unwrap();
+        }
+        let recovered: DistKeyShare = receiver.dist_key_share().unwrap();
+        assert_eq!(
+            s.shares[lost as usize].get_pri_share(),
+            recovered.get_pri_share()
+        );
+        assert_eq!(s.shares[0].get_public_key(), recovered.get_public_key());
+    }
+
+    #[test]
+    fn test_recovery_invalid() {
+        let (n, t) = (5, 3);
+        let s = setup(n, t);
+        let (_, rep_pub) = gen_pair();
+        let lost: u32 = 4;
+
+        RecoveryHelper::new(
+            s.secs[0],
+            &s.shares[0],
+            s.pubs.clone(),
+            &[0, 1],
+            lost,
+            rep_pub,
+        )
+        .expect_err("Must fail, less helpers than the threshold");
+        RecoveryHelper::new(
+            s.secs[0],
+            &s.shares[0],
+            s.pubs.clone(),
+            &[0, 1, 4],
+            lost,
+            rep_pub,
+        )
+        .expect_err("Must fail, lost participant helping");
+        RecoveryHelper::new(
+            s.secs[0],
+            &s.shares[0],
+            s.pubs.clone(),
+            &[1, 2, 3],
+            lost,
+            rep_pub,
+        )
+        .expect_err("Must fail, not a helper");
+
+        let indices: Vec<u32> = vec![0, 1, 2];
+        let mut helpers = helpers(&s, &indices, lost, rep_pub);
+        let deal: RecoveryDeal = helpers[0].deal().unwrap();
+
+        // commitments not adding up to the contribution of the helper
+        let mut bad: RecoveryDeal = deal.clone();
+        bad.commitments[2] = GE::generator().pk_to_key_slice();
+        bad.signature =
+            GE::sign_msg(&s.secs[0], &s.pubs[0], &bad.hash(), &0u32.to_le_bytes()).unwrap();
+        helpers[1]
+            .process_deal(&bad)
+            .expect_err("Must fail, wrong commitments");
+
+        let mut bad: RecoveryDeal = deal.clone();
+        bad.index = 2;
+        helpers[1]
+            .process_deal(&bad)
+            .expect_err("Must fail, invalid signature");
+
+        helpers[1].process_deal(&deal).unwrap();
+    }
+}
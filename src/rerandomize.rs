@@ -0,0 +1,222 @@
+//! Re-randomization of the shares of a distributed key, for proactive security.
+//!
+//! The holders of the shares jointly generate a sharing of zero, with the
+//! zero_sharing sub-protocol, and add it to their shares: every participant gets a fresh share of the same secret and
+//! the public key is preserved, while shares stolen before the re-randomization
+//! can't be combined with the new ones. Unlike a resharing, the participants
+//! and the threshold stay the same and no vss session is run:
+//!  1. every participant broadcasts a ZeroDeal holding the commitments of a
+//!     random polynomial whose constant term is zero, and its evaluation for
+//!     every participant encrypted to it.
+//!  2. every participant checks its evaluations against the commitments of the
+//!     deals, adds them to its share and adds the commitments to the public
+//!     polynomial of the key.
+//!
+//! All the participants must deal: a missing or invalid deal aborts the
+//! re-randomization, and the old shares must then be kept.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::utils;
+use crate::zero_sharing;
+
+use curve_traits::CurvePoint;
+use dkg::DistKeyShare;
+use encoding::Encoder;
+use poly::{PriShare, PubPoly};
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+pub use zero_sharing::ZeroDeal;
+use zero_sharing::ZeroSharing;
+
+// session_id binds the messages of a re-randomization to the current public
+// polynomial of the key, which changes with every re-randomization, and to the
+// participants.
+fn session_id<P: CurvePoint>(commits: &[P], participants: &[P]) -> Vec<u8> {
+    let commits: Vec<Vec<u8>> = commits.iter().map(P::pk_to_key_slice).collect();
+    let participants: Vec<Vec<u8>> = participants.iter().map(P::pk_to_key_slice).collect();
+    Encoder::new(b"rerandomizesession")
+        .put_list(&commits)
+        .put_list(&participants)
+        .hash()
+        .to_vec()
+}
+
+/// Rerandomizer is run by every holder of a share of the distributed key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Rerandomizer<P: CurvePoint = GE> {
+    // share of the distributed key held by this node
+    share: PriShare<P::Scalar>,
+    // public polynomial of the distributed key
+    pub_poly: PubPoly<P>,
+    // joint sharing of zero added to the shares
+    zero: ZeroSharing<P>,
+}
+
+impl<P: CurvePoint> Rerandomizer<P> {
+    /// longterm: the secret (private) key of the node,
+    /// share: the share of the distributed key held by this node,
+    /// participants: the list of participants pubkeys, indexed by the index of
+    /// their share. All of them must take part.
+    pub fn new(
+        longterm: P::Scalar,
+        share: &DistKeyShare<P>,
+        participants: Vec<P>,
+    ) -> Result<Rerandomizer<P>, Box<dyn Error>> {
+        Rerandomizer::new_with_rng(longterm, share, participants, &mut utils::rand_hack())
+    }
+
+    /// Same as new, with the zero-sharing drawn from rng.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        share: &DistKeyShare<P>,
+        participants: Vec<P>,
+        rng: &mut R,
+    ) -> Result<Rerandomizer<P>, Box<dyn Error>> {
+        let pub_poly: PubPoly<P> = share.pub_poly();
+        if (participants.len() as u32) < pub_poly.threshold() {
+            bail!("rerandomize: less participants than the threshold");
+        }
+        let pri_share: PriShare<P::Scalar> = share.get_pri_share();
+        let session_id = session_id(share.get_commitments(), &participants);
+        let zero = ZeroSharing::new_with_rng(
+            longterm,
+            participants,
+            pub_poly.threshold(),
+            &session_id,
+            rng,
+        )?;
+        if zero.index() != pri_share.i {
+            bail!("rerandomize: own public key not found at the index of the share");
+        }
+        Ok(Rerandomizer {
+            share: pri_share,
+            pub_poly,
+            zero,
+        })
+    }
+
+    /// deal returns the deal of this participant, to be broadcasted to the
+    /// other participants.
+    pub fn deal(&mut self) -> Result<ZeroDeal<P>, Box<dyn Error>> {
+        self.zero.deal()
+    }
+
+    /// Same as deal, with the encryptions drawn from rng.
+    pub fn deal_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<ZeroDeal<P>, Box<dyn Error>> {
+        self.zero.deal_with_rng(rng)
+    }
+
+    /// process_deal verifies the deal of another participant and stores its
+    /// evaluation for this participant.
+    pub fn process_deal(&mut self, deal: &ZeroDeal<P>) -> Result<(), Box<dyn Error>> {
+        self.zero.process_deal(deal)
+    }
+
+    /// finished returns true once the deals of all the participants are
+    /// processed, including the own deal of this participant.
+    pub fn finished(&self) -> bool {
+        self.zero.finished()
+    }
+
+    /// dist_key_share returns the re-randomized share of the distributed key,
+    /// which replaces the old one.
+    pub fn dist_key_share(&self) -> Result<DistKeyShare<P>, Box<dyn Error>> {
+        let share = poly::rerandomize_share(&self.share, &self.zero.zero_shares()?)?;
+        let pub_poly = poly::rerandomize_pub_poly(&self.pub_poly, &self.zero.zero_polys()?)?;
+        if !pub_poly.check(&share) {
+            bail!("rerandomize: new share not matching the new public polynomial");
+        }
+        let (_, commits) = pub_poly.info();
+        Ok(DistKeyShare::new(commits, share))
+    }
+
+    pub fn index(&self) -> u32 {
+        self.share.i
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_rerandomize() {
+        let (n, t) = (5, 3);
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(n);
+        let mut dkgs: Vec<DistKeyGenerator> = secs
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, pubs.clone(), t).unwrap())
+            .collect();
+        let network = sim::Network::new(n, Default::default(), ChaCha20Rng::seed_from_u64(42));
+        let shares: Vec<DistKeyShare> = sim::run_dkg(&network, &mut dkgs, 100)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+
+        let mut parts: Vec<Rerandomizer> = secs
+            .iter()
+            .zip(shares.iter())
+            .map(|(sec, share)| Rerandomizer::new(*sec, share, pubs.clone()).unwrap())
+            .collect();
+        let deals: Vec<ZeroDeal> = parts.iter_mut().map(|r| r.deal().unwrap()).collect();
+        parts[0]
+            .dist_key_share()
+            .expect_err("Must fail, deals of the other participants missing");
+
+        let mut bad: ZeroDeal = deals[1].clone();
+        bad.parts.swap(0, 2);
+        bad.signature = GE::sign_msg(&secs[1], &pubs[1], &bad.hash(), &1u32.to_le_bytes()).unwrap();
+        parts[0]
+            .clone()
+            .process_deal(&bad)
+            .expect_err("Must fail, part not matching the commitments");
+
+        for (i, r) in parts.iter_mut().enumerate() {
+            for (j, deal) in deals.iter().enumerate() {
+                if i != j {
+                    r.process_deal(deal).unwrap();
+                }
+            }
+            r.process_deal(&deals[(i + 1) % deals.len()])
+                .expect_err("Must fail, deal already processed");
+            assert!(r.finished());
+        }
+
+        let new_shares: Vec<DistKeyShare> =
+            parts.iter().map(|r| r.dist_key_share().unwrap()).collect();
+        let old: Vec<PriShare<FE>> = shares.iter().map(|s| s.get_pri_share()).collect();
+        let new: Vec<PriShare<FE>> = new_shares.iter().map(|s| s.get_pri_share()).collect();
+        for (s, r) in shares.iter().zip(new_shares.iter()) {
+            assert_eq!(s.get_public_key(), r.get_public_key());
+            assert_ne!(s.get_pri_share(), r.get_pri_share());
+            assert_eq!(new_shares[0].get_commitments(), r.get_commitments());
+        }
+        assert_eq!(
+            poly::recover_secret(&old[..3], t).unwrap(),
+            poly::recover_secret(&new[2..], t).unwrap()
+        );
+        // the old and new shares don't mix
+        let mixed: Vec<PriShare<FE>> = vec![old[0].clone(), old[1].clone(), new[2].clone()];
+        assert_ne!(
+            poly::recover_secret(&old[..3], t).unwrap(),
+            poly::recover_secret(&mixed, t).unwrap()
+        );
+    }
+}
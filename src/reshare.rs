@@ -0,0 +1,625 @@
+//! Resharing of a distributed key to a new set of participants.
+//!
+//! The holders of the shares of a distributed key act as dealers: each of them
+//! shares its own share with a vss Dealer towards the new participants and with
+//! the new threshold. Once the deals are certified, the dealers reveal the
+//! commitments of their polynomial, whose constant term must be their public
+//! share of the old key. Every new participant combines the deals of at least
+//! t_old dealers with Lagrange interpolation, which gives a fresh sharing of the
+//! same secret: the distributed public key is preserved while the old shares
+//! become useless once deleted.
+//!
+//! Resharing to the same participants with another threshold changes the
+//! threshold of the distributed key: see ReshareReceiver::change_threshold.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::utils;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECScalar};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+
+use dkg::{Deal, DistKeyShare, Justification, Response, SecretCommits};
+use poly::{PriShare, PubPoly};
+use utils::bitwise_eq;
+use vss::Deal as vssDeal;
+use vss::Dealer;
+use vss::EncryptedDeal;
+use vss::Response as vssResponce;
+use vss::Verifier as vssVerifier;
+
+/// ReshareDealer is run by a holder of a share of the old distributed key, to
+/// deal it to the new participants.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ReshareDealer<P: CurvePoint = GE> {
+    // Index of the share in the old sharing of the key
+    index: u32,
+    // longterm secret, i.e. private key of the node
+    long: P::Scalar,
+    // longterm public key
+    pub_key: P,
+    // vss dealer sharing the old share among the new participants
+    dealer: Dealer<P>,
+}
+
+/// ReshareReceiver is run by every new participant to collect the deals of the
+/// old share holders and compute its share of the distributed key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ReshareReceiver<P: CurvePoint = GE> {
+    // Index of the participant in the list of new participants
+    index: u32,
+    // longterm secret, i.e. private key of the node
+    long: P::Scalar,
+    // pub keys of the old share holders
+    old_participants: Vec<P>,
+    // public polynomial of the distributed key
+    old_pub_poly: PubPoly<P>,
+    // pub keys of the new participants
+    new_participants: Vec<P>,
+    // new threshold
+    new_t: u32,
+    // verifiers to process deals from old share holder i
+    verifiers: HashMap<u32, vssVerifier<P>>,
+    // commitments to the polynomial of each dealer
+    commitments: HashMap<u32, PubPoly<P>>,
+}
+
+impl<P: CurvePoint> ReshareDealer<P> {
+    /// longterm: the secret (private) key of the node,
+    /// share: the share of the distributed key held by this node,
+    /// new_participants: the list of the new participants pubkeys,
+    /// new_t: threshold of the new sharing
+    pub fn new(
+        longterm: P::Scalar,
+        share: &DistKeyShare<P>,
+        new_participants: Vec<P>,
+        new_t: u32,
+    ) -> Result<ReshareDealer<P>, Box<dyn Error>> {
+        let pub_key: P = P::generator().scalar_mul(&longterm.get_element());
+        let pri_share: PriShare<P::Scalar> = share.get_pri_share();
+        let dealer = Dealer::new(longterm, pri_share.v, new_participants, new_t)?;
+
+        Ok(ReshareDealer {
+            index: pri_share.i,
+            long: longterm,
+            pub_key,
+            dealer,
+        })
+    }
+
+    /// deals returns the deals to send to the new participants, indexed by
+    /// their position in the list of new participants.
+    pub fn deals(&self) -> Result<HashMap<u32, Deal<P>>, Box<dyn Error>> {
+        let deals: Vec<EncryptedDeal<P>> = self.dealer.encrypt_deals()?;
+        Ok(deals
+            .into_iter()
+            .enumerate()
+            .map(|(i, deal)| {
+                (
+                    i as u32,
+                    Deal {
+                        index: self.index,
+                        deal,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// process_response processes the response of a new participant to the deal
+    /// of this dealer. It returns a justification that must be broadcasted to
+    /// all the new participants in case of a complaint.
+    pub fn process_response(
+        &mut self,
+        resp: &Response,
+    ) -> Result<Option<Justification<P>>, Box<dyn Error>> {
+        if resp.index != self.index {
+            bail!("reshare: response for another dealer");
+        }
+
+        Ok(self
+            .dealer
+            .process_response(&resp.response)?
+            .map(|justification| Justification {
+                index: self.index,
+                justification,
+            }))
+    }
+
+    /// set_timeout considers all the new participants which did not respond
+    /// yet as complaining.
+    pub fn set_timeout(&mut self) {
+        self.dealer.set_timeout()
+    }
+
+    /// secret_commits returns the commitments of the coefficients of the
+    /// polynomial sharing the old share, to be broadcasted to the new
+    /// participants. The deal must be certified.
+    pub fn secret_commits(&self) -> Result<SecretCommits, Box<dyn Error>> {
+        if !self.dealer.deal_certified() {
+            bail!("reshare: can't give SecretCommits if deal not certified");
+        }
+        let commitments: Vec<Vec<u8>> = self.dealer.commits()?;
+        let msg: [u8; 32] = SecretCommits::hash(&commitments, self.index)?;
+        let signature = P::sign_msg(&self.long, &self.pub_key, &msg, &self.index.to_le_bytes())?;
+
+        Ok(SecretCommits {
+            index: self.index,
+            commitments,
+            session_id: self.dealer.get_session_id().to_vec(),
+            signature,
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl<P: CurvePoint> ReshareReceiver<P> {
+    /// longterm: the secret (private) key of the node,
+    /// old_participants: the list of pubkeys of the old share holders, indexed
+    /// by the index of their share,
+    /// old_commits: the coefficients of the public polynomial of the
+    /// distributed key (DistKeyShare::get_commitments),
+    /// new_participants: the list of the new participants pubkeys,
+    /// new_t: threshold of the new sharing
+    ///
+    /// It returns an error if the secret key's commitment can't be found in the
+    /// list of new participants.
+    pub fn new(
+        longterm: P::Scalar,
+        old_participants: Vec<P>,
+        old_commits: Vec<P>,
+        new_participants: Vec<P>,
+        new_t: u32,
+    ) -> Result<ReshareReceiver<P>, Box<dyn Error>> {
+        let pub_k: P = P::generator().scalar_mul(&longterm.get_element());
+        let index = new_participants
+            .iter()
+            .position(|point| point == &pub_k)
+            .ok_or_else(|| {
+                simple_error!("reshare: own public key not found in list of new participants")
+            })?;
+
+        if !vss::valid_t(new_t, &new_participants) {
+            bail!("reshare: invalid new threshold");
+        }
+        if old_commits.is_empty() || old_commits.len() > old_participants.len() {
+            bail!("reshare: invalid old commitments");
+        }
+
+        Ok(ReshareReceiver {
+            index: index as u32,
+            long: longterm,
+            old_participants,
+            old_pub_poly: PubPoly::new(P::generator(), old_commits),
+            new_participants,
+            new_t,
+            verifiers: Default::default(),
+            commitments: Default::default(),
+        })
+    }
+
+    /// change_threshold returns the receiver of a participant resharing the
+    /// distributed key among the same participants with the new threshold
+    /// new_t, which can be lower or higher than the current one. The share of
+    /// the participant gives the public polynomial of the key, and every holder
+    /// of a share runs a ReshareDealer towards the same participants with new_t.
+    pub fn change_threshold(
+        longterm: P::Scalar,
+        participants: Vec<P>,
+        share: &DistKeyShare<P>,
+        new_t: u32,
+    ) -> Result<ReshareReceiver<P>, Box<dyn Error>> {
+        ReshareReceiver::new(
+            longterm,
+            participants.clone(),
+            share.get_commitments().to_vec(),
+            participants,
+            new_t,
+        )
+    }
+
+    /// process_deal verifies the deal of an old share holder and returns the
+    /// response to broadcast to the other new participants and to the dealer.
+    pub fn process_deal(&mut self, dd: &Deal<P>) -> Result<Response, Box<dyn Error>> {
+        let pub_k = self
+            .old_participants
+            .get(dd.index as usize)
+            .ok_or_else(|| simple_error!("reshare: deal out of bounds index"))?;
+
+        if self.verifiers.contains_key(&dd.index) {
+            bail!("reshare: already received deal from same index");
+        }
+
+        let mut ver: vssVerifier<P> =
+            vssVerifier::new(self.long, *pub_k, self.new_participants.clone())?;
+        let resp: vssResponce = ver.process_encrypted_deal(&dd.deal)?;
+        self.verifiers.insert(dd.index, ver);
+
+        Ok(Response {
+            index: dd.index,
+            response: resp,
+        })
+    }
+
+    /// process_response stores the response of another new participant to the
+    /// deal of an old share holder.
+    pub fn process_response(&mut self, resp: &Response) -> Result<(), Box<dyn Error>> {
+        self.verifiers
+            .get_mut(&resp.index)
+            .ok_or_else(|| simple_error!("reshare: response received but no deal for it"))?
+            .process_response(&resp.response)
+    }
+
+    /// process_justification validates the justification of an old share
+    /// holder.
+    pub fn process_justification(&mut self, j: &Justification<P>) -> Result<(), Box<dyn Error>> {
+        self.verifiers
+            .get_mut(&j.index)
+            .ok_or_else(|| simple_error!("reshare: justification received but no deal for it"))?
+            .process_justification(&j.justification)
+    }
+
+    /// set_timeout triggers the timeout on all verifiers.
+    pub fn set_timeout(&mut self) {
+        for v in self.verifiers.values_mut() {
+            v.set_timeout();
+        }
+    }
+
+    /// qual returns the indices of the old share holders whose deal is
+    /// certified and whose commitments have been verified.
+    pub fn qual(&self) -> Vec<u32> {
+        let mut qual: Vec<u32> = self
+            .verifiers
+            .iter()
+            .filter(|(i, v)| v.deal_certified() && self.commitments.contains_key(*i))
+            .map(|(&i, _)| i)
+            .collect();
+        qual.sort_unstable();
+        qual
+    }
+
+    /// process_secret_commit verifies the commitments revealed by an old share
+    /// holder: they must match the deal received from it and the constant term
+    /// must be its public share of the distributed key. It returns an error
+    /// otherwise, in which case the dealer is left out of the resharing.
+    pub fn process_secret_commit(&mut self, sc: &SecretCommits) -> Result<(), Box<dyn Error>> {
+        let pub_k: &P = self
+            .old_participants
+            .get(sc.index as usize)
+            .ok_or_else(|| simple_error!("reshare: secretcommits with index out of bounds"))?;
+
+        let v: &vssVerifier<P> = self
+            .verifiers
+            .get(&sc.index)
+            .filter(|v| v.deal_certified())
+            .ok_or_else(|| simple_error!("reshare: secretcommits from a non certified dealer"))?;
+
+        if !bitwise_eq(&v.session_id(), &sc.session_id) {
+            bail!("reshare: secretcommits received with wrong session id");
+        }
+
+        let msg: [u8; 32] = SecretCommits::hash(&sc.commitments, sc.index)?;
+        P::verify_signature(pub_k, &sc.signature, &msg, &sc.index.to_le_bytes())
+            .map_err(|e| simple_error!("reshare: invalid signature in SecretCommit: {}", e))?;
+
+        let commitments: Vec<P> = sc
+            .commitments
+            .iter()
+            .map(|x| P::from_bytes(x))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|x| simple_error!("Error while constructing point from bytes: {}", x))?;
+        if commitments.len() as u32 != self.new_t {
+            bail!("reshare: secretcommits with wrong threshold");
+        }
+
+        // the dealer must be sharing its share of the old key
+        if commitments[0] != self.old_pub_poly.eval(sc.index).v {
+            bail!("reshare: secretcommits not sharing the old share of the dealer");
+        }
+
+        let deal: vssDeal<P> = v.get_deal()?;
+        let polynomial: PubPoly<P> = PubPoly::new(P::generator(), commitments);
+        if !polynomial.check(&deal.sec_share) {
+            bail!("reshare: deal not matching the secretcommits");
+        }
+
+        self.commitments.insert(sc.index, polynomial);
+        Ok(())
+    }
+
+    /// finished returns true once enough old share holders are qualified to
+    /// compute the new share.
+    pub fn finished(&self) -> bool {
+        self.qual().len() as u32 >= self.old_pub_poly.threshold()
+    }
+
+    /// dist_key_share returns the share of the distributed key for this new
+    /// participant. The deals and commitments of the qualified old share holders
+    /// are combined with Lagrange interpolation, so the public key is the same as
+    /// before the resharing.
+    pub fn dist_key_share(&self) -> Result<DistKeyShare<P>, Box<dyn Error>> {
+        if !self.finished() {
+            bail!("reshare: not enough qualified dealers");
+        }
+
+        let qual: Vec<u32> = self.qual();
+        let mut share: P::Scalar = ECScalar::zero();
+        let mut commits: Vec<P> = vec![P::identity(); self.new_t as usize];
+        for &i in qual.iter() {
+            let lambda: P::Scalar = poly::lagrange_coefficient(i, &qual);
+
+            let s: P::Scalar = self.verifiers[&i].get_deal()?.sec_share.v;
+            share = share.add(&s.mul(&lambda.get_element()).get_element());
+
+            let (_, dealer_commits) = self.commitments[&i].info();
+            for (c, dc) in commits.iter_mut().zip(dealer_commits.iter()) {
+                *c = c.add_point(&dc.scalar_mul(&lambda.get_element()).get_element());
+            }
+        }
+
+        if commits[0] != self.old_pub_poly.commit() {
+            bail!("reshare: distributed public key not preserved");
+        }
+
+        Ok(DistKeyShare::new(
+            commits,
+            PriShare {
+                i: self.index,
+                v: share,
+            },
+        ))
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_traits::ECPoint;
+    use crate::dkg::DistKeyGenerator;
+    use crate::ristretto_curve::FE;
+
+    fn gen_pair() -> (FE, GE) {
+        let secret: FE = ECScalar::new_random();
+        let public: GE = GE::generator().scalar_mul(&secret.get_element());
+        (secret, public)
+    }
+
+    fn gen_dist_secret(parts_sec: &[FE], parts_pub: &[GE], t: u32) -> Vec<DistKeyShare> {
+        let mut dkgs: Vec<DistKeyGenerator> = parts_sec
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, parts_pub.to_vec(), t).unwrap())
+            .collect();
+
+        // 1. broadcast deals
+        let mut resps: Vec<Response> = Vec::new();
+        for i in 0..dkgs.len() {
+            let deals = dkgs[i].deals().unwrap();
+            for (&j, deal) in deals.iter() {
+                resps.push(dkgs[j as usize].process_deal(deal).unwrap());
+            }
+        }
+        // 2. broadcast responses
+        for r in resps.iter() {
+            for dkg in dkgs.iter_mut() {
+                if r.response.index != dkg.index() {
+                    assert!(dkg.process_response(r).unwrap().is_none());
+                }
+            }
+        }
+        // 3. broadcast secret commitments
+        for i in 0..dkgs.len() {
+            let scs: SecretCommits = dkgs[i].secret_commits().unwrap();
+            for (j, dkg) in dkgs.iter_mut().enumerate() {
+                if i != j {
+                    assert!(dkg.process_secret_commit(&scs).unwrap().is_none());
+                }
+            }
+        }
+
+        dkgs.iter()
+            .map(DistKeyGenerator::dist_key_share)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    struct Setup {
+        old_sec: Vec<FE>,
+        old_pub: Vec<GE>,
+        old_shares: Vec<DistKeyShare>,
+        new_sec: Vec<FE>,
+        new_pub: Vec<GE>,
+    }
+
+    fn setup(old_n: u32, old_t: u32, new_n: u32) -> Setup {
+        let (old_sec, old_pub): (Vec<FE>, Vec<GE>) = (0..old_n).map(|_| gen_pair()).unzip();
+        let (new_sec, new_pub): (Vec<FE>, Vec<GE>) = (0..new_n).map(|_| gen_pair()).unzip();
+        let old_shares = gen_dist_secret(&old_sec, &old_pub, old_t);
+        Setup {
+            old_sec,
+            old_pub,
+            old_shares,
+            new_sec,
+            new_pub,
+        }
+    }
+
+    fn new_receivers(s: &Setup, new_t: u32) -> Vec<ReshareReceiver> {
+        s.new_sec
+            .iter()
+            .map(|sec| {
+                ReshareReceiver::new(
+                    *sec,
+                    s.old_pub.clone(),
+                    s.old_shares[0].get_commitments().to_vec(),
+                    s.new_pub.clone(),
+                    new_t,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    // deal runs the deals and responses between the dealers and the receivers
+    fn deal(dealers: &mut [ReshareDealer], receivers: &mut [ReshareReceiver]) {
+        let mut resps: Vec<Response> = Vec::new();
+        for dealer in dealers.iter() {
+            for (&j, deal) in dealer.deals().unwrap().iter() {
+                resps.push(receivers[j as usize].process_deal(deal).unwrap());
+            }
+        }
+        for r in resps.iter() {
+            for receiver in receivers.iter_mut() {
+                if r.response.index != receiver.index() {
+                    receiver.process_response(r).unwrap();
+                }
+            }
+            let dealer = dealers.iter_mut().find(|d| d.index() == r.index).unwrap();
+            assert!(dealer.process_response(r).unwrap().is_none());
+        }
+    }
+
+    // reshare runs the resharing from the old share holders in `dealers`,
+    // skipping the secret commits of the ones in `silent`
+    fn reshare(s: &Setup, dealers: &[u32], silent: &[u32], new_t: u32) -> Vec<ReshareReceiver> {
+        let mut dealers: Vec<ReshareDealer> = dealers
+            .iter()
+            .map(|&i| {
+                let i = i as usize;
+                ReshareDealer::new(s.old_sec[i], &s.old_shares[i], s.new_pub.clone(), new_t)
+                    .unwrap()
+            })
+            .collect();
+        let mut receivers = new_receivers(s, new_t);
+
+        deal(&mut dealers, &mut receivers);
+        for dealer in dealers.iter().filter(|d| !silent.contains(&d.index())) {
+            let scs: SecretCommits = dealer.secret_commits().unwrap();
+            for receiver in receivers.iter_mut() {
+                receiver.process_secret_commit(&scs).unwrap();
+            }
+        }
+        receivers
+    }
+
+    #[test]
+    fn test_reshare_whole() {
+        let (old_n, old_t) = (5, 3);
+        let (new_n, new_t) = (7, 4);
+        let s = setup(old_n, old_t, new_n);
+        let public_key = s.old_shares[0].get_public_key();
+
+        // the share holder 2 doesn't take part, 4 doesn't reveal its commitments
+        let receivers = reshare(&s, &[0, 1, 3, 4], &[4], new_t);
+        let new_shares: Vec<DistKeyShare> = receivers
+            .iter()
+            .map(|r| r.dist_key_share().unwrap())
+            .collect();
+
+        let pub_poly: PubPoly =
+            PubPoly::new(GE::generator(), new_shares[0].get_commitments().to_vec());
+        assert_eq!(new_t, pub_poly.threshold());
+        for share in new_shares.iter() {
+            assert_eq!(public_key, share.get_public_key());
+            assert!(pub_poly.check(&share.get_pri_share()));
+        }
+
+        let old_pri: Vec<PriShare<FE>> = s.old_shares.iter().map(|s| s.get_pri_share()).collect();
+        let new_pri: Vec<PriShare<FE>> = new_shares.iter().map(|s| s.get_pri_share()).collect();
+        let secret: FE = poly::recover_secret(&old_pri, old_t).unwrap();
+        assert_eq!(secret, poly::recover_secret(&new_pri[3..], new_t).unwrap());
+        assert!(poly::recover_secret(&new_pri[..(new_t - 1) as usize], new_t).is_err());
+    }
+
+    #[test]
+    fn test_reshare_not_enough_dealers() {
+        let (n, t) = (5, 3);
+        let s = setup(n, t, n);
+
+        let receivers = reshare(&s, &[0, 1, 2], &[1], t);
+        assert!(!receivers[0].finished());
+        receivers[0]
+            .dist_key_share()
+            .expect_err("Must fail, not enough dealers");
+    }
+
+    #[test]
+    fn test_reshare_wrong_secret() {
+        let (n, t) = (5, 3);
+        let s = setup(n, t, n);
+
+        // dealer 0 sharing the share of another holder of the old key
+        let mut dealer: ReshareDealer =
+            ReshareDealer::new(s.old_sec[0], &s.old_shares[1], s.new_pub.clone(), t).unwrap();
+        dealer.index = 0;
+        let mut dealers = vec![dealer];
+        let mut receivers = new_receivers(&s, t);
+        deal(&mut dealers, &mut receivers);
+
+        let scs: SecretCommits = dealers[0].secret_commits().unwrap();
+        receivers[0]
+            .process_secret_commit(&scs)
+            .expect_err("Must fail, wrong share dealt");
+        assert!(receivers[0].qual().is_empty());
+    }
+
+    #[test]
+    fn test_reshare_change_threshold() {
+        let (n, t) = (5, 3);
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = (0..n).map(|_| gen_pair()).unzip();
+        let shares: Vec<DistKeyShare> = gen_dist_secret(&secs, &pubs, t);
+        let old_pri: Vec<PriShare<FE>> = shares.iter().map(|s| s.get_pri_share()).collect();
+        let secret: FE = poly::recover_secret(&old_pri, t).unwrap();
+
+        for &new_t in &[t + 1, t - 1] {
+            let mut dealers: Vec<ReshareDealer> = secs
+                .iter()
+                .zip(shares.iter())
+                .map(|(sec, share)| ReshareDealer::new(*sec, share, pubs.clone(), new_t).unwrap())
+                .collect();
+            let mut receivers: Vec<ReshareReceiver> = secs
+                .iter()
+                .zip(shares.iter())
+                .map(|(sec, share)| {
+                    ReshareReceiver::change_threshold(*sec, pubs.clone(), share, new_t).unwrap()
+                })
+                .collect();
+            deal(&mut dealers, &mut receivers);
+            for dealer in dealers.iter() {
+                let scs: SecretCommits = dealer.secret_commits().unwrap();
+                for receiver in receivers.iter_mut() {
+                    receiver.process_secret_commit(&scs).unwrap();
+                }
+            }
+
+            let new_shares: Vec<DistKeyShare> = receivers
+                .iter()
+                .map(|r| r.dist_key_share().unwrap())
+                .collect();
+            let new_pri: Vec<PriShare<FE>> = new_shares.iter().map(|s| s.get_pri_share()).collect();
+            for (old, new) in shares.iter().zip(new_shares.iter()) {
+                assert_eq!(old.get_public_key(), new.get_public_key());
+                assert_eq!(old.get_pri_share().i, new.get_pri_share().i);
+                assert_eq!(new_t as usize, new.get_commitments().len());
+            }
+            assert_eq!(secret, poly::recover_secret(&new_pri, new_t).unwrap());
+            assert!(poly::recover_secret(&new_pri[..(new_t - 1) as usize], new_t).is_err());
+        }
+    }
+}
@@ -1,22 +1,34 @@
 //! Ristretto curve - the cryptographic backend of the library
 
-use crate::curve_traits::{ECPoint, ECScalar};
+use crate::blake;
+use crate::curve_traits;
+use crate::curve_traits::{CurvePoint, ECPoint, ECScalar};
+use crate::error::Error;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::sign;
 use crate::utils;
-use std::convert::From;
-use std::error::Error;
-use std::fmt;
+use core::convert::From;
+use core::fmt;
 
-use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
-use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_TABLE};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
 use hex::{decode, encode};
+use rand_core::{CryptoRng, RngCore};
 use serde::de;
 use serde::de::Visitor;
 use serde::ser::{Serialize, Serializer};
 use serde::{Deserialize, Deserializer};
+use sha2::Sha512;
 
 pub const SECRET_KEY_SIZE: usize = 32;
 
+// 2^(l-1)/4 with the non-residue 2, a primitive 4th root of unity of the
+// scalar field, little endian
+const ROOT_OF_UNITY: &str = "d407beebdf7587befe83ce425356f00e7ac2c1ab606d3d7de78179e010734a09";
+
 pub type SK = Scalar;
 pub type PK = CompressedRistretto;
 
@@ -55,10 +67,10 @@ impl From<SK> for RistrettoScalar {
 }
 
 impl ECScalar<SK> for RistrettoScalar {
-    fn new_random() -> Self {
+    fn new_random_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
         Self {
             purpose: "random",
-            fe: SK::random(&mut utils::rand_hack()),
+            fe: SK::random(rng),
         }
     }
 
@@ -81,6 +93,18 @@ impl ECScalar<SK> for RistrettoScalar {
         encode(self.get_element().to_bytes())
     }
 
+    fn to_bytes(&self) -> Vec<u8> {
+        self.fe.to_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let bytes_array_32 = utils::arr32_from_slice(bytes)?;
+        Ok(Self {
+            purpose: "from_bytes",
+            fe: SK::from_bytes_mod_order(bytes_array_32),
+        })
+    }
+
     fn add(&self, other: &SK) -> Self {
         Self {
             purpose: "add",
@@ -145,8 +169,9 @@ impl<'de> Visitor<'de> for RistrettoScalarVisitor {
     }
 
     fn visit_str<E: de::Error>(self, s: &str) -> Result<RistrettoScalar, E> {
-        let s_bytes: Vec<u8> = decode(s).expect("Failed in decoding");
-        let b: [u8; 32] = utils::arr32_from_slice(&s_bytes).expect("Failed in array creation");
+        let s_bytes: Vec<u8> = decode(s).map_err(E::custom)?;
+        let b: [u8; 32] = utils::arr32_from_slice(&s_bytes)
+            .map_err(|_| E::custom("ristretto: invalid scalar length"))?;
         let s: Scalar = Scalar::from_bytes_mod_order(b);
         Ok(RistrettoScalar {
             purpose: "from_bytes",
@@ -222,6 +247,121 @@ impl ECPoint<PK, SK> for RistrettoCurvPoint {
     }
 }
 
+impl CurvePoint for RistrettoCurvPoint {
+    type SK = SK;
+    type PK = PK;
+    type Scalar = RistrettoScalar;
+
+    const CURVE_ID: &'static str = "ristretto255";
+    const TWO_ADICITY: u32 = 2;
+
+    fn root_of_unity() -> RistrettoScalar {
+        RistrettoScalar::from_bytes(&decode(ROOT_OF_UNITY).unwrap()).unwrap()
+    }
+
+    fn identity() -> RistrettoCurvPoint {
+        RistrettoCurvPoint {
+            purpose: "identity",
+            ge: CompressedRistretto([0u8; 32]),
+        }
+    }
+
+    /// Hash bytes with blake2xb and take the first 32 bytes chunk of the output
+    /// which decodes to a valid point
+    fn hash_to_point(bytes: &[u8]) -> Result<RistrettoCurvPoint, Box<dyn Error>> {
+        let hash = blake::new_blake2xb(bytes.to_vec());
+        for value in hash {
+            for chunk in value.as_ref().chunks(32) {
+                if let Ok(point) = RistrettoCurvPoint::from_bytes(chunk) {
+                    return Ok(point);
+                }
+            }
+        }
+        bail!("Error hash")
+    }
+
+    fn hash_to_scalar(bytes: &[u8]) -> RistrettoScalar {
+        RistrettoScalar {
+            purpose: "from_hash",
+            fe: SK::hash_from_bytes::<Sha512>(bytes),
+        }
+    }
+
+    const HASH_TO_GROUP_SUITE: &'static str = "ristretto255_XMD:SHA-512_R255MAP_RO_";
+
+    /// hash_to_ristretto255 of RFC 9380: the ristretto255 map of 64 bytes of
+    /// expand_message_xmd with SHA-512
+    fn hash_to_group(msg: &[u8], dst: &[u8]) -> Result<RistrettoCurvPoint, Box<dyn Error>> {
+        let uniform: Vec<u8> = curve_traits::expand_message_xmd(msg, dst, 64)?;
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&uniform);
+        Ok(map_to_group(&bytes))
+    }
+
+    /// multiplication by the precomputed table of the basepoint of dalek
+    fn base_mul(scalar: &RistrettoScalar) -> RistrettoCurvPoint {
+        RistrettoCurvPoint {
+            purpose: "base_mul",
+            ge: (&RISTRETTO_BASEPOINT_TABLE * &scalar.get_element()).compress(),
+        }
+    }
+
+    /// Straus' method for small inputs and Pippenger's for large ones
+    fn multi_scalar_mul(
+        scalars: &[RistrettoScalar],
+        points: &[RistrettoCurvPoint],
+    ) -> RistrettoCurvPoint {
+        let sum = RistrettoPoint::vartime_multiscalar_mul(
+            scalars.iter().map(|s| s.get_element()),
+            points.iter().map(|p| p.ge.decompress().unwrap()),
+        );
+        RistrettoCurvPoint {
+            purpose: "multi_scalar_mul",
+            ge: sum.compress(),
+        }
+    }
+
+    /// Ristretto keys are signed with schnorrkel (sr25519)
+    #[cfg(feature = "std")]
+    fn sign_msg(
+        secret: &RistrettoScalar,
+        public: &RistrettoCurvPoint,
+        msg: &[u8],
+        context: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        sign::sign_msg(
+            secret.get_element().to_bytes(),
+            public.get_element().to_bytes(),
+            msg,
+            context,
+        )
+    }
+
+    fn verify_signature(
+        public: &RistrettoCurvPoint,
+        signature: &[u8],
+        msg: &[u8],
+        context: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        sign::verify_signature(&public.pk_to_key_slice(), signature, msg, context)
+            .map_err(|e| simple_error!("{}", e))?;
+        Ok(())
+    }
+
+    /// The schnorrkel signatures are verified with one batch verification
+    #[cfg(feature = "std")]
+    fn verify_signatures(
+        publics: &[RistrettoCurvPoint],
+        signatures: &[&[u8]],
+        msgs: &[&[u8]],
+        contexts: &[&[u8]],
+    ) -> Result<(), Vec<usize>> {
+        let keys: Vec<Vec<u8>> = publics.iter().map(|p| p.pk_to_key_slice()).collect();
+        let keys: Vec<&[u8]> = keys.iter().map(|k| k.as_slice()).collect();
+        sign::verify_batch(msgs, signatures, &keys, contexts)
+    }
+}
+
 impl Serialize for RistrettoCurvPoint {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -250,8 +390,15 @@ impl<'de> Visitor<'de> for RistrettoCurvPointVisitor {
     }
 
     fn visit_str<E: de::Error>(self, s: &str) -> Result<RistrettoCurvPoint, E> {
-        let s_bytes: Vec<u8> = decode(s).expect("Failed in decoding");
+        let s_bytes: Vec<u8> = decode(s).map_err(E::custom)?;
+        if s_bytes.len() != 32 {
+            return Err(E::custom("ristretto: invalid point length"));
+        }
         let ge: CompressedRistretto = CompressedRistretto::from_slice(&s_bytes);
+        // the point operations decompress it
+        if ge.decompress().is_none() {
+            return Err(E::custom("ristretto: invalid point"));
+        }
         Ok(RistrettoCurvPoint {
             purpose: "from_bytes",
             ge,
@@ -264,3 +411,67 @@ impl PartialEq for RistrettoCurvPoint {
         self.get_element() == other.get_element()
     }
 }
+
+// map_to_group is the one-way map of RFC 9496, from 64 uniform bytes to a
+// point.
+fn map_to_group(uniform: &[u8; 64]) -> RistrettoCurvPoint {
+    RistrettoCurvPoint {
+        purpose: "from_hash",
+        ge: RistrettoPoint::from_uniform_bytes(uniform).compress(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::Digest;
+
+    #[test]
+    fn test_ristretto_hash_to_group() {
+        // RFC 9496, A.3: the 64 bytes inputs are the SHA-512 of the labels
+        let vectors = [
+            (
+                "Ristretto is traditionally a short shot of espresso coffee",
+                "3066f82a1a747d45120d1740f14358531a8f04bbffe6a819f86dfe50f44a0a46",
+            ),
+            (
+                "made with the normal amount of ground coffee but extracted with",
+                "f26e5b6f7d362d2d2a94c5d0e7602cb4773c95a2e5c31a64f133189fa76ed61b",
+            ),
+            (
+                "about half the amount of water in the same amount of time",
+                "006ccd2a9e6867e6a2c5cea83d3302cc9de128dd2a9a57dd8ee7b9d7ffe02826",
+            ),
+            (
+                "by using a finer grind.",
+                "f8f0c87cf237953c5890aec3998169005dae3eca1fbb04548c635953c817f92a",
+            ),
+            (
+                "This produces a concentrated shot of coffee per volume.",
+                "ae81e7dedf20a497e10c304a765c1767a42d6e06029758d2d7e8ef7cc4c41179",
+            ),
+            (
+                "Just pulling a normal shot short will produce a weaker shot",
+                "e2705652ff9f5e44d3e841bf1c251cf7dddb77d140870d1ab2ed64f1a9ce8628",
+            ),
+            (
+                "and is not a Ristretto as some believe.",
+                "80bd07262511cdde4863f8a7434cef696750681cb9510eea557088f76d9e5065",
+            ),
+        ];
+        for (label, expected) in vectors.iter() {
+            let mut uniform = [0u8; 64];
+            uniform.copy_from_slice(&Sha512::digest(label.as_bytes()));
+            assert_eq!(*expected, map_to_group(&uniform).to_hex());
+        }
+
+        // hash_to_ristretto255 maps the expand_message_xmd of RFC 9380, K.3
+        let dst = b"QUUX-V01-CS02-with-expander-SHA512-256";
+        let mut uniform = [0u8; 64];
+        uniform.copy_from_slice(&curve_traits::expand_message_xmd(b"abc", dst, 64).unwrap());
+        assert_eq!(
+            map_to_group(&uniform),
+            GE::hash_to_group(b"abc", dst).unwrap()
+        );
+    }
+}
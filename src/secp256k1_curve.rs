@@ -0,0 +1,545 @@
+//! Secp256k1 curve - an alternative backend producing keys usable with
+//! Bitcoin/Ethereum tooling. Enabled with the `secp256k1` feature.
+
+use crate::blake;
+use crate::curve_traits;
+use crate::curve_traits::{CurvePoint, ECPoint, ECScalar};
+use std::convert::From;
+use std::error::Error;
+use std::fmt;
+
+use hex::{decode, encode};
+use k256::elliptic_curve::ff::Field;
+use k256::elliptic_curve::group::{Curve, Group};
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar};
+use num_bigint::BigUint;
+use rand_core::{CryptoRng, RngCore};
+use serde::de;
+use serde::de::Visitor;
+use serde::ser::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+
+pub const SECRET_KEY_SIZE: usize = 32;
+
+// 5^((n-1)/2^6) with the non-residue 5, a primitive 2^6-th root of unity of
+// the scalar field, big endian
+const ROOT_OF_UNITY: &str = "0d1f8eab98dcd1aca7dc810e065710cbb96e9abebbe451fa15b4f83d2d2ad232";
+/// Size of a point in SEC1 compressed form
+pub const PUBLIC_KEY_SIZE: usize = 33;
+
+// secp256k1_XMD:SHA-256_SSWU_RO_ of RFC 9380: the simplified SWU map to the
+// curve y^2 = x^3 + ISO_A * x + ISO_B, followed by its 3-isogeny to
+// secp256k1, with Z = -SSWU_Z. Big endian field elements.
+const FIELD_MODULUS: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+const ISO_A: &str = "3f8731abdd661adca08a5558f0f5d272e953d363cb6f0e5d405447c01a444533";
+const ISO_B: u32 = 1771;
+const SSWU_Z: u32 = 11;
+// coefficients of the numerators and monic denominators of the isogeny, by
+// increasing degree, RFC 9380 appendix E.1
+const ISO_X_NUM: [&str; 4] = [
+    "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa8c7",
+    "07d3d4c80bc321d5b9f315cea7fd44c5d595d2fc0bf63b92dfff1044f17c6581",
+    "534c328d23f234e6e2a413deca25caece4506144037c40314ecbd0b53d9dd262",
+    "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa88c",
+];
+const ISO_X_DEN: [&str; 2] = [
+    "d35771193d94918a9ca34ccbb7b640dd86cd409542f8487d9fe6b745781eb49b",
+    "edadc6f64383dc1df7c4b2d51b54225406d36b641f5e41bbc52a56612a8c6d14",
+];
+const ISO_Y_NUM: [&str; 4] = [
+    "4bda12f684bda12f684bda12f684bda12f684bda12f684bda12f684b8e38e23c",
+    "c75e0c32d5cb7c0fa9d0a54b12a0a6d5647ab046d686da6fdffc90fc201d71a3",
+    "29a6194691f91a73715209ef6512e576722830a201be2018a765e85a9ecee931",
+    "2f684bda12f684bda12f684bda12f684bda12f684bda12f684bda12f38e38d84",
+];
+const ISO_Y_DEN: [&str; 3] = [
+    "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffff93b",
+    "7a06534bb8bdb49fd5e9e6632722c2989467c1bfc8e8d978dfb425d2685c2573",
+    "6484aa716545ca2cf3a70c3fa8fe337e0a3d21162f0d6299a7bf8192bfd2a76f",
+];
+
+pub type SK = Scalar;
+pub type PK = ProjectivePoint;
+
+#[derive(Clone, Debug, Copy)]
+pub struct Secp256k1Scalar {
+    purpose: &'static str,
+    fe: SK,
+}
+#[derive(Clone, Debug, Copy)]
+pub struct Secp256k1Point {
+    purpose: &'static str,
+    ge: PK,
+}
+pub type GE = Secp256k1Point;
+pub type FE = Secp256k1Scalar;
+
+impl From<u64> for Secp256k1Scalar {
+    fn from(n: u64) -> Self {
+        Self {
+            purpose: "from_big_int",
+            fe: SK::from(n),
+        }
+    }
+}
+
+impl From<SK> for Secp256k1Scalar {
+    fn from(scalar: SK) -> Self {
+        Self {
+            purpose: "from_scalar",
+            fe: scalar,
+        }
+    }
+}
+
+impl ECScalar<SK> for Secp256k1Scalar {
+    fn new_random_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            purpose: "random",
+            fe: SK::random(rng),
+        }
+    }
+
+    fn zero() -> Self {
+        Self {
+            purpose: "zero",
+            fe: SK::zero(),
+        }
+    }
+
+    fn get_element(&self) -> SK {
+        self.fe
+    }
+    fn set_element(&mut self, element: SK) {
+        self.fe = element
+    }
+
+    fn to_hex(&self) -> String {
+        encode(self.to_bytes())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.fe.to_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        if bytes.len() != SECRET_KEY_SIZE {
+            bail!("Invalid scalar length")
+        }
+        Ok(Self {
+            purpose: "from_bytes",
+            fe: SK::from_bytes_reduced(FieldBytes::from_slice(bytes)),
+        })
+    }
+
+    fn add(&self, other: &SK) -> Self {
+        Self {
+            purpose: "add",
+            fe: self.get_element() + other,
+        }
+    }
+
+    fn mul(&self, other: &SK) -> Self {
+        Self {
+            purpose: "mul",
+            fe: self.get_element() * other,
+        }
+    }
+
+    fn sub(&self, other: &SK) -> Self {
+        Self {
+            purpose: "sub",
+            fe: self.get_element() - other,
+        }
+    }
+
+    fn invert(&self) -> Self {
+        // zero has no inverse, keep it as zero like the ristretto backend does
+        let inv: SK = Option::from(self.get_element().invert()).unwrap_or_else(SK::zero);
+        Self {
+            purpose: "invert",
+            fe: inv,
+        }
+    }
+}
+
+impl Default for Secp256k1Scalar {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl Serialize for Secp256k1Scalar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256k1Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Secp256k1Scalar, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(Secp256k1ScalarVisitor)
+    }
+}
+
+struct Secp256k1ScalarVisitor;
+
+impl<'de> Visitor<'de> for Secp256k1ScalarVisitor {
+    type Value = Secp256k1Scalar;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("secp256k1 scalar")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Secp256k1Scalar, E> {
+        let s_bytes: Vec<u8> = decode(s).map_err(E::custom)?;
+        Secp256k1Scalar::from_bytes(&s_bytes).map_err(E::custom)
+    }
+}
+
+impl PartialEq for Secp256k1Scalar {
+    fn eq(&self, other: &Secp256k1Scalar) -> bool {
+        self.get_element() == other.get_element()
+    }
+}
+
+impl ECPoint<PK, SK> for Secp256k1Point {
+    fn generator() -> Secp256k1Point {
+        Secp256k1Point {
+            purpose: "base_fe",
+            ge: PK::generator(),
+        }
+    }
+
+    /// The point in SEC1 compressed form. The identity, which has no such
+    /// encoding, is represented by zero bytes.
+    fn pk_to_key_slice(&self) -> Vec<u8> {
+        if bool::from(self.ge.is_identity()) {
+            return vec![0u8; PUBLIC_KEY_SIZE];
+        }
+        self.ge
+            .to_affine()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn get_element(&self) -> PK {
+        self.ge
+    }
+
+    fn to_hex(&self) -> String {
+        encode(self.pk_to_key_slice())
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Secp256k1Point, Box<dyn Error>> {
+        if bytes.len() != PUBLIC_KEY_SIZE {
+            bail!("Invalid Public Key length")
+        }
+        if bytes.iter().all(|b| *b == 0) {
+            return Ok(Secp256k1Point::identity());
+        }
+        let encoded =
+            EncodedPoint::from_bytes(bytes).map_err(|_| simple_error!("Invalid Public Key"))?;
+        let affine: Option<AffinePoint> = AffinePoint::from_encoded_point(&encoded).into();
+        let new_point = affine
+            .map(|x| Secp256k1Point {
+                purpose: "from_bytes",
+                ge: PK::from(x),
+            })
+            .ok_or_else(|| simple_error!("Invalid Public Key"))?;
+        Ok(new_point)
+    }
+
+    fn scalar_mul(&self, fe: &SK) -> Secp256k1Point {
+        Secp256k1Point {
+            purpose: "scalar_point_mul",
+            ge: self.ge * *fe,
+        }
+    }
+
+    fn add_point(&self, other: &PK) -> Secp256k1Point {
+        Secp256k1Point {
+            purpose: "combine",
+            ge: self.ge + *other,
+        }
+    }
+
+    fn sub_point(&self, other: &PK) -> Secp256k1Point {
+        Secp256k1Point {
+            purpose: "sub",
+            ge: self.ge - *other,
+        }
+    }
+}
+
+impl CurvePoint for Secp256k1Point {
+    type SK = SK;
+    type PK = PK;
+    type Scalar = Secp256k1Scalar;
+
+    const CURVE_ID: &'static str = "secp256k1";
+    const TWO_ADICITY: u32 = 6;
+
+    fn root_of_unity() -> Secp256k1Scalar {
+        Secp256k1Scalar::from_bytes(&decode(ROOT_OF_UNITY).unwrap()).unwrap()
+    }
+
+    fn identity() -> Secp256k1Point {
+        Secp256k1Point {
+            purpose: "identity",
+            ge: PK::identity(),
+        }
+    }
+
+    /// Try-and-increment: hash bytes with blake2xb and use the first 32 bytes
+    /// chunk of the output which is the x coordinate of a point on the curve
+    fn hash_to_point(bytes: &[u8]) -> Result<Secp256k1Point, Box<dyn Error>> {
+        let hash = blake::new_blake2xb(bytes.to_vec());
+        for value in hash {
+            for chunk in value.as_ref().chunks(32) {
+                let mut candidate: Vec<u8> = vec![0x02];
+                candidate.extend_from_slice(chunk);
+                if let Ok(point) = Secp256k1Point::from_bytes(&candidate) {
+                    return Ok(point);
+                }
+            }
+        }
+        bail!("Error hash")
+    }
+
+    const HASH_TO_GROUP_SUITE: &'static str = "secp256k1_XMD:SHA-256_SSWU_RO_";
+
+    /// hash_to_curve of RFC 9380 with expand_message_xmd over SHA-256 and the
+    /// simplified SWU map. It isn't constant time, which suits the public
+    /// inputs it is used for.
+    fn hash_to_group(msg: &[u8], dst: &[u8]) -> Result<Secp256k1Point, Box<dyn Error>> {
+        let p = field_element(FIELD_MODULUS);
+        let uniform: Vec<u8> = curve_traits::expand_message_xmd_sha256(msg, dst, 96)?;
+        let q0 = map_to_curve(&(BigUint::from_bytes_be(&uniform[..48]) % &p))?;
+        let q1 = map_to_curve(&(BigUint::from_bytes_be(&uniform[48..]) % &p))?;
+        Ok(q0.add_point(&q1.get_element()))
+    }
+
+    fn hash_to_scalar(bytes: &[u8]) -> Secp256k1Scalar {
+        let digest = Sha256::digest(bytes);
+        Secp256k1Scalar {
+            purpose: "from_hash",
+            fe: SK::from_bytes_reduced(FieldBytes::from_slice(digest.as_slice())),
+        }
+    }
+}
+
+fn field_element(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).expect("Invalid field element")
+}
+
+// map_to_curve maps the field element u to secp256k1 with the simplified SWU
+// map and the isogeny, RFC 9380 sections 6.6.2 and 6.6.3.
+fn map_to_curve(u: &BigUint) -> Result<Secp256k1Point, Box<dyn Error>> {
+    let p = field_element(FIELD_MODULUS);
+    let inv = |x: &BigUint| x.modpow(&(&p - 2u32), &p);
+    let is_square = |x: &BigUint| x.modpow(&((&p - 1u32) >> 1), &p) != &p - 1u32;
+    let a = field_element(ISO_A);
+    let b = BigUint::from(ISO_B);
+    let z = &p - SSWU_Z;
+    let g = |x: &BigUint| (x * x * x + &a * x + &b) % &p;
+
+    let u2 = u * u % &p;
+    let tv1 = (&z * &z * &u2 * &u2 + &z * &u2) % &p;
+    let x1 = if tv1 == BigUint::from(0u32) {
+        &b * inv(&(&z * &a % &p)) % &p
+    } else {
+        (&p - &b) * inv(&a) % &p * (inv(&tv1) + 1u32) % &p
+    };
+    let gx1 = g(&x1);
+    let (x, gx) = if is_square(&gx1) {
+        (x1, gx1)
+    } else {
+        let x2 = &z * &u2 % &p * &x1 % &p;
+        let gx2 = g(&x2);
+        (x2, gx2)
+    };
+    // p = 3 mod 4
+    let mut y = gx.modpow(&((&p + 1u32) >> 2), &p);
+    if sgn0(&y) != sgn0(u) {
+        y = (&p - &y) % &p;
+    }
+
+    let eval = |coefficients: &[&str], monic: bool| -> BigUint {
+        let start = if monic {
+            BigUint::from(1u32)
+        } else {
+            BigUint::from(0u32)
+        };
+        coefficients
+            .iter()
+            .rev()
+            .fold(start, |acc, c| (acc * &x + field_element(c)) % &p)
+    };
+    let x_den = eval(&ISO_X_DEN, true);
+    let y_den = eval(&ISO_Y_DEN, true);
+    if x_den == BigUint::from(0u32) || y_den == BigUint::from(0u32) {
+        return Ok(Secp256k1Point::identity());
+    }
+    let x = eval(&ISO_X_NUM, false) * inv(&x_den) % &p;
+    let y = y * eval(&ISO_Y_NUM, false) % &p * inv(&y_den) % &p;
+
+    let mut bytes = vec![0u8; PUBLIC_KEY_SIZE];
+    bytes[0] = 0x02 | sgn0(&y);
+    let x = x.to_bytes_be();
+    bytes[PUBLIC_KEY_SIZE - x.len()..].copy_from_slice(&x);
+    Secp256k1Point::from_bytes(&bytes)
+}
+
+// sgn0 returns the parity of the field element.
+fn sgn0(x: &BigUint) -> u8 {
+    x.to_bytes_le()[0] & 1
+}
+
+impl Default for Secp256k1Point {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Serialize for Secp256k1Point {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256k1Point {
+    fn deserialize<D>(deserializer: D) -> Result<Secp256k1Point, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(Secp256k1PointVisitor)
+    }
+}
+
+struct Secp256k1PointVisitor;
+
+impl<'de> Visitor<'de> for Secp256k1PointVisitor {
+    type Value = Secp256k1Point;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Secp256k1Point")
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Secp256k1Point, E> {
+        let s_bytes: Vec<u8> = decode(s).map_err(E::custom)?;
+        Secp256k1Point::from_bytes(&s_bytes).map_err(E::custom)
+    }
+}
+
+impl PartialEq for Secp256k1Point {
+    fn eq(&self, other: &Secp256k1Point) -> bool {
+        self.pk_to_key_slice() == other.pk_to_key_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vss::{recover_secret, Deal, Dealer, EncryptedDeal, Response, Verifier};
+
+    fn gen_pair() -> (FE, GE) {
+        let secret: FE = ECScalar::new_random();
+        let public: GE = GE::generator().scalar_mul(&secret.get_element());
+        (secret, public)
+    }
+
+    #[test]
+    fn test_secp256k1_point_bytes() {
+        let (_, point) = gen_pair();
+        let bytes = point.pk_to_key_slice();
+        assert_eq!(PUBLIC_KEY_SIZE, bytes.len());
+        assert_eq!(point, GE::from_bytes(&bytes).unwrap());
+
+        let identity = GE::identity();
+        assert_eq!(
+            identity,
+            GE::from_bytes(&identity.pk_to_key_slice()).unwrap()
+        );
+        assert_eq!(point, point.add_point(&identity.get_element()));
+    }
+
+    #[test]
+    fn test_secp256k1_hash_to_point() {
+        let first = GE::hash_to_point(b"some bytes").unwrap();
+        let second = GE::hash_to_point(b"some bytes").unwrap();
+        let other = GE::hash_to_point(b"other bytes").unwrap();
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn test_secp256k1_hash_to_group() {
+        // RFC 9380, J.8.1 secp256k1_XMD:SHA-256_SSWU_RO_
+        let dst = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+        assert_eq!(
+            "03c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+            GE::hash_to_group(b"", dst).unwrap().to_hex()
+        );
+        assert_eq!(
+            "023377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+            GE::hash_to_group(b"abc", dst).unwrap().to_hex()
+        );
+    }
+
+    #[test]
+    fn test_secp256k1_signature() {
+        let (secret, public) = gen_pair();
+        let sig = GE::sign_msg(&secret, &public, b"message", b"context").unwrap();
+        GE::verify_signature(&public, &sig, b"message", b"context").unwrap();
+        GE::verify_signature(&public, &sig, b"other message", b"context").unwrap_err();
+    }
+
+    #[test]
+    fn test_secp256k1_vss_whole() {
+        let nb_verifiers: u32 = 7;
+        let threshold = crate::vss::minimum_t(nb_verifiers);
+        let (verifiers_sec, verifiers_pub): (Vec<FE>, Vec<GE>) =
+            (0..nb_verifiers).map(|_| gen_pair()).unzip();
+        let (dealer_sec, dealer_pub) = gen_pair();
+        let (secret, _) = gen_pair();
+
+        let mut dealer: Dealer<GE> =
+            Dealer::new(dealer_sec, secret, verifiers_pub.clone(), threshold).unwrap();
+        let mut verifiers: Vec<Verifier<GE>> = verifiers_sec
+            .iter()
+            .map(|sec| Verifier::new(*sec, dealer_pub, verifiers_pub.clone()).unwrap())
+            .collect();
+
+        let enc_deals: Vec<EncryptedDeal<GE>> = dealer.encrypt_deals().unwrap();
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(deal, v)| v.process_encrypted_deal(deal).unwrap())
+            .collect();
+
+        for resp in resps.iter() {
+            for (i, v) in verifiers.iter_mut().enumerate() {
+                if resp.index != i as u32 {
+                    v.process_response(resp).unwrap();
+                }
+            }
+            assert!(dealer.process_response(resp).unwrap().is_none());
+        }
+
+        let deals: Vec<Deal<GE>> = verifiers.iter().map(|v| v.get_deal().unwrap()).collect();
+        let recovered: FE = recover_secret(&deals, threshold).unwrap();
+        assert_eq!(secret, recovered);
+    }
+}
@@ -0,0 +1,190 @@
+//! Serde representation of byte strings as hex strings in human readable
+//! formats, e.g. JSON for web clients, used with `#[serde(with = "...")]` on
+//! the byte fields of the protocol messages. Points and scalars are already
+//! serialized as hex strings by the curve backends.
+//!
+//! Binary formats write byte strings, e.g. a CBOR byte string, and bincode
+//! keeps the encoding of a plain `Vec<u8>`, so that the wire format of the
+//! messages doesn't change.
+
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use serde::de::{Error as _, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Bytes serializes a byte string as a hex string or as bytes.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(self.0))
+        } else {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
+
+// ByteBuf deserializes a byte string serialized by Bytes.
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ByteBuf, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            hex::decode(&s).map(ByteBuf).map_err(D::Error::custom)
+        } else {
+            deserializer.deserialize_byte_buf(ByteBufVisitor)
+        }
+    }
+}
+
+struct ByteBufVisitor;
+
+impl<'de> Visitor<'de> for ByteBufVisitor {
+    type Value = ByteBuf;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a byte string")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(v))
+    }
+
+    // formats without byte strings give a sequence of u8
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(ByteBuf(bytes))
+    }
+}
+
+/// serialize writes the bytes as a hex string in human readable formats.
+pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    Bytes(bytes).serialize(serializer)
+}
+
+/// deserialize reads the bytes written by serialize.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    Ok(ByteBuf::deserialize(deserializer)?.0)
+}
+
+/// list is the representation of a list of byte strings, as a list of hex
+/// strings in human readable formats.
+pub mod list {
+    use super::{ByteBuf, Bytes};
+    #[cfg(not(feature = "std"))]
+    use crate::prelude::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(list: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error> {
+        let list: Vec<Bytes> = list.iter().map(|b| Bytes(b)).collect();
+        list.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<u8>>, D::Error> {
+        let list: Vec<ByteBuf> = Vec::deserialize(deserializer)?;
+        Ok(list.into_iter().map(|b| b.0).collect())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::curve_traits::{ECPoint, ECScalar};
+    use crate::poly::PriShare;
+    use crate::ristretto_curve::{FE, GE};
+    use crate::vss::{Deal, EncryptedDeal, Justification, Response};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_json_schema() {
+        let resp = Response {
+            session_id: vec![0xab; 32],
+            index: 3,
+            approved: true,
+            signature: vec![0x01, 0x02],
+            reason: None,
+        };
+        let value = serde_json::to_value(&resp).unwrap();
+        assert_eq!(
+            json!({
+                "session_id": "ab".repeat(32),
+                "index": 3,
+                "approved": true,
+                "signature": "0102",
+                "reason": null,
+            }),
+            value
+        );
+        assert_eq!(resp, serde_json::from_value(value).unwrap());
+        // the binary encoding is the one of the plain byte vectors
+        assert_eq!(
+            bincode::serialize(&(vec![0xabu8; 32], 3u32, true, vec![1u8, 2], None::<u32>)).unwrap(),
+            bincode::serialize(&resp).unwrap()
+        );
+
+        let share: FE = ECScalar::new_random();
+        let deal: Deal<GE> = Deal {
+            session_id: vec![0xab; 32],
+            sec_share: PriShare { i: 1, v: share },
+            rnd_share: PriShare { i: 1, v: share },
+            t: 2,
+            commitments: vec![vec![0x01; 32], vec![0x02; 32]],
+            context: b"ctx".to_vec(),
+            commitments_root: Vec::new(),
+        };
+        let value = serde_json::to_value(&deal).unwrap();
+        assert_eq!(
+            json!(["01".repeat(32), "02".repeat(32)]),
+            value["commitments"]
+        );
+        assert_eq!(json!("637478"), value["context"]);
+        assert_eq!(json!(share.to_hex()), value["sec_share"]["v"]);
+        assert_eq!(deal, serde_json::from_value(value).unwrap());
+
+        let justification = Justification {
+            session_id: deal.session_id.clone(),
+            index: 1,
+            deal,
+            signature: vec![0x03; 64],
+        };
+        let value = serde_json::to_value(&justification).unwrap();
+        assert_eq!(json!("03".repeat(64)), value["signature"]);
+        assert_eq!(justification, serde_json::from_value(value).unwrap());
+
+        let encrypted: EncryptedDeal<GE> = EncryptedDeal {
+            version: 1,
+            session_id: vec![0xab; 32],
+            dh_key: ECPoint::generator(),
+            signature: vec![0x04; 64],
+            nonce: vec![0x05; 12],
+            cipher: vec![0x06; 48],
+            aead: Default::default(),
+            mode: Default::default(),
+        };
+        let value = serde_json::to_value(&encrypted).unwrap();
+        assert_eq!(json!("05".repeat(12)), value["nonce"]);
+        assert_eq!(json!("06".repeat(48)), value["cipher"]);
+        assert!(matches!(value["dh_key"], Value::String(_)));
+        assert_eq!(encrypted, serde_json::from_value(value).unwrap());
+
+        serde_json::from_value::<Response>(json!({
+            "session_id": "not hex",
+            "index": 3,
+            "approved": true,
+            "signature": "",
+        }))
+        .expect_err("Must fail, invalid hex");
+    }
+}
@@ -0,0 +1,484 @@
+//! Versioned on-disk format of the share of a distributed key, encrypted at
+//! rest under a passphrase. Enabled with the `share-file` feature.
+//!
+//! The file is a JSON document holding in clear the index of the share, the
+//! distributed public key and the commitments of the public polynomial, and the
+//! private share encrypted with ChaCha20Poly1305 under a key derived from the
+//! passphrase with Argon2id. The clear fields and the parameters of Argon2 are
+//! authenticated as the associated data of the encryption, and a decrypted
+//! share is checked against the commitments.
+//!
+//! The whole state of a Dealer, Verifier or DistKeyGenerator in the middle of
+//! a ceremony, secrets included, is exported the same way with
+//! ExportState::export_state, to migrate a node to new hardware. The
+//! StateFile holds the storage::Snapshot of the state encrypted under the
+//! passphrase, along with the kind of the state and its curve.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::storage;
+use crate::utils;
+use crate::vss;
+
+use aead::{generic_array::GenericArray, Aead, NewAead, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use curve_traits::{CurvePoint, ECScalar};
+use dkg::DistKeyShare;
+use encoding::Encoder;
+use poly::PriShare;
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use storage::Snapshot;
+
+/// Version of the share file format. Files of any other version are rejected.
+pub const SHARE_FILE_VERSION: u32 = 1;
+
+/// Version of the state file format. Files of any other version are rejected.
+pub const STATE_FILE_VERSION: u32 = 1;
+
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+/// Maximum parameters of Argon2id, four times the default ones, so that a
+/// crafted file can't make the key derivation exhaust the memory or the CPU
+/// before anything is authenticated.
+pub const MAX_M_COST: u32 = 4 * 65536;
+pub const MAX_T_COST: u32 = 4 * 3;
+pub const MAX_P_COST: u32 = 4;
+
+/// KdfParams are the parameters of Argon2id deriving the key of a share file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    // memory in KiB
+    pub m_cost: u32,
+    // number of passes
+    pub t_cost: u32,
+    // degree of parallelism
+    pub p_cost: u32,
+    pub salt: Vec<u8>,
+}
+
+impl Default for KdfParams {
+    fn default() -> KdfParams {
+        KdfParams {
+            m_cost: 65536,
+            t_cost: 3,
+            p_cost: 1,
+            salt: Vec::new(),
+        }
+    }
+}
+
+impl KdfParams {
+    // derive_key derives the encryption key from the passphrase, with
+    // parameters of at most MAX_M_COST, MAX_T_COST and MAX_P_COST.
+    fn derive_key(&self, passphrase: &[u8]) -> Result<[u8; 32], Box<dyn Error>> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            bail!(
+                "sharefile: argon2 parameters m_cost {}, t_cost {}, p_cost {} over the limits",
+                self.m_cost,
+                self.t_cost,
+                self.p_cost
+            );
+        }
+        let config = argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.m_cost,
+            time_cost: self.t_cost,
+            lanes: self.p_cost,
+            thread_mode: argon2::ThreadMode::Sequential,
+            hash_length: 32,
+            ..Default::default()
+        };
+        let key = argon2::hash_raw(passphrase, &self.salt, &config)
+            .map_err(|e| simple_error!("sharefile: key derivation failure: {}", e))?;
+        utils::arr32_from_slice(&key)
+    }
+}
+
+/// ShareFile is the on-disk format of the share of a distributed key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ShareFile<P: CurvePoint = GE> {
+    pub version: u32,
+    // CurvePoint::CURVE_ID of the curve of the key
+    pub curve: String,
+    // index of the share
+    pub index: u32,
+    // distributed public key
+    pub public_key: P,
+    // commitments of the public polynomial of the key
+    pub commitments: Vec<P>,
+    pub kdf: KdfParams,
+    pub nonce: Vec<u8>,
+    // encryption of the private share
+    pub cipher: Vec<u8>,
+}
+
+impl<P: CurvePoint> ShareFile<P> {
+    /// encrypt returns the share file of the share, encrypted under the
+    /// passphrase with the default parameters of Argon2id.
+    pub fn encrypt(
+        share: &DistKeyShare<P>,
+        passphrase: &[u8],
+    ) -> Result<ShareFile<P>, Box<dyn Error>> {
+        ShareFile::encrypt_with_params(
+            share,
+            passphrase,
+            KdfParams::default(),
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as encrypt, with the given parameters of Argon2id and the salt and
+    /// nonce drawn from rng.
+    pub fn encrypt_with_params<R: RngCore + CryptoRng>(
+        share: &DistKeyShare<P>,
+        passphrase: &[u8],
+        mut kdf: KdfParams,
+        rng: &mut R,
+    ) -> Result<ShareFile<P>, Box<dyn Error>> {
+        kdf.salt = vec![0u8; SALT_SIZE];
+        rng.fill_bytes(&mut kdf.salt);
+        let mut nonce = vec![0u8; NONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+        let pri_share: PriShare<P::Scalar> = share.get_pri_share();
+        let mut file = ShareFile {
+            version: SHARE_FILE_VERSION,
+            curve: P::CURVE_ID.to_string(),
+            index: pri_share.i,
+            public_key: share.get_public_key(),
+            commitments: share.get_commitments().to_vec(),
+            kdf,
+            nonce,
+            cipher: Vec::new(),
+        };
+        let key = file.kdf.derive_key(passphrase)?;
+        file.cipher = ChaCha20Poly1305::new(GenericArray::clone_from_slice(&key))
+            .encrypt(
+                GenericArray::from_slice(&file.nonce),
+                Payload {
+                    msg: &pri_share.v.to_bytes(),
+                    aad: &file.aad(),
+                },
+            )
+            .map_err(|_| simple_error!("sharefile: encryption failure!"))?;
+        Ok(file)
+    }
+
+    /// decrypt returns the share of the file, checked against its commitments.
+    /// It returns an error if the passphrase is wrong or the file altered.
+    pub fn decrypt(&self, passphrase: &[u8]) -> Result<DistKeyShare<P>, Box<dyn Error>> {
+        if self.version != SHARE_FILE_VERSION {
+            bail!("sharefile: unsupported version {}", self.version);
+        }
+        if self.curve != P::CURVE_ID {
+            bail!(
+                "sharefile: share over {} instead of {}",
+                self.curve,
+                P::CURVE_ID
+            );
+        }
+        if self.nonce.len() != NONCE_SIZE {
+            bail!("sharefile: invalid nonce size");
+        }
+        if self.commitments.first() != Some(&self.public_key) {
+            bail!("sharefile: public key not matching the commitments");
+        }
+        let key = self.kdf.derive_key(passphrase)?;
+        let plain = ChaCha20Poly1305::new(GenericArray::clone_from_slice(&key))
+            .decrypt(
+                GenericArray::from_slice(&self.nonce),
+                Payload {
+                    msg: &self.cipher,
+                    aad: &self.aad(),
+                },
+            )
+            .map_err(|_| simple_error!("sharefile: wrong passphrase or altered file"))?;
+        let share = PriShare {
+            i: self.index,
+            v: P::Scalar::from_bytes(&plain)?,
+        };
+        let share = DistKeyShare::new(self.commitments.clone(), share);
+        if !share.pub_poly().check(&share.get_pri_share()) {
+            bail!("sharefile: share not matching the commitments");
+        }
+        Ok(share)
+    }
+
+    // aad returns the associated data of the encryption, binding the clear
+    // fields.
+    fn aad(&self) -> Vec<u8> {
+        let commitments: Vec<Vec<u8>> = self.commitments.iter().map(P::pk_to_key_slice).collect();
+        Encoder::new(b"sharefile")
+            .put_u32(self.version)
+            .put_bytes(self.curve.as_bytes())
+            .put_u32(self.index)
+            .put_bytes(&self.public_key.pk_to_key_slice())
+            .put_list(&commitments)
+            .put_u32(self.kdf.m_cost)
+            .put_u32(self.kdf.t_cost)
+            .put_u32(self.kdf.p_cost)
+            .put_bytes(&self.kdf.salt)
+            .into_bytes()
+    }
+
+    /// save writes the share file to path.
+    pub fn save<A: AsRef<Path>>(&self, path: A) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// load reads a share file from path.
+    pub fn load<A: AsRef<Path>>(path: A) -> Result<ShareFile<P>, Box<dyn Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+/// save_share encrypts the share under the passphrase and writes it to path.
+pub fn save_share<P: CurvePoint, A: AsRef<Path>>(
+    path: A,
+    share: &DistKeyShare<P>,
+    passphrase: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    ShareFile::encrypt(share, passphrase)?.save(path)
+}
+
+/// load_share reads the share file at path and decrypts it with the
+/// passphrase.
+pub fn load_share<P: CurvePoint, A: AsRef<Path>>(
+    path: A,
+    passphrase: &[u8],
+) -> Result<DistKeyShare<P>, Box<dyn Error>> {
+    ShareFile::load(path)?.decrypt(passphrase)
+}
+
+/// StateFile is the exported state of a protocol, see ExportState.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateFile {
+    pub version: u32,
+    // ExportState::KIND of the state
+    pub kind: String,
+    // CurvePoint::CURVE_ID of the curve of the state
+    pub curve: String,
+    pub kdf: KdfParams,
+    pub nonce: Vec<u8>,
+    // encryption of the snapshot of the state
+    pub cipher: Vec<u8>,
+}
+
+impl StateFile {
+    // aad returns the associated data of the encryption, binding the clear
+    // fields.
+    fn aad(&self) -> Vec<u8> {
+        Encoder::new(b"statefile")
+            .put_u32(self.version)
+            .put_bytes(self.kind.as_bytes())
+            .put_bytes(self.curve.as_bytes())
+            .put_u32(self.kdf.m_cost)
+            .put_u32(self.kdf.t_cost)
+            .put_u32(self.kdf.p_cost)
+            .put_bytes(&self.kdf.salt)
+            .into_bytes()
+    }
+}
+
+/// ExportState is implemented by the protocol states which can be exported
+/// to another node in an encrypted StateFile. The deadlines and the
+/// certification policy aren't part of the state, and must be set again once
+/// imported.
+pub trait ExportState: Snapshot {
+    /// KIND names the state, so that a state isn't imported as another one.
+    const KIND: &'static str;
+    /// CURVE_ID is the CurvePoint::CURVE_ID of the curve of the state.
+    const CURVE_ID: &'static str;
+
+    /// export_state returns the StateFile of the state encrypted under the
+    /// passphrase with the default parameters of Argon2id, encoded in JSON.
+    fn export_state(&self, passphrase: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.export_state_with_params(passphrase, KdfParams::default(), &mut utils::rand_hack())
+    }
+
+    /// Same as export_state, with the given parameters of Argon2id and the
+    /// salt and nonce drawn from rng.
+    fn export_state_with_params<R: RngCore + CryptoRng>(
+        &self,
+        passphrase: &[u8],
+        mut kdf: KdfParams,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        kdf.salt = vec![0u8; SALT_SIZE];
+        rng.fill_bytes(&mut kdf.salt);
+        let mut nonce = vec![0u8; NONCE_SIZE];
+        rng.fill_bytes(&mut nonce);
+        let mut file = StateFile {
+            version: STATE_FILE_VERSION,
+            kind: Self::KIND.to_string(),
+            curve: Self::CURVE_ID.to_string(),
+            kdf,
+            nonce,
+            cipher: Vec::new(),
+        };
+        let key = file.kdf.derive_key(passphrase)?;
+        file.cipher = ChaCha20Poly1305::new(GenericArray::clone_from_slice(&key))
+            .encrypt(
+                GenericArray::from_slice(&file.nonce),
+                Payload {
+                    msg: &self.snapshot()?,
+                    aad: &file.aad(),
+                },
+            )
+            .map_err(|_| simple_error!("sharefile: encryption failure!"))?;
+        Ok(serde_json::to_vec(&file)?)
+    }
+
+    /// import_state returns the state exported in the blob. It returns an
+    /// error if the passphrase is wrong, the blob altered, or if it holds
+    /// another kind of state or a state over another curve.
+    fn import_state(blob: &[u8], passphrase: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let file: StateFile = serde_json::from_slice(blob)?;
+        if file.version != STATE_FILE_VERSION {
+            bail!("sharefile: unsupported state version {}", file.version);
+        }
+        if file.kind != Self::KIND || file.curve != Self::CURVE_ID {
+            bail!(
+                "sharefile: {} state over {} instead of {} over {}",
+                file.kind,
+                file.curve,
+                Self::KIND,
+                Self::CURVE_ID
+            );
+        }
+        if file.nonce.len() != NONCE_SIZE {
+            bail!("sharefile: invalid nonce size");
+        }
+        let key = file.kdf.derive_key(passphrase)?;
+        let snapshot = ChaCha20Poly1305::new(GenericArray::clone_from_slice(&key))
+            .decrypt(
+                GenericArray::from_slice(&file.nonce),
+                Payload {
+                    msg: &file.cipher,
+                    aad: &file.aad(),
+                },
+            )
+            .map_err(|_| simple_error!("sharefile: wrong passphrase or altered state"))?;
+        Self::restore(&snapshot)
+    }
+}
+
+impl<P: CurvePoint> ExportState for vss::Dealer<P> {
+    const KIND: &'static str = "vss-dealer";
+    const CURVE_ID: &'static str = P::CURVE_ID;
+}
+
+impl<P: CurvePoint> ExportState for vss::Verifier<P> {
+    const KIND: &'static str = "vss-verifier";
+    const CURVE_ID: &'static str = P::CURVE_ID;
+}
+
+impl<P: CurvePoint> ExportState for dkg::DistKeyGenerator<P> {
+    const KIND: &'static str = "dkg";
+    const CURVE_ID: &'static str = P::CURVE_ID;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poly::PriPoly;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_state_file() {
+        let (secs, pubs) = crate::sim::new_participants::<GE>(4);
+        let node: dkg::DistKeyGenerator =
+            dkg::DistKeyGenerator::new(secs[0], pubs.clone(), 3).unwrap();
+        let kdf = KdfParams {
+            m_cost: 64,
+            t_cost: 1,
+            ..Default::default()
+        };
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let blob = node
+            .export_state_with_params(b"passphrase", kdf.clone(), &mut rng)
+            .unwrap();
+        let imported = dkg::DistKeyGenerator::<GE>::import_state(&blob, b"passphrase").unwrap();
+        assert_eq!(node.index(), imported.index());
+        assert_eq!(node.participants(), imported.participants());
+
+        dkg::DistKeyGenerator::<GE>::import_state(&blob, b"wrong passphrase")
+            .expect_err("Must fail, wrong passphrase");
+        vss::Dealer::<GE>::import_state(&blob, b"passphrase").expect_err("Must fail, not a dealer");
+        let mut file: StateFile = serde_json::from_slice(&blob).unwrap();
+        file.kdf.t_cost = 2;
+        dkg::DistKeyGenerator::<GE>::import_state(
+            &serde_json::to_vec(&file).unwrap(),
+            b"passphrase",
+        )
+        .expect_err("Must fail, altered parameters");
+        let mut file: StateFile = serde_json::from_slice(&blob).unwrap();
+        file.kdf.m_cost = u32::MAX;
+        let err = dkg::DistKeyGenerator::<GE>::import_state(
+            &serde_json::to_vec(&file).unwrap(),
+            b"passphrase",
+        )
+        .expect_err("Must fail, oversized m_cost");
+        assert!(err.to_string().contains("over the limits"));
+
+        let dealer: vss::Dealer = vss::Dealer::new(secs[0], secs[1], pubs, 3).unwrap();
+        let blob = dealer
+            .export_state_with_params(b"passphrase", kdf, &mut rng)
+            .unwrap();
+        let imported = vss::Dealer::<GE>::import_state(&blob, b"passphrase").unwrap();
+        assert_eq!(
+            dealer.secret_commit().unwrap(),
+            imported.secret_commit().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_share_file() {
+        let poly: PriPoly<GE> = PriPoly::new(3, None);
+        let (_, commits) = poly.commit(None).info();
+        let share: DistKeyShare = DistKeyShare::new(commits, poly.eval(2));
+        let kdf = KdfParams {
+            m_cost: 64,
+            t_cost: 1,
+            ..Default::default()
+        };
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let file = ShareFile::encrypt_with_params(&share, b"passphrase", kdf, &mut rng).unwrap();
+        assert_eq!(2, file.index);
+
+        let path = std::env::temp_dir().join("threshold-dkg-test-share.json");
+        file.save(&path).unwrap();
+        let loaded: ShareFile = ShareFile::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let decrypted = loaded.decrypt(b"passphrase").unwrap();
+        assert_eq!(share.get_pri_share(), decrypted.get_pri_share());
+        assert_eq!(share.get_commitments(), decrypted.get_commitments());
+
+        loaded
+            .decrypt(b"wrong passphrase")
+            .expect_err("Must fail, wrong passphrase");
+        let mut altered = loaded.clone();
+        altered.index = 1;
+        altered
+            .decrypt(b"passphrase")
+            .expect_err("Must fail, altered index");
+        let mut altered = loaded;
+        altered.curve = "secp256k1".to_string();
+        altered
+            .decrypt(b"passphrase")
+            .expect_err("Must fail, other curve");
+    }
+}
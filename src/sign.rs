@@ -1,16 +1,26 @@
 //! Helper functions to sign and verify signatures
 //! using Schnorr signature algorithm
+//!
+//! The schnorrkel signing context of the protocol messages is given by
+//! encoding::Domain::signing_context, which binds the signatures of a
+//! deployment to its domain.
 
-use std::error::Error;
-
+#[cfg(feature = "std")]
 use curve25519_dalek::scalar::Scalar;
 
-use crate::curve_traits::ECPoint;
+use crate::curve_traits::{CurvePoint, ECPoint, ECScalar};
+use crate::error::Error;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+#[cfg(feature = "std")]
 use crate::ristretto_curve::{FE, GE};
+#[cfg(feature = "std")]
 use crate::utils;
 
 use schnorrkel::keys::PublicKey as schnrPubKey;
 use schnorrkel::sign::Signature as schnrSig;
+#[cfg(feature = "std")]
+use schnorrkel::signing_context;
 use schnorrkel::SignatureError;
 
 pub fn verify_signature(
@@ -25,6 +35,47 @@ pub fn verify_signature(
     Ok(())
 }
 
+/// verify_batch checks the schnorrkel signatures sigs[i] of msgs[i] under
+/// pubs[i] in the signing contexts contexts[i] with one batch verification.
+/// If the batch fails, the signatures are verified one at a time and the
+/// positions of the invalid ones are returned.
+#[cfg(feature = "std")]
+pub fn verify_batch(
+    msgs: &[&[u8]],
+    sigs: &[&[u8]],
+    pubs: &[&[u8]],
+    contexts: &[&[u8]],
+) -> Result<(), Vec<usize>> {
+    let n = msgs.len();
+    if sigs.len() != n || pubs.len() != n || contexts.len() != n {
+        let len = n.max(sigs.len()).max(pubs.len()).max(contexts.len());
+        return Err((0..len).collect());
+    }
+    let keys: Result<Vec<schnrPubKey>, SignatureError> =
+        pubs.iter().map(|p| schnrPubKey::from_bytes(p)).collect();
+    let signatures: Result<Vec<schnrSig>, SignatureError> =
+        sigs.iter().map(|s| schnrSig::from_bytes(s)).collect();
+    if let (Ok(keys), Ok(signatures)) = (keys, signatures) {
+        let transcripts = msgs
+            .iter()
+            .zip(contexts.iter())
+            .map(|(msg, context)| signing_context(context).bytes(msg));
+        if schnorrkel::verify_batch(transcripts, &signatures, &keys, false).is_ok() {
+            return Ok(());
+        }
+    }
+    // fallback identifying the invalid signatures
+    let failed: Vec<usize> = (0..n)
+        .filter(|&i| verify_signature(pubs[i], sigs[i], msgs[i], contexts[i]).is_err())
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+#[cfg(feature = "std")]
 pub fn sign_msg(
     scalar: [u8; 32],
     point: [u8; 32],
@@ -42,3 +93,65 @@ pub fn sign_msg(
 
     Ok(signature)
 }
+
+/// schnorr_sign computes a Schnorr signature R || s, with s = k + e * secret
+/// and e = H(context, R, public, msg). It is the signature scheme used by curve
+/// backends which do not provide their own.
+#[cfg(feature = "std")]
+pub fn schnorr_sign<P: CurvePoint>(
+    secret: &P::Scalar,
+    public: &P,
+    msg: &[u8],
+    context: &[u8],
+) -> Vec<u8> {
+    let k: P::Scalar = ECScalar::new_random();
+    let r: P = P::base_mul(&k);
+    let r_buff: Vec<u8> = r.pk_to_key_slice();
+
+    let e: P::Scalar = schnorr_challenge(&r_buff, public, msg, context);
+    let s: P::Scalar = e.mul(&secret.get_element()).add(&k.get_element());
+
+    [r_buff, s.to_bytes()].concat()
+}
+
+/// schnorr_verify checks a signature created by schnorr_sign.
+pub fn schnorr_verify<P: CurvePoint>(
+    public: &P,
+    signature: &[u8],
+    msg: &[u8],
+    context: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    let point_len = public.pk_to_key_slice().len();
+    if signature.len() <= point_len {
+        bail!("sign: signature too short")
+    }
+
+    let r: P = P::from_bytes(&signature[..point_len])?;
+    let s: P::Scalar = P::Scalar::from_bytes(&signature[point_len..])?;
+    // from_bytes reduces s: s + l would be another valid signature
+    if s.to_bytes()[..] != signature[point_len..] {
+        bail!("sign: non-canonical signature")
+    }
+    let e: P::Scalar = schnorr_challenge(&signature[..point_len], public, msg, context);
+
+    // s * G == R + e * public
+    let left: P = P::base_mul(&s);
+    let right: P = public
+        .scalar_mul(&e.get_element())
+        .add_point(&r.get_element());
+    if left != right {
+        bail!("sign: invalid signature")
+    }
+
+    Ok(())
+}
+
+fn schnorr_challenge<P: CurvePoint>(r: &[u8], public: &P, msg: &[u8], context: &[u8]) -> P::Scalar {
+    let mut buff: Vec<u8> = Vec::new();
+    buff.extend_from_slice(&(context.len() as u64).to_le_bytes());
+    buff.extend_from_slice(context);
+    buff.extend_from_slice(r);
+    buff.extend_from_slice(&public.pk_to_key_slice());
+    buff.extend_from_slice(msg);
+    P::hash_to_scalar(&buff)
+}
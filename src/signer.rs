@@ -0,0 +1,297 @@
+//! Longterm keys of the protocol participants
+//!
+//! The vss Dealer and Verifier only use their longterm private key through the
+//! Signer and KeyAgreement traits, so that it can live outside of the process
+//! memory, e.g. in an HSM behind PKCS#11 or in a KMS. SoftwareSigner is the
+//! default implementation, holding the private key in memory.
+//!
+//! A participant may also hold distinct signing and encryption keys, as
+//! required by key-separation policies: its ParticipantIdentity is the pair of
+//! public keys, and IdentityKeys signs with one private key and decrypts with
+//! the other.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::curve_traits;
+use crate::dh;
+use crate::dleq;
+use crate::ristretto_curve;
+
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use dleq::DleqProof;
+use ristretto_curve::GE;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Signer signs messages with a longterm private key.
+pub trait Signer<P: CurvePoint = GE> {
+    /// public returns the public key.
+    fn public(&self) -> P;
+
+    /// sign returns the signature of msg in the given context, to be verified
+    /// with P::verify_signature.
+    fn sign(&self, msg: &[u8], context: &[u8]) -> Result<Vec<u8>, Box<dyn Error>>;
+}
+
+/// KeyAgreement computes Diffie-Hellman shared keys with a longterm private
+/// key.
+pub trait KeyAgreement<P: CurvePoint = GE> {
+    /// dh returns the shared key of the private key with the public key peer.
+    fn dh(&self, peer: &P) -> Result<P, Box<dyn Error>>;
+
+    /// prove_dh returns the shared key with peer along with a DleqProof that
+    /// it has the same discrete logarithm with respect to peer as the public
+    /// key with respect to the generator. It is only needed to issue vss
+    /// complaints; a key which can't produce the proof returns an error.
+    fn prove_dh(&self, peer: &P) -> Result<(P, DleqProof<P>), Box<dyn Error>>;
+}
+
+/// SoftwareSigner holds the longterm private key in memory. It serializes as
+/// the private key alone.
+#[derive(Clone)]
+pub struct SoftwareSigner<P: CurvePoint = GE> {
+    secret: P::Scalar,
+    public: P,
+}
+
+impl<P: CurvePoint> SoftwareSigner<P> {
+    pub fn new(secret: P::Scalar) -> SoftwareSigner<P> {
+        let public: P = P::generator().scalar_mul(&secret.get_element());
+        SoftwareSigner { secret, public }
+    }
+
+    /// secret returns the private key.
+    pub fn secret(&self) -> P::Scalar {
+        self.secret
+    }
+}
+
+impl<P: CurvePoint> Signer<P> for SoftwareSigner<P> {
+    fn public(&self) -> P {
+        self.public
+    }
+
+    fn sign(&self, msg: &[u8], context: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        P::sign_msg(&self.secret, &self.public, msg, context)
+    }
+}
+
+impl<P: CurvePoint> KeyAgreement<P> for SoftwareSigner<P> {
+    fn dh(&self, peer: &P) -> Result<P, Box<dyn Error>> {
+        Ok(dh::dh_exchange(&self.secret, peer))
+    }
+
+    fn prove_dh(&self, peer: &P) -> Result<(P, DleqProof<P>), Box<dyn Error>> {
+        let (proof, _, shared) = DleqProof::new(&P::generator(), peer, &self.secret);
+        Ok((shared, proof))
+    }
+}
+
+impl<P: CurvePoint> fmt::Debug for SoftwareSigner<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SoftwareSigner")
+            .field("public", &self.public)
+            .finish()
+    }
+}
+
+impl<P: CurvePoint> Serialize for SoftwareSigner<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.secret.serialize(serializer)
+    }
+}
+
+impl<'de, P: CurvePoint> Deserialize<'de> for SoftwareSigner<P> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let secret: P::Scalar = <P::Scalar as Deserialize>::deserialize(deserializer)?;
+        Ok(SoftwareSigner::new(secret))
+    }
+}
+
+/// ParticipantIdentity holds the public keys of a participant: the signing
+/// key identifies it in the list of verifiers and verifies its signatures,
+/// and the encryption key receives its deals.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ParticipantIdentity<P: CurvePoint = GE> {
+    pub signing: P,
+    pub encryption: P,
+}
+
+impl<P: CurvePoint> ParticipantIdentity<P> {
+    pub fn new(signing: P, encryption: P) -> ParticipantIdentity<P> {
+        ParticipantIdentity {
+            signing,
+            encryption,
+        }
+    }
+
+    /// single returns the identity of a participant using the same key to
+    /// sign and to decrypt.
+    pub fn single(key: P) -> ParticipantIdentity<P> {
+        ParticipantIdentity::new(key, key)
+    }
+}
+
+/// IdentityKeys holds distinct signing and encryption private keys in memory.
+/// It signs with the signing key and computes the shared keys with the
+/// encryption key. It serializes as the pair of private keys.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct IdentityKeys<P: CurvePoint = GE> {
+    signing: SoftwareSigner<P>,
+    encryption: SoftwareSigner<P>,
+}
+
+impl<P: CurvePoint> IdentityKeys<P> {
+    pub fn new(signing: P::Scalar, encryption: P::Scalar) -> IdentityKeys<P> {
+        IdentityKeys {
+            signing: SoftwareSigner::new(signing),
+            encryption: SoftwareSigner::new(encryption),
+        }
+    }
+
+    /// identity returns the public keys of the participant.
+    pub fn identity(&self) -> ParticipantIdentity<P> {
+        ParticipantIdentity::new(self.signing.public(), self.encryption.public())
+    }
+}
+
+impl<P: CurvePoint> Signer<P> for IdentityKeys<P> {
+    fn public(&self) -> P {
+        self.signing.public()
+    }
+
+    fn sign(&self, msg: &[u8], context: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.signing.sign(msg, context)
+    }
+}
+
+impl<P: CurvePoint> KeyAgreement<P> for IdentityKeys<P> {
+    fn dh(&self, peer: &P) -> Result<P, Box<dyn Error>> {
+        self.encryption.dh(peer)
+    }
+
+    fn prove_dh(&self, peer: &P) -> Result<(P, DleqProof<P>), Box<dyn Error>> {
+        self.encryption.prove_dh(peer)
+    }
+}
+
+impl<P: CurvePoint> fmt::Debug for IdentityKeys<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdentityKeys")
+            .field("identity", &self.identity())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::Domain;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+    use crate::vss::{Dealer, Verifier};
+    use std::cell::Cell;
+
+    // ExternalKey stands for a key held out of process, counting its uses.
+    struct ExternalKey {
+        key: SoftwareSigner,
+        uses: Cell<u32>,
+    }
+
+    impl Signer for ExternalKey {
+        fn public(&self) -> GE {
+            self.key.public()
+        }
+
+        fn sign(&self, msg: &[u8], context: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+            self.uses.set(self.uses.get() + 1);
+            self.key.sign(msg, context)
+        }
+    }
+
+    impl KeyAgreement for ExternalKey {
+        fn dh(&self, peer: &GE) -> Result<GE, Box<dyn Error>> {
+            self.uses.set(self.uses.get() + 1);
+            self.key.dh(peer)
+        }
+
+        fn prove_dh(&self, _peer: &GE) -> Result<(GE, DleqProof), Box<dyn Error>> {
+            bail!("no proofs from this key")
+        }
+    }
+
+    #[test]
+    fn test_external_key() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(3);
+        let external = |sec: &FE| ExternalKey {
+            key: SoftwareSigner::new(*sec),
+            uses: Cell::new(0),
+        };
+        let secret: FE = ECScalar::new_random();
+        let mut dealer = Dealer::with_signer(external(&secs[0]), secret, pubs.clone(), 2).unwrap();
+        let mut verifier =
+            Verifier::with_signer(external(&secs[1]), pubs[0], pubs.clone()).unwrap();
+
+        let encr_d = dealer.encrypt_deal(1).unwrap();
+        let resp = verifier.process_encrypted_deal(&encr_d).unwrap();
+        assert!(resp.approved);
+        assert!(dealer.process_response(&resp).unwrap().is_none());
+        // a signature by the dealer, a shared key and a signature by the verifier
+        assert_eq!(1, dealer.signer().uses.get());
+        assert_eq!(2, verifier.signer().uses.get());
+    }
+
+    #[test]
+    fn test_identity_keys() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(1);
+        let keys: Vec<IdentityKeys> = (0..3)
+            .map(|_| IdentityKeys::new(ECScalar::new_random(), ECScalar::new_random()))
+            .collect();
+        let identities: Vec<ParticipantIdentity> = keys.iter().map(|k| k.identity()).collect();
+        let signing: Vec<GE> = identities.iter().map(|id| id.signing).collect();
+        let secret: FE = ECScalar::new_random();
+        let mut dealer = Dealer::new(secs[0], secret, signing.clone(), 2).unwrap();
+        dealer.set_identities(&identities).unwrap();
+        let new_verifier = |i: usize| -> Verifier<GE, IdentityKeys> {
+            Verifier::with_identity(keys[i].clone(), pubs[0], &identities).unwrap()
+        };
+
+        // the deals are encrypted to the encryption keys
+        let encr_deals = dealer.encrypt_deals().unwrap();
+        let mut unaware = Verifier::with_signer(keys[0].clone(), pubs[0], signing.clone()).unwrap();
+        unaware
+            .decrypt_deal(&encr_deals[0])
+            .expect_err("Must fail, deal encrypted to another key");
+        let mut verifier = new_verifier(0);
+        let resp = verifier.process_encrypted_deal(&encr_deals[0]).unwrap();
+        assert!(resp.approved);
+        assert!(dealer.process_response(&resp).unwrap().is_none());
+
+        // and the complaints are judged with them
+        let complaint = new_verifier(2).complaint(&encr_deals[2]).unwrap();
+        complaint
+            .judge(&pubs[0], &signing)
+            .expect_err("Must fail, proof for the encryption key");
+        assert!(!complaint
+            .judge_identities(&pubs[0], &identities, None, &[], &Domain::default())
+            .unwrap());
+        assert!(!verifier.process_complaint(&complaint).unwrap());
+
+        let mut swapped = identities.clone();
+        swapped[1].encryption = swapped[2].encryption;
+        Verifier::with_identity(keys[1].clone(), pubs[0], &swapped)
+            .expect_err("Must fail, encryption key of another verifier");
+    }
+
+    #[test]
+    fn test_software_signer_serde() {
+        let sec: FE = ECScalar::new_random();
+        let signer: SoftwareSigner = SoftwareSigner::new(sec);
+        let bytes = bincode::serialize(&signer).unwrap();
+        assert_eq!(bincode::serialize(&sec).unwrap(), bytes);
+        let decoded: SoftwareSigner = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(signer.public(), decoded.public());
+    }
+}
@@ -0,0 +1,468 @@
+//! In-memory network simulator to run the DKG between many participants
+//! without sockets.
+//!
+//! A Network carries the messages sent through its SimTransport endpoints with
+//! a configurable latency, drop rate and reordering. Time is discrete: messages
+//! are delivered to the inboxes of their recipients when the network ticks.
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::ristretto_curve;
+use crate::transport;
+
+use curve_traits::{CurvePoint, ECScalar};
+use dkg::{DistKeyGenerator, DistKeyShare};
+use rand_core::RngCore;
+use ristretto_curve::GE;
+use transport::{Message, Transport};
+
+/// SimConfig holds the behaviour of a simulated network.
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    // Minimum number of ticks before a message is delivered
+    pub min_latency: u64,
+    // Maximum number of ticks before a message is delivered
+    pub max_latency: u64,
+    // Probability for a message to be lost, between 0 and 1
+    pub drop_rate: f64,
+    // If false, the messages between two participants are delivered in the
+    // order they were sent
+    pub reorder: bool,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            min_latency: 1,
+            max_latency: 1,
+            drop_rate: 0.0,
+            reorder: false,
+        }
+    }
+}
+
+struct InFlight<P: CurvePoint> {
+    deliver_at: u64,
+    from: u32,
+    to: u32,
+    msg: Message<P>,
+}
+
+struct Bus<P: CurvePoint> {
+    config: SimConfig,
+    rng: Box<dyn RngCore + Send>,
+    now: u64,
+    in_flight: Vec<InFlight<P>>,
+    inboxes: Vec<VecDeque<(u32, Message<P>)>>,
+    // delivery time of the last message sent on each link, to keep them in order
+    last_delivery: HashMap<(u32, u32), u64>,
+    dropped: usize,
+}
+
+impl<P: CurvePoint> Bus<P> {
+    fn push(&mut self, from: u32, to: u32, msg: Message<P>) -> Result<(), Box<dyn Error>> {
+        if to as usize >= self.inboxes.len() {
+            bail!("sim: unknown recipient {}", to);
+        }
+
+        let draw = self.rng.next_u64() as f64 / u64::MAX as f64;
+        if draw < self.config.drop_rate {
+            self.dropped += 1;
+            return Ok(());
+        }
+
+        let spread = self
+            .config
+            .max_latency
+            .saturating_sub(self.config.min_latency)
+            + 1;
+        let mut deliver_at = self.now + self.config.min_latency + self.rng.next_u64() % spread;
+        if !self.config.reorder {
+            let last = self.last_delivery.entry((from, to)).or_default();
+            deliver_at = deliver_at.max(*last);
+            *last = deliver_at;
+        }
+
+        self.in_flight.push(InFlight {
+            deliver_at,
+            from,
+            to,
+            msg,
+        });
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        self.now += 1;
+        let now = self.now;
+        let (mut due, in_flight): (Vec<InFlight<P>>, Vec<InFlight<P>>) =
+            mem::take(&mut self.in_flight)
+                .into_iter()
+                .partition(|m| m.deliver_at <= now);
+        self.in_flight = in_flight;
+
+        if self.config.reorder {
+            // Fisher-Yates shuffle of the messages delivered during this tick
+            for i in (1..due.len()).rev() {
+                let j = (self.rng.next_u64() % (i as u64 + 1)) as usize;
+                due.swap(i, j);
+            }
+        }
+
+        for m in due {
+            self.inboxes[m.to as usize].push_back((m.from, m.msg));
+        }
+    }
+}
+
+/// Network is an in-memory network between n participants.
+pub struct Network<P: CurvePoint = GE> {
+    bus: Arc<Mutex<Bus<P>>>,
+}
+
+impl<P: CurvePoint> Network<P> {
+    /// new returns a network between n participants behaving according to
+    /// config, drawing the latencies and losses from rng.
+    pub fn new<R: RngCore + Send + 'static>(n: u32, config: SimConfig, rng: R) -> Network<P> {
+        let bus = Bus {
+            config,
+            rng: Box::new(rng),
+            now: 0,
+            in_flight: Vec::new(),
+            inboxes: (0..n).map(|_| VecDeque::new()).collect(),
+            last_delivery: HashMap::new(),
+            dropped: 0,
+        };
+        Network {
+            bus: Arc::new(Mutex::new(bus)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<Bus<P>> {
+        self.bus.lock().expect("sim: poisoned network")
+    }
+
+    /// transport returns the endpoint of the participant at index.
+    pub fn transport(&self, index: u32) -> SimTransport<P> {
+        SimTransport {
+            index,
+            bus: Arc::clone(&self.bus),
+        }
+    }
+
+    /// tick advances the time by one unit and delivers the messages which are
+    /// due.
+    pub fn tick(&self) {
+        self.lock().tick()
+    }
+
+    /// now returns the number of ticks elapsed.
+    pub fn now(&self) -> u64 {
+        self.lock().now
+    }
+
+    /// idle returns true if no message is in flight nor waiting in an inbox.
+    pub fn idle(&self) -> bool {
+        let bus = self.lock();
+        bus.in_flight.is_empty() && bus.inboxes.iter().all(VecDeque::is_empty)
+    }
+
+    /// dropped returns the number of messages lost so far.
+    pub fn dropped(&self) -> usize {
+        self.lock().dropped
+    }
+}
+
+/// SimTransport is the endpoint of a participant on a Network.
+pub struct SimTransport<P: CurvePoint = GE> {
+    index: u32,
+    bus: Arc<Mutex<Bus<P>>>,
+}
+
+impl<P: CurvePoint> SimTransport<P> {
+    fn lock(&self) -> Result<MutexGuard<Bus<P>>, Box<dyn Error>> {
+        self.bus
+            .lock()
+            .map_err(|_| simple_error!("sim: poisoned network").into())
+    }
+}
+
+impl<P: CurvePoint> Transport<P> for SimTransport<P> {
+    fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn send(&mut self, to: u32, msg: Message<P>) -> Result<(), Box<dyn Error>> {
+        self.lock()?.push(self.index, to, msg)
+    }
+
+    fn broadcast(&mut self, msg: Message<P>) -> Result<(), Box<dyn Error>> {
+        let mut bus = self.lock()?;
+        for to in 0..bus.inboxes.len() as u32 {
+            if to != self.index {
+                bus.push(self.index, to, msg.clone())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self) -> Result<Option<(u32, Message<P>)>, Box<dyn Error>> {
+        Ok(self.lock()?.inboxes[self.index as usize].pop_front())
+    }
+}
+
+/// new_participants returns the longterm key pairs of n participants.
+pub fn new_participants<P: CurvePoint>(n: u32) -> (Vec<P::Scalar>, Vec<P>) {
+    (0..n)
+        .map(|_| {
+            let secret: P::Scalar = ECScalar::new_random();
+            let public: P = P::generator().scalar_mul(&secret.get_element());
+            (secret, public)
+        })
+        .unzip()
+}
+
+/// new_dkgs returns the DistKeyGenerators of n new participants for a DKG of
+/// threshold t.
+pub fn new_dkgs<P: CurvePoint>(n: u32, t: u32) -> Result<Vec<DistKeyGenerator<P>>, Box<dyn Error>> {
+    let (secrets, publics) = new_participants::<P>(n);
+    secrets
+        .into_iter()
+        .map(|s| DistKeyGenerator::new(s, publics.clone(), t))
+        .collect()
+}
+
+// handle processes a message and sends out the resulting messages.
+fn handle<P: CurvePoint, T: Transport<P>>(
+    dkg: &mut DistKeyGenerator<P>,
+    transport: &mut T,
+    msg: &Message<P>,
+) -> Result<(), Box<dyn Error>> {
+    match msg {
+        Message::Deal(d) => {
+            let resp = dkg.process_deal(d)?;
+            transport.broadcast(Message::Response(resp))
+        }
+        Message::Response(r) => match dkg.process_response(r)? {
+            Some(j) => transport.broadcast(Message::Justification(j)),
+            None => Ok(()),
+        },
+        Message::Justification(j) => dkg.process_justification(j),
+        Message::SecretCommits(sc) => match dkg.process_secret_commit(sc)? {
+            Some(cc) => transport.broadcast(Message::ComplaintCommits(cc)),
+            None => Ok(()),
+        },
+        Message::ComplaintCommits(cc) => {
+            let rc = dkg.process_complaints_commits(cc)?;
+            transport.broadcast(Message::ReconstructCommits(rc))
+        }
+        Message::ReconstructCommits(rc) => dkg.process_reconstruct_commits(rc),
+        Message::Echo(_) => bail!("sim: echo broadcast not supported"),
+    }
+}
+
+// step processes the messages received by a participant. A message which can't
+// be processed yet, such as a response arriving before its deal, is kept in
+// pending and retried at the next step.
+fn step<P: CurvePoint, T: Transport<P>>(
+    dkg: &mut DistKeyGenerator<P>,
+    transport: &mut T,
+    pending: &mut Vec<Message<P>>,
+) -> Result<(), Box<dyn Error>> {
+    while let Some((_, msg)) = transport.recv()? {
+        pending.push(msg);
+    }
+    for msg in mem::take(pending) {
+        if handle(dkg, transport, &msg).is_err() {
+            pending.push(msg);
+        }
+    }
+    Ok(())
+}
+
+fn step_all<P: CurvePoint, T: Transport<P>>(
+    dkgs: &mut [DistKeyGenerator<P>],
+    transports: &mut [T],
+    pending: &mut [Vec<Message<P>>],
+) -> Result<(), Box<dyn Error>> {
+    for ((dkg, transport), pending) in dkgs
+        .iter_mut()
+        .zip(transports.iter_mut())
+        .zip(pending.iter_mut())
+    {
+        step(dkg, transport, pending)?;
+    }
+    Ok(())
+}
+
+/// run_dkg runs the DKG between the participants over the network, the
+/// participant at index i using dkgs[i]. Each phase lasts until it completes
+/// or for timeout ticks, after which the participants time out. It returns the
+/// distributed key share of every participant which finished the protocol.
+pub fn run_dkg<P: CurvePoint>(
+    network: &Network<P>,
+    dkgs: &mut [DistKeyGenerator<P>],
+    timeout: u64,
+) -> Result<Vec<Option<DistKeyShare<P>>>, Box<dyn Error>> {
+    let n = dkgs.len();
+    let mut transports: Vec<SimTransport<P>> =
+        (0..n as u32).map(|i| network.transport(i)).collect();
+    let mut pending: Vec<Vec<Message<P>>> = (0..n).map(|_| Vec::new()).collect();
+
+    // 1. deals, responses and justifications
+    for (dkg, transport) in dkgs.iter_mut().zip(transports.iter_mut()) {
+        for (i, deal) in dkg.deals()? {
+            transport.send(i, Message::Deal(deal))?;
+        }
+    }
+    let start = network.now();
+    while !dkgs.iter().all(|d| d.qual().len() == n) && network.now() - start < timeout {
+        network.tick();
+        step_all(dkgs, &mut transports, &mut pending)?;
+    }
+    if !dkgs.iter().all(|d| d.qual().len() == n) {
+        dkgs.iter_mut().for_each(DistKeyGenerator::set_timeout);
+    }
+
+    // 2. secret commits, complaints and reconstructions
+    for (dkg, transport) in dkgs.iter_mut().zip(transports.iter_mut()) {
+        // a participant whose deal is not certified has nothing to reveal
+        if let Ok(sc) = dkg.secret_commits() {
+            transport.broadcast(Message::SecretCommits(sc))?;
+        }
+    }
+    let start = network.now();
+    while !dkgs.iter().all(DistKeyGenerator::finished) && network.now() - start < timeout {
+        network.tick();
+        step_all(dkgs, &mut transports, &mut pending)?;
+    }
+
+    Ok(dkgs
+        .iter()
+        .map(|d| {
+            if d.finished() {
+                d.dist_key_share().ok()
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn new_network(n: u32, config: SimConfig) -> Network {
+        Network::new(n, config, ChaCha20Rng::seed_from_u64(42))
+    }
+
+    fn response(index: u32) -> Message {
+        Message::Response(dkg::Response {
+            index,
+            response: Default::default(),
+        })
+    }
+
+    fn received(t: &mut SimTransport) -> Vec<u32> {
+        let mut indices = Vec::new();
+        while let Some((_, Message::Response(r))) = t.recv().unwrap() {
+            indices.push(r.index);
+        }
+        indices
+    }
+
+    #[test]
+    fn test_sim_network_latency_and_order() {
+        let config = SimConfig {
+            min_latency: 2,
+            max_latency: 6,
+            ..Default::default()
+        };
+        let network = new_network(3, config);
+        let mut t0 = network.transport(0);
+        let mut t1 = network.transport(1);
+        for i in 0..20 {
+            t0.send(1, response(i)).unwrap();
+        }
+        t0.send(3, response(0))
+            .expect_err("Must fail, unknown recipient");
+
+        network.tick();
+        assert!(received(&mut t1).is_empty());
+        let mut indices = Vec::new();
+        while !network.idle() {
+            network.tick();
+            indices.extend(received(&mut t1));
+        }
+        assert!(network.now() <= 6);
+        // delivered in order without reordering
+        assert_eq!((0..20).collect::<Vec<u32>>(), indices);
+    }
+
+    #[test]
+    fn test_sim_network_broadcast_and_drop() {
+        let network = new_network(3, Default::default());
+        let mut t0 = network.transport(0);
+        t0.broadcast(response(7)).unwrap();
+        network.tick();
+        assert!(received(&mut t0).is_empty());
+        assert_eq!(vec![7], received(&mut network.transport(1)));
+        assert_eq!(vec![7], received(&mut network.transport(2)));
+
+        let config = SimConfig {
+            drop_rate: 0.5,
+            ..Default::default()
+        };
+        let network = new_network(2, config);
+        let mut t0 = network.transport(0);
+        for i in 0..1000 {
+            t0.send(1, response(i)).unwrap();
+        }
+        network.tick();
+        let delivered = received(&mut network.transport(1)).len();
+        assert_eq!(1000, delivered + network.dropped());
+        assert!(delivered > 400 && delivered < 600);
+    }
+
+    fn check_dkg(n: u32, t: u32, config: SimConfig) {
+        let network = new_network(n, config);
+        let mut dkgs: Vec<DistKeyGenerator> = new_dkgs(n, t).unwrap();
+        let shares = run_dkg(&network, &mut dkgs, 100).unwrap();
+
+        let public: GE = shares[0].as_ref().unwrap().get_public_key();
+        for share in shares.iter() {
+            assert_eq!(public, share.as_ref().unwrap().get_public_key());
+        }
+    }
+
+    #[test]
+    fn test_sim_dkg_reordered() {
+        let config = SimConfig {
+            min_latency: 1,
+            max_latency: 5,
+            reorder: true,
+            ..Default::default()
+        };
+        check_dkg(7, 4, config);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_sim_dkg_50_nodes() {
+        let config = SimConfig {
+            min_latency: 1,
+            max_latency: 10,
+            reorder: true,
+            ..Default::default()
+        };
+        check_dkg(50, 26, config);
+    }
+}
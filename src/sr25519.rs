@@ -0,0 +1,195 @@
+//! Threshold signatures compatible with sr25519 (schnorrkel)
+//!
+//! The FROST protocol of the tss module, with the challenge of schnorrkel
+//! instead of the one of dss: the aggregated signature verifies as a standard
+//! sr25519 signature of the distributed public key, e.g. with
+//! schnorrkel::PublicKey::verify_simple, so that a key generated by the dkg
+//! can act as a Substrate/Polkadot session or stash key. Only the Ristretto
+//! backend is supported.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::encoding;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::sign;
+use crate::tss;
+
+use curve_traits::{ECPoint, ECScalar};
+use dkg::DistKeyShare;
+use encoding::Encoder;
+use poly::PubPoly;
+use ristretto_curve::{FE, GE};
+use schnorrkel::context::{signing_context, SigningTranscript};
+use schnorrkel::PublicKey;
+use serde::{Deserialize, Serialize};
+use tss::{SignatureShare, SigningCommitments};
+
+/// Signer is a tss::Signer issuing signature shares of sr25519 signatures.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Signer {
+    inner: tss::Signer<GE>,
+}
+
+impl Signer {
+    /// Create a Signer out of the distributed key share of this node and the
+    /// threshold t used during the dkg.
+    pub fn new(share: DistKeyShare<GE>, t: u32) -> Signer {
+        Signer {
+            inner: tss::Signer::new(share, t),
+        }
+    }
+
+    /// index returns the index of this signer in the list of participants.
+    pub fn index(&self) -> u32 {
+        self.inner.index()
+    }
+
+    /// commit returns the commitments to fresh nonces, see tss::Signer::commit.
+    pub fn commit(&mut self) -> SigningCommitments<GE> {
+        self.inner.commit()
+    }
+
+    /// sign issues the signature share of this signer for msg in the signing
+    /// context, as for schnorrkel's sign_simple(context, msg). The nonces are
+    /// consumed, see tss::Signer::sign.
+    pub fn sign(
+        &mut self,
+        context: &[u8],
+        msg: &[u8],
+        commitments: &[SigningCommitments<GE>],
+    ) -> Result<SignatureShare<GE>, Box<dyn Error>> {
+        let public: GE = self.inner.public_key();
+        self.inner
+            .sign_with(&binding_msg(context, msg), commitments, |r| {
+                challenge(&public, r, context, msg)
+            })
+    }
+}
+
+/// verify_signature_share checks the signature share of a signer for msg in
+/// the signing context against the public polynomial of the distributed key.
+pub fn verify_signature_share(
+    pub_poly: &PubPoly<GE>,
+    context: &[u8],
+    msg: &[u8],
+    commitments: &[SigningCommitments<GE>],
+    share: &SignatureShare<GE>,
+) -> Result<(), Box<dyn Error>> {
+    let binding = binding_msg(context, msg);
+    let r: GE = tss::group_commitment(&binding, commitments);
+    let c: FE = challenge(&pub_poly.commit(), &r, context, msg)?;
+    tss::verify_share_with(pub_poly, &binding, commitments, share, &c)
+}
+
+/// aggregate verifies the signature shares and combines them into the 64 bytes
+/// of an sr25519 signature of msg in the signing context.
+pub fn aggregate(
+    pub_poly: &PubPoly<GE>,
+    context: &[u8],
+    msg: &[u8],
+    commitments: &[SigningCommitments<GE>],
+    shares: &[SignatureShare<GE>],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let binding = binding_msg(context, msg);
+    let r: GE = tss::group_commitment(&binding, commitments);
+    let c: FE = challenge(&pub_poly.commit(), &r, context, msg)?;
+    let z: FE = tss::aggregate_with(pub_poly, &binding, commitments, shares, &c)?;
+
+    // R || z, with the marker of schnorrkel signatures
+    let mut buff: Vec<u8> = r.pk_to_key_slice();
+    buff.extend_from_slice(&z.to_bytes());
+    buff[63] |= 128;
+    Ok(buff)
+}
+
+/// verify checks an sr25519 signature of msg in the signing context.
+pub fn verify(
+    public: &GE,
+    context: &[u8],
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    sign::verify_signature(&public.pk_to_key_slice(), signature, msg, context)
+        .map_err(|e| simple_error!("sr25519: invalid signature: {}", e).into())
+}
+
+// binding_msg binds both the signing context and the message to the binding
+// factors of the signers.
+fn binding_msg(context: &[u8], msg: &[u8]) -> Vec<u8> {
+    Encoder::new(b"sr25519")
+        .put_bytes(context)
+        .put_bytes(msg)
+        .into_bytes()
+}
+
+// challenge computes the challenge of schnorrkel for the group commitment r,
+// from the transcript of the message in the signing context.
+fn challenge(public: &GE, r: &GE, context: &[u8], msg: &[u8]) -> Result<FE, Box<dyn Error>> {
+    let public = PublicKey::from_bytes(&public.pk_to_key_slice())
+        .map_err(|e| simple_error!("sr25519: invalid public key: {}", e))?;
+    // R is a Ristretto point as well as a public key
+    let r = PublicKey::from_bytes(&r.pk_to_key_slice())
+        .map_err(|e| simple_error!("sr25519: invalid commitment: {}", e))?;
+
+    let mut t = signing_context(context).bytes(msg);
+    t.proto_name(b"Schnorr-sig");
+    t.commit_point(b"sign:pk", public.as_compressed());
+    t.commit_point(b"sign:R", r.as_compressed());
+    let c = t.challenge_scalar(b"sign:c");
+    FE::from_bytes(&c.to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::sim;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const CTX: &[u8] = b"substrate";
+    const MSG: &[u8] = b"hello sr25519";
+
+    #[test]
+    fn test_sr25519_threshold() {
+        let (n, t) = (5, 3);
+        let mut dkgs: Vec<DistKeyGenerator> = sim::new_dkgs(n, t).unwrap();
+        let network = sim::Network::new(n, Default::default(), ChaCha20Rng::seed_from_u64(7));
+        let shares: Vec<DistKeyShare> = sim::run_dkg(&network, &mut dkgs, 100)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        let pub_poly: PubPoly = shares[0].pub_poly();
+        let mut signers: Vec<Signer> = shares.into_iter().map(|s| Signer::new(s, t)).collect();
+        let signing: &mut [Signer] = &mut signers[1..1 + t as usize];
+
+        let commitments: Vec<SigningCommitments> = signing.iter_mut().map(Signer::commit).collect();
+        let mut sig_shares: Vec<SignatureShare> = signing
+            .iter_mut()
+            .map(|s| s.sign(CTX, MSG, &commitments).unwrap())
+            .collect();
+        for share in sig_shares.iter() {
+            verify_signature_share(&pub_poly, CTX, MSG, &commitments, share).unwrap();
+        }
+        let sig = aggregate(&pub_poly, CTX, MSG, &commitments, &sig_shares).unwrap();
+        assert_eq!(64, sig.len());
+
+        // a standard sr25519 signature of the distributed key
+        let public = PublicKey::from_bytes(&pub_poly.commit().pk_to_key_slice()).unwrap();
+        let signature = schnorrkel::Signature::from_bytes(&sig).unwrap();
+        public.verify_simple(CTX, MSG, &signature).unwrap();
+        verify(&pub_poly.commit(), CTX, MSG, &sig).unwrap();
+        assert!(verify(&pub_poly.commit(), b"other", MSG, &sig).is_err());
+        assert!(verify(&pub_poly.commit(), CTX, b"other msg", &sig).is_err());
+
+        sig_shares[0].share = ECScalar::new_random();
+        verify_signature_share(&pub_poly, CTX, MSG, &commitments, &sig_shares[0])
+            .expect_err("Must fail, invalid share");
+        aggregate(&pub_poly, CTX, MSG, &commitments, &sig_shares)
+            .expect_err("Must fail, invalid share");
+    }
+}
@@ -0,0 +1,242 @@
+//! Persistent state of the protocols, to resume a ceremony after a crash.
+//!
+//! Dealer, Verifier and DistKeyGenerator can be saved as snapshots. A
+//! Checkpointed state saves a snapshot to its Storage after every processed
+//! message, before the answer to the message is returned to the caller. A node
+//! restarting from its last snapshot thus never sends two different answers to
+//! the same message, nor processes a message twice.
+//!
+//! Snapshots hold the longterm secret key and the shares of the participant:
+//! the storage must be protected accordingly.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::vss;
+
+use curve_traits::CurvePoint;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Version of the snapshot format. Snapshots of any other version are rejected.
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// Snapshot is implemented by the protocol states which can be saved and
+/// restored.
+pub trait Snapshot: Serialize + DeserializeOwned {
+    /// snapshot returns the encoding of the whole state.
+    fn snapshot(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut buff: Vec<u8> = vec![SNAPSHOT_VERSION];
+        buff.extend(bincode::serialize(self)?);
+        Ok(buff)
+    }
+
+    /// restore returns the state saved in snapshot.
+    fn restore(snapshot: &[u8]) -> Result<Self, Box<dyn Error>> {
+        match snapshot.split_first() {
+            Some((&SNAPSHOT_VERSION, state)) => Ok(bincode::deserialize(state)?),
+            Some(_) => bail!("storage: unsupported snapshot version"),
+            None => bail!("storage: empty snapshot"),
+        }
+    }
+}
+
+impl<P: CurvePoint> Snapshot for vss::Dealer<P> {}
+impl<P: CurvePoint> Snapshot for vss::Verifier<P> {}
+impl<P: CurvePoint> Snapshot for dkg::DistKeyGenerator<P> {}
+
+/// Storage stores snapshots under a key. A save must be durable when it
+/// returns.
+pub trait Storage {
+    fn save(&mut self, key: &str, snapshot: &[u8]) -> Result<(), Box<dyn Error>>;
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>>;
+}
+
+impl<S: Storage> Storage for &mut S {
+    fn save(&mut self, key: &str, snapshot: &[u8]) -> Result<(), Box<dyn Error>> {
+        (**self).save(key, snapshot)
+    }
+
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        (**self).load(key)
+    }
+}
+
+/// MemoryStorage keeps the snapshots in memory, mostly useful for tests.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    snapshots: HashMap<String, Vec<u8>>,
+}
+
+impl Storage for MemoryStorage {
+    fn save(&mut self, key: &str, snapshot: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.snapshots.insert(key.to_string(), snapshot.to_vec());
+        Ok(())
+    }
+
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        Ok(self.snapshots.get(key).cloned())
+    }
+}
+
+/// FileStorage keeps each snapshot in a file of a directory. Snapshots are
+/// written to a temporary file which replaces the previous one once synced, so
+/// that a crash during a save leaves the previous snapshot intact.
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    /// new returns the storage in dir, which is created if needed.
+    pub fn new<D: Into<PathBuf>>(dir: D) -> Result<FileStorage, Box<dyn Error>> {
+        let dir: PathBuf = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(FileStorage { dir })
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let valid = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            bail!("storage: invalid key {:?}", key);
+        }
+        Ok(self.dir.join(format!("{}.snapshot", key)))
+    }
+}
+
+impl Storage for FileStorage {
+    fn save(&mut self, key: &str, snapshot: &[u8]) -> Result<(), Box<dyn Error>> {
+        let path = self.path(key)?;
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(snapshot)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn load(&self, key: &str) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        let path = self.path(key)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read(path)?))
+    }
+}
+
+/// Checkpointed holds a protocol state which is saved to the storage after
+/// every modification.
+pub struct Checkpointed<T: Snapshot, S: Storage> {
+    state: T,
+    storage: S,
+    key: String,
+}
+
+impl<T: Snapshot, S: Storage> Checkpointed<T, S> {
+    /// new saves the initial state under key and returns it checkpointed.
+    pub fn new(state: T, storage: S, key: &str) -> Result<Checkpointed<T, S>, Box<dyn Error>> {
+        let mut c = Checkpointed {
+            state,
+            storage,
+            key: key.to_string(),
+        };
+        c.save()?;
+        Ok(c)
+    }
+
+    /// restore returns the state last saved under key, or None if there is
+    /// none.
+    pub fn restore(storage: S, key: &str) -> Result<Option<Checkpointed<T, S>>, Box<dyn Error>> {
+        match storage.load(key)? {
+            Some(snapshot) => Ok(Some(Checkpointed {
+                state: T::restore(&snapshot)?,
+                storage,
+                key: key.to_string(),
+            })),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        let snapshot = self.state.snapshot()?;
+        self.storage.save(&self.key, &snapshot)
+    }
+
+    /// process applies f to the state, typically to process a message, and
+    /// saves the state before returning the result of f. The state is saved
+    /// even if f fails, as it might have been modified.
+    pub fn process<R, F>(&mut self, f: F) -> Result<R, Box<dyn Error>>
+    where
+        F: FnOnce(&mut T) -> Result<R, Box<dyn Error>>,
+    {
+        let res = f(&mut self.state);
+        self.save()?;
+        res
+    }
+
+    pub fn state(&self) -> &T {
+        &self.state
+    }
+
+    pub fn into_inner(self) -> T {
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use dkg::{DistKeyGenerator, Response};
+
+    #[test]
+    fn test_storage_checkpointed_dkg() {
+        let mut dkgs: Vec<DistKeyGenerator> = sim::new_dkgs(3, 2).unwrap();
+        let deals = dkgs[0].deals().unwrap();
+        let mut storage = MemoryStorage::default();
+
+        let dkg1 = dkgs.remove(1);
+        let mut c = Checkpointed::new(dkg1, &mut storage, "dkg-1").unwrap();
+        let resp: Response = c.process(|d| d.process_deal(&deals[&1])).unwrap();
+        assert!(resp.response.approved);
+        drop(c);
+
+        // the restored state remembers the processed deal
+        let mut c: Checkpointed<DistKeyGenerator, _> = Checkpointed::restore(&mut storage, "dkg-1")
+            .unwrap()
+            .unwrap();
+        let again: Response = c.process(|d| d.process_deal(&deals[&1])).unwrap();
+        assert_eq!(resp.response, again.response);
+
+        let mut snapshot = c.state().snapshot().unwrap();
+        snapshot[0] = SNAPSHOT_VERSION + 1;
+        let restored: Result<DistKeyGenerator, _> = Snapshot::restore(&snapshot);
+        restored.expect_err("Must fail, unsupported version");
+
+        drop(c);
+        let missing = Checkpointed::<DistKeyGenerator, _>::restore(&mut storage, "dkg-2");
+        assert!(missing.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_storage_file() {
+        let dir = std::env::temp_dir().join(format!("dkg-storage-{}", std::process::id()));
+        let mut storage = FileStorage::new(&dir).unwrap();
+        assert_eq!(None, storage.load("node").unwrap());
+        storage.save("node", &[1, 2, 3]).unwrap();
+        storage.save("node", &[4, 5]).unwrap();
+        assert_eq!(Some(vec![4, 5]), storage.load("node").unwrap());
+        storage
+            .save("../node", &[1])
+            .expect_err("Must fail, invalid key");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,298 @@
+//! Threshold BLS signatures
+//!
+//! The distributed key lives in G1 and signatures in G2, as in drand: messages
+//! are hashed to G2 with the hash_to_curve suite of drand and a partial
+//! signature is the index of the share as a big endian u16 followed by the
+//! compressed signature. Any t valid partial signatures are aggregated into a
+//! regular BLS signature verifiable against the distributed public key.
+//!
+//! For blind signatures, the client blinds the hash of its message with a
+//! random factor r and sends r * H(msg) to the signers, who sign it without
+//! learning the message. The client unblinds the aggregated signature with
+//! 1/r into the regular signature of msg.
+//! Enabled with the `tbls` feature.
+
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::error::Error;
+
+use crate::bls12_381_curve;
+use crate::curve_traits;
+use crate::poly;
+use crate::utils;
+
+use bls12_381_curve::{hash_to_g2, pairing, G1Point, G2Point, FE, G2_SIZE};
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use poly::{PriShare, PubPoly, PubShare};
+use rand_core::{CryptoRng, RngCore};
+
+/// Domain separation tag of the signatures on G2 used by drand
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+/// Size of the share index prefixed to a partial signature
+pub const INDEX_SIZE: usize = 2;
+/// Size of a BLS signature
+pub const SIG_SIZE: usize = G2_SIZE;
+/// Size of a partial signature
+pub const PARTIAL_SIG_SIZE: usize = INDEX_SIZE + SIG_SIZE;
+
+/// sign returns the BLS signature of msg under secret.
+pub fn sign(secret: &FE, msg: &[u8]) -> Vec<u8> {
+    hash_to_g2(msg, DST)
+        .scalar_mul(&secret.get_element())
+        .pk_to_key_slice()
+}
+
+/// verify checks the BLS signature sig of msg under public.
+pub fn verify(public: &G1Point, msg: &[u8], sig: &[u8]) -> Result<(), Box<dyn Error>> {
+    verify_point(public, &hash_to_g2(msg, DST), sig)
+}
+
+// verify_point checks the BLS signature sig of the point h of G2 under public:
+// e(g1, s) == e(public, h)
+fn verify_point(public: &G1Point, h: &G2Point, sig: &[u8]) -> Result<(), Box<dyn Error>> {
+    let s: G2Point = G2Point::from_bytes(sig).map_err(|e| simple_error!("tbls: {}", e))?;
+    if pairing(&G1Point::generator(), &s) != pairing(public, h) {
+        bail!("tbls: invalid signature");
+    }
+    Ok(())
+}
+
+/// sign_partial returns the partial signature of msg under the private share.
+pub fn sign_partial(share: &PriShare<FE>, msg: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    sign_partial_point(share, &hash_to_g2(msg, DST))
+}
+
+fn sign_partial_point(share: &PriShare<FE>, h: &G2Point) -> Result<Vec<u8>, Box<dyn Error>> {
+    let index: u16 = share
+        .i
+        .try_into()
+        .map_err(|_| simple_error!("tbls: share index too large"))?;
+    let mut buff: Vec<u8> = index.to_be_bytes().to_vec();
+    buff.extend_from_slice(&h.scalar_mul(&share.v.get_element()).pk_to_key_slice());
+    Ok(buff)
+}
+
+/// index returns the index of the share which issued the partial signature.
+pub fn index(partial: &[u8]) -> Result<u32, Box<dyn Error>> {
+    if partial.len() != PARTIAL_SIG_SIZE {
+        bail!("tbls: invalid partial signature length");
+    }
+    Ok(u16::from_be_bytes([partial[0], partial[1]]) as u32)
+}
+
+/// verify_partial checks the partial signature of msg against the public share
+/// of its issuer, evaluated from the public polynomial.
+pub fn verify_partial(
+    pub_poly: &PubPoly<G1Point>,
+    msg: &[u8],
+    partial: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    verify_partial_point(pub_poly, &hash_to_g2(msg, DST), partial)
+}
+
+fn verify_partial_point(
+    pub_poly: &PubPoly<G1Point>,
+    h: &G2Point,
+    partial: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    let i: u32 = index(partial)?;
+    let pub_share: PubShare<G1Point> = pub_poly.eval(i);
+    verify_point(&pub_share.v, h, &partial[INDEX_SIZE..])
+}
+
+/// aggregate verifies the partial signatures and recovers the full signature of
+/// msg out of t valid ones by Lagrange interpolation. Invalid partial
+/// signatures are ignored; an error is returned if less than t are valid among
+/// the first n.
+pub fn aggregate(
+    pub_poly: &PubPoly<G1Point>,
+    msg: &[u8],
+    partials: &[Vec<u8>],
+    t: u32,
+    n: u32,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    aggregate_point(pub_poly, &hash_to_g2(msg, DST), partials, t, n)
+}
+
+fn aggregate_point(
+    pub_poly: &PubPoly<G1Point>,
+    h: &G2Point,
+    partials: &[Vec<u8>],
+    t: u32,
+    n: u32,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut shares: Vec<PubShare<G2Point>> = Vec::new();
+    let mut seen: HashSet<u32> = HashSet::new();
+    for partial in partials.iter() {
+        if verify_partial_point(pub_poly, h, partial).is_err() {
+            continue;
+        }
+        let i: u32 = index(partial)?;
+        if i >= n || !seen.insert(i) {
+            continue;
+        }
+        let sig: G2Point = G2Point::from_bytes(&partial[INDEX_SIZE..])?;
+        shares.push(PubShare::new(i, sig));
+        if shares.len() as u32 >= t {
+            break;
+        }
+    }
+
+    if (shares.len() as u32) < t {
+        bail!("tbls: not enough valid partial signatures");
+    }
+
+    let sig: G2Point = poly::recover_commit(&mut shares, t)?;
+    Ok(sig.pk_to_key_slice())
+}
+
+/// blind returns the blinding factor r, to be kept by the client, and the
+/// blinded message r * H(msg) to be signed by the signers.
+pub fn blind(msg: &[u8]) -> (FE, Vec<u8>) {
+    blind_with_rng(msg, &mut utils::rand_hack())
+}
+
+/// Same as blind, with the blinding factor drawn from rng.
+pub fn blind_with_rng<R: RngCore + CryptoRng>(msg: &[u8], rng: &mut R) -> (FE, Vec<u8>) {
+    let r: FE = ECScalar::new_random_with_rng(rng);
+    let blinded: G2Point = hash_to_g2(msg, DST).scalar_mul(&r.get_element());
+    (r, blinded.pk_to_key_slice())
+}
+
+/// sign_blind_partial returns the partial signature of the blinded message
+/// under the private share. The signer learns nothing about the message.
+pub fn sign_blind_partial(share: &PriShare<FE>, blinded: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    sign_partial_point(share, &decode_blinded(blinded)?)
+}
+
+/// verify_blind_partial checks the partial signature of the blinded message,
+/// see verify_partial.
+pub fn verify_blind_partial(
+    pub_poly: &PubPoly<G1Point>,
+    blinded: &[u8],
+    partial: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    verify_partial_point(pub_poly, &decode_blinded(blinded)?, partial)
+}
+
+/// aggregate_blind recovers the blind signature of the blinded message from t
+/// valid partial signatures, see aggregate.
+pub fn aggregate_blind(
+    pub_poly: &PubPoly<G1Point>,
+    blinded: &[u8],
+    partials: &[Vec<u8>],
+    t: u32,
+    n: u32,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    aggregate_point(pub_poly, &decode_blinded(blinded)?, partials, t, n)
+}
+
+/// unblind returns the signature of the message out of the blind signature
+/// and the blinding factor r. It is verifiable with verify.
+pub fn unblind(r: &FE, blind_sig: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    if *r == FE::zero() {
+        bail!("tbls: zero blinding factor");
+    }
+    let s: G2Point = G2Point::from_bytes(blind_sig).map_err(|e| simple_error!("tbls: {}", e))?;
+    Ok(s.scalar_mul(&r.invert().get_element()).pk_to_key_slice())
+}
+
+fn decode_blinded(blinded: &[u8]) -> Result<G2Point, Box<dyn Error>> {
+    let h: G2Point =
+        G2Point::from_bytes(blinded).map_err(|e| simple_error!("tbls: blinded message: {}", e))?;
+    if h == G2Point::identity() {
+        bail!("tbls: blinded message is the identity");
+    }
+    Ok(h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poly::PriPoly;
+
+    const MSG: &[u8] = b"Hello threshold Boneh-Lynn-Shacham";
+
+    fn gen_poly(n: u32, t: u32) -> (Vec<PriShare<FE>>, PubPoly<G1Point>) {
+        let pri_poly: PriPoly<G1Point> = PriPoly::new(t, None);
+        (pri_poly.shares(n), pri_poly.commit(None))
+    }
+
+    #[test]
+    fn test_tbls_whole() {
+        let (n, t) = (10, 6);
+        let (shares, pub_poly) = gen_poly(n, t);
+
+        let partials: Vec<Vec<u8>> = shares
+            .iter()
+            .map(|s| sign_partial(s, MSG).unwrap())
+            .collect();
+        for partial in partials.iter() {
+            assert_eq!(PARTIAL_SIG_SIZE, partial.len());
+            verify_partial(&pub_poly, MSG, partial).unwrap();
+        }
+
+        // any t partial signatures give the same signature
+        let sig = aggregate(&pub_poly, MSG, &partials[..t as usize], t, n).unwrap();
+        let other = aggregate(&pub_poly, MSG, &partials[(n - t) as usize..], t, n).unwrap();
+        assert_eq!(sig, other);
+        verify(&pub_poly.commit(), MSG, &sig).unwrap();
+        verify(&pub_poly.commit(), b"other message", &sig).expect_err("Must fail, wrong msg");
+    }
+
+    #[test]
+    fn test_tbls_blind() {
+        let (n, t) = (5, 3);
+        let (shares, pub_poly) = gen_poly(n, t);
+
+        let (r, blinded) = blind(MSG);
+        let partials: Vec<Vec<u8>> = shares
+            .iter()
+            .map(|s| sign_blind_partial(s, &blinded).unwrap())
+            .collect();
+        for partial in partials.iter() {
+            verify_blind_partial(&pub_poly, &blinded, partial).unwrap();
+            verify_partial(&pub_poly, MSG, partial).expect_err("Must fail, blinded message");
+        }
+
+        let blind_sig = aggregate_blind(&pub_poly, &blinded, &partials[1..], t, n).unwrap();
+        verify(&pub_poly.commit(), MSG, &blind_sig).expect_err("Must fail, still blinded");
+        let sig = unblind(&r, &blind_sig).unwrap();
+        verify(&pub_poly.commit(), MSG, &sig).unwrap();
+        // the same signature as without blinding
+        let plain: Vec<Vec<u8>> = shares
+            .iter()
+            .map(|s| sign_partial(s, MSG).unwrap())
+            .collect();
+        assert_eq!(sig, aggregate(&pub_poly, MSG, &plain, t, n).unwrap());
+
+        // another blinding of the same message looks unrelated
+        let (_, other) = blind(MSG);
+        assert_ne!(blinded, other);
+        sign_blind_partial(&shares[0], &G2Point::identity().pk_to_key_slice())
+            .expect_err("Must fail, identity");
+    }
+
+    #[test]
+    fn test_tbls_invalid_partials() {
+        let (n, t) = (7, 4);
+        let (shares, pub_poly) = gen_poly(n, t);
+
+        let mut partials: Vec<Vec<u8>> = shares
+            .iter()
+            .map(|s| sign_partial(s, MSG).unwrap())
+            .collect();
+        // partial of share 0 signed by share 1
+        let forged: Vec<u8> = partials[1][INDEX_SIZE..].to_vec();
+        partials[0][INDEX_SIZE..].copy_from_slice(&forged);
+        verify_partial(&pub_poly, MSG, &partials[0]).expect_err("Must fail, wrong signer");
+        verify_partial(&pub_poly, MSG, &partials[1][..SIG_SIZE])
+            .expect_err("Must fail, wrong length");
+
+        // the invalid partial is skipped
+        let sig = aggregate(&pub_poly, MSG, &partials[..=t as usize], t, n).unwrap();
+        verify(&pub_poly.commit(), MSG, &sig).unwrap();
+        aggregate(&pub_poly, MSG, &partials[..t as usize], t, n)
+            .expect_err("Must fail, not enough valid partials");
+    }
+}
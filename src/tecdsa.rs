@@ -0,0 +1,667 @@
+//! Threshold ECDSA over secp256k1 - experimental, research only.
+//!
+//! The key is generated by the DKG over the secp256k1 backend, with threshold
+//! t. Signing follows the honest majority approach of Gennaro et al.: the
+//! product of two sharings of degree t-1 is a sharing of degree 2t-2, which 2t-1
+//! participants can open, so that at least 2t-1 participants are needed and
+//! n >= 2t-1. No zero-knowledge proof is used: a malicious participant can make
+//! the signature invalid, which is detected when more than 2t-1 shares are
+//! received or when the signature is verified, but not attributed.
+//!  1. presign round 1: every participant broadcasts a PresignDeal sharing a
+//!     random nonce k, a random mask a and two zero-sharings b and c of degree
+//!     2t-2, encrypted to every participant.
+//!  2. presign round 2: every participant sums the parts it received and
+//!     broadcasts a PresignShare holding w_i = k_i * a_i + b_i. Any 2t-1 of
+//!     them open w = k * a, so that a_i / w is a share of 1/k, and R = k * G is
+//!     the sum of the commitments to the nonces.
+//!  3. sign: given the hash m of the message, every participant of the
+//!     presigning broadcasts s_i = (a_i / w) * (m + r * x_i) + c_i, and any
+//!     2t-1 of them open the signature s = (m + r * x) / k.
+//! A Presignature must sign a single message.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::encoding;
+use crate::poly;
+use crate::recovery;
+use crate::secp256k1_curve;
+use crate::utils;
+
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use dkg::DistKeyShare;
+use encoding::Encoder;
+use poly::{PriPoly, PriShare, PubPoly};
+use rand_core::{CryptoRng, RngCore};
+use recovery::{decrypt_part, encrypt_part, EncryptedPart};
+use secp256k1_curve::{FE, GE};
+use serde::{Deserialize, Serialize};
+use utils::bitwise_eq;
+
+/// PresignParts are the evaluations of the sharings of a dealer for one
+/// participant, encrypted to it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignParts {
+    pub k: EncryptedPart<GE>,
+    pub a: EncryptedPart<GE>,
+    pub b: EncryptedPart<GE>,
+    pub c: EncryptedPart<GE>,
+}
+
+/// PresignDeal is broadcasted by every participant in the first round of the
+/// presigning.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignDeal {
+    // Index of the participant issuing the deal
+    pub index: u32,
+    pub session_id: Vec<u8>,
+    // commitments of the sharings of the nonce and of the mask
+    pub k_commitments: Vec<Vec<u8>>,
+    pub a_commitments: Vec<Vec<u8>>,
+    // commitments of the zero-sharings, but the constant terms which are zero
+    pub b_commitments: Vec<Vec<u8>>,
+    pub c_commitments: Vec<Vec<u8>>,
+    // parts[j] holds the evaluations for the j-th participant
+    pub parts: Vec<PresignParts>,
+    pub signature: Vec<u8>,
+}
+
+/// PresignShare is broadcasted by every participant in the second round of the
+/// presigning.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignShare {
+    pub index: u32,
+    pub session_id: Vec<u8>,
+    // share of k * a
+    pub w: FE,
+    pub signature: Vec<u8>,
+}
+
+/// Presignature is the output of the presigning for one participant, which
+/// signs a single message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Presignature {
+    pub index: u32,
+    pub session_id: Vec<u8>,
+    // nonce commitment R = k * G
+    pub big_r: GE,
+    // share of 1/k
+    kinv: FE,
+    // share of the zero-sharing masking the partial signature
+    mask: FE,
+}
+
+/// PartialSignature is broadcasted by every participant of the signing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub index: u32,
+    pub session_id: Vec<u8>,
+    pub s: FE,
+}
+
+/// Signature is an ECDSA signature with low s, along with the recovery id of
+/// the public key.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Signature {
+    pub r: FE,
+    pub s: FE,
+    pub recovery_id: u8,
+}
+
+impl Signature {
+    /// to_bytes returns the 64 bytes r || s encoding of the signature.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.r.to_bytes(), self.s.to_bytes()].concat()
+    }
+}
+
+impl PresignDeal {
+    /// hash returns the hash of the deal signed by its participant.
+    pub fn hash(&self) -> [u8; 32] {
+        let enc = Encoder::new(b"tecdsapresigndeal")
+            .put_bytes(&self.session_id)
+            .put_u32(self.index)
+            .put_list(&self.k_commitments)
+            .put_list(&self.a_commitments)
+            .put_list(&self.b_commitments)
+            .put_list(&self.c_commitments)
+            .put_u32(self.parts.len() as u32);
+        self.parts
+            .iter()
+            .flat_map(|p| vec![&p.k, &p.a, &p.b, &p.c])
+            .fold(enc, |enc, part| {
+                enc.put_bytes(&part.dh_key.pk_to_key_slice())
+                    .put_bytes(&part.nonce)
+                    .put_bytes(&part.cipher)
+            })
+            .hash()
+    }
+}
+
+impl PresignShare {
+    /// hash returns the hash of the share signed by its participant.
+    pub fn hash(&self) -> [u8; 32] {
+        Encoder::new(b"tecdsapresignshare")
+            .put_bytes(&self.session_id)
+            .put_u32(self.index)
+            .put_bytes(&self.w.to_bytes())
+            .hash()
+    }
+}
+
+// session_id binds the messages of a presigning to the distributed key, the
+// participants and the label of the presigning.
+fn session_id(commits: &[GE], participants: &[GE], label: &[u8]) -> Vec<u8> {
+    let commits: Vec<Vec<u8>> = commits.iter().map(GE::pk_to_key_slice).collect();
+    let participants: Vec<Vec<u8>> = participants.iter().map(GE::pk_to_key_slice).collect();
+    Encoder::new(b"tecdsasession")
+        .put_list(&commits)
+        .put_list(&participants)
+        .put_bytes(label)
+        .hash()
+        .to_vec()
+}
+
+/// Presigner runs the presigning for a holder of a share of the distributed
+/// key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Presigner {
+    // longterm secret, i.e. private key of the node
+    long: FE,
+    // pub keys of the participants, indexed by the index of their share
+    participants: Vec<GE>,
+    // index of the share of this node
+    index: u32,
+    // threshold of the distributed key
+    t: u32,
+    session_id: Vec<u8>,
+    // coefficients of the sharings k, a, b and c of this node
+    coeffs: [Vec<FE>; 4],
+    // evaluation for this node of the sharings k, a, b and c of each participant
+    received: HashMap<u32, [FE; 4]>,
+    // commitment to the nonce of each participant
+    nonces: HashMap<u32, GE>,
+    // share of k * a
+    w: Option<FE>,
+}
+
+impl Presigner {
+    /// longterm: the secret (private) key of the node,
+    /// share: the share of the distributed key held by this node,
+    /// participants: the list of participants pubkeys, indexed by the index of
+    /// their share. All of them must take part, and at least 2t-1 are needed.
+    /// label: a label unique to the presigning, e.g. a counter.
+    pub fn new(
+        longterm: FE,
+        share: &DistKeyShare<GE>,
+        participants: Vec<GE>,
+        label: &[u8],
+    ) -> Result<Presigner, Box<dyn Error>> {
+        Presigner::new_with_rng(
+            longterm,
+            share,
+            participants,
+            label,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as new, with the sharings drawn from rng.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        longterm: FE,
+        share: &DistKeyShare<GE>,
+        participants: Vec<GE>,
+        label: &[u8],
+        rng: &mut R,
+    ) -> Result<Presigner, Box<dyn Error>> {
+        let t = share.get_commitments().len() as u32;
+        if (participants.len() as u32) < 2 * t - 1 {
+            bail!("tecdsa: less than 2t-1 participants");
+        }
+        let index = share.get_pri_share().i;
+        let pub_k: GE = GE::generator().scalar_mul(&longterm.get_element());
+        if participants.get(index as usize) != Some(&pub_k) {
+            bail!("tecdsa: own public key not found at the index of the share");
+        }
+
+        let k: PriPoly<GE> = PriPoly::new_with_rng(t, None, rng);
+        let a: PriPoly<GE> = PriPoly::new_with_rng(t, None, rng);
+        let b: PriPoly<GE> = PriPoly::zero_with_rng(2 * t - 1, rng);
+        let c: PriPoly<GE> = PriPoly::zero_with_rng(2 * t - 1, rng);
+        let session_id = session_id(share.get_commitments(), &participants, label);
+        Ok(Presigner {
+            long: longterm,
+            participants,
+            index,
+            t,
+            session_id,
+            coeffs: [
+                k.coefficients(),
+                a.coefficients(),
+                b.coefficients(),
+                c.coefficients(),
+            ],
+            received: Default::default(),
+            nonces: Default::default(),
+            w: None,
+        })
+    }
+
+    /// deal returns the deal of this participant, to be broadcasted to the
+    /// other participants.
+    pub fn deal(&mut self) -> Result<PresignDeal, Box<dyn Error>> {
+        self.deal_with_rng(&mut utils::rand_hack())
+    }
+
+    /// Same as deal, with the encryptions drawn from rng.
+    pub fn deal_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<PresignDeal, Box<dyn Error>> {
+        let polys: Vec<PriPoly<GE>> = self
+            .coeffs
+            .iter()
+            .map(|c| PriPoly::coefficients_to_pri_poly(c.clone()))
+            .collect();
+        let commits: Vec<Vec<Vec<u8>>> = polys
+            .iter()
+            .map(|p| {
+                let (_, commits) = p.commit(None).info();
+                commits.iter().map(GE::pk_to_key_slice).collect()
+            })
+            .collect();
+        let mut parts: Vec<PresignParts> = Vec::with_capacity(self.participants.len());
+        for (j, pub_k) in self.participants.iter().enumerate() {
+            let mut enc = polys
+                .iter()
+                .map(|p| encrypt_part(pub_k, &self.session_id, &p.eval(j as u32).v, rng));
+            parts.push(PresignParts {
+                k: enc.next().unwrap()?,
+                a: enc.next().unwrap()?,
+                b: enc.next().unwrap()?,
+                c: enc.next().unwrap()?,
+            });
+        }
+        let mut deal = PresignDeal {
+            index: self.index,
+            session_id: self.session_id.clone(),
+            k_commitments: commits[0].clone(),
+            a_commitments: commits[1].clone(),
+            b_commitments: commits[2][1..].to_vec(),
+            c_commitments: commits[3][1..].to_vec(),
+            parts,
+            signature: Vec::new(),
+        };
+        let pub_key: GE = self.participants[self.index as usize];
+        deal.signature = GE::sign_msg(
+            &self.long,
+            &pub_key,
+            &deal.hash(),
+            &self.index.to_le_bytes(),
+        )?;
+        let own = [
+            polys[0].eval(self.index).v,
+            polys[1].eval(self.index).v,
+            polys[2].eval(self.index).v,
+            polys[3].eval(self.index).v,
+        ];
+        self.received.insert(self.index, own);
+        self.nonces
+            .insert(self.index, polys[0].commit(None).commit());
+        Ok(deal)
+    }
+
+    /// process_deal verifies the deal of another participant and stores its
+    /// evaluations for this participant.
+    pub fn process_deal(&mut self, deal: &PresignDeal) -> Result<(), Box<dyn Error>> {
+        if self.received.contains_key(&deal.index) {
+            bail!("tecdsa: already received deal from same index");
+        }
+        let pub_k: &GE = self
+            .participants
+            .get(deal.index as usize)
+            .ok_or_else(|| simple_error!("tecdsa: deal index out of bounds"))?;
+        if !bitwise_eq(&deal.session_id, &self.session_id) {
+            bail!("tecdsa: deal with wrong session id");
+        }
+        GE::verify_signature(
+            pub_k,
+            &deal.signature,
+            &deal.hash(),
+            &deal.index.to_le_bytes(),
+        )
+        .map_err(|e| simple_error!("tecdsa: invalid signature in deal: {}", e))?;
+
+        let t = self.t as usize;
+        if deal.k_commitments.len() != t
+            || deal.a_commitments.len() != t
+            || deal.b_commitments.len() + 1 != 2 * t - 1
+            || deal.c_commitments.len() + 1 != 2 * t - 1
+            || deal.parts.len() != self.participants.len()
+        {
+            bail!("tecdsa: deal with wrong number of commitments or parts");
+        }
+        let pub_polys: Vec<PubPoly<GE>> = vec![
+            decode_pub_poly(&deal.k_commitments, false)?,
+            decode_pub_poly(&deal.a_commitments, false)?,
+            decode_pub_poly(&deal.b_commitments, true)?,
+            decode_pub_poly(&deal.c_commitments, true)?,
+        ];
+
+        let i = self.index;
+        let parts = deal
+            .parts
+            .get(i as usize)
+            .ok_or_else(|| simple_error!("tecdsa: no parts in the deal"))?;
+        let mut values = [FE::zero(); 4];
+        for (n, part) in [&parts.k, &parts.a, &parts.b, &parts.c].iter().enumerate() {
+            let v: FE = decrypt_part::<GE>(&self.long, &self.session_id, part)?;
+            if !pub_polys[n].check(&PriShare { i, v }) {
+                bail!("tecdsa: part not matching the commitments of the deal");
+            }
+            values[n] = v;
+        }
+        self.received.insert(deal.index, values);
+        self.nonces.insert(deal.index, pub_polys[0].commit());
+        Ok(())
+    }
+
+    /// share returns the share of this participant, to be broadcasted once the
+    /// deals of all the participants are processed.
+    pub fn share(&mut self) -> Result<PresignShare, Box<dyn Error>> {
+        if self.received.len() != self.participants.len() {
+            bail!("tecdsa: deals of some participants are missing");
+        }
+        let [k, a, b, _] = self.sums();
+        let w: FE = k.mul(&a.get_element()).add(&b.get_element());
+        let mut share = PresignShare {
+            index: self.index,
+            session_id: self.session_id.clone(),
+            w,
+            signature: Vec::new(),
+        };
+        let pub_key: GE = self.participants[self.index as usize];
+        share.signature = GE::sign_msg(
+            &self.long,
+            &pub_key,
+            &share.hash(),
+            &self.index.to_le_bytes(),
+        )?;
+        self.w = Some(w);
+        Ok(share)
+    }
+
+    /// presignature opens k * a from the shares of at least 2t-1 participants,
+    /// including this one, and returns the presignature of this participant.
+    /// It returns an error if the shares are inconsistent.
+    pub fn presignature(&self, shares: &[PresignShare]) -> Result<Presignature, Box<dyn Error>> {
+        let own: FE = self
+            .w
+            .ok_or_else(|| simple_error!("tecdsa: own share not computed yet"))?;
+        let mut values: HashMap<u32, FE> = HashMap::new();
+        for share in shares {
+            let pub_k: &GE = self
+                .participants
+                .get(share.index as usize)
+                .ok_or_else(|| simple_error!("tecdsa: share index out of bounds"))?;
+            if !bitwise_eq(&share.session_id, &self.session_id) {
+                bail!("tecdsa: share with wrong session id");
+            }
+            GE::verify_signature(
+                pub_k,
+                &share.signature,
+                &share.hash(),
+                &share.index.to_le_bytes(),
+            )
+            .map_err(|e| simple_error!("tecdsa: invalid signature in share: {}", e))?;
+            values.insert(share.index, share.w);
+        }
+        if values.insert(self.index, own).map_or(false, |w| w != own) {
+            bail!("tecdsa: share of this participant not matching its own");
+        }
+        let w: FE = open(&values, 2 * self.t - 1)?;
+        if w == FE::zero() {
+            bail!("tecdsa: k * a is zero");
+        }
+
+        let [_, a, _, c] = self.sums();
+        let big_r: GE = self
+            .nonces
+            .values()
+            .fold(GE::identity(), |acc, r| acc.add_point(&r.get_element()));
+        Ok(Presignature {
+            index: self.index,
+            session_id: self.session_id.clone(),
+            big_r,
+            kinv: a.mul(&w.invert().get_element()),
+            mask: c,
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    // sums returns the shares of this participant of k, a, b and c.
+    fn sums(&self) -> [FE; 4] {
+        let mut sums = [FE::zero(); 4];
+        for values in self.received.values() {
+            for (sum, v) in sums.iter_mut().zip(values.iter()) {
+                *sum = sum.add(&v.get_element());
+            }
+        }
+        sums
+    }
+}
+
+impl Presignature {
+    /// sign returns the partial signature of the 32 bytes hash of a message
+    /// with the share of the distributed key. The presignature is consumed,
+    /// as signing two messages with it reveals the key.
+    pub fn sign(
+        self,
+        share: &DistKeyShare<GE>,
+        hash: &[u8],
+    ) -> Result<PartialSignature, Box<dyn Error>> {
+        let x: PriShare<FE> = share.get_pri_share();
+        if x.i != self.index {
+            bail!("tecdsa: share of another participant");
+        }
+        let m: FE = hash_to_scalar(hash)?;
+        let r: FE = x_coordinate(&self.big_r)?;
+        let s: FE = m
+            .add(&r.mul(&x.v.get_element()).get_element())
+            .mul(&self.kinv.get_element())
+            .add(&self.mask.get_element());
+        Ok(PartialSignature {
+            index: self.index,
+            session_id: self.session_id,
+            s,
+        })
+    }
+}
+
+/// aggregate opens the signature of the 32 bytes hash of a message from the
+/// partial signatures of at least 2t-1 participants of the presigning whose
+/// nonce commitment is big_r, and checks it under the public key. It returns
+/// an error if the partial signatures are inconsistent or the signature
+/// invalid.
+pub fn aggregate(
+    public_key: &GE,
+    big_r: &GE,
+    hash: &[u8],
+    partials: &[PartialSignature],
+    t: u32,
+) -> Result<Signature, Box<dyn Error>> {
+    let session_id = &partials
+        .first()
+        .ok_or_else(|| simple_error!("tecdsa: no partial signature"))?
+        .session_id;
+    if partials
+        .iter()
+        .any(|p| !bitwise_eq(&p.session_id, session_id))
+    {
+        bail!("tecdsa: partial signatures of different presignings");
+    }
+    let values: HashMap<u32, FE> = partials.iter().map(|p| (p.index, p.s)).collect();
+    let s: FE = open(&values, 2 * t - 1)?;
+
+    // the parity of R, flipped along with s to keep s low
+    let mut recovery_id = big_r.pk_to_key_slice()[0] & 1;
+    let neg: FE = FE::zero().sub(&s.get_element());
+    let s = if neg.to_bytes() < s.to_bytes() {
+        recovery_id ^= 1;
+        neg
+    } else {
+        s
+    };
+    let sig = Signature {
+        r: x_coordinate(big_r)?,
+        s,
+        recovery_id,
+    };
+    verify(public_key, hash, &sig)?;
+    Ok(sig)
+}
+
+/// verify checks the ECDSA signature of the 32 bytes hash of a message under
+/// the public key.
+pub fn verify(public_key: &GE, hash: &[u8], sig: &Signature) -> Result<(), Box<dyn Error>> {
+    if sig.r == FE::zero() || sig.s == FE::zero() {
+        bail!("tecdsa: invalid signature");
+    }
+    let m: FE = hash_to_scalar(hash)?;
+    let sinv: FE = sig.s.invert();
+    let u1: FE = m.mul(&sinv.get_element());
+    let u2: FE = sig.r.mul(&sinv.get_element());
+    let x: GE = GE::generator()
+        .scalar_mul(&u1.get_element())
+        .add_point(&public_key.scalar_mul(&u2.get_element()).get_element());
+    if x == GE::identity() || x_coordinate(&x)? != sig.r {
+        bail!("tecdsa: invalid signature");
+    }
+    Ok(())
+}
+
+// open returns the value at 0 of the sharing of degree d-1 whose shares are
+// values, interpolated from d of them. The other shares must lie on the same
+// polynomial.
+fn open(values: &HashMap<u32, FE>, d: u32) -> Result<FE, Box<dyn Error>> {
+    if (values.len() as u32) < d {
+        bail!("tecdsa: not enough shares, {} needed", d);
+    }
+    let mut indices: Vec<u32> = values.keys().cloned().collect();
+    indices.sort_unstable();
+    let (base, extra) = indices.split_at(d as usize);
+    let interpolate = |at: Option<u32>| {
+        base.iter().fold(FE::zero(), |acc, &i| {
+            let l: FE = match at {
+                Some(at) => poly::lagrange_coefficient_at(i, base, at),
+                None => poly::lagrange_coefficient(i, base),
+            };
+            acc.add(&l.mul(&values[&i].get_element()).get_element())
+        })
+    };
+    for &j in extra {
+        if interpolate(Some(j)) != values[&j] {
+            bail!("tecdsa: inconsistent share of participant {}", j);
+        }
+    }
+    Ok(interpolate(None))
+}
+
+fn decode_pub_poly(commitments: &[Vec<u8>], zero: bool) -> Result<PubPoly<GE>, Box<dyn Error>> {
+    let mut commits: Vec<GE> = Vec::with_capacity(commitments.len() + 1);
+    if zero {
+        commits.push(GE::identity());
+    }
+    for c in commitments {
+        commits.push(GE::from_bytes(c)?);
+    }
+    Ok(PubPoly::new(GE::generator(), commits))
+}
+
+fn hash_to_scalar(hash: &[u8]) -> Result<FE, Box<dyn Error>> {
+    if hash.len() != 32 {
+        bail!("tecdsa: the hash of the message must be 32 bytes");
+    }
+    ECScalar::from_bytes(hash)
+}
+
+// x_coordinate returns the x-coordinate of the point reduced modulo the group
+// order, i.e. r for the nonce commitment R.
+fn x_coordinate(p: &GE) -> Result<FE, Box<dyn Error>> {
+    ECScalar::from_bytes(&p.pk_to_key_slice()[1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkg::DistKeyGenerator;
+    use crate::sim;
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_tecdsa() {
+        let (n, t) = (6, 3);
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(n);
+        let mut dkgs: Vec<DistKeyGenerator<GE>> = secs
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, pubs.clone(), t).unwrap())
+            .collect();
+        let network = sim::Network::new(n, Default::default(), ChaCha20Rng::seed_from_u64(42));
+        let shares: Vec<DistKeyShare<GE>> = sim::run_dkg(&network, &mut dkgs, 100)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        let public_key = shares[0].get_public_key();
+
+        let mut presigners: Vec<Presigner> = secs
+            .iter()
+            .zip(shares.iter())
+            .map(|(sec, share)| Presigner::new(*sec, share, pubs.clone(), b"0").unwrap())
+            .collect();
+        let deals: Vec<PresignDeal> = presigners.iter_mut().map(|p| p.deal().unwrap()).collect();
+        presigners[1]
+            .process_deal(&deals[1])
+            .expect_err("Must fail, already received");
+        for p in presigners.iter_mut() {
+            for deal in deals.iter().filter(|d| d.index != p.index()) {
+                p.process_deal(deal).unwrap();
+            }
+        }
+        let pre_shares: Vec<PresignShare> =
+            presigners.iter_mut().map(|p| p.share().unwrap()).collect();
+        presigners[0]
+            .presignature(&pre_shares[..(2 * t - 2) as usize])
+            .expect_err("Must fail, less than 2t-1 shares");
+        let presigs: Vec<Presignature> = presigners
+            .iter()
+            .map(|p| p.presignature(&pre_shares).unwrap())
+            .collect();
+        let big_r = presigs[0].big_r;
+        assert!(presigs.iter().all(|p| p.big_r == big_r));
+
+        let hash = Sha256::digest(b"Hello threshold ECDSA");
+        let partials: Vec<PartialSignature> = presigs
+            .into_iter()
+            .zip(shares.iter())
+            .map(|(p, share)| p.sign(share, &hash).unwrap())
+            .collect();
+        let sig = aggregate(&public_key, &big_r, &hash, &partials[1..], t).unwrap();
+        assert_eq!(64, sig.to_bytes().len());
+        verify(&public_key, &hash, &sig).unwrap();
+        let other = Sha256::digest(b"other message");
+        verify(&public_key, &other, &sig).expect_err("Must fail, wrong message");
+
+        // a wrong partial signature is detected with more than 2t-1 of them
+        let mut forged = partials.clone();
+        forged[5].s = FE::new_random();
+        aggregate(&public_key, &big_r, &hash, &forged, t).expect_err("Must fail, wrong partial");
+    }
+}
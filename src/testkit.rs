@@ -0,0 +1,210 @@
+//! Adversarial participants of the vss, enabled with the `testkit` feature, to
+//! test an orchestration of the protocol against Byzantine behavior.
+//!
+//! A MaliciousDealer holds an honest Dealer along with a shadow Dealer of the
+//! same longterm key and verifiers sharing another secret. It equivocates by
+//! sending the deals of the shadow session to some verifiers, sends wrong
+//! shares, or replays the deals of a previous session. A MaliciousVerifier
+//! complains about valid deals or withholds its responses.
+//!
+//! The misbehaving messages are signed with the keys of the participants, so
+//! that they reach the checks of the honest ones.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::ristretto_curve;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECScalar};
+use ristretto_curve::GE;
+use vss::{Dealer, EncryptedDeal, Response, Verifier};
+
+/// MaliciousDealer is a dealer misbehaving on demand.
+#[derive(Clone, Debug)]
+pub struct MaliciousDealer<P: CurvePoint = GE> {
+    // dealer of the session seen by the honest verifiers
+    dealer: Dealer<P>,
+    // dealer of the same key sharing another secret, to equivocate
+    shadow: Dealer<P>,
+}
+
+impl<P: CurvePoint> MaliciousDealer<P> {
+    /// new returns a MaliciousDealer of the given longterm key sharing the
+    /// secret among the verifiers, as vss::Dealer::new.
+    pub fn new(
+        longterm: P::Scalar,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        t: u32,
+    ) -> Result<MaliciousDealer<P>, Box<dyn Error>> {
+        let shadow_secret: P::Scalar = ECScalar::new_random();
+        Ok(MaliciousDealer {
+            dealer: Dealer::new(longterm, secret, verifiers.clone(), t)?,
+            shadow: Dealer::new(longterm, shadow_secret, verifiers, t)?,
+        })
+    }
+
+    /// dealer returns the dealer of the session, e.g. to process the
+    /// responses.
+    pub fn dealer(&mut self) -> &mut Dealer<P> {
+        &mut self.dealer
+    }
+
+    /// honest_deals returns the encrypted deals of the session.
+    pub fn honest_deals(&self) -> Result<Vec<EncryptedDeal<P>>, Box<dyn Error>> {
+        self.dealer.encrypt_deals()
+    }
+
+    /// equivocating_deals returns the encrypted deals of the session, except
+    /// for the verifiers of the targets which get the deals of another secret,
+    /// signed by the same dealer.
+    pub fn equivocating_deals(
+        &self,
+        targets: &[u32],
+    ) -> Result<Vec<EncryptedDeal<P>>, Box<dyn Error>> {
+        (0..self.dealer.deals.len() as u32)
+            .map(|i| {
+                if targets.contains(&i) {
+                    self.shadow.encrypt_deal(i)
+                } else {
+                    self.dealer.encrypt_deal(i)
+                }
+            })
+            .collect()
+    }
+
+    /// conflicting_deals returns two deals of different secrets for the
+    /// verifier at index i, both signed by the dealer.
+    pub fn conflicting_deals(
+        &self,
+        i: u32,
+    ) -> Result<(EncryptedDeal<P>, EncryptedDeal<P>), Box<dyn Error>> {
+        Ok((self.dealer.encrypt_deal(i)?, self.shadow.encrypt_deal(i)?))
+    }
+
+    /// corrupt_shares replaces the shares of the deals of the targets by
+    /// random ones, so that the deals don't verify against the commitments.
+    /// The justifications of the dealer reveal the corrupted deals.
+    pub fn corrupt_shares(&mut self, targets: &[u32]) {
+        for deal in self.dealer.deals.iter_mut() {
+            if targets.contains(&deal.sec_share.i) {
+                deal.sec_share.v = ECScalar::new_random();
+            }
+        }
+    }
+
+    /// replayed_deals returns the deals of a previous session of the dealer,
+    /// e.g. kept from a former run, to be sent again in the current one.
+    pub fn replayed_deals(previous: &[EncryptedDeal<P>]) -> Vec<EncryptedDeal<P>> {
+        previous.to_vec()
+    }
+}
+
+/// MaliciousVerifier is a verifier misbehaving on demand.
+#[derive(Clone, Debug)]
+pub struct MaliciousVerifier<P: CurvePoint = GE> {
+    verifier: Verifier<P>,
+}
+
+impl<P: CurvePoint> MaliciousVerifier<P> {
+    /// new returns a MaliciousVerifier of the given longterm key, as
+    /// vss::Verifier::new.
+    pub fn new(
+        longterm: P::Scalar,
+        dealer: P,
+        verifiers: Vec<P>,
+    ) -> Result<MaliciousVerifier<P>, Box<dyn Error>> {
+        Ok(MaliciousVerifier {
+            verifier: Verifier::new(longterm, dealer, verifiers)?,
+        })
+    }
+
+    /// verifier returns the underlying verifier, e.g. to process the
+    /// responses of the others.
+    pub fn verifier(&mut self) -> &mut Verifier<P> {
+        &mut self.verifier
+    }
+
+    /// false_complaint returns a signed complaint Response about the deal,
+    /// whatever its validity. The dealer answers it with a Justification.
+    pub fn false_complaint(
+        &mut self,
+        encr_d: &EncryptedDeal<P>,
+    ) -> Result<Response, Box<dyn Error>> {
+        Ok(self.verifier.complaint(encr_d)?.response)
+    }
+
+    /// withhold processes the deal without returning the response, which is
+    /// never sent to the other participants.
+    pub fn withhold(&mut self, encr_d: &EncryptedDeal<P>) -> Result<(), Box<dyn Error>> {
+        self.verifier.process_encrypted_deal(encr_d)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use crate::vss::EquivocationError;
+
+    #[test]
+    fn test_testkit() {
+        let (secs, pubs) = sim::new_participants::<GE>(6);
+        let (dealer_sec, verifiers_sec) = (secs[0], &secs[1..]);
+        let (dealer_pub, verifiers_pub) = (pubs[0], pubs[1..].to_vec());
+        let secret: ristretto_curve::FE = ECScalar::new_random();
+        let new_verifier = |i: usize| -> Verifier {
+            Verifier::new(verifiers_sec[i], dealer_pub, verifiers_pub.clone()).unwrap()
+        };
+        let mut dealer: MaliciousDealer =
+            MaliciousDealer::new(dealer_sec, secret, verifiers_pub.clone(), 3).unwrap();
+
+        // equivocation is detected by the verifier receiving both deals
+        let (first, second) = dealer.conflicting_deals(1).unwrap();
+        let mut verifier = new_verifier(1);
+        verifier.process_encrypted_deal(&first).unwrap();
+        let err = verifier.process_encrypted_deal(&second).unwrap_err();
+        assert!(err.downcast_ref::<EquivocationError>().is_some());
+        // and a replayed deal of another session too
+        let mut verifier = new_verifier(1);
+        verifier.process_encrypted_deal(&second).unwrap();
+        let replayed = MaliciousDealer::replayed_deals(&[first]);
+        verifier
+            .process_encrypted_deal(&replayed[0])
+            .expect_err("Must fail, deal of another session");
+
+        // split views: the targets end up in another session
+        let deals = dealer.equivocating_deals(&[4]).unwrap();
+        let resp = new_verifier(4).process_encrypted_deal(&deals[4]).unwrap();
+        assert!(dealer.dealer().process_response(&resp).is_err());
+
+        // wrong shares are complained about, and the justification reveals
+        // the invalid deal
+        dealer.corrupt_shares(&[2]);
+        let deals = dealer.honest_deals().unwrap();
+        let resp = new_verifier(2).process_encrypted_deal(&deals[2]).unwrap();
+        assert!(!resp.approved);
+        let justification = dealer.dealer().process_response(&resp).unwrap().unwrap();
+        justification
+            .verify(&dealer_pub, &verifiers_pub, &resp.session_id)
+            .expect_err("Must fail, invalid deal revealed");
+
+        // a false complaint is answered by a valid justification
+        let mut liar: MaliciousVerifier =
+            MaliciousVerifier::new(verifiers_sec[0], dealer_pub, verifiers_pub.clone()).unwrap();
+        let resp = liar.false_complaint(&deals[0]).unwrap();
+        assert!(!resp.approved);
+        let justification = dealer.dealer().process_response(&resp).unwrap().unwrap();
+        justification
+            .verify(&dealer_pub, &verifiers_pub, &resp.session_id)
+            .unwrap();
+
+        // a withheld response never reaches the dealer
+        let mut silent: MaliciousVerifier =
+            MaliciousVerifier::new(verifiers_sec[3], dealer_pub, verifiers_pub.clone()).unwrap();
+        silent.withhold(&deals[3]).unwrap();
+        assert!(!dealer.dealer().responses().contains_key(&3));
+    }
+}
@@ -0,0 +1,123 @@
+//! Choice of the threshold t of a sharing among n participants.
+//!
+//! A threshold t keeps the secret from any f < t faulty participants, and lets
+//! the n - f honest ones recover it as long as t <= n - f. A FaultModel
+//! bounds f in terms of n: an honest majority tolerates f < n/2, which is the
+//! vss::minimum_t of the protocol, while a Byzantine setting tolerates
+//! f < n/3 and requires the quorum 2f+1 of the BFT protocols. validate checks
+//! a threshold against the model of a deployment instead of hard-coding one
+//! formula.
+
+use crate::error::Error;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use crate::vss;
+
+/// FaultModel bounds the number of faulty participants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultModel {
+    /// Less than half of the participants are faulty.
+    HonestMajority,
+    /// Less than a third of the participants are faulty, as in BFT protocols.
+    Byzantine,
+    /// At most the given number of participants are faulty.
+    Faults(u32),
+}
+
+impl FaultModel {
+    /// max_faults returns the number of faulty participants among n
+    /// tolerated by the model.
+    pub fn max_faults(&self, n: u32) -> u32 {
+        match self {
+            FaultModel::HonestMajority => n.saturating_sub(1) / 2,
+            FaultModel::Byzantine => n.saturating_sub(1) / 3,
+            FaultModel::Faults(f) => *f,
+        }
+    }
+
+    /// threshold returns the recommended threshold of the model for n
+    /// participants.
+    pub fn threshold(&self, n: u32) -> u32 {
+        match self {
+            FaultModel::HonestMajority => honest_majority_t(n),
+            FaultModel::Byzantine => bft_t(n),
+            FaultModel::Faults(f) => f + 1,
+        }
+    }
+}
+
+/// honest_majority_t returns the threshold (n+1)/2 of an honest majority,
+/// vss::minimum_t.
+pub fn honest_majority_t(n: u32) -> u32 {
+    vss::minimum_t(n)
+}
+
+/// bft_t returns the BFT quorum floor(2n/3)+1, i.e. 2f+1 of n = 3f+1
+/// participants, capped to n.
+pub fn bft_t(n: u32) -> u32 {
+    (2 * n / 3 + 1).min(n)
+}
+
+/// max_faults returns the number of faulty participants tolerated by a
+/// threshold t among n participants, both to keep the secret and to recover
+/// it: min(t-1, n-t).
+pub fn max_faults(n: u32, t: u32) -> u32 {
+    t.saturating_sub(1).min(n.saturating_sub(t))
+}
+
+/// validate checks the threshold t of n participants against the fault
+/// model: the faulty participants of the model can't recover the secret on
+/// their own, and the others can without them.
+pub fn validate(n: u32, t: u32, model: FaultModel) -> Result<(), Box<dyn Error>> {
+    if t < 2 || t > n {
+        bail!(
+            "thresholds: invalid threshold {} for {} participants, it must be between 2 and {}",
+            t,
+            n,
+            n
+        );
+    }
+    let f = model.max_faults(n);
+    if t <= f {
+        bail!(
+            "thresholds: threshold {} recoverable by {} faulty participants",
+            t,
+            f
+        );
+    }
+    if t > n - f {
+        bail!(
+            "thresholds: threshold {} not reachable by the {} honest participants",
+            t,
+            n - f
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(3, honest_majority_t(5));
+        assert_eq!(3, bft_t(4));
+        assert_eq!(5, bft_t(7));
+        assert_eq!(2, bft_t(2));
+        assert_eq!(2, max_faults(7, 5));
+        assert_eq!(1, max_faults(7, 2));
+
+        for n in 3..20 {
+            for model in [FaultModel::HonestMajority, FaultModel::Byzantine].iter() {
+                validate(n, model.threshold(n), *model).unwrap();
+                assert!(max_faults(n, model.threshold(n)) >= model.max_faults(n));
+            }
+        }
+        validate(7, 2, FaultModel::Byzantine).expect_err("Must fail, 2 faulty recover");
+        validate(7, 6, FaultModel::Byzantine).expect_err("Must fail, 5 honest can't recover");
+        validate(7, 4, FaultModel::Faults(3)).unwrap();
+        validate(7, 4, FaultModel::Faults(4)).expect_err("Must fail, too many faults");
+        validate(3, 4, FaultModel::HonestMajority).expect_err("Must fail, t > n");
+    }
+}
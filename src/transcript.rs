@@ -0,0 +1,458 @@
+//! Transcripts of the vss protocol, to audit a disputed ceremony.
+//!
+//! A RecordingDealer or RecordingVerifier wraps a Dealer or a Verifier and
+//! records in a Transcript every message it receives and sends, in order,
+//! along with whether a received message was accepted. replay_dealer and
+//! replay_verifier run the initial state of the participant over the transcript
+//! again and return the final state. The replay fails if it diverges from the
+//! transcript, i.e. if a message isn't accepted or rejected as recorded, or if
+//! the participant sends other messages than the recorded ones.
+//!
+//! Signatures are randomized: the messages sent during the replay are compared
+//! with the recorded ones on their signed content, and the encrypted deals on
+//! their session only.
+
+use std::error::Error;
+use std::time::Instant;
+
+use crate::curve_traits;
+use crate::ristretto_curve;
+use crate::signer;
+use crate::storage;
+use crate::vss;
+
+use curve_traits::CurvePoint;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use signer::{KeyAgreement, Signer, SoftwareSigner};
+use storage::Snapshot;
+use vss::{Complaint, Dealer, EncryptedDeal, Justification, Response, Verifier};
+
+/// Direction of a recorded message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// TranscriptMessage is a message of the vss protocol, or the timeout of the
+/// responses.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum TranscriptMessage<P: CurvePoint = GE> {
+    // Encrypted deal for the verifier at the given index
+    EncryptedDeal(u32, EncryptedDeal<P>),
+    Response(Response),
+    Justification(Justification<P>),
+    Complaint(Complaint<P>),
+    Timeout,
+}
+
+/// Record is a message of a transcript.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Record<P: CurvePoint = GE> {
+    pub direction: Direction,
+    pub message: TranscriptMessage<P>,
+    // Whether an inbound message was processed without error, always true for
+    // an outbound message
+    pub accepted: bool,
+}
+
+/// Transcript is the ordered list of the messages received and sent by a
+/// participant.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Transcript<P: CurvePoint = GE> {
+    records: Vec<Record<P>>,
+}
+
+impl<P: CurvePoint> Transcript<P> {
+    pub fn new() -> Transcript<P> {
+        Transcript {
+            records: Vec::new(),
+        }
+    }
+
+    pub fn records(&self) -> &[Record<P>] {
+        &self.records
+    }
+
+    fn inbound<T>(&mut self, message: TranscriptMessage<P>, result: &Result<T, Box<dyn Error>>) {
+        self.records.push(Record {
+            direction: Direction::Inbound,
+            message,
+            accepted: result.is_ok(),
+        });
+    }
+
+    fn outbound(&mut self, message: TranscriptMessage<P>) {
+        self.records.push(Record {
+            direction: Direction::Outbound,
+            message,
+            accepted: true,
+        });
+    }
+}
+
+impl<P: CurvePoint> Snapshot for Transcript<P> {}
+
+/// RecordingDealer is a Dealer recording its transcript.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "K: Serialize", deserialize = "K: Deserialize<'de>"))]
+pub struct RecordingDealer<P: CurvePoint = GE, K = SoftwareSigner<P>> {
+    dealer: Dealer<P, K>,
+    transcript: Transcript<P>,
+}
+
+impl<P: CurvePoint, K: Signer<P>> RecordingDealer<P, K> {
+    pub fn new(dealer: Dealer<P, K>) -> RecordingDealer<P, K> {
+        RecordingDealer {
+            dealer,
+            transcript: Transcript::new(),
+        }
+    }
+
+    pub fn dealer(&self) -> &Dealer<P, K> {
+        &self.dealer
+    }
+
+    pub fn transcript(&self) -> &Transcript<P> {
+        &self.transcript
+    }
+
+    /// into_parts returns the dealer and its transcript.
+    pub fn into_parts(self) -> (Dealer<P, K>, Transcript<P>) {
+        (self.dealer, self.transcript)
+    }
+
+    /// encrypt_deals is Dealer::encrypt_deals, recording the deals.
+    pub fn encrypt_deals(&mut self) -> Result<Vec<EncryptedDeal<P>>, Box<dyn Error>> {
+        let deals = self.dealer.encrypt_deals()?;
+        for (i, d) in deals.iter().enumerate() {
+            self.transcript
+                .outbound(TranscriptMessage::EncryptedDeal(i as u32, d.clone()));
+        }
+        Ok(deals)
+    }
+
+    /// process_response is Dealer::process_response, recording the response
+    /// and the justification if any.
+    pub fn process_response(
+        &mut self,
+        r: &Response,
+    ) -> Result<Option<Justification<P>>, Box<dyn Error>> {
+        let result = self.dealer.process_response(r);
+        self.transcript
+            .inbound(TranscriptMessage::Response(r.clone()), &result);
+        if let Ok(Some(j)) = &result {
+            self.transcript
+                .outbound(TranscriptMessage::Justification(j.clone()));
+        }
+        result
+    }
+
+    pub fn set_timeout(&mut self) {
+        self.dealer.set_timeout();
+        self.transcript
+            .inbound::<()>(TranscriptMessage::Timeout, &Ok(()));
+    }
+
+    /// tick is Dealer::tick, recording the timeout if it happens.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let timeout = self.dealer.tick(now);
+        if timeout {
+            self.transcript
+                .inbound::<()>(TranscriptMessage::Timeout, &Ok(()));
+        }
+        timeout
+    }
+}
+
+/// RecordingVerifier is a Verifier recording its transcript.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "K: Serialize", deserialize = "K: Deserialize<'de>"))]
+pub struct RecordingVerifier<P: CurvePoint = GE, K = SoftwareSigner<P>> {
+    verifier: Verifier<P, K>,
+    transcript: Transcript<P>,
+}
+
+impl<P: CurvePoint, K: Signer<P> + KeyAgreement<P>> RecordingVerifier<P, K> {
+    pub fn new(verifier: Verifier<P, K>) -> RecordingVerifier<P, K> {
+        RecordingVerifier {
+            verifier,
+            transcript: Transcript::new(),
+        }
+    }
+
+    pub fn verifier(&self) -> &Verifier<P, K> {
+        &self.verifier
+    }
+
+    pub fn transcript(&self) -> &Transcript<P> {
+        &self.transcript
+    }
+
+    /// into_parts returns the verifier and its transcript.
+    pub fn into_parts(self) -> (Verifier<P, K>, Transcript<P>) {
+        (self.verifier, self.transcript)
+    }
+
+    /// process_encrypted_deal is Verifier::process_encrypted_deal, recording
+    /// the deal and the response.
+    pub fn process_encrypted_deal(
+        &mut self,
+        encr_d: &EncryptedDeal<P>,
+    ) -> Result<Response, Box<dyn Error>> {
+        let result = self.verifier.process_encrypted_deal(encr_d);
+        let index = self.verifier.index();
+        self.transcript.inbound(
+            TranscriptMessage::EncryptedDeal(index, encr_d.clone()),
+            &result,
+        );
+        if let Ok(r) = &result {
+            self.transcript
+                .outbound(TranscriptMessage::Response(r.clone()));
+        }
+        result
+    }
+
+    pub fn process_response(&mut self, r: &Response) -> Result<(), Box<dyn Error>> {
+        let result = self.verifier.process_response(r);
+        self.transcript
+            .inbound(TranscriptMessage::Response(r.clone()), &result);
+        result
+    }
+
+    pub fn process_justification(&mut self, j: &Justification<P>) -> Result<(), Box<dyn Error>> {
+        let result = self.verifier.process_justification(j);
+        self.transcript
+            .inbound(TranscriptMessage::Justification(j.clone()), &result);
+        result
+    }
+
+    pub fn process_complaint(&mut self, c: &Complaint<P>) -> Result<bool, Box<dyn Error>> {
+        let result = self.verifier.process_complaint(c);
+        self.transcript
+            .inbound(TranscriptMessage::Complaint(c.clone()), &result);
+        result
+    }
+
+    pub fn set_timeout(&mut self) {
+        self.verifier.set_timeout();
+        self.transcript
+            .inbound::<()>(TranscriptMessage::Timeout, &Ok(()));
+    }
+
+    /// tick is Verifier::tick, recording the timeout if it happens.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let timeout = self.verifier.tick(now);
+        if timeout {
+            self.transcript
+                .inbound::<()>(TranscriptMessage::Timeout, &Ok(()));
+        }
+        timeout
+    }
+}
+
+/// replay_dealer runs the dealer, in its state at the start of the transcript,
+/// over the transcript and returns its final state.
+pub fn replay_dealer<P: CurvePoint, K: Signer<P>>(
+    mut dealer: Dealer<P, K>,
+    transcript: &Transcript<P>,
+) -> Result<Dealer<P, K>, Box<dyn Error>> {
+    let mut sent: Vec<TranscriptMessage<P>> = Vec::new();
+    for (i, record) in transcript.records.iter().enumerate() {
+        if record.direction == Direction::Outbound {
+            check_sent(i, &mut sent, &record.message)?;
+            continue;
+        }
+        check_all_sent(i, &sent)?;
+        let accepted = match &record.message {
+            TranscriptMessage::Response(r) => match dealer.process_response(r) {
+                Ok(Some(j)) => {
+                    sent.push(TranscriptMessage::Justification(j));
+                    true
+                }
+                Ok(None) => true,
+                Err(_) => false,
+            },
+            TranscriptMessage::Timeout => {
+                dealer.set_timeout();
+                true
+            }
+            _ => bail!("transcript: record {} isn't a message for a dealer", i),
+        };
+        check_accepted(i, record, accepted)?;
+    }
+    check_all_sent(transcript.records.len(), &sent)?;
+    Ok(dealer)
+}
+
+/// replay_verifier runs the verifier, in its state at the start of the
+/// transcript, over the transcript and returns its final state.
+pub fn replay_verifier<P: CurvePoint, K: Signer<P> + KeyAgreement<P>>(
+    mut verifier: Verifier<P, K>,
+    transcript: &Transcript<P>,
+) -> Result<Verifier<P, K>, Box<dyn Error>> {
+    let mut sent: Vec<TranscriptMessage<P>> = Vec::new();
+    for (i, record) in transcript.records.iter().enumerate() {
+        if record.direction == Direction::Outbound {
+            check_sent(i, &mut sent, &record.message)?;
+            continue;
+        }
+        check_all_sent(i, &sent)?;
+        let accepted = match &record.message {
+            TranscriptMessage::EncryptedDeal(_, d) => match verifier.process_encrypted_deal(d) {
+                Ok(r) => {
+                    sent.push(TranscriptMessage::Response(r));
+                    true
+                }
+                Err(_) => false,
+            },
+            TranscriptMessage::Response(r) => verifier.process_response(r).is_ok(),
+            TranscriptMessage::Justification(j) => verifier.process_justification(j).is_ok(),
+            TranscriptMessage::Complaint(c) => verifier.process_complaint(c).is_ok(),
+            TranscriptMessage::Timeout => {
+                verifier.set_timeout();
+                true
+            }
+        };
+        check_accepted(i, record, accepted)?;
+    }
+    check_all_sent(transcript.records.len(), &sent)?;
+    Ok(verifier)
+}
+
+fn check_accepted<P: CurvePoint>(
+    i: usize,
+    record: &Record<P>,
+    accepted: bool,
+) -> Result<(), Box<dyn Error>> {
+    if record.accepted != accepted {
+        bail!(
+            "transcript: replay diverges at record {}, accepted: {}",
+            i,
+            accepted
+        );
+    }
+    Ok(())
+}
+
+// check_sent checks that the recorded outbound message is the next one sent
+// during the replay.
+fn check_sent<P: CurvePoint>(
+    i: usize,
+    sent: &mut Vec<TranscriptMessage<P>>,
+    recorded: &TranscriptMessage<P>,
+) -> Result<(), Box<dyn Error>> {
+    let same = match recorded {
+        // the deals are sent by the dealer before the replayed messages
+        TranscriptMessage::EncryptedDeal(..) => return Ok(()),
+        TranscriptMessage::Response(a) => match sent.first() {
+            Some(TranscriptMessage::Response(b)) => a.hash_self()? == b.hash_self()?,
+            _ => false,
+        },
+        TranscriptMessage::Justification(a) => match sent.first() {
+            Some(TranscriptMessage::Justification(b)) => {
+                Justification::hash(&a.session_id, a.index, &a.deal)?
+                    == Justification::hash(&b.session_id, b.index, &b.deal)?
+            }
+            _ => false,
+        },
+        _ => false,
+    };
+    if !same {
+        bail!("transcript: replay sends another message at record {}", i);
+    }
+    sent.remove(0);
+    Ok(())
+}
+
+fn check_all_sent<P: CurvePoint>(
+    i: usize,
+    sent: &[TranscriptMessage<P>],
+) -> Result<(), Box<dyn Error>> {
+    if !sent.is_empty() {
+        bail!("transcript: replay sends a message missing at record {}", i);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_traits::ECScalar;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+
+    #[test]
+    fn test_transcript_replay() {
+        let n = 5;
+        let (secs, pubs) = sim::new_participants::<GE>(n);
+        let (dealer_sec, dealer_pub) = sim::new_participants::<GE>(1);
+        let secret: FE = ECScalar::new_random();
+        let mut dealer: Dealer = Dealer::new(dealer_sec[0], secret, pubs.clone(), 3).unwrap();
+        // the deal of verifier 1 is invalid
+        dealer.deals[1].sec_share.v = ECScalar::new_random();
+        let initial_dealer = dealer.clone();
+        let mut dealer = RecordingDealer::new(dealer);
+
+        let initial: Vec<Verifier> = secs
+            .iter()
+            .map(|s| Verifier::new(*s, dealer_pub[0], pubs.clone()).unwrap())
+            .collect();
+        let mut verifiers: Vec<RecordingVerifier> = initial
+            .iter()
+            .cloned()
+            .map(RecordingVerifier::new)
+            .collect();
+
+        let deals = dealer.encrypt_deals().unwrap();
+        let mut responses: Vec<Response> = Vec::new();
+        for (i, v) in verifiers.iter_mut().enumerate() {
+            responses.push(v.process_encrypted_deal(&deals[i]).unwrap());
+        }
+        // a deal received twice is rejected
+        assert!(verifiers[0].process_encrypted_deal(&deals[0]).is_err());
+        let mut justifications: Vec<Justification> = Vec::new();
+        for r in responses.iter() {
+            if let Some(j) = dealer.process_response(r).unwrap() {
+                justifications.push(j);
+            }
+            for v in verifiers.iter_mut() {
+                if v.verifier().index() != r.index {
+                    v.process_response(r).unwrap();
+                }
+            }
+        }
+        // the justification reveals the invalid deal
+        assert_eq!(1, justifications.len());
+        for v in verifiers.iter_mut() {
+            assert!(v.process_justification(&justifications[0]).is_err());
+        }
+        dealer.set_timeout();
+
+        let (dealer, transcript) = dealer.into_parts();
+        let transcript = Transcript::restore(&transcript.snapshot().unwrap()).unwrap();
+        let replayed = replay_dealer(initial_dealer.clone(), &transcript).unwrap();
+        assert_eq!(dealer.status(), replayed.status());
+
+        let mut transcripts: Vec<Transcript> = Vec::new();
+        for (v, init) in verifiers.into_iter().zip(initial.into_iter()) {
+            let (v, transcript) = v.into_parts();
+            let replayed = replay_verifier(init.clone(), &transcript).unwrap();
+            assert_eq!(v.status(), replayed.status());
+            assert_eq!(v.deal_certified(), replayed.deal_certified());
+
+            // a transcript with a missing message doesn't replay
+            let mut truncated = transcript.clone();
+            truncated.records.remove(1);
+            assert!(replay_verifier(init, &truncated).is_err());
+            transcripts.push(transcript);
+        }
+
+        // the transcript of a verifier isn't one of a dealer
+        assert!(replay_dealer(initial_dealer, &transcripts[0]).is_err());
+    }
+}
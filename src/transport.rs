@@ -0,0 +1,51 @@
+//! Transport abstraction over which the participants of a DKG exchange their
+//! messages.
+//!
+//! The protocol types do not depend on any network: they consume and produce
+//! messages, and a Transport carries them between participants. Deals are sent
+//! point-to-point to their recipient while all the other messages are
+//! broadcasted to every other participant. Where the broadcast is made of
+//! point-to-point links, responses and justifications can be carried by the
+//! echo broadcast of the broadcast module.
+
+use std::error::Error;
+
+use crate::broadcast;
+use crate::curve_traits;
+use crate::dkg;
+use crate::ristretto_curve;
+
+use curve_traits::CurvePoint;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+
+/// Message is any message of the DKG protocol.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum Message<P: CurvePoint = GE> {
+    Deal(dkg::Deal<P>),
+    Response(dkg::Response),
+    Justification(dkg::Justification<P>),
+    SecretCommits(dkg::SecretCommits),
+    ComplaintCommits(dkg::ComplaintCommits<P>),
+    ReconstructCommits(dkg::ReconstructCommits<P>),
+    // Echo broadcast of a response or a justification
+    Echo(broadcast::EchoMessage<P>),
+}
+
+/// Transport delivers the messages of a participant, identified by its index in
+/// the list of participants, to the other participants.
+pub trait Transport<P: CurvePoint = GE> {
+    /// index returns the index of the local participant.
+    fn index(&self) -> u32;
+
+    /// send delivers msg to the participant at index to only.
+    fn send(&mut self, to: u32, msg: Message<P>) -> Result<(), Box<dyn Error>>;
+
+    /// broadcast delivers msg to every other participant.
+    fn broadcast(&mut self, msg: Message<P>) -> Result<(), Box<dyn Error>>;
+
+    /// recv returns the next received message along with the index of its
+    /// sender, or None if no message is pending.
+    fn recv(&mut self) -> Result<Option<(u32, Message<P>)>, Box<dyn Error>>;
+}
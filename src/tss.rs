@@ -0,0 +1,692 @@
+//! Threshold Schnorr signatures (FROST)
+//!
+//! Two-round signing protocol on top of the distributed key generated by the
+//! dkg module. In the first round every signer broadcasts a commitment to a pair
+//! of fresh nonces, in the second round it issues a signature share. Any t
+//! shares are aggregated into a Schnorr signature verifiable with dss::verify.
+//!
+//! With an adaptor point T, the signers issue instead shares of a pre-signature
+//! (R, z'), whose nonce is offset by T. The holder of the discrete log t of T
+//! completes it into the signature (R + T, z' + t), and t is extracted from the
+//! pre-signature and the signature, e.g. for atomic swaps.
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::poly;
+use crate::ristretto_curve;
+
+use curve_traits::{CurvePoint, ECScalar};
+use dkg::DistKeyShare;
+use poly::{LagrangeCoefficients, PubPoly, PubShare};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+
+/// SigningCommitments is broadcasted by a signer in the first round. It commits
+/// to the hiding and binding nonces of the signer, which can only be used once.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SigningCommitments<P: CurvePoint = GE> {
+    // Index of the signer in the list of participants of the dkg
+    pub index: u32,
+    // Commitment to the hiding nonce
+    pub hiding: P,
+    // Commitment to the binding nonce
+    pub binding: P,
+}
+
+/// PreSignature is the aggregation of signature shares issued for an adaptor
+/// point, which the discrete log of the adaptor completes into a signature.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PreSignature<P: CurvePoint = GE> {
+    // Group commitment R of the signers, without the adaptor
+    pub r: P,
+    // Adaptor point T
+    pub adaptor: P,
+    // z' = z - t
+    pub z: P::Scalar,
+}
+
+/// SignatureShare is issued by a signer in the second round and sent to the
+/// aggregator.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SignatureShare<P: CurvePoint = GE> {
+    // Index of the signer in the list of participants of the dkg
+    pub index: u32,
+    // z_i = d_i + e_i * rho_i + lambda_i * s_i * c
+    pub share: P::Scalar,
+}
+
+// Secret nonces matching the SigningCommitments of a signer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct SigningNonces<P: CurvePoint = GE> {
+    hiding: P::Scalar,
+    binding: P::Scalar,
+}
+
+/// Signer holds the distributed key share of a participant and its nonces
+/// between the two rounds of the protocol.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Signer<P: CurvePoint = GE> {
+    // Distributed key share generated by the dkg
+    share: DistKeyShare<P>,
+    // Public polynomial of the distributed key
+    pub_poly: PubPoly<P>,
+    // threshold
+    t: u32,
+    // Nonces committed to in the first round, consumed by the second one
+    nonces: Option<SigningNonces<P>>,
+    // Lagrange coefficients of the sets of signers already seen
+    #[serde(skip)]
+    lagrange: LagrangeCoefficients<P>,
+}
+
+impl<P: CurvePoint> Signer<P> {
+    /// Create a Signer out of the distributed key share of this node and the
+    /// threshold t used during the dkg.
+    pub fn new(share: DistKeyShare<P>, t: u32) -> Signer<P> {
+        let pub_poly = share.pub_poly();
+        Signer {
+            share,
+            pub_poly,
+            t,
+            nonces: None,
+            lagrange: LagrangeCoefficients::new(),
+        }
+    }
+
+    /// index returns the index of this signer in the list of participants.
+    pub fn index(&self) -> u32 {
+        self.share.get_pri_share().i
+    }
+
+    /// public_key returns the distributed public key.
+    pub fn public_key(&self) -> P {
+        self.share.get_public_key()
+    }
+
+    /// commit generates fresh nonces and returns the commitments which must be
+    /// broadcasted to the other signers. Calling it again discards the previous
+    /// nonces.
+    pub fn commit(&mut self) -> SigningCommitments<P> {
+        let nonces: SigningNonces<P> = SigningNonces {
+            hiding: ECScalar::new_random(),
+            binding: ECScalar::new_random(),
+        };
+        let generator = P::generator();
+        let commitments = SigningCommitments {
+            index: self.index(),
+            hiding: generator.scalar_mul(&nonces.hiding.get_element()),
+            binding: generator.scalar_mul(&nonces.binding.get_element()),
+        };
+        self.nonces = Some(nonces);
+        commitments
+    }
+
+    /// sign issues the signature share of this signer for msg. `commitments` are
+    /// the commitments of all the signers taking part to this signature,
+    /// including ours. The nonces are consumed, so a new commit() is needed
+    /// before the next signature.
+    pub fn sign(
+        &mut self,
+        msg: &[u8],
+        commitments: &[SigningCommitments<P>],
+    ) -> Result<SignatureShare<P>, Box<dyn Error>> {
+        let public: P = self.public_key();
+        self.sign_with(msg, commitments, |r| Ok(challenge(r, &public, msg)))
+    }
+
+    /// sign_adaptor issues the signature share of this signer for msg under
+    /// the adaptor point, see sign. The shares aggregate into a PreSignature.
+    pub fn sign_adaptor(
+        &mut self,
+        msg: &[u8],
+        adaptor: &P,
+        commitments: &[SigningCommitments<P>],
+    ) -> Result<SignatureShare<P>, Box<dyn Error>> {
+        let public: P = self.public_key();
+        self.sign_with(&adaptor_msg(msg, adaptor), commitments, |r| {
+            Ok(adaptor_challenge(r, adaptor, &public, msg))
+        })
+    }
+
+    // sign_with is sign with the challenge of another Schnorr scheme, computed
+    // from the group commitment R. msg is bound to the binding factors.
+    pub(crate) fn sign_with<F>(
+        &mut self,
+        msg: &[u8],
+        commitments: &[SigningCommitments<P>],
+        challenge: F,
+    ) -> Result<SignatureShare<P>, Box<dyn Error>>
+    where
+        F: Fn(&P) -> Result<P::Scalar, Box<dyn Error>>,
+    {
+        check_commitments(commitments, self.t)?;
+
+        let index = self.index();
+        let own = commitments
+            .iter()
+            .find(|c| c.index == index)
+            .ok_or_else(|| simple_error!("tss: own commitments not found"))?;
+
+        let nonces = self
+            .nonces
+            .take()
+            .ok_or_else(|| simple_error!("tss: no nonces, commit must be called first"))?;
+        let generator = P::generator();
+        if *own
+            != (SigningCommitments {
+                index,
+                hiding: generator.scalar_mul(&nonces.hiding.get_element()),
+                binding: generator.scalar_mul(&nonces.binding.get_element()),
+            })
+        {
+            bail!("tss: own commitments don't match the nonces");
+        }
+
+        let rho: P::Scalar = binding_factor(index, msg, commitments);
+        let c: P::Scalar = challenge(&group_commitment(msg, commitments))?;
+        let lambda: P::Scalar = lagrange_coefficient(&mut self.lagrange, index, commitments)?;
+
+        // z_i = d_i + e_i * rho_i + lambda_i * s_i * c
+        let share: P::Scalar = lambda
+            .mul(&self.share.get_pri_share().v.get_element())
+            .mul(&c.get_element())
+            .add(&nonces.binding.mul(&rho.get_element()).get_element())
+            .add(&nonces.hiding.get_element());
+
+        Ok(SignatureShare { index, share })
+    }
+
+    /// verify_share checks the signature share of another signer, see
+    /// verify_signature_share.
+    pub fn verify_share(
+        &self,
+        msg: &[u8],
+        commitments: &[SigningCommitments<P>],
+        share: &SignatureShare<P>,
+    ) -> Result<(), Box<dyn Error>> {
+        verify_signature_share(&self.pub_poly, msg, commitments, share)
+    }
+}
+
+/// verify_signature_share checks a signature share against the public
+/// polynomial of the distributed key. It returns an error if the share is
+/// invalid, which identifies the misbehaving signer.
+pub fn verify_signature_share<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    msg: &[u8],
+    commitments: &[SigningCommitments<P>],
+    share: &SignatureShare<P>,
+) -> Result<(), Box<dyn Error>> {
+    let c: P::Scalar = challenge(&group_commitment(msg, commitments), &pub_poly.commit(), msg);
+    verify_share_with(pub_poly, msg, commitments, share, &c)
+}
+
+// verify_share_with is verify_signature_share for the challenge c of another
+// Schnorr scheme.
+pub(crate) fn verify_share_with<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    msg: &[u8],
+    commitments: &[SigningCommitments<P>],
+    share: &SignatureShare<P>,
+    c: &P::Scalar,
+) -> Result<(), Box<dyn Error>> {
+    let mut lagrange = LagrangeCoefficients::new();
+    verify_share_cached(pub_poly, msg, commitments, share, c, &mut lagrange)
+}
+
+// verify_share_cached is verify_share_with taking the Lagrange coefficient of
+// the signer from lagrange, shared by the verifications of the shares of one
+// set of signers.
+fn verify_share_cached<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    msg: &[u8],
+    commitments: &[SigningCommitments<P>],
+    share: &SignatureShare<P>,
+    c: &P::Scalar,
+    lagrange: &mut LagrangeCoefficients<P>,
+) -> Result<(), Box<dyn Error>> {
+    check_commitments(commitments, pub_poly.threshold())?;
+
+    let comm = commitments
+        .iter()
+        .find(|c| c.index == share.index)
+        .ok_or_else(|| simple_error!("tss: no commitments for signature share"))?;
+
+    let rho: P::Scalar = binding_factor(share.index, msg, commitments);
+    let lambda: P::Scalar = lagrange_coefficient(lagrange, share.index, commitments)?;
+    let pub_share: PubShare<P> = pub_poly.eval(share.index);
+
+    // z_i * G == D_i + rho_i * E_i + (c * lambda_i) * Y_i
+    let left: P = P::generator().scalar_mul(&share.share.get_element());
+    let right: P = pub_share
+        .v
+        .scalar_mul(&c.mul(&lambda.get_element()).get_element())
+        .add_point(&comm.binding.scalar_mul(&rho.get_element()).get_element())
+        .add_point(&comm.hiding.get_element());
+
+    if left != right {
+        bail!("tss: invalid signature share from {}", share.index);
+    }
+
+    Ok(())
+}
+
+/// verify_adaptor_signature_share is verify_signature_share for a share
+/// issued under the adaptor point.
+pub fn verify_adaptor_signature_share<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    msg: &[u8],
+    adaptor: &P,
+    commitments: &[SigningCommitments<P>],
+    share: &SignatureShare<P>,
+) -> Result<(), Box<dyn Error>> {
+    let binding_msg = adaptor_msg(msg, adaptor);
+    let r: P = group_commitment(&binding_msg, commitments);
+    let c: P::Scalar = adaptor_challenge(&r, adaptor, &pub_poly.commit(), msg);
+    verify_share_with(pub_poly, &binding_msg, commitments, share, &c)
+}
+
+/// aggregate verifies the signature shares and combines them into a Schnorr
+/// signature R || z for msg, which can be checked with dss::verify against the
+/// distributed public key.
+pub fn aggregate<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    msg: &[u8],
+    commitments: &[SigningCommitments<P>],
+    shares: &[SignatureShare<P>],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let r: P = group_commitment(msg, commitments);
+    let c: P::Scalar = challenge(&r, &pub_poly.commit(), msg);
+    let z: P::Scalar = aggregate_with(pub_poly, msg, commitments, shares, &c)?;
+
+    // R || z
+    let mut buff: Vec<u8> = r.pk_to_key_slice();
+    buff.extend_from_slice(&z.to_bytes());
+    Ok(buff)
+}
+
+// aggregate_with verifies the signature shares for the challenge c of another
+// Schnorr scheme and returns their sum z.
+pub(crate) fn aggregate_with<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    msg: &[u8],
+    commitments: &[SigningCommitments<P>],
+    shares: &[SignatureShare<P>],
+    c: &P::Scalar,
+) -> Result<P::Scalar, Box<dyn Error>> {
+    if shares.len() != commitments.len() {
+        bail!("tss: expected one signature share per commitment");
+    }
+    // the same valid share twice would pass the checks and be summed twice
+    let mut indices: HashSet<u32> = HashSet::new();
+    for share in shares.iter() {
+        if !indices.insert(share.index) {
+            bail!("tss: duplicate signature share of signer {}", share.index);
+        }
+        if !commitments.iter().any(|c| c.index == share.index) {
+            bail!(
+                "tss: signature share of signer {} without commitment",
+                share.index
+            );
+        }
+    }
+
+    let mut lagrange = LagrangeCoefficients::new();
+    let mut z: P::Scalar = ECScalar::zero();
+    for share in shares.iter() {
+        verify_share_cached(pub_poly, msg, commitments, share, c, &mut lagrange)?;
+        z = z.add(&share.share.get_element());
+    }
+    Ok(z)
+}
+
+/// aggregate_adaptor verifies the signature shares issued under the adaptor
+/// point and combines them into a PreSignature for msg.
+pub fn aggregate_adaptor<P: CurvePoint>(
+    pub_poly: &PubPoly<P>,
+    msg: &[u8],
+    adaptor: &P,
+    commitments: &[SigningCommitments<P>],
+    shares: &[SignatureShare<P>],
+) -> Result<PreSignature<P>, Box<dyn Error>> {
+    let binding_msg = adaptor_msg(msg, adaptor);
+    let r: P = group_commitment(&binding_msg, commitments);
+    let c: P::Scalar = adaptor_challenge(&r, adaptor, &pub_poly.commit(), msg);
+    let z: P::Scalar = aggregate_with(pub_poly, &binding_msg, commitments, shares, &c)?;
+    Ok(PreSignature {
+        r,
+        adaptor: *adaptor,
+        z,
+    })
+}
+
+impl<P: CurvePoint> PreSignature<P> {
+    /// verify checks that the pre-signature completes into a signature of msg
+    /// under public with the discrete log of its adaptor point:
+    /// z' * G == R + c * A, with c = H(R + T || A || msg).
+    pub fn verify(&self, public: &P, msg: &[u8]) -> Result<(), Box<dyn Error>> {
+        let c: P::Scalar = adaptor_challenge(&self.r, &self.adaptor, public, msg);
+        let left: P = P::generator().scalar_mul(&self.z.get_element());
+        let right: P = public
+            .scalar_mul(&c.get_element())
+            .add_point(&self.r.get_element());
+        if left != right {
+            bail!("tss: invalid pre-signature");
+        }
+        Ok(())
+    }
+
+    /// complete returns the signature R + T || z' + t, verifiable with
+    /// dss::verify, given the discrete log t of the adaptor point.
+    pub fn complete(&self, secret: &P::Scalar) -> Result<Vec<u8>, Box<dyn Error>> {
+        if P::generator().scalar_mul(&secret.get_element()) != self.adaptor {
+            bail!("tss: secret not matching the adaptor point");
+        }
+        let r: P = self.r.add_point(&self.adaptor.get_element());
+        let z: P::Scalar = self.z.add(&secret.get_element());
+        let mut buff: Vec<u8> = r.pk_to_key_slice();
+        buff.extend_from_slice(&z.to_bytes());
+        Ok(buff)
+    }
+
+    /// extract returns the discrete log t of the adaptor point from the
+    /// signature completing the pre-signature.
+    pub fn extract(&self, sig: &[u8]) -> Result<P::Scalar, Box<dyn Error>> {
+        let r: Vec<u8> = self
+            .r
+            .add_point(&self.adaptor.get_element())
+            .pk_to_key_slice();
+        if sig.len() <= r.len() || sig[..r.len()] != r[..] {
+            bail!("tss: signature not completing the pre-signature");
+        }
+        let z: P::Scalar = ECScalar::from_bytes(&sig[r.len()..])?;
+        let secret: P::Scalar = z.sub(&self.z.get_element());
+        if P::generator().scalar_mul(&secret.get_element()) != self.adaptor {
+            bail!("tss: signature not completing the pre-signature");
+        }
+        Ok(secret)
+    }
+}
+
+// check_commitments makes sure there are enough signers and that each of them
+// appears only once.
+fn check_commitments<P: CurvePoint>(
+    commitments: &[SigningCommitments<P>],
+    t: u32,
+) -> Result<(), Box<dyn Error>> {
+    if (commitments.len() as u32) < t {
+        bail!("tss: not enough signers");
+    }
+    let mut seen: HashSet<u32> = HashSet::new();
+    if !commitments.iter().all(|c| seen.insert(c.index)) {
+        bail!("tss: duplicated signer in commitments");
+    }
+    Ok(())
+}
+
+// encode_commitments serializes the commitments of all signers ordered by index
+fn encode_commitments<P: CurvePoint>(commitments: &[SigningCommitments<P>]) -> Vec<u8> {
+    let mut sorted: Vec<&SigningCommitments<P>> = commitments.iter().collect();
+    sorted.sort_by_key(|c| c.index);
+
+    let mut buff: Vec<u8> = Vec::new();
+    for comm in sorted {
+        buff.extend_from_slice(&comm.index.to_le_bytes());
+        buff.extend_from_slice(&comm.hiding.pk_to_key_slice());
+        buff.extend_from_slice(&comm.binding.pk_to_key_slice());
+    }
+    buff
+}
+
+// binding_factor computes rho_i = H("frost-binding" || i || msg || commitments)
+fn binding_factor<P: CurvePoint>(
+    index: u32,
+    msg: &[u8],
+    commitments: &[SigningCommitments<P>],
+) -> P::Scalar {
+    let mut buff: Vec<u8> = b"frost-binding".to_vec();
+    buff.extend_from_slice(&index.to_le_bytes());
+    buff.extend_from_slice(&(msg.len() as u64).to_le_bytes());
+    buff.extend_from_slice(msg);
+    buff.extend_from_slice(&encode_commitments(commitments));
+    P::hash_to_scalar(&buff)
+}
+
+// group_commitment computes R = sum(D_i + rho_i * E_i)
+pub(crate) fn group_commitment<P: CurvePoint>(
+    msg: &[u8],
+    commitments: &[SigningCommitments<P>],
+) -> P {
+    let factors: HashMap<u32, P::Scalar> = commitments
+        .iter()
+        .map(|c| (c.index, binding_factor(c.index, msg, commitments)))
+        .collect();
+
+    commitments.iter().fold(P::identity(), |acc, c| {
+        let rho: &P::Scalar = &factors[&c.index];
+        acc.add_point(&c.hiding.get_element())
+            .add_point(&c.binding.scalar_mul(&rho.get_element()).get_element())
+    })
+}
+
+// challenge computes c = H(R || A || msg), the same hash as the one used by dss
+fn challenge<P: CurvePoint>(r: &P, public: &P, msg: &[u8]) -> P::Scalar {
+    let mut buff: Vec<u8> = r.pk_to_key_slice();
+    buff.extend_from_slice(&public.pk_to_key_slice());
+    buff.extend_from_slice(msg);
+    P::hash_to_scalar(&buff)
+}
+
+// adaptor_challenge computes c = H(R + T || A || msg), the challenge of the
+// completed signature.
+fn adaptor_challenge<P: CurvePoint>(r: &P, adaptor: &P, public: &P, msg: &[u8]) -> P::Scalar {
+    challenge(&r.add_point(&adaptor.get_element()), public, msg)
+}
+
+// adaptor_msg binds the adaptor point to the binding factors, the point having
+// a fixed size.
+fn adaptor_msg<P: CurvePoint>(msg: &[u8], adaptor: &P) -> Vec<u8> {
+    let mut buff: Vec<u8> = msg.to_vec();
+    buff.extend_from_slice(&adaptor.pk_to_key_slice());
+    buff
+}
+
+// lagrange_coefficient computes lambda_i for the set of signers, evaluated at 0,
+// the coefficients of the set being cached in lagrange
+fn lagrange_coefficient<P: CurvePoint>(
+    lagrange: &mut LagrangeCoefficients<P>,
+    index: u32,
+    commitments: &[SigningCommitments<P>],
+) -> Result<P::Scalar, Box<dyn Error>> {
+    let indices: Vec<u32> = commitments.iter().map(|c| c.index).collect();
+    lagrange.coefficient(index, &indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve_traits;
+    use crate::dss;
+    use crate::ristretto_curve;
+    use curve_traits::{ECPoint, ECScalar};
+    use dkg::*;
+    use ristretto_curve::{FE, GE};
+
+    const MSG: &[u8] = b"hello FROST";
+
+    fn gen_pair() -> (FE, GE) {
+        let generator = GE::generator();
+        let g_scalar: FE = ECScalar::new_random();
+        let g_point: GE = generator.scalar_mul(&g_scalar.get_element());
+        (g_scalar, g_point)
+    }
+
+    fn gen_dist_secret(participants_count: u32, t: u32) -> Vec<DistKeyShare> {
+        let (parts_sec, parts_pub): (Vec<FE>, Vec<GE>) =
+            (0..participants_count).map(|_| gen_pair()).unzip();
+        let mut dkgs: Vec<DistKeyGenerator> = parts_sec
+            .iter()
+            .map(|sec| DistKeyGenerator::new(*sec, parts_pub.clone(), t).unwrap())
+            .collect();
+
+        // 1. broadcast deals
+        let mut resps: Vec<Response> = Vec::new();
+        for i in 0..participants_count as usize {
+            let deals = dkgs[i].deals().unwrap();
+            for (&j, deal) in deals.iter() {
+                resps.push(dkgs[j as usize].process_deal(deal).unwrap());
+            }
+        }
+        // 2. broadcast responses
+        for r in resps.iter() {
+            for dkg in dkgs.iter_mut() {
+                if r.response.index != dkg.index() {
+                    assert!(dkg.process_response(r).unwrap().is_none());
+                }
+            }
+        }
+        // 3. broadcast secret commitments
+        for i in 0..participants_count as usize {
+            let scs: SecretCommits = dkgs[i].secret_commits().unwrap();
+            for (j, dkg) in dkgs.iter_mut().enumerate() {
+                if i != j {
+                    assert!(dkg.process_secret_commit(&scs).unwrap().is_none());
+                }
+            }
+        }
+
+        dkgs.iter()
+            .map(DistKeyGenerator::dist_key_share)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn setup(n: u32, t: u32) -> (Vec<Signer>, PubPoly) {
+        let shares = gen_dist_secret(n, t);
+        let pub_poly = PubPoly::new(GE::generator(), shares[0].get_commitments().to_vec());
+        let signers = shares.into_iter().map(|s| Signer::new(s, t)).collect();
+        (signers, pub_poly)
+    }
+
+    #[test]
+    fn test_tss_whole() {
+        let (n, t) = (7, 4);
+        let (mut signers, pub_poly) = setup(n, t);
+        // any t signers, not starting at 0
+        let signing: &mut [Signer] = &mut signers[2..2 + t as usize];
+
+        let commitments: Vec<SigningCommitments> = signing.iter_mut().map(Signer::commit).collect();
+        let shares: Vec<SignatureShare> = signing
+            .iter_mut()
+            .map(|s| s.sign(MSG, &commitments).unwrap())
+            .collect();
+
+        for share in shares.iter() {
+            signing[0].verify_share(MSG, &commitments, share).unwrap();
+        }
+
+        let sig = aggregate(&pub_poly, MSG, &commitments, &shares).unwrap();
+        assert!(dss::verify(pub_poly.commit(), MSG, &sig).unwrap());
+        assert!(!dss::verify(pub_poly.commit(), b"other msg", &sig).unwrap());
+    }
+
+    #[test]
+    fn test_tss_bad_share() {
+        let (n, t) = (5, 3);
+        let (mut signers, pub_poly) = setup(n, t);
+        let signing: &mut [Signer] = &mut signers[..t as usize];
+
+        let commitments: Vec<SigningCommitments> = signing.iter_mut().map(Signer::commit).collect();
+        let mut shares: Vec<SignatureShare> = signing
+            .iter_mut()
+            .map(|s| s.sign(MSG, &commitments).unwrap())
+            .collect();
+
+        shares[1].share = ECScalar::new_random();
+        verify_signature_share(&pub_poly, MSG, &commitments, &shares[0]).unwrap();
+        verify_signature_share(&pub_poly, MSG, &commitments, &shares[1])
+            .expect_err("Must fail, invalid share");
+        aggregate(&pub_poly, MSG, &commitments, &shares).expect_err("Must fail, invalid share");
+
+        // a valid share given twice, in place of another signer's
+        shares[1] = shares[0].clone();
+        let err = aggregate(&pub_poly, MSG, &commitments, &shares).unwrap_err();
+        assert!(err.to_string().contains("duplicate signature share"));
+        shares[1].index = n + 1;
+        let err = aggregate(&pub_poly, MSG, &commitments, &shares).unwrap_err();
+        assert!(err.to_string().contains("without commitment"));
+    }
+
+    #[test]
+    fn test_tss_adaptor() {
+        let (n, t) = (5, 3);
+        let (mut signers, pub_poly) = setup(n, t);
+        let signing: &mut [Signer] = &mut signers[1..1 + t as usize];
+        let (secret, adaptor) = gen_pair();
+
+        let commitments: Vec<SigningCommitments> = signing.iter_mut().map(Signer::commit).collect();
+        let shares: Vec<SignatureShare> = signing
+            .iter_mut()
+            .map(|s| s.sign_adaptor(MSG, &adaptor, &commitments).unwrap())
+            .collect();
+        for share in shares.iter() {
+            verify_adaptor_signature_share(&pub_poly, MSG, &adaptor, &commitments, share).unwrap();
+        }
+        verify_signature_share(&pub_poly, MSG, &commitments, &shares[0])
+            .expect_err("Must fail, share under an adaptor");
+
+        let pre = aggregate_adaptor(&pub_poly, MSG, &adaptor, &commitments, &shares).unwrap();
+        pre.verify(&pub_poly.commit(), MSG).unwrap();
+        pre.verify(&pub_poly.commit(), b"other msg")
+            .expect_err("Must fail, wrong msg");
+
+        // the pre-signature alone isn't a signature
+        let mut not_sig: Vec<u8> = pre.r.pk_to_key_slice();
+        not_sig.extend_from_slice(&pre.z.to_bytes());
+        assert!(!dss::verify(pub_poly.commit(), MSG, &not_sig).unwrap());
+
+        let (other, _) = gen_pair();
+        pre.complete(&other).expect_err("Must fail, wrong secret");
+        let sig = pre.complete(&secret).unwrap();
+        assert!(dss::verify(pub_poly.commit(), MSG, &sig).unwrap());
+        assert_eq!(secret, pre.extract(&sig).unwrap());
+        pre.extract(&not_sig)
+            .expect_err("Must fail, not the completed signature");
+    }
+
+    #[test]
+    fn test_tss_sign_errors() {
+        let (n, t) = (5, 3);
+        let (mut signers, _) = setup(n, t);
+
+        // sign without commit
+        let commitments: Vec<SigningCommitments> =
+            signers[1..].iter_mut().map(Signer::commit).collect();
+        signers[0]
+            .sign(MSG, &commitments)
+            .expect_err("Must fail, no nonces");
+
+        // not enough signers
+        let commitments: Vec<SigningCommitments> =
+            signers[..2].iter_mut().map(Signer::commit).collect();
+        signers[0]
+            .sign(MSG, &commitments)
+            .expect_err("Must fail, not enough signers");
+
+        // nonces are used only once
+        let commitments: Vec<SigningCommitments> =
+            signers[..3].iter_mut().map(Signer::commit).collect();
+        signers[0].sign(MSG, &commitments).unwrap();
+        signers[0]
+            .sign(MSG, &commitments)
+            .expect_err("Must fail, nonces already used");
+    }
+}
@@ -1,17 +1,27 @@
 //! Misc helper functions used by other modules
 
-use std::convert::TryInto;
-use std::error::Error;
+use core::convert::TryInto;
 
+use crate::error::Error;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+#[cfg(feature = "std")]
 use crate::curve_traits;
+#[cfg(feature = "std")]
 use crate::ristretto_curve;
 
+#[cfg(feature = "std")]
 use curve_traits::{ECPoint, ECScalar};
+#[cfg(feature = "std")]
 use ristretto_curve::{FE, GE};
 
+#[cfg(feature = "std")]
 use rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "std")]
 use schnorrkel::keys::Keypair;
 
+#[cfg(feature = "std")]
 pub fn create_keypair(secret_key: &FE, pub_key: &GE) -> Result<Keypair, Box<dyn Error>> {
     let mut csprng = rand_hack();
     let mut nonce: [u8; 32] = [0u8; 32];
@@ -27,12 +37,13 @@ pub fn create_keypair(secret_key: &FE, pub_key: &GE) -> Result<Keypair, Box<dyn
     Ok(keypair)
 }
 
+#[cfg(feature = "std")]
 pub fn rand_hack() -> impl RngCore + CryptoRng {
     ::rand_core::OsRng
 }
 
 pub fn arr32_from_slice(bytes: &[u8]) -> Result<[u8; 32], Box<dyn Error>> {
-    Ok(bytes.try_into()?)
+    bytes.try_into().map_err(|e| simple_error!("{}", e).into())
 }
 
 // Compare byte strings in constant time (linear in min(a, b))
@@ -0,0 +1,233 @@
+//! Deterministic test vectors of the vss, enabled with the `vectors` feature,
+//! to validate other implementations (e.g. Go or TypeScript) against this one.
+//!
+//! From a 32 bytes seed, generate draws every key, polynomial, ephemeral key
+//! and nonce from ChaCha20 and runs a vss session: the dealer deals to every
+//! verifier, every verifier responds and the last one complains falsely about
+//! its deal, which the dealer answers with a justification. The Fixture holds
+//! all of it and is written as JSON, points, scalars and byte strings being
+//! hex strings.
+//!
+//! The signatures are randomized by the signature schemes, so they differ
+//! between two fixtures of the same seed: an implementation checks them with
+//! the public keys of the fixture, while every other field is reproducible.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::poly;
+use crate::ristretto_curve;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECScalar};
+use poly::{PriPoly, PriShare};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use vss::{Deal, Dealer, EncryptedDeal, Justification, Response, Verifier};
+
+/// Fixture is a complete run of a vss session generated from a seed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Fixture<P: CurvePoint = GE> {
+    #[serde(with = "crate::serde_hex")]
+    pub seed: Vec<u8>,
+    // CurvePoint::CURVE_ID of the curve of the fixture
+    pub curve: String,
+    pub t: u32,
+    // longterm keys of the dealer
+    pub dealer_secret: P::Scalar,
+    pub dealer_public: P,
+    // longterm keys of the verifiers, in the order of their indices
+    pub verifier_secrets: Vec<P::Scalar>,
+    pub verifier_publics: Vec<P>,
+    // shared secret and coefficients of the secret polynomial
+    pub secret: P::Scalar,
+    pub polynomial: Vec<P::Scalar>,
+    #[serde(with = "crate::serde_hex")]
+    pub session_id: Vec<u8>,
+    // deal and encrypted deal of every verifier
+    pub deals: Vec<Deal<P>>,
+    pub encrypted_deals: Vec<EncryptedDeal<P>>,
+    // response of every verifier, the last one being a complaint
+    pub responses: Vec<Response>,
+    // justification of the dealer answering the complaint
+    pub justifications: Vec<Justification<P>>,
+}
+
+/// generate returns the fixture of a vss session between a dealer and n
+/// verifiers of threshold t, drawn from the seed.
+pub fn generate<P: CurvePoint>(
+    seed: [u8; 32],
+    n: u32,
+    t: u32,
+) -> Result<Fixture<P>, Box<dyn Error>> {
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    let dealer_secret: P::Scalar = ECScalar::new_random_with_rng(&mut rng);
+    let verifier_secrets: Vec<P::Scalar> = (0..n)
+        .map(|_| ECScalar::new_random_with_rng(&mut rng))
+        .collect();
+    let verifier_publics: Vec<P> = verifier_secrets.iter().map(P::base_mul).collect();
+    let secret: P::Scalar = ECScalar::new_random_with_rng(&mut rng);
+
+    let mut dealer: Dealer<P> =
+        Dealer::new_with_rng(dealer_secret, secret, verifier_publics.clone(), t, &mut rng)?;
+    let deals: Vec<Deal<P>> = dealer.deals.clone();
+    let session_id: Vec<u8> = deals[0].session_id.clone();
+    let mut shares: Vec<PriShare<P::Scalar>> = deals.iter().map(|d| d.sec_share.clone()).collect();
+    let polynomial: PriPoly<P> = poly::recover_pri_poly(&mut shares, t)?;
+    let encrypted_deals: Vec<EncryptedDeal<P>> = (0..n)
+        .map(|i| dealer.encrypt_deal_with_rng(i, &mut rng))
+        .collect::<Result<_, _>>()?;
+
+    let dealer_public: P = P::base_mul(&dealer_secret);
+    let mut responses: Vec<Response> = Vec::with_capacity(n as usize);
+    let mut justifications: Vec<Justification<P>> = Vec::new();
+    for (i, encrypted) in encrypted_deals.iter().enumerate() {
+        let mut verifier: Verifier<P> =
+            Verifier::new(verifier_secrets[i], dealer_public, verifier_publics.clone())?;
+        let response = if i as u32 == n - 1 {
+            verifier.complaint(encrypted)?.response
+        } else {
+            verifier.process_encrypted_deal(encrypted)?
+        };
+        if let Some(justification) = dealer.process_response(&response)? {
+            justifications.push(justification);
+        }
+        responses.push(response);
+    }
+
+    Ok(Fixture {
+        seed: seed.to_vec(),
+        curve: P::CURVE_ID.to_string(),
+        t,
+        dealer_secret,
+        dealer_public,
+        verifier_secrets,
+        verifier_publics,
+        secret,
+        polynomial: polynomial.coefficients(),
+        session_id,
+        deals,
+        encrypted_deals,
+        responses,
+        justifications,
+    })
+}
+
+impl<P: CurvePoint> Fixture<P> {
+    /// to_json returns the JSON encoding of the fixture.
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// from_json reads a fixture written by to_json.
+    pub fn from_json(json: &str) -> Result<Fixture<P>, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// check runs the checks expected from another implementation against
+    /// the fixture: the keys, the deals against the polynomial and the
+    /// commitments, the decryption of the encrypted deals, the responses and
+    /// the justifications, and the recovery of the secret.
+    pub fn check(&self) -> Result<(), Box<dyn Error>> {
+        if self.curve != P::CURVE_ID {
+            bail!(
+                "vectors: fixture over {} instead of {}",
+                self.curve,
+                P::CURVE_ID
+            );
+        }
+        let mut keys = self
+            .verifier_secrets
+            .iter()
+            .zip(self.verifier_publics.iter());
+        if P::base_mul(&self.dealer_secret) != self.dealer_public
+            || self.verifier_secrets.len() != self.deals.len()
+            || self.verifier_publics.len() != self.deals.len()
+            || keys.any(|(s, p)| P::base_mul(s) != *p)
+        {
+            bail!("vectors: keys not matching");
+        }
+        let polynomial: PriPoly<P> = PriPoly::coefficients_to_pri_poly(self.polynomial.clone());
+        if *polynomial.secret() != self.secret {
+            bail!("vectors: polynomial not sharing the secret");
+        }
+
+        for (i, (deal, encrypted)) in self
+            .deals
+            .iter()
+            .zip(self.encrypted_deals.iter())
+            .enumerate()
+        {
+            deal.verify(&self.verifier_publics, &self.session_id)?;
+            if deal.sec_share != polynomial.eval(i as u32) {
+                bail!("vectors: share {} not matching the polynomial", i);
+            }
+            let mut verifier: Verifier<P> = Verifier::new(
+                self.verifier_secrets[i],
+                self.dealer_public,
+                self.verifier_publics.clone(),
+            )?;
+            if verifier.decrypt_deal(encrypted)? != *deal {
+                bail!("vectors: encrypted deal {} not matching the deal", i);
+            }
+        }
+        for response in self.responses.iter() {
+            response.verify(&self.verifier_publics, &self.session_id)?;
+        }
+        for justification in self.justifications.iter() {
+            justification.verify(
+                &self.dealer_public,
+                &self.verifier_publics,
+                &self.session_id,
+            )?;
+        }
+        if vss::recover_secret(&self.deals, self.t)? != self.secret {
+            bail!("vectors: deals not recovering the secret");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors() {
+        let fixture: Fixture = generate([7u8; 32], 5, 3).unwrap();
+        fixture.check().unwrap();
+        assert_eq!(5, fixture.responses.len());
+        assert!(!fixture.responses[4].approved);
+        assert_eq!(1, fixture.justifications.len());
+        assert_eq!(4, fixture.justifications[0].index);
+
+        // reproducible from the seed, except for the signatures
+        let again: Fixture = generate([7u8; 32], 5, 3).unwrap();
+        assert_eq!(fixture.verifier_publics, again.verifier_publics);
+        assert_eq!(fixture.polynomial, again.polynomial);
+        assert_eq!(fixture.session_id, again.session_id);
+        assert_eq!(fixture.deals, again.deals);
+        for (e1, e2) in fixture
+            .encrypted_deals
+            .iter()
+            .zip(again.encrypted_deals.iter())
+        {
+            assert_eq!(e1.dh_key, e2.dh_key);
+            assert_eq!(e1.cipher, e2.cipher);
+        }
+        let other: Fixture = generate([8u8; 32], 5, 3).unwrap();
+        assert_ne!(fixture.session_id, other.session_id);
+
+        let json = fixture.to_json().unwrap();
+        let read: Fixture = Fixture::from_json(&json).unwrap();
+        read.check().unwrap();
+        assert_eq!(fixture.deals, read.deals);
+
+        let mut altered = read;
+        altered.deals[1].sec_share.v = ECScalar::new_random();
+        altered.check().expect_err("Must fail, altered share");
+    }
+}
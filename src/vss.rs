@@ -3,43 +3,113 @@
 //! Can be used to share a secret with a group of verifiers.
 //! The secret can be recovered only by a subset of at least T verifiers.
 
-use std::collections::HashMap;
-use std::convert::TryInto;
-use std::error::Error;
-use std::io::Write;
-use std::rc::Rc;
-
-use crate::blake;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "std")]
+use core::sync::atomic::{compiler_fence, Ordering};
+
+#[cfg(feature = "std")]
+use crate::certificate;
 use crate::curve_traits;
+#[cfg(feature = "std")]
 use crate::dh;
+#[cfg(feature = "std")]
+use crate::digest;
+#[cfg(feature = "std")]
+use crate::dkg;
+#[cfg(feature = "std")]
+use crate::dleq;
+use crate::encoding;
+#[cfg(feature = "std")]
+use crate::envelope;
+use crate::error::Error;
+#[cfg(feature = "std")]
+use crate::events;
+#[cfg(feature = "std")]
+use crate::evidence;
+use crate::fft;
+#[cfg(feature = "std")]
+use crate::group;
+#[cfg(feature = "hpke")]
+use crate::hpke;
+#[cfg(feature = "std")]
+use crate::metrics;
 use crate::poly;
+#[cfg(feature = "pq-kem")]
+use crate::pq;
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
 use crate::ristretto_curve;
-use crate::sign;
+#[cfg(feature = "std")]
+use crate::signer;
+#[cfg(feature = "std")]
+use crate::transport;
 use crate::utils;
-
-use crate::poly::{PriPoly, PriShare, PubPoly, PubShare};
-use aead::{generic_array::GenericArray, Aead, Payload};
+#[cfg(feature = "std")]
+use crate::weighted;
+#[cfg(feature = "std")]
+use crate::wire;
+
+#[cfg(feature = "std")]
+use crate::poly::PriPoly;
+use crate::poly::{PriShare, PubPoly};
+#[cfg(feature = "std")]
+use aead::{generic_array::GenericArray, Aead, NewAead, Payload};
+#[cfg(feature = "std")]
 use aes_gcm::Aes256Gcm;
-use curve_traits::{ECPoint, ECScalar};
-use ristretto_curve::{FE, GE};
+#[cfg(feature = "std")]
+use certificate::Certificate;
+#[cfg(feature = "std")]
+use chacha20poly1305::ChaCha20Poly1305;
+use curve_traits::{CurvePoint, ECScalar};
+#[cfg(feature = "std")]
+use digest::DealDigest;
+#[cfg(feature = "std")]
+use dleq::DleqProof;
+use encoding::{Domain, Encoder, HDerivation, HashSuite};
+#[cfg(feature = "std")]
+use envelope::Envelope;
+#[cfg(feature = "std")]
+use events::Event;
+#[cfg(feature = "std")]
+use evidence::{Evidence, Misbehavior};
+#[cfg(feature = "std")]
+use group::GroupConfig;
+#[cfg(feature = "std")]
+use rand_core::{CryptoRng, RngCore};
+use ristretto_curve::GE;
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use signer::{IdentityKeys, KeyAgreement, ParticipantIdentity, Signer, SoftwareSigner};
+#[cfg(feature = "std")]
+use transport::Message;
 use utils::bitwise_eq;
+#[cfg(feature = "std")]
+use weighted::Weights;
 
 /// Dealer encapsulates for creating and distributing the shares and for
 /// replying to any Responses.
+#[cfg(feature = "std")]
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct Dealer {
-    // private key of dealer
-    long: FE,
+#[serde(bound(serialize = "K: Serialize", deserialize = "K: Deserialize<'de>"))]
+pub struct Dealer<P: CurvePoint = GE, K = SoftwareSigner<P>> {
+    // longterm key of dealer
+    long: K,
     // public key of dealer
-    pub_key: GE,
+    pub_key: P,
     // the secret to be shared
-    secret: FE,
+    secret: P::Scalar,
     // secret commits of the secret polynomial polynomial to be shared with verifiers
     secret_commits: Vec<Vec<u8>>,
     // pub keys of verifiers
-    verifiers: Rc<[GE]>,
+    verifiers: Arc<[P]>,
     // context for hkdf encryption
     hkdf_context: Vec<u8>,
     // threshold security parameter
@@ -47,60 +117,519 @@ pub struct Dealer {
     // hash of stuff specific to the session: verifiers pub keys, dealer pub key, etc
     session_id: Vec<u8>,
     // Deals to be encrypted and distributed to verifiers. It is pub(crate) for tests.
-    pub(crate) deals: Vec<Deal>,
+    pub(crate) deals: Vec<Deal<P>>,
     // Collects deals and responses
-    aggregator: Aggregator,
+    aggregator: Aggregator<P>,
+    // AEAD scheme encrypting the deals
+    #[serde(default)]
+    aead: DealAead,
+    // Key derivation encrypting the deals
+    #[serde(default)]
+    mode: DealMode,
+    // KEM public keys of the verifiers, for the hybrid mode
+    #[cfg(feature = "pq-kem")]
+    #[serde(default)]
+    kem_keys: Vec<Vec<u8>>,
+    // If true, the deals carry the Merkle root of the commitments
+    #[serde(default)]
+    compact: bool,
 }
 
 /// Aggregator is used to collect all deals, and responses for one protocol run.
 /// It brings common functionalities for both Dealer and Verifier structs.
 /// If threshold is 0, Aggregator is in invalid state and should be populated with correct data.
+#[cfg(feature = "std")]
 #[derive(Clone, Debug, Deserialize, Serialize)]
-struct Aggregator {
+#[serde(bound = "")]
+pub(crate) struct Aggregator<P: CurvePoint = GE> {
     // Pub key of dealer
-    dealer: GE,
+    dealer: P,
     // Pub keys of verifiers
-    verifiers: Rc<[GE]>,
+    pub(crate) verifiers: Arc<[P]>,
     // Map between participant id and response
-    responses: HashMap<u32, Response>,
+    pub(crate) responses: HashMap<u32, Response>,
     // All Responses received have to have the same session_id
-    session_id: Vec<u8>,
+    pub(crate) session_id: Vec<u8>,
     // Deal, used for distributed secret reconstruction
-    deal: Deal,
+    deal: Deal<P>,
     // Security parameter T. If 0, means Aggregator isn't fully initialized
     threshold: u32,
     // set bad_dealer to true, so that deal_certified always returns false
-    bad_dealer: bool,
+    pub(crate) bad_dealer: bool,
+    // rule deciding when the deal is certified
+    #[serde(default)]
+    pub(crate) certification: Certification,
+    // x-coordinates of the shares, if not the default i+1 of index i
+    #[serde(default)]
+    pub(crate) points: Option<Arc<[P::Scalar]>>,
+    // time limit of the responses, not kept across serialization
+    #[serde(skip)]
+    deadline: Option<Instant>,
+    // time limit of the justifications, not kept across serialization
+    #[serde(skip)]
+    justification_deadline: Option<Instant>,
+    // start of the session for the metrics, not kept across serialization
+    #[serde(skip)]
+    started: Option<Instant>,
+    // verifiers whose complaint was justified before it was received
+    #[serde(default)]
+    justified: HashSet<u32>,
+    // domain of the session ids, hashes and signatures
+    #[serde(default)]
+    pub(crate) domain: Domain,
+    // responses the certification rule waits for
+    #[serde(default)]
+    quorum: Quorum,
+    // encryption keys of the verifiers, if distinct from their signing keys
+    #[serde(default)]
+    encryption_keys: Option<Arc<[P]>>,
+    // whether the dealer is a verifier, and its index if so
+    #[serde(default)]
+    dealer_role: DealerRole,
+    #[serde(default)]
+    dealer_index: Option<u32>,
+    // policy replacing the certification rule, not kept across serialization
+    #[serde(skip)]
+    policy: Option<Arc<dyn CertificationPolicy<P>>>,
+    // signed responses as received, before a justification approves them
+    #[serde(default)]
+    signed: HashMap<u32, Response>,
+    // set by tick once the deadline of the responses has passed
+    #[serde(default)]
+    deadline_passed: bool,
+}
+
+/// Certification is the rule deciding when a deal is certified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Certification {
+    /// Rabin's rule: every verifier responded or timed out, at least t of them
+    /// approved, and every complaint has been answered by a valid
+    /// Justification. A dealer can't be disqualified by false complaints, at
+    /// the cost of a justification round revealing the complained shares.
+    Justified,
+    /// Approval-only rule of the Pedersen DKG: the deal is certified once all
+    /// the verifiers approved it, and there are no justifications. A complaint
+    /// or a missing response disqualifies the dealer. It saves the
+    /// justification round and never reveals a share, but any single verifier
+    /// can disqualify any dealer: it suits committees whose members are all
+    /// expected to be online and honest, where a failed run can be restarted
+    /// without the disqualified members.
+    ApprovalOnly,
+}
+
+impl Default for Certification {
+    fn default() -> Certification {
+        Certification::Justified
+    }
+}
+
+/// Quorum is the set of responses the Justified rule waits for before
+/// certifying a deal, set in DealerConfig and VerifierConfig.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quorum {
+    /// Every verifier responded or timed out with set_timeout or tick, and at
+    /// least t of them approved: a crashed verifier delays the certification
+    /// until the timeout.
+    Full,
+    /// At least t verifiers approved, without waiting for the others. An
+    /// invalid justification answering a later complaint still disqualifies
+    /// the dealer.
+    Threshold,
+    /// At least t verifiers approved, and every verifier responded or tick
+    /// observed the deadline passing, without timing the others out: their
+    /// late responses are still processed. Unlike the deadline, the passing of
+    /// the deadline is kept across serialization.
+    ThresholdWithDeadline,
+    /// Every verifier approved, possibly after a justification.
+    Unanimous,
+}
+
+impl Default for Quorum {
+    fn default() -> Quorum {
+        Quorum::Full
+    }
+}
+
+/// DealerRole tells whether the dealer is one of the verifiers, set in
+/// DealerConfig and VerifierConfig.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DealerRole {
+    /// Nothing is assumed: a dealer listed among the verifiers is a verifier
+    /// as any other, which processes its deal and responds.
+    Unspecified,
+    /// The dealer isn't a verifier, and must not be in the list of verifiers.
+    External,
+    /// The dealer is the verifier of its public key, as in a DKG: it keeps its
+    /// own share and approves its deal implicitly, without a Response, which
+    /// the dealer and the verifiers count towards the certification.
+    Participant,
+}
+
+impl Default for DealerRole {
+    fn default() -> DealerRole {
+        DealerRole::Unspecified
+    }
+}
+
+/// CertificationPolicy decides when a deal is certified in place of the
+/// Certification rule, e.g. certified iff all the members of QUAL approved.
+/// Dealer::set_policy and Verifier::set_policy install it; it is consulted by
+/// deal_certified with a read-only view of the responses received.
+#[cfg(feature = "std")]
+pub trait CertificationPolicy<P: CurvePoint = GE>: fmt::Debug + Send + Sync {
+    /// deal_certified returns true if the deal of the view is certified.
+    fn deal_certified(&self, view: &AggregatorView<P>) -> bool;
+}
+
+/// AggregatorView is a read-only view of the responses gathered by a Dealer
+/// or a Verifier, for external certification logic.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct AggregatorView<'a, P: CurvePoint = GE> {
+    aggregator: &'a Aggregator<P>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, P: CurvePoint> AggregatorView<'a, P> {
+    /// dealer returns the longterm public key of the dealer.
+    pub fn dealer(&self) -> &P {
+        &self.aggregator.dealer
+    }
+
+    /// verifiers returns the longterm public keys of the verifiers.
+    pub fn verifiers(&self) -> &[P] {
+        &self.aggregator.verifiers
+    }
+
+    /// threshold returns the threshold of the deal, 0 if it isn't known yet.
+    pub fn threshold(&self) -> u32 {
+        self.aggregator.threshold
+    }
+
+    /// session_id returns the session id of the deal.
+    pub fn session_id(&self) -> &[u8] {
+        &self.aggregator.session_id
+    }
+
+    /// certification returns the Certification rule of the session.
+    pub fn certification(&self) -> Certification {
+        self.aggregator.certification
+    }
+
+    /// quorum returns the Quorum of the certification rule.
+    pub fn quorum(&self) -> Quorum {
+        self.aggregator.quorum
+    }
+
+    /// responses returns the responses received, by verifier index.
+    pub fn responses(&self) -> &HashMap<u32, Response> {
+        self.aggregator.responses()
+    }
+
+    /// approvals returns the sorted indices of the verifiers which approved
+    /// the deal.
+    pub fn approvals(&self) -> Vec<u32> {
+        self.aggregator.approvals()
+    }
+
+    /// complaints returns the sorted indices of the verifiers which
+    /// complained, or were marked as complaining by set_timeout.
+    pub fn complaints(&self) -> Vec<u32> {
+        self.aggregator.complaints()
+    }
+
+    /// complaint_reasons returns the reasons of the complaints along with
+    /// the index of their verifier, ComplaintReason::Timeout for the ones
+    /// marked by set_timeout.
+    pub fn complaint_reasons(&self) -> Vec<(u32, Option<ComplaintReason>)> {
+        self.aggregator.complaint_reasons()
+    }
+
+    /// missing_responders returns the sorted indices of the verifiers which
+    /// haven't responded yet.
+    pub fn missing_responders(&self) -> Vec<u32> {
+        self.aggregator.missing_responders()
+    }
+
+    /// dealer_index returns the index of the dealer among the verifiers, if it
+    /// approved its deal implicitly.
+    pub fn dealer_index(&self) -> Option<u32> {
+        self.aggregator.dealer_index
+    }
+
+    /// bad_dealer returns true if the dealer has been proven faulty, e.g. by
+    /// an invalid justification.
+    pub fn bad_dealer(&self) -> bool {
+        self.aggregator.bad_dealer
+    }
+
+    /// rule_certified returns true if the deal is certified by the
+    /// Certification rule, for policies refining it.
+    pub fn rule_certified(&self) -> bool {
+        self.aggregator.rule_certified()
+    }
 }
 
 /// Deal encapsulates the verifiable secret share and is sent by the dealer to a verifier.
 #[derive(Default, Serialize, Deserialize, PartialEq, Debug, Clone)]
-pub struct Deal {
+#[serde(bound = "")]
+pub struct Deal<P: CurvePoint = GE> {
+    #[serde(with = "crate::serde_hex")]
     pub session_id: Vec<u8>,
     // Share of distributed secret
-    pub sec_share: PriShare<FE>,
+    pub sec_share: PriShare<P::Scalar>,
     // Share of random, used for share verification
-    pub rnd_share: PriShare<FE>,
+    pub rnd_share: PriShare<P::Scalar>,
     // Threshold security parameter
     pub t: u32,
     // Polynomial committments for share verification
+    #[serde(with = "crate::serde_hex::list")]
+    pub commitments: Vec<Vec<u8>>,
+    // Application context bound to the session id, empty if none
+    #[serde(default, with = "crate::serde_hex")]
+    pub context: Vec<u8>,
+    // Merkle root of the commitments of a compact deal, whose commitments are
+    // broadcast in DealCommitments; empty otherwise
+    #[serde(default, with = "crate::serde_hex")]
+    pub commitments_root: Vec<u8>,
+}
+
+/// DealCommitments are the commitments of the deals, broadcast once by the
+/// dealer to all the verifiers when the deals are compact, see
+/// Dealer::set_compact_commitments.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DealCommitments {
+    // SessionID of the deals
+    #[serde(with = "crate::serde_hex")]
+    pub session_id: Vec<u8>,
+    // Polynomial commitments of the deals
+    #[serde(with = "crate::serde_hex::list")]
     pub commitments: Vec<Vec<u8>>,
+    // Signature of the dealer over the session id and the commitments
+    #[serde(with = "crate::serde_hex")]
+    pub signature: Vec<u8>,
+}
+
+/// SessionContext binds a run of the protocol to an application and an epoch,
+/// so that two applications, or two epochs of an application, with the same
+/// committee can't produce colliding sessions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionContext {
+    // Label of the application, e.g. its name and the purpose of the key
+    pub label: Vec<u8>,
+    // Epoch of the committee, e.g. incremented at every resharing
+    pub epoch: u64,
+}
+
+impl SessionContext {
+    pub fn new(label: &[u8], epoch: u64) -> SessionContext {
+        SessionContext {
+            label: label.to_vec(),
+            epoch,
+        }
+    }
+
+    /// encode returns the context bytes bound to the session id.
+    pub fn encode(&self) -> Vec<u8> {
+        Encoder::new(b"sessioncontext")
+            .put_bytes(&self.label)
+            .put_u64(self.epoch)
+            .into_bytes()
+    }
 }
 
+/// Version of the EncryptedDeal wire format. Deals of any other version are
+/// rejected.
+pub const ENCRYPTED_DEAL_VERSION: u8 = 1;
+
 /// EncryptedDeal contains the deal in a encrypted form only decipherable by the
 /// correct recipient. The encryption is performed in a similar manner as what is
-/// done in TLS. The dealer generates a temporary key pair, signs it with its
-/// longterm secret key.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct EncryptedDeal {
+/// done in TLS. The dealer generates a temporary key pair and signs, with its
+/// longterm secret key, the whole encrypted deal along with the recipient index.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct EncryptedDeal<P: CurvePoint = GE> {
+    // Version of the wire format, ENCRYPTED_DEAL_VERSION
+    pub version: u8,
+    // SessionID of the encrypted deal
+    #[serde(with = "crate::serde_hex")]
+    pub session_id: Vec<u8>,
     // Ephemeral Diffie Hellman key
-    pub dh_key: GE,
-    // Signature of the DH key by the longterm key of the dealer
+    pub dh_key: P,
+    // Signature of the hash of the encrypted deal by the longterm key of the dealer
+    #[serde(with = "crate::serde_hex")]
     pub signature: Vec<u8>,
-    // Nonce used for the encryption
+    // Random nonce used for the encryption, unique to this deal, empty with HPKE
+    #[serde(with = "crate::serde_hex")]
     pub nonce: Vec<u8>,
     // AEAD encryption of the deal marshalled by protobuf
+    #[serde(with = "crate::serde_hex")]
     pub cipher: Vec<u8>,
+    // AEAD scheme of the encryption
+    #[serde(default)]
+    pub aead: DealAead,
+    // Key derivation of the encryption
+    #[serde(default)]
+    pub mode: DealMode,
+}
+
+/// AddressedDeal is an encrypted deal along with its recipient, see
+/// Dealer::encrypted_deals_addressed.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressedDeal<P: CurvePoint = GE> {
+    // Index of the recipient in the list of verifiers
+    pub index: u32,
+    // Public key of the recipient
+    pub public: P,
+    pub deal: EncryptedDeal<P>,
+    // Envelope of the deal, signed by the dealer
+    pub envelope: Envelope,
+}
+
+/// DealAead is the AEAD scheme encrypting the deals. The Dealer picks it and
+/// records it in every EncryptedDeal, and the verifiers decrypt with the scheme
+/// recorded in the deal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DealAead {
+    Aes256Gcm,
+    /// Faster than AES256-GCM without hardware support for AES, e.g. in wasm
+    /// or on embedded targets.
+    ChaCha20Poly1305,
+}
+
+impl Default for DealAead {
+    fn default() -> DealAead {
+        DealAead::Aes256Gcm
+    }
+}
+
+#[cfg(feature = "std")]
+impl DealAead {
+    /// hpke_id returns the identifier of the AEAD in RFC 9180.
+    #[cfg(feature = "hpke")]
+    pub fn hpke_id(self) -> u16 {
+        match self {
+            DealAead::Aes256Gcm => 0x0002,
+            DealAead::ChaCha20Poly1305 => 0x0003,
+        }
+    }
+
+    // seal encrypts msg with the given key and nonce.
+    pub(crate) fn seal(
+        self,
+        key: &[u8; 32],
+        nonce: &[u8],
+        aad: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let key = GenericArray::clone_from_slice(key);
+        let nonce = GenericArray::from_slice(nonce);
+        let pay = Payload { msg, aad };
+        let cipher = match self {
+            DealAead::Aes256Gcm => Aes256Gcm::new(key).encrypt(nonce, pay),
+            DealAead::ChaCha20Poly1305 => ChaCha20Poly1305::new(key).encrypt(nonce, pay),
+        };
+        cipher.map_err(|_| simple_error!("vss: encryption failure!").into())
+    }
+
+    // open decrypts a cipher of seal.
+    fn open(
+        self,
+        key: &[u8; 32],
+        nonce: &[u8],
+        aad: &[u8],
+        cipher: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let key = GenericArray::clone_from_slice(key);
+        let nonce = GenericArray::from_slice(nonce);
+        let pay = Payload { msg: cipher, aad };
+        let plain = match self {
+            DealAead::Aes256Gcm => Aes256Gcm::new(key).decrypt(nonce, pay),
+            DealAead::ChaCha20Poly1305 => ChaCha20Poly1305::new(key).decrypt(nonce, pay),
+        };
+        plain.map_err(|_| simple_error!("vss: failed decrypt {:?} cipher deal", self).into())
+    }
+}
+
+/// DealMode is the construction deriving the key encrypting a deal from the
+/// Diffie Hellman key of the ephemeral key with the key of the verifier. It is
+/// recorded in every EncryptedDeal along with the DealAead. The modes behind a
+/// feature flag exist without it, so that the encoding doesn't depend on the
+/// features, but deals in these modes can't be encrypted nor decrypted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DealMode {
+    /// The Diffie Hellman key is fed into a HKDF whose output is the key, and
+    /// the nonce is random.
+    Legacy = 0,
+    /// RFC 9180 HPKE in base mode (see the hpke module), with an empty nonce in
+    /// the EncryptedDeal. It needs the hpke feature.
+    Hpke = 1,
+    /// Post-quantum hybrid mode (see the pq module): the key is derived from
+    /// both the Diffie Hellman key and a KEM shared secret, and the nonce is
+    /// random. The verifiers can't complain about such deals, as they can't
+    /// prove the KEM shared secret. It needs the pq-kem feature.
+    Hybrid = 2,
+}
+
+impl Default for DealMode {
+    fn default() -> DealMode {
+        DealMode::Legacy
+    }
+}
+
+#[cfg(feature = "std")]
+impl DealMode {
+    // key returns the key and the nonce encrypting a deal for the recipient,
+    // from the Diffie Hellman key pre of the ephemeral key dh_key and, in
+    // hybrid mode, the KEM shared secret. nonce is the nonce of the
+    // EncryptedDeal.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(
+        not(all(feature = "hpke", feature = "pq-kem")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn key<P: CurvePoint>(
+        self,
+        aead: DealAead,
+        pre: &P,
+        kem_shared: &[u8],
+        dh_key: &P,
+        recipient: &P,
+        hkdf_context: &[u8],
+        nonce: &[u8],
+    ) -> Result<([u8; 32], Vec<u8>), Box<dyn Error>> {
+        match self {
+            DealMode::Legacy => {
+                if nonce.len() != dh::NONCE_SIZE {
+                    bail!("vss: invalid nonce size in encrypted deal");
+                }
+                Ok((dh::derive_key(pre, hkdf_context), nonce.to_vec()))
+            }
+            #[cfg(feature = "hpke")]
+            DealMode::Hpke => {
+                if !nonce.is_empty() {
+                    bail!("vss: nonce in a HPKE encrypted deal");
+                }
+                let shared = hpke::shared_secret(pre, dh_key, recipient);
+                let (key, nonce) = hpke::key_schedule(&shared, aead.hpke_id(), hkdf_context);
+                Ok((key, nonce.to_vec()))
+            }
+            #[cfg(not(feature = "hpke"))]
+            DealMode::Hpke => bail!("vss: HPKE deals need the hpke feature"),
+            #[cfg(feature = "pq-kem")]
+            DealMode::Hybrid => {
+                if nonce.len() != dh::NONCE_SIZE {
+                    bail!("vss: invalid nonce size in encrypted deal");
+                }
+                let key = pq::derive_key(pre, kem_shared, hkdf_context);
+                Ok((key, nonce.to_vec()))
+            }
+            #[cfg(not(feature = "pq-kem"))]
+            DealMode::Hybrid => bail!("vss: hybrid deals need the pq-kem feature"),
+        }
+    }
 }
 
 /// Response is sent by the verifiers to all participants and holds each
@@ -108,55 +637,177 @@ pub struct EncryptedDeal {
 #[derive(Clone, Default, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Response {
     // SessionID related to this run of the protocol
+    #[serde(with = "crate::serde_hex")]
     pub session_id: Vec<u8>,
     // Index of the verifier issuing this Response
     pub index: u32,
     // Approved is true if the Response is valid
     pub approved: bool,
     // Signature over the whole packet
+    #[serde(with = "crate::serde_hex")]
     pub signature: Vec<u8>,
+    // Reason of a complaint, signed along with the response, None for an
+    // approval or a complaint of an older verifier
+    #[serde(default)]
+    pub reason: Option<ComplaintReason>,
+}
+
+/// ComplaintReason tells why a verifier complained about its deal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum ComplaintReason {
+    /// The deal couldn't be decrypted or decoded.
+    DecryptFailed,
+    /// The deal is about another session.
+    BadSessionId,
+    /// The deal is malformed, e.g. its threshold, index or commitments.
+    InvalidDeal,
+    /// The share doesn't verify against the commitments.
+    ShareMismatch,
+    /// The verifier didn't respond before the timeout. It is only set
+    /// locally by set_timeout and never signed.
+    Timeout,
+}
+
+impl ComplaintReason {
+    /// code returns the code of the reason signed in a Response.
+    pub fn code(&self) -> u32 {
+        match self {
+            ComplaintReason::DecryptFailed => 1,
+            ComplaintReason::BadSessionId => 2,
+            ComplaintReason::InvalidDeal => 3,
+            ComplaintReason::ShareMismatch => 4,
+            ComplaintReason::Timeout => 5,
+        }
+    }
 }
 
 /// Justification is a message that is broadcasted by the Dealer in response to
 /// a Complaint. It contains the original Complaint as well as the shares
 /// distributed to the complainer.
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
-pub struct Justification {
+#[serde(bound = "")]
+pub struct Justification<P: CurvePoint = GE> {
     // SessionID related to the current run of the protocol
+    #[serde(with = "crate::serde_hex")]
     pub session_id: Vec<u8>,
     // Index of the verifier who issued the Complaint,i.e. index of this Deal
     pub index: u32,
     // Deal in cleartext
-    pub deal: Deal,
+    pub deal: Deal<P>,
     // Signature over the whole packet
+    #[serde(with = "crate::serde_hex")]
     pub signature: Vec<u8>,
 }
 
+/// Complaint is a complaint Response along with what anybody needs to decrypt
+/// the deal it is about: the verifier reveals the Diffie-Hellman key shared
+/// with the dealer for this deal only, with a Chaum-Pedersen proof that it
+/// derives from its longterm key. The dealer can thus be judged without its
+/// Justification.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Complaint<P: CurvePoint = GE> {
+    // Complaint response of the verifier
+    pub response: Response,
+    // Encrypted deal received by the verifier, signed by the dealer
+    pub deal: EncryptedDeal<P>,
+    // Diffie Hellman key shared by the verifier and the dealer for this deal
+    pub dh_shared: P,
+    // Proof that dh_shared is the longterm key of the verifier times dh_key
+    pub proof: DleqProof<P>,
+    // Commitments broadcast by the dealer, if the deal is compact
+    #[serde(default)]
+    pub commitments: Option<DealCommitments>,
+}
+
+/// ProtocolStatus summarizes the progress of a run of the protocol, as seen by a
+/// Dealer or a Verifier. Indices are positions in the list of verifiers, sorted.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProtocolStatus {
+    // Security parameter T, 0 if the deal hasn't been received yet
+    pub threshold: u32,
+    // Verifiers which approved the deal
+    pub approvals: Vec<u32>,
+    // Verifiers which complained about the deal, or didn't respond before the timeout
+    pub complaints: Vec<u32>,
+    // Verifiers which haven't responded yet
+    pub missing_responders: Vec<u32>,
+    // True if enough verifiers approved the deal
+    pub enough_approvals: bool,
+    // True if the deal is certified
+    pub deal_certified: bool,
+}
+
 /// Verifier receives a Deal from a Dealer, can reply with a Complaint, and can
 /// collaborate with other Verifiers to reconstruct a secret.
+#[cfg(feature = "std")]
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct Verifier {
-    // Longterm secret (priv key) of Verifier
-    longterm: FE,
+#[serde(bound(serialize = "K: Serialize", deserialize = "K: Deserialize<'de>"))]
+pub struct Verifier<P: CurvePoint = GE, K = SoftwareSigner<P>> {
+    // Longterm key of Verifier
+    longterm: K,
     // Public key of Verifier
-    pub_k: GE,
+    pub_k: P,
     // Public key of dealer
-    dealer: GE,
+    dealer: P,
     // Index of this verifier
     index: u32,
     // Pub keys of verifiers (including our pub key)
-    verifiers: Rc<[GE]>,
+    verifiers: Arc<[P]>,
     // Context for hkdf function
     hkdf_context: Vec<u8>,
-    aggregator: Aggregator,
+    aggregator: Aggregator<P>,
+    // KEM secret key, for the hybrid mode
+    #[cfg(feature = "pq-kem")]
+    #[serde(default)]
+    kem_secret: Option<Vec<u8>>,
+    // Application context bound to the session id
+    #[serde(default)]
+    context: Vec<u8>,
+    // Encrypted deal received and the response issued for it, to answer its
+    // re-deliveries
+    #[serde(default)]
+    received: Option<(EncryptedDeal<P>, Response)>,
+    // Commitments broadcast by the dealer, to verify a compact deal
+    #[serde(default)]
+    commitments: Option<DealCommitments>,
+    // Digests of the deals gossiped by the other verifiers, by index
+    #[serde(default)]
+    digests: HashMap<u32, DealDigest>,
 }
 
-pub fn valid_t(t: u32, verifiers: &[GE]) -> bool {
+/// VerifierIdentity is the longterm identity of a verifier in a list of
+/// verifiers, independent of any dealer. It spawns a Verifier per session,
+/// e.g. one per dealer of a DKG, sharing the list of verifiers and applying
+/// the context, domain and config of the identity.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(bound(serialize = "K: Serialize", deserialize = "K: Deserialize<'de>"))]
+pub struct VerifierIdentity<P: CurvePoint = GE, K = SoftwareSigner<P>> {
+    // Longterm key of the verifier
+    longterm: K,
+    // Index of the verifier
+    index: u32,
+    // Pub keys of verifiers, shared with the sessions
+    verifiers: Arc<[P]>,
+    // Application context and domain of the sessions
+    #[serde(default)]
+    context: Vec<u8>,
+    #[serde(default)]
+    domain: Domain,
+    // Certification options of the sessions
+    #[serde(default)]
+    config: VerifierConfig,
+}
+
+pub fn valid_t<P>(t: u32, verifiers: &[P]) -> bool {
     t >= 2 && t <= verifiers.len() as u32
 }
 
 // minimum_t returns the minimum safe T that is proven to be secure with this
-// protocol. It expects n, the total number of participants.
+// protocol. It expects n, the total number of participants. See the
+// thresholds module for the thresholds of other fault models.
 // WARNING: Setting a lower T could make
 // the whole protocol insecure. Setting a higher T only makes it harder to
 // reconstruct the secret.
@@ -164,7 +815,8 @@ pub fn minimum_t(n: u32) -> u32 {
     (n + 1) / 2
 }
 
-impl Dealer {
+#[cfg(feature = "std")]
+impl<P: CurvePoint> Dealer<P> {
     /// creates a Dealer capable of leading the secret sharing scheme. It
     /// does not have to be trusted by other Verifiers. The security parameter t is
     /// the number of shares required to reconstruct the secret. It is HIGHLY
@@ -177,39 +829,260 @@ impl Dealer {
     /// `verifiers`: list of pubkeys of verifiers
     /// `threshold`: security parameter t. Any t+1 share holders can recover the secret
     pub fn new(
-        longterm: FE,
-        secret: FE,
-        verifiers: Vec<GE>,
+        longterm: P::Scalar,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        threshold: u32,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        Dealer::new_with_rng(
+            longterm,
+            secret,
+            verifiers,
+            threshold,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as new, with the secret sharing polynomials drawn from rng, e.g. a
+    /// seeded DRBG to generate reproducible test vectors.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        threshold: u32,
+        rng: &mut R,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        Dealer::create(
+            SoftwareSigner::new(longterm),
+            secret,
+            verifiers,
+            None,
+            &[],
+            &Domain::default(),
+            threshold,
+            rng,
+        )
+    }
+
+    /// for_existing_key creates a Dealer sharing an existing private key, e.g.
+    /// to retire the single copy of a long-lived key in favor of its shares. It
+    /// checks that key is a valid non-zero scalar whose public key is expected,
+    /// and overwrites key with zero once the deals are created. For an ed25519
+    /// key, the scalar is given by ed25519_curve::import_secret_key.
+    pub fn for_existing_key(
+        longterm: P::Scalar,
+        key: &mut P::Scalar,
+        expected: &P,
+        verifiers: Vec<P>,
+        threshold: u32,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        Dealer::for_existing_key_with_rng(
+            longterm,
+            key,
+            expected,
+            verifiers,
+            threshold,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as for_existing_key, with the secret sharing polynomials drawn
+    /// from rng.
+    pub fn for_existing_key_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        key: &mut P::Scalar,
+        expected: &P,
+        verifiers: Vec<P>,
+        threshold: u32,
+        rng: &mut R,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        if *key == P::Scalar::zero() {
+            bail!("dealer: the key to share is zero");
+        }
+        if P::base_mul(key) != *expected {
+            bail!("dealer: the key to share doesn't match the expected public key");
+        }
+        let dealer = Dealer::new_with_rng(longterm, *key, verifiers, threshold, rng)?;
+        *key = P::Scalar::zero();
+        // keeps the write from being reordered or elided as a dead store
+        compiler_fence(Ordering::SeqCst);
+        Ok(dealer)
+    }
+
+    /// new_with_points creates a Dealer whose share for the verifier at index i
+    /// is the evaluation at points[i] instead of i+1, e.g. with points derived
+    /// from the public keys of the verifiers with points_from_keys, so that the
+    /// shares don't depend on the position of the verifiers in the list. The
+    /// points must be distinct and non-zero, and the verifiers must be given
+    /// the same points with Verifier::set_points.
+    pub fn new_with_points(
+        longterm: P::Scalar,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        points: Vec<P::Scalar>,
+        threshold: u32,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        Dealer::new_with_points_with_rng(
+            longterm,
+            secret,
+            verifiers,
+            points,
+            threshold,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as new_with_points, with the secret sharing polynomials drawn from
+    /// rng.
+    pub fn new_with_points_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        points: Vec<P::Scalar>,
+        threshold: u32,
+        rng: &mut R,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        check_points::<P>(&points, verifiers.len())?;
+        Dealer::create(
+            SoftwareSigner::new(longterm),
+            secret,
+            verifiers,
+            Some(points.into()),
+            &[],
+            &Domain::default(),
+            threshold,
+            rng,
+        )
+    }
+
+    /// new_weighted creates a Dealer sharing the secret among participants
+    /// holding weights[j] shares each, see the weighted module. The threshold
+    /// is a number of shares, and the encrypted deal at index i must be given
+    /// to the participant weights.owner(i).
+    pub fn new_weighted(
+        longterm: P::Scalar,
+        secret: P::Scalar,
+        participants: &[P],
+        weights: &Weights,
+        threshold: u32,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        Dealer::new_weighted_with_rng(
+            longterm,
+            secret,
+            participants,
+            weights,
+            threshold,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as new_weighted, with the secret sharing polynomials drawn from rng.
+    pub fn new_weighted_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        secret: P::Scalar,
+        participants: &[P],
+        weights: &Weights,
+        threshold: u32,
+        rng: &mut R,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        let verifiers: Vec<P> = weights.expand(participants)?;
+        Dealer::new_with_rng(longterm, secret, verifiers, threshold, rng)
+    }
+
+    /// key returns the longterm key pair used by this Dealer.
+    pub fn key(&self) -> (P::Scalar, P) {
+        (self.long.secret(), self.pub_key)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint, K: Signer<P>> Dealer<P, K> {
+    /// with_signer creates a Dealer as new, whose longterm key is held by
+    /// signer, e.g. in an HSM.
+    pub fn with_signer(
+        signer: K,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        threshold: u32,
+    ) -> Result<Dealer<P, K>, Box<dyn Error>> {
+        Dealer::with_signer_and_rng(
+            signer,
+            secret,
+            verifiers,
+            threshold,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as with_signer, with the secret sharing polynomials drawn from rng.
+    pub fn with_signer_and_rng<R: RngCore + CryptoRng>(
+        signer: K,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        threshold: u32,
+        rng: &mut R,
+    ) -> Result<Dealer<P, K>, Box<dyn Error>> {
+        Dealer::create(
+            signer,
+            secret,
+            verifiers,
+            None,
+            &[],
+            &Domain::default(),
+            threshold,
+            rng,
+        )
+    }
+
+    /// signer returns the longterm key of this Dealer.
+    pub fn signer(&self) -> &K {
+        &self.long
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create<R: RngCore + CryptoRng>(
+        signer: K,
+        secret: P::Scalar,
+        verifiers: Vec<P>,
+        points: Option<Arc<[P::Scalar]>>,
+        context: &[u8],
+        domain: &Domain,
         threshold: u32,
-    ) -> Result<Dealer, Box<dyn Error>> {
+        rng: &mut R,
+    ) -> Result<Dealer<P, K>, Box<dyn Error>> {
         if !valid_t(threshold, &verifiers) {
             bail!("Invalid threshold")
         }
 
-        let h: GE = derive_h(&verifiers)?;
-        let sec_pri_poly: PriPoly = PriPoly::new(threshold, Some(secret));
-        let rand_pri_poly: PriPoly = PriPoly::new(threshold, None);
+        let h: P = derive_h_in(domain.h_derivation(), &verifiers)?;
+        let sec_pri_poly: PriPoly<P> = PriPoly::new_with_rng(threshold, Some(secret), rng);
+        let rand_pri_poly: PriPoly<P> = PriPoly::new_with_rng(threshold, None, rng);
 
-        let generator = GE::generator();
-        let dealer_pub: GE = generator.scalar_mul(&longterm.get_element());
+        let generator = P::generator();
+        let dealer_pub: P = signer.public();
 
         // Compute public polynomial coefficients
-        let f_pub: PubPoly = sec_pri_poly.commit(Some(generator));
-        let g_pub: PubPoly = rand_pri_poly.commit(Some(h));
+        let f_pub: PubPoly<P> = sec_pri_poly.commit(Some(generator));
+        let g_pub: PubPoly<P> = rand_pri_poly.commit(Some(h));
 
-        let c: PubPoly = f_pub.add(&g_pub)?;
+        let c: PubPoly<P> = f_pub.add(&g_pub)?;
 
         let (_, commitments) = c.info();
-        let commitments: Vec<Vec<u8>> = commitments
-            .iter()
-            .map(|x| x.get_element().to_bytes().to_vec())
-            .collect();
-
-        let session_id: [u8; 32] = session_id(&dealer_pub, &verifiers, &commitments, threshold);
+        let commitments: Vec<Vec<u8>> = commitments.iter().map(|x| x.pk_to_key_slice()).collect();
+
+        let session_id: [u8; 32] = session_id_at(
+            domain,
+            &dealer_pub,
+            &verifiers,
+            points.as_deref(),
+            context,
+            &commitments,
+            threshold,
+        );
 
-        let verifiers: Rc<[GE]> = verifiers.into();
+        let verifiers: Arc<[P]> = verifiers.into();
 
-        let aggregator = Aggregator::new(
+        let mut aggregator = Aggregator::new(
             dealer_pub,
             verifiers.clone(),
             threshold,
@@ -218,30 +1091,61 @@ impl Dealer {
 
         // deals are to be encrypted and distributed to respective
         // verifiers, one deal per verifier
-        let deals: Vec<Deal> = (0..verifiers.len() as u32)
-            .map(|i| {
-                let sec_share: PriShare<FE> = sec_pri_poly.eval(i as u32);
-                let rnd_share: PriShare<FE> = rand_pri_poly.eval(i as u32);
-                Deal {
-                    session_id: session_id.to_vec(),
-                    sec_share,
-                    rnd_share,
-                    t: threshold,
-                    commitments: commitments.clone(),
-                }
+        let n = verifiers.len() as u32;
+        let eval_domain = points.as_deref().and_then(fft::domain_of::<P>);
+        let shares: Vec<(PriShare<P::Scalar>, PriShare<P::Scalar>)> = match (&points, eval_domain) {
+            // one FFT instead of n evaluations for the points of a domain
+            (_, Some(eval_domain)) => {
+                let sec_evals = eval_domain.fft(&sec_pri_poly.coeffs)?;
+                let rnd_evals = eval_domain.fft(&rand_pri_poly.coeffs)?;
+                (0..n)
+                    .zip(sec_evals.into_iter().zip(rnd_evals))
+                    .map(|(i, (sec, rnd))| (PriShare { i, v: sec }, PriShare { i, v: rnd }))
+                    .collect()
+            }
+            (Some(points), None) => (0..n)
+                .zip(points.iter())
+                .map(|(i, x)| {
+                    let sec_share = PriShare {
+                        i,
+                        v: sec_pri_poly.eval_at(x),
+                    };
+                    let rnd_share = PriShare {
+                        i,
+                        v: rand_pri_poly.eval_at(x),
+                    };
+                    (sec_share, rnd_share)
+                })
+                .collect(),
+            (None, None) => sec_pri_poly
+                .shares(n)
+                .into_iter()
+                .zip(rand_pri_poly.shares(n))
+                .collect(),
+        };
+        aggregator.points = points;
+        aggregator.domain = domain.clone();
+        let deals: Vec<Deal<P>> = shares
+            .into_iter()
+            .map(|(sec_share, rnd_share)| Deal {
+                session_id: session_id.to_vec(),
+                sec_share,
+                rnd_share,
+                t: threshold,
+                commitments: commitments.clone(),
+                context: context.to_vec(),
+                commitments_root: Vec::new(),
             })
             .collect();
 
-        let hkdf_context: Vec<u8> = dh::context(&dealer_pub, &verifiers);
+        let hkdf_context: Vec<u8> = dh::context_in(domain.hash_suite(), &dealer_pub, &verifiers);
 
         let (_, secret_commits) = f_pub.info();
-        let secret_commits: Vec<Vec<u8>> = secret_commits
-            .iter()
-            .map(|x| x.get_element().to_bytes().to_vec())
-            .collect();
+        let secret_commits: Vec<Vec<u8>> =
+            secret_commits.iter().map(|x| x.pk_to_key_slice()).collect();
 
         Ok(Dealer {
-            long: longterm,
+            long: signer,
             pub_key: dealer_pub,
             session_id: session_id.to_vec(),
             secret,
@@ -251,58 +1155,197 @@ impl Dealer {
             t: threshold,
             deals,
             aggregator,
+            aead: DealAead::default(),
+            mode: DealMode::default(),
+            #[cfg(feature = "pq-kem")]
+            kem_keys: Vec::new(),
+            compact: false,
+        })
+    }
+
+    /// set_aead sets the AEAD scheme encrypting the deals, AES256-GCM by
+    /// default.
+    pub fn set_aead(&mut self, aead: DealAead) {
+        self.aead = aead;
+    }
+
+    /// aead returns the AEAD scheme encrypting the deals.
+    pub fn aead(&self) -> DealAead {
+        self.aead
+    }
+
+    /// set_mode sets the key derivation encrypting the deals, DealMode::Legacy
+    /// by default.
+    pub fn set_mode(&mut self, mode: DealMode) {
+        self.mode = mode;
+    }
+
+    /// mode returns the key derivation encrypting the deals.
+    pub fn mode(&self) -> DealMode {
+        self.mode
+    }
+
+    /// set_compact_commitments makes the encrypted deals carry the Merkle root
+    /// of the commitments in place of the t commitments, which are broadcast
+    /// once to all the verifiers with deal_commitments. The messages of a
+    /// large committee shrink from O(n*t) to O(n + t).
+    pub fn set_compact_commitments(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    /// deal_commitments returns the commitments of the deals signed by the
+    /// dealer, to be broadcast to the verifiers before the compact deals.
+    pub fn deal_commitments(&self) -> Result<DealCommitments, Box<dyn Error>> {
+        let hash = DealCommitments::hash(&self.session_id, &self.deals[0].commitments);
+        let signature = self.long.sign(&hash, &[])?;
+        Ok(DealCommitments {
+            session_id: self.session_id.clone(),
+            commitments: self.deals[0].commitments.clone(),
+            signature,
         })
     }
 
+    /// set_identities sets the identities of the verifiers, in the order of
+    /// the verifiers, whose signing keys must be the verifiers: the deals are
+    /// encrypted to their encryption keys instead. It must be called before
+    /// the deals are encrypted.
+    pub fn set_identities(
+        &mut self,
+        identities: &[ParticipantIdentity<P>],
+    ) -> Result<(), Box<dyn Error>> {
+        self.aggregator.set_identities(identities)
+    }
+
+    /// set_kem_keys sets the KEM public keys of the verifiers, in the order of
+    /// the verifiers, to encrypt the deals in DealMode::Hybrid.
+    #[cfg(feature = "pq-kem")]
+    pub fn set_kem_keys(&mut self, keys: Vec<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+        if keys.len() != self.verifiers.len() {
+            bail!(
+                "dealer: {} KEM keys for {} verifiers",
+                keys.len(),
+                self.verifiers.len()
+            );
+        }
+        self.kem_keys = keys;
+        Ok(())
+    }
+
+    // encapsulate returns a KEM shared secret with the verifier at index i and
+    // its encapsulation in hybrid mode, and empty ones otherwise.
+    #[cfg(feature = "pq-kem")]
+    fn encapsulate(&self, i: u32) -> Result<(Vec<u8>, Vec<u8>), Box<dyn Error>> {
+        if self.mode != DealMode::Hybrid {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let public = self
+            .kem_keys
+            .get(i as usize)
+            .ok_or_else(|| simple_error!("dealer: no KEM key of verifier {}", i))?;
+        pq::encapsulate(public)
+    }
+
+    #[cfg(not(feature = "pq-kem"))]
+    fn encapsulate(&self, _i: u32) -> Result<(Vec<u8>, Vec<u8>), Box<dyn Error>> {
+        Ok((Vec::new(), Vec::new()))
+    }
+
     /// encrypt_deal returns the encryption of the deal that must be given to the
     /// verifier at index i.
     /// The dealer first generates a temporary Diffie Hellman key, signs it using its
     /// longterm key, and computes the shared key depending on its longterm and
     /// ephemeral key and the verifier's public key.
     /// This shared key is then fed into a HKDF whose output is the key to a AEAD
-    /// (AES256-GCM) scheme to encrypt the deal.
-    pub fn encrypt_deal(&self, i: u32) -> Result<EncryptedDeal, Box<dyn Error>> {
-        let v_pub = self
+    /// (see set_aead) scheme to encrypt the deal. Every call uses a fresh ephemeral
+    /// key and a fresh random nonce, which is sent along in the EncryptedDeal.
+    pub fn encrypt_deal(&self, i: u32) -> Result<EncryptedDeal<P>, Box<dyn Error>> {
+        self.encrypt_deal_with_rng(i, &mut utils::rand_hack())
+    }
+
+    /// encrypt_deal_to returns the encrypted deal of the verifier of the
+    /// public key, whose index is its position in the list of verifiers.
+    pub fn encrypt_deal_to(&self, verifier: &P) -> Result<EncryptedDeal<P>, Box<dyn Error>> {
+        let i = self
             .verifiers
+            .iter()
+            .position(|v| v == verifier)
+            .ok_or_else(|| simple_error!("vss: public key not found in the list of verifiers"))?;
+        self.encrypt_deal(i as u32)
+    }
+
+    /// Same as encrypt_deal, with the ephemeral key and the nonce drawn from rng.
+    pub fn encrypt_deal_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        i: u32,
+        rng: &mut R,
+    ) -> Result<EncryptedDeal<P>, Box<dyn Error>> {
+        let v_pub = self
+            .aggregator
+            .encryption_keys()
             .get(i as usize)
             .ok_or_else(|| simple_error!("dealer: wrong index to generate encrypted deal"))?;
 
         // gen ephemeral key
-        let generator = GE::generator();
-        let dh_secret: FE = ECScalar::new_random();
-        let dh_key: GE = generator.scalar_mul(&dh_secret.get_element());
-        // signs the public key
-        let dh_pub_buf: [u8; 32] = dh_key.get_element().to_bytes();
-        let signature = sign::sign_msg(
-            self.long.get_element().to_bytes(),
-            self.pub_key.get_element().to_bytes(),
-            &dh_pub_buf,
-            &i.to_le_bytes(),
-        )?;
+        let dh_secret: P::Scalar = ECScalar::new_random_with_rng(rng);
+        let dh_key: P = P::base_mul(&dh_secret);
 
-        // AES256-GCM
-        let pre: GE = dh::dh_exchange(&dh_secret, v_pub);
-        let gcm: Aes256Gcm = dh::new_aead(&pre, &self.hkdf_context);
+        let pre: P = dh::dh_exchange(&dh_secret, v_pub);
 
-        let nonce = GenericArray::from_slice(&[0u8; 12]);
+        // a fresh nonce for every encryption, so that no (key, nonce) pair is
+        // reused; HPKE derives it along with the key
+        let nonce: Vec<u8> = match self.mode {
+            DealMode::Legacy | DealMode::Hybrid => dh::new_nonce(rng).to_vec(),
+            DealMode::Hpke => Vec::new(),
+        };
+        // the KEM ciphertext leads the cipher in hybrid mode
+        let (kem_shared, mut cipher) = self.encapsulate(i)?;
+        let (key, aead_nonce) = self.mode.key(
+            self.aead,
+            &pre,
+            &kem_shared,
+            &dh_key,
+            v_pub,
+            &self.hkdf_context,
+            &nonce,
+        )?;
         let deal = self
             .deals
             .get(i as usize)
             .ok_or_else(|| simple_error!("dealer: wrong index to get deal"))?;
-        let deal_buff: Vec<u8> = bincode::serialize(deal)?;
-        let pay = Payload {
-            msg: deal_buff.as_ref(),
-            aad: self.hkdf_context.as_ref(),
+        let deal_buff: Vec<u8> = if self.compact {
+            bincode::serialize(&deal.compact(self.aggregator.domain.hash_suite()))?
+        } else {
+            bincode::serialize(deal)?
         };
-        let cipher = gcm
-            .encrypt(nonce, pay)
-            .map_err(|_| simple_error!("vss: encryption failure!"))?;
+        cipher.extend(
+            self.aead
+                .seal(&key, &aead_nonce, &self.hkdf_context, &deal_buff)?,
+        );
+
+        // signs the whole encrypted deal, bound to its recipient
+        let e_hash = EncryptedDeal::hash(
+            ENCRYPTED_DEAL_VERSION,
+            self.aead,
+            self.mode,
+            &self.session_id,
+            i,
+            &dh_key,
+            &nonce,
+            &cipher,
+        );
+        let signature = self
+            .long
+            .sign(&e_hash, &self.aggregator.domain.signing_context(i))?;
 
         Ok(EncryptedDeal {
+            version: ENCRYPTED_DEAL_VERSION,
+            session_id: self.session_id.clone(),
             cipher,
-            nonce: nonce.to_vec(),
+            nonce,
             dh_key,
             signature,
+            aead: self.aead,
+            mode: self.mode,
         })
     }
 
@@ -315,38 +1358,121 @@ impl Dealer {
     /// encrypt_deals calls encrypt_deal for each index of the verifier and
     /// returns the list of encrypted deals. Each index in the returned slice
     /// corresponds to the index in the list of verifiers.
-    pub fn encrypt_deals(&self) -> Result<Vec<EncryptedDeal>, Box<dyn Error>> {
+    pub fn encrypt_deals(&self) -> Result<Vec<EncryptedDeal<P>>, Box<dyn Error>> {
+        self.encrypt_deals_with_rng(&mut utils::rand_hack())
+    }
+
+    /// Same as encrypt_deals, drawing the randomness of the encryptions from rng.
+    pub fn encrypt_deals_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<Vec<EncryptedDeal<P>>, Box<dyn Error>> {
         (0..self.verifiers.len() as u32)
-            .map(|i| self.encrypt_deal(i))
+            .map(|i| self.encrypt_deal_with_rng(i, rng))
             .collect()
     }
 
-    /// process_response analyzes the given Response. If it's a valid complaint, then
-    /// it returns a Justification. This Justification must be broadcasted to every
-    /// participants. If it's an invalid complaint, it returns an error about the
-    /// complaint. The verifiers will also ignore an invalid Complaint.
+    /// encrypted_deals_addressed returns the encrypted deal of every verifier
+    /// along with its index and public key, and the suggested Envelope of the
+    /// deal as a message of the dealer at dealer_index in the DKG, so that no
+    /// deal is sent to another verifier than its recipient.
+    pub fn encrypted_deals_addressed(
+        &self,
+        dealer_index: u32,
+    ) -> Result<Vec<AddressedDeal<P>>, Box<dyn Error>> {
+        self.encrypted_deals_addressed_with_rng(dealer_index, &mut utils::rand_hack())
+    }
+
+    /// Same as encrypted_deals_addressed, drawing the randomness of the
+    /// encryptions from rng.
+    pub fn encrypted_deals_addressed_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        dealer_index: u32,
+        rng: &mut R,
+    ) -> Result<Vec<AddressedDeal<P>>, Box<dyn Error>> {
+        let mut deals = Vec::with_capacity(self.verifiers.len());
+        for (i, public) in self.verifiers.iter().enumerate() {
+            let deal = self.encrypt_deal_with_rng(i as u32, rng)?;
+            let msg = Message::Deal(dkg::Deal {
+                index: dealer_index,
+                deal: deal.clone(),
+            });
+            let envelope =
+                Envelope::seal_with_signer(&self.long, dealer_index, &self.session_id, &msg)?;
+            deals.push(AddressedDeal {
+                index: i as u32,
+                public: *public,
+                deal,
+                envelope,
+            });
+        }
+        Ok(deals)
+    }
+
+    /// process_response analyzes the given Response. If it's a valid complaint, then
+    /// it returns a Justification. This Justification must be broadcasted to every
+    /// participants. If it's an invalid complaint, it returns an error about the
+    /// complaint. The verifiers will also ignore an invalid Complaint.
     pub fn process_response(
         &mut self,
         r: &Response,
-    ) -> Result<Option<Justification>, Box<dyn Error>> {
-        self.aggregator.verify_response(r)?;
+    ) -> Result<Option<Justification<P>>, Box<dyn Error>> {
+        let _span = events::span("vss-dealer", &self.session_id, None, None);
+        let certified = self.aggregator.deal_certified();
+        let added = self.aggregator.verify_response(r)?;
+        self.justify(r, added, certified)
+    }
+
+    /// process_responses is process_response for several responses, whose
+    /// signatures are verified at once. If a signature is invalid, no
+    /// response is recorded and the error names the verifiers of the invalid
+    /// signatures. It returns the Justifications of the valid complaints.
+    pub fn process_responses(
+        &mut self,
+        rs: &[Response],
+    ) -> Result<Vec<Justification<P>>, Box<dyn Error>> {
+        let _span = events::span("vss-dealer", &self.session_id, None, None);
+        self.aggregator.verify_responses(rs)?;
+        let mut justifications: Vec<Justification<P>> = Vec::new();
+        for r in rs {
+            let certified = self.aggregator.deal_certified();
+            let added = self.aggregator.accept_response(r)?;
+            justifications.extend(self.justify(r, added, certified)?);
+        }
+        Ok(justifications)
+    }
+
+    // justify returns the Justification of a complaint just added, if any.
+    fn justify(
+        &mut self,
+        r: &Response,
+        added: bool,
+        certified: bool,
+    ) -> Result<Option<Justification<P>>, Box<dyn Error>> {
+        if !added {
+            return Ok(None);
+        }
+        self.aggregator.record_certified(certified);
 
-        if r.approved {
+        if r.approved || self.aggregator.certification == Certification::ApprovalOnly {
             return Ok(None);
         }
 
-        let j_hash = Justification::hash(&self.session_id, r.index, &self.deals[r.index as usize])?;
-        let signature = sign::sign_msg(
-            self.long.get_element().to_bytes(),
-            self.pub_key.get_element().to_bytes(),
-            &j_hash,
-            &r.index.to_le_bytes(),
-        )?;
+        let deal: Deal<P> = self
+            .deals
+            .get(r.index as usize)
+            .cloned()
+            .ok_or_else(|| simple_error!("dealer: index out of bounds in response"))?;
+        let j_hash =
+            Justification::hash_in(&self.aggregator.domain, &self.session_id, r.index, &deal)?;
+        let signature = self
+            .long
+            .sign(&j_hash, &self.aggregator.domain.signing_context(r.index))?;
 
         Ok(Some(Justification {
             session_id: self.session_id.clone(),
             index: r.index,
-            deal: self.deals[r.index as usize].clone(),
+            deal,
             signature,
         }))
     }
@@ -354,13 +1480,12 @@ impl Dealer {
     /// secret_commit returns the commitment of the secret being shared by this
     /// dealer. This function is only to be called once the deal has enough approvals
     /// and is verified otherwise it returns Err.
-    pub fn secret_commit(&self) -> Result<GE, Box<dyn Error>> {
+    pub fn secret_commit(&self) -> Result<P, Box<dyn Error>> {
         if !self.aggregator.enough_approvals() || !self.deal_certified() {
             bail!("Not enough approvas or the deal is not certified");
         }
 
-        let generator = GE::generator();
-        Ok(generator.scalar_mul(&self.secret.get_element()))
+        Ok(P::base_mul(&self.secret))
     }
 
     /// commits returns the commitments of the coefficient of the secret polynomial
@@ -373,11 +1498,6 @@ impl Dealer {
         Ok(self.secret_commits.clone())
     }
 
-    /// key returns the longterm key pair used by this Dealer.
-    pub fn key(&self) -> (FE, GE) {
-        (self.long, self.pub_key)
-    }
-
     /// get_session_id returns the current sessionID generated by this dealer for this
     /// protocol run.
     pub fn get_session_id(&self) -> &[u8] {
@@ -391,393 +1511,2507 @@ impl Dealer {
         self.aggregator.clean_verifiers()
     }
 
-    // unsafe_set_response_dkg is an UNSAFE bypass method to allow DKG to use VSS
-    // that works on basis of approval only.
-    pub(crate) fn unsafe_set_response_dkg(
-        &mut self,
-        index: u32,
-        approved: bool,
-    ) -> Result<(), Box<dyn Error>> {
-        self.aggregator.unsafe_set_response_dkg(index, approved)
+    /// set_deadline sets the time limit of the responses, enforced by tick
+    /// instead of a call to set_timeout. The deadline isn't serialized.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.aggregator.deadline = Some(deadline);
     }
-}
 
-impl Aggregator {
-    pub fn new(dealer: GE, verifiers: Rc<[GE]>, threshold: u32, session_id: Vec<u8>) -> Self {
-        Self {
-            dealer,
-            verifiers,
-            session_id,
-            threshold,
-            responses: HashMap::new(),
-            deal: Deal::default(),
-            bad_dealer: false,
-        }
+    /// tick takes care of the verifiers who have not responded, as
+    /// set_timeout, once now is past the deadline; with
+    /// Quorum::ThresholdWithDeadline it only records that the deadline passed.
+    /// It returns true if it did, which happens only once per deadline.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        self.aggregator.tick(now)
     }
 
-    pub fn verify_response(&mut self, r: &Response) -> Result<(), Box<dyn Error>> {
-        let s1: [u8; 32] = r.session_id.as_slice().try_into()?;
-        let s2: [u8; 32] = self.session_id.as_slice().try_into()?;
-
-        if !bitwise_eq(&s1, &s2) {
-            bail!("vss: receiving inconsistent sessionID in response");
-        }
-        let pub_k = self
-            .verifiers
-            .get(r.index as usize)
-            .ok_or_else(|| simple_error!("vss: index out of bounds in response"))?;
-        // schnorrkel PublicKey to verify signature
-        let response_h = r.hash_self()?;
+    /// responses returns the responses received by this dealer, by verifier index.
+    pub fn responses(&self) -> &HashMap<u32, Response> {
+        self.aggregator.responses()
+    }
 
-        sign::verify_signature(
-            pub_k.get_element().to_bytes().as_ref(),
-            r.signature.as_ref(),
-            response_h.as_ref(),
-            r.index.to_le_bytes().as_ref(),
-        )
-        .map_err(|e| simple_error!("vss: incorrect response signature: {}", e))?;
+    /// approvals returns the indices of the verifiers which approved the deal.
+    pub fn approvals(&self) -> Vec<u32> {
+        self.aggregator.approvals()
+    }
 
-        self.add_response(r)?;
+    /// complaints returns the indices of the verifiers which complained about
+    /// the deal, or were marked as complaining by set_timeout.
+    pub fn complaints(&self) -> Vec<u32> {
+        self.aggregator.complaints()
+    }
 
-        Ok(())
+    /// complaint_reasons returns the reasons of the complaints along with
+    /// the index of their verifier, ComplaintReason::Timeout for the ones
+    /// marked by set_timeout.
+    pub fn complaint_reasons(&self) -> Vec<(u32, Option<ComplaintReason>)> {
+        self.aggregator.complaint_reasons()
     }
 
-    pub fn add_response(&mut self, r: &Response) -> Result<(), Box<dyn Error>> {
-        if self.verifiers.len() <= r.index as usize {
-            bail!("vss: index out of bounds in Respose")
-        }
-        if self.responses.contains_key(&r.index) {
-            bail!("vss: already existing response from same origin")
-        }
-        self.responses.insert(r.index, r.clone());
+    /// missing_responders returns the indices of the verifiers which haven't
+    /// responded yet, i.e. the ones set_timeout would mark as complaining.
+    pub fn missing_responders(&self) -> Vec<u32> {
+        self.aggregator.missing_responders()
+    }
 
-        Ok(())
+    /// status returns a summary of the progress of the protocol.
+    pub fn status(&self) -> ProtocolStatus {
+        self.aggregator.status()
     }
 
-    pub(crate) fn unsafe_set_response_dkg(
-        &mut self,
-        index: u32,
-        approved: bool,
-    ) -> Result<(), Box<dyn Error>> {
-        let r = Response {
-            session_id: self.session_id.clone(),
-            index,
-            approved,
-            ..Default::default()
-        };
-        self.add_response(&r)
+    /// set_certification sets the rule deciding when the deal is certified,
+    /// Certification::Justified by default. It must be the same for the dealer
+    /// and all the verifiers.
+    pub fn set_certification(&mut self, certification: Certification) {
+        self.aggregator.certification = certification;
     }
 
-    // clean_verifiers checks the aggregator's response array and creates a StatusComplaint
-    // response for all verifiers who have no response in the array.
-    pub fn clean_verifiers(&mut self) {
-        for i in 0..self.verifiers.len() as u32 {
-            if self.responses.get(&i).is_none() {
-                let response = Response {
-                    session_id: self.deal.session_id.clone(),
-                    index: i,
-                    approved: false,
-                    ..Default::default()
-                };
-                self.responses.insert(i, response);
-            }
-        }
+    pub fn certification(&self) -> Certification {
+        self.aggregator.certification
     }
 
-    // enough_approvals returns true if enough verifiers have sent their approval for
-    // the deal they received.
-    pub fn enough_approvals(&self) -> bool {
-        let n_approved = self.responses.values().filter(|r| r.approved).count();
-        n_approved as u32 >= self.threshold
+    /// set_quorum sets the responses the certification rule waits for,
+    /// Quorum::Full by default.
+    pub fn set_quorum(&mut self, quorum: Quorum) {
+        self.aggregator.quorum = quorum;
     }
 
-    // deal_certified returns true if there has been less than t complaints, all
-    // Justifications were correct and if enough_approvals() returns true.
-    pub fn deal_certified(&self) -> bool {
-        if self.threshold == 0 {
-            return false;
-        }
+    pub fn quorum(&self) -> Quorum {
+        self.aggregator.quorum
+    }
 
-        let verifiers_stable =
-            (0..self.verifiers.len() as u32).all(|i| self.responses.contains_key(&i));
+    /// set_policy installs a CertificationPolicy deciding when the deal is
+    /// certified in place of the certification rule. It isn't serialized,
+    /// and must be set again on a restored state.
+    pub fn set_policy(&mut self, policy: Arc<dyn CertificationPolicy<P>>) {
+        self.aggregator.policy = Some(policy);
+    }
 
-        let too_much_complaints: bool = !verifiers_stable || self.bad_dealer;
-        self.enough_approvals() && !too_much_complaints
+    /// aggregator returns a read-only view of the responses received.
+    pub fn aggregator(&self) -> AggregatorView<P> {
+        self.aggregator.view()
     }
-}
 
-impl Verifier {
-    /// new_verifier returns a Verifier out of:
-    /// - its longterm secret key
-    /// - the longterm dealer public key
-    /// - the list of public key of verifiers. The list MUST include the public key
-    /// of this Verifier also.
-    /// The security parameter t of the secret sharing scheme is automatically set to
-    /// a default safe value. If a different t value is required, it is possible to set
-    /// it with verifier.set_t().
-    pub fn new(longterm: FE, dealer: GE, verifiers: Vec<GE>) -> Result<Verifier, Box<dyn Error>> {
-        let verifiers: Rc<[GE]> = verifiers.into();
+    /// set_dealer_index declares the dealer as the verifier at index, as in a
+    /// DKG where every participant deals to all the participants including
+    /// itself. The dealer approves its own deal without sending a Response.
+    pub fn set_dealer_index(&mut self, index: u32) -> Result<(), Box<dyn Error>> {
+        self.aggregator.set_dealer_index(index)
+    }
 
-        let generator = GE::generator();
-        let pub_k: GE = generator.scalar_mul(&longterm.get_element());
+    /// set_dealer_role checks the role of the dealer against the list of
+    /// verifiers. A DealerRole::Participant is declared as the verifier of
+    /// its public key, as with set_dealer_index.
+    pub fn set_dealer_role(&mut self, role: DealerRole) -> Result<(), Box<dyn Error>> {
+        let position = self.aggregator.dealer_position(role)?;
+        if let (DealerRole::Participant, Some(i)) = (role, position) {
+            self.aggregator.set_dealer_index(i)?;
+        }
+        self.aggregator.dealer_role = role;
+        Ok(())
+    }
 
-        let index = verifiers
-            .iter()
-            .position(|point| point == &pub_k)
-            .map(|i| i as u32)
-            .ok_or_else(|| simple_error!("vss: public key not found in the list of verifiers"))?;
-        let hkdf_context = dh::context(&dealer, &verifiers);
+    pub fn dealer_role(&self) -> DealerRole {
+        self.aggregator.dealer_role
+    }
 
-        let aggregator = Aggregator::new(dealer, verifiers.clone(), 0, Vec::new());
+    /// own_deal returns the deal of the dealer itself, once declared as a
+    /// verifier with set_dealer_role or set_dealer_index.
+    pub fn own_deal(&self) -> Option<&Deal<P>> {
+        self.aggregator
+            .dealer_index
+            .and_then(|i| self.deals.get(i as usize))
+    }
 
-        Ok(Verifier {
-            longterm,
-            pub_k,
-            dealer,
-            verifiers,
-            hkdf_context,
-            index,
-            aggregator,
-        })
+    /// certificate returns the Certificate of the deal, once it is certified.
+    pub fn certificate(&self) -> Result<Certificate<P>, Box<dyn Error>> {
+        let context = self.deals.first().map_or(&[][..], |d| &d.context[..]);
+        self.aggregator.certificate(&self.secret_commits, context)
     }
+}
 
-    // process_encrypted_deal decrypt the deal received from the Dealer.
-    /// If the deal is valid, i.e. the verifier can verify its shares
-    /// against the public coefficients and the signature is valid, an approval
-    /// response is returned and must be broadcasted to every participants
-    /// including the dealer.
-    /// If the deal itself is invalid, it returns a complaint response that must be
-    /// broadcasted to every other participants including the dealer.
-    /// If the deal has already been received, or the signature generation of the
-    /// response failed, it returns an error without any responses.
-    pub fn process_encrypted_deal(
-        &mut self,
-        encr_d: &EncryptedDeal,
-    ) -> Result<Response, Box<dyn Error>> {
-        let deal = self.decrypt_deal(encr_d)?;
+/// DealerConfig holds the options of a Dealer built with a DealerBuilder.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct DealerConfig<P: CurvePoint = GE> {
+    /// Security parameter t, minimum_t(n) if None
+    pub threshold: Option<u32>,
+    /// Secret to share, drawn at random if None
+    pub secret: Option<P::Scalar>,
+    /// Application context bound to the session id. The verifiers must be
+    /// given the same with Verifier::set_context.
+    pub context: Vec<u8>,
+    /// Domain of the session ids, hashes and signatures. The verifiers must be
+    /// given the same with Verifier::set_domain.
+    pub domain: Domain,
+    /// AEAD scheme encrypting the deals
+    pub aead: DealAead,
+    /// Rule deciding when the deal is certified
+    pub certification: Certification,
+    /// Responses the rule waits for before certifying the deal
+    pub quorum: Quorum,
+    /// Whether the dealer is one of the verifiers
+    pub dealer_role: DealerRole,
+    /// Time limit of the responses from the creation of the Dealer, enforced
+    /// by Dealer::tick
+    pub timeout: Option<Duration>,
+}
 
-        if deal.sec_share.i != self.index {
-            bail!("vss: verifier got wrong index from deal")
-        }
+/// VerifierConfig holds the certification options of a Verifier, applied
+/// with Verifier::set_config. They must match the ones of the DealerConfig.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct VerifierConfig {
+    /// Rule deciding when the deal is certified
+    pub certification: Certification,
+    /// Responses the rule waits for before certifying the deal
+    pub quorum: Quorum,
+    /// Whether the dealer is one of the verifiers
+    #[serde(default)]
+    pub dealer_role: DealerRole,
+    /// Time limit of the responses from the call to set_config, enforced by
+    /// Verifier::tick or by Quorum::ThresholdWithDeadline
+    pub timeout: Option<Duration>,
+    /// Time limit of the justifications from the call to set_config, after
+    /// which Verifier::tick disqualifies a dealer leaving a complaint
+    /// unanswered
+    #[serde(default)]
+    pub justification_timeout: Option<Duration>,
+}
 
-        let session_id = session_id(&self.dealer, self.verifiers(), &deal.commitments, deal.t);
+/// ConfigError is the error of a DealerBuilder given an invalid
+/// configuration.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The list of verifiers is empty.
+    NoVerifiers,
+    /// The verifier at the given index is listed twice.
+    DuplicateVerifier(u32),
+    /// The threshold isn't between 2 and the number of verifiers n.
+    InvalidThreshold { t: u32, n: u32 },
+    /// The timeout is zero.
+    ZeroTimeout,
+    /// The external dealer is the verifier at the given index.
+    ExternalDealerListed(u32),
+    /// The participating dealer isn't in the list of verifiers.
+    DealerNotListed,
+}
 
-        if deal.session_id != session_id {
-            bail!("vss: session id doesn't match");
+#[cfg(feature = "std")]
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NoVerifiers => write!(f, "vss: no verifiers"),
+            ConfigError::DuplicateVerifier(i) => {
+                write!(f, "vss: verifier {} appears twice in the verifiers", i)
+            }
+            ConfigError::InvalidThreshold { t, n } => write!(
+                f,
+                "vss: invalid threshold {} for {} verifiers, it must be between 2 and {}",
+                t, n, n
+            ),
+            ConfigError::ZeroTimeout => write!(f, "vss: zero timeout"),
+            ConfigError::ExternalDealerListed(i) => {
+                write!(f, "vss: external dealer at index {} of the verifiers", i)
+            }
+            ConfigError::DealerNotListed => write!(f, "vss: dealer not in the list of verifiers"),
         }
+    }
+}
 
-        if self.aggregator.threshold == 0 {
-            self.aggregator = Aggregator::new(
-                self.dealer,
-                self.verifiers.clone(),
-                deal.t,
-                deal.session_id.clone(),
-            );
+#[cfg(feature = "std")]
+impl Error for ConfigError {}
+
+/// EquivocationError is the error of a signed message conflicting with the
+/// one already received from the same origin. It holds the message received
+/// first and the conflicting one, e.g. for an Evidence.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub enum EquivocationError<P: CurvePoint = GE> {
+    /// The dealer signed two different deals for this verifier.
+    Deal(Box<EncryptedDeal<P>>, Box<EncryptedDeal<P>>),
+    /// A verifier signed both an approval and a complaint.
+    Response(Response, Response),
+    /// Two verifiers signed digests of different deals: the dealer sent them
+    /// different commitments, unless the second verifier lies about its deal.
+    Digest(DealDigest, DealDigest),
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint> fmt::Display for EquivocationError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquivocationError::Deal(..) => write!(f, "vss: the dealer sent two different deals"),
+            EquivocationError::Response(r, _) => write!(
+                f,
+                "vss: verifier {} sent an approval and a complaint",
+                r.index
+            ),
+            EquivocationError::Digest(_, d) => write!(
+                f,
+                "vss: verifier {} received a deal of other commitments",
+                d.index
+            ),
         }
+    }
+}
 
-        if self.aggregator.deal.t != 0 {
-            bail!("vss: verifier already received a deal")
+#[cfg(feature = "std")]
+impl<P: CurvePoint> Error for EquivocationError<P> {}
+
+/// DealerBuilder builds a Dealer out of a DealerConfig, checking it first,
+/// e.g. `DealerBuilder::new(longterm, verifiers).threshold(3).build()`.
+///
+/// ```rust
+/// # use threshold_dkg::curve_traits::{ECPoint, ECScalar};
+/// # use threshold_dkg::ristretto_curve::{FE, GE};
+/// # use threshold_dkg::vss::DealerBuilder;
+/// # let keys: Vec<FE> = (0..5).map(|_| ECScalar::new_random()).collect();
+/// # let verifiers: Vec<GE> = keys.iter().map(|k| GE::generator().scalar_mul(&k.get_element())).collect();
+/// let dealer = DealerBuilder::new(keys[0], verifiers)
+///     .threshold(3)
+///     .context(b"my-app")
+///     .build()
+///     .unwrap();
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct DealerBuilder<P: CurvePoint = GE> {
+    longterm: P::Scalar,
+    verifiers: Vec<P>,
+    config: DealerConfig<P>,
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint> DealerBuilder<P> {
+    /// new starts the configuration of a Dealer with the longterm private key
+    /// of the dealer and the public keys of the verifiers.
+    pub fn new(longterm: P::Scalar, verifiers: Vec<P>) -> DealerBuilder<P> {
+        DealerBuilder {
+            longterm,
+            verifiers,
+            config: DealerConfig::default(),
         }
+    }
 
-        self.aggregator.session_id = deal.session_id.clone();
-        self.aggregator.deal = deal.clone();
+    /// from_group starts the configuration of a Dealer sharing to the
+    /// participants of the group, with its threshold, its hash as context and
+    /// its domain. The verifiers are created with Verifier::for_group.
+    pub fn from_group(longterm: P::Scalar, group: &GroupConfig<P>) -> DealerBuilder<P> {
+        DealerBuilder::new(longterm, group.participants.clone())
+            .threshold(group.threshold)
+            .context(&group.hash())
+            .domain(&group.domain())
+    }
 
-        let approved = deal.verify(&self.aggregator.verifiers, &session_id).is_ok();
+    /// config replaces the whole configuration.
+    pub fn config(mut self, config: DealerConfig<P>) -> Self {
+        self.config = config;
+        self
+    }
 
-        let r_hash = Response::hash(&session_id, self.index, approved as u32)?;
-        let signature = sign::sign_msg(
-            self.longterm.get_element().to_bytes(),
-            self.pub_k.get_element().to_bytes(),
-            &r_hash,
-            &self.index.to_le_bytes(),
-        )?;
+    pub fn threshold(mut self, t: u32) -> Self {
+        self.config.threshold = Some(t);
+        self
+    }
 
-        let response = Response {
-            index: self.index,
-            session_id: session_id.to_vec(),
-            approved,
-            signature,
-        };
+    pub fn secret(mut self, secret: P::Scalar) -> Self {
+        self.config.secret = Some(secret);
+        self
+    }
 
-        self.aggregator.add_response(&response)?;
+    pub fn context(mut self, context: &[u8]) -> Self {
+        self.config.context = context.to_vec();
+        self
+    }
 
-        Ok(response)
+    /// session_context binds the session to the application label and epoch
+    /// of the context, as context does with its encoding.
+    pub fn session_context(self, context: &SessionContext) -> Self {
+        self.context(&context.encode())
     }
 
-    pub fn decrypt_deal(&mut self, encr_d: &EncryptedDeal) -> Result<Deal, Box<dyn Error>> {
-        let eph_buff = encr_d.dh_key.get_element().to_bytes();
+    /// domain separates the session and its messages from the ones of other
+    /// deployments using the same longterm keys.
+    pub fn domain(mut self, domain: &Domain) -> Self {
+        self.config.domain = domain.clone();
+        self
+    }
 
-        // verify signature
-        sign::verify_signature(
-            self.dealer.get_element().to_bytes().as_ref(),
-            encr_d.signature.as_ref(),
-            eph_buff.as_ref(),
-            self.index.to_le_bytes().as_ref(),
-        )
-        .map_err(|e| simple_error!("vss: signature verification failed: {}", e))?;
+    pub fn aead(mut self, aead: DealAead) -> Self {
+        self.config.aead = aead;
+        self
+    }
 
-        // compute shared key and AES526-GCM cipher
-        let pre: GE = dh::dh_exchange(&self.longterm, &encr_d.dh_key);
-        let gcm = dh::new_aead(&pre, &self.hkdf_context);
-        let nonce = GenericArray::from_slice(encr_d.nonce.as_slice());
-        let decrypted = gcm.decrypt(
-            nonce,
-            Payload {
-                msg: encr_d.cipher.as_ref(),
-                aad: self.hkdf_context.as_ref(),
-            },
-        );
-        let decrypted_vec =
-            decrypted.map_err(|_| simple_error!("vss: failed decrypt AES526-GCM cipher deal"))?;
-        let decoded: Deal = bincode::deserialize(&decrypted_vec[..])?;
+    pub fn certification(mut self, certification: Certification) -> Self {
+        self.config.certification = certification;
+        self
+    }
 
-        Ok(decoded)
+    pub fn quorum(mut self, quorum: Quorum) -> Self {
+        self.config.quorum = quorum;
+        self
     }
 
-    pub fn deal_certified(&self) -> bool {
-        self.aggregator.deal_certified()
+    pub fn dealer_role(mut self, role: DealerRole) -> Self {
+        self.config.dealer_role = role;
+        self
     }
 
-    pub fn enough_approvals(&self) -> bool {
-        self.aggregator.enough_approvals()
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
     }
 
-    // process_justification takes a Justification from dealer and returns an error if
-    // something went wrong during the verification. If it is the case, that
-    // probably means the Dealer is acting maliciously. In order to be sure, call
-    // `v.EnoughApprovals()` and if true, `v.DealCertified()`.
-    pub fn process_justification(
-        &mut self,
-        justification: &Justification,
-    ) -> Result<(), Box<dyn Error>> {
-        if self.aggregator.verifiers.len() <= justification.index as usize {
-            bail!("vss: index out of bounds in justification")
+    /// check returns the threshold of the configuration, or the ConfigError
+    /// describing why it is invalid.
+    pub fn check(&self) -> Result<u32, ConfigError> {
+        let n = self.verifiers.len() as u32;
+        if n == 0 {
+            return Err(ConfigError::NoVerifiers);
         }
-
-        // copy our session id to use later when we borrow mut self
-        let session_id = self.session_id().to_vec();
-        if session_id != justification.deal.session_id {
-            bail!("vss: session id doesn't match");
+        for (i, v) in self.verifiers.iter().enumerate() {
+            if self.verifiers[..i].contains(v) {
+                return Err(ConfigError::DuplicateVerifier(i as u32));
+            }
         }
-
-        let resp: &mut Response = self
-            .aggregator
-            .responses
-            .get_mut(&justification.index)
-            .ok_or_else(|| simple_error!("vss: no complaints received for this justification"))?;
-
-        if resp.approved {
-            bail!("vss: justification received for an approval")
+        let t = self.config.threshold.unwrap_or_else(|| minimum_t(n));
+        if !valid_t(t, &self.verifiers) {
+            return Err(ConfigError::InvalidThreshold { t, n });
         }
-
-        // if aggregator isn't fully initialized
-        if self.aggregator.deal.t == 0 {
-            self.aggregator.session_id = justification.deal.session_id.clone();
-            self.aggregator.deal = justification.deal.clone();
+        if self.config.timeout == Some(Duration::from_secs(0)) {
+            return Err(ConfigError::ZeroTimeout);
         }
-
-        let verif = justification
-            .deal
-            .verify(&self.aggregator.verifiers, &session_id);
-
-        if let Err(e) = verif {
-            self.aggregator.bad_dealer = true;
-            bail!(e);
+        let dealer = P::base_mul(&self.longterm);
+        match (
+            self.config.dealer_role,
+            self.verifiers.iter().position(|v| v == &dealer),
+        ) {
+            (DealerRole::External, Some(i)) => {
+                return Err(ConfigError::ExternalDealerListed(i as u32))
+            }
+            (DealerRole::Participant, None) => return Err(ConfigError::DealerNotListed),
+            _ => (),
         }
+        Ok(t)
+    }
 
-        resp.approved = true;
+    /// build returns the Dealer, or a ConfigError if the configuration is
+    /// invalid.
+    pub fn build(self) -> Result<Dealer<P>, Box<dyn Error>> {
+        self.build_with_rng(&mut utils::rand_hack())
+    }
 
-        Ok(())
+    /// Same as build, with the secret, if not given, and the secret sharing
+    /// polynomials drawn from rng.
+    pub fn build_with_rng<R: RngCore + CryptoRng>(
+        self,
+        rng: &mut R,
+    ) -> Result<Dealer<P>, Box<dyn Error>> {
+        let t = self.check()?;
+        let secret = match self.config.secret {
+            Some(secret) => secret,
+            None => ECScalar::new_random_with_rng(rng),
+        };
+        let mut dealer = Dealer::create(
+            SoftwareSigner::new(self.longterm),
+            secret,
+            self.verifiers,
+            None,
+            &self.config.context,
+            &self.config.domain,
+            t,
+            rng,
+        )?;
+        dealer.set_aead(self.config.aead);
+        dealer.set_certification(self.config.certification);
+        dealer.set_quorum(self.config.quorum);
+        dealer.set_dealer_role(self.config.dealer_role)?;
+        if let Some(timeout) = self.config.timeout {
+            dealer.set_deadline(Instant::now() + timeout);
+        }
+        Ok(dealer)
     }
+}
 
-    pub fn set_timeout(&mut self) {
-        self.aggregator.clean_verifiers();
+#[cfg(feature = "std")]
+impl<P: CurvePoint> Aggregator<P> {
+    pub fn new(dealer: P, verifiers: Arc<[P]>, threshold: u32, session_id: Vec<u8>) -> Self {
+        Self {
+            dealer,
+            verifiers,
+            session_id,
+            threshold,
+            responses: HashMap::new(),
+            deal: Deal::default(),
+            bad_dealer: false,
+            certification: Certification::default(),
+            quorum: Quorum::default(),
+            encryption_keys: None,
+            dealer_role: DealerRole::default(),
+            dealer_index: None,
+            points: None,
+            deadline: None,
+            justification_deadline: None,
+            started: Some(Instant::now()),
+            justified: HashSet::new(),
+            domain: Domain::default(),
+            policy: None,
+            signed: HashMap::new(),
+            deadline_passed: false,
+        }
     }
 
-    // index returns the index of the verifier in the list of participants used
-    // during this run of the protocol.
-    pub fn index(&self) -> u32 {
-        self.index
+    // set_identities sets the encryption keys of the identities, whose
+    // signing keys must be the verifiers.
+    fn set_identities(
+        &mut self,
+        identities: &[ParticipantIdentity<P>],
+    ) -> Result<(), Box<dyn Error>> {
+        if identities.len() != self.verifiers.len()
+            || identities
+                .iter()
+                .zip(self.verifiers.iter())
+                .any(|(id, v)| id.signing != *v)
+        {
+            bail!("vss: identities not matching the verifiers");
+        }
+        self.encryption_keys = Some(identities.iter().map(|id| id.encryption).collect());
+        Ok(())
     }
 
-    // key returns the longterm key pair this verifier is using during this protocol
-    // run.
-    pub fn key(&self) -> (FE, GE) {
-        (self.longterm, self.pub_k)
+    // encryption_keys returns the keys receiving the deals of the verifiers.
+    fn encryption_keys(&self) -> &[P] {
+        self.encryption_keys.as_deref().unwrap_or(&self.verifiers)
     }
 
-    // returns session id for this protocol run.
-    pub fn session_id(&self) -> &[u8] {
-        &self.aggregator.deal.session_id
+    // view returns a read-only view of the aggregator.
+    pub fn view(&self) -> AggregatorView<P> {
+        AggregatorView { aggregator: self }
     }
 
-    // get_deal returns the Deal that this verifier has received. It returns
-    // error if the deal is not certified or there is not enough approvals.
-    pub fn get_deal(&self) -> Result<Deal, Box<dyn Error>> {
-        if !self.aggregator.enough_approvals() || !self.aggregator.deal_certified() {
-            bail!("Not enough approvals or deal not certified");
+    // process_complaint judges a complaint and records its response, see
+    // Verifier::process_complaint.
+    pub(crate) fn process_complaint(
+        &mut self,
+        c: &Complaint<P>,
+        context: &[u8],
+    ) -> Result<bool, Box<dyn Error>> {
+        let certified = self.deal_certified();
+        if let Some(points) = self.points.as_deref() {
+            check_points::<P>(points, self.verifiers.len())?;
         }
+        let dealer_faulty = c.judge_with(
+            &self.dealer,
+            &self.verifiers,
+            self.encryption_keys(),
+            self.points.as_deref(),
+            context,
+            &self.domain,
+        )?;
+
+        match self.responses.get(&c.response.index) {
+            Some(r) if r == &c.response => (),
+            Some(_) => bail!("vss: complaint not matching the response received"),
+            None => {
+                self.verify_response(&c.response)?;
+            }
+        }
+
+        if dealer_faulty {
+            self.bad_dealer = true;
+        } else if self.certification == Certification::Justified {
+            if let Some(r) = self.responses.get_mut(&c.response.index) {
+                r.approved = true;
+            }
+        }
+        self.record_certified(certified);
+        Ok(dealer_faulty)
+    }
+
+    // process_justification verifies a justification of the dealer, see
+    // Verifier::process_justification.
+    pub(crate) fn process_justification(
+        &mut self,
+        justification: &Justification<P>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.certification == Certification::ApprovalOnly {
+            bail!("vss: no justification with the approval-only certification")
+        }
+        let certified = self.deal_certified();
+        if self.verifiers.len() <= justification.index as usize {
+            bail!("vss: index out of bounds in justification")
+        }
+
+        if self.session_id.is_empty() || self.session_id != justification.deal.session_id {
+            bail!("vss: session id doesn't match");
+        }
+        if justification.deal.sec_share.i != justification.index {
+            bail!("vss: justification about another deal");
+        }
+
+        match self.responses.get(&justification.index) {
+            Some(r) if r.approved => bail!("vss: justification received for an approval"),
+            Some(_) => (),
+            // the complaint hasn't been received, e.g. by a late verifier: the
+            // signature of the dealer binds the justification to the session
+            None => {
+                if !bitwise_eq(&justification.session_id, &self.session_id) {
+                    bail!("vss: session id doesn't match");
+                }
+                justification.verify_signature(&self.dealer, &self.domain)?;
+            }
+        }
+
+        // if the deal isn't known yet, as for a Watcher
+        if self.deal.t == 0 {
+            self.deal = justification.deal.clone();
+        }
+
+        if let Err(e) = self.verify_deal(&justification.deal, &self.session_id) {
+            self.bad_dealer = true;
+            metrics::with(|m| m.verification_failed("justification"));
+            bail!(e);
+        }
+
+        match self.responses.get_mut(&justification.index) {
+            Some(r) => r.approved = true,
+            None => {
+                self.justified.insert(justification.index);
+            }
+        }
+        events::record(Event::JustificationAccepted {
+            index: justification.index,
+        });
+        self.record_certified(certified);
+
+        Ok(())
+    }
+
+    // tick records that the deadline has passed and calls clean_verifiers,
+    // unless the quorum lets the late responses in, and calls
+    // end_justifications once the justification deadline has passed. It
+    // returns true if it did either.
+    fn tick(&mut self, now: Instant) -> bool {
+        let mut ticked = false;
+        match self.deadline {
+            Some(deadline) if now >= deadline => {
+                self.deadline = None;
+                self.deadline_passed = true;
+                if self.quorum != Quorum::ThresholdWithDeadline {
+                    self.clean_verifiers();
+                }
+                ticked = true;
+            }
+            _ => (),
+        }
+        match self.justification_deadline {
+            Some(deadline) if now >= deadline => {
+                self.justification_deadline = None;
+                self.end_justifications();
+                ticked = true;
+            }
+            _ => (),
+        }
+        ticked
+    }
+
+    // pending_justifications returns the sorted indices of the verifiers whose
+    // signed complaint hasn't been answered by a valid justification.
+    fn pending_justifications(&self) -> Vec<u32> {
+        let mut indices: Vec<u32> = self
+            .responses
+            .values()
+            .filter(|r| !r.approved && !r.signature.is_empty())
+            .map(|r| r.index)
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    // end_justifications ends the justification phase: the verifiers which
+    // haven't responded are timed out, and the dealer is bad if a complaint is
+    // left unanswered. It returns true if the dealer is bad.
+    fn end_justifications(&mut self) -> bool {
+        self.justification_deadline = None;
+        if !self.missing_responders().is_empty() {
+            self.clean_verifiers();
+        }
+        if !self.pending_justifications().is_empty() {
+            self.bad_dealer = true;
+        }
+        self.bad_dealer
+    }
+
+    // point returns the x-coordinate of the share at index i.
+    fn point(&self, i: u32) -> Result<P::Scalar, Box<dyn Error>> {
+        match &self.points {
+            Some(points) => points
+                .get(i as usize)
+                .copied()
+                .ok_or_else(|| simple_error!("vss: index out of bounds in Deal").into()),
+            None => Ok(P::Scalar::from(i as u64 + 1)),
+        }
+    }
+
+    // verify_deal verifies the deal at the x-coordinate of its share.
+    fn verify_deal(&self, deal: &Deal<P>, sid: &[u8]) -> Result<(), Box<dyn Error>> {
+        deal.verify_in(
+            &self.domain,
+            &self.verifiers,
+            sid,
+            &self.point(deal.sec_share.i)?,
+        )
+    }
+
+    // deal_complaint returns the reason to complain about the deal, or None
+    // if it verifies.
+    fn deal_complaint(&self, deal: &Deal<P>, sid: &[u8]) -> Option<ComplaintReason> {
+        match self.point(deal.sec_share.i) {
+            Ok(x) => deal.complaint_reason(&self.domain, &self.verifiers, sid, &x),
+            Err(_) => Some(ComplaintReason::InvalidDeal),
+        }
+    }
+
+    // verify_response checks a response and adds it. It returns false if the
+    // response was already received.
+    pub fn verify_response(&mut self, r: &Response) -> Result<bool, Box<dyn Error>> {
+        metrics::check(
+            "response",
+            r.verify_in(&self.domain, &self.verifiers, &self.session_id),
+        )?;
+        self.accept_response(r)
+    }
+
+    // verify_responses checks several responses at once, before any of them
+    // is added with accept_response.
+    fn verify_responses(&self, rs: &[Response]) -> Result<(), Box<dyn Error>> {
+        metrics::check(
+            "response",
+            Response::verify_batch_in(rs, &self.domain, &self.verifiers, &self.session_id),
+        )
+    }
+
+    // accept_response adds a verified response. It returns false if the
+    // response was already received.
+    fn accept_response(&mut self, r: &Response) -> Result<bool, Box<dyn Error>> {
+        let added = self.add_response(r)?;
+        if added {
+            self.record_response(r);
+        }
+
+        Ok(added)
+    }
+
+    // add_response adds a response, or returns false if the same response was
+    // already received from its verifier, e.g. re-delivered by the transport.
+    // A signed response conflicting with the one received gives an
+    // EquivocationError.
+    pub fn add_response(&mut self, r: &Response) -> Result<bool, Box<dyn Error>> {
+        if self.verifiers.len() <= r.index as usize {
+            bail!("vss: index out of bounds in Respose")
+        }
+        if let Some(existing) = self.responses.get(&r.index) {
+            // responses added on timeout or for the dealer aren't signed
+            if existing.signature.is_empty() || r.signature.is_empty() {
+                bail!("vss: already existing response from same origin")
+            }
+            // a complaint approved by a justification is compared as signed
+            let signed = self.signed.get(&r.index).unwrap_or(existing);
+            if signed.signature == r.signature
+                || (signed.approved == r.approved && signed.reason == r.reason)
+            {
+                return Ok(false);
+            }
+            return Err(Box::new(EquivocationError::<P>::Response(
+                signed.clone(),
+                r.clone(),
+            )));
+        }
+        if !r.signature.is_empty() {
+            self.signed.insert(r.index, r.clone());
+        }
+        let mut r = r.clone();
+        // the complaint was justified before it was received
+        r.approved |= self.justified.contains(&r.index);
+        self.responses.insert(r.index, r);
+
+        Ok(true)
+    }
+
+    // set_dealer_index records the implicit approval of the dealer, being the
+    // verifier at index.
+    pub fn set_dealer_index(&mut self, index: u32) -> Result<(), Box<dyn Error>> {
+        let r = Response {
+            session_id: self.session_id.clone(),
+            index,
+            approved: true,
+            ..Default::default()
+        };
+        self.add_response(&r)?;
+        self.dealer_index = Some(index);
+        Ok(())
+    }
+
+    // dealer_position returns the index of the dealer in the verifiers, and
+    // checks it against the dealer role.
+    fn dealer_position(&self, role: DealerRole) -> Result<Option<u32>, Box<dyn Error>> {
+        let position = self
+            .verifiers
+            .iter()
+            .position(|v| v == &self.dealer)
+            .map(|i| i as u32);
+        match (role, position) {
+            (DealerRole::External, Some(i)) => {
+                bail!("vss: external dealer at index {} of the verifiers", i)
+            }
+            (DealerRole::Participant, None) => bail!("vss: dealer not in the list of verifiers"),
+            _ => Ok(position),
+        }
+    }
+
+    // clean_verifiers checks the aggregator's response array and creates a StatusComplaint
+    // response for all verifiers who have no response in the array.
+    pub fn clean_verifiers(&mut self) {
+        let certified = self.deal_certified();
+        for i in 0..self.verifiers.len() as u32 {
+            if self.responses.get(&i).is_none() {
+                let approved = self.justified.contains(&i);
+                let response = Response {
+                    session_id: self.session_id.clone(),
+                    index: i,
+                    approved,
+                    reason: Some(ComplaintReason::Timeout).filter(|_| !approved),
+                    ..Default::default()
+                };
+                self.responses.insert(i, response);
+            }
+        }
+        self.record_certified(certified);
+    }
+
+    // responses returns the responses received so far, by verifier index.
+    pub fn responses(&self) -> &HashMap<u32, Response> {
+        &self.responses
+    }
+
+    // approvals returns the sorted indices of the verifiers which approved the deal.
+    pub fn approvals(&self) -> Vec<u32> {
+        self.responders(true)
+    }
+
+    // complaints returns the sorted indices of the verifiers which complained.
+    pub fn complaints(&self) -> Vec<u32> {
+        self.responders(false)
+    }
+
+    // complaint_reasons returns the reasons of the complaints, by increasing
+    // index, None for a complaint without a reason.
+    pub fn complaint_reasons(&self) -> Vec<(u32, Option<ComplaintReason>)> {
+        self.complaints()
+            .into_iter()
+            .map(|i| (i, self.responses[&i].reason))
+            .collect()
+    }
+
+    fn responders(&self, approved: bool) -> Vec<u32> {
+        let mut indices: Vec<u32> = self
+            .responses
+            .iter()
+            .filter(|(_, r)| r.approved == approved)
+            .map(|(&i, _)| i)
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    // missing_responders returns the sorted indices of the verifiers which
+    // haven't responded yet.
+    pub fn missing_responders(&self) -> Vec<u32> {
+        (0..self.verifiers.len() as u32)
+            .filter(|i| !self.responses.contains_key(i))
+            .collect()
+    }
+
+    // status summarizes the progress of the protocol.
+    pub fn status(&self) -> ProtocolStatus {
+        ProtocolStatus {
+            threshold: self.threshold,
+            approvals: self.approvals(),
+            complaints: self.complaints(),
+            missing_responders: self.missing_responders(),
+            enough_approvals: self.enough_approvals(),
+            deal_certified: self.deal_certified(),
+        }
+    }
+
+    // enough_approvals returns true if enough verifiers have sent their approval for
+    // the deal they received.
+    pub fn enough_approvals(&self) -> bool {
+        let n_approved = self.responses.values().filter(|r| r.approved).count();
+        n_approved as u32 >= self.threshold
+    }
+
+    // deal_certified returns true if the deal is certified by the policy if
+    // any, or by the certification rule otherwise.
+    pub fn deal_certified(&self) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+        match &self.policy {
+            Some(policy) => policy.deal_certified(&self.view()),
+            None => self.rule_certified(),
+        }
+    }
+
+    // rule_certified returns true if there has been less than t complaints, all
+    // Justifications were correct and if enough_approvals() returns true, once
+    // the responses of the quorum are in. With the approval-only rule, all the
+    // verifiers must have approved instead.
+    fn rule_certified(&self) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+
+        if self.certification == Certification::ApprovalOnly {
+            return !self.bad_dealer
+                && (0..self.verifiers.len() as u32)
+                    .all(|i| self.responses.get(&i).map_or(false, |r| r.approved));
+        }
+
+        let verifiers_stable =
+            (0..self.verifiers.len() as u32).all(|i| self.responses.contains_key(&i));
+        let quorum_reached = match self.quorum {
+            Quorum::Full => verifiers_stable,
+            Quorum::Threshold => true,
+            Quorum::ThresholdWithDeadline => verifiers_stable || self.deadline_passed,
+            Quorum::Unanimous => (0..self.verifiers.len() as u32)
+                .all(|i| self.responses.get(&i).map_or(false, |r| r.approved)),
+        };
+
+        let too_much_complaints: bool = !quorum_reached || self.bad_dealer;
+        self.enough_approvals() && !too_much_complaints
+    }
+
+    // certificate returns the Certificate of the certified deal of the given
+    // commitments and context, with the approvals signed by their verifier. A
+    // complaint answered by a justification, or the approval of the dealer
+    // itself, isn't signed as an approval and is left out.
+    fn certificate(
+        &self,
+        commitments: &[Vec<u8>],
+        context: &[u8],
+    ) -> Result<Certificate<P>, Box<dyn Error>> {
+        if !self.deal_certified() {
+            bail!("vss: deal not certified");
+        }
+        let mut responses: Vec<Response> = self
+            .responses
+            .values()
+            .filter(|r| {
+                r.approved
+                    && r.verify_in(&self.domain, &self.verifiers, &self.session_id)
+                        .is_ok()
+            })
+            .cloned()
+            .collect();
+        if responses.len() < self.threshold as usize {
+            bail!("vss: not enough signed approvals for a certificate");
+        }
+        responses.sort_by_key(|r| r.index);
+        Ok(Certificate {
+            session_id: self.session_id.clone(),
+            t: self.threshold,
+            commitments: commitments.to_vec(),
+            context: context.to_vec(),
+            points: self.points.as_deref().map(<[P::Scalar]>::to_vec),
+            responses,
+        })
+    }
+
+    // record_certified records the Certified event if the deal got certified,
+    // given whether it was before the last message.
+    pub(crate) fn record_certified(&self, was_certified: bool) {
+        if !was_certified && self.deal_certified() {
+            events::record(Event::Certified);
+            if let Some(started) = self.started {
+                metrics::with(|m| m.session_certified(started.elapsed()));
+            }
+        }
+    }
+
+    // record_response records the event of a response added to the
+    // aggregator, and its metrics.
+    pub(crate) fn record_response(&self, r: &Response) {
+        events::record(r.event());
+        metrics::with(|m| {
+            if !r.approved {
+                m.complaint_filed();
+            }
+            if let Some(started) = self.started {
+                m.response_received(started.elapsed());
+            }
+        });
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint> Verifier<P> {
+    /// new_verifier returns a Verifier out of:
+    /// - its longterm secret key
+    /// - the longterm dealer public key
+    /// - the list of public key of verifiers. The list MUST include the public key
+    /// of this Verifier also.
+    /// The security parameter t of the secret sharing scheme is automatically set to
+    /// a default safe value. If a different t value is required, it is possible to set
+    /// it with verifier.set_t().
+    pub fn new(
+        longterm: P::Scalar,
+        dealer: P,
+        verifiers: Vec<P>,
+    ) -> Result<Verifier<P>, Box<dyn Error>> {
+        Verifier::with_signer(SoftwareSigner::new(longterm), dealer, verifiers)
+    }
+
+    /// for_group returns a Verifier of a Dealer built with
+    /// DealerBuilder::from_group, which rejects the deals of another group.
+    pub fn for_group(
+        longterm: P::Scalar,
+        dealer: P,
+        group: &GroupConfig<P>,
+    ) -> Result<Verifier<P>, Box<dyn Error>> {
+        group.check()?;
+        let mut verifier = Verifier::new(longterm, dealer, group.participants.clone())?;
+        verifier.set_context(&group.hash())?;
+        verifier.set_domain(&group.domain())?;
+        Ok(verifier)
+    }
+
+    /// new_at returns a Verifier for the share at the given index, whose public
+    /// key must be the one of longterm. Unlike new, the public key may appear
+    /// several times in the list of verifiers, e.g. with weighted sharing.
+    pub fn new_at(
+        longterm: P::Scalar,
+        dealer: P,
+        verifiers: Vec<P>,
+        index: u32,
+    ) -> Result<Verifier<P>, Box<dyn Error>> {
+        Verifier::with_signer_at(SoftwareSigner::new(longterm), dealer, verifiers, index)
+    }
+
+    // key returns the longterm key pair this verifier is using during this protocol
+    // run.
+    pub fn key(&self) -> (P::Scalar, P) {
+        (self.longterm.secret(), self.pub_k)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint> VerifierIdentity<P> {
+    /// new returns the identity of the verifier of the longterm key, which
+    /// must be in the list of verifiers.
+    pub fn new(
+        longterm: P::Scalar,
+        verifiers: Arc<[P]>,
+    ) -> Result<VerifierIdentity<P>, Box<dyn Error>> {
+        VerifierIdentity::with_signer(SoftwareSigner::new(longterm), verifiers)
+    }
+
+    /// new_at returns the identity of the verifier at the given index, as
+    /// Verifier::new_at.
+    pub fn new_at(
+        longterm: P::Scalar,
+        verifiers: Arc<[P]>,
+        index: u32,
+    ) -> Result<VerifierIdentity<P>, Box<dyn Error>> {
+        VerifierIdentity::with_signer_at(SoftwareSigner::new(longterm), verifiers, index)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint, K: Signer<P> + KeyAgreement<P> + Clone> VerifierIdentity<P, K> {
+    /// with_signer is new for a longterm key held by signer.
+    pub fn with_signer(
+        signer: K,
+        verifiers: Arc<[P]>,
+    ) -> Result<VerifierIdentity<P, K>, Box<dyn Error>> {
+        let pub_k: P = signer.public();
+        let index = verifiers
+            .iter()
+            .position(|point| point == &pub_k)
+            .map(|i| i as u32)
+            .ok_or_else(|| simple_error!("vss: public key not found in the list of verifiers"))?;
+        VerifierIdentity::with_signer_at(signer, verifiers, index)
+    }
+
+    /// with_signer_at is new_at for a longterm key held by signer.
+    pub fn with_signer_at(
+        signer: K,
+        verifiers: Arc<[P]>,
+        index: u32,
+    ) -> Result<VerifierIdentity<P, K>, Box<dyn Error>> {
+        if verifiers.get(index as usize) != Some(&signer.public()) {
+            bail!(
+                "vss: public key not found at index {} of the verifiers",
+                index
+            );
+        }
+        Ok(VerifierIdentity {
+            longterm: signer,
+            index,
+            verifiers,
+            context: Vec::new(),
+            domain: Domain::default(),
+            config: VerifierConfig::default(),
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn public(&self) -> P {
+        self.longterm.public()
+    }
+
+    pub fn verifiers(&self) -> &[P] {
+        &self.verifiers
+    }
+
+    /// set_context sets the application context of the sessions, see
+    /// Verifier::set_context.
+    pub fn set_context(&mut self, context: &[u8]) {
+        self.context = context.to_vec();
+    }
+
+    /// set_domain sets the domain of the sessions, see Verifier::set_domain.
+    pub fn set_domain(&mut self, domain: &Domain) {
+        self.domain = domain.clone();
+    }
+
+    /// set_config sets the certification options of the sessions, the
+    /// timeout starting at the creation of each session.
+    pub fn set_config(&mut self, config: &VerifierConfig) {
+        self.config = config.clone();
+    }
+
+    /// session returns a Verifier of the deal of the dealer, sharing the list
+    /// of verifiers of the identity.
+    pub fn session(&self, dealer: P) -> Result<Verifier<P, K>, Box<dyn Error>> {
+        let mut verifier = Verifier::create(
+            self.longterm.clone(),
+            dealer,
+            self.verifiers.clone(),
+            self.index,
+        )?;
+        verifier.context = self.context.clone();
+        verifier.set_domain(&self.domain)?;
+        verifier.set_config(&self.config);
+        Ok(verifier)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint> Verifier<P, IdentityKeys<P>> {
+    /// with_identity returns a Verifier of distinct signing and encryption
+    /// keys, out of the identities of all the verifiers.
+    pub fn with_identity(
+        keys: IdentityKeys<P>,
+        dealer: P,
+        identities: &[ParticipantIdentity<P>],
+    ) -> Result<Verifier<P, IdentityKeys<P>>, Box<dyn Error>> {
+        let identity = keys.identity();
+        let verifiers: Vec<P> = identities.iter().map(|id| id.signing).collect();
+        let mut verifier = Verifier::with_signer(keys, dealer, verifiers)?;
+        if identities[verifier.index as usize] != identity {
+            bail!("vss: encryption key not matching the identity of the verifier");
+        }
+        verifier.set_identities(identities)?;
+        Ok(verifier)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint, K: Signer<P> + KeyAgreement<P>> Verifier<P, K> {
+    /// with_signer returns a Verifier as new, whose longterm key is held by
+    /// signer, e.g. in an HSM.
+    pub fn with_signer(
+        signer: K,
+        dealer: P,
+        verifiers: Vec<P>,
+    ) -> Result<Verifier<P, K>, Box<dyn Error>> {
+        let pub_k: P = signer.public();
+        let index = verifiers
+            .iter()
+            .position(|point| point == &pub_k)
+            .map(|i| i as u32)
+            .ok_or_else(|| simple_error!("vss: public key not found in the list of verifiers"))?;
+        Verifier::with_signer_at(signer, dealer, verifiers, index)
+    }
+
+    /// signer returns the longterm key of this Verifier.
+    pub fn signer(&self) -> &K {
+        &self.longterm
+    }
+
+    /// identity returns the VerifierIdentity of this Verifier, to spawn the
+    /// sessions of other dealers with the same context, domain and
+    /// certification options.
+    pub fn identity(&self) -> VerifierIdentity<P, K>
+    where
+        K: Clone,
+    {
+        VerifierIdentity {
+            longterm: self.longterm.clone(),
+            index: self.index,
+            verifiers: self.verifiers.clone(),
+            context: self.context.clone(),
+            domain: self.aggregator.domain.clone(),
+            config: VerifierConfig {
+                certification: self.aggregator.certification,
+                quorum: self.aggregator.quorum,
+                dealer_role: self.aggregator.dealer_role,
+                timeout: None,
+                justification_timeout: None,
+            },
+        }
+    }
+
+    /// with_signer_at is new_at for a longterm key held by signer.
+    pub fn with_signer_at(
+        signer: K,
+        dealer: P,
+        verifiers: Vec<P>,
+        index: u32,
+    ) -> Result<Verifier<P, K>, Box<dyn Error>> {
+        Verifier::create(signer, dealer, verifiers.into(), index)
+    }
+
+    // create returns the Verifier at index of the shared list of verifiers.
+    fn create(
+        signer: K,
+        dealer: P,
+        verifiers: Arc<[P]>,
+        index: u32,
+    ) -> Result<Verifier<P, K>, Box<dyn Error>> {
+        let pub_k: P = signer.public();
+        if verifiers.get(index as usize) != Some(&pub_k) {
+            bail!(
+                "vss: public key not found at index {} of the verifiers",
+                index
+            );
+        }
+        let hkdf_context = dh::context(&dealer, &verifiers);
+
+        let aggregator = Aggregator::new(dealer, verifiers.clone(), 0, Vec::new());
+
+        Ok(Verifier {
+            longterm: signer,
+            pub_k,
+            dealer,
+            verifiers,
+            hkdf_context,
+            index,
+            aggregator,
+            #[cfg(feature = "pq-kem")]
+            kem_secret: None,
+            context: Vec::new(),
+            received: None,
+            commitments: None,
+            digests: HashMap::new(),
+        })
+    }
+
+    // process_encrypted_deal decrypt the deal received from the Dealer.
+    /// If the deal is valid, i.e. the verifier can verify its shares
+    /// against the public coefficients and the signature is valid, an approval
+    /// response is returned and must be broadcasted to every participants
+    /// including the dealer.
+    /// If the deal itself is invalid, it returns a complaint response that must be
+    /// broadcasted to every other participants including the dealer.
+    /// A re-delivery of the deal already processed returns the same response,
+    /// while another deal signed by the dealer gives an EquivocationError. If
+    /// the signature generation of the response failed, it returns an error
+    /// without any responses.
+    pub fn process_encrypted_deal(
+        &mut self,
+        encr_d: &EncryptedDeal<P>,
+    ) -> Result<Response, Box<dyn Error>> {
+        let _span = events::span("vss-verifier", &encr_d.session_id, Some(self.index), None);
+        if let Some((prev, response)) = &self.received {
+            if prev == encr_d {
+                return Ok(response.clone());
+            }
+            verify_deal_signature(&self.aggregator.domain, &self.dealer, self.index, encr_d)?;
+            // the dealer may encrypt the same deal again
+            let (prev, response) = (prev.clone(), response.clone());
+            match self
+                .decrypt_deal(encr_d)
+                .and_then(|deal| self.expand_deal(deal))
+            {
+                Ok(deal) if deal.encode() == self.aggregator.deal.encode() => return Ok(response),
+                _ => {
+                    return Err(Box::new(EquivocationError::Deal(
+                        Box::new(prev),
+                        Box::new(encr_d.clone()),
+                    )))
+                }
+            }
+        }
+        let deal = metrics::check("deal", self.decrypt_deal(encr_d))?;
+        let deal = self.expand_deal(deal)?;
+
+        if deal.sec_share.i != self.index {
+            bail!("vss: verifier got wrong index from deal")
+        }
+
+        if !bitwise_eq(&deal.context, &self.context) {
+            bail!("vss: deal bound to another application context or epoch");
+        }
+
+        let session_id = session_id_at(
+            &self.aggregator.domain,
+            &self.dealer,
+            self.verifiers(),
+            self.aggregator.points.as_deref(),
+            &self.context,
+            &deal.commitments,
+            deal.t,
+        );
+
+        if deal.session_id != session_id {
+            bail!("vss: session id doesn't match");
+        }
+
+        if self.aggregator.threshold == 0 {
+            let certification = self.aggregator.certification;
+            let points = self.aggregator.points.take();
+            let deadline = self.aggregator.deadline;
+            let justification_deadline = self.aggregator.justification_deadline;
+            let domain = std::mem::take(&mut self.aggregator.domain);
+            let quorum = self.aggregator.quorum;
+            let encryption_keys = self.aggregator.encryption_keys.take();
+            let dealer_role = self.aggregator.dealer_role;
+            let policy = self.aggregator.policy.take();
+            let deadline_passed = self.aggregator.deadline_passed;
+            self.aggregator = Aggregator::new(
+                self.dealer,
+                self.verifiers.clone(),
+                deal.t,
+                deal.session_id.clone(),
+            );
+            self.aggregator.certification = certification;
+            self.aggregator.points = points;
+            self.aggregator.deadline = deadline;
+            self.aggregator.justification_deadline = justification_deadline;
+            self.aggregator.domain = domain;
+            self.aggregator.quorum = quorum;
+            self.aggregator.encryption_keys = encryption_keys;
+            self.aggregator.dealer_role = dealer_role;
+            self.aggregator.policy = policy;
+            self.aggregator.deadline_passed = deadline_passed;
+        }
+
+        if self.aggregator.deal.t != 0 {
+            bail!("vss: verifier already received a deal")
+        }
+        let dealer_index = self
+            .aggregator
+            .dealer_position(self.aggregator.dealer_role)?;
+
+        self.aggregator.session_id = deal.session_id.clone();
+        self.aggregator.deal = deal.clone();
+
+        let reason = self.aggregator.deal_complaint(&deal, &session_id);
+        let approved = reason.is_none();
+
+        let r_hash = Response::hash_reason_in(
+            &self.aggregator.domain,
+            &session_id,
+            self.index,
+            approved as u32,
+            reason,
+        )?;
+        let signature = self
+            .longterm
+            .sign(&r_hash, &self.aggregator.domain.signing_context(self.index))?;
+
+        let response = Response {
+            index: self.index,
+            session_id: session_id.to_vec(),
+            approved,
+            signature,
+            reason,
+        };
+
+        metrics::with(|m| m.deal_processed(approved));
+        if !approved {
+            metrics::with(|m| m.verification_failed("deal"));
+        }
+        self.aggregator.add_response(&response)?;
+        self.aggregator.record_response(&response);
+        self.received = Some((encr_d.clone(), response.clone()));
+        match dealer_index {
+            Some(i)
+                if self.aggregator.dealer_role == DealerRole::Participant && i != self.index =>
+            {
+                self.aggregator.set_dealer_index(i)?
+            }
+            _ => (),
+        }
+
+        Ok(response)
+    }
+
+    pub fn decrypt_deal(&mut self, encr_d: &EncryptedDeal<P>) -> Result<Deal<P>, Box<dyn Error>> {
+        if encr_d.version != ENCRYPTED_DEAL_VERSION {
+            bail!(
+                "vss: unsupported encrypted deal version {}, expected {}",
+                encr_d.version,
+                ENCRYPTED_DEAL_VERSION
+            );
+        }
+
+        // cheap checks before the signature
+        wire::check_encrypted_deal(encr_d)?;
+        verify_deal_signature(&self.aggregator.domain, &self.dealer, self.index, encr_d)?;
+
+        // compute shared key and decrypt
+        let pre: P = self.longterm.dh(&encr_d.dh_key)?;
+        let recipient: P = self.aggregator.encryption_keys()[self.index as usize];
+        open_deal(
+            &pre,
+            &recipient,
+            self.kem_secret(),
+            &self.hkdf_context,
+            encr_d,
+            self.max_threshold(),
+        )
+        .map_err(|err| self.suite_mismatch(&pre, &recipient, encr_d).unwrap_or(err))
+    }
+
+    // suite_mismatch returns the error of a deal which doesn't decrypt with
+    // the hash suite of this verifier but with another one, if any.
+    fn suite_mismatch(
+        &self,
+        pre: &P,
+        recipient: &P,
+        encr_d: &EncryptedDeal<P>,
+    ) -> Option<Box<dyn Error>> {
+        let own = self.aggregator.domain.hash_suite();
+        let other = HashSuite::all().iter().find(|&&suite| {
+            suite != own
+                && open_deal(
+                    pre,
+                    recipient,
+                    self.kem_secret(),
+                    &dh::context_in(suite, &self.dealer, &self.verifiers),
+                    encr_d,
+                    self.max_threshold(),
+                )
+                .is_ok()
+        })?;
+        let err: Box<dyn Error> = simple_error!(
+            "vss: deal of hash suite {} instead of {}",
+            other.id(),
+            own.id()
+        )
+        .into();
+        Some(err)
+    }
+
+    /// process_commitments records the commitments broadcast by the dealer,
+    /// see Dealer::set_compact_commitments. They must be processed before a
+    /// compact deal.
+    pub fn process_commitments(&mut self, c: &DealCommitments) -> Result<(), Box<dyn Error>> {
+        c.verify(&self.dealer)?;
+        match &self.commitments {
+            Some(prev) if prev.session_id != c.session_id || prev.commitments != c.commitments => {
+                bail!("vss: dealer broadcast conflicting commitments")
+            }
+            _ => self.commitments = Some(c.clone()),
+        }
+        Ok(())
+    }
+
+    // expand_deal returns the deal with the commitments broadcast by the
+    // dealer if it is compact, and the deal itself otherwise.
+    fn expand_deal(&self, mut deal: Deal<P>) -> Result<Deal<P>, Box<dyn Error>> {
+        if deal.commitments_root.is_empty() {
+            return Ok(deal);
+        }
+        let c = self
+            .commitments
+            .as_ref()
+            .ok_or_else(|| simple_error!("vss: commitments of the compact deal not received"))?;
+        deal.expand(self.aggregator.domain.hash_suite(), &c.commitments)?;
+        Ok(deal)
+    }
+
+    /// set_identities sets the identities of the verifiers, as
+    /// Dealer::set_identities: the deal of this verifier is decrypted with
+    /// the key agreement of its longterm key, and the complaints of the others
+    /// are judged with their encryption keys.
+    pub fn set_identities(
+        &mut self,
+        identities: &[ParticipantIdentity<P>],
+    ) -> Result<(), Box<dyn Error>> {
+        self.aggregator.set_identities(identities)
+    }
+
+    /// set_kem_key sets the KEM secret key of this verifier, to decrypt the
+    /// deals in DealMode::Hybrid.
+    #[cfg(feature = "pq-kem")]
+    pub fn set_kem_key(&mut self, secret: Vec<u8>) {
+        self.kem_secret = Some(secret);
+    }
+
+    #[cfg(feature = "pq-kem")]
+    fn kem_secret(&self) -> Option<&[u8]> {
+        self.kem_secret.as_deref()
+    }
+
+    #[cfg(not(feature = "pq-kem"))]
+    fn kem_secret(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// complaint returns the Complaint of this verifier about the given deal,
+    /// to be broadcasted in place of its complaint Response. The deal must not
+    /// have been approved; if it hasn't been processed at all, e.g. because it
+    /// couldn't be decrypted, a complaint Response is issued for it.
+    pub fn complaint(&mut self, encr_d: &EncryptedDeal<P>) -> Result<Complaint<P>, Box<dyn Error>> {
+        if encr_d.mode == DealMode::Hybrid {
+            bail!("vss: can't complain about a hybrid deal");
+        }
+        verify_deal_signature(&self.aggregator.domain, &self.dealer, self.index, encr_d)?;
+
+        let response: Response = match self.aggregator.responses.get(&self.index) {
+            Some(r) if r.approved => bail!("vss: can't complain about an approved deal"),
+            Some(r) => r.clone(),
+            None => {
+                let reason = Some(ComplaintReason::DecryptFailed);
+                let r_hash = Response::hash_reason_in(
+                    &self.aggregator.domain,
+                    &encr_d.session_id,
+                    self.index,
+                    0,
+                    reason,
+                )?;
+                let signature = self
+                    .longterm
+                    .sign(&r_hash, &self.aggregator.domain.signing_context(self.index))?;
+                let r = Response {
+                    session_id: encr_d.session_id.clone(),
+                    index: self.index,
+                    approved: false,
+                    signature,
+                    reason,
+                };
+                self.aggregator.add_response(&r)?;
+                r
+            }
+        };
+        if !bitwise_eq(&response.session_id, &encr_d.session_id) {
+            bail!("vss: complaint about a deal of another session");
+        }
+
+        let (dh_shared, proof) = self.longterm.prove_dh(&encr_d.dh_key)?;
+        Ok(Complaint {
+            response,
+            deal: encr_d.clone(),
+            dh_shared,
+            proof,
+            commitments: self.commitments.clone(),
+        })
+    }
+
+    /// digest returns the DealDigest of the deal received, signed by this
+    /// verifier, to be gossiped to the other verifiers.
+    pub fn digest(&self) -> Result<DealDigest, Box<dyn Error>> {
+        if self.aggregator.deal.t == 0 {
+            bail!("vss: no deal received");
+        }
+        let root = commitments_root_in(
+            self.aggregator.domain.hash_suite(),
+            &self.aggregator.deal.commitments,
+        );
+        let hash = DealDigest::hash_in(
+            &self.aggregator.domain,
+            &self.aggregator.session_id,
+            self.index,
+            &root,
+        );
+        let signature = self
+            .longterm
+            .sign(&hash, &self.aggregator.domain.signing_context(self.index))?;
+        Ok(DealDigest {
+            session_id: self.aggregator.session_id.clone(),
+            index: self.index,
+            commitments_root: root.to_vec(),
+            signature,
+        })
+    }
+
+    /// process_digest checks the DealDigest gossiped by another verifier
+    /// against the deal received, which must be processed first. A digest of
+    /// another session or other commitments gives an
+    /// EquivocationError::Digest, and is listed by digest_conflicts.
+    pub fn process_digest(&mut self, digest: &DealDigest) -> Result<(), Box<dyn Error>> {
+        if digest.index == self.index {
+            bail!("vss: digest of this verifier");
+        }
+        digest.verify_in(&self.aggregator.domain, &self.verifiers)?;
+        let own = self.digest()?;
+        match self.digests.get(&digest.index) {
+            Some(prev) if !prev.same_deal(digest) => {
+                bail!("vss: verifier sent two different digests")
+            }
+            Some(_) => (),
+            None => {
+                self.digests.insert(digest.index, digest.clone());
+            }
+        }
+        if !own.same_deal(digest) {
+            return Err(Box::new(EquivocationError::<P>::Digest(
+                own,
+                digest.clone(),
+            )));
+        }
+        Ok(())
+    }
+
+    /// digest_conflicts returns the sorted indices of the verifiers whose
+    /// digest doesn't match the deal received.
+    pub fn digest_conflicts(&self) -> Vec<u32> {
+        let root = commitments_root_in(
+            self.aggregator.domain.hash_suite(),
+            &self.aggregator.deal.commitments,
+        );
+        let mut indices: Vec<u32> = self
+            .digests
+            .values()
+            .filter(|d| {
+                !bitwise_eq(&d.session_id, &self.aggregator.session_id)
+                    || !bitwise_eq(&d.commitments_root, &root)
+            })
+            .map(|d| d.index)
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    /// process_complaint judges the Complaint of another verifier and records
+    /// its response. It returns true if the dealer is at fault, in which case
+    /// the deal will never be certified. Otherwise the complaint is unfounded:
+    /// the revealed deal is valid and counts as approved, as with a
+    /// Justification. It returns an error if the complaint itself is invalid.
+    pub fn process_complaint(&mut self, c: &Complaint<P>) -> Result<bool, Box<dyn Error>> {
+        let _span = events::span(
+            "vss-verifier",
+            &c.response.session_id,
+            Some(self.index),
+            None,
+        );
+        self.aggregator.process_complaint(c, &self.context)
+    }
+
+    pub fn deal_certified(&self) -> bool {
+        self.aggregator.deal_certified()
+    }
+
+    pub fn enough_approvals(&self) -> bool {
+        self.aggregator.enough_approvals()
+    }
+
+    // process_justification takes a Justification from dealer and returns an error if
+    // something went wrong during the verification. If it is the case, that
+    // probably means the Dealer is acting maliciously. In order to be sure, call
+    // `v.EnoughApprovals()` and if true, `v.DealCertified()`.
+    pub fn process_justification(
+        &mut self,
+        justification: &Justification<P>,
+    ) -> Result<(), Box<dyn Error>> {
+        let _span = events::span(
+            "vss-verifier",
+            &justification.session_id,
+            Some(self.index),
+            None,
+        );
+        self.aggregator.process_justification(justification)
+    }
+
+    /// process_justifications is process_justification for several
+    /// justifications, whose signatures are verified at once before any of
+    /// them is processed. The error of invalid signatures names the indices
+    /// of the justifications.
+    pub fn process_justifications(
+        &mut self,
+        justifications: &[Justification<P>],
+    ) -> Result<(), Box<dyn Error>> {
+        let _span = events::span("vss-verifier", self.session_id(), Some(self.index), None);
+        metrics::check(
+            "justification",
+            Justification::verify_signatures(justifications, &self.dealer, &self.aggregator.domain),
+        )?;
+        justifications
+            .iter()
+            .try_for_each(|j| self.aggregator.process_justification(j))
+    }
+
+    pub fn set_timeout(&mut self) {
+        self.aggregator.clean_verifiers();
+    }
+
+    /// set_deadline sets the time limit of the responses, see
+    /// Dealer::set_deadline.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.aggregator.deadline = Some(deadline);
+    }
+
+    /// set_justification_deadline sets the time limit of the justifications
+    /// answering the complaints, enforced by tick with end_justifications. It
+    /// should be past the deadline of the responses. The deadline isn't
+    /// serialized.
+    pub fn set_justification_deadline(&mut self, deadline: Instant) {
+        self.aggregator.justification_deadline = Some(deadline);
+    }
+
+    /// pending_justifications returns the indices of the verifiers whose
+    /// complaint the dealer hasn't justified yet.
+    pub fn pending_justifications(&self) -> Vec<u32> {
+        self.aggregator.pending_justifications()
+    }
+
+    /// end_justifications ends the justification phase: the verifiers which
+    /// haven't responded are timed out as with set_timeout, and the dealer is
+    /// marked bad if a complaint is left without a valid justification, so
+    /// that the deal is never certified. It returns true if the dealer is bad.
+    pub fn end_justifications(&mut self) -> bool {
+        self.aggregator.end_justifications()
+    }
+
+    /// set_config applies the certification options of the config, the
+    /// timeouts starting now.
+    pub fn set_config(&mut self, config: &VerifierConfig) {
+        self.set_certification(config.certification);
+        self.set_quorum(config.quorum);
+        self.set_dealer_role(config.dealer_role);
+        if let Some(timeout) = config.timeout {
+            self.set_deadline(Instant::now() + timeout);
+        }
+        if let Some(timeout) = config.justification_timeout {
+            self.set_justification_deadline(Instant::now() + timeout);
+        }
+    }
+
+    /// tick takes care of the verifiers who have not responded once now is
+    /// past the deadline, see Dealer::tick, and calls end_justifications once
+    /// now is past the justification deadline.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        self.aggregator.tick(now)
+    }
+
+    /// responses returns the responses received by this verifier, by verifier index.
+    pub fn responses(&self) -> &HashMap<u32, Response> {
+        self.aggregator.responses()
+    }
+
+    /// approvals returns the indices of the verifiers which approved the deal.
+    pub fn approvals(&self) -> Vec<u32> {
+        self.aggregator.approvals()
+    }
+
+    /// complaints returns the indices of the verifiers which complained about
+    /// the deal, or were marked as complaining by set_timeout.
+    pub fn complaints(&self) -> Vec<u32> {
+        self.aggregator.complaints()
+    }
+
+    /// complaint_reasons returns the reasons of the complaints along with
+    /// the index of their verifier, ComplaintReason::Timeout for the ones
+    /// marked by set_timeout.
+    pub fn complaint_reasons(&self) -> Vec<(u32, Option<ComplaintReason>)> {
+        self.aggregator.complaint_reasons()
+    }
+
+    /// missing_responders returns the indices of the verifiers which haven't
+    /// responded yet, i.e. the ones set_timeout would mark as complaining.
+    pub fn missing_responders(&self) -> Vec<u32> {
+        self.aggregator.missing_responders()
+    }
+
+    /// status returns a summary of the progress of the protocol.
+    pub fn status(&self) -> ProtocolStatus {
+        self.aggregator.status()
+    }
+
+    // index returns the index of the verifier in the list of participants used
+    // during this run of the protocol.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    // returns session id for this protocol run.
+    pub fn session_id(&self) -> &[u8] {
+        &self.aggregator.deal.session_id
+    }
+
+    // get_deal returns the Deal that this verifier has received. It returns
+    // error if the deal is not certified or there is not enough approvals.
+    pub fn get_deal(&self) -> Result<Deal<P>, Box<dyn Error>> {
+        if !self.aggregator.enough_approvals() || !self.aggregator.deal_certified() {
+            bail!("Not enough approvals or deal not certified");
+        }
+
+        Ok(self.aggregator.deal.clone())
+    }
+
+    // process_response analyzes the given response. If it's a valid complaint, the
+    // verifier should expect to see a Justification from the Dealer. It returns an
+    // error if it's not a valid response.
+    // Call `v.deal_certified()` to check if the whole protocol is finished.
+    pub fn process_response(&mut self, resp: &Response) -> Result<(), Box<dyn Error>> {
+        let _span = events::span("vss-verifier", &resp.session_id, Some(self.index), None);
+        let certified = self.aggregator.deal_certified();
+        self.aggregator.verify_response(resp)?;
+        self.aggregator.record_certified(certified);
+        Ok(())
+    }
+
+    /// process_responses is process_response for several responses, whose
+    /// signatures are verified at once. If a signature is invalid, no
+    /// response is recorded and the error names the verifiers of the invalid
+    /// signatures.
+    pub fn process_responses(&mut self, rs: &[Response]) -> Result<(), Box<dyn Error>> {
+        let _span = events::span("vss-verifier", self.session_id(), Some(self.index), None);
+        let certified = self.aggregator.deal_certified();
+        self.aggregator.verify_responses(rs)?;
+        for r in rs {
+            self.aggregator.accept_response(r)?;
+        }
+        self.aggregator.record_certified(certified);
+        Ok(())
+    }
+
+    /// set_certification sets the rule deciding when the deal is certified,
+    /// Certification::Justified by default. It must be the same for the dealer
+    /// and all the verifiers.
+    pub fn set_certification(&mut self, certification: Certification) {
+        self.aggregator.certification = certification;
+    }
+
+    pub fn certification(&self) -> Certification {
+        self.aggregator.certification
+    }
+
+    /// set_quorum sets the responses the certification rule waits for,
+    /// Quorum::Full by default.
+    pub fn set_quorum(&mut self, quorum: Quorum) {
+        self.aggregator.quorum = quorum;
+    }
+
+    pub fn quorum(&self) -> Quorum {
+        self.aggregator.quorum
+    }
+
+    /// set_policy installs a CertificationPolicy deciding when the deal is
+    /// certified in place of the certification rule. It isn't serialized,
+    /// and must be set again on a restored state.
+    pub fn set_policy(&mut self, policy: Arc<dyn CertificationPolicy<P>>) {
+        self.aggregator.policy = Some(policy);
+    }
+
+    /// aggregator returns a read-only view of the responses received.
+    pub fn aggregator(&self) -> AggregatorView<P> {
+        self.aggregator.view()
+    }
+
+    /// max_threshold returns the threshold of the session, or the number of
+    /// verifiers while the deal isn't processed, which bounds the commitments
+    /// of the messages of the session (see wire).
+    pub fn max_threshold(&self) -> u32 {
+        match self.aggregator.threshold {
+            0 => self.verifiers.len() as u32,
+            t => t,
+        }
+    }
+
+    /// set_dealer_index declares the dealer as the verifier at index, whose
+    /// approval of its own deal is implicit (see Dealer::set_dealer_index). It
+    /// must be called after the deal has been processed.
+    pub fn set_dealer_index(&mut self, index: u32) -> Result<(), Box<dyn Error>> {
+        self.aggregator.set_dealer_index(index)
+    }
+
+    /// set_dealer_role sets the role of the dealer, see DealerRole. The deal
+    /// of an external dealer listed among the verifiers is rejected, and the
+    /// approval of a participating dealer is counted once the deal is
+    /// processed, without calling set_dealer_index.
+    pub fn set_dealer_role(&mut self, role: DealerRole) {
+        self.aggregator.dealer_role = role;
+    }
+
+    pub fn dealer_role(&self) -> DealerRole {
+        self.aggregator.dealer_role
+    }
+
+    /// set_points sets the x-coordinates of the shares of a Dealer created with
+    /// new_with_points. It must be called before processing the deal.
+    pub fn set_points(&mut self, points: Vec<P::Scalar>) -> Result<(), Box<dyn Error>> {
+        if self.aggregator.threshold != 0 {
+            bail!("vss: deal already received");
+        }
+        check_points::<P>(&points, self.verifiers.len())?;
+        self.aggregator.points = Some(points.into());
+        Ok(())
+    }
+
+    /// set_context sets the application context of a Dealer built with
+    /// DealerBuilder::context. It must be called before processing the deal.
+    pub fn set_context(&mut self, context: &[u8]) -> Result<(), Box<dyn Error>> {
+        if self.aggregator.threshold != 0 {
+            bail!("vss: deal already received");
+        }
+        self.context = context.to_vec();
+        Ok(())
+    }
+
+    /// set_session_context is set_context for a Dealer built with
+    /// DealerBuilder::session_context.
+    pub fn set_session_context(&mut self, context: &SessionContext) -> Result<(), Box<dyn Error>> {
+        self.set_context(&context.encode())
+    }
+
+    /// set_domain sets the domain of a Dealer built with DealerBuilder::domain.
+    /// It must be called before processing the deal.
+    pub fn set_domain(&mut self, domain: &Domain) -> Result<(), Box<dyn Error>> {
+        if self.aggregator.threshold != 0 {
+            bail!("vss: deal already received");
+        }
+        self.aggregator.domain = domain.clone();
+        self.hkdf_context = dh::context_in(domain.hash_suite(), &self.dealer, &self.verifiers);
+        Ok(())
+    }
+
+    pub fn verifiers(&self) -> &[P] {
+        &self.verifiers
+    }
+
+    /// certificate returns the Certificate of the deal received, once it is
+    /// certified.
+    pub fn certificate(&self) -> Result<Certificate<P>, Box<dyn Error>> {
+        let deal = &self.aggregator.deal;
+        self.aggregator
+            .certificate(&deal.commitments, &deal.context)
+    }
+
+    /// evidence returns the Evidence of a misbehavior of the dealer, signed by
+    /// this verifier. It returns an error if the misbehavior isn't proven.
+    pub fn evidence(&self, misbehavior: Misbehavior<P>) -> Result<Evidence<P>, Box<dyn Error>> {
+        Evidence::sign(
+            &self.longterm,
+            self.index,
+            self.dealer,
+            self.verifiers.to_vec(),
+            self.aggregator.points.as_deref().map(|p| p.to_vec()),
+            &self.context,
+            misbehavior,
+        )
+    }
+}
+
+impl Response {
+    pub fn hash(session_id: &[u8], index: u32, approved: u32) -> Result<[u8; 32], Box<dyn Error>> {
+        Response::hash_in(&Domain::default(), session_id, index, approved)
+    }
+
+    /// hash_in is hash for a session of the given domain.
+    pub fn hash_in(
+        domain: &Domain,
+        session_id: &[u8],
+        index: u32,
+        approved: u32,
+    ) -> Result<[u8; 32], Box<dyn Error>> {
+        Response::hash_reason_in(domain, session_id, index, approved, None)
+    }
+
+    pub fn hash_self(&self) -> Result<[u8; 32], Box<dyn Error>> {
+        self.hash_self_in(&Domain::default())
+    }
+
+    /// hash_reason_in is hash_in for a complaint with a reason. The hash of
+    /// a response without a reason is the one of hash_in.
+    pub fn hash_reason_in(
+        domain: &Domain,
+        session_id: &[u8],
+        index: u32,
+        approved: u32,
+        reason: Option<ComplaintReason>,
+    ) -> Result<[u8; 32], Box<dyn Error>> {
+        let mut encoder = domain
+            .encoder(b"response")
+            .put_bytes(session_id)
+            .put_u32(index)
+            .put_u32(approved);
+        if let Some(reason) = reason {
+            encoder = encoder.put_u32(reason.code());
+        }
+        Ok(encoder.hash())
+    }
+
+    pub fn hash_self_in(&self, domain: &Domain) -> Result<[u8; 32], Box<dyn Error>> {
+        Response::hash_reason_in(
+            domain,
+            &self.session_id,
+            self.index,
+            self.approved as u32,
+            self.reason,
+        )
+    }
+
+    // event returns the event of the response.
+    #[cfg(feature = "std")]
+    fn event(&self) -> Event {
+        if self.approved {
+            Event::DealVerified { index: self.index }
+        } else {
+            Event::ComplaintFiled { index: self.index }
+        }
+    }
 
-        Ok(self.aggregator.deal.clone())
+    /// verify checks that the response is about the given session and is
+    /// signed by the verifier at its index.
+    pub fn verify<P: CurvePoint>(
+        &self,
+        verifiers: &[P],
+        session_id: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        self.verify_in(&Domain::default(), verifiers, session_id)
     }
 
-    // process_response analyzes the given response. If it's a valid complaint, the
-    // verifier should expect to see a Justification from the Dealer. It returns an
-    // error if it's not a valid response.
-    // Call `v.deal_certified()` to check if the whole protocol is finished.
-    pub fn process_response(&mut self, resp: &Response) -> Result<(), Box<dyn Error>> {
-        self.aggregator.verify_response(resp)
+    /// verify_in is verify for a session of the given domain.
+    pub fn verify_in<P: CurvePoint>(
+        &self,
+        domain: &Domain,
+        verifiers: &[P],
+        session_id: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        let (pub_k, hash) = self.signed_in(domain, verifiers, session_id)?;
+        P::verify_signature(
+            pub_k,
+            self.signature.as_ref(),
+            hash.as_ref(),
+            &domain.signing_context(self.index),
+        )
+        .map_err(|e| simple_error!("vss: incorrect response signature: {}", e).into())
     }
 
-    // unsafe_set_response_dkg is an UNSAFE bypass method to allow DKG to use VSS
-    // that works on basis of approval only.
-    pub(crate) fn unsafe_set_response_dkg(
-        &mut self,
-        index: u32,
-        approved: bool,
+    /// verify_batch_in is verify_in for several responses, whose signatures
+    /// are verified at once with CurvePoint::verify_signatures. The error of
+    /// invalid signatures names the indices of their verifiers.
+    pub fn verify_batch_in<P: CurvePoint>(
+        responses: &[Response],
+        domain: &Domain,
+        verifiers: &[P],
+        session_id: &[u8],
     ) -> Result<(), Box<dyn Error>> {
-        self.aggregator.unsafe_set_response_dkg(index, approved)
+        let mut publics: Vec<P> = Vec::with_capacity(responses.len());
+        let mut hashes: Vec<[u8; 32]> = Vec::with_capacity(responses.len());
+        for r in responses {
+            let (pub_k, hash) = r.signed_in(domain, verifiers, session_id)?;
+            publics.push(*pub_k);
+            hashes.push(hash);
+        }
+        let contexts: Vec<Vec<u8>> = responses
+            .iter()
+            .map(|r| domain.signing_context(r.index))
+            .collect();
+        let signatures: Vec<&[u8]> = responses.iter().map(|r| r.signature.as_ref()).collect();
+        let msgs: Vec<&[u8]> = hashes.iter().map(|h| h.as_ref()).collect();
+        let contexts: Vec<&[u8]> = contexts.iter().map(|c| c.as_ref()).collect();
+        P::verify_signatures(&publics, &signatures, &msgs, &contexts).map_err(|failed| {
+            let indices: Vec<u32> = failed.iter().map(|&i| responses[i].index).collect();
+            simple_error!(
+                "vss: incorrect response signatures of the verifiers {:?}",
+                indices
+            )
+            .into()
+        })
     }
 
-    pub fn verifiers(&self) -> &[GE] {
-        &self.verifiers
+    // signed_in checks the response but its signature, and returns the public
+    // key of its verifier and the hash it signed.
+    fn signed_in<'a, P: CurvePoint>(
+        &self,
+        domain: &Domain,
+        verifiers: &'a [P],
+        session_id: &[u8],
+    ) -> Result<(&'a P, [u8; 32]), Box<dyn Error>> {
+        if session_id.len() != 32 || !bitwise_eq(&self.session_id, session_id) {
+            bail!("vss: receiving inconsistent sessionID in response");
+        }
+        let pub_k = verifiers
+            .get(self.index as usize)
+            .ok_or_else(|| simple_error!("vss: index out of bounds in response"))?;
+        match self.reason {
+            Some(ComplaintReason::Timeout) => bail!("vss: signed timeout in response"),
+            Some(_) if self.approved => bail!("vss: complaint reason in an approval"),
+            _ => (),
+        }
+        Ok((pub_k, self.hash_self_in(domain)?))
     }
 }
 
-impl Response {
-    pub fn hash(session_id: &[u8], index: u32, approved: u32) -> Result<[u8; 32], Box<dyn Error>> {
-        let mut hasher = Sha256::new();
-        hasher.write_all(b"response".as_ref()).unwrap();
-        hasher.write_all(session_id.as_ref()).unwrap();
-        hasher.write_all(&index.to_le_bytes()).unwrap();
-        hasher.write_all(&approved.to_le_bytes()).unwrap();
-        let result = hasher.result().as_slice().try_into()?;
-        Ok(result)
+impl<P: CurvePoint> EncryptedDeal<P> {
+    /// hash returns the hash of an encrypted deal for the verifier at index,
+    /// which is signed by the dealer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn hash(
+        version: u8,
+        aead: DealAead,
+        mode: DealMode,
+        session_id: &[u8],
+        index: u32,
+        dh_key: &P,
+        nonce: &[u8],
+        cipher: &[u8],
+    ) -> [u8; 32] {
+        let enc = Encoder::new(b"encrypteddeal")
+            .put_u8(version)
+            .put_bytes(session_id)
+            .put_u32(index)
+            .put_bytes(&dh_key.pk_to_key_slice())
+            .put_bytes(nonce)
+            .put_bytes(cipher);
+        // the default schemes aren't encoded, for compatibility
+        if aead == DealAead::default() && mode == DealMode::default() {
+            return enc.hash();
+        }
+        enc.put_u8(aead as u8).put_u8(mode as u8).hash()
     }
 
-    pub fn hash_self(&self) -> Result<[u8; 32], Box<dyn Error>> {
-        Response::hash(&self.session_id, self.index, self.approved as u32)
+    /// hash_self returns the hash of this encrypted deal for the verifier at
+    /// index.
+    pub fn hash_self(&self, index: u32) -> [u8; 32] {
+        EncryptedDeal::hash(
+            self.version,
+            self.aead,
+            self.mode,
+            &self.session_id,
+            index,
+            &self.dh_key,
+            &self.nonce,
+            &self.cipher,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: CurvePoint> Complaint<P> {
+    /// judge verifies the complaint against the dealer and the verifiers of the
+    /// protocol run and returns true if the dealer is at fault, i.e. the deal
+    /// doesn't decrypt to a valid deal for the complaining verifier. It returns
+    /// false if the complaint is unfounded, and an error if the complaint is
+    /// invalid.
+    pub fn judge(&self, dealer: &P, verifiers: &[P]) -> Result<bool, Box<dyn Error>> {
+        self.judge_with(dealer, verifiers, verifiers, None, &[], &Domain::default())
+    }
+
+    /// judge_at is judge for a Dealer created with new_with_points.
+    pub fn judge_at(
+        &self,
+        dealer: &P,
+        verifiers: &[P],
+        points: &[P::Scalar],
+    ) -> Result<bool, Box<dyn Error>> {
+        check_points::<P>(points, verifiers.len())?;
+        self.judge_with(
+            dealer,
+            verifiers,
+            verifiers,
+            Some(points),
+            &[],
+            &Domain::default(),
+        )
+    }
+
+    /// judge_in_context is judge for a session bound to an application
+    /// context with DealerBuilder::context, along with the x-coordinates of
+    /// the shares if the Dealer was created with points.
+    pub fn judge_in_context(
+        &self,
+        dealer: &P,
+        verifiers: &[P],
+        points: Option<&[P::Scalar]>,
+        context: &[u8],
+    ) -> Result<bool, Box<dyn Error>> {
+        self.judge_in_domain(dealer, verifiers, points, context, &Domain::default())
+    }
+
+    /// judge_in_domain is judge_in_context for a session of the domain given
+    /// with DealerBuilder::domain.
+    pub fn judge_in_domain(
+        &self,
+        dealer: &P,
+        verifiers: &[P],
+        points: Option<&[P::Scalar]>,
+        context: &[u8],
+        domain: &Domain,
+    ) -> Result<bool, Box<dyn Error>> {
+        if let Some(points) = points {
+            check_points::<P>(points, verifiers.len())?;
+        }
+        self.judge_with(dealer, verifiers, verifiers, points, context, domain)
+    }
+
+    /// judge_identities is judge_in_domain for verifiers of distinct signing
+    /// and encryption keys, see Dealer::set_identities.
+    pub fn judge_identities(
+        &self,
+        dealer: &P,
+        identities: &[ParticipantIdentity<P>],
+        points: Option<&[P::Scalar]>,
+        context: &[u8],
+        domain: &Domain,
+    ) -> Result<bool, Box<dyn Error>> {
+        if let Some(points) = points {
+            check_points::<P>(points, identities.len())?;
+        }
+        let verifiers: Vec<P> = identities.iter().map(|id| id.signing).collect();
+        let encryption: Vec<P> = identities.iter().map(|id| id.encryption).collect();
+        self.judge_with(dealer, &verifiers, &encryption, points, context, domain)
+    }
+
+    // judge_with judges the complaint of a verifier whose deal was encrypted
+    // to its key in encryption.
+    fn judge_with(
+        &self,
+        dealer: &P,
+        verifiers: &[P],
+        encryption: &[P],
+        points: Option<&[P::Scalar]>,
+        context: &[u8],
+        domain: &Domain,
+    ) -> Result<bool, Box<dyn Error>> {
+        if self.deal.mode == DealMode::Hybrid {
+            bail!("vss: complaint about a hybrid deal");
+        }
+        let index: u32 = self.response.index;
+        let pub_k: &P = verifiers
+            .get(index as usize)
+            .ok_or_else(|| simple_error!("vss: index out of bounds in complaint"))?;
+        if self.response.approved {
+            bail!("vss: complaint holding an approval");
+        }
+        if !bitwise_eq(&self.response.session_id, &self.deal.session_id) {
+            bail!("vss: complaint about a deal of another session");
+        }
+        P::verify_signature(
+            pub_k,
+            &self.response.signature,
+            &self.response.hash_self_in(domain)?,
+            &domain.signing_context(index),
+        )
+        .map_err(|e| simple_error!("vss: incorrect response signature: {}", e))?;
+        verify_deal_signature(domain, dealer, index, &self.deal)?;
+        let recipient: &P = encryption
+            .get(index as usize)
+            .ok_or_else(|| simple_error!("vss: index out of bounds in complaint"))?;
+        self.proof
+            .verify(
+                &P::generator(),
+                &self.deal.dh_key,
+                recipient,
+                &self.dh_shared,
+            )
+            .map_err(|e| simple_error!("vss: invalid proof of the shared key: {}", e))?;
+        if let Some(c) = &self.commitments {
+            c.verify(dealer)?;
+        }
+
+        // the deal is signed by the dealer, which is to blame for its content
+        Ok(self
+            .check_deal(dealer, verifiers, recipient, points, context, domain)
+            .is_err())
+    }
+
+    fn check_deal(
+        &self,
+        dealer: &P,
+        verifiers: &[P],
+        recipient: &P,
+        points: Option<&[P::Scalar]>,
+        context: &[u8],
+        domain: &Domain,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.deal.version != ENCRYPTED_DEAL_VERSION {
+            bail!("vss: unsupported encrypted deal version");
+        }
+        let mut deal: Deal<P> = open_deal(
+            &self.dh_shared,
+            recipient,
+            None,
+            &dh::context_in(domain.hash_suite(), dealer, verifiers),
+            &self.deal,
+            verifiers.len() as u32,
+        )?;
+        if !deal.commitments_root.is_empty() {
+            // without the signed commitments, the complaint is invalid
+            match &self.commitments {
+                Some(c) => deal.expand(domain.hash_suite(), &c.commitments)?,
+                None => bail!("vss: commitments of the compact deal not received"),
+            }
+        }
+        if deal.sec_share.i != self.response.index {
+            bail!("vss: deal for another verifier");
+        }
+        let sid = session_id_at(
+            domain,
+            dealer,
+            verifiers,
+            points,
+            context,
+            &deal.commitments,
+            deal.t,
+        );
+        let x = match points {
+            Some(points) => points[deal.sec_share.i as usize],
+            None => P::Scalar::from(deal.sec_share.i as u64 + 1),
+        };
+        deal.verify_in(domain, verifiers, &sid, &x)
     }
 }
 
-impl Justification {
-    pub fn hash(session_id: &[u8], index: u32, deal: &Deal) -> Result<[u8; 32], Box<dyn Error>> {
-        let mut hasher = Sha256::new();
-        hasher.write_all(b"justification".as_ref()).unwrap();
-        hasher.write_all(session_id.as_ref()).unwrap();
-        hasher.write_all(&index.to_le_bytes()).unwrap();
-        let deal_buff: Vec<u8> = bincode::serialize(deal)?;
-        hasher.write_all(deal_buff.as_ref()).unwrap();
-        let result = hasher.result().as_slice().try_into()?;
-        Ok(result)
+impl<P: CurvePoint> Justification<P> {
+    pub fn hash(session_id: &[u8], index: u32, deal: &Deal<P>) -> Result<[u8; 32], Box<dyn Error>> {
+        Justification::hash_in(&Domain::default(), session_id, index, deal)
+    }
+
+    /// hash_in is hash for a session of the given domain.
+    pub fn hash_in(
+        domain: &Domain,
+        session_id: &[u8],
+        index: u32,
+        deal: &Deal<P>,
+    ) -> Result<[u8; 32], Box<dyn Error>> {
+        Ok(domain
+            .encoder(b"justification")
+            .put_bytes(session_id)
+            .put_u32(index)
+            .put_bytes(&deal.encode())
+            .hash())
+    }
+
+    /// verify checks that the justification is signed by the dealer and
+    /// reveals a valid deal of the session for the verifier at its index. It
+    /// doesn't need the complaint answered by the justification, so that any
+    /// participant can check a broadcasted justification.
+    pub fn verify(
+        &self,
+        dealer: &P,
+        verifiers: &[P],
+        session_id: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        self.verify_in(&Domain::default(), dealer, verifiers, session_id)
+    }
+
+    /// verify_in is verify for a session of the given domain.
+    pub fn verify_in(
+        &self,
+        domain: &Domain,
+        dealer: &P,
+        verifiers: &[P],
+        session_id: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        self.verify_header(domain, dealer, verifiers, session_id)?;
+        let x = P::Scalar::from(self.index as u64 + 1);
+        self.deal.verify_in(domain, verifiers, session_id, &x)
+    }
+
+    /// verify_in_domain is verify_in for a Dealer created with points, whose
+    /// share for the verifier at index is the evaluation at points[index], and
+    /// for a compact deal, whose commitments are the DealCommitments signed by
+    /// the dealer, as Complaint::judge_in_domain.
+    #[cfg(feature = "std")]
+    pub fn verify_in_domain(
+        &self,
+        dealer: &P,
+        verifiers: &[P],
+        session_id: &[u8],
+        points: Option<&[P::Scalar]>,
+        commitments: Option<&DealCommitments>,
+        domain: &Domain,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(points) = points {
+            check_points::<P>(points, verifiers.len())?;
+        }
+        self.verify_header(domain, dealer, verifiers, session_id)?;
+        let mut deal: Deal<P> = self.deal.clone();
+        if !deal.commitments_root.is_empty() {
+            // without the signed commitments, the justification is invalid
+            match commitments {
+                Some(c) if bitwise_eq(&c.session_id, session_id) => {
+                    c.verify(dealer)?;
+                    deal.expand(domain.hash_suite(), &c.commitments)?;
+                }
+                Some(_) => bail!("vss: commitments of another session"),
+                None => bail!("vss: commitments of the compact deal not received"),
+            }
+        }
+        let x = match points {
+            Some(points) => points[self.index as usize],
+            None => P::Scalar::from(self.index as u64 + 1),
+        };
+        deal.verify_in(domain, verifiers, session_id, &x)
+    }
+
+    // verify_header checks the session ids, the index and the signature of
+    // the justification.
+    fn verify_header(
+        &self,
+        domain: &Domain,
+        dealer: &P,
+        verifiers: &[P],
+        session_id: &[u8],
+    ) -> Result<(), Box<dyn Error>> {
+        if session_id.len() != 32
+            || !bitwise_eq(&self.session_id, session_id)
+            || !bitwise_eq(&self.deal.session_id, session_id)
+        {
+            bail!("vss: receiving inconsistent sessionID in justification");
+        }
+        if verifiers.len() <= self.index as usize {
+            bail!("vss: index out of bounds in justification")
+        }
+        if self.deal.sec_share.i != self.index {
+            bail!("vss: justification about another deal");
+        }
+        self.verify_signature(dealer, domain)
+    }
+
+    /// verify_signatures checks the signatures of the dealer on several
+    /// justifications at once, with CurvePoint::verify_signatures. The error
+    /// of invalid signatures names the indices of the justifications.
+    pub fn verify_signatures(
+        justifications: &[Justification<P>],
+        dealer: &P,
+        domain: &Domain,
+    ) -> Result<(), Box<dyn Error>> {
+        let hashes: Vec<[u8; 32]> = justifications
+            .iter()
+            .map(|j| Justification::hash_in(domain, &j.session_id, j.index, &j.deal))
+            .collect::<Result<_, _>>()?;
+        let contexts: Vec<Vec<u8>> = justifications
+            .iter()
+            .map(|j| domain.signing_context(j.index))
+            .collect();
+        let publics: Vec<P> = vec![*dealer; justifications.len()];
+        let signatures: Vec<&[u8]> = justifications
+            .iter()
+            .map(|j| j.signature.as_ref())
+            .collect();
+        let msgs: Vec<&[u8]> = hashes.iter().map(|h| h.as_ref()).collect();
+        let contexts: Vec<&[u8]> = contexts.iter().map(|c| c.as_ref()).collect();
+        P::verify_signatures(&publics, &signatures, &msgs, &contexts).map_err(|failed| {
+            let indices: Vec<u32> = failed.iter().map(|&i| justifications[i].index).collect();
+            simple_error!(
+                "vss: incorrect justification signatures of the indices {:?}",
+                indices
+            )
+            .into()
+        })
+    }
+
+    // verify_signature checks the signature of the dealer on the justification.
+    fn verify_signature(&self, dealer: &P, domain: &Domain) -> Result<(), Box<dyn Error>> {
+        let hash = Justification::hash_in(domain, &self.session_id, self.index, &self.deal)?;
+        P::verify_signature(
+            dealer,
+            self.signature.as_ref(),
+            hash.as_ref(),
+            &domain.signing_context(self.index),
+        )
+        .map_err(|e| simple_error!("vss: incorrect justification signature: {}", e).into())
     }
 }
 
-impl Deal {
+impl<P: CurvePoint> Deal<P> {
+    /// encode returns the canonical encoding of the deal, used in the hashes
+    /// which embed a deal.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = Encoder::new(b"deal")
+            .put_bytes(&self.session_id)
+            .put_u32(self.sec_share.i)
+            .put_bytes(&self.sec_share.v.to_bytes())
+            .put_u32(self.rnd_share.i)
+            .put_bytes(&self.rnd_share.v.to_bytes())
+            .put_u32(self.t)
+            .put_list(&self.commitments);
+        // no context and no root aren't encoded, for compatibility
+        if !self.context.is_empty() {
+            enc = enc.put_bytes(&self.context);
+        }
+        if !self.commitments_root.is_empty() {
+            enc = enc.put_bytes(b"root").put_bytes(&self.commitments_root);
+        }
+        enc.into_bytes()
+    }
+
+    // compact returns the deal carrying the Merkle root of its commitments in
+    // place of the commitments.
+    #[cfg(feature = "std")]
+    fn compact(&self, suite: HashSuite) -> Deal<P> {
+        Deal {
+            commitments: Vec::new(),
+            commitments_root: commitments_root_in(suite, &self.commitments).to_vec(),
+            ..self.clone()
+        }
+    }
+
+    // expand sets the commitments of a compact deal, which must match its
+    // Merkle root.
+    #[cfg(feature = "std")]
+    fn expand(&mut self, suite: HashSuite, commitments: &[Vec<u8>]) -> Result<(), Box<dyn Error>> {
+        if !self.commitments.is_empty()
+            || commitments_root_in(suite, commitments)[..] != self.commitments_root[..]
+        {
+            bail!("vss: commitments don't match the root of the compact deal");
+        }
+        self.commitments = commitments.to_vec();
+        self.commitments_root = Vec::new();
+        Ok(())
+    }
+
     /// analyzes the deal and returns an error if it's incorrect. If
     /// inclusion is true, it also returns an error if it the second time this struct
     /// analyzes a Deal.
-    pub fn verify(&self, verifiers: &[GE], sid: &[u8]) -> Result<(), Box<dyn Error>> {
+    pub fn verify(&self, verifiers: &[P], sid: &[u8]) -> Result<(), Box<dyn Error>> {
+        let x = P::Scalar::from(self.sec_share.i as u64 + 1);
+        self.verify_at(verifiers, sid, &x)
+    }
+
+    /// verify_at is verify for a deal of a Dealer created with new_with_points,
+    /// whose share is the evaluation at x.
+    pub fn verify_at(
+        &self,
+        verifiers: &[P],
+        sid: &[u8],
+        x: &P::Scalar,
+    ) -> Result<(), Box<dyn Error>> {
+        self.verify_in(&Domain::default(), verifiers, sid, x)
+    }
+
+    /// verify_in is verify_at for a session of the given domain, whose
+    /// HDerivation gives the second generator of the commitments.
+    pub fn verify_in(
+        &self,
+        domain: &Domain,
+        verifiers: &[P],
+        sid: &[u8],
+        x: &P::Scalar,
+    ) -> Result<(), Box<dyn Error>> {
         if !valid_t(self.t, verifiers) {
             bail!("vss: invalid t received in Deal")
         }
@@ -786,8 +4020,8 @@ impl Deal {
             bail!("vss: find different sessionIDs from Deal")
         }
 
-        let fi: PriShare<FE> = self.sec_share.clone();
-        let gi: PriShare<FE> = self.rnd_share.clone();
+        let fi: PriShare<P::Scalar> = self.sec_share.clone();
+        let gi: PriShare<P::Scalar> = self.rnd_share.clone();
         if fi.i != gi.i {
             bail!("vss: not the same index for f and g share in Deal")
         }
@@ -795,84 +4029,540 @@ impl Deal {
             bail!("vss: index out of bounds in Deal")
         }
         // compute fi * G + gi * H
-        let generator = GE::generator();
-        let fig: GE = generator.scalar_mul(&fi.v.get_element());
-        let h: GE = derive_h(&verifiers)?;
-        let gih: GE = h.scalar_mul(&gi.v.get_element());
-        let ci: GE = fig.add_point(&gih.get_element());
+        let generator = P::generator();
+        let fig: P = P::base_mul(&fi.v);
+        let h: P = derive_h_in(domain.h_derivation(), verifiers)?;
+        let gih: P = h.scalar_mul(&gi.v.get_element());
+        let ci: P = fig.add_point(&gih.get_element());
 
-        let mut commitments: Vec<GE> = Vec::new();
+        let mut commitments: Vec<P> = Vec::new();
         for comm in self.commitments.iter() {
-            let point = GE::from_bytes(comm.as_ref())
+            let point = P::from_bytes(comm.as_ref())
                 .map_err(|_| simple_error!("vss: error while construct point from bytes"))?;
             commitments.push(point);
         }
-        let commit_poly: PubPoly = poly::PubPoly::new(generator, commitments);
-
-        let pub_share: PubShare<GE> = commit_poly.eval(fi.i);
-        if ci != pub_share.v {
-            bail!("vss: share does not verify against commitments in Deal")
+        let commit_poly: PubPoly<P> = poly::PubPoly::new(generator, commitments);
+
+        if ci != commit_poly.eval_at(x) {
+            bail!("vss: share does not verify against commitments in Deal")
+        }
+
+        Ok(())
+    }
+
+    /// complaint_reason returns the reason to complain about the deal, or
+    /// None if verify_in accepts it.
+    pub fn complaint_reason(
+        &self,
+        domain: &Domain,
+        verifiers: &[P],
+        sid: &[u8],
+        x: &P::Scalar,
+    ) -> Option<ComplaintReason> {
+        self.verify_in(domain, verifiers, sid, x).err()?;
+        if sid != self.session_id.as_slice() {
+            return Some(ComplaintReason::BadSessionId);
+        }
+        let malformed = !valid_t(self.t, verifiers)
+            || self.sec_share.i != self.rnd_share.i
+            || self.sec_share.i >= verifiers.len() as u32
+            || self
+                .commitments
+                .iter()
+                .any(|c| P::from_bytes(c.as_ref()).is_err());
+        if malformed {
+            Some(ComplaintReason::InvalidDeal)
+        } else {
+            Some(ComplaintReason::ShareMismatch)
+        }
+    }
+}
+
+// verify_deal_signature checks the signature of the dealer on the encrypted
+// deal for the verifier at index.
+#[cfg(feature = "std")]
+pub(crate) fn verify_deal_signature<P: CurvePoint>(
+    domain: &Domain,
+    dealer: &P,
+    index: u32,
+    encr_d: &EncryptedDeal<P>,
+) -> Result<(), Box<dyn Error>> {
+    let e_hash = encr_d.hash_self(index);
+    P::verify_signature(
+        dealer,
+        encr_d.signature.as_ref(),
+        e_hash.as_ref(),
+        &domain.signing_context(index),
+    )
+    .map_err(|e| simple_error!("vss: signature verification failed: {}", e))?;
+    Ok(())
+}
+
+// open_deal decrypts an encrypted deal with the Diffie Hellman key shared by the
+// dealer and its verifier, whose public key is recipient, along with its KEM
+// secret key in hybrid mode. The deal holds at most t commitments.
+#[cfg(feature = "std")]
+fn open_deal<P: CurvePoint>(
+    pre: &P,
+    recipient: &P,
+    kem_secret: Option<&[u8]>,
+    hkdf_context: &[u8],
+    encr_d: &EncryptedDeal<P>,
+    t: u32,
+) -> Result<Deal<P>, Box<dyn Error>> {
+    let decrypted_vec = open_cipher(pre, recipient, kem_secret, hkdf_context, encr_d)?;
+    let decoded: Deal<P> = wire::decode(&decrypted_vec[..])?;
+    wire::check_deal(&decoded, t)?;
+
+    if !bitwise_eq(&decoded.session_id, &encr_d.session_id) {
+        bail!("vss: session id of encrypted deal doesn't match the deal");
+    }
+
+    Ok(decoded)
+}
+
+// open_cipher returns the plaintext of an encrypted deal, see open_deal.
+#[cfg(feature = "std")]
+pub(crate) fn open_cipher<P: CurvePoint>(
+    pre: &P,
+    recipient: &P,
+    kem_secret: Option<&[u8]>,
+    hkdf_context: &[u8],
+    encr_d: &EncryptedDeal<P>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (kem_shared, cipher) = decapsulate(kem_secret, encr_d)?;
+    let (key, nonce) = encr_d.mode.key(
+        encr_d.aead,
+        pre,
+        &kem_shared,
+        &encr_d.dh_key,
+        recipient,
+        hkdf_context,
+        &encr_d.nonce,
+    )?;
+    encr_d.aead.open(&key, &nonce, hkdf_context, cipher)
+}
+
+// decapsulate returns the KEM shared secret leading the cipher of a deal in
+// hybrid mode and the rest of the cipher, or an empty secret and the whole
+// cipher otherwise.
+#[cfg(feature = "pq-kem")]
+fn decapsulate<'a, P: CurvePoint>(
+    kem_secret: Option<&[u8]>,
+    encr_d: &'a EncryptedDeal<P>,
+) -> Result<(Vec<u8>, &'a [u8]), Box<dyn Error>> {
+    if encr_d.mode != DealMode::Hybrid {
+        return Ok((Vec::new(), &encr_d.cipher));
+    }
+    let secret = kem_secret.ok_or_else(|| simple_error!("vss: no KEM key for a hybrid deal"))?;
+    if encr_d.cipher.len() < pq::CIPHERTEXT_SIZE {
+        bail!("vss: hybrid deal without KEM ciphertext");
+    }
+    let (kem_cipher, cipher) = encr_d.cipher.split_at(pq::CIPHERTEXT_SIZE);
+    Ok((pq::decapsulate(kem_cipher, secret)?, cipher))
+}
+
+#[cfg(all(feature = "std", not(feature = "pq-kem")))]
+fn decapsulate<'a, P: CurvePoint>(
+    _kem_secret: Option<&[u8]>,
+    encr_d: &'a EncryptedDeal<P>,
+) -> Result<(Vec<u8>, &'a [u8]), Box<dyn Error>> {
+    Ok((Vec::new(), &encr_d.cipher))
+}
+
+#[cfg(feature = "std")]
+impl DealCommitments {
+    pub fn hash(session_id: &[u8], commitments: &[Vec<u8>]) -> [u8; 32] {
+        Encoder::new(b"dealcommitments")
+            .put_bytes(session_id)
+            .put_list(commitments)
+            .hash()
+    }
+
+    /// verify checks the signature of the dealer on the commitments.
+    pub fn verify<P: CurvePoint>(&self, dealer: &P) -> Result<(), Box<dyn Error>> {
+        let hash = DealCommitments::hash(&self.session_id, &self.commitments);
+        P::verify_signature(dealer, &self.signature, &hash, &[])
+            .map_err(|e| simple_error!("vss: incorrect commitments signature: {}", e).into())
+    }
+}
+
+/// commitments_root returns the Merkle root of the commitments of a deal.
+/// Leaves and nodes are hashed with distinct tags, and the last node of an odd
+/// level is carried up.
+pub fn commitments_root(commitments: &[Vec<u8>]) -> [u8; 32] {
+    commitments_root_in(HashSuite::default(), commitments)
+}
+
+/// commitments_root_in is commitments_root with the commitment hash of the
+/// suite.
+pub fn commitments_root_in(suite: HashSuite, commitments: &[Vec<u8>]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = commitments
+        .iter()
+        .map(|c| suite.commitment_hash(&Encoder::new(b"merkleleaf").put_bytes(c).into_bytes()))
+        .collect();
+    if level.is_empty() {
+        return suite.commitment_hash(&Encoder::new(b"merkleleaf").into_bytes());
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => suite.commitment_hash(
+                    &Encoder::new(b"merklenode")
+                        .put_bytes(left)
+                        .put_bytes(right)
+                        .into_bytes(),
+                ),
+                _ => pair[0],
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Hash dealer and verifiers pub keys, committments to get a unique session id
+pub fn session_id<P: CurvePoint>(
+    dealer: &P,
+    verifiers: &[P],
+    commitments: &[Vec<u8>],
+    t: u32,
+) -> [u8; 32] {
+    session_id_at(
+        &Domain::default(),
+        dealer,
+        verifiers,
+        None,
+        &[],
+        commitments,
+        t,
+    )
+}
+
+// session_id_at is session_id in the domain, bound to the x-coordinates of the
+// shares if they are not the default ones, and to the application context if
+// any.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn session_id_at<P: CurvePoint>(
+    domain: &Domain,
+    dealer: &P,
+    verifiers: &[P],
+    points: Option<&[P::Scalar]>,
+    context: &[u8],
+    commitments: &[Vec<u8>],
+    t: u32,
+) -> [u8; 32] {
+    let verifiers: Vec<Vec<u8>> = verifiers.iter().map(|v| v.pk_to_key_slice()).collect();
+    session_id_of_keys::<P>(
+        domain,
+        &dealer.pk_to_key_slice(),
+        &verifiers,
+        points,
+        context,
+        commitments,
+        t,
+    )
+}
+
+// session_id_of_keys is session_id_at for the encoded keys of the dealer and
+// of the verifiers, to encode them once for many sessions.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn session_id_of_keys<P: CurvePoint>(
+    domain: &Domain,
+    dealer: &[u8],
+    verifiers: &[Vec<u8>],
+    points: Option<&[P::Scalar]>,
+    context: &[u8],
+    commitments: &[Vec<u8>],
+    t: u32,
+) -> [u8; 32] {
+    let mut enc = domain
+        .encoder(b"sessionid")
+        .put_bytes(dealer)
+        .put_list(verifiers)
+        .put_list(commitments)
+        .put_u32(t);
+    if let Some(points) = points {
+        let points: Vec<Vec<u8>> = points.iter().map(|x| x.to_bytes()).collect();
+        enc = enc.put_list(&points);
+    }
+    if !context.is_empty() {
+        enc = enc.put_bytes(b"context").put_bytes(context);
+    }
+    let suite = domain.hash_suite();
+    if suite != HashSuite::default() {
+        enc = enc.put_bytes(b"hashsuite").put_bytes(suite.id().as_bytes());
+    }
+    let h_derivation = domain.h_derivation();
+    if h_derivation != HDerivation::Legacy {
+        enc = enc
+            .put_bytes(b"hderivation")
+            .put_bytes(h_derivation.id().as_bytes());
+    }
+    suite.session_hash(&enc.into_bytes())
+}
+
+/// points_from_keys derives the x-coordinate of the share of every verifier
+/// from its public key, for Dealer::new_with_points.
+pub fn points_from_keys<P: CurvePoint>(verifiers: &[P]) -> Vec<P::Scalar> {
+    verifiers
+        .iter()
+        .map(|v| {
+            P::hash_to_scalar(
+                &Encoder::new(b"evalpoint")
+                    .put_bytes(&v.pk_to_key_slice())
+                    .into_bytes(),
+            )
+        })
+        .collect()
+}
+
+// check_points returns an error unless there are n distinct and non-zero
+// x-coordinates.
+#[cfg(feature = "std")]
+pub(crate) fn check_points<P: CurvePoint>(
+    points: &[P::Scalar],
+    n: usize,
+) -> Result<(), Box<dyn Error>> {
+    if points.len() != n {
+        bail!(
+            "vss: {} evaluation points for {} verifiers",
+            points.len(),
+            n
+        );
+    }
+    let zero: P::Scalar = P::Scalar::zero();
+    let mut seen: HashSet<Vec<u8>> = HashSet::new();
+    for x in points.iter() {
+        if *x == zero || !seen.insert(x.to_bytes()) {
+            bail!("vss: evaluation points must be distinct and non-zero");
+        }
+    }
+    Ok(())
+}
+
+/// DERIVE_H_DST is the domain separation tag of derive_h, followed by the
+/// CurvePoint::HASH_TO_GROUP_SUITE of the curve.
+pub const DERIVE_H_DST: &[u8] = b"THRESHOLD-DKG-V01-DERIVE-H-";
+
+/// derive_h hashes the public keys of the verifiers to the second generator
+/// H of the commitments with the default HDerivation.
+pub fn derive_h<P: CurvePoint>(verifiers: &[P]) -> Result<P, Box<dyn Error>> {
+    derive_h_in(HDerivation::default(), verifiers)
+}
+
+/// derive_h_in is derive_h with the given derivation: the RFC 9380
+/// hash_to_group of the curve for HashToGroup, derive_h_legacy for Legacy.
+pub fn derive_h_in<P: CurvePoint>(
+    derivation: HDerivation,
+    verifiers: &[P],
+) -> Result<P, Box<dyn Error>> {
+    match derivation {
+        HDerivation::HashToGroup => {
+            let dst: Vec<u8> = [DERIVE_H_DST, P::HASH_TO_GROUP_SUITE.as_bytes()].concat();
+            P::hash_to_group(&verifier_keys(verifiers), &dst)
+        }
+        HDerivation::Legacy => derive_h_legacy(verifiers),
+    }
+}
+
+/// derive_h_legacy is the derivation of H of kyber and of the previous
+/// versions: the rejection sampling of the blake2xb hash of the public keys
+/// of the verifiers with hash_to_point.
+pub fn derive_h_legacy<P: CurvePoint>(verifiers: &[P]) -> Result<P, Box<dyn Error>> {
+    P::hash_to_point(&verifier_keys(verifiers))
+}
+
+// verifier_keys returns the concatenation of the public keys of the
+// verifiers.
+fn verifier_keys<P: CurvePoint>(verifiers: &[P]) -> Vec<u8> {
+    let points_bytes: Vec<Vec<u8>> = verifiers.iter().map(|x| x.pk_to_key_slice()).collect();
+    points_bytes.concat()
+}
+
+/// recover_secret recovers the secret shared by a Dealer by gathering at least t
+/// Deals from the verifiers. It returns an error if there is not enough Deals or
+/// if all Deals don't have the same SessionID.
+pub fn recover_secret<P: CurvePoint>(
+    deals: &[Deal<P>],
+    t: u32,
+) -> Result<P::Scalar, Box<dyn Error>> {
+    let mut shares: Vec<PriShare<P::Scalar>> = Vec::new();
+    let sess_id: Vec<u8> = match deals.first() {
+        Some(deal) => deal.session_id.clone(),
+        None => bail!("vss: no deal to recover the secret"),
+    };
+    for deal in deals.iter() {
+        if bitwise_eq(&sess_id[..], &deal.session_id[..]) {
+            shares.push(deal.sec_share.clone());
+        } else {
+            bail!("vss: all deals need to have same session id")
+        }
+    }
+    let secret: P::Scalar = poly::recover_secret(shares.as_mut_slice(), t)?;
+    Ok(secret)
+}
+
+/// recover_secret_at is recover_secret for the Deals of a Dealer created with
+/// new_with_points. If the points are the first points of an
+/// fft::EvaluationDomain, the secret is recovered with an inverse FFT, which
+/// returns an error if the deals of the whole domain are not consistent.
+pub fn recover_secret_at<P: CurvePoint>(
+    deals: &[Deal<P>],
+    points: &[P::Scalar],
+    t: u32,
+) -> Result<P::Scalar, Box<dyn Error>> {
+    let mut shares: Vec<PriShare<P::Scalar>> = Vec::new();
+    let sess_id: Vec<u8> = match deals.first() {
+        Some(deal) => deal.session_id.clone(),
+        None => bail!("vss: no deal to recover the secret"),
+    };
+    for deal in deals.iter() {
+        if !bitwise_eq(&sess_id[..], &deal.session_id[..]) {
+            bail!("vss: all deals need to have same session id")
+        }
+        shares.push(deal.sec_share.clone());
+    }
+    if let Some(domain) = fft::domain_of::<P>(points) {
+        return domain.recover_secret(&shares, t);
+    }
+    poly::recover_secret_at(&shares, points, t)
+}
+
+/// robust_recover_secret recovers the secret shared by a Dealer out of Deals
+/// that may have been tampered with by their holders. Every Deal is checked
+/// against the session: its commitments must hash, along with the dealer and
+/// verifiers public keys, to `session_id` and its share must verify against
+/// them. The secret is recovered from the valid Deals, and the indices of the
+/// invalid ones are returned with it. It returns an error, listing the invalid
+/// indices, if there are less than t valid Deals.
+pub fn robust_recover_secret<P: CurvePoint>(
+    dealer: &P,
+    verifiers: &[P],
+    session_id: &[u8],
+    deals: &[Deal<P>],
+    t: u32,
+) -> Result<(P::Scalar, Vec<u32>), Box<dyn Error>> {
+    let mut shares: Vec<PriShare<P::Scalar>> = Vec::new();
+    let mut invalid: Vec<u32> = Vec::new();
+    for deal in deals.iter() {
+        let index: u32 = deal.sec_share.i;
+        let expected = session_id_at(
+            &Domain::default(),
+            dealer,
+            verifiers,
+            None,
+            &deal.context,
+            &deal.commitments,
+            deal.t,
+        );
+        if !bitwise_eq(&expected, session_id) || deal.verify(verifiers, session_id).is_err() {
+            invalid.push(index);
+        } else if shares.iter().all(|s| s.i != index) {
+            shares.push(deal.sec_share.clone());
+        }
+    }
+
+    if (shares.len() as u32) < t {
+        bail!(
+            "vss: not enough valid deals to recover secret, invalid deals: {:?}",
+            invalid
+        );
+    }
+    let secret: P::Scalar = poly::recover_secret(&shares, t)?;
+    Ok((secret, invalid))
+}
+
+/// SecretRecoverer recovers the secret shared by a Dealer out of Deals pushed
+/// as they arrive, instead of a slice of all the Deals as recover_secret. A
+/// Deal of another session than the first one, or of an index already pushed,
+/// is rejected on push, and the secret can be recovered as soon as t Deals
+/// were accepted.
+#[derive(Clone, Debug)]
+pub struct SecretRecoverer<P: CurvePoint = GE> {
+    t: u32,
+    // session of the deals, the one of the first deal if not given
+    session_id: Option<Vec<u8>>,
+    // public keys of the verifiers, to verify the deals against their
+    // commitments
+    verifiers: Option<Vec<P>>,
+    shares: Vec<PriShare<P::Scalar>>,
+}
+
+impl<P: CurvePoint> SecretRecoverer<P> {
+    /// new returns a SecretRecoverer of threshold t, accepting the Deals of
+    /// the session of the first Deal pushed.
+    pub fn new(t: u32) -> SecretRecoverer<P> {
+        SecretRecoverer {
+            t,
+            session_id: None,
+            verifiers: None,
+            shares: Vec::new(),
+        }
+    }
+
+    /// for_session returns a SecretRecoverer of threshold t accepting only the
+    /// Deals of the session which verify against their commitments, as
+    /// robust_recover_secret.
+    pub fn for_session(verifiers: Vec<P>, session_id: &[u8], t: u32) -> SecretRecoverer<P> {
+        SecretRecoverer {
+            t,
+            session_id: Some(session_id.to_vec()),
+            verifiers: Some(verifiers),
+            shares: Vec::new(),
+        }
+    }
+
+    /// push adds the share of the deal. It returns an error, leaving the
+    /// SecretRecoverer unchanged, if the deal is of another session, of an
+    /// index already pushed or, for a SecretRecoverer of for_session, invalid.
+    pub fn push(&mut self, deal: &Deal<P>) -> Result<(), Box<dyn Error>> {
+        if let Some(sid) = &self.session_id {
+            if !bitwise_eq(sid, &deal.session_id) {
+                bail!("vss: all deals need to have same session id");
+            }
+        }
+        let index = deal.sec_share.i;
+        if self.shares.iter().any(|s| s.i == index) {
+            bail!("vss: duplicate deal of index {}", index);
+        }
+        if let Some(verifiers) = &self.verifiers {
+            deal.verify(verifiers, &deal.session_id)?;
+        }
+        if self.session_id.is_none() {
+            self.session_id = Some(deal.session_id.clone());
         }
-
+        self.shares.push(deal.sec_share.clone());
         Ok(())
     }
-}
 
-/// Hash dealer and verifiers pub keys, committments to get a unique session id
-pub fn session_id(dealer: &GE, verifiers: &[GE], commitments: &[Vec<u8>], t: u32) -> [u8; 32] {
-    let mut hasher = Sha256::new();
-    hasher.input(dealer.get_element().to_bytes());
-
-    for ver in verifiers {
-        hasher.input(ver.get_element().to_bytes());
+    /// len returns the number of shares pushed.
+    pub fn len(&self) -> usize {
+        self.shares.len()
     }
 
-    for comm in commitments {
-        hasher.input(comm);
+    /// is_empty returns true if no share was pushed.
+    pub fn is_empty(&self) -> bool {
+        self.shares.is_empty()
     }
 
-    hasher.write_all(&t.to_le_bytes()).unwrap();
-
-    hasher
-        .result()
-        .as_slice()
-        .try_into()
-        .expect("Slice with incorrect length")
-}
-
-/// Hash verifiers pub keys as bytes and return the hash as Point
-pub fn derive_h(verifiers: &[GE]) -> Result<GE, Box<dyn Error>> {
-    let points_bytes: Vec<[u8; 32]> = verifiers
-        .iter()
-        .map(|x| x.get_element().to_bytes())
-        .collect();
-    let buffer: Vec<u8> = points_bytes.concat();
-    let hash = blake::new_blake2xb(buffer);
-    for value in hash {
-        for chunk in value.as_ref().chunks(32) {
-            if let Ok(point) = ECPoint::from_bytes(chunk) {
-                return Ok(point);
-            }
-        }
+    /// can_recover returns true once t shares were pushed.
+    pub fn can_recover(&self) -> bool {
+        self.shares.len() >= self.t as usize
     }
-    bail!("Error hash")
-}
 
-/// recover_secret recovers the secret shared by a Dealer by gathering at least t
-/// Deals from the verifiers. It returns an error if there is not enough Deals or
-/// if all Deals don't have the same SessionID.
-pub fn recover_secret(deals: &[Deal], t: u32) -> Result<FE, Box<dyn Error>> {
-    let mut shares: Vec<PriShare<FE>> = Vec::new();
-    let sess_id: Vec<u8> = deals[0].session_id.clone();
-    for deal in deals.iter() {
-        if bitwise_eq(&sess_id[..], &deal.session_id[..]) {
-            shares.push(deal.sec_share.clone());
-        } else {
-            bail!("vss: all deals need to have same session id")
+    /// try_recover returns the secret, or an error if less than t shares were
+    /// pushed.
+    pub fn try_recover(&self) -> Result<P::Scalar, Box<dyn Error>> {
+        if !self.can_recover() {
+            bail!(
+                "vss: {} of {} deals to recover the secret",
+                self.shares.len(),
+                self.t
+            );
         }
+        poly::recover_secret(&self.shares, self.t)
     }
-    let secret: FE = poly::recover_secret(shares.as_mut_slice(), t)?;
-    Ok(secret)
 }
 
 #[cfg(test)]
@@ -880,13 +4570,17 @@ mod tests {
     use super::*;
     use crate::curve_traits;
     use crate::ristretto_curve;
+    use crate::sign;
     use dh::*;
     use utils::bitwise_eq;
 
     use curve_traits::{ECPoint, ECScalar};
+    use rand_chacha::rand_core::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
     use ristretto_curve::{FE, GE};
     use schnorrkel::context::signing_context;
     use schnorrkel::{Keypair, Signature};
+    use std::time::Duration;
 
     struct InitData {
         nb_verifiers: u32,
@@ -1005,6 +4699,361 @@ mod tests {
         assert_eq!(sec, dealer.secret);
     }
 
+    #[test]
+    fn test_vss_points() {
+        let init_data: InitData = setup(7);
+        let points: Vec<FE> = points_from_keys(&init_data.verifiers_pub);
+        let mut dealer: Dealer = Dealer::new_with_points(
+            init_data.dealer_sec,
+            init_data.secret,
+            init_data.verifiers_pub.clone(),
+            points.clone(),
+            init_data.vss_threshold,
+        )
+        .unwrap();
+        let (_, mut verifiers) = gen_all(&init_data);
+
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        // the session of a verifier without the points is another one
+        let mut v: Verifier = verifiers[0].clone();
+        assert!(v.process_encrypted_deal(&enc_deals[0]).is_err());
+
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(deal, v)| {
+                v.set_points(points.clone()).unwrap();
+                v.process_encrypted_deal(deal).unwrap()
+            })
+            .collect();
+        for resp in resps.iter() {
+            assert!(resp.approved);
+            for v in verifiers.iter_mut().filter(|v| v.index() != resp.index) {
+                v.process_response(resp).unwrap();
+            }
+            dealer.process_response(resp).unwrap();
+        }
+        assert!(verifiers[0].set_points(points.clone()).is_err());
+
+        let deals: Vec<Deal> = verifiers.iter().map(|v| v.get_deal().unwrap()).collect();
+        let sec = recover_secret_at(&deals, &points, init_data.vss_threshold).unwrap();
+        assert_eq!(init_data.secret, sec);
+        // the default evaluation points don't interpolate these shares
+        assert_ne!(
+            init_data.secret,
+            recover_secret(&deals, init_data.vss_threshold).unwrap()
+        );
+
+        let mut dup: Vec<FE> = points.clone();
+        dup[1] = dup[0];
+        assert!(Dealer::<GE>::new_with_points(
+            init_data.dealer_sec,
+            init_data.secret,
+            init_data.verifiers_pub.clone(),
+            dup,
+            init_data.vss_threshold,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_vss_justification_points() {
+        let init_data: InitData = setup(7);
+        let points: Vec<FE> = points_from_keys(&init_data.verifiers_pub);
+        let mut dealer: Dealer = Dealer::new_with_points(
+            init_data.dealer_sec,
+            init_data.secret,
+            init_data.verifiers_pub.clone(),
+            points.clone(),
+            init_data.vss_threshold,
+        )
+        .unwrap();
+        let (_, verifiers) = gen_all(&init_data);
+        let good_deal: Deal = dealer.deals[0].clone();
+        dealer.deals[0].sec_share.v = FE::new_random();
+        let enc_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        dealer.deals[0] = good_deal;
+        let mut v: Verifier = verifiers[0].clone();
+        v.set_points(points.clone()).unwrap();
+        let resp: Response = v.process_encrypted_deal(&enc_deal).unwrap();
+        assert!(!resp.approved);
+        let j: Justification = dealer.process_response(&resp).unwrap().unwrap();
+
+        // the share is the evaluation at the point of the verifier
+        let sid = dealer.get_session_id().to_vec();
+        let pubs = &init_data.verifiers_pub;
+        let domain = Domain::default();
+        j.verify_in_domain(
+            &init_data.dealer_pub,
+            pubs,
+            &sid,
+            Some(&points),
+            None,
+            &domain,
+        )
+        .unwrap();
+        j.verify(&init_data.dealer_pub, pubs, &sid)
+            .expect_err("Must fail, not at the default points");
+        let mut swapped: Vec<FE> = points.clone();
+        swapped.swap(0, 1);
+        j.verify_in_domain(
+            &init_data.dealer_pub,
+            pubs,
+            &sid,
+            Some(&swapped),
+            None,
+            &domain,
+        )
+        .expect_err("Must fail, other points");
+    }
+
+    #[test]
+    fn test_vss_deadline() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let now = Instant::now();
+        dealer.set_deadline(now + Duration::from_secs(10));
+
+        // the first verifier stays silent
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        for (deal, v) in enc_deals.iter().zip(verifiers.iter_mut()).skip(1) {
+            let resp = v.process_encrypted_deal(deal).unwrap();
+            dealer.process_response(&resp).unwrap();
+        }
+        assert!(!dealer.tick(now));
+        assert!(!dealer.deal_certified());
+        assert_eq!(vec![0], dealer.missing_responders());
+
+        assert!(dealer.tick(now + Duration::from_secs(10)));
+        assert!(dealer.missing_responders().is_empty());
+        assert_eq!(vec![0], dealer.complaints());
+        assert!(dealer.deal_certified());
+        // the deadline is enforced once
+        assert!(!dealer.tick(now + Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_vss_justification_deadline() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let good_d: Deal = dealer.deals[0].clone();
+        dealer.deals[0].sec_share.v = FE::new_random();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        dealer.deals[0] = good_d;
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(deal, v)| v.process_encrypted_deal(deal).unwrap())
+            .collect();
+        assert!(!resps[0].approved);
+        let now = Instant::now();
+        for v in verifiers.iter_mut() {
+            v.set_justification_deadline(now + Duration::from_secs(10));
+            for resp in resps.iter().filter(|r| r.index != v.index()) {
+                v.process_response(resp).unwrap();
+            }
+        }
+        // the unanswered complaint doesn't prevent the t approvals
+        assert_eq!(vec![0], verifiers[1].pending_justifications());
+        assert!(verifiers[1].deal_certified());
+
+        let j: Justification = dealer.process_response(&resps[0]).unwrap().unwrap();
+        verifiers[2].process_justification(&j).unwrap();
+        assert!(verifiers[2].pending_justifications().is_empty());
+
+        assert!(!verifiers[1].tick(now));
+        assert!(verifiers[1].tick(now + Duration::from_secs(10)));
+        assert!(verifiers[1].aggregator().bad_dealer());
+        assert!(!verifiers[1].deal_certified());
+        assert!(verifiers[1].end_justifications());
+        assert!(!verifiers[2].end_justifications());
+        assert!(verifiers[2].deal_certified());
+    }
+
+    #[test]
+    fn test_vss_batch_verification() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let good: Vec<Deal> = dealer.deals[..2].to_vec();
+        dealer.deals[0].sec_share.v = FE::new_random();
+        dealer.deals[1].sec_share.v = FE::new_random();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        dealer.deals[..2].clone_from_slice(&good);
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(deal, v)| v.process_encrypted_deal(deal).unwrap())
+            .collect();
+
+        // an invalid signature rejects the whole batch, naming its verifier
+        let mut forged: Vec<Response> = resps.clone();
+        forged[3].signature = resps[4].signature.clone();
+        let err = dealer.process_responses(&forged).unwrap_err();
+        assert!(err.to_string().contains("verifiers [3]"));
+        assert!(dealer.aggregator.responses.is_empty());
+
+        let justifications: Vec<Justification> = dealer.process_responses(&resps).unwrap();
+        assert_eq!(
+            vec![0, 1],
+            justifications.iter().map(|j| j.index).collect::<Vec<u32>>()
+        );
+        verifiers[2].process_responses(&resps).unwrap();
+        assert_eq!(vec![0, 1], verifiers[2].pending_justifications());
+
+        let mut forged: Vec<Justification> = justifications.clone();
+        forged[1].signature = justifications[0].signature.clone();
+        let err = verifiers[2].process_justifications(&forged).unwrap_err();
+        assert!(err.to_string().contains("indices [1]"));
+        assert_eq!(vec![0, 1], verifiers[2].pending_justifications());
+
+        verifiers[2]
+            .process_justifications(&justifications)
+            .unwrap();
+        assert!(verifiers[2].pending_justifications().is_empty());
+        assert!(verifiers[2].deal_certified());
+    }
+
+    #[test]
+    fn test_vss_robust_recover_secret() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(deal, v)| v.process_encrypted_deal(deal).unwrap())
+            .collect();
+        for resp in resps.iter() {
+            for v in verifiers.iter_mut().filter(|v| v.index() != resp.index) {
+                v.process_response(resp).unwrap();
+            }
+            dealer.process_response(resp).unwrap();
+        }
+
+        let mut deals: Vec<Deal> = verifiers.iter().map(|v| v.get_deal().unwrap()).collect();
+        // a wrong share
+        deals[1].sec_share.v = ECScalar::new_random();
+        // a share consistent with forged commitments
+        let forged: Dealer = gen_dealer(
+            init_data.dealer_sec,
+            init_data.secret,
+            init_data.verifiers_pub.clone(),
+            init_data.vss_threshold,
+        );
+        deals[4] = forged.deals[4].clone();
+        deals[4].session_id = dealer.session_id.clone();
+        assert!(deals[4]
+            .verify(&init_data.verifiers_pub, &dealer.session_id)
+            .is_ok());
+
+        let (sec, invalid) = robust_recover_secret(
+            &init_data.dealer_pub,
+            &init_data.verifiers_pub,
+            &dealer.session_id,
+            &deals,
+            init_data.vss_threshold,
+        )
+        .unwrap();
+        assert_eq!(sec, dealer.secret);
+        assert_eq!(vec![1, 4], invalid);
+
+        // not enough valid deals left
+        robust_recover_secret(
+            &init_data.dealer_pub,
+            &init_data.verifiers_pub,
+            &dealer.session_id,
+            &deals[..init_data.vss_threshold as usize],
+            init_data.vss_threshold,
+        )
+        .expect_err("Must fail, not enough valid deals");
+    }
+
+    #[test]
+    fn test_vss_secret_recoverer() {
+        let init_data: InitData = setup(7);
+        let dealer: Dealer = gen_dealer(
+            init_data.dealer_sec,
+            init_data.secret,
+            init_data.verifiers_pub.clone(),
+            init_data.vss_threshold,
+        );
+        let t = init_data.vss_threshold as usize;
+
+        let mut recoverer: SecretRecoverer = SecretRecoverer::new(init_data.vss_threshold);
+        recoverer.try_recover().expect_err("Must fail, no deal");
+        for deal in dealer.deals[..t - 1].iter() {
+            recoverer.push(deal).unwrap();
+        }
+        recoverer
+            .push(&dealer.deals[0])
+            .expect_err("Must fail, duplicate deal");
+        let mut other = dealer.deals[t].clone();
+        other.session_id = vec![0xab; 32];
+        recoverer
+            .push(&other)
+            .expect_err("Must fail, other session");
+        assert!(!recoverer.can_recover());
+        recoverer.try_recover().expect_err("Must fail, t-1 deals");
+        recoverer.push(&dealer.deals[t]).unwrap();
+        assert_eq!(t, recoverer.len());
+        assert_eq!(dealer.secret, recoverer.try_recover().unwrap());
+
+        // deals checked against the commitments
+        let mut recoverer: SecretRecoverer = SecretRecoverer::for_session(
+            init_data.verifiers_pub.clone(),
+            &dealer.session_id,
+            init_data.vss_threshold,
+        );
+        let mut wrong = dealer.deals[0].clone();
+        wrong.sec_share.v = ECScalar::new_random();
+        recoverer.push(&wrong).expect_err("Must fail, wrong share");
+        assert!(recoverer.is_empty());
+        for deal in dealer.deals[..t].iter() {
+            recoverer.push(deal).unwrap();
+        }
+        assert_eq!(dealer.secret, recoverer.try_recover().unwrap());
+    }
+
+    #[test]
+    fn test_vss_dealer_with_rng() {
+        let init_data: InitData = setup(7);
+        let new_dealer = |seed: u64| {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let dealer: Dealer = Dealer::new_with_rng(
+                init_data.dealer_sec,
+                init_data.secret,
+                init_data.verifiers_pub.clone(),
+                init_data.vss_threshold,
+                &mut rng,
+            )
+            .unwrap();
+            let enc_deal: EncryptedDeal = dealer.encrypt_deal_with_rng(0, &mut rng).unwrap();
+            (dealer, enc_deal)
+        };
+
+        // same seed, same sharing and encryption
+        let (d1, e1) = new_dealer(42);
+        let (d2, e2) = new_dealer(42);
+        assert_eq!(d1.deals, d2.deals);
+        assert_eq!(d1.session_id, d2.session_id);
+        assert_eq!(e1.dh_key, e2.dh_key);
+        assert_eq!(e1.nonce, e2.nonce);
+        assert_eq!(e1.cipher, e2.cipher);
+
+        let (d3, e3) = new_dealer(43);
+        assert_ne!(d1.session_id, d3.session_id);
+        assert_ne!(e1.dh_key, e3.dh_key);
+
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[0],
+            init_data.dealer_pub,
+            init_data.verifiers_pub.clone(),
+        )
+        .unwrap();
+        assert_eq!(d1.deals[0], v.decrypt_deal(&e1).unwrap());
+    }
+
     #[test]
     fn test_vss_dealer_new() {
         let init_data: InitData = setup(7);
@@ -1016,15 +5065,267 @@ mod tests {
         )
         .expect("Failed to create dealer");
 
-        for threshold in 0..=1 {
-            Dealer::new(
-                init_data.dealer_sec,
-                init_data.secret,
-                init_data.verifiers_pub.clone(),
-                threshold as u32,
-            )
-            .expect_err("Can't create dealer with threshold 0, 1");
+        for threshold in 0..=1 {
+            Dealer::new(
+                init_data.dealer_sec,
+                init_data.secret,
+                init_data.verifiers_pub.clone(),
+                threshold as u32,
+            )
+            .expect_err("Can't create dealer with threshold 0, 1");
+        }
+    }
+
+    #[test]
+    fn test_vss_dealer_builder() {
+        let init_data: InitData = setup(7);
+        let verifiers_pub = init_data.verifiers_pub.clone();
+        let dealer: Dealer = DealerBuilder::new(init_data.dealer_sec, verifiers_pub.clone())
+            .secret(init_data.secret)
+            .context(b"app")
+            .aead(DealAead::ChaCha20Poly1305)
+            .timeout(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        assert_eq!(minimum_t(7), dealer.t);
+        assert_eq!(DealAead::ChaCha20Poly1305, dealer.aead());
+        assert_eq!(init_data.secret, dealer.secret);
+
+        // the verifiers must be given the same context
+        let enc_deal = dealer.encrypt_deal(0).unwrap();
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[0],
+            init_data.dealer_pub,
+            verifiers_pub.clone(),
+        )
+        .unwrap();
+        assert!(v.process_encrypted_deal(&enc_deal).is_err());
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[0],
+            init_data.dealer_pub,
+            verifiers_pub.clone(),
+        )
+        .unwrap();
+        v.set_context(b"app").unwrap();
+        assert!(v.process_encrypted_deal(&enc_deal).unwrap().approved);
+
+        let builder = DealerBuilder::new(init_data.dealer_sec, verifiers_pub.clone());
+        assert_eq!(
+            Err(ConfigError::InvalidThreshold { t: 8, n: 7 }),
+            builder.clone().threshold(8).check()
+        );
+        assert_eq!(
+            Err(ConfigError::ZeroTimeout),
+            builder.timeout(Duration::from_secs(0)).check()
+        );
+        let mut duplicated = verifiers_pub.clone();
+        duplicated[3] = duplicated[1];
+        let err = DealerBuilder::<GE>::new(init_data.dealer_sec, duplicated)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            Some(&ConfigError::DuplicateVerifier(3)),
+            err.downcast_ref::<ConfigError>()
+        );
+        assert_eq!(
+            Err(ConfigError::NoVerifiers),
+            DealerBuilder::<GE>::new(init_data.dealer_sec, Vec::new()).check()
+        );
+    }
+
+    #[test]
+    fn test_vss_session_context() {
+        let init_data: InitData = setup(7);
+        let verifiers_pub = init_data.verifiers_pub.clone();
+        let ctx = SessionContext::new(b"wallet", 3);
+        let dealer: Dealer = DealerBuilder::new(init_data.dealer_sec, verifiers_pub.clone())
+            .secret(init_data.secret)
+            .session_context(&ctx)
+            .build()
+            .unwrap();
+        let enc_deal = dealer.encrypt_deal(0).unwrap();
+
+        // a verifier at another epoch rejects the deal
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[0],
+            init_data.dealer_pub,
+            verifiers_pub.clone(),
+        )
+        .unwrap();
+        v.set_session_context(&SessionContext::new(b"wallet", 4))
+            .unwrap();
+        assert_eq!(
+            "vss: deal bound to another application context or epoch",
+            v.process_encrypted_deal(&enc_deal).unwrap_err().to_string()
+        );
+
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[0],
+            init_data.dealer_pub,
+            verifiers_pub.clone(),
+        )
+        .unwrap();
+        v.set_session_context(&ctx).unwrap();
+        assert!(v.process_encrypted_deal(&enc_deal).unwrap().approved);
+        assert!(v.set_session_context(&ctx).is_err());
+        assert_eq!(ctx.encode(), v.context);
+        assert_ne!(ctx.encode(), SessionContext::new(b"wallet", 4).encode());
+    }
+
+    #[test]
+    fn test_vss_for_existing_key() {
+        let init_data: InitData = setup(7);
+        let expected: GE = GE::generator().scalar_mul(&init_data.secret.get_element());
+        let mut key: FE = init_data.secret;
+        let mut wrong: FE = ECScalar::new_random();
+        Dealer::for_existing_key(
+            init_data.dealer_sec,
+            &mut wrong,
+            &expected,
+            init_data.verifiers_pub.clone(),
+            init_data.vss_threshold,
+        )
+        .expect_err("Must fail, not the expected public key");
+        assert_ne!(FE::zero(), wrong);
+        Dealer::for_existing_key(
+            init_data.dealer_sec,
+            &mut FE::zero(),
+            &GE::identity(),
+            init_data.verifiers_pub.clone(),
+            init_data.vss_threshold,
+        )
+        .expect_err("Must fail, zero key");
+
+        let dealer: Dealer = Dealer::for_existing_key(
+            init_data.dealer_sec,
+            &mut key,
+            &expected,
+            init_data.verifiers_pub.clone(),
+            init_data.vss_threshold,
+        )
+        .unwrap();
+        assert_eq!(FE::zero(), key);
+        assert_eq!(expected, dealer.secret_commit().unwrap());
+    }
+
+    #[test]
+    fn test_vss_domain() {
+        let init_data: InitData = setup(7);
+        let verifiers_pub = init_data.verifiers_pub.clone();
+        let domain = Domain::new(b"deployment a");
+        let mut dealer: Dealer = DealerBuilder::new(init_data.dealer_sec, verifiers_pub.clone())
+            .secret(init_data.secret)
+            .domain(&domain)
+            .build()
+            .unwrap();
+        let enc_deal = dealer.encrypt_deal(0).unwrap();
+
+        // the signature of the dealer isn't valid in another domain
+        let new_verifier = || -> Verifier {
+            Verifier::new(
+                init_data.verifiers_sec[0],
+                init_data.dealer_pub,
+                verifiers_pub.clone(),
+            )
+            .unwrap()
+        };
+        let mut v = new_verifier();
+        v.process_encrypted_deal(&enc_deal)
+            .expect_err("Must fail, default domain");
+        let mut v = new_verifier();
+        v.set_domain(&Domain::new(b"deployment b")).unwrap();
+        v.process_encrypted_deal(&enc_deal)
+            .expect_err("Must fail, another domain");
+
+        let mut v = new_verifier();
+        v.set_domain(&domain).unwrap();
+        let resp = v.process_encrypted_deal(&enc_deal).unwrap();
+        assert!(resp.approved);
+        assert!(v.set_domain(&domain).is_err());
+        resp.verify(&verifiers_pub, &resp.session_id)
+            .expect_err("Must fail, response of another domain");
+        resp.verify_in(&domain, &verifiers_pub, &resp.session_id)
+            .unwrap();
+        assert!(dealer.process_response(&resp).unwrap().is_none());
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_vss_hash_suite() {
+        let init_data: InitData = setup(7);
+        let verifiers_pub = init_data.verifiers_pub.clone();
+        let domain = Domain::default().with_suite(HashSuite::Blake3Sha512);
+        let dealer: Dealer = DealerBuilder::new(init_data.dealer_sec, verifiers_pub.clone())
+            .secret(init_data.secret)
+            .domain(&domain)
+            .build()
+            .unwrap();
+        let enc_deal = dealer.encrypt_deal(0).unwrap();
+        let new_verifier = || -> Verifier {
+            Verifier::new(
+                init_data.verifiers_sec[0],
+                init_data.dealer_pub,
+                verifiers_pub.clone(),
+            )
+            .unwrap()
+        };
+
+        // a verifier of the default suite names the suite of the deal
+        let mut v = new_verifier();
+        let err = v.process_encrypted_deal(&enc_deal).unwrap_err();
+        assert!(err.to_string().contains("hash suite blake3-sha512"));
+
+        let mut v = new_verifier();
+        v.set_domain(&domain).unwrap();
+        assert!(v.process_encrypted_deal(&enc_deal).unwrap().approved);
+        let commitments = v.aggregator.deal.commitments.clone();
+        let default_sid = session_id(
+            &init_data.dealer_pub,
+            &verifiers_pub,
+            &commitments,
+            init_data.vss_threshold,
+        );
+        assert_ne!(&default_sid[..], dealer.get_session_id());
+        let digest = v.digest().unwrap();
+        assert_eq!(
+            commitments_root_in(HashSuite::Blake3Sha512, &commitments).to_vec(),
+            digest.commitments_root
+        );
+        assert_ne!(
+            commitments_root(&commitments).to_vec(),
+            digest.commitments_root
+        );
+    }
+
+    #[test]
+    fn test_vss_encrypted_deals_addressed() {
+        let init_data: InitData = setup(5);
+        let (dealer, mut verifiers) = gen_all(&init_data);
+        let addressed = dealer.encrypted_deals_addressed(0).unwrap();
+        assert_eq!(5, addressed.len());
+        for (a, v) in addressed.iter().zip(verifiers.iter_mut()) {
+            assert_eq!(v.index(), a.index);
+            assert_eq!(init_data.verifiers_pub[a.index as usize], a.public);
+            assert!(v.process_encrypted_deal(&a.deal).unwrap().approved);
+        }
+
+        // the dealer is the participant at index 0 of the DKG
+        let mut participants = init_data.verifiers_pub.clone();
+        participants[0] = init_data.dealer_pub;
+        let envelope = &addressed[1].envelope;
+        let msg: Message = envelope
+            .open(&participants, dealer.get_session_id())
+            .unwrap();
+        match msg {
+            Message::Deal(d) => {
+                assert_eq!(0, d.index);
+                assert_eq!(addressed[1].deal, d.deal);
+            }
+            _ => panic!("not a deal"),
         }
+        envelope
+            .open(&init_data.verifiers_pub, dealer.get_session_id())
+            .expect_err("Must fail, not signed by the verifier 0");
     }
 
     #[test]
@@ -1203,6 +5504,166 @@ mod tests {
         enc_deal.cipher = correct_cipher;
     }
 
+    #[test]
+    fn test_vss_encrypt_deal_fresh_nonce() {
+        let init_data: InitData = setup(7);
+        let (dealer, mut verifiers) = gen_all(&init_data);
+
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+
+        // no two deals share the same (key, nonce) pair
+        for (i, d1) in enc_deals.iter().enumerate() {
+            assert_eq!(dh::NONCE_SIZE, d1.nonce.len());
+            for d2 in enc_deals.iter().skip(i + 1) {
+                assert_ne!(d1.dh_key, d2.dh_key);
+                assert_ne!(d1.nonce, d2.nonce);
+            }
+        }
+
+        // encrypting the same deal twice gives a different key and nonce
+        let again: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        assert_ne!(enc_deals[0].dh_key, again.dh_key);
+        assert_ne!(enc_deals[0].nonce, again.nonce);
+
+        let v: &mut Verifier = &mut verifiers[0];
+        let d1: Deal = v.decrypt_deal(&enc_deals[0]).unwrap();
+        let d2: Deal = v.decrypt_deal(&again).unwrap();
+        assert_eq!(d1, d2);
+    }
+
+    #[test]
+    fn test_vss_verifier_decrypt_deal_wrong_nonce() {
+        let init_data: InitData = setup(7);
+        let (dealer, mut verifiers) = gen_all(&init_data);
+        let v: &mut Verifier = &mut verifiers[0];
+
+        let mut enc_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+
+        // nonce of another deal
+        let correct_nonce = enc_deal.nonce.clone();
+        enc_deal.nonce = dealer.encrypt_deal(1).unwrap().nonce;
+        v.decrypt_deal(&enc_deal).expect_err("Wrong nonce");
+
+        // all zero nonce
+        enc_deal.nonce = [0u8; 12].to_vec();
+        v.decrypt_deal(&enc_deal).expect_err("Wrong nonce");
+
+        // nonce of invalid size
+        enc_deal.nonce = [0u8; 5].to_vec();
+        v.decrypt_deal(&enc_deal).expect_err("Invalid nonce size");
+
+        enc_deal.nonce = correct_nonce;
+        v.decrypt_deal(&enc_deal).expect("Must work fine");
+    }
+
+    #[test]
+    fn test_vss_verifier_decrypt_deal_signed_fields() {
+        let init_data: InitData = setup(7);
+        let (dealer, mut verifiers) = gen_all(&init_data);
+        let v: &mut Verifier = &mut verifiers[1];
+
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        let mut enc_deal: EncryptedDeal = enc_deals[1].clone();
+        v.decrypt_deal(&enc_deal).expect("Must work fine");
+
+        // deal of another recipient
+        v.decrypt_deal(&enc_deals[0])
+            .expect_err("Must fail, deal of another verifier");
+
+        // ciphertext and nonce swapped with the ones of another recipient
+        enc_deal.nonce = enc_deals[0].nonce.clone();
+        enc_deal.cipher = enc_deals[0].cipher.clone();
+        v.decrypt_deal(&enc_deal)
+            .expect_err("Must fail, swapped ciphertext");
+        enc_deal = enc_deals[1].clone();
+
+        // another session id
+        enc_deal.session_id = vec![0u8; 32];
+        v.decrypt_deal(&enc_deal)
+            .expect_err("Must fail, wrong session id");
+        enc_deal = enc_deals[1].clone();
+
+        // older wire format
+        enc_deal.version = 0;
+        let err = v.decrypt_deal(&enc_deal).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unsupported encrypted deal version"));
+    }
+
+    #[test]
+    fn test_vss_chacha20poly1305() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        assert_eq!(DealAead::Aes256Gcm, dealer.aead());
+        dealer.set_aead(DealAead::ChaCha20Poly1305);
+
+        let mut enc_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        assert_eq!(DealAead::ChaCha20Poly1305, enc_deal.aead);
+        let v: &mut Verifier = &mut verifiers[0];
+        assert_eq!(dealer.deals[0], v.decrypt_deal(&enc_deal).unwrap());
+
+        // the scheme is signed along with the deal
+        enc_deal.aead = DealAead::Aes256Gcm;
+        v.decrypt_deal(&enc_deal)
+            .expect_err("Must fail, wrong aead");
+        enc_deal.aead = DealAead::ChaCha20Poly1305;
+
+        let resp = v.process_encrypted_deal(&enc_deal).unwrap();
+        assert!(resp.approved);
+    }
+
+    #[cfg(feature = "hpke")]
+    #[test]
+    fn test_vss_hpke() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        dealer.set_mode(DealMode::Hpke);
+        dealer.set_aead(DealAead::ChaCha20Poly1305);
+
+        let mut enc_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        assert_eq!(DealMode::Hpke, enc_deal.mode);
+        assert!(enc_deal.nonce.is_empty());
+        let v: &mut Verifier = &mut verifiers[0];
+        assert_eq!(dealer.deals[0], v.decrypt_deal(&enc_deal).unwrap());
+
+        // the mode is signed along with the deal
+        enc_deal.mode = DealMode::Legacy;
+        v.decrypt_deal(&enc_deal)
+            .expect_err("Must fail, wrong mode");
+        enc_deal.mode = DealMode::Hpke;
+
+        let resp = v.process_encrypted_deal(&enc_deal).unwrap();
+        assert!(resp.approved);
+    }
+
+    #[cfg(feature = "pq-kem")]
+    #[test]
+    fn test_vss_hybrid() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let (kem_pubs, kem_secs): (Vec<Vec<u8>>, Vec<Vec<u8>>) =
+            (0..7).map(|_| crate::pq::new_kem_key()).unzip();
+        dealer.set_mode(DealMode::Hybrid);
+        assert!(dealer.encrypt_deal(0).is_err());
+        assert!(dealer.set_kem_keys(kem_pubs[1..].to_vec()).is_err());
+        dealer.set_kem_keys(kem_pubs).unwrap();
+
+        let enc_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        let v: &mut Verifier = &mut verifiers[0];
+        v.decrypt_deal(&enc_deal)
+            .expect_err("Must fail, no KEM key");
+        v.set_kem_key(kem_secs[1].clone());
+        v.decrypt_deal(&enc_deal)
+            .expect_err("Must fail, wrong KEM key");
+        v.set_kem_key(kem_secs[0].clone());
+        assert_eq!(dealer.deals[0], v.decrypt_deal(&enc_deal).unwrap());
+        assert!(v.complaint(&enc_deal).is_err());
+
+        let resp = v.process_encrypted_deal(&enc_deal).unwrap();
+        assert!(resp.approved);
+    }
+
     #[test]
     fn test_vss_verifier_receive_deal_correct_deal() {
         let init_data: InitData = setup(7);
@@ -1286,10 +5747,153 @@ mod tests {
 
         let enc_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
 
-        v.process_encrypted_deal(&enc_deal)
+        let resp = v
+            .process_encrypted_deal(&enc_deal)
             .expect("First time work fine");
-        v.process_encrypted_deal(&enc_deal)
-            .expect_err("Already received this deal");
+        // a re-delivery, even encrypted again, is a no-op
+        assert_eq!(resp, v.process_encrypted_deal(&enc_deal).unwrap());
+        let again: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        assert_eq!(resp, v.process_encrypted_deal(&again).unwrap());
+        assert_eq!(1, v.aggregator.responses.len());
+
+        // another deal signed by the dealer is an equivocation
+        let mut other: Dealer = dealer.clone();
+        other.deals[0].sec_share.v = FE::new_random();
+        let forked: EncryptedDeal = other.encrypt_deal(0).unwrap();
+        let err = v.process_encrypted_deal(&forked).unwrap_err();
+        match err.downcast_ref::<EquivocationError>() {
+            Some(EquivocationError::Deal(a, b)) => {
+                assert_eq!(enc_deal, **a);
+                assert_eq!(forked, **b);
+            }
+            _ => panic!("expected an equivocation, got {}", err),
+        }
+
+        // and a deal not signed by the dealer is rejected
+        let mut forged: EncryptedDeal = forked;
+        forged.signature = enc_deal.signature.clone();
+        let err = v.process_encrypted_deal(&forged).unwrap_err();
+        assert!(err.downcast_ref::<EquivocationError>().is_none());
+    }
+
+    #[test]
+    fn test_vss_justification_verify() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let good_deal: Deal = dealer.deals[0].clone();
+        dealer.deals[0].sec_share.v = FE::new_random();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        dealer.deals[0] = good_deal;
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(d, v)| v.process_encrypted_deal(d).unwrap())
+            .collect();
+        assert!(!resps[0].approved);
+        let j: Justification = dealer.process_response(&resps[0]).unwrap().unwrap();
+
+        let sid = dealer.get_session_id().to_vec();
+        let pubs = &init_data.verifiers_pub;
+        j.verify(&init_data.dealer_pub, pubs, &sid).unwrap();
+        assert!(j.verify(&pubs[0], pubs, &sid).is_err());
+        assert!(j.verify(&init_data.dealer_pub, pubs, &[0u8; 32]).is_err());
+        let mut bad: Justification = j.clone();
+        bad.index = 1;
+        assert!(bad.verify(&init_data.dealer_pub, pubs, &sid).is_err());
+
+        // verifiers which haven't received the complaint accept the
+        // justification, which applies to the complaint once received
+        let mut late: Verifier = verifiers[1].clone();
+        late.process_justification(&j).unwrap();
+        assert!(late.process_justification(&bad).is_err());
+        for r in resps[2..].iter() {
+            late.process_response(r).unwrap();
+        }
+        let mut timed_out: Verifier = late.clone();
+        timed_out.set_timeout();
+        assert!(timed_out.deal_certified());
+        late.process_response(&resps[0]).unwrap();
+        assert_eq!(7, late.status().approvals.len());
+        assert!(late.deal_certified());
+    }
+
+    #[test]
+    fn test_vss_response_redelivery() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        let resp: Response = verifiers[1].process_encrypted_deal(&enc_deals[1]).unwrap();
+
+        assert!(dealer.process_response(&resp).unwrap().is_none());
+        assert!(dealer.process_response(&resp).unwrap().is_none());
+        verifiers[0].process_response(&resp).unwrap();
+        verifiers[0].process_response(&resp).unwrap();
+        assert_eq!(vec![1], verifiers[0].status().approvals);
+
+        // a complaint signed by the same verifier is an equivocation
+        let r_hash = Response::hash(&resp.session_id, 1, 0).unwrap();
+        let complaint = Response {
+            approved: false,
+            signature: GE::sign_msg(
+                &init_data.verifiers_sec[1],
+                &init_data.verifiers_pub[1],
+                &r_hash,
+                &1u32.to_le_bytes(),
+            )
+            .unwrap(),
+            ..resp.clone()
+        };
+        let err = dealer.process_response(&complaint).unwrap_err();
+        match err.downcast_ref::<EquivocationError>() {
+            Some(EquivocationError::Response(a, b)) => {
+                assert_eq!(&resp, a);
+                assert_eq!(&complaint, b);
+            }
+            _ => panic!("expected an equivocation, got {}", err),
+        }
+    }
+
+    #[test]
+    fn test_vss_complaint_equivocation() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let good: Deal = dealer.deals[1].clone();
+        dealer.deals[1].sec_share.v = FE::new_random();
+        let enc_deal: EncryptedDeal = dealer.encrypt_deal(1).unwrap();
+        dealer.deals[1] = good;
+        let resp: Response = verifiers[1].process_encrypted_deal(&enc_deal).unwrap();
+        assert_eq!(Some(ComplaintReason::ShareMismatch), resp.reason);
+
+        let j: Justification = dealer.process_response(&resp).unwrap().unwrap();
+        verifiers[0].process_response(&resp).unwrap();
+        verifiers[0].process_justification(&j).unwrap();
+        assert!(verifiers[0].responses()[&1].approved);
+        // the justified complaint is still a re-delivery
+        verifiers[0].process_response(&resp).unwrap();
+
+        // another complaint signed by the same verifier is an equivocation
+        let reason = Some(ComplaintReason::InvalidDeal);
+        let r_hash =
+            Response::hash_reason_in(&Domain::default(), &resp.session_id, 1, 0, reason).unwrap();
+        let other = Response {
+            reason,
+            signature: GE::sign_msg(
+                &init_data.verifiers_sec[1],
+                &init_data.verifiers_pub[1],
+                &r_hash,
+                &1u32.to_le_bytes(),
+            )
+            .unwrap(),
+            ..resp.clone()
+        };
+        let err = verifiers[0].process_response(&other).unwrap_err();
+        match err.downcast_ref::<EquivocationError>() {
+            Some(EquivocationError::Response(a, b)) => {
+                assert_eq!(&resp, a);
+                assert_eq!(&other, b);
+            }
+            _ => panic!("expected an equivocation, got {}", err),
+        }
     }
 
     #[test]
@@ -1303,6 +5907,7 @@ mod tests {
         v.process_encrypted_deal(&enc_deal)
             .expect("New deal, must work fine");
         v.aggregator.deal.t = 0; // reset aggregator in such way
+        v.received = None;
 
         v.aggregator.responses.insert(
             v.index,
@@ -1335,6 +5940,105 @@ mod tests {
         assert!(!resp.approved);
     }
 
+    #[test]
+    fn test_vss_complaint() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        for (v, deal) in verifiers.iter_mut().zip(enc_deals.iter()).skip(1) {
+            assert!(v.process_encrypted_deal(deal).unwrap().approved);
+        }
+
+        // the deal of verifier 0 doesn't verify against the commitments
+        dealer.deals[0].rnd_share.v = ECScalar::new_random();
+        let bad_deal: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        assert!(
+            !verifiers[0]
+                .process_encrypted_deal(&bad_deal)
+                .unwrap()
+                .approved
+        );
+        let c: Complaint = verifiers[0].complaint(&bad_deal).unwrap();
+        assert!(c
+            .judge(&init_data.dealer_pub, &init_data.verifiers_pub)
+            .unwrap());
+        assert!(verifiers[1].process_complaint(&c).unwrap());
+        assert!(verifiers[1].aggregator.bad_dealer);
+
+        // the shared key must be the one of the complaining verifier
+        let mut forged: Complaint = c.clone();
+        forged.dh_shared = GE::generator();
+        forged
+            .judge(&init_data.dealer_pub, &init_data.verifiers_pub)
+            .expect_err("Must fail, invalid proof");
+        verifiers[2]
+            .process_complaint(&forged)
+            .expect_err("Must fail, invalid proof");
+    }
+
+    #[test]
+    fn test_vss_complaint_unfounded() {
+        let init_data: InitData = setup(7);
+        let (dealer, mut verifiers) = gen_all(&init_data);
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        for (v, deal) in verifiers.iter_mut().zip(enc_deals.iter()).skip(1) {
+            v.process_encrypted_deal(deal).unwrap();
+        }
+
+        // verifier 0 complains about a valid deal
+        let c: Complaint = verifiers[0].complaint(&enc_deals[0]).unwrap();
+        assert!(!c.response.approved);
+        assert!(!c
+            .judge(&init_data.dealer_pub, &init_data.verifiers_pub)
+            .unwrap());
+        assert!(!verifiers[1].process_complaint(&c).unwrap());
+        assert!(verifiers[1].aggregator.responses[&0].approved);
+
+        // no complaint about an approved deal
+        verifiers[1]
+            .complaint(&enc_deals[1])
+            .expect_err("Must fail, deal approved");
+    }
+
+    #[test]
+    fn test_vss_compact_commitments() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        dealer.set_compact_commitments(true);
+        let commitments: DealCommitments = dealer.deal_commitments().unwrap();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        dealer.set_compact_commitments(false);
+        let full: EncryptedDeal = dealer.encrypt_deal(0).unwrap();
+        assert!(enc_deals[0].cipher.len() < full.cipher.len());
+
+        verifiers[0]
+            .process_encrypted_deal(&enc_deals[0])
+            .expect_err("Must fail, commitments not received");
+        let mut forged: DealCommitments = commitments.clone();
+        forged.commitments.pop();
+        verifiers[0]
+            .process_commitments(&forged)
+            .expect_err("Must fail, invalid signature");
+        for (v, deal) in verifiers.iter_mut().zip(enc_deals.iter()) {
+            v.process_commitments(&commitments).unwrap();
+            if v.index() != 1 {
+                assert!(v.process_encrypted_deal(deal).unwrap().approved);
+            }
+        }
+        assert_eq!(dealer.deals[0], verifiers[0].aggregator.deal);
+
+        // a complaint carries the commitments to judge a compact deal
+        let c: Complaint = verifiers[1].complaint(&enc_deals[1]).unwrap();
+        assert!(!c
+            .judge(&init_data.dealer_pub, &init_data.verifiers_pub)
+            .unwrap());
+        let mut forged: Complaint = c.clone();
+        forged.commitments = None;
+        forged
+            .judge(&init_data.dealer_pub, &init_data.verifiers_pub)
+            .expect_err("Must fail, commitments missing");
+    }
+
     #[test]
     fn test_vss_aggregator_verify_justification() {
         let init_data: InitData = setup(7);
@@ -1381,6 +6085,40 @@ mod tests {
             .expect_err("no complaints for this justification before");
     }
 
+    #[test]
+    fn test_vss_malformed_messages() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, _) = gen_all(&init_data);
+
+        // a complaint of an unknown verifier
+        let mut resp = Response {
+            session_id: dealer.session_id.clone(),
+            index: 7,
+            approved: false,
+            signature: custom_signature(),
+            reason: None,
+        };
+        dealer
+            .process_response(&resp)
+            .expect_err("Must fail, index out of bounds");
+        resp.session_id.truncate(4);
+        dealer
+            .process_response(&resp)
+            .expect_err("Must fail, short session id");
+
+        recover_secret::<GE>(&[], init_data.vss_threshold).expect_err("Must fail, no deal");
+        poly::recover_pub_poly::<GE>(&mut [], 0).expect_err("Must fail, no share");
+
+        // points and scalars of invalid encodings
+        let not_canonical = "ff".repeat(32);
+        for hex in ["zz", "00", not_canonical.as_str()].iter() {
+            let bytes = bincode::serialize(hex).unwrap();
+            bincode::deserialize::<GE>(&bytes).expect_err("Must fail, invalid point");
+        }
+        let bytes = bincode::serialize("0102").unwrap();
+        bincode::deserialize::<FE>(&bytes).expect_err("Must fail, invalid scalar");
+    }
+
     #[test]
     fn test_vss_aggregator_verify_response_duplicate() {
         let init_data: InitData = setup(7);
@@ -1406,8 +6144,9 @@ mod tests {
 
         assert_eq!(v0.aggregator.responses.get(&v1.index).unwrap(), &resp1);
 
-        v0.process_response(&resp1)
-            .expect_err("Already processed response");
+        // a re-delivery is a no-op
+        v0.process_response(&resp1).expect("Must work fine");
+        assert_eq!(v0.aggregator.responses.get(&v1.index).unwrap(), &resp1);
 
         v0.aggregator.responses.insert(
             v1.index,
@@ -1445,7 +6184,7 @@ mod tests {
         resp.index = 45;
         let r_hash = Response::hash(&resp.session_id.to_vec(), resp.index, false as u32).unwrap();
         let sig: Vec<u8> = sign::sign_msg(
-            v.longterm.get_element().to_bytes(),
+            v.longterm.secret().get_element().to_bytes(),
             v.pub_k.get_element().to_bytes(),
             &r_hash,
             &v.index.to_le_bytes(),
@@ -1461,7 +6200,7 @@ mod tests {
         // wrong signature
         let good_sig = resp.signature.clone();
         resp.signature = sign::sign_msg(
-            v.longterm.get_element().to_bytes(),
+            v.longterm.secret().get_element().to_bytes(),
             v.pub_k.get_element().to_bytes(),
             &[0u8; 32],
             &v.index.to_le_bytes(),
@@ -1479,6 +6218,28 @@ mod tests {
         v.aggregator.verify_response(&resp).expect_err("Wrong ID");
     }
 
+    #[test]
+    fn test_vss_response_verify() {
+        let init_data: InitData = setup(7);
+        let (dealer, mut verifiers) = gen_all(&init_data);
+        let verifiers_pub = &init_data.verifiers_pub;
+
+        let enc_deal: EncryptedDeal = dealer.encrypt_deal(1).unwrap();
+        let mut resp = verifiers[1].process_encrypted_deal(&enc_deal).unwrap();
+        resp.verify(verifiers_pub, &dealer.session_id).unwrap();
+
+        resp.verify(verifiers_pub, &[0u8; 32])
+            .expect_err("Wrong ID");
+        resp.verify(verifiers_pub, &dealer.session_id[..31])
+            .expect_err("Short ID");
+        resp.index = 2;
+        resp.verify(verifiers_pub, &dealer.session_id)
+            .expect_err("Wrong signer");
+        resp.index = 45;
+        resp.verify(verifiers_pub, &dealer.session_id)
+            .expect_err("Wrong index");
+    }
+
     #[test]
     fn test_vss_aggregator_verify_deal() {
         let init_data: InitData = setup(7);
@@ -1600,6 +6361,279 @@ mod tests {
         assert!(dealer.aggregator.deal_certified());
     }
 
+    #[test]
+    fn test_vss_status() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+
+        let status: ProtocolStatus = dealer.status();
+        assert_eq!(init_data.vss_threshold, status.threshold);
+        assert_eq!((0..7).collect::<Vec<u32>>(), status.missing_responders);
+        assert!(status.approvals.is_empty() && status.complaints.is_empty());
+
+        // verifier 2 gets a wrong deal, 5 and 6 don't respond
+        dealer.deals[2].rnd_share.v = ECScalar::new_random();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        let resps: Vec<Response> = (0..5)
+            .map(|i| verifiers[i].process_encrypted_deal(&enc_deals[i]).unwrap())
+            .collect();
+        for resp in resps.iter() {
+            dealer.process_response(resp).unwrap();
+            if resp.index != 0 {
+                verifiers[0].process_response(resp).unwrap();
+            }
+        }
+
+        for status in [dealer.status(), verifiers[0].status()].iter() {
+            assert_eq!(vec![0, 1, 3, 4], status.approvals);
+            assert_eq!(vec![2], status.complaints);
+            assert_eq!(vec![5, 6], status.missing_responders);
+            assert!(status.enough_approvals);
+            assert!(!status.deal_certified);
+        }
+        assert_eq!(5, dealer.responses().len());
+
+        dealer.set_timeout();
+        assert_eq!(vec![2, 5, 6], dealer.complaints());
+        assert!(dealer.missing_responders().is_empty());
+    }
+
+    #[derive(Debug)]
+    struct QualPolicy {
+        qual: Vec<u32>,
+    }
+
+    impl CertificationPolicy for QualPolicy {
+        fn deal_certified(&self, view: &AggregatorView) -> bool {
+            !view.bad_dealer()
+                && self
+                    .qual
+                    .iter()
+                    .all(|i| view.responses().get(i).map_or(false, |r| r.approved))
+        }
+    }
+
+    #[test]
+    fn test_vss_certification_policy() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        let policy = Arc::new(QualPolicy {
+            qual: vec![0, 1, 2],
+        });
+        dealer.set_policy(policy.clone());
+        verifiers[0].set_policy(policy);
+
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        let resps: Vec<Response> = (0..3)
+            .map(|i| verifiers[i].process_encrypted_deal(&enc_deals[i]).unwrap())
+            .collect();
+        for resp in resps.iter() {
+            assert!(!dealer.deal_certified());
+            dealer.process_response(resp).unwrap();
+            if resp.index != 0 {
+                verifiers[0].process_response(resp).unwrap();
+            }
+        }
+
+        // certified by the policy, before the threshold and the timeout
+        assert!(dealer.deal_certified());
+        assert!(verifiers[0].deal_certified());
+        let view = dealer.aggregator();
+        assert_eq!(vec![0, 1, 2], view.approvals());
+        assert_eq!(vec![3, 4, 5, 6], view.missing_responders());
+        assert_eq!(init_data.vss_threshold, view.threshold());
+        assert!(!view.rule_certified());
+    }
+
+    #[test]
+    fn test_vss_complaint_reason() {
+        let init_data: InitData = setup(7);
+        let (mut dealer, mut verifiers) = gen_all(&init_data);
+        dealer.deals[0].sec_share.v = FE::new_random();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+
+        let resp = verifiers[0].process_encrypted_deal(&enc_deals[0]).unwrap();
+        assert_eq!(Some(ComplaintReason::ShareMismatch), resp.reason);
+        let approval = verifiers[1].process_encrypted_deal(&enc_deals[1]).unwrap();
+        assert_eq!(None, approval.reason);
+        let c: Complaint = verifiers[3].complaint(&enc_deals[3]).unwrap();
+        assert_eq!(Some(ComplaintReason::DecryptFailed), c.response.reason);
+
+        let v: &mut Verifier = &mut verifiers[2];
+        v.process_encrypted_deal(&enc_deals[2]).unwrap();
+        // the reason is signed
+        let mut forged = resp.clone();
+        forged.reason = Some(ComplaintReason::BadSessionId);
+        v.process_response(&forged)
+            .expect_err("Must fail, reason not signed");
+        forged.reason = Some(ComplaintReason::Timeout);
+        v.process_response(&forged)
+            .expect_err("Must fail, signed timeout");
+        v.process_response(&resp).unwrap();
+        v.process_response(&approval).unwrap();
+        v.process_response(&c.response).unwrap();
+        v.set_timeout();
+
+        let reasons = v.complaint_reasons();
+        assert_eq!(
+            v.complaints(),
+            reasons.iter().map(|(i, _)| *i).collect::<Vec<u32>>()
+        );
+        assert_eq!((0, Some(ComplaintReason::ShareMismatch)), reasons[0]);
+        assert_eq!((3, Some(ComplaintReason::DecryptFailed)), reasons[1]);
+        assert!(reasons[2..]
+            .iter()
+            .all(|(_, r)| *r == Some(ComplaintReason::Timeout)));
+        assert_eq!(reasons, v.aggregator().complaint_reasons());
+    }
+
+    #[test]
+    fn test_vss_dealer_role() {
+        let init_data: InitData = setup(5);
+        let listed =
+            || DealerBuilder::new(init_data.verifiers_sec[0], init_data.verifiers_pub.clone());
+        assert_eq!(
+            Err(ConfigError::ExternalDealerListed(0)),
+            listed().dealer_role(DealerRole::External).check()
+        );
+        assert_eq!(
+            Err(ConfigError::DealerNotListed),
+            DealerBuilder::new(init_data.dealer_sec, init_data.verifiers_pub.clone())
+                .dealer_role(DealerRole::Participant)
+                .check()
+        );
+
+        // a participating dealer keeps its share and approves implicitly
+        let dealer: Dealer = listed()
+            .dealer_role(DealerRole::Participant)
+            .build()
+            .unwrap();
+        assert_eq!(0, dealer.own_deal().unwrap().sec_share.i);
+        assert_eq!(vec![0], dealer.approvals());
+        assert!(listed().build().unwrap().own_deal().is_none());
+
+        let enc_deal = dealer.encrypt_deal(1).unwrap();
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[1],
+            init_data.verifiers_pub[0],
+            init_data.verifiers_pub.clone(),
+        )
+        .unwrap();
+        v.set_config(&VerifierConfig {
+            dealer_role: DealerRole::Participant,
+            ..Default::default()
+        });
+        v.process_encrypted_deal(&enc_deal).unwrap();
+        assert_eq!(vec![0, 1], v.approvals());
+        assert_eq!(Some(0), v.aggregator().dealer_index());
+
+        let mut v: Verifier = Verifier::new(
+            init_data.verifiers_sec[1],
+            init_data.verifiers_pub[0],
+            init_data.verifiers_pub.clone(),
+        )
+        .unwrap();
+        v.set_dealer_role(DealerRole::External);
+        v.process_encrypted_deal(&enc_deal)
+            .expect_err("Must fail, external dealer listed");
+    }
+
+    #[test]
+    fn test_vss_quorum() {
+        let init_data: InitData = setup(7);
+        let run = |quorum: Quorum, timeout: Option<Duration>| -> (Dealer, Verifier) {
+            let mut config: DealerConfig = DealerConfig {
+                secret: Some(init_data.secret),
+                quorum,
+                ..Default::default()
+            };
+            config.timeout = timeout;
+            let mut dealer: Dealer =
+                DealerBuilder::new(init_data.dealer_sec, init_data.verifiers_pub.clone())
+                    .config(config)
+                    .build()
+                    .unwrap();
+            let (_, mut verifiers) = gen_all(&init_data);
+            verifiers[0].set_config(&VerifierConfig {
+                quorum,
+                timeout,
+                ..Default::default()
+            });
+            let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+            // 4 = t approvals, the others don't respond
+            for i in 0..4 {
+                let resp = verifiers[i].process_encrypted_deal(&enc_deals[i]).unwrap();
+                dealer.process_response(&resp).unwrap();
+                if i != 0 {
+                    verifiers[0].process_response(&resp).unwrap();
+                }
+            }
+            let verifier = verifiers.swap_remove(0);
+            (dealer, verifier)
+        };
+
+        let (mut dealer, _) = run(Quorum::Full, None);
+        assert!(!dealer.deal_certified());
+        dealer.set_timeout();
+        assert!(dealer.deal_certified());
+
+        let (dealer, verifier) = run(Quorum::Threshold, None);
+        assert!(dealer.deal_certified() && verifier.deal_certified());
+
+        let (dealer, _) = run(Quorum::ThresholdWithDeadline, Some(Duration::from_secs(60)));
+        assert!(!dealer.deal_certified());
+        let (mut dealer, mut verifier) = run(Quorum::ThresholdWithDeadline, None);
+        let now = Instant::now();
+        dealer.set_deadline(now);
+        verifier.set_deadline(now);
+        // the deadline is only observed by tick
+        assert!(!dealer.deal_certified() && !verifier.deal_certified());
+        assert!(dealer.tick(now) && verifier.tick(now));
+        assert!(dealer.deal_certified() && verifier.deal_certified());
+        assert_eq!(3, dealer.aggregator().missing_responders().len());
+        let dealer: Dealer = bincode::deserialize(&bincode::serialize(&dealer).unwrap()).unwrap();
+        assert!(dealer.deal_certified());
+
+        let (mut dealer, _) = run(Quorum::Unanimous, None);
+        assert_eq!(Quorum::Unanimous, dealer.aggregator().quorum());
+        dealer.set_timeout();
+        assert!(!dealer.deal_certified());
+    }
+
+    #[test]
+    fn test_vss_verifier_identity() {
+        let init_data: InitData = setup(5);
+        let verifiers: Arc<[GE]> = init_data.verifiers_pub.clone().into();
+        let mut identity: VerifierIdentity =
+            VerifierIdentity::new(init_data.verifiers_sec[1], verifiers.clone()).unwrap();
+        assert_eq!(1, identity.index());
+        identity.set_context(b"ceremony");
+
+        // one session per dealer, sharing the list of verifiers
+        for _ in 0..2 {
+            let (dealer_sec, dealer_pub) = gen_pair();
+            let dealer: Dealer = DealerBuilder::new(dealer_sec, init_data.verifiers_pub.clone())
+                .context(b"ceremony")
+                .build()
+                .unwrap();
+            let mut session: Verifier = identity.session(dealer_pub).unwrap();
+            assert_eq!(verifiers.as_ptr(), session.verifiers().as_ptr());
+            let enc_deal = dealer.encrypt_deal(1).unwrap();
+            assert!(session.process_encrypted_deal(&enc_deal).unwrap().approved);
+        }
+
+        // a verifier gives back its identity
+        let (_, dealer_pub) = gen_pair();
+        let mut v: Verifier = identity.session(dealer_pub).unwrap();
+        v.set_quorum(Quorum::Threshold);
+        let again = v.identity().session(dealer_pub).unwrap();
+        assert_eq!(Quorum::Threshold, again.quorum());
+        assert_eq!(b"ceremony".to_vec(), again.context);
+
+        VerifierIdentity::new(init_data.dealer_sec, verifiers)
+            .expect_err("Must fail, not a verifier");
+    }
+
     #[test]
     fn test_vss_verifier_set_timeout() {
         let init_data: InitData = setup(7);
@@ -1703,6 +6737,95 @@ mod tests {
         let pub6: GE = generator.scalar_mul(&priv6.get_element());
 
         let some_vec: Vec<GE> = vec![pub_1, pub_2, pub3, pub4, pub5, pub6];
-        derive_h(&some_vec).unwrap();
+        let h: GE = derive_h(&some_vec).unwrap();
+        assert_eq!(h, derive_h(&some_vec).unwrap());
+        assert_ne!(h, derive_h(&some_vec[1..]).unwrap());
+        assert_eq!(h, derive_h_in(HDerivation::default(), &some_vec).unwrap());
+
+        let legacy: GE = derive_h_in(HDerivation::Legacy, &some_vec).unwrap();
+        assert_eq!(legacy, derive_h_legacy(&some_vec).unwrap());
+        let dst = b"THRESHOLD-DKG-V01-DERIVE-H-ristretto255_XMD:SHA-512_R255MAP_RO_";
+        let buffer: Vec<u8> = some_vec.iter().flat_map(|p| p.pk_to_key_slice()).collect();
+        let hashed: GE = derive_h_in(HDerivation::HashToGroup, &some_vec).unwrap();
+        assert_eq!(GE::hash_to_group(&buffer, dst).unwrap(), hashed);
+        assert_ne!(legacy, hashed);
+    }
+
+    #[test]
+    fn test_vss_h_derivation() {
+        let init_data: InitData = setup(7);
+        let verifiers_pub = init_data.verifiers_pub.clone();
+        let new_dealer = |derivation: HDerivation| -> Dealer {
+            DealerBuilder::new(init_data.dealer_sec, verifiers_pub.clone())
+                .secret(init_data.secret)
+                .domain(&Domain::default().with_h_derivation(derivation))
+                .build()
+                .unwrap()
+        };
+        let legacy = new_dealer(HDerivation::Legacy);
+        let hashed = new_dealer(HDerivation::HashToGroup);
+        assert_ne!(legacy.get_session_id(), hashed.get_session_id());
+
+        // a verifier only accepts the deals of its own derivation
+        for (dealer, derivation) in [
+            (&legacy, HDerivation::Legacy),
+            (&hashed, HDerivation::HashToGroup),
+        ]
+        .iter()
+        {
+            let enc_deal = dealer.encrypt_deal(0).unwrap();
+            let new_verifier = |derivation: HDerivation| -> Verifier {
+                let mut v = Verifier::new(
+                    init_data.verifiers_sec[0],
+                    init_data.dealer_pub,
+                    verifiers_pub.clone(),
+                )
+                .unwrap();
+                v.set_domain(&Domain::default().with_h_derivation(derivation))
+                    .unwrap();
+                v
+            };
+            let other = match derivation {
+                HDerivation::Legacy => HDerivation::HashToGroup,
+                HDerivation::HashToGroup => HDerivation::Legacy,
+            };
+            let mut v = new_verifier(other);
+            v.process_encrypted_deal(&enc_deal)
+                .expect_err("Must fail, another derivation of H");
+            let mut v = new_verifier(*derivation);
+            assert!(v.process_encrypted_deal(&enc_deal).unwrap().approved);
+        }
+    }
+
+    #[test]
+    fn test_vss_hash_vectors() {
+        // golden vectors of the canonical encoding, which must never change
+        // for a given ENCODING_VERSION
+        let resp_hash = Response::hash(b"session", 3, 1).unwrap();
+        assert_eq!(
+            "a5b581893e9e53454ee48ac4d5f99eccd25b01adce69b6e44c98772648b4e3ab",
+            hex::encode(resp_hash)
+        );
+
+        let deal: Deal = Deal {
+            session_id: vec![2, 2],
+            sec_share: PriShare {
+                i: 1,
+                v: FE::from(5u64),
+            },
+            rnd_share: PriShare {
+                i: 1,
+                v: FE::from(7u64),
+            },
+            t: 2,
+            commitments: vec![vec![0xaa; 3], vec![0xbb]],
+            context: Vec::new(),
+            commitments_root: Vec::new(),
+        };
+        let j_hash = Justification::hash(&[2, 2], 1, &deal).unwrap();
+        assert_eq!(
+            "d31b2cb4875a122aa4dc4c03e5a4d09c5488853809352d9e7d6baf3c529a4384",
+            hex::encode(j_hash)
+        );
     }
 }
@@ -0,0 +1,294 @@
+//! JavaScript bindings
+//!
+//! Thin wasm-bindgen wrappers around the vss Dealer and Verifier and the dkg
+//! DistKeyGenerator on the default Ristretto backend, so that browser clients
+//! can take part in the protocols. Keys, scalars and messages are passed as
+//! their bincode encoding in Uint8Arrays, and errors are thrown as strings.
+//! Enabled with the `wasm` feature, for the wasm32-unknown-unknown target.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dkg;
+use crate::ristretto_curve;
+use crate::vss;
+use crate::wire;
+
+use curve_traits::{ECPoint, ECScalar};
+use ristretto_curve::{FE, GE};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::prelude::*;
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, JsValue> {
+    wire::decode(bytes).map_err(js_err)
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, JsValue> {
+    bincode::serialize(value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn js_err(e: Box<dyn Error>) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+/// new_secret returns a fresh private key.
+#[wasm_bindgen(js_name = newSecret)]
+pub fn new_secret() -> Result<Vec<u8>, JsValue> {
+    let secret: FE = ECScalar::new_random();
+    encode(&secret)
+}
+
+/// public_key returns the public key of the private key.
+#[wasm_bindgen(js_name = publicKey)]
+pub fn public_key(secret: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let secret: FE = decode(secret)?;
+    encode(&GE::generator().scalar_mul(&secret.get_element()))
+}
+
+/// Dealer wraps vss::Dealer.
+#[wasm_bindgen]
+pub struct Dealer {
+    inner: vss::Dealer,
+}
+
+#[wasm_bindgen]
+impl Dealer {
+    /// new takes the longterm private key of the dealer, the secret to share,
+    /// the list of public keys of the verifiers and the threshold.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        longterm: &[u8],
+        secret: &[u8],
+        verifiers: &[u8],
+        t: u32,
+    ) -> Result<Dealer, JsValue> {
+        let inner = vss::Dealer::new(decode(longterm)?, decode(secret)?, decode(verifiers)?, t)
+            .map_err(js_err)?;
+        Ok(Dealer { inner })
+    }
+
+    /// encryptDeals returns the list of EncryptedDeals, one per verifier.
+    #[wasm_bindgen(js_name = encryptDeals)]
+    pub fn encrypt_deals(&self) -> Result<Vec<u8>, JsValue> {
+        encode(&self.inner.encrypt_deals().map_err(js_err)?)
+    }
+
+    /// processResponse returns a Justification to broadcast, if any.
+    #[wasm_bindgen(js_name = processResponse)]
+    pub fn process_response(&mut self, response: &[u8]) -> Result<Option<Vec<u8>>, JsValue> {
+        let j = self
+            .inner
+            .process_response(&wire::decode_response::<GE>(response).map_err(js_err)?)
+            .map_err(js_err)?;
+        j.map(|j| encode(&j)).transpose()
+    }
+
+    #[wasm_bindgen(js_name = dealCertified)]
+    pub fn deal_certified(&self) -> bool {
+        self.inner.deal_certified()
+    }
+}
+
+/// Verifier wraps vss::Verifier.
+#[wasm_bindgen]
+pub struct Verifier {
+    inner: vss::Verifier,
+}
+
+#[wasm_bindgen]
+impl Verifier {
+    /// new takes the longterm private key of the verifier, the public key of
+    /// the dealer and the list of public keys of the verifiers.
+    #[wasm_bindgen(constructor)]
+    pub fn new(longterm: &[u8], dealer: &[u8], verifiers: &[u8]) -> Result<Verifier, JsValue> {
+        let inner = vss::Verifier::new(decode(longterm)?, decode(dealer)?, decode(verifiers)?)
+            .map_err(js_err)?;
+        Ok(Verifier { inner })
+    }
+
+    /// processEncryptedDeal returns the Response to broadcast.
+    #[wasm_bindgen(js_name = processEncryptedDeal)]
+    pub fn process_encrypted_deal(&mut self, deal: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let resp = self
+            .inner
+            .process_encrypted_deal(&wire::decode_encrypted_deal::<GE>(deal).map_err(js_err)?)
+            .map_err(js_err)?;
+        encode(&resp)
+    }
+
+    #[wasm_bindgen(js_name = processResponse)]
+    pub fn process_response(&mut self, response: &[u8]) -> Result<(), JsValue> {
+        self.inner
+            .process_response(&wire::decode_response::<GE>(response).map_err(js_err)?)
+            .map_err(js_err)
+    }
+
+    #[wasm_bindgen(js_name = processJustification)]
+    pub fn process_justification(&mut self, justification: &[u8]) -> Result<(), JsValue> {
+        let t = self.inner.max_threshold();
+        self.inner
+            .process_justification(
+                &wire::decode_justification::<GE>(justification, t).map_err(js_err)?,
+            )
+            .map_err(js_err)
+    }
+
+    #[wasm_bindgen(js_name = dealCertified)]
+    pub fn deal_certified(&self) -> bool {
+        self.inner.deal_certified()
+    }
+
+    /// deal returns the Deal of this verifier, holding its share.
+    pub fn deal(&self) -> Result<Vec<u8>, JsValue> {
+        encode(&self.inner.get_deal().map_err(js_err)?)
+    }
+}
+
+/// DistKeyGenerator wraps dkg::DistKeyGenerator.
+#[wasm_bindgen]
+pub struct DistKeyGenerator {
+    inner: dkg::DistKeyGenerator,
+}
+
+#[wasm_bindgen]
+impl DistKeyGenerator {
+    /// new takes the longterm private key of this participant, the list of
+    /// public keys of the participants and the threshold.
+    #[wasm_bindgen(constructor)]
+    pub fn new(longterm: &[u8], participants: &[u8], t: u32) -> Result<DistKeyGenerator, JsValue> {
+        let inner = dkg::DistKeyGenerator::new(decode(longterm)?, decode(participants)?, t)
+            .map_err(js_err)?;
+        Ok(DistKeyGenerator { inner })
+    }
+
+    /// deals returns the map of the Deals to send, by index of the recipient.
+    pub fn deals(&mut self) -> Result<Vec<u8>, JsValue> {
+        encode(&self.inner.deals().map_err(js_err)?)
+    }
+
+    /// processDeal returns the Response to broadcast.
+    #[wasm_bindgen(js_name = processDeal)]
+    pub fn process_deal(&mut self, deal: &[u8]) -> Result<Vec<u8>, JsValue> {
+        encode(
+            &self
+                .inner
+                .process_deal(&wire::decode_dkg_deal::<GE>(deal).map_err(js_err)?)
+                .map_err(js_err)?,
+        )
+    }
+
+    /// processResponse returns a Justification to broadcast, if any.
+    #[wasm_bindgen(js_name = processResponse)]
+    pub fn process_response(&mut self, response: &[u8]) -> Result<Option<Vec<u8>>, JsValue> {
+        let j = self
+            .inner
+            .process_response(&wire::decode_dkg_response::<GE>(response).map_err(js_err)?)
+            .map_err(js_err)?;
+        j.map(|j| encode(&j)).transpose()
+    }
+
+    #[wasm_bindgen(js_name = processJustification)]
+    pub fn process_justification(&mut self, justification: &[u8]) -> Result<(), JsValue> {
+        let t = self.inner.threshold();
+        self.inner
+            .process_justification(
+                &wire::decode_dkg_justification::<GE>(justification, t).map_err(js_err)?,
+            )
+            .map_err(js_err)
+    }
+
+    #[wasm_bindgen(js_name = setTimeout)]
+    pub fn set_timeout(&mut self) {
+        self.inner.set_timeout()
+    }
+
+    pub fn certified(&self) -> bool {
+        self.inner.certified()
+    }
+
+    /// secretCommits returns the SecretCommits to broadcast.
+    #[wasm_bindgen(js_name = secretCommits)]
+    pub fn secret_commits(&mut self) -> Result<Vec<u8>, JsValue> {
+        encode(&self.inner.secret_commits().map_err(js_err)?)
+    }
+
+    /// processSecretCommits returns a ComplaintCommits to broadcast, if any.
+    #[wasm_bindgen(js_name = processSecretCommits)]
+    pub fn process_secret_commits(&mut self, sc: &[u8]) -> Result<Option<Vec<u8>>, JsValue> {
+        let t = self.inner.threshold();
+        let cc = self
+            .inner
+            .process_secret_commit(&wire::decode_secret_commits::<GE>(sc, t).map_err(js_err)?)
+            .map_err(js_err)?;
+        cc.map(|cc| encode(&cc)).transpose()
+    }
+
+    /// processComplaintCommits returns the ReconstructCommits to broadcast.
+    #[wasm_bindgen(js_name = processComplaintCommits)]
+    pub fn process_complaint_commits(&mut self, cc: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let t = self.inner.threshold();
+        let rc = self
+            .inner
+            .process_complaints_commits(
+                &wire::decode_complaint_commits::<GE>(cc, t).map_err(js_err)?,
+            )
+            .map_err(js_err)?;
+        encode(&rc)
+    }
+
+    #[wasm_bindgen(js_name = processReconstructCommits)]
+    pub fn process_reconstruct_commits(&mut self, rc: &[u8]) -> Result<(), JsValue> {
+        self.inner
+            .process_reconstruct_commits(
+                &wire::decode_reconstruct_commits::<GE>(rc).map_err(js_err)?,
+            )
+            .map_err(js_err)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.inner.finished()
+    }
+
+    /// distKeyShare returns the DistKeyShare of this participant.
+    #[wasm_bindgen(js_name = distKeyShare)]
+    pub fn dist_key_share(&self) -> Result<Vec<u8>, JsValue> {
+        encode(&self.inner.dist_key_share().map_err(js_err)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_vss() {
+        let n = 5;
+        let t = vss::minimum_t(n);
+        let dealer_sec = new_secret().unwrap();
+        let secret = new_secret().unwrap();
+        let verifiers_sec: Vec<Vec<u8>> = (0..n).map(|_| new_secret().unwrap()).collect();
+        let verifiers_pub: Vec<GE> = verifiers_sec
+            .iter()
+            .map(|s| decode(&public_key(s).unwrap()).unwrap())
+            .collect();
+        let verifiers_pub = encode(&verifiers_pub).unwrap();
+        let dealer_pub = public_key(&dealer_sec).unwrap();
+
+        let mut dealer = Dealer::new(&dealer_sec, &secret, &verifiers_pub, t).unwrap();
+        let enc_deals: Vec<vss::EncryptedDeal> = decode(&dealer.encrypt_deals().unwrap()).unwrap();
+        let mut deals: Vec<vss::Deal> = Vec::new();
+        for (sec, enc_deal) in verifiers_sec.iter().zip(enc_deals.iter()) {
+            let mut v = Verifier::new(sec, &dealer_pub, &verifiers_pub).unwrap();
+            let resp = v
+                .process_encrypted_deal(&encode(enc_deal).unwrap())
+                .unwrap();
+            let r: vss::Response = decode(&resp).unwrap();
+            assert!(r.approved);
+            assert!(dealer.process_response(&resp).unwrap().is_none());
+            deals.push(decode(&v.deal().unwrap()).unwrap());
+        }
+        assert!(dealer.deal_certified());
+        let secret: FE = decode(&secret).unwrap();
+        assert_eq!(secret, vss::recover_secret(&deals, t).unwrap());
+    }
+}
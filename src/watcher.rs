@@ -0,0 +1,187 @@
+//! Monitoring of a run of the vss protocol from outside
+//!
+//! A Watcher isn't one of the verifiers and only knows the public keys of the
+//! dealer and of the verifiers, along with the session id and the threshold
+//! announced by the dealer. It processes the broadcasted messages as a
+//! Verifier does, without any deal of its own.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::ristretto_curve;
+use crate::vss;
+
+use curve_traits::CurvePoint;
+use encoding::Domain;
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use vss::{
+    check_points, valid_t, Aggregator, Certification, Complaint, Justification, ProtocolStatus,
+    Response,
+};
+
+/// Watcher follows a run of the protocol from outside. It isn't one of the
+/// verifiers and only knows the public keys of the dealer and of the
+/// verifiers, along with the session id and the threshold announced by the
+/// dealer. It processes the broadcasted responses, complaints and
+/// justifications to tell whether the deal is certified, e.g. to monitor a
+/// ceremony.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(bound = "")]
+pub struct Watcher<P: CurvePoint = GE> {
+    aggregator: Aggregator<P>,
+    // application context bound to the session id, to judge the complaints
+    context: Vec<u8>,
+}
+
+impl<P: CurvePoint> Watcher<P> {
+    pub fn new(
+        dealer: P,
+        verifiers: Vec<P>,
+        session_id: &[u8],
+        t: u32,
+    ) -> Result<Watcher<P>, Box<dyn Error>> {
+        if session_id.len() != 32 {
+            bail!("vss: invalid session id");
+        }
+        if !valid_t(t, &verifiers) {
+            bail!("vss: invalid threshold received");
+        }
+        Ok(Watcher {
+            aggregator: Aggregator::new(dealer, verifiers.into(), t, session_id.to_vec()),
+            context: Vec::new(),
+        })
+    }
+
+    /// set_certification sets the rule deciding when the deal is certified,
+    /// which must be the one of the dealer and the verifiers.
+    pub fn set_certification(&mut self, certification: Certification) {
+        self.aggregator.certification = certification;
+    }
+
+    /// set_points sets the x-coordinates of the shares of a Dealer created
+    /// with new_with_points.
+    pub fn set_points(&mut self, points: Vec<P::Scalar>) -> Result<(), Box<dyn Error>> {
+        check_points::<P>(&points, self.aggregator.verifiers.len())?;
+        self.aggregator.points = Some(points.into());
+        Ok(())
+    }
+
+    /// set_context sets the application context of the session, to judge the
+    /// complaints.
+    pub fn set_context(&mut self, context: &[u8]) {
+        self.context = context.to_vec();
+    }
+
+    /// set_domain sets the domain of the session, to verify its messages.
+    pub fn set_domain(&mut self, domain: &Domain) {
+        self.aggregator.domain = domain.clone();
+    }
+
+    /// set_dealer_index declares the dealer as the verifier at index, see
+    /// Verifier::set_dealer_index.
+    pub fn set_dealer_index(&mut self, index: u32) -> Result<(), Box<dyn Error>> {
+        self.aggregator.set_dealer_index(index)
+    }
+
+    /// process_response checks the signature of a response and records it.
+    pub fn process_response(&mut self, resp: &Response) -> Result<(), Box<dyn Error>> {
+        let certified = self.aggregator.deal_certified();
+        self.aggregator.verify_response(resp)?;
+        self.aggregator.record_certified(certified);
+        Ok(())
+    }
+
+    /// process_complaint judges a complaint, see Verifier::process_complaint.
+    pub fn process_complaint(&mut self, c: &Complaint<P>) -> Result<bool, Box<dyn Error>> {
+        self.aggregator.process_complaint(c, &self.context)
+    }
+
+    /// process_justification checks the deal revealed by a justification of
+    /// the dealer. It returns an error if the deal is invalid, in which case
+    /// the deal will never be certified.
+    pub fn process_justification(
+        &mut self,
+        justification: &Justification<P>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.aggregator.process_justification(justification)
+    }
+
+    pub fn set_timeout(&mut self) {
+        self.aggregator.clean_verifiers();
+    }
+
+    pub fn deal_certified(&self) -> bool {
+        self.aggregator.deal_certified()
+    }
+
+    pub fn enough_approvals(&self) -> bool {
+        self.aggregator.enough_approvals()
+    }
+
+    pub fn status(&self) -> ProtocolStatus {
+        self.aggregator.status()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim;
+    use curve_traits::ECScalar;
+    use ristretto_curve::FE;
+    use vss::{minimum_t, Deal, Dealer, EncryptedDeal, Verifier};
+
+    #[test]
+    fn test_vss_watcher() {
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(7);
+        let (dealer_sec, dealer_pub): (Vec<FE>, Vec<GE>) = sim::new_participants(1);
+        let t = minimum_t(7);
+        let mut dealer: Dealer =
+            Dealer::new(dealer_sec[0], FE::new_random(), pubs.clone(), t).unwrap();
+        let mut verifiers: Vec<Verifier> = secs
+            .iter()
+            .map(|s| Verifier::new(*s, dealer_pub[0], pubs.clone()).unwrap())
+            .collect();
+        let mut watcher: Watcher =
+            Watcher::new(dealer_pub[0], pubs.clone(), dealer.get_session_id(), t).unwrap();
+        assert!(Watcher::new(dealer_pub[0], pubs.clone(), &[1, 2, 3], t).is_err());
+
+        // the first deal is invalid, and justified
+        let good_deal: Deal = dealer.deals[0].clone();
+        dealer.deals[0].sec_share.v = FE::new_random();
+        let enc_deals: Vec<EncryptedDeal> = dealer.encrypt_deals().unwrap();
+        dealer.deals[0] = good_deal;
+        let resps: Vec<Response> = enc_deals
+            .iter()
+            .zip(verifiers.iter_mut())
+            .map(|(d, v)| v.process_encrypted_deal(d).unwrap())
+            .collect();
+        assert!(!resps[0].approved);
+
+        let mut justification: Option<Justification> = None;
+        for resp in resps.iter() {
+            watcher.process_response(resp).unwrap();
+            if let Some(j) = dealer.process_response(resp).unwrap() {
+                justification = Some(j);
+            }
+        }
+        assert!(watcher.deal_certified());
+        assert_eq!(vec![0], watcher.status().complaints);
+        watcher.process_response(&resps[1]).unwrap();
+        assert_eq!(vec![0], watcher.status().complaints);
+
+        let mut j = justification.unwrap();
+        let good_v: FE = j.deal.sec_share.v;
+        j.deal.sec_share.v = FE::new_random();
+        let mut bad_watcher = watcher.clone();
+        assert!(bad_watcher.process_justification(&j).is_err());
+        assert!(!bad_watcher.deal_certified());
+
+        j.deal.sec_share.v = good_v;
+        watcher.process_justification(&j).unwrap();
+        assert!(watcher.deal_certified());
+        assert_eq!(7, watcher.status().approvals.len());
+    }
+}
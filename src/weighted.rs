@@ -0,0 +1,224 @@
+//! Weighted secret sharing
+//!
+//! A participant of weight w holds w shares of the secret, at consecutive
+//! evaluation indices, e.g. in stake-weighted committees. The vss Dealer shares
+//! the secret among the list of participants expanded with their weights, and a
+//! participant runs one Verifier per share it holds. Thresholds count shares:
+//! a set of participants can recover the secret once their weights add up to
+//! t, with vss::recover_secret over all of their deals.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::ops::Range;
+
+use crate::curve_traits;
+use crate::vss;
+
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use serde::{Deserialize, Serialize};
+use vss::Verifier;
+
+/// Weights holds the number of shares of each participant. The shares of
+/// participant j are at the indices following the shares of participant j-1.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Weights {
+    weights: Vec<u32>,
+    // index of the first share of each participant
+    offsets: Vec<u32>,
+}
+
+impl Weights {
+    /// new returns the Weights of the participants, in the order of the list of
+    /// participants. It returns an error if a weight is zero.
+    pub fn new(weights: Vec<u32>) -> Result<Weights, Box<dyn Error>> {
+        if weights.is_empty() {
+            bail!("weighted: no participants");
+        }
+        if weights.contains(&0) {
+            bail!("weighted: weights must be positive");
+        }
+        let mut offsets: Vec<u32> = Vec::with_capacity(weights.len());
+        let mut total: u32 = 0;
+        for w in weights.iter() {
+            offsets.push(total);
+            total = total
+                .checked_add(*w)
+                .ok_or_else(|| simple_error!("weighted: too many shares"))?;
+        }
+        Ok(Weights { weights, offsets })
+    }
+
+    /// len returns the number of participants.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// is_empty returns true if there are no participants, which new rejects.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// total returns the total number of shares.
+    pub fn total(&self) -> u32 {
+        self.offsets[self.offsets.len() - 1] + self.weights[self.weights.len() - 1]
+    }
+
+    /// weight returns the number of shares of the participant, or 0 if it
+    /// doesn't exist.
+    pub fn weight(&self, participant: u32) -> u32 {
+        self.weights.get(participant as usize).copied().unwrap_or(0)
+    }
+
+    /// indices returns the evaluation indices of the shares of the participant.
+    pub fn indices(&self, participant: u32) -> Range<u32> {
+        match self.offsets.get(participant as usize) {
+            Some(&start) => start..start + self.weights[participant as usize],
+            None => 0..0,
+        }
+    }
+
+    /// owner returns the participant holding the share at index i.
+    pub fn owner(&self, i: u32) -> Option<u32> {
+        if i >= self.total() {
+            return None;
+        }
+        // the last participant whose first share is at or before i
+        let owner = match self.offsets.binary_search(&i) {
+            Ok(j) => j,
+            Err(j) => j - 1,
+        };
+        Some(owner as u32)
+    }
+
+    /// expand returns the list of verifiers of the shares, where every
+    /// participant appears once per share it holds.
+    pub fn expand<P: Clone>(&self, participants: &[P]) -> Result<Vec<P>, Box<dyn Error>> {
+        if participants.len() != self.weights.len() {
+            bail!(
+                "weighted: {} weights for {} participants",
+                self.weights.len(),
+                participants.len()
+            );
+        }
+        Ok(participants
+            .iter()
+            .zip(self.weights.iter())
+            .flat_map(|(p, &w)| std::iter::repeat(p.clone()).take(w as usize))
+            .collect())
+    }
+
+    /// weight_of returns the number of shares held by the given participants,
+    /// each of them counted once.
+    pub fn weight_of(&self, participants: &[u32]) -> u32 {
+        let distinct: HashSet<u32> = participants.iter().copied().collect();
+        distinct.iter().map(|&j| self.weight(j)).sum()
+    }
+
+    /// reaches returns true if the given participants hold at least t shares
+    /// together, i.e. if they can recover a secret shared with threshold t.
+    pub fn reaches(&self, participants: &[u32], t: u32) -> bool {
+        self.weight_of(participants) >= t
+    }
+}
+
+/// verifiers returns the Verifiers of the participant whose longterm key is
+/// given, one per share it holds, in the order of the share indices.
+pub fn verifiers<P: CurvePoint>(
+    longterm: P::Scalar,
+    dealer: P,
+    participants: &[P],
+    weights: &Weights,
+) -> Result<Vec<Verifier<P>>, Box<dyn Error>> {
+    let pub_k: P = P::generator().scalar_mul(&longterm.get_element());
+    let participant = participants
+        .iter()
+        .position(|p| p == &pub_k)
+        .ok_or_else(|| simple_error!("weighted: public key not found in the participants"))?;
+    let verifiers: Vec<P> = weights.expand(participants)?;
+    weights
+        .indices(participant as u32)
+        .map(|i| Verifier::new_at(longterm, dealer, verifiers.clone(), i))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve;
+    use crate::sim;
+    use ristretto_curve::{FE, GE};
+    use vss::{Deal, Dealer};
+
+    #[test]
+    fn test_weights() {
+        let weights = Weights::new(vec![1, 2, 3]).unwrap();
+        assert_eq!(3, weights.len());
+        assert_eq!(6, weights.total());
+        assert_eq!(1..3, weights.indices(1));
+        assert_eq!(3..6, weights.indices(2));
+        assert_eq!(0..0, weights.indices(3));
+        let owners: Vec<Option<u32>> = (0..7).map(|i| weights.owner(i)).collect();
+        assert_eq!(
+            vec![Some(0), Some(1), Some(1), Some(2), Some(2), Some(2), None],
+            owners
+        );
+        assert_eq!(
+            vec!['a', 'b', 'b', 'c', 'c', 'c'],
+            weights.expand(&['a', 'b', 'c']).unwrap()
+        );
+        assert!(weights.expand(&['a', 'b']).is_err());
+
+        assert_eq!(4, weights.weight_of(&[0, 2, 2]));
+        assert!(weights.reaches(&[0, 2], 4));
+        assert!(!weights.reaches(&[0, 1], 4));
+
+        assert!(Weights::new(vec![]).is_err());
+        assert!(Weights::new(vec![1, 0]).is_err());
+    }
+
+    #[test]
+    fn test_weighted_vss() {
+        let weights = Weights::new(vec![1, 2, 3]).unwrap();
+        let t: u32 = 4;
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(3);
+        let (dealer_sec, dealer_pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(1);
+        let secret: FE = ECScalar::new_random();
+
+        let mut dealer: Dealer =
+            Dealer::new_weighted(dealer_sec[0], secret, &pubs, &weights, t).unwrap();
+        let mut holders: Vec<Vec<Verifier>> = secs
+            .iter()
+            .map(|sec| verifiers(*sec, dealer_pubs[0], &pubs, &weights).unwrap())
+            .collect();
+        let counts: Vec<usize> = holders.iter().map(|v| v.len()).collect();
+        assert_eq!(vec![1, 2, 3], counts);
+
+        let mut responses = Vec::new();
+        for (i, encr_d) in dealer.encrypt_deals().unwrap().iter().enumerate() {
+            let owner = weights.owner(i as u32).unwrap();
+            let first = weights.indices(owner).start;
+            let v: &mut Verifier = &mut holders[owner as usize][i - first as usize];
+            let resp = v.process_encrypted_deal(encr_d).unwrap();
+            assert!(resp.approved);
+            assert!(dealer.process_response(&resp).unwrap().is_none());
+            responses.push(resp);
+        }
+        assert!(dealer.deal_certified());
+
+        for v in holders.iter_mut().flatten() {
+            for resp in responses.iter().filter(|r| r.index != v.index()) {
+                v.process_response(resp).unwrap();
+            }
+        }
+
+        // participants 0 and 2 hold 4 shares together
+        assert!(weights.reaches(&[0, 2], t));
+        let deals: Vec<Deal> = holders[0]
+            .iter()
+            .chain(holders[2].iter())
+            .map(|v| v.get_deal().unwrap())
+            .collect();
+        assert_eq!(secret, vss::recover_secret(&deals, t).unwrap());
+        assert!(!weights.reaches(&[0, 1], t));
+    }
+}
@@ -0,0 +1,373 @@
+//! Bounded decoding of the protocol messages received from the network.
+//!
+//! bincode trusts the lengths it reads, so that a few bytes announcing a huge
+//! vector make the decoder allocate and loop for nothing. The decoders of this
+//! module cap the size of a message and reject trailing bytes, and the checks
+//! bound every field of the vss messages: 32 bytes session ids, signatures of
+//! the size of the Schnorr signatures of the curve, at most t commitments of
+//! the size of an encoded point. The threshold t is the one of the session
+//! given by the receiver, or the number of participants when the receiver
+//! doesn't know it yet, and never the one announced by the message. The checks
+//! are cheap and run before any curve operation is spent on the message.
+
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::dh;
+use crate::dkg;
+use crate::transport;
+use crate::vss;
+
+use bincode::Options;
+use curve_traits::{CurvePoint, ECPoint, ECScalar};
+use serde::de::DeserializeOwned;
+use transport::Message;
+use vss::{Deal, EncryptedDeal, Justification, Response};
+
+/// Maximum size of an encoded message
+pub const MAX_MESSAGE_SIZE: u64 = 1 << 20;
+
+/// Size of the session ids
+pub const SESSION_ID_SIZE: usize = 32;
+
+/// decode decodes a value encoded with bincode::serialize, of at most
+/// MAX_MESSAGE_SIZE bytes and without trailing bytes.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Box<dyn Error>> {
+    decode_with_limit(bytes, MAX_MESSAGE_SIZE)
+}
+
+/// decode_with_limit is decode, for a value of at most limit bytes.
+pub fn decode_with_limit<T: DeserializeOwned>(
+    bytes: &[u8],
+    limit: u64,
+) -> Result<T, Box<dyn Error>> {
+    if bytes.len() as u64 > limit {
+        bail!(
+            "wire: message of {} bytes over the limit of {}",
+            bytes.len(),
+            limit
+        );
+    }
+    // the encoding of bincode::serialize
+    let value: T = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(limit)
+        .reject_trailing_bytes()
+        .deserialize(bytes)?;
+    Ok(value)
+}
+
+/// decode_encrypted_deal decodes and checks an EncryptedDeal.
+pub fn decode_encrypted_deal<P: CurvePoint>(
+    bytes: &[u8],
+) -> Result<EncryptedDeal<P>, Box<dyn Error>> {
+    let deal: EncryptedDeal<P> = decode(bytes)?;
+    check_encrypted_deal(&deal)?;
+    Ok(deal)
+}
+
+/// decode_justification decodes and checks a Justification of a session of
+/// threshold t.
+pub fn decode_justification<P: CurvePoint>(
+    bytes: &[u8],
+    t: u32,
+) -> Result<Justification<P>, Box<dyn Error>> {
+    let justification: Justification<P> = decode(bytes)?;
+    check_justification(&justification, t)?;
+    Ok(justification)
+}
+
+/// decode_response decodes and checks a Response of a vss run over the curve P.
+pub fn decode_response<P: CurvePoint>(bytes: &[u8]) -> Result<Response, Box<dyn Error>> {
+    let response: Response = decode(bytes)?;
+    check_response::<P>(&response)?;
+    Ok(response)
+}
+
+/// decode_dkg_deal decodes a dkg::Deal and checks its encrypted deal.
+pub fn decode_dkg_deal<P: CurvePoint>(bytes: &[u8]) -> Result<dkg::Deal<P>, Box<dyn Error>> {
+    let deal: dkg::Deal<P> = decode(bytes)?;
+    check_encrypted_deal(&deal.deal)?;
+    Ok(deal)
+}
+
+/// decode_dkg_response decodes a dkg::Response and checks its response.
+pub fn decode_dkg_response<P: CurvePoint>(bytes: &[u8]) -> Result<dkg::Response, Box<dyn Error>> {
+    let response: dkg::Response = decode(bytes)?;
+    check_response::<P>(&response.response)?;
+    Ok(response)
+}
+
+/// decode_dkg_justification decodes a dkg::Justification and checks its
+/// justification, of a dkg of threshold t.
+pub fn decode_dkg_justification<P: CurvePoint>(
+    bytes: &[u8],
+    t: u32,
+) -> Result<dkg::Justification<P>, Box<dyn Error>> {
+    let justification: dkg::Justification<P> = decode(bytes)?;
+    check_justification(&justification.justification, t)?;
+    Ok(justification)
+}
+
+/// decode_secret_commits decodes and checks a dkg::SecretCommits of a dkg of
+/// threshold t.
+pub fn decode_secret_commits<P: CurvePoint>(
+    bytes: &[u8],
+    t: u32,
+) -> Result<dkg::SecretCommits, Box<dyn Error>> {
+    let sc: dkg::SecretCommits = decode(bytes)?;
+    check_secret_commits::<P>(&sc, t)?;
+    Ok(sc)
+}
+
+/// decode_complaint_commits decodes and checks a dkg::ComplaintCommits of a
+/// dkg of threshold t.
+pub fn decode_complaint_commits<P: CurvePoint>(
+    bytes: &[u8],
+    t: u32,
+) -> Result<dkg::ComplaintCommits<P>, Box<dyn Error>> {
+    let cc: dkg::ComplaintCommits<P> = decode(bytes)?;
+    check_complaint_commits(&cc, t)?;
+    Ok(cc)
+}
+
+/// decode_reconstruct_commits decodes and checks a dkg::ReconstructCommits.
+pub fn decode_reconstruct_commits<P: CurvePoint>(
+    bytes: &[u8],
+) -> Result<dkg::ReconstructCommits<P>, Box<dyn Error>> {
+    let rc: dkg::ReconstructCommits<P> = decode(bytes)?;
+    check_reconstruct_commits(&rc)?;
+    Ok(rc)
+}
+
+/// decode_message decodes a message of the transports and checks the vss
+/// messages it holds, of a session of threshold t.
+pub fn decode_message<P: CurvePoint>(bytes: &[u8], t: u32) -> Result<Message<P>, Box<dyn Error>> {
+    let msg: Message<P> = decode(bytes)?;
+    check_message(&msg, t)?;
+    Ok(msg)
+}
+
+/// check_message checks the size of the fields of the vss messages held by a
+/// message of the transports, of a session of threshold t.
+pub fn check_message<P: CurvePoint>(msg: &Message<P>, t: u32) -> Result<(), Box<dyn Error>> {
+    match msg {
+        Message::Deal(d) => check_encrypted_deal(&d.deal)?,
+        Message::Response(r) => check_response::<P>(&r.response)?,
+        Message::Justification(j) => check_justification(&j.justification, t)?,
+        Message::SecretCommits(sc) => check_secret_commits::<P>(sc, t)?,
+        Message::ComplaintCommits(cc) => check_complaint_commits(cc, t)?,
+        Message::ReconstructCommits(rc) => check_reconstruct_commits(rc)?,
+        Message::Echo(_) => {}
+    }
+    Ok(())
+}
+
+/// signature_size returns the size of the Schnorr signatures over the curve P,
+/// 64 bytes over ristretto.
+pub fn signature_size<P: CurvePoint>() -> usize {
+    point_size::<P>() + P::Scalar::zero().to_bytes().len()
+}
+
+/// check_encrypted_deal checks the size of the fields of an EncryptedDeal.
+pub fn check_encrypted_deal<P: CurvePoint>(deal: &EncryptedDeal<P>) -> Result<(), Box<dyn Error>> {
+    if deal.version != vss::ENCRYPTED_DEAL_VERSION {
+        bail!("wire: unsupported encrypted deal version {}", deal.version);
+    }
+    check_session_id(&deal.session_id)?;
+    check_signature::<P>(&deal.signature)?;
+    if !deal.nonce.is_empty() && deal.nonce.len() != dh::NONCE_SIZE {
+        bail!("wire: invalid nonce size in encrypted deal");
+    }
+    Ok(())
+}
+
+/// check_deal checks the size of the fields of a decrypted Deal of a session
+/// of threshold t, which holds at most t commitments, or the Merkle root of the
+/// commitments if compact.
+pub fn check_deal<P: CurvePoint>(deal: &Deal<P>, t: u32) -> Result<(), Box<dyn Error>> {
+    check_session_id(&deal.session_id)?;
+    if deal.sec_share.i != deal.rnd_share.i {
+        bail!("wire: not the same index for the shares of the deal");
+    }
+    if deal.t > t {
+        bail!("wire: threshold {} of the deal over {}", deal.t, t);
+    }
+    if !deal.commitments_root.is_empty() {
+        if deal.commitments_root.len() != 32 || !deal.commitments.is_empty() {
+            bail!("wire: invalid compact deal");
+        }
+        return Ok(());
+    }
+    check_commitments::<P>(&deal.commitments, t)
+}
+
+/// check_justification checks the size of the fields of a Justification and
+/// of its deal, of a session of threshold t.
+pub fn check_justification<P: CurvePoint>(
+    j: &Justification<P>,
+    t: u32,
+) -> Result<(), Box<dyn Error>> {
+    check_session_id(&j.session_id)?;
+    check_signature::<P>(&j.signature)?;
+    check_deal(&j.deal, t)
+}
+
+/// check_response checks the size of the fields of a Response of a vss run
+/// over the curve P.
+pub fn check_response<P: CurvePoint>(r: &Response) -> Result<(), Box<dyn Error>> {
+    check_session_id(&r.session_id)?;
+    check_signature::<P>(&r.signature)
+}
+
+fn check_secret_commits<P: CurvePoint>(
+    sc: &dkg::SecretCommits,
+    t: u32,
+) -> Result<(), Box<dyn Error>> {
+    check_session_id(&sc.session_id)?;
+    check_signature::<P>(&sc.signature)?;
+    check_commitments::<P>(&sc.commitments, t)
+}
+
+fn check_complaint_commits<P: CurvePoint>(
+    cc: &dkg::ComplaintCommits<P>,
+    t: u32,
+) -> Result<(), Box<dyn Error>> {
+    check_deal(&cc.deal, t)?;
+    check_signature::<P>(&cc.signature)
+}
+
+fn check_reconstruct_commits<P: CurvePoint>(
+    rc: &dkg::ReconstructCommits<P>,
+) -> Result<(), Box<dyn Error>> {
+    check_session_id(&rc.session_id)?;
+    check_signature::<P>(&rc.signature)
+}
+
+fn check_session_id(session_id: &[u8]) -> Result<(), Box<dyn Error>> {
+    if session_id.len() != SESSION_ID_SIZE {
+        bail!("wire: session id of {} bytes", session_id.len());
+    }
+    Ok(())
+}
+
+fn check_signature<P: CurvePoint>(signature: &[u8]) -> Result<(), Box<dyn Error>> {
+    if signature.len() != signature_size::<P>() {
+        bail!("wire: signature of {} bytes", signature.len());
+    }
+    Ok(())
+}
+
+fn check_commitments<P: CurvePoint>(commitments: &[Vec<u8>], t: u32) -> Result<(), Box<dyn Error>> {
+    if commitments.len() > t as usize {
+        bail!(
+            "wire: {} commitments for a threshold of {}",
+            commitments.len(),
+            t
+        );
+    }
+    let size = point_size::<P>();
+    if commitments.iter().any(|c| c.len() != size) {
+        bail!("wire: invalid commitment size");
+    }
+    Ok(())
+}
+
+fn point_size<P: CurvePoint>() -> usize {
+    P::generator().pk_to_key_slice().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve::{FE, GE};
+    use crate::sim;
+    use rand_chacha::rand_core::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use vss::{Dealer, Verifier};
+
+    fn encrypted_deal() -> (Verifier, Vec<u8>) {
+        let (secs, pubs) = sim::new_participants::<GE>(4);
+        let secret: FE = ECScalar::new_random();
+        let dealer: Dealer = Dealer::new(secs[0], secret, pubs.clone(), 3).unwrap();
+        let verifier: Verifier = Verifier::new(secs[1], pubs[0], pubs).unwrap();
+        let deal = dealer.encrypt_deal(1).unwrap();
+        (verifier, bincode::serialize(&deal).unwrap())
+    }
+
+    #[test]
+    fn test_wire_decode() {
+        let (mut verifier, bytes) = encrypted_deal();
+        let deal: EncryptedDeal = decode_encrypted_deal(&bytes).unwrap();
+        assert_eq!(64, signature_size::<GE>());
+        assert!(verifier.process_encrypted_deal(&deal).unwrap().approved);
+
+        decode_encrypted_deal::<GE>(&[bytes.as_slice(), &[0]].concat())
+            .expect_err("Must fail, trailing byte");
+        decode_encrypted_deal::<GE>(&bytes[..bytes.len() - 1]).expect_err("Must fail, truncated");
+        decode_with_limit::<EncryptedDeal>(&bytes, bytes.len() as u64 - 1)
+            .expect_err("Must fail, over the limit");
+
+        // a length announcing a huge vector
+        let mut huge = bytes.clone();
+        huge[1..9].copy_from_slice(&u64::MAX.to_le_bytes());
+        decode_encrypted_deal::<GE>(&huge).expect_err("Must fail, huge session id");
+
+        let mut wrong = deal.clone();
+        wrong.signature.push(0);
+        check_encrypted_deal(&wrong).expect_err("Must fail, signature size");
+        let mut wrong = deal;
+        wrong.session_id.truncate(16);
+        check_encrypted_deal(&wrong).expect_err("Must fail, session id size");
+    }
+
+    #[test]
+    fn test_wire_threshold() {
+        // the commitments are bounded by the threshold of the receiver, not by
+        // the one announced by the message
+        let (mut verifier, bytes) = encrypted_deal();
+        let deal: Deal = verifier
+            .decrypt_deal(&decode_encrypted_deal(&bytes).unwrap())
+            .unwrap();
+        check_deal(&deal, 3).unwrap();
+        check_deal(&deal, 2).expect_err("Must fail, threshold over the session one");
+        let mut wrong = deal.clone();
+        wrong.commitments.push(wrong.commitments[0].clone());
+        check_deal(&wrong, 3).expect_err("Must fail, t+1 commitments");
+
+        let point = GE::generator().pk_to_key_slice();
+        let mut sc = dkg::SecretCommits {
+            index: 0,
+            commitments: vec![point; 3],
+            session_id: vec![0; SESSION_ID_SIZE],
+            signature: vec![0; signature_size::<GE>()],
+        };
+        decode_secret_commits::<GE>(&bincode::serialize(&sc).unwrap(), 3).unwrap();
+        sc.commitments.push(sc.commitments[0].clone());
+        decode_secret_commits::<GE>(&bincode::serialize(&sc).unwrap(), 3)
+            .expect_err("Must fail, t+1 commitments");
+    }
+
+    #[test]
+    fn test_wire_fuzz() {
+        // mutated and random inputs are rejected without panics
+        let (_, bytes) = encrypted_deal();
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        for _ in 0..2000 {
+            let mut mutated = bytes.clone();
+            for _ in 0..1 + rng.next_u32() % 4 {
+                let i = rng.next_u32() as usize % mutated.len();
+                mutated[i] = rng.next_u32() as u8;
+            }
+            let _ = decode_encrypted_deal::<GE>(&mutated);
+            let _ = decode_justification::<GE>(&mutated, 3);
+            let _ = decode_message::<GE>(&mutated, 3);
+
+            let mut random = vec![0u8; rng.next_u32() as usize % 512];
+            rng.fill_bytes(&mut random);
+            let _ = decode_encrypted_deal::<GE>(&random);
+            let _ = decode_response::<GE>(&random);
+            let _ = decode_message::<GE>(&random, 3);
+        }
+    }
+}
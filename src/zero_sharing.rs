@@ -0,0 +1,349 @@
+//! Joint random sharing of zero, the building block of the refresh, the
+//! threshold change and the recovery of shares.
+//!
+//! Every participant deals a random polynomial whose constant term is zero and
+//! the participants sum the evaluations they receive: the sums are shares of
+//! zero under the sum of the public polynomials, and none of the participants
+//! knows the polynomial behind them. The sub-protocol runs in one round:
+//!  1. every participant broadcasts a ZeroDeal holding the commitments of its
+//!     polynomial, but the constant term which is zero, and its evaluation for
+//!     every participant encrypted to it.
+//!  2. every participant checks its evaluations against the commitments of the
+//!     deals and sums them, along with the public polynomials.
+//!
+//! The constant term isn't sent, so that a deal can only commit to zero, and
+//! verify_zero_poly checks a public polynomial received by other means. All
+//! the participants must deal: a missing or invalid deal aborts the sharing.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::curve_traits;
+use crate::encoding;
+use crate::poly;
+use crate::recovery;
+use crate::ristretto_curve;
+use crate::utils;
+
+use curve_traits::{CurvePoint, ECScalar};
+use encoding::Encoder;
+use poly::{PriPoly, PriShare, PubPoly};
+use rand_core::{CryptoRng, RngCore};
+use recovery::{decrypt_part, encrypt_part, EncryptedPart};
+use ristretto_curve::GE;
+use serde::{Deserialize, Serialize};
+use utils::bitwise_eq;
+
+/// ZeroDeal is broadcasted by every participant of a zero-sharing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ZeroDeal<P: CurvePoint = GE> {
+    // Index of the participant issuing the deal
+    pub index: u32,
+    pub session_id: Vec<u8>,
+    // commitments of the coefficients of the zero-sharing, but the constant
+    // term which is zero
+    pub commitments: Vec<Vec<u8>>,
+    // parts[j] is the evaluation of the zero-sharing for the j-th participant,
+    // encrypted to it
+    pub parts: Vec<EncryptedPart<P>>,
+    pub signature: Vec<u8>,
+}
+
+impl<P: CurvePoint> ZeroDeal<P> {
+    /// hash returns the hash of the deal signed by its participant.
+    pub fn hash(&self) -> [u8; 32] {
+        let enc = Encoder::new(b"zerodeal")
+            .put_bytes(&self.session_id)
+            .put_u32(self.index)
+            .put_list(&self.commitments)
+            .put_u32(self.parts.len() as u32);
+        self.parts
+            .iter()
+            .fold(enc, |enc, part| {
+                enc.put_bytes(&part.dh_key.pk_to_key_slice())
+                    .put_bytes(&part.nonce)
+                    .put_bytes(&part.cipher)
+            })
+            .hash()
+    }
+
+    /// pub_poly returns the public polynomial of the zero-sharing of the deal,
+    /// whose constant term is the identity.
+    pub fn pub_poly(&self) -> Result<PubPoly<P>, Box<dyn Error>> {
+        let mut commits: Vec<P> = vec![P::identity()];
+        for c in self.commitments.iter() {
+            commits.push(P::from_bytes(c)?);
+        }
+        Ok(PubPoly::new(P::generator(), commits))
+    }
+}
+
+/// verify_zero_poly checks that a public polynomial commits to zero.
+pub fn verify_zero_poly<P: CurvePoint>(pub_poly: &PubPoly<P>) -> Result<(), Box<dyn Error>> {
+    if pub_poly.commit() != P::identity() {
+        bail!("zerosharing: public polynomial not committing to zero");
+    }
+    Ok(())
+}
+
+/// ZeroSharing is run by every participant of a joint sharing of zero.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ZeroSharing<P: CurvePoint = GE> {
+    // longterm secret, i.e. private key of the node
+    long: P::Scalar,
+    // index of this node in the participants
+    index: u32,
+    // pub keys of the participants
+    participants: Vec<P>,
+    // security parameter t of the sharing
+    threshold: u32,
+    session_id: Vec<u8>,
+    // coefficients of the zero-sharing of this node
+    coeffs: Vec<P::Scalar>,
+    // evaluation for this node of the zero-sharing of each participant
+    received: HashMap<u32, P::Scalar>,
+    // public polynomial of the zero-sharing of each participant
+    zero_polys: HashMap<u32, PubPoly<P>>,
+}
+
+impl<P: CurvePoint> ZeroSharing<P> {
+    /// longterm: the secret (private) key of the node,
+    /// participants: the list of participants pubkeys, all of which must deal,
+    /// threshold: the security parameter t of the sharing,
+    /// session_id: the id binding the deals to the calling protocol.
+    pub fn new(
+        longterm: P::Scalar,
+        participants: Vec<P>,
+        threshold: u32,
+        session_id: &[u8],
+    ) -> Result<ZeroSharing<P>, Box<dyn Error>> {
+        ZeroSharing::new_with_rng(
+            longterm,
+            participants,
+            threshold,
+            session_id,
+            &mut utils::rand_hack(),
+        )
+    }
+
+    /// Same as new, with the zero-sharing drawn from rng.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        longterm: P::Scalar,
+        participants: Vec<P>,
+        threshold: u32,
+        session_id: &[u8],
+        rng: &mut R,
+    ) -> Result<ZeroSharing<P>, Box<dyn Error>> {
+        if threshold == 0 || (participants.len() as u32) < threshold {
+            bail!("zerosharing: invalid threshold");
+        }
+        let pub_k: P = P::generator().scalar_mul(&longterm.get_element());
+        let index = participants
+            .iter()
+            .position(|p| p == &pub_k)
+            .ok_or_else(|| simple_error!("zerosharing: own public key not found"))?;
+        let zero: PriPoly<P> = PriPoly::zero_with_rng(threshold, rng);
+        Ok(ZeroSharing {
+            long: longterm,
+            index: index as u32,
+            participants,
+            threshold,
+            session_id: session_id.to_vec(),
+            coeffs: zero.coefficients(),
+            received: Default::default(),
+            zero_polys: Default::default(),
+        })
+    }
+
+    /// deal returns the deal of this participant, to be broadcasted to the
+    /// other participants.
+    pub fn deal(&mut self) -> Result<ZeroDeal<P>, Box<dyn Error>> {
+        self.deal_with_rng(&mut utils::rand_hack())
+    }
+
+    /// Same as deal, with the encryptions drawn from rng.
+    pub fn deal_with_rng<R: RngCore + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<ZeroDeal<P>, Box<dyn Error>> {
+        let zero: PriPoly<P> = PriPoly::coefficients_to_pri_poly(self.coeffs.clone());
+        let zero_pub: PubPoly<P> = zero.commit(None);
+        let (_, commits) = zero_pub.info();
+        let parts: Vec<EncryptedPart<P>> = self
+            .participants
+            .iter()
+            .enumerate()
+            .map(|(j, pub_k)| encrypt_part(pub_k, &self.session_id, &zero.eval(j as u32).v, rng))
+            .collect::<Result<_, _>>()?;
+        let mut deal = ZeroDeal {
+            index: self.index,
+            session_id: self.session_id.clone(),
+            commitments: commits[1..].iter().map(P::pk_to_key_slice).collect(),
+            parts,
+            signature: Vec::new(),
+        };
+        let pub_key: P = self.participants[self.index as usize];
+        deal.signature = P::sign_msg(
+            &self.long,
+            &pub_key,
+            &deal.hash(),
+            &self.index.to_le_bytes(),
+        )?;
+        self.received.insert(self.index, zero.eval(self.index).v);
+        self.zero_polys.insert(self.index, zero_pub);
+        Ok(deal)
+    }
+
+    /// process_deal verifies the deal of another participant and stores its
+    /// evaluation for this participant.
+    pub fn process_deal(&mut self, deal: &ZeroDeal<P>) -> Result<(), Box<dyn Error>> {
+        if self.zero_polys.contains_key(&deal.index) {
+            bail!("zerosharing: already received deal from same index");
+        }
+        let pub_k: &P = self
+            .participants
+            .get(deal.index as usize)
+            .ok_or_else(|| simple_error!("zerosharing: deal index out of bounds"))?;
+        if !bitwise_eq(&deal.session_id, &self.session_id) {
+            bail!("zerosharing: deal with wrong session id");
+        }
+        P::verify_signature(
+            pub_k,
+            &deal.signature,
+            &deal.hash(),
+            &deal.index.to_le_bytes(),
+        )
+        .map_err(|e| simple_error!("zerosharing: invalid signature in deal: {}", e))?;
+
+        if deal.commitments.len() + 1 != self.threshold as usize
+            || deal.parts.len() != self.participants.len()
+        {
+            bail!("zerosharing: deal with wrong number of commitments or parts");
+        }
+        let zero_pub: PubPoly<P> = deal.pub_poly()?;
+
+        let i = self.index;
+        let part = deal
+            .parts
+            .get(i as usize)
+            .ok_or_else(|| simple_error!("zerosharing: no part in the deal"))?;
+        let v: P::Scalar = decrypt_part(&self.long, &self.session_id, part)?;
+        if !zero_pub.check(&PriShare { i, v }) {
+            bail!("zerosharing: part not matching the commitments of the deal");
+        }
+        self.received.insert(deal.index, v);
+        self.zero_polys.insert(deal.index, zero_pub);
+        Ok(())
+    }
+
+    /// finished returns true once the deals of all the participants are
+    /// processed, including the own deal of this participant.
+    pub fn finished(&self) -> bool {
+        self.zero_polys.len() == self.participants.len()
+    }
+
+    /// zero_shares returns the evaluations for this participant of the
+    /// zero-sharings of all the participants.
+    pub fn zero_shares(&self) -> Result<Vec<PriShare<P::Scalar>>, Box<dyn Error>> {
+        if !self.finished() {
+            bail!("zerosharing: deals of some participants are missing");
+        }
+        Ok(self
+            .received
+            .values()
+            .map(|v| PriShare {
+                i: self.index,
+                v: *v,
+            })
+            .collect())
+    }
+
+    /// zero_polys returns the public polynomials of the zero-sharings of all
+    /// the participants.
+    pub fn zero_polys(&self) -> Result<Vec<PubPoly<P>>, Box<dyn Error>> {
+        if !self.finished() {
+            bail!("zerosharing: deals of some participants are missing");
+        }
+        Ok(self.zero_polys.values().cloned().collect())
+    }
+
+    /// share returns the share of zero of this participant, the sum of its
+    /// evaluations.
+    pub fn share(&self) -> Result<PriShare<P::Scalar>, Box<dyn Error>> {
+        let zero = PriShare {
+            i: self.index,
+            v: P::Scalar::zero(),
+        };
+        poly::rerandomize_share(&zero, &self.zero_shares()?)
+    }
+
+    /// pub_poly returns the public polynomial of the joint sharing of zero, the
+    /// sum of the public polynomials of the participants.
+    pub fn pub_poly(&self) -> Result<PubPoly<P>, Box<dyn Error>> {
+        let polys = self.zero_polys()?;
+        let mut res: PubPoly<P> = polys[0].clone();
+        for p in polys[1..].iter() {
+            res = res.add(p)?;
+        }
+        verify_zero_poly(&res)?;
+        Ok(res)
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ristretto_curve::FE;
+    use crate::sim;
+
+    #[test]
+    fn test_zero_sharing() {
+        let (n, t) = (5, 3);
+        let (secs, pubs): (Vec<FE>, Vec<GE>) = sim::new_participants(n);
+        let mut parts: Vec<ZeroSharing> = secs
+            .iter()
+            .map(|sec| ZeroSharing::new(*sec, pubs.clone(), t, b"session").unwrap())
+            .collect();
+        let deals: Vec<ZeroDeal> = parts.iter_mut().map(|z| z.deal().unwrap()).collect();
+        parts[0]
+            .share()
+            .expect_err("Must fail, deals of the other participants missing");
+
+        let mut bad: ZeroDeal = deals[1].clone();
+        bad.session_id = b"other session".to_vec();
+        parts[0]
+            .clone()
+            .process_deal(&bad)
+            .expect_err("Must fail, deal of another session");
+
+        for (i, z) in parts.iter_mut().enumerate() {
+            for (j, deal) in deals.iter().enumerate() {
+                if i != j {
+                    z.process_deal(deal).unwrap();
+                }
+            }
+            assert!(z.finished());
+        }
+
+        let pub_poly: PubPoly<GE> = parts[0].pub_poly().unwrap();
+        let shares: Vec<PriShare<FE>> = parts
+            .iter()
+            .map(|z| {
+                let share = z.share().unwrap();
+                assert!(pub_poly.check(&share));
+                assert_ne!(FE::zero(), share.v);
+                share
+            })
+            .collect();
+        assert_eq!(FE::zero(), poly::recover_secret(&shares[1..4], t).unwrap());
+
+        let one: PriPoly<GE> = PriPoly::new(t, Some(FE::from(1u64)));
+        verify_zero_poly(&one.commit(None)).expect_err("Must fail, commits to one");
+    }
+}